@@ -1,6 +0,0 @@
-// Anti-Hacking Module - Cybersecurity and Exploit Prevention
-// TODO: Implement behavioral anomaly detection, encrypted telemetry, air-gapped fallback
-
-pub fn placeholder() {
-    println!("Anti-Hacking Module - Ready for implementation");
-}
@@ -0,0 +1,70 @@
+//! Abnormal command rates
+
+use crate::CyberIndicator;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandRateConfig {
+    /// Sustained commands per second from one source that count as a flood
+    pub max_per_sec: f32,
+    /// Window the rate is averaged over (seconds)
+    pub window_secs: u64,
+}
+
+impl Default for CommandRateConfig {
+    fn default() -> Self {
+        Self {
+            max_per_sec: 5.0,
+            window_secs: 5,
+        }
+    }
+}
+
+/// Counts commands per source; each flood is reported once until the
+/// source calms down
+#[derive(Debug, Clone)]
+pub struct CommandRateMonitor {
+    config: CommandRateConfig,
+    commands: HashMap<String, VecDeque<DateTime<Utc>>>,
+    flooding: HashSet<String>,
+}
+
+impl CommandRateMonitor {
+    pub fn new(config: CommandRateConfig) -> Self {
+        Self {
+            config,
+            commands: HashMap::new(),
+            flooding: HashSet::new(),
+        }
+    }
+
+    /// Count a command from `source`, e.g. `api`, `mqtt` or a client address
+    pub fn record(&mut self, source: &str, at: DateTime<Utc>) {
+        self.commands.entry(source.to_string()).or_default().push_back(at);
+    }
+
+    /// Sources that started flooding since the last check
+    pub fn check(&mut self, now: DateTime<Utc>) -> Vec<CyberIndicator> {
+        let window_secs = self.config.window_secs.max(1);
+        let since = now - Duration::seconds(window_secs as i64);
+        let mut indicators = Vec::new();
+        self.commands.retain(|source, times| {
+            while times.front().is_some_and(|at| *at <= since) {
+                times.pop_front();
+            }
+            let rate_per_sec = times.len() as f32 / window_secs as f32;
+            if rate_per_sec <= self.config.max_per_sec {
+                self.flooding.remove(source);
+            } else if self.flooding.insert(source.clone()) {
+                indicators.push(CyberIndicator::CommandFlood {
+                    source: source.clone(),
+                    rate_per_sec,
+                });
+            }
+            !times.is_empty()
+        });
+        indicators
+    }
+}
@@ -0,0 +1,315 @@
+//! GPS spoofing and jamming signatures
+
+use crate::CyberIndicator;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dark_phoenix_core::Position;
+use serde::{Deserialize, Serialize};
+
+/// Fewest satellites a receiver needs for a 3D fix
+const MIN_FIX_SATELLITES: usize = 4;
+
+/// One fix from the GPS receiver
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpsFix {
+    pub position: Position,
+    pub cn0_dbhz: Vec<f32>, // Carrier-to-noise density of each tracked satellite
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Motion and altitude as the flight controller knows them without GPS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InertialSample {
+    /// From the IMU, and optical flow where fitted: north, east, down (m/s)
+    pub velocity_ned: (f64, f64, f64),
+    pub baro_altitude_m: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpoofingConfig {
+    /// Fastest the airframe can move; faster apparent movement is a jump (m/s)
+    pub max_speed_mps: f64,
+    /// Smallest C/N0 standard deviation expected from real satellites (dB-Hz)
+    pub min_cn0_spread_dbhz: f32,
+    /// Satellites needed before the C/N0 spread means anything
+    pub min_satellites: usize,
+    /// Strongest C/N0 a real satellite arrives with (dB-Hz)
+    pub max_cn0_dbhz: f32,
+    /// Fall in mean C/N0 below its usual level that means jamming (dB-Hz)
+    pub jamming_cn0_drop_dbhz: f32,
+    /// How far a fix may stray from dead reckoning right after they last agreed (m)
+    pub max_dead_reckoning_error_m: f64,
+    /// How fast dead reckoning is allowed to drift from the truth (m/s)
+    pub dead_reckoning_drift_mps: f64,
+    /// Largest disagreement between GPS and barometric climb since they last agreed (m)
+    pub max_altitude_disagreement_m: f64,
+    /// How long dead reckoning runs from one trusted fix before the next
+    /// takes over; re-anchoring on every fix would let a slow drag through
+    pub dead_reckoning_window_secs: u64,
+}
+
+impl Default for SpoofingConfig {
+    fn default() -> Self {
+        Self {
+            max_speed_mps: 40.0,
+            min_cn0_spread_dbhz: 1.5,
+            min_satellites: 6,
+            max_cn0_dbhz: 55.0,
+            jamming_cn0_drop_dbhz: 10.0,
+            max_dead_reckoning_error_m: 15.0,
+            dead_reckoning_drift_mps: 0.5,
+            max_altitude_disagreement_m: 10.0,
+            dead_reckoning_window_secs: 30,
+        }
+    }
+}
+
+/// Where GPS and the inertial sensors last agreed, carried forward on the IMU
+#[derive(Debug, Clone)]
+struct DeadReckoning {
+    position: Position,
+    anchored_at: DateTime<Utc>,
+    gps_altitude_m: f64,
+    baro_altitude_m: Option<f64>,
+    /// Whether the IMU has reported since the anchor, so there is something to compare
+    moved: bool,
+}
+
+/// Reports every impossible jump, and each other signature once until it
+/// clears
+#[derive(Debug, Clone)]
+pub struct SpoofingDetector {
+    config: SpoofingConfig,
+    last: Option<GpsFix>,
+    uniform_reported: bool,
+    strong_reported: bool,
+    jamming_reported: bool,
+    inconsistent_reported: bool,
+    /// Usual mean C/N0, from fixes that looked genuine
+    cn0_baseline: Option<f32>,
+    dead_reckoning: Option<DeadReckoning>,
+    last_inertial: Option<InertialSample>,
+}
+
+impl SpoofingDetector {
+    pub fn new(config: SpoofingConfig) -> Self {
+        Self {
+            config,
+            last: None,
+            uniform_reported: false,
+            strong_reported: false,
+            jamming_reported: false,
+            inconsistent_reported: false,
+            cn0_baseline: None,
+            dead_reckoning: None,
+            last_inertial: None,
+        }
+    }
+
+    /// Carry the dead-reckoned position forward
+    pub fn observe_inertial(&mut self, sample: &InertialSample) {
+        if let (Some(reckoning), Some(last)) = (&mut self.dead_reckoning, &self.last_inertial) {
+            let elapsed = (sample.timestamp - last.timestamp).num_milliseconds() as f64 / 1000.0;
+            if elapsed > 0.0 {
+                let (north, east, _) = sample.velocity_ned;
+                let bearing = east.atan2(north).to_degrees();
+                reckoning.position = reckoning.position.destination(bearing, north.hypot(east) * elapsed);
+                reckoning.moved = true;
+            }
+        }
+        self.last_inertial = Some(sample.clone());
+    }
+
+    pub fn observe(&mut self, fix: &GpsFix) -> Vec<CyberIndicator> {
+        let mut indicators = Vec::new();
+        let jump = self.last.replace(fix.clone()).and_then(|last| {
+            let elapsed = (fix.timestamp - last.timestamp).num_milliseconds() as f64 / 1000.0;
+            let distance = last.position.distance_m(&fix.position);
+            // Fixes under 100 ms apart are timed as 100 ms so receiver jitter does not read as speed
+            let speed = distance / elapsed.max(0.1);
+            (elapsed > 0.0 && speed > self.config.max_speed_mps).then(|| {
+                format!("position jumped {:.0} m in {:.1} s ({:.0} m/s)", distance, elapsed, speed)
+            })
+        });
+        let jumped = jump.is_some();
+        indicators.extend(jump.map(|reason| CyberIndicator::GpsSpoofing { reason }));
+
+        let signal = self.check_signal(fix);
+        let signal_ok = signal.is_empty() && !self.uniform_reported && !self.strong_reported && !self.jamming_reported;
+        indicators.extend(signal);
+
+        let inconsistency = self.check_inertial(fix);
+        let consistent = inconsistency.is_none();
+        match inconsistency {
+            Some(reason) if !self.inconsistent_reported => {
+                self.inconsistent_reported = true;
+                indicators.push(CyberIndicator::GpsSpoofing { reason });
+            },
+            Some(_) => {},
+            None => self.inconsistent_reported = false,
+        }
+
+        // Only a fix that looks genuine becomes the new reference, once the window is up
+        let window = chrono::Duration::seconds(self.config.dead_reckoning_window_secs as i64);
+        let due = self.dead_reckoning.as_ref().is_none_or(|reckoning| fix.timestamp - reckoning.anchored_at >= window);
+        if !jumped && consistent && due {
+            self.dead_reckoning = Some(DeadReckoning {
+                position: fix.position.clone(),
+                anchored_at: fix.timestamp,
+                gps_altitude_m: fix.position.altitude,
+                baro_altitude_m: self.last_inertial.as_ref().map(|sample| sample.baro_altitude_m),
+                moved: false,
+            });
+        }
+        if let Some(mean) = mean(&fix.cn0_dbhz).filter(|_| !jumped && consistent && signal_ok) {
+            self.cn0_baseline = Some(self.cn0_baseline.map_or(mean, |baseline| baseline * 0.9 + mean * 0.1));
+        }
+        indicators
+    }
+
+    /// Uniform, overly strong or collapsing signal strength
+    fn check_signal(&mut self, fix: &GpsFix) -> Vec<CyberIndicator> {
+        let mut indicators = Vec::new();
+        let satellites = fix.cn0_dbhz.len();
+        let spread = cn0_spread(&fix.cn0_dbhz).filter(|_| satellites >= self.config.min_satellites);
+        match spread {
+            Some(spread) if spread < self.config.min_cn0_spread_dbhz => {
+                if !self.uniform_reported {
+                    self.uniform_reported = true;
+                    indicators.push(CyberIndicator::GpsSpoofing {
+                        reason: format!("{} satellites within {:.1} dB-Hz of each other", satellites, spread),
+                    });
+                }
+            },
+            _ => self.uniform_reported = false,
+        }
+
+        let strongest = fix.cn0_dbhz.iter().copied().fold(f32::MIN, f32::max);
+        if strongest > self.config.max_cn0_dbhz {
+            if !self.strong_reported {
+                self.strong_reported = true;
+                indicators.push(CyberIndicator::GpsSpoofing {
+                    reason: format!("a satellite at {:.1} dB-Hz, stronger than any from orbit", strongest),
+                });
+            }
+        } else {
+            self.strong_reported = false;
+        }
+
+        let jamming = match (self.cn0_baseline, mean(&fix.cn0_dbhz)) {
+            _ if satellites < MIN_FIX_SATELLITES && self.cn0_baseline.is_some() => {
+                Some(format!("only {} satellites still tracked", satellites))
+            },
+            (Some(baseline), Some(mean)) if baseline - mean >= self.config.jamming_cn0_drop_dbhz => Some(format!(
+                "mean C/N0 fell to {:.1} dB-Hz from a usual {:.1}",
+                mean, baseline
+            )),
+            _ => None,
+        };
+        match jamming {
+            Some(reason) if !self.jamming_reported => {
+                self.jamming_reported = true;
+                indicators.push(CyberIndicator::GpsJamming { reason });
+            },
+            Some(_) => {},
+            None => self.jamming_reported = false,
+        }
+        indicators
+    }
+
+    /// Why the fix disagrees with dead reckoning or the barometer, if it does
+    fn check_inertial(&self, fix: &GpsFix) -> Option<String> {
+        let reckoning = self.dead_reckoning.as_ref().filter(|reckoning| reckoning.moved)?;
+        let elapsed = (fix.timestamp - reckoning.anchored_at).num_milliseconds().max(0) as f64 / 1000.0;
+        let error = reckoning.position.distance_m(&fix.position);
+        let allowed = self.config.max_dead_reckoning_error_m + self.config.dead_reckoning_drift_mps * elapsed;
+        if error > allowed {
+            return Some(format!("fix {:.0} m from dead reckoning (allowed {:.0} m)", error, allowed));
+        }
+        let baro_climb = reckoning.baro_altitude_m.zip(self.last_inertial.as_ref()).map(|(then, now)| now.baro_altitude_m - then)?;
+        let gps_climb = fix.position.altitude - reckoning.gps_altitude_m;
+        ((gps_climb - baro_climb).abs() > self.config.max_altitude_disagreement_m).then(|| {
+            format!("GPS altitude changed {:+.0} m but the barometer {:+.0} m", gps_climb, baro_climb)
+        })
+    }
+}
+
+fn mean(cn0_dbhz: &[f32]) -> Option<f32> {
+    (!cn0_dbhz.is_empty()).then(|| cn0_dbhz.iter().sum::<f32>() / cn0_dbhz.len() as f32)
+}
+
+/// Standard deviation of the satellites' C/N0
+fn cn0_spread(cn0_dbhz: &[f32]) -> Option<f32> {
+    let mean = mean(cn0_dbhz)?;
+    let variance = cn0_dbhz.iter().map(|cn0| (cn0 - mean).powi(2)).sum::<f32>() / cn0_dbhz.len() as f32;
+    Some(variance.sqrt())
+}
+
+/// How the drone knows where it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NavigationMode {
+    /// GPS fused with the inertial sensors, as normal
+    Gnss,
+    /// GPS ignored: position held from the IMU, barometer and optical flow,
+    /// speed capped and no waypoints far from the last trusted fix
+    DeadReckoning,
+}
+
+impl std::fmt::Display for NavigationMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            NavigationMode::Gnss => "GPS navigation",
+            NavigationMode::DeadReckoning => "dead-reckoning navigation",
+        })
+    }
+}
+
+/// Position and per-satellite signal strength, e.g. from UBX-NAV-SAT
+#[async_trait]
+pub trait GpsReceiver: Send + Sync {
+    async fn read_fix(&self) -> Result<GpsFix, Box<dyn std::error::Error>>;
+}
+
+/// Velocity and barometric altitude, e.g. from the flight controller's
+/// non-GPS estimator
+#[async_trait]
+pub trait InertialSource: Send + Sync {
+    async fn read_inertial(&self) -> Result<InertialSample, Box<dyn std::error::Error>>;
+}
+
+/// Receiver placeholder holding a fixed position under an ordinary sky
+pub(crate) struct SimulatedGpsReceiver;
+
+#[async_trait]
+impl GpsReceiver for SimulatedGpsReceiver {
+    async fn read_fix(&self) -> Result<GpsFix, Box<dyn std::error::Error>> {
+        let now = Utc::now();
+        Ok(GpsFix {
+            position: Position {
+                latitude: 0.0,
+                longitude: 0.0,
+                altitude: 0.0,
+                timestamp: now,
+            },
+            cn0_dbhz: vec![44.0, 41.5, 38.0, 35.5, 33.0, 30.5, 28.0],
+            timestamp: now,
+        })
+    }
+}
+
+/// Inertial placeholder for a drone hovering in place
+pub(crate) struct SimulatedInertialSource;
+
+#[async_trait]
+impl InertialSource for SimulatedInertialSource {
+    async fn read_inertial(&self) -> Result<InertialSample, Box<dyn std::error::Error>> {
+        Ok(InertialSample {
+            velocity_ned: (0.0, 0.0, 0.0),
+            baro_altitude_m: 0.0,
+            timestamp: Utc::now(),
+        })
+    }
+}
@@ -0,0 +1,178 @@
+//! Login attempts on the companion computer
+
+use crate::CyberIndicator;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// A connection or login seen by the companion computer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionAttempt {
+    pub service: String, // "ssh", "telnet", ...
+    pub source: IpAddr,
+    pub user: Option<String>,
+    pub succeeded: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntrusionConfig {
+    /// Ground station and maintenance hosts allowed to log in
+    pub allowed_sources: Vec<IpAddr>,
+    /// Failed logins from one source before it is reported
+    pub max_failures: u32,
+    /// Window failures are counted over, and how long a source stays reported (seconds)
+    pub window_secs: u64,
+}
+
+impl Default for IntrusionConfig {
+    fn default() -> Self {
+        Self {
+            allowed_sources: Vec::new(),
+            max_failures: 3,
+            window_secs: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IntrusionDetector {
+    config: IntrusionConfig,
+    failures: HashMap<IpAddr, Vec<DateTime<Utc>>>,
+    reported: HashMap<IpAddr, DateTime<Utc>>,
+}
+
+impl IntrusionDetector {
+    pub fn new(config: IntrusionConfig) -> Self {
+        Self {
+            config,
+            failures: HashMap::new(),
+            reported: HashMap::new(),
+        }
+    }
+
+    pub fn observe(&mut self, attempt: &ConnectionAttempt) -> Option<CyberIndicator> {
+        let telnet = attempt.service == "telnet";
+        if self.config.allowed_sources.contains(&attempt.source) && !telnet {
+            return None;
+        }
+        let window = Duration::seconds(self.config.window_secs as i64);
+        let since = attempt.timestamp - window;
+        self.reported.retain(|_, at| *at > since);
+
+        let failures = self.failures.entry(attempt.source).or_default();
+        failures.retain(|at| *at > since);
+        if !attempt.succeeded {
+            failures.push(attempt.timestamp);
+        }
+        let count = failures.len() as u32;
+
+        // A successful login always counts; probing only once per window
+        let probing = telnet || count >= self.config.max_failures;
+        if !attempt.succeeded && (!probing || self.reported.contains_key(&attempt.source)) {
+            return None;
+        }
+        self.reported.insert(attempt.source, attempt.timestamp);
+        Some(CyberIndicator::IntrusionAttempt {
+            service: attempt.service.clone(),
+            source: attempt.source,
+            failures: count,
+            logged_in: attempt.succeeded,
+        })
+    }
+}
+
+/// Connections and logins since the previous poll
+#[async_trait]
+pub trait ConnectionMonitor: Send + Sync {
+    async fn poll_attempts(&self) -> Result<Vec<ConnectionAttempt>, Box<dyn std::error::Error>>;
+}
+
+/// Follows sshd and telnetd lines appended to a syslog auth log
+pub struct AuthLogMonitor {
+    path: PathBuf,
+    offset: Mutex<Option<u64>>,
+}
+
+impl AuthLogMonitor {
+    /// Starts from the end of the log; earlier entries are not reported
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            offset: Mutex::new(None),
+        }
+    }
+}
+
+#[async_trait]
+impl ConnectionMonitor for AuthLogMonitor {
+    async fn poll_attempts(&self) -> Result<Vec<ConnectionAttempt>, Box<dyn std::error::Error>> {
+        let mut file = std::fs::File::open(&self.path)?;
+        let len = file.metadata()?.len();
+        let mut offset = self.offset.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let start = match *offset {
+            // Rotated or truncated: read the new file from the top
+            Some(previous) if previous <= len => previous,
+            Some(_) => 0,
+            None => len,
+        };
+        file.seek(SeekFrom::Start(start))?;
+        let mut appended = String::new();
+        let read = file.read_to_string(&mut appended)?;
+        *offset = Some(start + read as u64);
+
+        let now = Utc::now();
+        Ok(appended.lines().filter_map(|line| parse_auth_line(line, now)).collect())
+    }
+}
+
+fn parse_auth_line(line: &str, timestamp: DateTime<Utc>) -> Option<ConnectionAttempt> {
+    let (service, succeeded, user) = if line.contains("telnetd") && line.contains("connect from") {
+        ("telnet", false, None)
+    } else if line.contains("sshd") {
+        let user = |marker: &str| {
+            line.split(marker)
+                .nth(1)
+                .and_then(|rest| rest.split_whitespace().next())
+                .map(str::to_string)
+        };
+        if line.contains("Accepted ") {
+            ("ssh", true, user(" for "))
+        } else if line.contains("Failed password") || line.contains("Invalid user") {
+            ("ssh", false, user(" user ").or_else(|| user(" for ")))
+        } else {
+            return None;
+        }
+    } else {
+        return None;
+    };
+    let source = line
+        .split(" from ")
+        .nth(1)?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    Some(ConnectionAttempt {
+        service: service.to_string(),
+        source,
+        user,
+        succeeded,
+        timestamp,
+    })
+}
+
+/// Placeholder for a drone without a companion computer: nothing to log into
+pub(crate) struct SimulatedConnectionMonitor;
+
+#[async_trait]
+impl ConnectionMonitor for SimulatedConnectionMonitor {
+    async fn poll_attempts(&self) -> Result<Vec<ConnectionAttempt>, Box<dyn std::error::Error>> {
+        Ok(Vec::new())
+    }
+}
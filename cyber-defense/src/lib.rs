@@ -0,0 +1,461 @@
+//! Cyber-defense: attacks on the drone itself
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use dark_phoenix_core::{DecisionTrace, DroneState, EventType, ModuleResult, ThreatLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::sync::Arc;
+use threat_detection::{ThreatAssessment, ThreatEvidence, ThreatType};
+use tokio::sync::{broadcast, Mutex, RwLock};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+pub mod commands;
+pub mod gps;
+pub mod intrusion;
+pub mod link;
+
+pub use commands::{CommandRateConfig, CommandRateMonitor};
+pub use gps::{GpsFix, GpsReceiver, InertialSample, InertialSource, NavigationMode, SpoofingConfig, SpoofingDetector};
+pub use intrusion::{AuthLogMonitor, ConnectionAttempt, ConnectionMonitor, IntrusionConfig, IntrusionDetector};
+pub use link::{JammingConfig, JammingDetector, LinkMonitor, LinkSample};
+
+/// Cyber-defense configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CyberDefenseConfig {
+    pub link: JammingConfig,
+    pub gps: SpoofingConfig,
+    pub intrusion: IntrusionConfig,
+    pub commands: CommandRateConfig,
+    pub response: ResponseConfig,
+}
+
+/// How the module answers what it finds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseConfig {
+    /// Block sources, rotate keys and go autonomous without waiting for an operator
+    pub auto_harden: bool,
+    /// Minimum time before the same response is repeated (seconds)
+    pub cooldown_secs: u64,
+    /// How often the link, GPS, inertial and login sources are read (milliseconds)
+    pub poll_interval_ms: u64,
+}
+
+impl Default for ResponseConfig {
+    fn default() -> Self {
+        Self {
+            auto_harden: true,
+            cooldown_secs: 300,
+            poll_interval_ms: 500,
+        }
+    }
+}
+
+/// Something that looks like an attack
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CyberIndicator {
+    LinkJamming { noise_floor_dbm: f32, packet_loss: f32 },
+    GpsSpoofing { reason: String },
+    GpsJamming { reason: String },
+    IntrusionAttempt {
+        service: String,
+        source: IpAddr,
+        failures: u32,
+        logged_in: bool,
+    },
+    CommandFlood { source: String, rate_per_sec: f32 },
+}
+
+impl CyberIndicator {
+    pub fn threat_level(&self) -> ThreatLevel {
+        match self {
+            CyberIndicator::IntrusionAttempt { logged_in: true, .. } => ThreatLevel::Red,
+            CyberIndicator::IntrusionAttempt { .. } => ThreatLevel::Yellow,
+            CyberIndicator::LinkJamming { .. }
+            | CyberIndicator::GpsSpoofing { .. }
+            | CyberIndicator::GpsJamming { .. }
+            | CyberIndicator::CommandFlood { .. } => ThreatLevel::Orange,
+        }
+    }
+
+    pub fn confidence(&self) -> f32 {
+        match self {
+            CyberIndicator::IntrusionAttempt { .. } => 0.95, // Straight from the auth log
+            CyberIndicator::CommandFlood { .. } => 0.85,
+            CyberIndicator::LinkJamming { .. } => 0.8,
+            CyberIndicator::GpsJamming { .. } => 0.75,
+            CyberIndicator::GpsSpoofing { .. } => 0.7, // Multipath can mimic both signatures
+        }
+    }
+
+    /// Hardening that answers this indicator
+    pub fn responses(&self) -> Vec<HardeningAction> {
+        match self {
+            // Remote commands can no longer be trusted to arrive
+            CyberIndicator::LinkJamming { .. } => vec![HardeningAction::AutonomousMode],
+            // Navigation commands relative to a false position would lead the drone away,
+            // and so would the false position itself
+            CyberIndicator::GpsSpoofing { .. } => vec![HardeningAction::DeadReckoning, HardeningAction::AutonomousMode],
+            CyberIndicator::GpsJamming { .. } => vec![HardeningAction::DeadReckoning],
+            CyberIndicator::IntrusionAttempt { source, logged_in: true, .. } => vec![
+                HardeningAction::BlockSource(*source),
+                HardeningAction::RotateKeys,
+                HardeningAction::AutonomousMode,
+            ],
+            CyberIndicator::IntrusionAttempt { source, .. } => vec![HardeningAction::BlockSource(*source)],
+            // Whoever is sending may hold a valid key
+            CyberIndicator::CommandFlood { .. } => vec![HardeningAction::RotateKeys, HardeningAction::AutonomousMode],
+        }
+    }
+}
+
+impl std::fmt::Display for CyberIndicator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CyberIndicator::LinkJamming { noise_floor_dbm, packet_loss } => write!(
+                f,
+                "Command link jammed: noise floor {:.0} dBm, {:.0}% packet loss",
+                noise_floor_dbm,
+                packet_loss * 100.0
+            ),
+            CyberIndicator::GpsSpoofing { reason } => write!(f, "GPS spoofing suspected: {}", reason),
+            CyberIndicator::GpsJamming { reason } => write!(f, "GPS jamming suspected: {}", reason),
+            CyberIndicator::IntrusionAttempt { service, source, logged_in: true, .. } => {
+                write!(f, "Unauthorized {} login from {}", service, source)
+            },
+            CyberIndicator::IntrusionAttempt { service, source, failures, .. } => {
+                write!(f, "{} intrusion attempt from {} ({} failed logins)", service, source, failures)
+            },
+            CyberIndicator::CommandFlood { source, rate_per_sec } => {
+                write!(f, "Command flood from {}: {:.1} commands/s", source, rate_per_sec)
+            },
+        }
+    }
+}
+
+/// Automatic responses to an attack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HardeningAction {
+    /// Firewall the source off the companion computer
+    BlockSource(IpAddr),
+    /// Replace the command-link keys so captured ones stop working
+    RotateKeys,
+    /// Stop taking remote commands and fly the current mission on board
+    AutonomousMode,
+    /// Navigate without GPS until an operator clears it
+    DeadReckoning,
+}
+
+impl std::fmt::Display for HardeningAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HardeningAction::BlockSource(source) => write!(f, "Block {}", source),
+            HardeningAction::RotateKeys => write!(f, "Rotate command-link keys"),
+            HardeningAction::AutonomousMode => write!(f, "Switch to autonomous mode"),
+            HardeningAction::DeadReckoning => write!(f, "Switch to {}", NavigationMode::DeadReckoning),
+        }
+    }
+}
+
+/// Carries out hardening on the drone's systems
+#[async_trait]
+pub trait Hardener: Send + Sync {
+    async fn block_source(&self, source: IpAddr) -> Result<(), Box<dyn std::error::Error>>;
+
+    async fn rotate_keys(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Ignore, or again accept, remote commands
+    async fn set_autonomous(&self, autonomous: bool) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Tell the flight controller whether to trust GPS
+    async fn set_navigation_mode(&self, mode: NavigationMode) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// Hardening placeholder used until the firewall, key store and flight
+/// controller are attached
+struct SimulatedHardener;
+
+#[async_trait]
+impl Hardener for SimulatedHardener {
+    async fn block_source(&self, source: IpAddr) -> Result<(), Box<dyn std::error::Error>> {
+        // Placeholder - would add a firewall drop rule
+        info!("🔐 Blocking {}", source);
+        Ok(())
+    }
+
+    async fn rotate_keys(&self) -> Result<(), Box<dyn std::error::Error>> {
+        info!("🔐 Rotating command-link keys");
+        Ok(())
+    }
+
+    async fn set_autonomous(&self, autonomous: bool) -> Result<(), Box<dyn std::error::Error>> {
+        info!("🔐 Remote commands {}", if autonomous { "ignored" } else { "accepted" });
+        Ok(())
+    }
+
+    async fn set_navigation_mode(&self, mode: NavigationMode) -> Result<(), Box<dyn std::error::Error>> {
+        // Placeholder - would set the EKF source set on the flight controller
+        info!("🔐 Navigating by {}", mode);
+        Ok(())
+    }
+}
+
+/// Cyber-defense module
+pub struct CyberDefense {
+    config: CyberDefenseConfig,
+    link_monitor: Box<dyn LinkMonitor>,
+    gps_receiver: Box<dyn GpsReceiver>,
+    inertial_source: Box<dyn InertialSource>,
+    connection_monitor: Box<dyn ConnectionMonitor>,
+    hardener: Box<dyn Hardener>,
+    jamming: JammingDetector,
+    spoofing: SpoofingDetector,
+    intrusion: IntrusionDetector,
+    commands: CommandRateMonitor,
+    /// When each response last ran, for the cooldown
+    last_response: HashMap<HardeningAction, DateTime<Utc>>,
+    autonomous: bool,
+    navigation: NavigationMode,
+    assessments: broadcast::Sender<ThreatAssessment>,
+}
+
+impl CyberDefense {
+    pub fn new(config: CyberDefenseConfig) -> Self {
+        let (assessments, _) = broadcast::channel(64);
+        Self {
+            jamming: JammingDetector::new(config.link.clone()),
+            spoofing: SpoofingDetector::new(config.gps.clone()),
+            intrusion: IntrusionDetector::new(config.intrusion.clone()),
+            commands: CommandRateMonitor::new(config.commands.clone()),
+            config,
+            link_monitor: Box::new(link::SimulatedLinkMonitor),
+            gps_receiver: Box::new(gps::SimulatedGpsReceiver),
+            inertial_source: Box::new(gps::SimulatedInertialSource),
+            connection_monitor: Box::new(intrusion::SimulatedConnectionMonitor),
+            hardener: Box::new(SimulatedHardener),
+            last_response: HashMap::new(),
+            autonomous: false,
+            navigation: NavigationMode::Gnss,
+            assessments,
+        }
+    }
+
+    pub fn with_link_monitor(mut self, monitor: Box<dyn LinkMonitor>) -> Self {
+        self.link_monitor = monitor;
+        self
+    }
+
+    pub fn with_gps_receiver(mut self, receiver: Box<dyn GpsReceiver>) -> Self {
+        self.gps_receiver = receiver;
+        self
+    }
+
+    /// The IMU velocity and barometer GPS fixes are checked against
+    pub fn with_inertial_source(mut self, source: Box<dyn InertialSource>) -> Self {
+        self.inertial_source = source;
+        self
+    }
+
+    /// e.g. `AuthLogMonitor::new("/var/log/auth.log")` on the companion computer
+    pub fn with_connection_monitor(mut self, monitor: Box<dyn ConnectionMonitor>) -> Self {
+        self.connection_monitor = monitor;
+        self
+    }
+
+    pub fn with_hardener(mut self, hardener: Box<dyn Hardener>) -> Self {
+        self.hardener = hardener;
+        self
+    }
+
+    /// Every `CyberThreat` assessment from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ThreatAssessment> {
+        self.assessments.subscribe()
+    }
+
+    /// Whether remote commands are currently being refused
+    pub fn is_autonomous(&self) -> bool {
+        self.autonomous
+    }
+
+    /// Whether the drone is navigating by GPS or without it
+    pub fn navigation_mode(&self) -> NavigationMode {
+        self.navigation
+    }
+
+    /// Count a command received from `source`; call from every command entry point
+    pub fn record_command(&mut self, source: &str) {
+        self.commands.record(source, Utc::now());
+    }
+
+    /// Read every source once and return what looks like an attack
+    pub async fn scan(&mut self) -> Vec<CyberIndicator> {
+        let mut indicators = Vec::new();
+        match self.link_monitor.read_link().await {
+            Ok(sample) => indicators.extend(self.jamming.observe(&sample)),
+            Err(e) => warn!("Link monitor read failed: {}", e),
+        }
+        // Dead reckoning first, so the fix is checked against where the drone is now
+        match self.inertial_source.read_inertial().await {
+            Ok(sample) => self.spoofing.observe_inertial(&sample),
+            Err(e) => warn!("Inertial read failed: {}", e),
+        }
+        match self.gps_receiver.read_fix().await {
+            Ok(fix) => indicators.extend(self.spoofing.observe(&fix)),
+            Err(e) => warn!("GPS read failed: {}", e),
+        }
+        match self.connection_monitor.poll_attempts().await {
+            Ok(attempts) => indicators.extend(attempts.iter().filter_map(|attempt| self.intrusion.observe(attempt))),
+            Err(e) => warn!("Connection monitor read failed: {}", e),
+        }
+        indicators.extend(self.commands.check(Utc::now()));
+        indicators
+    }
+
+    /// One assessment covering everything seen in a scan; several kinds of
+    /// attack at once suggest a coordinated one and rate a level higher
+    pub fn assess(&self, indicators: &[CyberIndicator]) -> Option<ThreatAssessment> {
+        let strongest = indicators.iter().max_by_key(|indicator| indicator.threat_level())?;
+        let mut threat_level = strongest.threat_level();
+        let mut trace = DecisionTrace::new();
+        for indicator in indicators {
+            trace.input(&indicator.to_string(), indicator.confidence(), None);
+        }
+        trace.rule("strongest indicator", threat_level, strongest.to_string());
+        let kinds: HashSet<_> = indicators.iter().map(std::mem::discriminant).collect();
+        if kinds.len() > 1 {
+            threat_level = match threat_level {
+                ThreatLevel::Green | ThreatLevel::Yellow => ThreatLevel::Orange,
+                _ => ThreatLevel::Red,
+            };
+            trace.rule("coordinated attack", threat_level, format!("{} kinds of attack at once", kinds.len()));
+        }
+        let confidence = indicators.iter().map(CyberIndicator::confidence).fold(0.0, f32::max);
+        let mut recommended_actions = Vec::new();
+        for action in indicators.iter().flat_map(CyberIndicator::responses) {
+            let action = action.to_string();
+            if !recommended_actions.contains(&action) {
+                recommended_actions.push(action);
+            }
+        }
+        Some(ThreatAssessment {
+            id: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            threat_level,
+            confidence,
+            threat_types: vec![ThreatType::CyberThreat],
+            position: None,
+            description: indicators.iter().map(|indicator| indicator.to_string()).collect::<Vec<_>>().join("; "),
+            recommended_actions,
+            evidence: ThreatEvidence::default(),
+            zone: None,
+            trace,
+        })
+    }
+
+    /// Carry out the responses to `indicators` that are not cooling down;
+    /// returns the ones that succeeded
+    pub async fn harden(&mut self, indicators: &[CyberIndicator]) -> Vec<HardeningAction> {
+        let now = Utc::now();
+        let cooldown = Duration::seconds(self.config.response.cooldown_secs as i64);
+        let mut taken = Vec::new();
+        for action in indicators.iter().flat_map(CyberIndicator::responses) {
+            let cooling = self.last_response.get(&action).is_some_and(|at| now - *at < cooldown);
+            let already = match action {
+                HardeningAction::AutonomousMode => self.autonomous,
+                HardeningAction::DeadReckoning => self.navigation == NavigationMode::DeadReckoning,
+                _ => false,
+            };
+            if taken.contains(&action) || cooling || already {
+                continue;
+            }
+            let result = match action {
+                HardeningAction::BlockSource(source) => self.hardener.block_source(source).await,
+                HardeningAction::RotateKeys => self.hardener.rotate_keys().await,
+                HardeningAction::AutonomousMode => self.hardener.set_autonomous(true).await,
+                HardeningAction::DeadReckoning => self.hardener.set_navigation_mode(NavigationMode::DeadReckoning).await,
+            };
+            match result {
+                Ok(()) => {
+                    warn!("🔐 {}", action);
+                    self.last_response.insert(action, now);
+                    match action {
+                        HardeningAction::AutonomousMode => self.autonomous = true,
+                        HardeningAction::DeadReckoning => self.navigation = NavigationMode::DeadReckoning,
+                        _ => {},
+                    }
+                    taken.push(action);
+                },
+                Err(e) => error!("Hardening failed ({}): {}", action, e),
+            }
+        }
+        taken
+    }
+
+    /// Accept remote commands again once an operator has cleared the attack
+    pub async fn restore_remote_control(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.autonomous {
+            return Ok(());
+        }
+        self.hardener.set_autonomous(false).await?;
+        self.autonomous = false;
+        self.last_response.remove(&HardeningAction::AutonomousMode);
+        info!("🔐 Remote control restored");
+        Ok(())
+    }
+
+    /// Trust GPS again once an operator has cleared the spoofing or jamming
+    pub async fn restore_gnss_navigation(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.navigation == NavigationMode::Gnss {
+            return Ok(());
+        }
+        self.hardener.set_navigation_mode(NavigationMode::Gnss).await?;
+        self.navigation = NavigationMode::Gnss;
+        self.last_response.remove(&HardeningAction::DeadReckoning);
+        info!("🔐 GPS navigation restored");
+        Ok(())
+    }
+
+    /// Scan on the poll interval, publishing assessments, hardening and
+    /// escalating the drone; returns only if the drone's event stream ends
+    pub async fn follow(defense: Arc<Mutex<CyberDefense>>, drone: Arc<RwLock<DroneState>>) -> ModuleResult {
+        let mut transitions = drone.read().await.subscribe_threat_transitions();
+        let poll_interval = std::time::Duration::from_millis(defense.lock().await.config.response.poll_interval_ms.max(10));
+        let mut poll = dark_phoenix_core::runtime::interval(poll_interval);
+        info!("🔐 Cyber-defense watching link, GPS, inertial sensors and logins");
+
+        loop {
+            tokio::select! {
+                _ = poll.tick() => {},
+                received = transitions.recv() => match received {
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                    _ => continue,
+                },
+            }
+            let (assessment, taken) = {
+                let mut defense = defense.lock().await;
+                let indicators = defense.scan().await;
+                let Some(assessment) = defense.assess(&indicators) else { continue };
+                let taken = if defense.config.response.auto_harden {
+                    defense.harden(&indicators).await
+                } else {
+                    Vec::new()
+                };
+                // No subscribers is fine
+                let _ = defense.assessments.send(assessment.clone());
+                (assessment, taken)
+            };
+
+            warn!("🔐 {}", assessment.description);
+            let mut drone = drone.write().await;
+            drone.log_event(
+                EventType::HackingAttempt,
+                assessment.description.clone(),
+                taken.iter().map(|action| action.to_string()).collect(),
+            );
+            drone.escalate_threat(assessment.threat_level, assessment.description);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Command-link jamming detection
+
+use crate::CyberIndicator;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One reading of the command-link radio
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkSample {
+    pub rssi_dbm: f32,
+    pub noise_floor_dbm: f32,
+    pub packet_loss: f32, // 0.0 - 1.0 over the radio's reporting window
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JammingConfig {
+    /// Noise floor above which the band is considered swamped (dBm)
+    pub noise_floor_dbm: f32,
+    /// Packet loss that must accompany the raised noise floor (0.0-1.0)
+    pub min_packet_loss: f32,
+    /// How long both must hold before jamming is reported (seconds)
+    pub sustain_secs: u64,
+}
+
+impl Default for JammingConfig {
+    fn default() -> Self {
+        Self {
+            noise_floor_dbm: -80.0, // A quiet 2.4 GHz band sits around -95 dBm
+            min_packet_loss: 0.3,
+            sustain_secs: 3,
+        }
+    }
+}
+
+/// Reports jamming once when it starts, and again only after the link has
+/// been clear in between
+#[derive(Debug, Clone)]
+pub struct JammingDetector {
+    config: JammingConfig,
+    suspected_since: Option<DateTime<Utc>>,
+    reported: bool,
+}
+
+impl JammingDetector {
+    pub fn new(config: JammingConfig) -> Self {
+        Self {
+            config,
+            suspected_since: None,
+            reported: false,
+        }
+    }
+
+    pub fn is_jammed(&self) -> bool {
+        self.reported
+    }
+
+    pub fn observe(&mut self, sample: &LinkSample) -> Option<CyberIndicator> {
+        let swamped = sample.noise_floor_dbm > self.config.noise_floor_dbm && sample.packet_loss >= self.config.min_packet_loss;
+        if !swamped {
+            self.suspected_since = None;
+            self.reported = false;
+            return None;
+        }
+        let since = *self.suspected_since.get_or_insert(sample.timestamp);
+        if self.reported || (sample.timestamp - since).num_seconds() < self.config.sustain_secs as i64 {
+            return None;
+        }
+        self.reported = true;
+        Some(CyberIndicator::LinkJamming {
+            noise_floor_dbm: sample.noise_floor_dbm,
+            packet_loss: sample.packet_loss,
+        })
+    }
+}
+
+/// Command-link radio statistics, e.g. from the telemetry modem
+#[async_trait]
+pub trait LinkMonitor: Send + Sync {
+    async fn read_link(&self) -> Result<LinkSample, Box<dyn std::error::Error>>;
+}
+
+/// Link placeholder reporting a clean, quiet channel
+pub(crate) struct SimulatedLinkMonitor;
+
+#[async_trait]
+impl LinkMonitor for SimulatedLinkMonitor {
+    async fn read_link(&self) -> Result<LinkSample, Box<dyn std::error::Error>> {
+        // Placeholder - would query the radio's RSSI and noise registers
+        Ok(LinkSample {
+            rssi_dbm: -60.0,
+            noise_floor_dbm: -95.0,
+            packet_loss: 0.0,
+            timestamp: Utc::now(),
+        })
+    }
+}
@@ -0,0 +1,468 @@
+//! HTTP + WebSocket control API (`api-server` feature)
+
+use crate::envelope::{http_command, SIGNATURE_HEADER};
+use crate::{
+    Action, AuthConfig, AuthContext, AuthError, CommandEnvelope, CommandSource, CommandVerifier, DroneState, Incident, Metrics, MissionEvent, ModuleControl, PairedController, Pairing, PairingCode,
+    PairingError, PairingRequest, PairingResponse, PanicButton, PanicCommand, PanicError, PanicOutcome, ReportError, ReportFormat, ReportScope, ReportSources, SystemHealth, TelemetryMessage,
+    ThreatLevel,
+};
+use axum::body::Body;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{FromRequestParts, Path, Query, Request, State};
+use axum::http::request::Parts;
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{error, info, warn};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiConfig {
+    /// Loopback by default; expose deliberately
+    pub bind: SocketAddr,
+    /// How often WebSocket clients get a status snapshot (ms)
+    pub status_interval_ms: u64,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            bind: SocketAddr::from(([127, 0, 0, 1], 8080)),
+            status_interval_ms: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreatLevelRequest {
+    pub level: ThreatLevel,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventsQuery {
+    limit: Option<usize>,
+}
+
+/// `GET /incidents/report`: one incident by id, or the events from `since`
+/// to `until`, as Markdown (the default), HTML or PDF
+#[derive(Debug, Deserialize)]
+pub struct ReportQuery {
+    pub incident: Option<uuid::Uuid>,
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+#[derive(Clone)]
+struct ApiState {
+    drone: Arc<RwLock<DroneState>>,
+    control: Option<Arc<dyn ModuleControl>>,
+    auth: Arc<AuthConfig>,
+    commands: Arc<CommandVerifier>,
+    pairing: Option<Arc<std::sync::Mutex<Pairing>>>,
+    reports: Arc<ReportSources>,
+    status_interval: Duration,
+}
+
+/// Largest request body a signature is checked over
+const MAX_SIGNED_BODY: usize = 64 * 1024;
+
+/// A request's bearer token and, if it was signed, its envelope
+struct Credentials {
+    token: Option<String>,
+    envelope: Option<CommandEnvelope>,
+}
+
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Credentials {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::to_string);
+        Ok(Self {
+            token,
+            envelope: parts.extensions.get::<CommandEnvelope>().cloned(),
+        })
+    }
+}
+
+/// An error reply: `{"error": "..."}` with the given status
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// The API routes, for embedding in a larger server
+pub fn router(
+    config: &ApiConfig,
+    drone: Arc<RwLock<DroneState>>,
+    control: Option<Arc<dyn ModuleControl>>,
+    auth: Arc<AuthConfig>,
+    commands: Arc<CommandVerifier>,
+    pairing: Option<Arc<std::sync::Mutex<Pairing>>>,
+    reports: Arc<ReportSources>,
+) -> Router {
+    let state = ApiState {
+        drone,
+        control,
+        auth,
+        commands,
+        pairing,
+        reports,
+        status_interval: Duration::from_millis(config.status_interval_ms.max(100)),
+    };
+    Router::new()
+        .route("/status", get(status))
+        .route("/health", get(health))
+        .route("/events", get(events))
+        .route("/threat-level", post(set_threat_level))
+        .route("/deterrence/test", post(test_deterrence))
+        .route("/fire-suppression/test", post(test_fire_suppression))
+        .route("/fire-suppression/activate", post(activate_fire_suppression))
+        .route("/shield/deploy", post(deploy_shield))
+        .route("/shield/retract", post(retract_shield))
+        .route("/ws", get(telemetry_socket))
+        .route("/pairing/code", post(open_pairing_code))
+        .route("/pairing", post(pair_controller))
+        .route("/pairing/controllers", get(paired_controllers))
+        .route("/pairing/controllers/:name", delete(revoke_controller))
+        .route("/incidents", get(incidents))
+        .route("/incidents/report", get(incident_report))
+        .layer(axum::middleware::from_fn(read_envelope))
+        .with_state(state)
+}
+
+/// `GET /metrics` in the Prometheus text format
+pub fn metrics_router(metrics: Metrics) -> Router {
+    Router::new().route(
+        "/metrics",
+        get(move || {
+            let metrics = metrics.clone();
+            async move { ([(header::CONTENT_TYPE, crate::metrics::CONTENT_TYPE)], metrics.render()) }
+        }),
+    )
+}
+
+/// `POST /panic`: verify a signed panic command, apply it to the drone and
+/// carry it out through `control`
+pub fn panic_router(
+    button: Arc<std::sync::Mutex<PanicButton>>,
+    drone: Arc<RwLock<DroneState>>,
+    control: Option<Arc<dyn ModuleControl>>,
+) -> Router {
+    Router::new().route(
+        "/panic",
+        post(move |Json(command): Json<PanicCommand>| async move {
+            let handled = {
+                let mut drone = drone.write().await;
+                let mut button = button.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                button.handle(&command, &mut drone, chrono::Utc::now())
+            };
+            let outcome = handled.map_err(|e| match e {
+                PanicError::NotActive => ApiError(StatusCode::CONFLICT, e.to_string()),
+                e => ApiError(StatusCode::UNAUTHORIZED, e.to_string()),
+            })?;
+            // The drone is at Red whatever the modules manage
+            match &control {
+                Some(control) => {
+                    if let Err(e) = crate::control::respond_to_panic(control.as_ref(), &outcome).await {
+                        error!("🆘 Modules failed to respond to the panic command: {}", e);
+                    }
+                },
+                None => warn!("🆘 No modules attached to respond to the panic command"),
+            }
+            ApiResult::<PanicOutcome>::Ok(Json(outcome))
+        }),
+    )
+}
+
+/// Serve the API, plus `/panic` and `/metrics` when given, until `shutdown` completes
+#[allow(clippy::too_many_arguments)] // One per piece of the drone the API reaches into
+pub async fn serve(
+    config: ApiConfig,
+    drone: Arc<RwLock<DroneState>>,
+    control: Option<Arc<dyn ModuleControl>>,
+    auth: Arc<AuthConfig>,
+    commands: Arc<CommandVerifier>,
+    pairing: Option<Arc<std::sync::Mutex<Pairing>>>,
+    reports: Arc<ReportSources>,
+    panic: Option<Arc<std::sync::Mutex<PanicButton>>>,
+    metrics: Option<Metrics>,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(config.bind).await?;
+    info!("🌐 Control API listening on {}", config.bind);
+    let mut app = router(&config, Arc::clone(&drone), control.clone(), auth, commands, pairing, reports);
+    if let Some(button) = panic {
+        app = app.merge(panic_router(button, drone, control));
+    }
+    if let Some(metrics) = metrics {
+        app = app.merge(metrics_router(metrics));
+    }
+    axum::serve(listener, app).with_graceful_shutdown(shutdown).await
+}
+
+async fn status(State(api): State<ApiState>, credentials: Credentials) -> ApiResult<TelemetryMessage> {
+    authorize(&api, &credentials, Action::ViewStatus).await?;
+    Ok(Json(TelemetryMessage::status(&*api.drone.read().await)))
+}
+
+async fn health(State(api): State<ApiState>, credentials: Credentials) -> ApiResult<SystemHealth> {
+    authorize(&api, &credentials, Action::ViewStatus).await?;
+    Ok(Json(api.drone.read().await.system_health.clone()))
+}
+
+async fn events(State(api): State<ApiState>, credentials: Credentials, Query(query): Query<EventsQuery>) -> ApiResult<Vec<MissionEvent>> {
+    authorize(&api, &credentials, Action::ViewStatus).await?;
+    let drone = api.drone.read().await;
+    let limit = query.limit.unwrap_or(100);
+    let skip = drone.mission_log.len().saturating_sub(limit);
+    Ok(Json(drone.mission_log[skip..].to_vec()))
+}
+
+async fn set_threat_level(State(api): State<ApiState>, credentials: Credentials, Json(request): Json<ThreatLevelRequest>) -> ApiResult<TelemetryMessage> {
+    let caller = authorize(&api, &credentials, Action::SetThreatLevel).await?;
+    let mut drone = api.drone.write().await;
+    if request.level == ThreatLevel::Omega {
+        caller.authorize(Action::AuthorizeOmega, &mut drone).map_err(auth_error)?;
+        drone.authorize_omega(&caller.principal);
+    }
+    drone
+        .request_threat_level(request.level, format!("Operator override by {}: {}", caller.principal, request.reason))
+        .map_err(|e| ApiError(StatusCode::CONFLICT, e.to_string()))?;
+    Ok(Json(TelemetryMessage::status(&drone)))
+}
+
+async fn test_deterrence(State(api): State<ApiState>, credentials: Credentials) -> Result<StatusCode, ApiError> {
+    authorize(&api, &credentials, Action::TestModule).await?;
+    let control = module_control(&api)?;
+    control
+        .test_deterrence()
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn test_fire_suppression(State(api): State<ApiState>, credentials: Credentials) -> Result<StatusCode, ApiError> {
+    authorize(&api, &credentials, Action::TestModule).await?;
+    let control = module_control(&api)?;
+    control
+        .test_fire_suppression()
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn activate_fire_suppression(State(api): State<ApiState>, credentials: Credentials) -> Result<StatusCode, ApiError> {
+    let caller = authorize(&api, &credentials, Action::ActivateFireSuppression).await?;
+    let control = module_control(&api)?;
+    info!("🧯 Manual fire suppression by {}", caller.principal);
+    control
+        .activate_fire_suppression()
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn deploy_shield(State(api): State<ApiState>, credentials: Credentials) -> Result<StatusCode, ApiError> {
+    authorize(&api, &credentials, Action::DeployShield).await?;
+    let control = module_control(&api)?;
+    control
+        .deploy_shield(true)
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn retract_shield(State(api): State<ApiState>, credentials: Credentials) -> Result<StatusCode, ApiError> {
+    authorize(&api, &credentials, Action::DeployShield).await?;
+    let control = module_control(&api)?;
+    control
+        .deploy_shield(false)
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn open_pairing_code(State(api): State<ApiState>, credentials: Credentials) -> ApiResult<PairingCode> {
+    let caller = authorize(&api, &credentials, Action::ManagePairing).await?;
+    let pairing = pairing(&api)?;
+    let drone = api.drone.read().await;
+    info!("🔐 Pairing code opened by {}", caller.principal);
+    let code = lock(&pairing).start(&drone, chrono::Utc::now());
+    Ok(Json(code))
+}
+
+async fn pair_controller(State(api): State<ApiState>, Json(request): Json<PairingRequest>) -> ApiResult<PairingResponse> {
+    let pairing = pairing(&api)?;
+    let mut drone = api.drone.write().await;
+    let paired = lock(&pairing).pair(&request, &api.commands, &mut drone, chrono::Utc::now());
+    paired.map(Json).map_err(pairing_error)
+}
+
+async fn paired_controllers(State(api): State<ApiState>, credentials: Credentials) -> ApiResult<Vec<PairedController>> {
+    authorize(&api, &credentials, Action::ManagePairing).await?;
+    let pairing = pairing(&api)?;
+    let controllers = lock(&pairing).controllers();
+    Ok(Json(controllers))
+}
+
+async fn revoke_controller(State(api): State<ApiState>, credentials: Credentials, Path(name): Path<String>) -> Result<StatusCode, ApiError> {
+    let caller = authorize(&api, &credentials, Action::ManagePairing).await?;
+    let pairing = pairing(&api)?;
+    let mut drone = api.drone.write().await;
+    let revoked = lock(&pairing).revoke(&name, &caller.principal, &api.commands, &mut drone, chrono::Utc::now());
+    revoked.map_err(pairing_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn incidents(State(api): State<ApiState>, credentials: Credentials) -> ApiResult<Vec<Incident>> {
+    authorize(&api, &credentials, Action::ViewStatus).await?;
+    let live = api.drone.read().await.mission_log.clone();
+    let reports = Arc::clone(&api.reports);
+    let events = crate::runtime::spawn_blocking(move || reports.events(&live))
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(report_error)?;
+    Ok(Json(crate::incident::incidents(&events)))
+}
+
+async fn incident_report(State(api): State<ApiState>, credentials: Credentials, Query(query): Query<ReportQuery>) -> Result<Response, ApiError> {
+    let caller = authorize(&api, &credentials, Action::ExportReport).await?;
+    let scope = match (query.incident, query.since, query.until) {
+        (Some(id), None, None) => ReportScope::Incident(id),
+        (None, since, until) => ReportScope::Range { since, until },
+        _ => return Err(ApiError(StatusCode::BAD_REQUEST, "give an incident or a time range, not both".to_string())),
+    };
+    let (name, live) = {
+        let drone = api.drone.read().await;
+        (drone.name.clone(), drone.mission_log.clone())
+    };
+    let reports = Arc::clone(&api.reports);
+    let rendered = crate::runtime::spawn_blocking(move || reports.report(&name, scope, &live, chrono::Utc::now())?.render(query.format))
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(report_error)?;
+    info!("📄 Incident report ({}) exported by {}", query.format, caller.principal);
+    Ok(([(header::CONTENT_TYPE, query.format.content_type())], rendered).into_response())
+}
+
+/// The caller behind the request's signature or bearer token, if they may
+/// perform `action`
+async fn authorize(api: &ApiState, credentials: &Credentials, action: Action) -> Result<AuthContext, ApiError> {
+    let mut drone = api.drone.write().await;
+    api.commands
+        .authorize(credentials.envelope.as_ref(), credentials.token.as_deref(), &api.auth, CommandSource::Api, action, &mut drone)
+        .map_err(auth_error)
+}
+
+/// Rebuild the envelope of a signed request from its headers and body, for
+/// `authorize` to verify
+async fn read_envelope(request: Request, next: Next) -> Result<Response, ApiError> {
+    if !request.headers().contains_key(SIGNATURE_HEADER) {
+        return Ok(next.run(request).await);
+    }
+    let (mut parts, body) = request.into_parts();
+    let body = axum::body::to_bytes(body, MAX_SIGNED_BODY)
+        .await
+        .map_err(|e| ApiError(StatusCode::PAYLOAD_TOO_LARGE, e.to_string()))?;
+    let path = parts.uri.path_and_query().map_or(parts.uri.path(), |path| path.as_str());
+    let command = http_command(parts.method.as_str(), path, &body);
+    if let Some(envelope) = CommandEnvelope::from_headers(command, |name| parts.headers.get(name).and_then(|value| value.to_str().ok())) {
+        parts.extensions.insert(envelope);
+    }
+    Ok(next.run(Request::from_parts(parts, Body::from(body))).await)
+}
+
+fn auth_error(e: AuthError) -> ApiError {
+    match e {
+        AuthError::Unauthenticated | AuthError::Rejected(_) => ApiError(StatusCode::UNAUTHORIZED, e.to_string()),
+        AuthError::Forbidden { .. } => ApiError(StatusCode::FORBIDDEN, e.to_string()),
+    }
+}
+
+fn pairing_error(e: PairingError) -> ApiError {
+    let status = match e {
+        PairingError::NoCode | PairingError::Expired | PairingError::WrongPin | PairingError::TooManyAttempts => StatusCode::UNAUTHORIZED,
+        PairingError::BadName(_) | PairingError::BadKey => StatusCode::BAD_REQUEST,
+        PairingError::NameTaken(_) => StatusCode::CONFLICT,
+        PairingError::UnknownController(_) => StatusCode::NOT_FOUND,
+        PairingError::Store(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    ApiError(status, e.to_string())
+}
+
+fn report_error(e: ReportError) -> ApiError {
+    let status = match e {
+        ReportError::UnknownIncident(_) => StatusCode::NOT_FOUND,
+        ReportError::BadRange | ReportError::UnknownFormat(_) => StatusCode::BAD_REQUEST,
+        ReportError::PdfUnavailable => StatusCode::NOT_IMPLEMENTED,
+        ReportError::Store(_) | ReportError::Evidence(..) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    ApiError(status, e.to_string())
+}
+
+fn pairing(api: &ApiState) -> Result<Arc<std::sync::Mutex<Pairing>>, ApiError> {
+    api.pairing
+        .clone()
+        .ok_or_else(|| ApiError(StatusCode::SERVICE_UNAVAILABLE, "pairing is not enabled".to_string()))
+}
+
+fn lock(pairing: &std::sync::Mutex<Pairing>) -> std::sync::MutexGuard<'_, Pairing> {
+    pairing.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn module_control(api: &ApiState) -> Result<Arc<dyn ModuleControl>, ApiError> {
+    api.control
+        .clone()
+        .ok_or_else(|| ApiError(StatusCode::SERVICE_UNAVAILABLE, "no modules attached".to_string()))
+}
+
+async fn telemetry_socket(State(api): State<ApiState>, credentials: Credentials, upgrade: WebSocketUpgrade) -> Result<Response, ApiError> {
+    authorize(&api, &credentials, Action::ViewStatus).await?;
+    Ok(upgrade.on_upgrade(move |socket| stream_telemetry(socket, api)))
+}
+
+/// Forward live telemetry plus a periodic status snapshot until the client leaves
+async fn stream_telemetry(mut socket: WebSocket, api: ApiState) {
+    let mut telemetry = api.drone.read().await.subscribe_telemetry();
+    let mut ticker = crate::runtime::interval(api.status_interval);
+    loop {
+        let message = tokio::select! {
+            _ = ticker.tick() => TelemetryMessage::status(&*api.drone.read().await),
+            received = telemetry.recv() => match received {
+                Ok(message) => message,
+                // A slow dashboard skips ahead rather than falling further behind
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+        };
+        let Ok(text) = serde_json::to_string(&message) else { continue };
+        if socket.send(Message::Text(text)).await.is_err() {
+            break;
+        }
+    }
+}
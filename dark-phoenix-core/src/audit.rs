@@ -0,0 +1,541 @@
+//! Tamper-evident audit log
+
+use crate::{DroneState, EventStore, Keyring, MissionEvent, ModuleResult, StoreError};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use tokio::sync::{broadcast, RwLock};
+
+/// Event store stream holding every mission event, one JSON line each
+pub const AUDIT_STREAM: &str = "audit_events";
+
+/// Event store stream holding the signed checkpoints
+pub const CHECKPOINT_STREAM: &str = "audit_checkpoints";
+
+/// `prev_hash` of a drone's very first event
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Identifies the export layout
+pub const EXPORT_FORMAT: &str = "dark-phoenix-audit/1";
+
+/// Where the next mission event joins the chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditHead {
+    pub sequence: u64,
+    /// Hash of the event before `sequence`
+    pub hash: String,
+}
+
+impl Default for AuditHead {
+    fn default() -> Self {
+        Self {
+            sequence: 0,
+            hash: GENESIS_HASH.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    /// Where the audit streams are kept
+    pub store_dir: PathBuf,
+    /// Hex ed25519 secret key, created on first use (absent = a new key every run)
+    pub key_path: Option<PathBuf>,
+    pub checkpoint_every: u64,
+    /// Longest an event waits for a checkpoint (seconds)
+    pub checkpoint_interval_secs: u64,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            store_dir: PathBuf::from("audit"),
+            key_path: None,
+            checkpoint_every: 100,
+            checkpoint_interval_secs: 300,
+        }
+    }
+}
+
+impl AuditConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.checkpoint_every == 0 {
+            problems.push("audit.checkpoint_every must be positive".to_string());
+        }
+        if self.checkpoint_interval_secs == 0 {
+            problems.push("audit.checkpoint_interval_secs must be positive".to_string());
+        }
+        problems
+    }
+}
+
+/// The drone's signed word that the chain up to `sequence` ended in `hash`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub sequence: u64,
+    pub hash: String,
+    pub timestamp: DateTime<Utc>,
+    pub signature: String,
+}
+
+impl Checkpoint {
+    fn sign(sequence: u64, hash: &str, timestamp: DateTime<Utc>, key: &SigningKey) -> Self {
+        let signature = key.sign(&Self::payload(sequence, hash, timestamp));
+        Self {
+            sequence,
+            hash: hash.to_string(),
+            timestamp,
+            signature: hex::encode(signature.to_bytes()),
+        }
+    }
+
+    /// The exact bytes signed
+    fn payload(sequence: u64, hash: &str, timestamp: DateTime<Utc>) -> Vec<u8> {
+        format!("dark-phoenix-audit-checkpoint\n{}\n{}\n{}", sequence, hash, timestamp.timestamp_millis()).into_bytes()
+    }
+
+    fn verify(&self, key: &VerifyingKey) -> bool {
+        let Some(signature) = hex::decode(&self.signature).ok().and_then(|bytes| Signature::from_slice(&bytes).ok()) else {
+            return false;
+        };
+        key.verify(&Self::payload(self.sequence, &self.hash, self.timestamp), &signature).is_ok()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AuditError {
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error("audit key {0}: {1}")]
+    Key(PathBuf, String),
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ChainError {
+    #[error("unsupported export format '{0}'")]
+    Format(String),
+    #[error("public key {0} is not the one trusted")]
+    UntrustedKey(String),
+    #[error("public key is malformed")]
+    BadKey,
+    #[error("entry {index} is not a mission event")]
+    Malformed { index: usize },
+    #[error("the chain starts at sequence {0} without a genesis link")]
+    NoGenesis(u64),
+    #[error("sequence jumps from {after} to {found}")]
+    Gap { after: u64, found: u64 },
+    #[error("event {sequence} does not link to the event before it")]
+    BrokenLink { sequence: u64 },
+    #[error("checkpoint at {sequence} has a bad signature")]
+    BadSignature { sequence: u64 },
+    #[error("a checkpoint seals event {sequence}, which is missing from the end of the log")]
+    Truncated { sequence: u64 },
+    #[error("checkpoint at {sequence} does not match the event logged there")]
+    CheckpointMismatch { sequence: u64 },
+}
+
+/// What a verified chain covers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainSummary {
+    pub events: usize,
+    pub first_sequence: Option<u64>,
+    pub last_sequence: Option<u64>,
+    pub checkpoints: usize,
+    /// Last event a signed checkpoint vouches for; anything after could
+    /// have been cut off without trace
+    pub sealed_through: Option<u64>,
+}
+
+/// Events and checkpoints in the form auditors receive them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditExport {
+    pub format: String,
+    /// Hex ed25519 key the checkpoints are signed with
+    pub public_key: String,
+    pub exported: DateTime<Utc>,
+    /// Each event's JSON line exactly as hashed
+    pub events: Vec<String>,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+impl AuditExport {
+    /// Check every link and checkpoint; `trusted_key` pins the drone's
+    /// public key rather than taking the export's word for it
+    pub fn verify(&self, trusted_key: Option<&str>) -> Result<ChainSummary, ChainError> {
+        if self.format != EXPORT_FORMAT {
+            return Err(ChainError::Format(self.format.clone()));
+        }
+        if trusted_key.is_some_and(|trusted| !trusted.eq_ignore_ascii_case(&self.public_key)) {
+            return Err(ChainError::UntrustedKey(self.public_key.clone()));
+        }
+        let key = hex::decode(&self.public_key)
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+            .ok_or(ChainError::BadKey)?;
+
+        let mut hashes = std::collections::HashMap::new();
+        let mut previous: Option<(u64, String)> = None;
+        for (index, line) in self.events.iter().enumerate() {
+            let event: MissionEvent = serde_json::from_str(line).map_err(|_| ChainError::Malformed { index })?;
+            match &previous {
+                None if event.sequence == 0 && event.prev_hash != GENESIS_HASH => {
+                    return Err(ChainError::BrokenLink { sequence: 0 });
+                },
+                // A later export may start mid-chain; checkpoints still pin it
+                None => {},
+                Some((sequence, _)) if event.sequence != sequence + 1 => {
+                    return Err(ChainError::Gap {
+                        after: *sequence,
+                        found: event.sequence,
+                    });
+                },
+                Some((_, hash)) if event.prev_hash != *hash => return Err(ChainError::BrokenLink { sequence: event.sequence }),
+                Some(_) => {},
+            }
+            let hash = hex::encode(Sha256::digest(line.as_bytes()));
+            hashes.insert(event.sequence, hash.clone());
+            previous = Some((event.sequence, hash));
+        }
+
+        let mut sealed_through = None;
+        for checkpoint in &self.checkpoints {
+            if !checkpoint.verify(&key) {
+                return Err(ChainError::BadSignature { sequence: checkpoint.sequence });
+            }
+            match hashes.get(&checkpoint.sequence) {
+                Some(hash) if *hash == checkpoint.hash => sealed_through = sealed_through.max(Some(checkpoint.sequence)),
+                Some(_) => return Err(ChainError::CheckpointMismatch { sequence: checkpoint.sequence }),
+                None if previous.as_ref().is_none_or(|(last, _)| checkpoint.sequence > *last) => {
+                    return Err(ChainError::Truncated { sequence: checkpoint.sequence });
+                },
+                // Before an export that starts mid-chain
+                None => {},
+            }
+        }
+        let first_sequence = self.events.first().and_then(|line| serde_json::from_str::<MissionEvent>(line).ok()).map(|event| event.sequence);
+        if let Some(first) = first_sequence.filter(|first| *first > 0 && sealed_through.is_none()) {
+            return Err(ChainError::NoGenesis(first));
+        }
+        Ok(ChainSummary {
+            events: self.events.len(),
+            first_sequence,
+            last_sequence: previous.map(|(sequence, _)| sequence),
+            checkpoints: self.checkpoints.len(),
+            sealed_through,
+        })
+    }
+}
+
+/// Persists the drone's mission events and signs checkpoints over them
+pub struct AuditLog {
+    config: AuditConfig,
+    store: EventStore,
+    key: SigningKey,
+    /// The next event to persist, and the hash it must link to
+    head: AuditHead,
+    last_checkpoint: Option<u64>,
+    checkpointed_at: DateTime<Utc>,
+    /// Where checkpoints are forwarded as they are signed
+    #[cfg(feature = "siem")]
+    siem: Option<crate::SiemForwarder>,
+}
+
+impl AuditLog {
+    /// Open the audit streams, picking up the chain where the last run left
+    /// it; `keyring` encrypts them at rest
+    pub fn open(config: AuditConfig, keyring: Option<Arc<Keyring>>) -> Result<Self, AuditError> {
+        let store = EventStore::open(&config.store_dir)?.with_keyring(keyring);
+        let key = match &config.key_path {
+            Some(path) => load_or_create_key(path)?,
+            None => {
+                let key = SigningKey::from_bytes(&rand::random());
+                tracing::warn!(
+                    "🔏 No audit key configured; checkpoints are signed with a key that dies with this run (public key {})",
+                    hex::encode(key.verifying_key().to_bytes())
+                );
+                key
+            },
+        };
+        if let Some(torn) = store.repair_tail(AUDIT_STREAM)? {
+            tracing::warn!("🔏 Audit log ended in a torn write; moved {} bytes to {}.torn", torn, AUDIT_STREAM);
+        }
+        let lines = store.read_lines(AUDIT_STREAM)?;
+        let last = lines
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(index, line)| serde_json::from_str::<MissionEvent>(line).ok().map(|event| (index, line, event)));
+        let head = match last {
+            Some((index, line, event)) => {
+                let unreadable = lines.len() - index - 1;
+                if unreadable > 0 {
+                    // Left in place so verification reports the damage
+                    tracing::warn!("🔏 {} unreadable audit line(s) after event {}; continuing the chain from it", unreadable, event.sequence);
+                }
+                AuditHead {
+                    sequence: event.sequence + 1,
+                    hash: hex::encode(Sha256::digest(line.as_bytes())),
+                }
+            },
+            None if !lines.is_empty() => {
+                tracing::warn!("🔏 No readable event in {} audit line(s); starting a new chain", lines.len());
+                AuditHead::default()
+            },
+            None => AuditHead::default(),
+        };
+        let last_checkpoint = store.read_recent::<Checkpoint>(CHECKPOINT_STREAM, 1)?.first().map(|checkpoint| checkpoint.sequence);
+        tracing::info!("🔏 Audit chain at event {} in {}", head.sequence, store.root().display());
+        Ok(Self {
+            config,
+            store,
+            key,
+            head,
+            last_checkpoint,
+            checkpointed_at: Utc::now(),
+            #[cfg(feature = "siem")]
+            siem: None,
+        })
+    }
+
+    /// Forward every checkpoint to a SIEM from now on
+    #[cfg(feature = "siem")]
+    pub fn set_siem(&mut self, forwarder: crate::SiemForwarder) {
+        self.siem = Some(forwarder);
+    }
+
+    /// Where the drone's next event must join, for `DroneState::with_audit_head`
+    pub fn head(&self) -> AuditHead {
+        self.head.clone()
+    }
+
+    pub fn public_key(&self) -> String {
+        hex::encode(self.key.verifying_key().to_bytes())
+    }
+
+    /// Persist the events logged since the last sync and sign a checkpoint
+    /// when one is due
+    pub fn sync(&mut self, drone: &DroneState, now: DateTime<Utc>) -> Result<Option<Checkpoint>, AuditError> {
+        let start = drone.mission_log.partition_point(|event| event.sequence < self.head.sequence);
+        for event in &drone.mission_log[start..] {
+            self.store.append(AUDIT_STREAM, event)?;
+            self.head = AuditHead {
+                sequence: event.sequence + 1,
+                hash: event.digest(),
+            };
+        }
+        let unsealed = self.head.sequence - self.last_checkpoint.map_or(0, |sequence| sequence + 1);
+        let overdue = now - self.checkpointed_at >= chrono::Duration::seconds(self.config.checkpoint_interval_secs as i64);
+        if unsealed >= self.config.checkpoint_every || (unsealed > 0 && overdue) {
+            return self.checkpoint(now);
+        }
+        Ok(None)
+    }
+
+    /// Sign the head of the chain now, e.g. on shutdown
+    pub fn checkpoint(&mut self, now: DateTime<Utc>) -> Result<Option<Checkpoint>, AuditError> {
+        self.checkpointed_at = now;
+        let Some(sequence) = self.head.sequence.checked_sub(1).filter(|sequence| Some(*sequence) != self.last_checkpoint) else {
+            return Ok(None);
+        };
+        let checkpoint = Checkpoint::sign(sequence, &self.head.hash, now, &self.key);
+        self.store.append(CHECKPOINT_STREAM, &checkpoint)?;
+        self.store.flush()?;
+        self.last_checkpoint = Some(sequence);
+        tracing::debug!("🔏 Audit checkpoint at event {}", sequence);
+        #[cfg(feature = "siem")]
+        if let Some(siem) = &self.siem {
+            siem.forward(&checkpoint);
+        }
+        Ok(Some(checkpoint))
+    }
+
+    pub fn export(&self) -> Result<AuditExport, AuditError> {
+        export(&self.store, &self.public_key())
+    }
+
+    /// Check the persisted chain against this drone's key
+    pub fn verify_chain(&self) -> Result<Result<ChainSummary, ChainError>, AuditError> {
+        let export = self.export()?;
+        Ok(export.verify(Some(&self.public_key())))
+    }
+}
+
+/// Persist new events every second until the drone is gone
+pub async fn follow(audit: Arc<Mutex<AuditLog>>, drone: Arc<RwLock<DroneState>>) -> ModuleResult {
+    let mut poll = crate::runtime::interval(std::time::Duration::from_secs(1));
+    let mut transitions = drone.read().await.subscribe_threat_transitions();
+    loop {
+        tokio::select! {
+            received = transitions.recv() => match received {
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                _ => continue,
+            },
+            _ = poll.tick() => {
+                let drone = drone.read().await;
+                let mut audit = audit.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                audit.sync(&drone, Utc::now())?;
+            },
+        }
+    }
+}
+
+/// Read an audit store into an export, e.g. after the drone has landed;
+/// `public_key` is the hex key its checkpoints were signed with
+pub fn export(store: &EventStore, public_key: &str) -> Result<AuditExport, AuditError> {
+    Ok(AuditExport {
+        format: EXPORT_FORMAT.to_string(),
+        public_key: public_key.to_string(),
+        exported: Utc::now(),
+        events: store.read_lines(AUDIT_STREAM)?,
+        checkpoints: store.read(CHECKPOINT_STREAM)?,
+    })
+}
+
+/// The hex public key belonging to the secret key at `path`
+pub fn public_key_of(path: &Path) -> Result<String, AuditError> {
+    Ok(hex::encode(read_key(path)?.verifying_key().to_bytes()))
+}
+
+fn read_key(path: &Path) -> Result<SigningKey, AuditError> {
+    let text = std::fs::read_to_string(path).map_err(|e| AuditError::Key(path.to_path_buf(), e.to_string()))?;
+    let bytes = hex::decode(text.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| AuditError::Key(path.to_path_buf(), "expected 64 hex digits".to_string()))?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+fn load_or_create_key(path: &Path) -> Result<SigningKey, AuditError> {
+    if path.exists() {
+        return read_key(path);
+    }
+    let key = SigningKey::from_bytes(&rand::random());
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let write = options
+        .open(path)
+        .and_then(|mut file| std::io::Write::write_all(&mut file, hex::encode(key.to_bytes()).as_bytes()));
+    write.map_err(|e| AuditError::Key(path.to_path_buf(), e.to_string()))?;
+    tracing::info!("🔏 Created audit key {}; public key {}", path.display(), hex::encode(key.verifying_key().to_bytes()));
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EventType;
+
+    /// An audit log in a fresh directory, with `count` events synced and sealed
+    fn logged(count: usize) -> (AuditLog, DroneState) {
+        let store_dir = std::env::temp_dir().join(format!("dark-phoenix-audit-{}", uuid::Uuid::new_v4()));
+        let config = AuditConfig {
+            key_path: Some(store_dir.join("audit.key")),
+            store_dir,
+            ..AuditConfig::default()
+        };
+        let mut audit = AuditLog::open(config, None).unwrap();
+        let mut drone = DroneState::new("test".to_string());
+        for index in 0..count {
+            drone.log_event(EventType::ThreatDetected, format!("event {}", index), Vec::new());
+        }
+        audit.sync(&drone, Utc::now()).unwrap();
+        audit.checkpoint(Utc::now()).unwrap();
+        (audit, drone)
+    }
+
+    #[test]
+    fn intact_chain_verifies() {
+        let (audit, _) = logged(5);
+        let summary = audit.verify_chain().unwrap().unwrap();
+        assert_eq!(summary.events, 5);
+        assert_eq!(summary.first_sequence, Some(0));
+        assert_eq!(summary.last_sequence, Some(4));
+        assert_eq!(summary.sealed_through, Some(4));
+    }
+
+    #[test]
+    fn altered_event_breaks_the_next_link() {
+        let (audit, _) = logged(5);
+        let mut export = audit.export().unwrap();
+        export.events[2] = export.events[2].replace("event 2", "event two");
+        assert_eq!(export.verify(None), Err(ChainError::BrokenLink { sequence: 3 }));
+    }
+
+    #[test]
+    fn dropped_event_is_a_gap() {
+        let (audit, _) = logged(5);
+        let mut export = audit.export().unwrap();
+        export.events.remove(2);
+        assert_eq!(export.verify(None), Err(ChainError::Gap { after: 1, found: 3 }));
+    }
+
+    #[test]
+    fn cut_off_tail_is_caught_by_the_checkpoint() {
+        let (audit, _) = logged(5);
+        let mut export = audit.export().unwrap();
+        export.events.truncate(3);
+        assert_eq!(export.verify(None), Err(ChainError::Truncated { sequence: 4 }));
+    }
+
+    #[test]
+    fn checkpoints_are_bound_to_the_key() {
+        let (audit, _) = logged(3);
+        let (other, _) = logged(0);
+        let export = audit.export().unwrap();
+        assert!(matches!(export.verify(Some(&other.public_key())), Err(ChainError::UntrustedKey(_))));
+
+        let mut forged = export.clone();
+        forged.public_key = other.public_key();
+        assert_eq!(forged.verify(None), Err(ChainError::BadSignature { sequence: 2 }));
+
+        let mut moved = export;
+        moved.checkpoints[0].hash = GENESIS_HASH.to_string();
+        assert_eq!(moved.verify(None), Err(ChainError::BadSignature { sequence: 2 }));
+    }
+
+    #[test]
+    fn reopened_log_continues_the_chain() {
+        let (audit, _) = logged(3);
+        let config = audit.config.clone();
+        drop(audit);
+        let mut audit = AuditLog::open(config, None).unwrap();
+        assert_eq!(audit.head().sequence, 3);
+
+        let mut drone = DroneState::new("test".to_string()).with_audit_head(audit.head());
+        drone.log_event(EventType::ThreatDeEscalated, "after restart".to_string(), Vec::new());
+        audit.sync(&drone, Utc::now()).unwrap();
+        let summary = audit.export().unwrap().verify(None).unwrap();
+        assert_eq!(summary.last_sequence, Some(3));
+    }
+
+    #[test]
+    fn torn_final_write_is_cut_off_and_the_chain_resumes() {
+        let (audit, _) = logged(3);
+        let config = audit.config.clone();
+        drop(audit);
+        let path = config.store_dir.join(format!("{}.jsonl", AUDIT_STREAM));
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        std::io::Write::write_all(&mut file, br#"{"id":"8c1f"#).unwrap();
+
+        let mut audit = AuditLog::open(config.clone(), None).unwrap();
+        assert_eq!(audit.head().sequence, 3);
+        let torn = std::fs::read_to_string(config.store_dir.join(format!("{}.torn", AUDIT_STREAM))).unwrap();
+        assert_eq!(torn, "{\"id\":\"8c1f\n");
+
+        let mut drone = DroneState::new("test".to_string()).with_audit_head(audit.head());
+        drone.log_event(EventType::ThreatDeEscalated, "after restart".to_string(), Vec::new());
+        audit.sync(&drone, Utc::now()).unwrap();
+        let summary = audit.verify_chain().unwrap().unwrap();
+        assert_eq!(summary.events, 4);
+    }
+}
@@ -0,0 +1,237 @@
+//! Who may command the drone
+
+use crate::{DroneState, EnvelopeError, EventType};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Watch status and telemetry
+    Observer,
+    /// Run the drone day to day: tests, deterrence, shield, arming
+    Operator,
+    /// Lethal-adjacent and irreversible commands: manual fire suppression, Omega
+    Commander,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Role::Observer => "observer",
+            Role::Operator => "operator",
+            Role::Commander => "commander",
+        })
+    }
+}
+
+/// What a command does, for permission checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    ViewStatus,
+    AcknowledgeThreat,
+    SetThreatLevel,
+    TestModule,
+    ArmDisarm,
+    ReturnToHome,
+    ActivateDeterrence,
+    DeployShield,
+    ActivateFireSuppression,
+    AuthorizeOmega,
+    ManagePairing,
+    ExportReport,
+}
+
+impl Action {
+    /// The least role allowed to perform the action
+    pub fn required_role(self) -> Role {
+        match self {
+            Action::ViewStatus => Role::Observer,
+            Action::AcknowledgeThreat
+            | Action::SetThreatLevel
+            | Action::TestModule
+            | Action::ArmDisarm
+            | Action::ReturnToHome
+            | Action::ActivateDeterrence
+            | Action::DeployShield
+            | Action::ExportReport => Role::Operator,
+            Action::ActivateFireSuppression | Action::AuthorizeOmega | Action::ManagePairing => Role::Commander,
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Action::ViewStatus => "view status",
+            Action::AcknowledgeThreat => "acknowledge threats",
+            Action::SetThreatLevel => "set the threat level",
+            Action::TestModule => "run module self-tests",
+            Action::ArmDisarm => "arm or disarm",
+            Action::ReturnToHome => "order a return to home",
+            Action::ActivateDeterrence => "activate deterrence",
+            Action::DeployShield => "move the shield",
+            Action::ActivateFireSuppression => "activate fire suppression",
+            Action::AuthorizeOmega => "authorize Omega",
+            Action::ManagePairing => "pair or revoke controllers",
+            Action::ExportReport => "export incident reports",
+        })
+    }
+}
+
+/// Where a command came in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandSource {
+    Api,
+    Grpc,
+    Mqtt,
+    Lora,
+    /// The dashboard on the drone's own terminal
+    Console,
+}
+
+impl fmt::Display for CommandSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CommandSource::Api => "API",
+            CommandSource::Grpc => "gRPC",
+            CommandSource::Mqtt => "MQTT",
+            CommandSource::Lora => "LoRa",
+            CommandSource::Console => "console",
+        })
+    }
+}
+
+/// The caller behind a command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthContext {
+    pub principal: String,
+    pub role: Role,
+    pub source: CommandSource,
+}
+
+impl AuthContext {
+    pub fn new(principal: &str, role: Role, source: CommandSource) -> Self {
+        Self {
+            principal: principal.to_string(),
+            role,
+            source,
+        }
+    }
+
+    pub fn permits(&self, action: Action) -> bool {
+        self.role >= action.required_role()
+    }
+
+    /// Allow `action`, or refuse it and record the refusal in the mission log
+    pub fn authorize(&self, action: Action, drone: &mut DroneState) -> Result<(), AuthError> {
+        if self.permits(action) {
+            return Ok(());
+        }
+        let error = AuthError::Forbidden {
+            principal: self.principal.clone(),
+            role: self.role,
+            action,
+        };
+        tracing::warn!("🔐 Refused over {}: {}", self.source, error);
+        drone.log_event(EventType::AccessDenied, format!("Refused over {}: {}", self.source, error), Vec::new());
+        Err(error)
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum AuthError {
+    #[error("missing or unknown access token")]
+    Unauthenticated,
+    #[error("{principal} ({role}) may not {action}; that needs {}", .action.required_role())]
+    Forbidden { principal: String, role: Role, action: Action },
+    /// A signed command that failed verification, or an unsigned one where
+    /// signing is required
+    #[error("{0}")]
+    Rejected(EnvelopeError),
+}
+
+/// A bearer token and the role it grants
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessToken {
+    pub principal: String,
+    pub token: String,
+    pub role: Role,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    pub tokens: Vec<AccessToken>,
+    /// Role of callers without a token (absent = they are turned away)
+    pub anonymous_role: Option<Role>,
+    /// Role of whoever sits at the drone's own terminal
+    pub console_role: Role,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            tokens: Vec::new(),
+            anonymous_role: Some(Role::Observer),
+            console_role: Role::Commander,
+        }
+    }
+}
+
+impl AuthConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (i, token) in self.tokens.iter().enumerate() {
+            if token.token.len() < 16 {
+                problems.push(format!("access token for '{}' must be at least 16 characters", token.principal));
+            }
+            if self.tokens[..i].iter().any(|earlier| earlier.token == token.token) {
+                problems.push(format!("access token for '{}' is also given to someone else", token.principal));
+            }
+        }
+        problems
+    }
+
+    /// Who presented `token`; an unknown token is refused rather than
+    /// treated as anonymous
+    pub fn authenticate(&self, token: Option<&str>, source: CommandSource) -> Result<AuthContext, AuthError> {
+        match token {
+            Some(token) => self
+                .tokens
+                .iter()
+                .find(|known| constant_time_eq(known.token.as_bytes(), token.as_bytes()))
+                .map(|known| AuthContext::new(&known.principal, known.role, source))
+                .ok_or(AuthError::Unauthenticated),
+            None => self
+                .anonymous_role
+                .map(|role| AuthContext::new("anonymous", role, source))
+                .ok_or(AuthError::Unauthenticated),
+        }
+    }
+
+    /// Authenticate the caller and check they may perform `action`, logging
+    /// any refusal
+    pub fn authorize(&self, token: Option<&str>, source: CommandSource, action: Action, drone: &mut DroneState) -> Result<AuthContext, AuthError> {
+        let caller = self.authenticate(token, source).inspect_err(|e| {
+            tracing::warn!("🔐 Refused to {} over {}: {}", action, source, e);
+            drone.log_event(EventType::AccessDenied, format!("Refused to {} over {}: {}", action, source, e), Vec::new());
+        })?;
+        caller.authorize(action, drone)?;
+        Ok(caller)
+    }
+
+    /// The caller at the drone's own terminal
+    pub fn console(&self) -> AuthContext {
+        AuthContext::new("console", self.console_role, CommandSource::Console)
+    }
+}
+
+/// Compare without leaking how much of a token matched through timing
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
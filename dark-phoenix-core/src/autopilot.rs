@@ -0,0 +1,87 @@
+//! Flight controller bridge (`mavlink` feature)
+
+use crate::{DroneState, EventType, FlightControl, FlightTelemetry, ModuleResult, Position};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+pub use ::flight::{FlightConfig, FlightController, FlightError, Imu, VehicleStatus};
+
+/// Autopilot streams run at up to 50 Hz; the drone state needs far less
+const REPORT_INTERVAL: Duration = Duration::from_millis(200);
+
+#[async_trait]
+impl FlightControl for FlightController {
+    async fn loiter(&self) -> ModuleResult {
+        Ok(FlightController::loiter(self).await?)
+    }
+
+    async fn return_to_home(&self) -> ModuleResult {
+        Ok(FlightController::return_to_home(self).await?)
+    }
+
+    async fn fly_to(&self, target: &Position, heading_deg: Option<f64>) -> ModuleResult {
+        let heading = heading_deg.map(|heading| heading as f32);
+        Ok(FlightController::fly_to(self, target.latitude, target.longitude, target.altitude, heading).await?)
+    }
+
+    async fn emergency_land(&self) -> ModuleResult {
+        Ok(FlightController::emergency_land(self).await?)
+    }
+}
+
+impl From<&VehicleStatus> for FlightTelemetry {
+    fn from(status: &VehicleStatus) -> Self {
+        let timestamp = Utc::now();
+        FlightTelemetry {
+            connected: status.connected,
+            armed: status.armed,
+            position: status.gps_lock().then_some(Position {
+                latitude: status.latitude,
+                longitude: status.longitude,
+                altitude: status.relative_altitude_m,
+                timestamp,
+            }),
+            roll_deg: status.attitude.roll_deg,
+            pitch_deg: status.attitude.pitch_deg,
+            yaw_deg: status.attitude.yaw_deg,
+            gps_lock: status.gps_lock(),
+            satellites: status.satellites,
+            battery_level: status.battery_remaining,
+            battery_voltage: status.battery_voltage,
+            battery_current_a: status.battery_current_a,
+            acceleration_mps2: status.imu.map(|imu| imu.acceleration_mps2),
+            angular_rate_dps: status.imu.map(|imu| imu.angular_rate_dps),
+            timestamp,
+        }
+    }
+}
+
+/// Report the autopilot's status into the drone as it streams in, logging
+/// when the link drops and returns; runs until the controller is dropped
+pub async fn follow(controller: Arc<FlightController>, drone: Arc<RwLock<DroneState>>) -> ModuleResult {
+    let mut status = controller.watch();
+    let mut connected = false;
+    while status.changed().await.is_ok() {
+        let telemetry = FlightTelemetry::from(&*status.borrow_and_update());
+        let mut drone = drone.write().await;
+        if telemetry.connected != connected {
+            connected = telemetry.connected;
+            if connected {
+                tracing::info!("🛩️ Flight controller connected");
+            } else {
+                drone.log_event(
+                    EventType::SystemMalfunction,
+                    "Flight controller link lost".to_string(),
+                    vec!["Autopilot failsafe in control".to_string()],
+                );
+            }
+        }
+        drone.report_flight(telemetry);
+        drop(drone);
+        crate::runtime::sleep(REPORT_INTERVAL).await;
+    }
+    Ok(())
+}
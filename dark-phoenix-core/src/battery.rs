@@ -0,0 +1,385 @@
+//! Battery state of health: charge cycles, voltage sag, internal resistance
+//! and temperature, kept across runs
+
+use crate::{DroneState, EventStore, EventType, Keyring, ModuleResult, StoreError, TelemetryMessage};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, RwLock};
+
+/// Event store stream holding every finished discharge session
+pub const BATTERY_STREAM: &str = "battery_cycles";
+
+/// How much each new resistance measurement moves the running estimate
+const RESISTANCE_SMOOTHING: f64 = 0.1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatteryConfig {
+    /// Identifies the pack in the history, so swapping packs starts afresh
+    pub pack_id: String,
+    /// Cycles after which the pack is expected to hold 80% of its capacity
+    pub rated_cycles: u32,
+    /// Where charge-cycle history is kept (absent = forgotten on restart)
+    pub store_dir: Option<PathBuf>,
+    /// Internal resistance of the new pack (absent = learned from its first flights)
+    pub nominal_resistance_mohm: Option<f64>,
+    /// Temperature the pack must never exceed
+    pub max_temperature_c: f32,
+    /// Below this current the voltage is taken as the resting voltage
+    pub rest_current_a: f32,
+    /// Above this current the voltage drop is measured as sag
+    pub load_current_a: f32,
+    /// Rise in charge that counts as a recharge and closes the session
+    pub recharge_percent: u8,
+}
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        Self {
+            pack_id: "main".to_string(),
+            rated_cycles: 300,
+            store_dir: None,
+            nominal_resistance_mohm: None,
+            max_temperature_c: 60.0,
+            rest_current_a: 1.0,
+            load_current_a: 5.0,
+            recharge_percent: 10,
+        }
+    }
+}
+
+impl BatteryConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.pack_id.is_empty() {
+            problems.push("battery.pack_id must not be empty".to_string());
+        }
+        if self.rated_cycles == 0 {
+            problems.push("battery.rated_cycles must be positive".to_string());
+        }
+        if self.nominal_resistance_mohm.is_some_and(|mohm| mohm <= 0.0) {
+            problems.push("battery.nominal_resistance_mohm must be positive".to_string());
+        }
+        if self.rest_current_a >= self.load_current_a {
+            problems.push("battery.rest_current_a must be below battery.load_current_a".to_string());
+        }
+        if self.recharge_percent == 0 {
+            problems.push("battery.recharge_percent must be positive".to_string());
+        }
+        problems
+    }
+}
+
+/// One reading of the pack, from the flight controller or the BMS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatterySample {
+    pub voltage_v: f32,
+    pub current_a: Option<f32>,
+    pub temperature_c: Option<f32>,
+    /// Remaining charge, 0-100%
+    pub level: Option<u8>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A finished discharge session, as persisted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChargeCycle {
+    pub pack_id: String,
+    pub started: DateTime<Utc>,
+    pub ended: DateTime<Utc>,
+    /// Charge used during the session, in percent of the pack
+    pub discharged_percent: f64,
+    /// Cycles on the pack, this session included
+    pub total_cycles: f64,
+    pub resistance_mohm: Option<f64>,
+    pub max_sag_v: Option<f32>,
+    pub max_temperature_c: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatteryGrade {
+    /// Retire the pack: it no longer holds 70% of its capacity, or has overheated
+    Retire,
+    Poor,
+    Fair,
+    Good,
+}
+
+impl BatteryGrade {
+    fn from_state_of_health(percent: u8) -> Self {
+        match percent {
+            90.. => BatteryGrade::Good,
+            80.. => BatteryGrade::Fair,
+            70.. => BatteryGrade::Poor,
+            _ => BatteryGrade::Retire,
+        }
+    }
+}
+
+impl fmt::Display for BatteryGrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BatteryGrade::Retire => "retire",
+            BatteryGrade::Poor => "poor",
+            BatteryGrade::Fair => "fair",
+            BatteryGrade::Good => "good",
+        })
+    }
+}
+
+/// Where the pack stands, for preflight checks and flight-time estimates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatteryHealthReport {
+    pub pack_id: String,
+    pub cycles: f64,
+    /// Share of the rated capacity the pack still holds (0-100%)
+    pub state_of_health: u8,
+    pub grade: BatteryGrade,
+    pub resistance_mohm: Option<f64>,
+    /// Growth in internal resistance since the pack was new (percent)
+    pub resistance_growth_percent: Option<f64>,
+    /// Largest voltage drop under load this session
+    pub max_sag_v: Option<f32>,
+    pub temperature_c: Option<f32>,
+    /// The pack has been above `max_temperature_c` this session
+    pub overheated: bool,
+}
+
+/// Tracks the pack's health across readings and runs
+#[derive(Debug, Clone)]
+pub struct BatteryHealth {
+    config: BatteryConfig,
+    store: Option<EventStore>,
+    /// Cycles from earlier sessions
+    previous_cycles: f64,
+    baseline_mohm: Option<f64>,
+    resistance_mohm: Option<f64>,
+    rest_voltage: Option<f32>,
+    last_level: Option<u8>,
+    temperature_c: Option<f32>,
+    session_started: Option<DateTime<Utc>>,
+    discharged_percent: f64,
+    max_sag_v: Option<f32>,
+    max_temperature_c: Option<f32>,
+    grade: Option<BatteryGrade>,
+}
+
+impl BatteryHealth {
+    pub fn new(config: BatteryConfig) -> Self {
+        Self {
+            baseline_mohm: config.nominal_resistance_mohm,
+            config,
+            store: None,
+            previous_cycles: 0.0,
+            resistance_mohm: None,
+            rest_voltage: None,
+            last_level: None,
+            temperature_c: None,
+            session_started: None,
+            discharged_percent: 0.0,
+            max_sag_v: None,
+            max_temperature_c: None,
+            grade: None,
+        }
+    }
+
+    /// Persist sessions to `store` and pick up the pack's history from it
+    pub fn with_event_store(mut self, store: EventStore) -> Result<Self, StoreError> {
+        let history: Vec<ChargeCycle> = store.read(BATTERY_STREAM)?;
+        let history: Vec<_> = history.into_iter().filter(|cycle| cycle.pack_id == self.config.pack_id).collect();
+        if let Some(last) = history.last() {
+            self.previous_cycles = last.total_cycles;
+            self.resistance_mohm = history.iter().rev().find_map(|cycle| cycle.resistance_mohm);
+            if self.baseline_mohm.is_none() {
+                self.baseline_mohm = history.iter().find_map(|cycle| cycle.resistance_mohm);
+            }
+            tracing::info!(
+                "🔋 Battery '{}': {:.1} cycles over {} sessions restored from {}",
+                self.config.pack_id,
+                self.previous_cycles,
+                history.len(),
+                store.root().display()
+            );
+        }
+        self.store = Some(store);
+        Ok(self)
+    }
+
+    /// Open the store named in the configuration, if there is one,
+    /// encrypted with `keyring` when given
+    pub fn from_config(config: BatteryConfig, keyring: Option<Arc<Keyring>>) -> Result<Self, StoreError> {
+        let store_dir = config.store_dir.clone();
+        let battery = Self::new(config);
+        match store_dir {
+            Some(dir) => battery.with_event_store(EventStore::open(dir)?.with_keyring(keyring)),
+            None => Ok(battery),
+        }
+    }
+
+    pub fn cycles(&self) -> f64 {
+        self.previous_cycles + self.discharged_percent / 100.0
+    }
+
+    /// Take in one reading of the pack
+    pub fn observe(&mut self, sample: &BatterySample) {
+        self.session_started.get_or_insert(sample.timestamp);
+
+        if let Some(level) = sample.level {
+            match self.last_level {
+                Some(last) if level < last => self.discharged_percent += f64::from(last - level),
+                Some(last) if level >= last.saturating_add(self.config.recharge_percent) => {
+                    tracing::info!("🔋 Battery '{}' recharged {}% -> {}%", self.config.pack_id, last, level);
+                    self.end_session(sample.timestamp);
+                    self.session_started = Some(sample.timestamp);
+                },
+                _ => {},
+            }
+            self.last_level = Some(level);
+        }
+
+        if let Some(current) = sample.current_a {
+            if current < self.config.rest_current_a {
+                self.rest_voltage = Some(sample.voltage_v);
+            } else if let Some(rest) = self.rest_voltage.filter(|_| current >= self.config.load_current_a) {
+                let sag = rest - sample.voltage_v;
+                if sag > 0.0 {
+                    self.max_sag_v = Some(self.max_sag_v.map_or(sag, |max| max.max(sag)));
+                    let mohm = f64::from(sag / current) * 1000.0;
+                    self.resistance_mohm = Some(match self.resistance_mohm {
+                        Some(estimate) => estimate + (mohm - estimate) * RESISTANCE_SMOOTHING,
+                        None => mohm,
+                    });
+                }
+            }
+        }
+
+        if let Some(temperature) = sample.temperature_c {
+            self.temperature_c = Some(temperature);
+            self.max_temperature_c = Some(self.max_temperature_c.map_or(temperature, |max| max.max(temperature)));
+        }
+    }
+
+    /// Close the current discharge session and persist it, e.g. on shutdown
+    pub fn end_session(&mut self, now: DateTime<Utc>) {
+        let Some(started) = self.session_started.take() else {
+            return;
+        };
+        if self.resistance_mohm.is_some() && self.baseline_mohm.is_none() {
+            // The pack's first measured session is what it is compared against
+            self.baseline_mohm = self.resistance_mohm;
+        }
+        let cycle = ChargeCycle {
+            pack_id: self.config.pack_id.clone(),
+            started,
+            ended: now,
+            discharged_percent: self.discharged_percent,
+            total_cycles: self.cycles(),
+            resistance_mohm: self.resistance_mohm,
+            max_sag_v: self.max_sag_v,
+            max_temperature_c: self.max_temperature_c,
+        };
+        self.previous_cycles = cycle.total_cycles;
+        self.discharged_percent = 0.0;
+        self.max_sag_v = None;
+        self.max_temperature_c = None;
+        if let Some(store) = &self.store {
+            if let Err(e) = store.append(BATTERY_STREAM, &cycle) {
+                tracing::error!("🔋 Failed to record battery session: {}", e);
+            }
+        }
+    }
+
+    /// Flush the session history to disk
+    pub fn flush(&self) -> Result<(), StoreError> {
+        self.store.as_ref().map_or(Ok(()), EventStore::flush)
+    }
+
+    pub fn report(&self) -> BatteryHealthReport {
+        let cycles = self.cycles();
+        let wear = 100.0 - 20.0 * cycles / f64::from(self.config.rated_cycles.max(1));
+        let growth = match (self.resistance_mohm, self.baseline_mohm) {
+            (Some(resistance), Some(baseline)) if baseline > 0.0 => Some((resistance / baseline - 1.0) * 100.0),
+            _ => None,
+        };
+        let aging = growth.map_or(100.0, |growth| 100.0 - 0.2 * growth.max(0.0));
+        let state_of_health = wear.min(aging).clamp(0.0, 100.0).round() as u8;
+        let overheated = self.max_temperature_c.is_some_and(|max| max > self.config.max_temperature_c);
+        let grade = if overheated {
+            BatteryGrade::Retire
+        } else {
+            BatteryGrade::from_state_of_health(state_of_health)
+        };
+        BatteryHealthReport {
+            pack_id: self.config.pack_id.clone(),
+            cycles,
+            state_of_health,
+            grade,
+            resistance_mohm: self.resistance_mohm,
+            resistance_growth_percent: growth,
+            max_sag_v: self.max_sag_v,
+            temperature_c: self.temperature_c,
+            overheated,
+        }
+    }
+
+    /// Report the pack's health into the drone, logging when its grade drops
+    pub fn update(&mut self, drone: &mut DroneState) -> BatteryHealthReport {
+        let report = self.report();
+        if self.grade.is_some_and(|grade| report.grade < grade) {
+            tracing::warn!("🔋 Battery '{}' health dropped to {} ({}%)", report.pack_id, report.grade, report.state_of_health);
+            drone.log_event(
+                EventType::BatteryDegraded,
+                format!("Battery '{}' health dropped to {} ({}%)", report.pack_id, report.grade, report.state_of_health),
+                describe(&report),
+            );
+        }
+        self.grade = Some(report.grade);
+        drone.report_battery_health(report.state_of_health);
+        report
+    }
+}
+
+fn describe(report: &BatteryHealthReport) -> Vec<String> {
+    let mut details = vec![format!("{:.1} charge cycles", report.cycles)];
+    if let (Some(resistance), Some(growth)) = (report.resistance_mohm, report.resistance_growth_percent) {
+        details.push(format!("internal resistance {:.1} mΩ ({:+.0}% since new)", resistance, growth));
+    }
+    if let Some(sag) = report.max_sag_v {
+        details.push(format!("{:.2} V sag under load", sag));
+    }
+    if report.overheated {
+        details.push(format!("overheated, {:.0} °C", report.temperature_c.unwrap_or_default()));
+    }
+    details
+}
+
+/// Follow the flight controller's battery readings until the drone is gone
+pub async fn follow(battery: Arc<Mutex<BatteryHealth>>, drone: Arc<RwLock<DroneState>>) -> ModuleResult {
+    let mut telemetry = drone.read().await.subscribe_telemetry();
+    loop {
+        let flight = match telemetry.recv().await {
+            Ok(TelemetryMessage::Flight(flight)) => flight,
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return Ok(()),
+        };
+        let Some(voltage_v) = flight.battery_voltage else {
+            continue;
+        };
+        let sample = BatterySample {
+            voltage_v,
+            current_a: flight.battery_current_a,
+            temperature_c: None,
+            level: flight.battery_level,
+            timestamp: flight.timestamp,
+        };
+        let mut drone = drone.write().await;
+        let mut battery = battery.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        battery.observe(&sample);
+        battery.update(&mut drone);
+    }
+}
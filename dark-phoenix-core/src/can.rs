@@ -0,0 +1,426 @@
+//! Actuator commands over CAN (`socketcan` feature)
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, watch};
+use tracing::{debug, info, warn};
+
+const COMMAND_BASE: u16 = 0x200;
+const REPLY_BASE: u16 = 0x280;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CanConfig {
+    pub interface: String,
+    /// How long a node has to answer a command
+    pub command_timeout_ms: u64,
+    /// Further attempts after a command goes unanswered
+    pub retries: u32,
+    pub reopen_ms: u64,
+    pub max_reopen_ms: u64,
+    /// Node ids of the actuator controllers (absent = not on the bus)
+    pub valve_node: Option<u8>,
+    pub nozzle_node: Option<u8>,
+    pub siren_node: Option<u8>,
+}
+
+impl Default for CanConfig {
+    fn default() -> Self {
+        Self {
+            interface: "can0".to_string(),
+            command_timeout_ms: 100,
+            retries: 2,
+            reopen_ms: 500,
+            max_reopen_ms: 10_000,
+            valve_node: None,
+            nozzle_node: None,
+            siren_node: None,
+        }
+    }
+}
+
+impl CanConfig {
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.interface.is_empty() {
+            problems.push("can.interface is empty".to_string());
+        }
+        if self.command_timeout_ms == 0 {
+            problems.push("can.command_timeout_ms must be positive".to_string());
+        }
+        let nodes = [("valve_node", self.valve_node), ("nozzle_node", self.nozzle_node), ("siren_node", self.siren_node)];
+        for (index, (name, node)) in nodes.iter().enumerate() {
+            let Some(node) = node else { continue };
+            if *node > 0x7F {
+                problems.push(format!("can.{} {:#x} must be below 0x80", name, node));
+            }
+            if let Some((other, _)) = nodes[..index].iter().find(|(_, taken)| *taken == Some(*node)) {
+                problems.push(format!("can.{} and can.{} are both node {:#x}", other, name, node));
+            }
+        }
+        problems
+    }
+}
+
+/// What an actuator controller is told to do
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActuatorCommand {
+    ValveOpen,
+    ValveClose,
+    /// Answered with the extinguisher pressure
+    ReadPressure,
+    NozzleDeploy,
+    NozzleRetract,
+    /// Degrees right of and above the nozzle's centre
+    NozzleAim { pan_deg: f32, tilt_deg: f32 },
+    NozzleEmergency,
+    /// Pitch and level (0.0-1.0); never acknowledged
+    SirenTone { frequency_hz: f32, level: f32 },
+}
+
+impl ActuatorCommand {
+    pub fn opcode(&self) -> u8 {
+        match self {
+            ActuatorCommand::ValveOpen => 0x01,
+            ActuatorCommand::ValveClose => 0x02,
+            ActuatorCommand::ReadPressure => 0x03,
+            ActuatorCommand::NozzleDeploy => 0x10,
+            ActuatorCommand::NozzleRetract => 0x11,
+            ActuatorCommand::NozzleAim { .. } => 0x12,
+            ActuatorCommand::NozzleEmergency => 0x13,
+            ActuatorCommand::SirenTone { .. } => 0x20,
+        }
+    }
+
+    /// Whether the node answers it
+    pub fn acknowledged(&self) -> bool {
+        !matches!(self, ActuatorCommand::SirenTone { .. })
+    }
+
+    /// Frame data for sequence number `sequence`
+    pub fn encode(&self, sequence: u8) -> Vec<u8> {
+        let mut data = vec![self.opcode(), sequence];
+        let centidegrees = |deg: f32| ((deg * 100.0).round().clamp(i16::MIN as f32, i16::MAX as f32) as i16).to_le_bytes();
+        match *self {
+            ActuatorCommand::NozzleAim { pan_deg, tilt_deg } => {
+                data.extend_from_slice(&centidegrees(pan_deg));
+                data.extend_from_slice(&centidegrees(tilt_deg));
+            },
+            ActuatorCommand::SirenTone { frequency_hz, level } => {
+                data.extend_from_slice(&(frequency_hz.round().clamp(0.0, u16::MAX as f32) as u16).to_le_bytes());
+                data.push((level.clamp(0.0, 1.0) * 255.0).round() as u8);
+            },
+            _ => {},
+        }
+        data
+    }
+}
+
+/// A node's answer to a command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActuatorReply {
+    pub node: u8,
+    pub opcode: u8,
+    pub sequence: u8,
+    /// 0 when the command was carried out, otherwise the node's fault code
+    pub status: u8,
+    pub payload: Vec<u8>,
+}
+
+impl ActuatorReply {
+    pub fn decode(id: u16, data: &[u8]) -> Option<Self> {
+        if !(REPLY_BASE..REPLY_BASE + 0x80).contains(&id) || data.len() < 3 {
+            return None;
+        }
+        Some(Self {
+            node: (id - REPLY_BASE) as u8,
+            opcode: data[0],
+            sequence: data[1],
+            status: data[2],
+            payload: data[3..].to_vec(),
+        })
+    }
+
+    /// Pressure in psi, from a `ReadPressure` answer
+    pub fn pressure_psi(&self) -> Option<f32> {
+        let bytes = self.payload.get(..2)?;
+        Some(u16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 10.0)
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum CanError {
+    #[error("CAN interface {interface}: {reason}")]
+    Open { interface: String, reason: String },
+    #[error("CAN bus unavailable ({0:?})")]
+    Unavailable(CanState),
+    #[error("CAN bus went off while waiting for node {0:#x}")]
+    BusOff(u8),
+    #[error("node {node:#x} did not answer opcode {opcode:#04x} after {attempts} attempts")]
+    Timeout { node: u8, opcode: u8, attempts: u32 },
+    #[error("node {node:#x} refused opcode {opcode:#04x} with status {status}")]
+    Rejected { node: u8, opcode: u8, status: u8 },
+    #[error("CAN bus task has stopped")]
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CanState {
+    Connecting,
+    Up,
+    /// The controller went bus-off and is being restarted
+    BusOff,
+    /// The interface could not be opened
+    Down,
+}
+
+/// A frame as the bus task sees it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkFrame {
+    Data { id: u16, data: Vec<u8> },
+    BusOff,
+    /// Any other error the controller reports
+    Error(String),
+}
+
+/// A CAN adapter: SocketCAN, or anything else that moves standard frames
+#[async_trait]
+pub trait CanLink: Send {
+    async fn send(&mut self, id: u16, data: &[u8]) -> std::io::Result<()>;
+
+    async fn recv(&mut self) -> std::io::Result<LinkFrame>;
+
+    /// Bring the controller back after bus-off, where the adapter can
+    fn restart(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+struct Outgoing {
+    node: u8,
+    command: ActuatorCommand,
+    reply: Option<oneshot::Sender<Result<ActuatorReply, CanError>>>,
+}
+
+/// Handle on the bus task; clones share one socket
+#[derive(Clone)]
+pub struct CanBus {
+    commands: mpsc::Sender<Outgoing>,
+    state: watch::Receiver<CanState>,
+    timeout: Duration,
+    retries: u32,
+}
+
+impl CanBus {
+    /// Open `config.interface` through SocketCAN
+    pub fn start(config: CanConfig) -> Self {
+        Self::start_with(config, SocketCanLink::open)
+    }
+
+    /// Run the bus over links made by `open`, which is called again after
+    /// every failure
+    pub fn start_with<L, F>(config: CanConfig, open: F) -> Self
+    where
+        L: CanLink + 'static,
+        F: FnMut(&str) -> Result<L, CanError> + Send + 'static,
+    {
+        let (commands, outgoing) = mpsc::channel(64);
+        let (state_tx, state) = watch::channel(CanState::Connecting);
+        let bus = Self {
+            commands,
+            state,
+            timeout: Duration::from_millis(config.command_timeout_ms.max(1)),
+            retries: config.retries,
+        };
+        crate::runtime::spawn(serve(config, open, outgoing, state_tx));
+        bus
+    }
+
+    pub fn state(&self) -> CanState {
+        *self.state.borrow()
+    }
+
+    /// Send `command` to `node` and wait for its answer, retrying on silence
+    pub async fn request(&self, node: u8, command: ActuatorCommand) -> Result<ActuatorReply, CanError> {
+        let attempts = self.retries + 1;
+        for attempt in 1..=attempts {
+            match self.state() {
+                CanState::Up => {},
+                state => return Err(CanError::Unavailable(state)),
+            }
+            let (reply, answer) = oneshot::channel();
+            self.commands
+                .send(Outgoing { node, command, reply: Some(reply) })
+                .await
+                .map_err(|_| CanError::Closed)?;
+            match crate::runtime::timeout(self.timeout, answer).await {
+                Ok(Ok(Ok(reply))) if reply.status == 0 => return Ok(reply),
+                Ok(Ok(Ok(reply))) => {
+                    return Err(CanError::Rejected { node, opcode: reply.opcode, status: reply.status })
+                },
+                Ok(Ok(Err(e))) => return Err(e),
+                Ok(Err(_)) => return Err(CanError::Closed),
+                Err(_) => debug!("CAN node {:#x} silent on opcode {:#04x} (attempt {}/{})", node, command.opcode(), attempt, attempts),
+            }
+        }
+        Err(CanError::Timeout { node, opcode: command.opcode(), attempts })
+    }
+
+    /// Queue `command` without waiting; dropped while the bus is down or
+    /// the queue is full, as a stale siren tone is worthless
+    pub fn send(&self, node: u8, command: ActuatorCommand) -> Result<(), CanError> {
+        match self.state() {
+            CanState::Up => {},
+            state => return Err(CanError::Unavailable(state)),
+        }
+        match self.commands.try_send(Outgoing { node, command, reply: None }) {
+            Ok(()) | Err(mpsc::error::TrySendError::Full(_)) => Ok(()),
+            Err(mpsc::error::TrySendError::Closed(_)) => Err(CanError::Closed),
+        }
+    }
+}
+
+type Pending = HashMap<(u8, u8), oneshot::Sender<Result<ActuatorReply, CanError>>>;
+
+async fn serve<L, F>(config: CanConfig, mut open: F, mut outgoing: mpsc::Receiver<Outgoing>, state: watch::Sender<CanState>)
+where
+    L: CanLink,
+    F: FnMut(&str) -> Result<L, CanError>,
+{
+    let max_backoff = Duration::from_millis(config.max_reopen_ms.max(config.reopen_ms));
+    let mut backoff = Duration::from_millis(config.reopen_ms);
+    let mut sequence = 0u8;
+    loop {
+        let mut link = match open(&config.interface) {
+            Ok(link) => link,
+            Err(e) => {
+                warn!("🚌 {}; retrying in {:?}", e, backoff);
+                state.send_replace(CanState::Down);
+                if !wait_for_reopen(&mut outgoing, backoff, CanState::Down).await {
+                    return;
+                }
+                backoff = (backoff * 2).min(max_backoff);
+                continue;
+            },
+        };
+        info!("🚌 CAN bus up on {}", config.interface);
+        state.send_replace(CanState::Up);
+        backoff = Duration::from_millis(config.reopen_ms);
+
+        let mut pending = Pending::new();
+        let bus_off = loop {
+            tokio::select! {
+                command = outgoing.recv() => {
+                    let Some(Outgoing { node, command, reply }) = command else { return };
+                    sequence = sequence.wrapping_add(1);
+                    if let Err(e) = link.send(COMMAND_BASE + node as u16, &command.encode(sequence)).await {
+                        warn!("🚌 CAN send to node {:#x} failed: {}", node, e);
+                        break false;
+                    }
+                    if let (Some(reply), true) = (reply, command.acknowledged()) {
+                        pending.retain(|_, waiting| !waiting.is_closed());
+                        pending.insert((node, sequence), reply);
+                    }
+                },
+                frame = link.recv() => match frame {
+                    Ok(LinkFrame::Data { id, data }) => {
+                        let Some(reply) = ActuatorReply::decode(id, &data) else { continue };
+                        if let Some(waiting) = pending.remove(&(reply.node, reply.sequence)) {
+                            let _ = waiting.send(Ok(reply));
+                        }
+                    },
+                    Ok(LinkFrame::BusOff) => break true,
+                    Ok(LinkFrame::Error(e)) => debug!("CAN controller error: {}", e),
+                    Err(e) => {
+                        warn!("🚌 CAN receive failed: {}", e);
+                        break false;
+                    },
+                },
+            }
+        };
+
+        let down = if bus_off { CanState::BusOff } else { CanState::Down };
+        state.send_replace(down);
+        for ((node, _), waiting) in pending.drain() {
+            let _ = waiting.send(Err(if bus_off { CanError::BusOff(node) } else { CanError::Unavailable(down) }));
+        }
+        if bus_off {
+            warn!("🚌 CAN bus off on {}; restarting in {:?}", config.interface, backoff);
+            if let Err(e) = link.restart() {
+                debug!("CAN restart left to the kernel: {}", e);
+            }
+        }
+        drop(link);
+        if !wait_for_reopen(&mut outgoing, backoff, down).await {
+            return;
+        }
+        backoff = (backoff * 2).min(max_backoff);
+    }
+}
+
+/// Sleep out `backoff`, failing commands that arrive meanwhile; false once
+/// every handle is gone
+async fn wait_for_reopen(outgoing: &mut mpsc::Receiver<Outgoing>, backoff: Duration, state: CanState) -> bool {
+    let sleep = crate::runtime::sleep(backoff);
+    tokio::pin!(sleep);
+    loop {
+        tokio::select! {
+            _ = &mut sleep => return true,
+            command = outgoing.recv() => match command {
+                Some(Outgoing { reply: Some(reply), .. }) => {
+                    let _ = reply.send(Err(CanError::Unavailable(state)));
+                },
+                Some(_) => {},
+                None => return false,
+            },
+        }
+    }
+}
+
+/// A SocketCAN interface such as `can0`
+pub struct SocketCanLink {
+    socket: socketcan::tokio::CanSocket,
+    interface: String,
+}
+
+impl SocketCanLink {
+    pub fn open(interface: &str) -> Result<Self, CanError> {
+        use socketcan::SocketOptions;
+        let open_error = |e: std::io::Error| CanError::Open { interface: interface.to_string(), reason: e.to_string() };
+        let socket = socketcan::tokio::CanSocket::open(interface).map_err(open_error)?;
+        socket.set_error_filter_accept_all().map_err(open_error)?;
+        Ok(Self { socket, interface: interface.to_string() })
+    }
+}
+
+#[async_trait]
+impl CanLink for SocketCanLink {
+    async fn send(&mut self, id: u16, data: &[u8]) -> std::io::Result<()> {
+        use socketcan::EmbeddedFrame;
+        let invalid = || std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("cannot frame id {:#x} with {} bytes", id, data.len()));
+        let id = socketcan::StandardId::new(id).ok_or_else(invalid)?;
+        let frame = socketcan::CanFrame::new(id, data).ok_or_else(invalid)?;
+        self.socket.write_frame(frame).await
+    }
+
+    async fn recv(&mut self) -> std::io::Result<LinkFrame> {
+        use socketcan::{EmbeddedFrame, Frame};
+        Ok(match self.socket.read_frame().await? {
+            socketcan::CanFrame::Data(frame) if !frame.is_extended() => LinkFrame::Data { id: frame.raw_id() as u16, data: frame.data().to_vec() },
+            socketcan::CanFrame::Error(frame) => {
+                let error = frame.into_error();
+                if error.is_bus_off() { LinkFrame::BusOff } else { LinkFrame::Error(error.to_string()) }
+            },
+            _ => LinkFrame::Error("ignored frame".to_string()),
+        })
+    }
+
+    fn restart(&mut self) -> std::io::Result<()> {
+        socketcan::CanInterface::open(&self.interface)
+            .and_then(|interface| interface.restart())
+            .map_err(|e| std::io::Error::other(e.to_string()))
+    }
+}
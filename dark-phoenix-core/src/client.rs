@@ -0,0 +1,202 @@
+//! Client for the control API of a running instance
+
+use crate::{CommandEnvelope, MissionEvent, SystemHealth, TelemetryMessage, ThreatLevel};
+use ed25519_dalek::SigningKey;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("cannot reach Dark Phoenix at {api} ({reason}); is `phoenix run` up with the API enabled?")]
+    Unreachable { api: String, reason: String },
+    /// The API refused the request; `message` is its own explanation
+    #[error("{message} ({status})")]
+    Api { status: u16, message: String },
+    #[error("unexpected response: {0}")]
+    Response(#[from] reqwest::Error),
+    #[error("bad JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{}: expected 64 hex digits", .0.display())]
+    SigningKey(PathBuf),
+    #[error("cannot read {}: {source}", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[cfg(feature = "ws-client")]
+    #[error("telemetry stream failed: {0}")]
+    Stream(Box<tokio_tungstenite::tungstenite::Error>),
+}
+
+#[cfg(feature = "ws-client")]
+impl From<tokio_tungstenite::tungstenite::Error> for ClientError {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        ClientError::Stream(Box::new(e))
+    }
+}
+
+/// Client for the control API at one base URL
+#[derive(Clone)]
+pub struct ApiClient {
+    base: String,
+    token: Option<String>,
+    /// Key id and key every request is signed with
+    signer: Option<(String, SigningKey)>,
+    http: reqwest::Client,
+}
+
+impl ApiClient {
+    pub fn new(base: &str, token: Option<&str>, signer: Option<(String, SigningKey)>) -> Self {
+        Self {
+            base: base.trim_end_matches('/').to_string(),
+            token: token.map(str::to_string),
+            signer,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    pub async fn status(&self) -> Result<TelemetryMessage, ClientError> {
+        self.get("/status").await
+    }
+
+    pub async fn health(&self) -> Result<SystemHealth, ClientError> {
+        self.get("/health").await
+    }
+
+    /// The newest `limit` mission events
+    pub async fn events(&self, limit: usize) -> Result<Vec<MissionEvent>, ClientError> {
+        self.get(&format!("/events?limit={}", limit)).await
+    }
+
+    /// Operator override of the threat level; the status after it
+    pub async fn set_threat_level(&self, level: ThreatLevel, reason: &str) -> Result<TelemetryMessage, ClientError> {
+        self.post_json("/threat-level", &serde_json::json!({ "level": level, "reason": reason })).await
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        let response = self.send(reqwest::Method::GET, path, Vec::new()).await?;
+        Ok(response.json().await?)
+    }
+
+    /// The response body as it is, e.g. a rendered report
+    pub async fn get_bytes(&self, path: &str) -> Result<Vec<u8>, ClientError> {
+        let response = self.send(reqwest::Method::GET, path, Vec::new()).await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    pub async fn post(&self, path: &str) -> Result<(), ClientError> {
+        self.send(reqwest::Method::POST, path, Vec::new()).await?;
+        Ok(())
+    }
+
+    pub async fn post_json<B: Serialize, T: DeserializeOwned>(&self, path: &str, body: &B) -> Result<T, ClientError> {
+        let response = self.send(reqwest::Method::POST, path, serde_json::to_vec(body)?).await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn delete(&self, path: &str) -> Result<(), ClientError> {
+        self.send(reqwest::Method::DELETE, path, Vec::new()).await?;
+        Ok(())
+    }
+
+    /// Live telemetry plus periodic status snapshots from `/ws`
+    #[cfg(feature = "ws-client")]
+    pub async fn subscribe(&self) -> Result<TelemetryStream, ClientError> {
+        use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+        let url = match self.base.split_once("://") {
+            Some(("https", rest)) => format!("wss://{}/ws", rest),
+            Some((_, rest)) => format!("ws://{}/ws", rest),
+            None => format!("ws://{}/ws", self.base),
+        };
+        let mut request = url.as_str().into_client_request()?;
+        for (name, value) in self.auth_headers("GET", "/ws", &[]) {
+            let Ok(value) = value.parse() else { continue };
+            request.headers_mut().insert(name, value);
+        }
+        let (socket, _) = tokio_tungstenite::connect_async(request).await.map_err(|e| ClientError::Unreachable {
+            api: self.base.clone(),
+            reason: e.to_string(),
+        })?;
+        Ok(TelemetryStream { socket })
+    }
+
+    /// Bearer token and envelope headers for a request
+    fn auth_headers(&self, method: &str, path: &str, body: &[u8]) -> Vec<(&'static str, String)> {
+        let mut headers = Vec::new();
+        if let Some(token) = &self.token {
+            headers.push(("authorization", format!("Bearer {}", token)));
+        }
+        if let Some((key_id, key)) = &self.signer {
+            let command = crate::envelope::http_command(method, path, body);
+            headers.extend(CommandEnvelope::sign(key_id, command, chrono::Utc::now(), key).headers());
+        }
+        headers
+    }
+
+    /// Errors carry the API's own message where there is one
+    async fn send(&self, method: reqwest::Method, path: &str, body: Vec<u8>) -> Result<reqwest::Response, ClientError> {
+        let mut request = self.http.request(method.clone(), format!("{}{}", self.base, path));
+        for (name, value) in self.auth_headers(method.as_str(), path, &body) {
+            request = request.header(name, value);
+        }
+        if !body.is_empty() {
+            request = request.header(reqwest::header::CONTENT_TYPE, "application/json").body(body);
+        }
+        let response = request.send().await.map_err(|e| ClientError::Unreachable {
+            api: self.base.clone(),
+            reason: e.to_string(),
+        })?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body: serde_json::Value = response.json().await.unwrap_or_default();
+        let message = body["error"].as_str().unwrap_or_else(|| status.canonical_reason().unwrap_or("request failed"));
+        Err(ClientError::Api {
+            status: status.as_u16(),
+            message: message.to_string(),
+        })
+    }
+}
+
+/// Telemetry from an open `/ws` connection
+#[cfg(feature = "ws-client")]
+pub struct TelemetryStream {
+    socket: tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
+}
+
+#[cfg(feature = "ws-client")]
+impl TelemetryStream {
+    /// The next message, or None once the drone closes the connection
+    pub async fn next(&mut self) -> Option<Result<TelemetryMessage, ClientError>> {
+        use futures_util::StreamExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        loop {
+            return match self.socket.next().await? {
+                Ok(Message::Text(text)) => Some(serde_json::from_str(&text).map_err(ClientError::from)),
+                Ok(Message::Close(_)) => None,
+                Ok(_) => continue,
+                Err(e) => Some(Err(e.into())),
+            };
+        }
+    }
+
+    pub async fn close(mut self) {
+        let _ = self.socket.close(None).await;
+    }
+}
+
+/// Read a key file written by `phoenix keys generate`
+pub fn read_signing_key(path: &Path) -> Result<SigningKey, ClientError> {
+    let text = std::fs::read_to_string(path).map_err(|source| ClientError::Io { path: path.to_path_buf(), source })?;
+    let bytes = hex::decode(text.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| ClientError::SigningKey(path.to_path_buf()))?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
@@ -0,0 +1,69 @@
+use crate::{ModuleResult, PanicOutcome, Position, Situation, ThreatLevel};
+use async_trait::async_trait;
+
+/// Commands the core cannot carry out itself, bridged by the integrator to
+/// the modules that own the hardware
+#[async_trait]
+pub trait ModuleControl: Send + Sync {
+    /// Bring deterrence up to match `level`
+    async fn activate_deterrence(&self, level: ThreatLevel) -> ModuleResult;
+
+    /// Bring deterrence up to `level`, worded for `situation`
+    async fn activate_deterrence_for(&self, level: ThreatLevel, situation: Situation) -> ModuleResult;
+
+    /// Silence deterrence, e.g. after a false alarm
+    async fn deactivate_deterrence(&self) -> ModuleResult;
+
+    /// Tell emergency contacts `summary` now, whatever the threat level
+    async fn notify_emergency_contacts(&self, summary: &str) -> ModuleResult;
+
+    async fn test_deterrence(&self) -> ModuleResult;
+
+    async fn test_fire_suppression(&self) -> ModuleResult;
+
+    async fn activate_fire_suppression(&self) -> ModuleResult;
+
+    /// Raise or lower the ballistic shield
+    async fn deploy_shield(&self, deploy: bool) -> ModuleResult;
+
+    /// Arm or disarm the response modules, e.g. from the dashboard
+    async fn set_armed(&self, armed: bool) -> ModuleResult;
+}
+
+/// Carry an accepted panic command out through the modules: deterrence for
+/// imminent danger and emergency contacts on a press, both stood down on a
+/// false alarm. Every step is tried; the first failure is returned
+pub async fn respond_to_panic(control: &dyn ModuleControl, outcome: &PanicOutcome) -> ModuleResult {
+    let results = match outcome {
+        PanicOutcome::Activated { device_id, .. } => [
+            control.activate_deterrence_for(ThreatLevel::Red, Situation::ImminentDanger).await,
+            control
+                .notify_emergency_contacts(&format!("Protectee pressed the panic button ({})", device_id))
+                .await,
+        ],
+        PanicOutcome::AlreadyActive { .. } => return Ok(()),
+        PanicOutcome::Cancelled { device_id, .. } => [
+            control.deactivate_deterrence().await,
+            control
+                .notify_emergency_contacts(&format!("FALSE ALARM: panic cancelled by {}, no assistance needed", device_id))
+                .await,
+        ],
+    };
+    results.into_iter().collect()
+}
+
+/// Commands to the flight controller; `emergency_landing` lands through it
+#[async_trait]
+pub trait FlightControl: Send + Sync {
+    /// Hold the current position
+    async fn loiter(&self) -> ModuleResult;
+
+    async fn return_to_home(&self) -> ModuleResult;
+
+    /// Fly to `target` (altitude above home) and hold there, facing
+    /// `heading_deg` when given
+    async fn fly_to(&self, target: &Position, heading_deg: Option<f64>) -> ModuleResult;
+
+    /// Descend and land where the drone is
+    async fn emergency_land(&self) -> ModuleResult;
+}
@@ -0,0 +1,144 @@
+//! Why the drone decided what it did
+
+use crate::ThreatLevel;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A number the decision was made from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceInput {
+    pub name: String,
+    pub value: f32,
+    /// Its share of a weighted score, when it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f32>,
+}
+
+/// One rule applied, and the level it left
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceStep {
+    /// What was checked, e.g. "fusion thresholds"
+    pub rule: String,
+    /// How it came out, in words
+    pub detail: String,
+    /// The value compared, and the threshold it was compared with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f32>,
+    pub level: ThreatLevel,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DecisionTrace {
+    pub inputs: Vec<TraceInput>,
+    /// In the order they were applied
+    pub steps: Vec<TraceStep>,
+}
+
+impl DecisionTrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty() && self.steps.is_empty()
+    }
+
+    pub fn input(&mut self, name: &str, value: f32, weight: Option<f32>) {
+        self.inputs.push(TraceInput {
+            name: name.to_string(),
+            value,
+            weight,
+        });
+    }
+
+    /// A rule that set the level without comparing a value
+    pub fn rule(&mut self, rule: &str, level: ThreatLevel, detail: impl Into<String>) {
+        self.steps.push(TraceStep {
+            rule: rule.to_string(),
+            detail: detail.into(),
+            value: None,
+            threshold: None,
+            level,
+        });
+    }
+
+    /// A rule that compared `value` with `threshold`
+    pub fn threshold(&mut self, rule: &str, level: ThreatLevel, value: f32, threshold: f32, detail: impl Into<String>) {
+        self.steps.push(TraceStep {
+            rule: rule.to_string(),
+            detail: detail.into(),
+            value: Some(value),
+            threshold: Some(threshold),
+            level,
+        });
+    }
+
+    /// The level the last step left
+    pub fn outcome(&self) -> Option<ThreatLevel> {
+        self.steps.last().map(|step| step.level)
+    }
+
+    /// The last step that changed the level, i.e. the one that decided it
+    pub fn decisive(&self) -> Option<&TraceStep> {
+        let mut decisive = None;
+        let mut previous = None;
+        for step in &self.steps {
+            if previous != Some(step.level) {
+                decisive = Some(step);
+            }
+            previous = Some(step.level);
+        }
+        decisive
+    }
+
+    /// This trace with `cause` ahead of it, e.g. the assessment behind a
+    /// threat level change
+    pub fn after(mut self, cause: &DecisionTrace) -> Self {
+        self.inputs.splice(0..0, cause.inputs.iter().cloned());
+        self.steps.splice(0..0, cause.steps.iter().cloned());
+        self
+    }
+
+    /// One line per input and step, for places that list text
+    pub fn lines(&self) -> Vec<String> {
+        let inputs = self.inputs.iter().map(|input| match input.weight {
+            Some(weight) => format!("{} {:.2} (weight {:.2})", input.name, input.value, weight),
+            None => format!("{} {:.2}", input.name, input.value),
+        });
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| format!("{} -> {}: {}", step.rule, step.level.as_str(), step.detail));
+        inputs.chain(steps).collect()
+    }
+}
+
+impl fmt::Display for DecisionTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "  (no decision trace recorded)");
+        }
+        if !self.inputs.is_empty() {
+            writeln!(f, "  Inputs:")?;
+            let width = self.inputs.iter().map(|input| input.name.len()).max().unwrap_or(0);
+            for input in &self.inputs {
+                write!(f, "    {:<width$}  {:>6.2}", input.name, input.value, width = width)?;
+                match input.weight {
+                    Some(weight) => writeln!(f, "  x weight {:.2}", weight)?,
+                    None => writeln!(f)?,
+                }
+            }
+        }
+        if !self.steps.is_empty() {
+            writeln!(f, "  Rules:")?;
+            let width = self.steps.iter().map(|step| step.rule.len()).max().unwrap_or(0);
+            for step in &self.steps {
+                writeln!(f, "    {:<width$}  {:<6}  {}", step.rule, step.level.as_str(), step.detail, width = width)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,786 @@
+//! Bandwidth-adaptive telemetry: keyframes plus binary deltas
+
+use crate::DroneState;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+use thiserror::Error;
+
+const MAGIC: &[u8; 2] = b"PD";
+const VERSION: u8 = 1;
+
+const KIND_KEYFRAME: u8 = 0;
+const KIND_DELTA: u8 = 1;
+
+const OP_SET: u8 = 0;
+const OP_ADD: u8 = 1;
+const OP_REMOVE: u8 = 2;
+
+const SEGMENT_KEY: u8 = 0;
+const SEGMENT_INDEX: u8 = 1;
+
+const VALUE_NULL: u8 = 0;
+const VALUE_FALSE: u8 = 1;
+const VALUE_TRUE: u8 = 2;
+const VALUE_INT: u8 = 3;
+const VALUE_UINT: u8 = 4;
+const VALUE_F32: u8 = 5;
+const VALUE_F64: u8 = 6;
+const VALUE_STRING: u8 = 7;
+const VALUE_EMPTY_ARRAY: u8 = 8;
+const VALUE_EMPTY_OBJECT: u8 = 9;
+
+/// One group of state fields sent together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeltaChannel {
+    pub name: String,
+    /// How often the channel is sent, if anything changed
+    pub interval_ms: u64,
+    /// Frames from one keyframe to the next
+    pub keyframe_every: u32,
+    /// Top-level `DroneState` fields, e.g. "position" (empty = all)
+    #[serde(default)]
+    pub fields: Vec<String>,
+}
+
+impl DeltaChannel {
+    fn new(name: &str, interval_ms: u64, keyframe_every: u32, fields: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            interval_ms,
+            keyframe_every,
+            fields: fields.iter().map(|field| field.to_string()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeltaConfig {
+    /// Fields no channel lists are not sent
+    pub channels: Vec<DeltaChannel>,
+    /// Cap on frame bytes per second across channels (absent = no cap)
+    pub max_bytes_per_sec: Option<u64>,
+}
+
+impl Default for DeltaConfig {
+    fn default() -> Self {
+        Self {
+            channels: vec![
+                DeltaChannel::new("position", 200, 50, &["position", "protectee", "last_update"]),
+                DeltaChannel::new("health", 1000, 30, &["threat", "system_health", "target_vitals", "active_modules", "geofence_status"]),
+                DeltaChannel::new("log", 5000, 12, &["id", "name", "mission_log", "audit_head"]),
+            ],
+            max_bytes_per_sec: None,
+        }
+    }
+}
+
+impl DeltaConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (i, channel) in self.channels.iter().enumerate() {
+            if channel.name.is_empty() || channel.name.len() > 255 {
+                problems.push(format!("delta channel {} needs a name of 1-255 bytes", i));
+            }
+            if self.channels[..i].iter().any(|other| other.name == channel.name) {
+                problems.push(format!("delta channel '{}' is listed twice", channel.name));
+            }
+            if channel.interval_ms == 0 {
+                problems.push(format!("delta channel '{}' interval_ms must be positive", channel.name));
+            }
+            if channel.keyframe_every == 0 {
+                problems.push(format!("delta channel '{}' keyframe_every must be positive", channel.name));
+            }
+        }
+        if self.max_bytes_per_sec == Some(0) {
+            problems.push("delta max_bytes_per_sec must be positive".to_string());
+        }
+        problems
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DeltaError {
+    #[error("state could not be encoded: {0}")]
+    Encode(#[from] serde_json::Error),
+    #[error("not a telemetry frame")]
+    NotAFrame,
+    #[error("unsupported frame version {0}")]
+    UnsupportedVersion(u8),
+    #[error("frame is truncated or malformed")]
+    Malformed,
+    #[error("delta {got} on channel '{channel}' does not follow frame {expected}; waiting for a keyframe")]
+    OutOfSync { channel: String, expected: u32, got: u32 },
+}
+
+/// One encoded frame, for sending on its channel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeltaFrame {
+    pub channel: String,
+    pub keyframe: bool,
+    pub seq: u32,
+    pub bytes: Vec<u8>,
+}
+
+/// What a decoded frame was
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedFrame {
+    pub channel: String,
+    pub keyframe: bool,
+    pub seq: u32,
+    pub sent: DateTime<Utc>,
+    /// Leaves set, added or removed
+    pub changes: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+type Path = Vec<Segment>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Op {
+    Set(usize, Value),
+    Add(Path, Value),
+    Remove(usize),
+}
+
+/// Leaves of one channel as last sent or received, by index
+#[derive(Debug, Clone, Default)]
+struct LeafTable {
+    paths: Vec<Path>,
+    /// Absent once removed; indices stay put until the next keyframe
+    values: Vec<Option<Value>>,
+    index: HashMap<Path, usize>,
+}
+
+impl LeafTable {
+    fn apply(&mut self, ops: Vec<Op>) -> Result<(), DeltaError> {
+        for op in ops {
+            match op {
+                Op::Set(index, value) => *self.values.get_mut(index).ok_or(DeltaError::Malformed)? = Some(value),
+                Op::Add(path, value) => match self.index.get(&path) {
+                    Some(&index) => self.values[index] = Some(value),
+                    None => {
+                        self.index.insert(path.clone(), self.paths.len());
+                        self.paths.push(path);
+                        self.values.push(Some(value));
+                    },
+                },
+                Op::Remove(index) => *self.values.get_mut(index).ok_or(DeltaError::Malformed)? = None,
+            }
+        }
+        Ok(())
+    }
+
+    /// Operations turning this table into `leaves`
+    fn diff(&self, leaves: &[(Path, Value)]) -> Vec<Op> {
+        let mut ops = Vec::new();
+        let mut present = vec![false; self.paths.len()];
+        for (path, value) in leaves {
+            match self.index.get(path) {
+                Some(&index) => {
+                    present[index] = true;
+                    if self.values[index].as_ref() != Some(value) {
+                        ops.push(Op::Set(index, value.clone()));
+                    }
+                },
+                None => ops.push(Op::Add(path.clone(), value.clone())),
+            }
+        }
+        for (index, present) in present.into_iter().enumerate() {
+            if !present && self.values[index].is_some() {
+                ops.push(Op::Remove(index));
+            }
+        }
+        ops
+    }
+
+    fn to_value(&self) -> Value {
+        let mut root = Value::Object(Map::new());
+        for (path, value) in self.paths.iter().zip(&self.values) {
+            if let Some(value) = value {
+                insert(&mut root, path, value.clone());
+            }
+        }
+        root
+    }
+}
+
+#[derive(Debug)]
+struct EncoderChannel {
+    config: DeltaChannel,
+    table: LeafTable,
+    seq: u32,
+    since_keyframe: u32,
+    last_sent: Option<DateTime<Utc>>,
+}
+
+/// Turns the drone state into frames, each channel at its own rate
+#[derive(Debug)]
+pub struct DeltaEncoder {
+    channels: Vec<EncoderChannel>,
+    max_bytes_per_sec: Option<u64>,
+    /// Start of the current budget second and the bytes sent in it
+    window: Option<(DateTime<Utc>, u64)>,
+}
+
+impl DeltaEncoder {
+    pub fn new(config: DeltaConfig) -> Self {
+        Self {
+            channels: config
+                .channels
+                .into_iter()
+                .map(|config| EncoderChannel {
+                    config,
+                    table: LeafTable::default(),
+                    seq: 0,
+                    since_keyframe: 0,
+                    last_sent: None,
+                })
+                .collect(),
+            max_bytes_per_sec: config.max_bytes_per_sec,
+            window: None,
+        }
+    }
+
+    /// Shortest channel interval, for how often to call [`Self::encode`]
+    pub fn tick_interval(&self) -> std::time::Duration {
+        let ms = self.channels.iter().map(|channel| channel.config.interval_ms).min().unwrap_or(1000);
+        std::time::Duration::from_millis(ms.max(1))
+    }
+
+    /// Start every channel over with a keyframe, e.g. after reconnecting
+    pub fn reset(&mut self) {
+        for channel in &mut self.channels {
+            channel.table = LeafTable::default();
+            channel.last_sent = None;
+        }
+    }
+
+    /// Frames for the channels due at `now`
+    pub fn encode(&mut self, drone: &DroneState, now: DateTime<Utc>) -> Result<Vec<DeltaFrame>, DeltaError> {
+        let state = serde_json::to_value(drone)?;
+        let mut frames = Vec::new();
+        // Longest waiting first, so a busy channel cannot starve the rest of the budget
+        let mut order: Vec<usize> = (0..self.channels.len()).collect();
+        order.sort_by_key(|&i| self.channels[i].last_sent);
+        for i in order {
+            let channel = &mut self.channels[i];
+            let interval = Duration::milliseconds(channel.config.interval_ms as i64);
+            if channel.last_sent.is_some_and(|last| now - last < interval) {
+                continue;
+            }
+            let mut leaves = Vec::new();
+            flatten(&select(&state, &channel.config.fields), &mut Vec::new(), &mut leaves);
+
+            let keyframe = channel.table.paths.is_empty() || channel.since_keyframe + 1 >= channel.config.keyframe_every;
+            let ops = if keyframe {
+                leaves.iter().map(|(path, value)| Op::Add(path.clone(), value.clone())).collect()
+            } else {
+                channel.table.diff(&leaves)
+            };
+            if ops.is_empty() {
+                channel.last_sent = Some(now);
+                continue;
+            }
+            let seq = channel.seq.wrapping_add(1);
+            let bytes = write_frame(&channel.config.name, keyframe, seq, now, &ops);
+
+            if let Some(budget) = self.max_bytes_per_sec {
+                let (start, used) = self.window.get_or_insert((now, 0));
+                if now - *start >= Duration::seconds(1) {
+                    (*start, *used) = (now, 0);
+                }
+                // A frame bigger than the whole budget still goes out on its own
+                if *used > 0 && *used + bytes.len() as u64 > budget {
+                    continue;
+                }
+                *used += bytes.len() as u64;
+            }
+
+            if keyframe {
+                channel.table = LeafTable::default();
+                channel.since_keyframe = 0;
+            } else {
+                channel.since_keyframe += 1;
+            }
+            channel.table.apply(ops)?;
+            channel.seq = seq;
+            channel.last_sent = Some(now);
+            frames.push(DeltaFrame {
+                channel: channel.config.name.clone(),
+                keyframe,
+                seq,
+                bytes,
+            });
+        }
+        Ok(frames)
+    }
+}
+
+#[derive(Debug, Default)]
+struct DecoderChannel {
+    table: LeafTable,
+    seq: u32,
+    /// A keyframe has been seen and nothing since was missed
+    synced: bool,
+}
+
+/// Rebuilds the state from frames, for the ground station
+#[derive(Debug, Default)]
+pub struct DeltaDecoder {
+    channels: HashMap<String, DecoderChannel>,
+}
+
+impl DeltaDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a frame to its channel
+    pub fn decode(&mut self, frame: &[u8]) -> Result<DecodedFrame, DeltaError> {
+        let mut reader = Reader(frame);
+        if reader.take(2)? != MAGIC {
+            return Err(DeltaError::NotAFrame);
+        }
+        let version = reader.byte()?;
+        if version != VERSION {
+            return Err(DeltaError::UnsupportedVersion(version));
+        }
+        let keyframe = match reader.byte()? {
+            KIND_KEYFRAME => true,
+            KIND_DELTA => false,
+            _ => return Err(DeltaError::Malformed),
+        };
+        let name = reader.string()?;
+        let seq = u32::try_from(reader.varint()?).map_err(|_| DeltaError::Malformed)?;
+        let sent = Utc.timestamp_millis_opt(unzigzag(reader.varint()?)).single().ok_or(DeltaError::Malformed)?;
+        let count = reader.varint()? as usize;
+        let mut ops = Vec::with_capacity(count.min(frame.len()));
+        for _ in 0..count {
+            ops.push(match reader.byte()? {
+                OP_SET => Op::Set(reader.varint()? as usize, reader.value()?),
+                OP_ADD => Op::Add(reader.path()?, reader.value()?),
+                OP_REMOVE => Op::Remove(reader.varint()? as usize),
+                _ => return Err(DeltaError::Malformed),
+            });
+        }
+        if !reader.0.is_empty() {
+            return Err(DeltaError::Malformed);
+        }
+
+        let channel = self.channels.entry(name.clone()).or_default();
+        if keyframe {
+            channel.table = LeafTable::default();
+        } else if !channel.synced || seq != channel.seq.wrapping_add(1) {
+            channel.synced = false;
+            return Err(DeltaError::OutOfSync {
+                channel: name,
+                expected: channel.seq.wrapping_add(1),
+                got: seq,
+            });
+        }
+        // A frame that fails half way leaves the channel waiting for a keyframe
+        channel.synced = false;
+        channel.table.apply(ops.clone())?;
+        channel.seq = seq;
+        channel.synced = true;
+        Ok(DecodedFrame {
+            channel: name,
+            keyframe,
+            seq,
+            sent,
+            changes: ops.len(),
+        })
+    }
+
+    /// The fields of one channel as last decoded
+    pub fn channel(&self, name: &str) -> Option<Value> {
+        self.channels.get(name).map(|channel| channel.table.to_value())
+    }
+
+    /// Every channel's fields together
+    pub fn state(&self) -> Value {
+        let mut state = Map::new();
+        for channel in self.channels.values() {
+            if let Value::Object(fields) = channel.table.to_value() {
+                state.extend(fields);
+            }
+        }
+        Value::Object(state)
+    }
+
+    /// The state as a type, e.g. `DroneState` once every channel has had a
+    /// keyframe
+    pub fn state_as<T: DeserializeOwned>(&self) -> Result<T, DeltaError> {
+        Ok(serde_json::from_value(self.state())?)
+    }
+}
+
+/// The listed top-level fields of the state
+fn select(state: &Value, fields: &[String]) -> Value {
+    match state {
+        Value::Object(all) if !fields.is_empty() => {
+            Value::Object(all.iter().filter(|(key, _)| fields.contains(key)).map(|(key, value)| (key.clone(), value.clone())).collect())
+        },
+        _ => state.clone(),
+    }
+}
+
+/// Every leaf under `value`; empty arrays and objects count as leaves so
+/// they survive the round trip
+fn flatten(value: &Value, path: &mut Path, out: &mut Vec<(Path, Value)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                path.push(Segment::Key(key.clone()));
+                flatten(value, path, out);
+                path.pop();
+            }
+        },
+        Value::Array(items) if !items.is_empty() => {
+            for (index, value) in items.iter().enumerate() {
+                path.push(Segment::Index(index));
+                flatten(value, path, out);
+                path.pop();
+            }
+        },
+        leaf => out.push((path.clone(), leaf.clone())),
+    }
+}
+
+fn insert(root: &mut Value, path: &[Segment], leaf: Value) {
+    let Some((segment, rest)) = path.split_first() else {
+        // An empty container leaf never replaces what other leaves built
+        let empty_container = matches!(&leaf, Value::Array(items) if items.is_empty()) || matches!(&leaf, Value::Object(map) if map.is_empty());
+        if !(empty_container && (root.is_array() || root.is_object())) {
+            *root = leaf;
+        }
+        return;
+    };
+    let child = match segment {
+        Segment::Key(key) => {
+            if !root.is_object() {
+                *root = Value::Object(Map::new());
+            }
+            root.as_object_mut().expect("just made an object").entry(key.clone()).or_insert(Value::Null)
+        },
+        Segment::Index(index) => {
+            if !root.is_array() {
+                *root = Value::Array(Vec::new());
+            }
+            let items = root.as_array_mut().expect("just made an array");
+            if items.len() <= *index {
+                items.resize(index + 1, Value::Null);
+            }
+            &mut items[*index]
+        },
+    };
+    insert(child, rest, leaf);
+}
+
+fn write_frame(channel: &str, keyframe: bool, seq: u32, sent: DateTime<Utc>, ops: &[Op]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(64);
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    out.push(if keyframe { KIND_KEYFRAME } else { KIND_DELTA });
+    write_str(&mut out, channel);
+    write_varint(&mut out, u64::from(seq));
+    write_varint(&mut out, zigzag(sent.timestamp_millis()));
+    write_varint(&mut out, ops.len() as u64);
+    for op in ops {
+        match op {
+            Op::Set(index, value) => {
+                out.push(OP_SET);
+                write_varint(&mut out, *index as u64);
+                write_value(&mut out, value);
+            },
+            Op::Add(path, value) => {
+                out.push(OP_ADD);
+                write_varint(&mut out, path.len() as u64);
+                for segment in path {
+                    match segment {
+                        Segment::Key(key) => {
+                            out.push(SEGMENT_KEY);
+                            write_str(&mut out, key);
+                        },
+                        Segment::Index(index) => {
+                            out.push(SEGMENT_INDEX);
+                            write_varint(&mut out, *index as u64);
+                        },
+                    }
+                }
+                write_value(&mut out, value);
+            },
+            Op::Remove(index) => {
+                out.push(OP_REMOVE);
+                write_varint(&mut out, *index as u64);
+            },
+        }
+    }
+    out
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => out.push(VALUE_NULL),
+        Value::Bool(false) => out.push(VALUE_FALSE),
+        Value::Bool(true) => out.push(VALUE_TRUE),
+        Value::Number(number) => {
+            if let Some(int) = number.as_i64() {
+                out.push(VALUE_INT);
+                write_varint(out, zigzag(int));
+            } else if let Some(uint) = number.as_u64() {
+                out.push(VALUE_UINT);
+                write_varint(out, uint);
+            } else {
+                let float = number.as_f64().unwrap_or_default();
+                if f64::from(float as f32) == float {
+                    out.push(VALUE_F32);
+                    out.extend_from_slice(&(float as f32).to_le_bytes());
+                } else {
+                    out.push(VALUE_F64);
+                    out.extend_from_slice(&float.to_le_bytes());
+                }
+            }
+        },
+        Value::String(string) => {
+            out.push(VALUE_STRING);
+            write_str(out, string);
+        },
+        Value::Array(_) => out.push(VALUE_EMPTY_ARRAY),
+        Value::Object(_) => out.push(VALUE_EMPTY_OBJECT),
+    }
+}
+
+fn write_str(out: &mut Vec<u8>, string: &str) {
+    write_varint(out, string.len() as u64);
+    out.extend_from_slice(string.as_bytes());
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], DeltaError> {
+        if self.0.len() < count {
+            return Err(DeltaError::Malformed);
+        }
+        let (taken, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, DeltaError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, DeltaError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DeltaError::Malformed)
+    }
+
+    fn string(&mut self) -> Result<String, DeltaError> {
+        let length = self.varint()? as usize;
+        String::from_utf8(self.take(length)?.to_vec()).map_err(|_| DeltaError::Malformed)
+    }
+
+    fn path(&mut self) -> Result<Path, DeltaError> {
+        let length = self.varint()? as usize;
+        let mut path = Vec::with_capacity(length.min(self.0.len()));
+        for _ in 0..length {
+            path.push(match self.byte()? {
+                SEGMENT_KEY => Segment::Key(self.string()?),
+                SEGMENT_INDEX => Segment::Index(self.varint()? as usize),
+                _ => return Err(DeltaError::Malformed),
+            });
+        }
+        Ok(path)
+    }
+
+    fn value(&mut self) -> Result<Value, DeltaError> {
+        Ok(match self.byte()? {
+            VALUE_NULL => Value::Null,
+            VALUE_FALSE => Value::Bool(false),
+            VALUE_TRUE => Value::Bool(true),
+            VALUE_INT => Value::from(unzigzag(self.varint()?)),
+            VALUE_UINT => Value::from(self.varint()?),
+            VALUE_F32 => {
+                let bytes = self.take(4)?.try_into().map_err(|_| DeltaError::Malformed)?;
+                Number::from_f64(f64::from(f32::from_le_bytes(bytes))).map_or(Value::Null, Value::Number)
+            },
+            VALUE_F64 => {
+                let bytes = self.take(8)?.try_into().map_err(|_| DeltaError::Malformed)?;
+                Number::from_f64(f64::from_le_bytes(bytes)).map_or(Value::Null, Value::Number)
+            },
+            VALUE_STRING => Value::String(self.string()?),
+            VALUE_EMPTY_ARRAY => Value::Array(Vec::new()),
+            VALUE_EMPTY_OBJECT => Value::Object(Map::new()),
+            _ => return Err(DeltaError::Malformed),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EventType;
+
+    fn everything(keyframe_every: u32) -> DeltaConfig {
+        DeltaConfig {
+            channels: vec![DeltaChannel::new("all", 100, keyframe_every, &[])],
+            max_bytes_per_sec: None,
+        }
+    }
+
+    fn decode_all(decoder: &mut DeltaDecoder, frames: &[DeltaFrame]) {
+        for frame in frames {
+            decoder.decode(&frame.bytes).unwrap();
+        }
+    }
+
+    #[test]
+    fn varints_round_trip() {
+        for value in [0, 1, -1, 63, -64, 1 << 40, i64::MAX, i64::MIN] {
+            let mut out = Vec::new();
+            write_varint(&mut out, zigzag(value));
+            assert_eq!(unzigzag(Reader(&out).varint().unwrap()), value);
+        }
+    }
+
+    #[test]
+    fn keyframe_rebuilds_the_state() {
+        let mut drone = DroneState::new("phoenix".to_string());
+        drone.log_event(EventType::ThreatDetected, "intruder".to_string(), vec!["siren".to_string()]);
+        let now = Utc::now();
+        let frames = DeltaEncoder::new(everything(10)).encode(&drone, now).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert!(frames[0].keyframe);
+
+        let mut decoder = DeltaDecoder::new();
+        let decoded = decoder.decode(&frames[0].bytes).unwrap();
+        assert_eq!(decoded.sent.timestamp_millis(), now.timestamp_millis());
+        assert_eq!(decoder.state(), serde_json::to_value(&drone).unwrap());
+    }
+
+    #[test]
+    fn deltas_carry_only_changes() {
+        let mut drone = DroneState::new("phoenix".to_string());
+        let mut encoder = DeltaEncoder::new(everything(10));
+        let mut decoder = DeltaDecoder::new();
+        let start = Utc::now();
+        let keyframe = encoder.encode(&drone, start).unwrap();
+        decode_all(&mut decoder, &keyframe);
+
+        // Nothing due before the interval, nothing sent when nothing changed
+        assert!(encoder.encode(&drone, start + Duration::milliseconds(50)).unwrap().is_empty());
+        assert!(encoder.encode(&drone, start + Duration::milliseconds(100)).unwrap().is_empty());
+
+        drone.position.latitude = 51.5;
+        drone.log_event(EventType::ThreatDetected, "intruder".to_string(), Vec::new());
+        let delta = encoder.encode(&drone, start + Duration::milliseconds(200)).unwrap();
+        assert_eq!(delta.len(), 1);
+        assert!(!delta[0].keyframe);
+        assert!(delta[0].bytes.len() < keyframe[0].bytes.len());
+        decode_all(&mut decoder, &delta);
+        assert_eq!(decoder.state(), serde_json::to_value(&drone).unwrap());
+
+        drone.position.latitude = 51.25;
+        decode_all(&mut decoder, &encoder.encode(&drone, start + Duration::milliseconds(300)).unwrap());
+        assert_eq!(decoder.state_as::<DroneState>().unwrap().position.latitude, 51.25);
+    }
+
+    #[test]
+    fn missed_delta_waits_for_a_keyframe() {
+        let mut drone = DroneState::new("phoenix".to_string());
+        let mut encoder = DeltaEncoder::new(everything(3));
+        let mut decoder = DeltaDecoder::new();
+        let start = Utc::now();
+        decode_all(&mut decoder, &encoder.encode(&drone, start).unwrap());
+
+        drone.position.altitude = 10.0;
+        let lost = encoder.encode(&drone, start + Duration::milliseconds(100)).unwrap();
+        assert!(!lost[0].keyframe);
+        drone.position.altitude = 20.0;
+        let next = encoder.encode(&drone, start + Duration::milliseconds(200)).unwrap();
+        assert!(matches!(decoder.decode(&next[0].bytes), Err(DeltaError::OutOfSync { expected: 2, got: 3, .. })));
+
+        drone.position.altitude = 30.0;
+        let keyframe = encoder.encode(&drone, start + Duration::milliseconds(300)).unwrap();
+        assert!(keyframe[0].keyframe);
+        decode_all(&mut decoder, &keyframe);
+        assert_eq!(decoder.state(), serde_json::to_value(&drone).unwrap());
+    }
+
+    #[test]
+    fn damaged_frames_are_refused() {
+        let drone = DroneState::new("phoenix".to_string());
+        let frames = DeltaEncoder::new(everything(10)).encode(&drone, Utc::now()).unwrap();
+        let bytes = &frames[0].bytes;
+        let mut decoder = DeltaDecoder::new();
+        assert!(matches!(decoder.decode(b"{}"), Err(DeltaError::NotAFrame)));
+        assert!(matches!(decoder.decode(b"PD\x09"), Err(DeltaError::UnsupportedVersion(9))));
+        assert!(matches!(decoder.decode(&bytes[..bytes.len() - 1]), Err(DeltaError::Malformed)));
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(decoder.decode(&trailing), Err(DeltaError::Malformed)));
+    }
+
+    #[test]
+    fn budget_defers_channels_without_losing_changes() {
+        let mut drone = DroneState::new("phoenix".to_string());
+        let mut encoder = DeltaEncoder::new(DeltaConfig {
+            max_bytes_per_sec: Some(64),
+            ..DeltaConfig::default()
+        });
+        let mut decoder = DeltaDecoder::new();
+        let start = Utc::now();
+        // Each keyframe is over budget, so one channel goes out per second
+        let first = encoder.encode(&drone, start).unwrap();
+        assert_eq!(first.len(), 1);
+        decode_all(&mut decoder, &first);
+
+        drone.position.latitude = 51.5;
+        for second in 1..=3 {
+            decode_all(&mut decoder, &encoder.encode(&drone, start + Duration::seconds(second)).unwrap());
+        }
+        let state = serde_json::to_value(&drone).unwrap();
+        for field in ["position", "system_health", "mission_log"] {
+            assert_eq!(decoder.state()[field], state[field], "{}", field);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! mDNS advertisement of the control API (`mdns` feature)
+
+use crate::DroneState;
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Service type the control API is announced under
+pub const SERVICE_TYPE: &str = "_phoenix._tcp.local.";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiscoveryConfig {
+    /// Instance name (absent = the drone's name)
+    pub instance: Option<String>,
+    /// Host name, without `.local` (absent = `phoenix-` and the start of the drone's id)
+    pub hostname: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum DiscoveryError {
+    #[error("mDNS failed: {0}")]
+    Mdns(#[from] mdns_sd::Error),
+    #[error("the API is bound to {0}, which only this machine can reach")]
+    Loopback(SocketAddr),
+}
+
+/// The announcement, withdrawn by `stop`
+pub struct Advertisement {
+    daemon: ServiceDaemon,
+    fullname: String,
+}
+
+impl Advertisement {
+    /// Say goodbye on the network so ground stations drop the drone at once
+    pub fn stop(self) {
+        if let Err(e) = self.daemon.unregister(&self.fullname) {
+            tracing::warn!("📡 Failed to withdraw the mDNS announcement: {}", e);
+        }
+        let _ = self.daemon.shutdown();
+    }
+}
+
+/// Announce the API listening on `api`; an unspecified address announces
+/// every interface's, following them as they change
+pub fn advertise(config: &DiscoveryConfig, api: SocketAddr, drone: &DroneState, signed: bool, pairing: bool) -> Result<Advertisement, DiscoveryError> {
+    if api.ip().is_loopback() {
+        return Err(DiscoveryError::Loopback(api));
+    }
+    let instance = config.instance.clone().unwrap_or_else(|| drone.name.clone());
+    let hostname = match &config.hostname {
+        Some(hostname) => format!("{}.local.", hostname.trim_end_matches('.').trim_end_matches(".local")),
+        None => format!("phoenix-{}.local.", &drone.id.simple().to_string()[..8]),
+    };
+    let properties = [
+        ("id", drone.id.to_string()),
+        ("version", env!("CARGO_PKG_VERSION").to_string()),
+        ("signed", signed.to_string()),
+        ("pairing", pairing.to_string()),
+    ];
+    let service = if api.ip().is_unspecified() {
+        ServiceInfo::new(SERVICE_TYPE, &instance, &hostname, "", api.port(), &properties[..])?.enable_addr_auto()
+    } else {
+        ServiceInfo::new(SERVICE_TYPE, &instance, &hostname, api.ip(), api.port(), &properties[..])?
+    };
+    let fullname = service.get_fullname().to_string();
+    let daemon = ServiceDaemon::new()?;
+    daemon.register(service)?;
+    tracing::info!("📡 Announcing the control API over mDNS as '{}' ({})", instance, hostname);
+    Ok(Advertisement { daemon, fullname })
+}
+
+/// A drone found on the network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveredDrone {
+    pub name: String,
+    /// Absent for an announcement without one
+    pub id: Option<Uuid>,
+    pub addresses: Vec<IpAddr>,
+    pub port: u16,
+    /// Commands must be signed
+    pub signed: bool,
+    /// Controllers may pair
+    pub pairing: bool,
+}
+
+impl DiscoveredDrone {
+    /// Base URL of its control API, over IPv4 where it has an address
+    pub fn api_url(&self) -> Option<String> {
+        let address = self.addresses.iter().find(|address| address.is_ipv4()).or(self.addresses.first())?;
+        Some(format!("http://{}", SocketAddr::new(*address, self.port)))
+    }
+}
+
+/// Listen for announcements for `timeout`, returning every drone heard
+pub async fn discover(timeout: Duration) -> Result<Vec<DiscoveredDrone>, DiscoveryError> {
+    let daemon = ServiceDaemon::new()?;
+    let events = daemon.browse(SERVICE_TYPE)?;
+    let heard = crate::runtime::spawn_blocking(move || {
+        let deadline = Instant::now() + timeout;
+        let mut drones = HashMap::new();
+        while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+            let Ok(event) = events.recv_timeout(left) else { break };
+            if let ServiceEvent::ServiceResolved(service) = event {
+                let flag = |key| service.get_property_val_str(key) == Some("true");
+                let name = service.get_fullname().trim_end_matches(SERVICE_TYPE).trim_end_matches('.').to_string();
+                let mut addresses: Vec<IpAddr> = service.get_addresses().iter().copied().collect();
+                addresses.sort();
+                let drone = DiscoveredDrone {
+                    id: service.get_property_val_str("id").and_then(|id| id.parse().ok()),
+                    addresses,
+                    port: service.get_port(),
+                    signed: flag("signed"),
+                    pairing: flag("pairing"),
+                    name,
+                };
+                drones.insert(service.get_fullname().to_string(), drone);
+            }
+        }
+        drones.into_values().collect::<Vec<_>>()
+    })
+    .await
+    .unwrap_or_default();
+    let _ = daemon.shutdown();
+    Ok(heard)
+}
@@ -0,0 +1,379 @@
+//! Signed commands
+
+use crate::{Action, AuthConfig, AuthContext, AuthError, CommandSource, DroneState, EventType, Role};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use thiserror::Error;
+
+/// Header (or gRPC metadata key) carrying the signing key's id
+pub const KEY_HEADER: &str = "x-phoenix-key";
+/// Header carrying the nonce
+pub const NONCE_HEADER: &str = "x-phoenix-nonce";
+/// Header carrying the timestamp, in Unix milliseconds
+pub const TIMESTAMP_HEADER: &str = "x-phoenix-timestamp";
+/// Header carrying the hex signature; its presence marks a request as signed
+pub const SIGNATURE_HEADER: &str = "x-phoenix-signature";
+
+/// A public key allowed to sign commands, and the role it grants
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandKey {
+    /// Named as the caller in the mission log
+    pub id: String,
+    pub role: Role,
+    /// Hex Ed25519 public key
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SignedCommandConfig {
+    pub keys: Vec<CommandKey>,
+    /// Refuse commands that are not signed; status reads may still use a token
+    pub required: bool,
+    /// Oldest command accepted
+    pub max_age_secs: u64,
+    /// How far ahead of the drone's clock a sender may be
+    pub max_clock_skew_secs: u64,
+}
+
+impl Default for SignedCommandConfig {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            required: false,
+            max_age_secs: 30,
+            max_clock_skew_secs: 5,
+        }
+    }
+}
+
+impl SignedCommandConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (i, key) in self.keys.iter().enumerate() {
+            if self.keys[..i].iter().any(|earlier| earlier.id == key.id) {
+                problems.push(format!("command key '{}' is defined twice", key.id));
+            }
+            if verifying_key(&key.public_key).is_none() {
+                problems.push(format!("command key '{}' is not a 64-hex-digit Ed25519 public key", key.id));
+            }
+        }
+        if self.max_age_secs == 0 {
+            problems.push("signed_commands.max_age_secs must be positive".to_string());
+        }
+        problems
+    }
+}
+
+/// A command and the signature that vouches for it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandEnvelope {
+    pub key_id: String,
+    pub command: String,
+    /// Random, never reused by the sender
+    pub nonce: String,
+    pub timestamp: DateTime<Utc>,
+    /// Hex Ed25519 signature of `signed_payload()`
+    pub signature: String,
+}
+
+impl CommandEnvelope {
+    /// Sign `command` with a fresh nonce, as a controller or test client would
+    pub fn sign(key_id: &str, command: String, timestamp: DateTime<Utc>, key: &SigningKey) -> Self {
+        let mut envelope = Self {
+            key_id: key_id.to_string(),
+            command,
+            nonce: hex::encode(rand::random::<[u8; 16]>()),
+            timestamp,
+            signature: String::new(),
+        };
+        envelope.signature = hex::encode(key.sign(envelope.signed_payload().as_bytes()).to_bytes());
+        envelope
+    }
+
+    /// Key id, nonce, timestamp (Unix milliseconds) and command, one per line
+    pub fn signed_payload(&self) -> String {
+        format!("dark-phoenix-command\n{}\n{}\n{}\n{}", self.key_id, self.nonce, self.timestamp.timestamp_millis(), self.command)
+    }
+
+    /// The `x-phoenix-*` headers that carry the envelope over HTTP or gRPC
+    pub fn headers(&self) -> [(&'static str, String); 4] {
+        [
+            (KEY_HEADER, self.key_id.clone()),
+            (NONCE_HEADER, self.nonce.clone()),
+            (TIMESTAMP_HEADER, self.timestamp.timestamp_millis().to_string()),
+            (SIGNATURE_HEADER, self.signature.clone()),
+        ]
+    }
+
+    /// Rebuild an envelope from its headers and the command they sign;
+    /// `None` if the request is not signed. Missing or garbled headers make
+    /// an envelope that fails verification rather than an unsigned request.
+    pub fn from_headers<'a>(command: String, header: impl Fn(&str) -> Option<&'a str>) -> Option<Self> {
+        let signature = header(SIGNATURE_HEADER)?.to_string();
+        let timestamp = header(TIMESTAMP_HEADER)
+            .and_then(|millis| millis.parse().ok())
+            .and_then(|millis| Utc.timestamp_millis_opt(millis).single())
+            .unwrap_or_default();
+        Some(Self {
+            key_id: header(KEY_HEADER).unwrap_or_default().to_string(),
+            command,
+            nonce: header(NONCE_HEADER).unwrap_or_default().to_string(),
+            timestamp,
+            signature,
+        })
+    }
+}
+
+/// What an HTTP request's signature covers: method, path with query, body
+pub fn http_command(method: &str, path: &str, body: &[u8]) -> String {
+    format!("{} {}\n{}", method, path, String::from_utf8_lossy(body))
+}
+
+/// What a gRPC call's signature covers: the method and the encoded request
+pub fn grpc_command(method: &str, message: &[u8]) -> String {
+    format!("{}\n{}", method, hex::encode(message))
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum EnvelopeError {
+    #[error("unsigned command refused; commands must be signed")]
+    Unsigned,
+    #[error("command envelope has no nonce")]
+    NoNonce,
+    #[error("unknown command key '{0}'")]
+    UnknownKey(String),
+    #[error("bad signature from command key '{0}'")]
+    BadSignature(String),
+    #[error("command is {0}s old")]
+    Expired(i64),
+    #[error("command is timestamped {0}s in the future")]
+    FromTheFuture(i64),
+    #[error("command nonce {nonce} from '{key_id}' replayed")]
+    Replayed { key_id: String, nonce: String },
+}
+
+/// Checks signed commands for every link; one verifier is shared by all of
+/// them, so a command captured on one cannot be replayed on another
+#[derive(Debug)]
+pub struct CommandVerifier {
+    config: SignedCommandConfig,
+    keys: RwLock<HashMap<String, (VerifyingKey, Role)>>,
+    /// Nonces accepted within the age window, by key, with their timestamps
+    seen: Mutex<HashMap<(String, String), DateTime<Utc>>>,
+}
+
+impl Default for CommandVerifier {
+    fn default() -> Self {
+        Self::new(SignedCommandConfig::default())
+    }
+}
+
+impl CommandVerifier {
+    /// Keys that do not parse are left out (`problems()` reports them)
+    pub fn new(config: SignedCommandConfig) -> Self {
+        let keys = config
+            .keys
+            .iter()
+            .filter_map(|key| Some((key.id.clone(), (verifying_key(&key.public_key)?, key.role))))
+            .collect();
+        Self {
+            config,
+            keys: RwLock::new(keys),
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn has_key(&self, id: &str) -> bool {
+        self.keys.read().unwrap_or_else(|poisoned| poisoned.into_inner()).contains_key(id)
+    }
+
+    /// Accept commands signed by `key` from now on; false if its public key
+    /// does not parse
+    pub fn add_key(&self, key: &CommandKey) -> bool {
+        let Some(public_key) = verifying_key(&key.public_key) else { return false };
+        self.keys.write().unwrap_or_else(|poisoned| poisoned.into_inner()).insert(key.id.clone(), (public_key, key.role));
+        true
+    }
+
+    /// Refuse commands signed by key `id` from now on; false if it was unknown
+    pub fn remove_key(&self, id: &str) -> bool {
+        self.keys.write().unwrap_or_else(|poisoned| poisoned.into_inner()).remove(id).is_some()
+    }
+
+    /// Check the signature, age and nonce, returning the signer; an
+    /// accepted command's nonce is used up, whatever it goes on to do
+    pub fn verify(&self, envelope: &CommandEnvelope, source: CommandSource, now: DateTime<Utc>) -> Result<AuthContext, EnvelopeError> {
+        let (key, role) = self
+            .keys
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&envelope.key_id)
+            .copied()
+            .ok_or_else(|| EnvelopeError::UnknownKey(envelope.key_id.clone()))?;
+        let bad_signature = || EnvelopeError::BadSignature(envelope.key_id.clone());
+        let signature = hex::decode(&envelope.signature)
+            .ok()
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .ok_or_else(bad_signature)?;
+        key.verify(envelope.signed_payload().as_bytes(), &signature).map_err(|_| bad_signature())?;
+        if envelope.nonce.is_empty() {
+            return Err(EnvelopeError::NoNonce);
+        }
+
+        let age = now - envelope.timestamp;
+        let max_age = Duration::seconds(self.config.max_age_secs as i64);
+        let max_skew = Duration::seconds(self.config.max_clock_skew_secs as i64);
+        if age > max_age {
+            return Err(EnvelopeError::Expired(age.num_seconds()));
+        }
+        if -age > max_skew {
+            return Err(EnvelopeError::FromTheFuture(-age.num_seconds()));
+        }
+        let mut seen = self.seen.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Anything older than the window is refused as expired anyway
+        seen.retain(|_, timestamp| now - *timestamp <= max_age + max_skew);
+        let nonce = (envelope.key_id.clone(), envelope.nonce.clone());
+        if seen.contains_key(&nonce) {
+            return Err(EnvelopeError::Replayed {
+                key_id: envelope.key_id.clone(),
+                nonce: envelope.nonce.clone(),
+            });
+        }
+        seen.insert(nonce, envelope.timestamp);
+        Ok(AuthContext::new(&envelope.key_id, role, source))
+    }
+
+    /// Authorize a command arriving over `source`: by its envelope if it has
+    /// one, otherwise by `token` through `auth` unless signing is required.
+    /// Refusals are logged; anything accepted from off the drone counts as
+    /// contact with the ground.
+    pub fn authorize(
+        &self,
+        envelope: Option<&CommandEnvelope>,
+        token: Option<&str>,
+        auth: &AuthConfig,
+        source: CommandSource,
+        action: Action,
+        drone: &mut DroneState,
+    ) -> Result<AuthContext, AuthError> {
+        let now = Utc::now();
+        let checked = match envelope {
+            Some(envelope) => self.verify(envelope, source, now),
+            None if self.config.required && action != Action::ViewStatus => Err(EnvelopeError::Unsigned),
+            None => Ok(auth.authorize(token, source, action, drone)?),
+        };
+        let caller = checked.map_err(|e| {
+            tracing::warn!("🔐 Rejected command to {} over {}: {}", action, source, e);
+            drone.log_event(EventType::CommandRejected, format!("Rejected command to {} over {}: {}", action, source, e), Vec::new());
+            AuthError::Rejected(e)
+        })?;
+        if envelope.is_some() {
+            caller.authorize(action, drone)?;
+        }
+        // Refused commands do not count, or anyone could hold the link open
+        if source != CommandSource::Console {
+            drone.system_health.last_ground_contact = Some(now);
+        }
+        Ok(caller)
+    }
+}
+
+pub(crate) fn verifying_key(public_key: &str) -> Option<VerifyingKey> {
+    let bytes = <[u8; 32]>::try_from(hex::decode(public_key).ok()?).ok()?;
+    VerifyingKey::from_bytes(&bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verifier(key: &SigningKey) -> CommandVerifier {
+        CommandVerifier::new(SignedCommandConfig {
+            keys: vec![CommandKey {
+                id: "ground".to_string(),
+                role: Role::Operator,
+                public_key: hex::encode(key.verifying_key().to_bytes()),
+            }],
+            ..SignedCommandConfig::default()
+        })
+    }
+
+    fn signed(key: &SigningKey, at: DateTime<Utc>) -> CommandEnvelope {
+        CommandEnvelope::sign("ground", http_command("POST", "/arm", b"{}"), at, key)
+    }
+
+    #[test]
+    fn signed_command_verifies() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let now = Utc::now();
+        let caller = verifier(&key).verify(&signed(&key, now), CommandSource::Api, now).unwrap();
+        assert_eq!(caller.principal, "ground");
+        assert_eq!(caller.role, Role::Operator);
+    }
+
+    #[test]
+    fn altered_command_or_wrong_key_is_refused() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let verifier = verifier(&key);
+        let now = Utc::now();
+        let mut altered = signed(&key, now);
+        altered.command = http_command("POST", "/disarm", b"{}");
+        assert_eq!(verifier.verify(&altered, CommandSource::Api, now), Err(EnvelopeError::BadSignature("ground".to_string())));
+
+        let forged = signed(&SigningKey::from_bytes(&[8; 32]), now);
+        assert_eq!(verifier.verify(&forged, CommandSource::Api, now), Err(EnvelopeError::BadSignature("ground".to_string())));
+
+        let mut unknown = signed(&key, now);
+        unknown.key_id = "intruder".to_string();
+        assert_eq!(verifier.verify(&unknown, CommandSource::Api, now), Err(EnvelopeError::UnknownKey("intruder".to_string())));
+    }
+
+    #[test]
+    fn replayed_nonce_is_refused_on_every_link() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let verifier = verifier(&key);
+        let now = Utc::now();
+        let envelope = signed(&key, now);
+        assert!(verifier.verify(&envelope, CommandSource::Api, now).is_ok());
+        let replayed = verifier.verify(&envelope, CommandSource::Mqtt, now + Duration::seconds(1));
+        assert!(matches!(replayed, Err(EnvelopeError::Replayed { .. })));
+        // A fresh nonce for the same command is fine
+        assert!(verifier.verify(&signed(&key, now), CommandSource::Api, now).is_ok());
+    }
+
+    #[test]
+    fn stale_and_future_commands_are_refused() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let verifier = verifier(&key);
+        let now = Utc::now();
+        let stale = signed(&key, now - Duration::seconds(31));
+        assert_eq!(verifier.verify(&stale, CommandSource::Api, now), Err(EnvelopeError::Expired(31)));
+        let early = signed(&key, now + Duration::seconds(6));
+        assert_eq!(verifier.verify(&early, CommandSource::Api, now), Err(EnvelopeError::FromTheFuture(6)));
+        assert!(verifier.verify(&signed(&key, now + Duration::seconds(4)), CommandSource::Api, now).is_ok());
+    }
+
+    #[test]
+    fn envelope_survives_headers() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let envelope = signed(&key, Utc.timestamp_millis_opt(1_700_000_000_123).unwrap());
+        let headers = envelope.headers();
+        let header = |name: &str| headers.iter().find(|(key, _)| *key == name).map(|(_, value)| value.as_str());
+        assert_eq!(CommandEnvelope::from_headers(envelope.command.clone(), header), Some(envelope));
+        assert_eq!(CommandEnvelope::from_headers(String::new(), |_| None), None);
+    }
+
+    #[test]
+    fn removed_key_is_refused() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let verifier = verifier(&key);
+        assert!(verifier.remove_key("ground"));
+        let now = Utc::now();
+        assert_eq!(verifier.verify(&signed(&key, now), CommandSource::Api, now), Err(EnvelopeError::UnknownKey("ground".to_string())));
+    }
+}
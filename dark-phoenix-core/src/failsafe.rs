@@ -0,0 +1,216 @@
+//! Failsafes: conditions under which the drone stops its mission and hovers,
+//! returns home or lands, whatever it was doing
+
+use crate::{DroneState, EventType, FlightAction, ModuleHealth, ThreatLevel};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Which conditions trigger a failsafe, and what each one does
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FailsafeConfig {
+    /// Charge below which to act (absent = never)
+    pub low_battery_percent: Option<u8>,
+    pub low_battery_action: FlightAction,
+    /// Charge below which there is no longer enough to get home
+    pub critical_battery_percent: Option<u8>,
+    pub critical_battery_action: FlightAction,
+    /// How long the GPS fix may be lost before acting
+    pub gps_loss_secs: Option<u64>,
+    pub gps_loss_action: FlightAction,
+    /// Supervised modules the drone cannot fly on without
+    pub critical_modules: Vec<String>,
+    pub module_offline_action: FlightAction,
+    /// How long deterrence holds the scene before the drone leaves a Red or
+    /// Omega threat (0 = leave at once)
+    pub handoff_secs: u64,
+}
+
+impl Default for FailsafeConfig {
+    fn default() -> Self {
+        Self {
+            low_battery_percent: Some(25),
+            low_battery_action: FlightAction::ReturnToHome,
+            critical_battery_percent: Some(10),
+            critical_battery_action: FlightAction::Land,
+            // Without a fix the drone cannot find home
+            gps_loss_secs: Some(10),
+            gps_loss_action: FlightAction::Land,
+            critical_modules: Vec::new(),
+            module_offline_action: FlightAction::ReturnToHome,
+            handoff_secs: 15,
+        }
+    }
+}
+
+impl FailsafeConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let thresholds = [
+            ("low_battery_percent", self.low_battery_percent),
+            ("critical_battery_percent", self.critical_battery_percent),
+        ];
+        for (name, percent) in thresholds {
+            if percent.is_some_and(|percent| percent > 100) {
+                problems.push(format!("failsafe.{} must be at most 100", name));
+            }
+        }
+        if let (Some(low), Some(critical)) = (self.low_battery_percent, self.critical_battery_percent) {
+            if critical > low {
+                problems.push(format!("failsafe.critical_battery_percent {} is above low_battery_percent {}", critical, low));
+            }
+        }
+        problems
+    }
+}
+
+/// Why a failsafe engaged
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailsafeTrigger {
+    LowBattery { percent: u8 },
+    CriticalBattery { percent: u8 },
+    GpsLost { secs: i64 },
+    ModuleOffline { module: String, health: ModuleHealth },
+}
+
+impl fmt::Display for FailsafeTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailsafeTrigger::LowBattery { percent } => write!(f, "battery low ({}%)", percent),
+            FailsafeTrigger::CriticalBattery { percent } => write!(f, "battery critical ({}%)", percent),
+            FailsafeTrigger::GpsLost { secs } => write!(f, "GPS fix lost for {}s", secs),
+            FailsafeTrigger::ModuleOffline { module, health } => write!(f, "module '{}' {:?}", module, health),
+        }
+    }
+}
+
+/// Deterrence holding the scene until the drone may leave
+#[derive(Debug, Clone)]
+struct Handoff {
+    action: FlightAction,
+    until: DateTime<Utc>,
+}
+
+/// Tracks failsafe triggers across protection cycles
+#[derive(Debug, Clone)]
+pub struct Failsafe {
+    config: FailsafeConfig,
+    gps_lost_since: Option<DateTime<Utc>>,
+    /// Action already taken for the current failsafe
+    engaged: Option<FlightAction>,
+    handoff: Option<Handoff>,
+}
+
+impl Failsafe {
+    pub fn new(config: FailsafeConfig) -> Self {
+        Self {
+            config,
+            gps_lost_since: None,
+            engaged: None,
+            handoff: None,
+        }
+    }
+
+    /// Action taken for the current failsafe, if one is engaged
+    pub fn engaged(&self) -> Option<FlightAction> {
+        self.engaged
+    }
+
+    /// Whether deterrence is holding the scene before the drone leaves
+    pub fn handing_off(&self) -> bool {
+        self.handoff.is_some()
+    }
+
+    /// Every active trigger with its configured action
+    fn triggers(&mut self, drone: &DroneState, now: DateTime<Utc>) -> Vec<(FailsafeTrigger, FlightAction)> {
+        let config = &self.config;
+        let health = &drone.system_health;
+        let mut triggers = Vec::new();
+
+        if config.critical_battery_percent.is_some_and(|percent| health.battery_level < percent) {
+            triggers.push((FailsafeTrigger::CriticalBattery { percent: health.battery_level }, config.critical_battery_action));
+        } else if config.low_battery_percent.is_some_and(|percent| health.battery_level < percent) {
+            triggers.push((FailsafeTrigger::LowBattery { percent: health.battery_level }, config.low_battery_action));
+        }
+
+        let gps_lost = lost_for(&mut self.gps_lost_since, !health.gps_lock, now);
+        if let (Some(lost), Some(limit)) = (gps_lost, config.gps_loss_secs) {
+            if lost >= Duration::seconds(limit as i64) {
+                triggers.push((FailsafeTrigger::GpsLost { secs: lost.num_seconds() }, config.gps_loss_action));
+            }
+        }
+
+        for module in &config.critical_modules {
+            if let Some(&health) = health.modules.get(module) {
+                if matches!(health, ModuleHealth::Failed | ModuleHealth::Unresponsive) {
+                    triggers.push((FailsafeTrigger::ModuleOffline { module: module.clone(), health }, config.module_offline_action));
+                }
+            }
+        }
+        triggers
+    }
+
+    /// Check the drone against every trigger; returns the action to take now,
+    /// which is held back while deterrence takes over a Red or Omega scene
+    pub fn check(&mut self, drone: &mut DroneState, now: DateTime<Utc>) -> Option<FlightAction> {
+        let triggers = self.triggers(drone, now);
+        let Some((trigger, action)) = triggers.iter().max_by_key(|(_, action)| *action).cloned() else {
+            let cleared = self.engaged.take().is_some() | self.handoff.take().is_some();
+            if cleared {
+                tracing::info!("🛟 Failsafe conditions cleared");
+            }
+            return None;
+        };
+
+        let mut action = action;
+        let reasons: Vec<String> = triggers.iter().map(|(trigger, _)| trigger.to_string()).collect();
+        if let Some(handoff) = &self.handoff {
+            // Landing cannot wait; anything else leaves once the handoff is up
+            if action < FlightAction::Land && now < handoff.until {
+                return None;
+            }
+            action = action.max(handoff.action);
+            self.handoff = None;
+        } else if self.engaged.is_some_and(|engaged| engaged >= action) {
+            return None;
+        } else if action == FlightAction::ReturnToHome && drone.threat_level() >= ThreatLevel::Red && self.config.handoff_secs > 0 {
+            let until = now + Duration::seconds(self.config.handoff_secs as i64);
+            tracing::warn!(
+                "🛟 Failsafe at {}: deterrence holds the scene for {}s before the drone leaves",
+                drone.threat_level().as_str(),
+                self.config.handoff_secs
+            );
+            drone.log_event(
+                EventType::DeterrenceHandoff,
+                format!("Failsafe ({}) during {} threat: handing the scene to deterrence", trigger, drone.threat_level().as_str()),
+                vec![
+                    format!("Triggers: {}", reasons.join(", ")),
+                    format!("{} at {}", action, until.format("%H:%M:%S UTC")),
+                ],
+            );
+            self.handoff = Some(Handoff { action, until });
+            return None;
+        }
+
+        tracing::warn!("🛟 Failsafe: {} - {}", trigger, action);
+        drone.log_event(
+            EventType::FailsafeEngaged,
+            format!("Failsafe engaged: {}", trigger),
+            vec![format!("Triggers: {}", reasons.join(", ")), format!("Corrective action: {}", action)],
+        );
+        self.engaged = Some(action);
+        Some(action)
+    }
+}
+
+/// How long a condition has held, tracking when it started
+fn lost_for(since: &mut Option<DateTime<Utc>>, lost: bool, now: DateTime<Utc>) -> Option<Duration> {
+    if !lost {
+        *since = None;
+        return None;
+    }
+    Some(now - *since.get_or_insert(now))
+}
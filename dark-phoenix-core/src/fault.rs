@@ -0,0 +1,387 @@
+//! Fault injection for resilience testing (`fault-injection` feature)
+
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+
+/// How a device misbehaves
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultKind {
+    /// The call fails at once
+    Error,
+    /// The call hangs for `hang_ms`, then fails
+    Timeout,
+    /// Reads repeat the last good value; commands are acknowledged but not carried out
+    Stuck,
+    /// Reads return a nonsensical value; commands are ignored
+    Garbage,
+}
+
+/// When and how one device misbehaves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaultRule {
+    pub device: String,
+    #[serde(default)]
+    pub operation: Option<String>, // Every operation on the device when absent
+    pub fault: FaultKind,
+    #[serde(default = "always")]
+    pub probability: f32, // Below 1.0 the fault is intermittent
+    #[serde(default)]
+    pub after_calls: u32, // Matching calls that go through cleanly first
+    #[serde(default)]
+    pub times: Option<u32>, // Stop injecting after this many faults
+    #[serde(default = "default_hang_ms")]
+    pub hang_ms: u64, // How long a timeout hangs before failing
+}
+
+fn always() -> f32 {
+    1.0
+}
+
+fn default_hang_ms() -> u64 {
+    5000
+}
+
+impl FaultRule {
+    /// Fail every call to `device` the given way
+    pub fn new(device: &str, fault: FaultKind) -> Self {
+        Self {
+            device: device.to_string(),
+            operation: None,
+            fault,
+            probability: always(),
+            after_calls: 0,
+            times: None,
+            hang_ms: default_hang_ms(),
+        }
+    }
+
+    /// Only affect one operation, e.g. `"close"`
+    pub fn on(mut self, operation: &str) -> Self {
+        self.operation = Some(operation.to_string());
+        self
+    }
+
+    /// Inject on only this share of matching calls
+    pub fn intermittent(mut self, probability: f32) -> Self {
+        self.probability = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn after_calls(mut self, calls: u32) -> Self {
+        self.after_calls = calls;
+        self
+    }
+
+    pub fn times(mut self, times: u32) -> Self {
+        self.times = Some(times);
+        self
+    }
+
+    pub fn hang_for(mut self, hang: Duration) -> Self {
+        self.hang_ms = hang.as_millis() as u64;
+        self
+    }
+
+    fn matches(&self, device: &str, operation: &str) -> bool {
+        self.device == device && self.operation.as_deref().is_none_or(|op| op == operation)
+    }
+}
+
+/// A scripted set of faults
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FaultPlan {
+    pub seed: u64, // Intermittent faults hit the same calls on every run with the same seed
+    pub faults: Vec<FaultRule>,
+}
+
+#[derive(Debug, Error)]
+pub enum FaultPlanError {
+    #[error("failed to load fault plan: {0}")]
+    Load(#[from] config::ConfigError),
+    #[error("invalid fault plan:\n  - {}", .0.join("\n  - "))]
+    Invalid(Vec<String>),
+}
+
+impl FaultPlan {
+    /// Read a TOML, YAML or JSON fault plan (by extension)
+    pub fn load(path: &Path) -> Result<Self, FaultPlanError> {
+        let plan: FaultPlan = config::Config::builder()
+            .add_source(config::File::from(path))
+            .build()?
+            .try_deserialize()?;
+        plan.validate()?;
+        Ok(plan)
+    }
+
+    pub fn validate(&self) -> Result<(), FaultPlanError> {
+        let mut problems = Vec::new();
+        for rule in &self.faults {
+            if rule.device.is_empty() {
+                problems.push("fault without a device".to_string());
+            }
+            if !(0.0..=1.0).contains(&rule.probability) {
+                problems.push(format!("{} probability must be between 0.0 and 1.0", rule.device));
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(FaultPlanError::Invalid(problems))
+        }
+    }
+}
+
+/// What a faulty device call returns instead of its result
+#[derive(Debug, Error)]
+pub enum FaultError {
+    #[error("injected fault: {device} {operation} failed")]
+    Failed { device: String, operation: String },
+    #[error("injected fault: {device} {operation} timed out after {hang_ms}ms")]
+    TimedOut { device: String, operation: String, hang_ms: u64 },
+}
+
+/// One fault that was injected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaultRecord {
+    pub device: String,
+    pub operation: String,
+    pub fault: FaultKind,
+    pub call: u32, // Which call to the operation it hit, from 1
+}
+
+#[derive(Debug, Default)]
+struct InjectorState {
+    /// Each rule with how many calls it matched and how many faults it injected
+    rules: Vec<(FaultRule, u32, u32)>,
+    calls: HashMap<(String, String), u32>,
+    injected: Vec<FaultRecord>,
+    rng: u64,
+}
+
+impl InjectorState {
+    /// splitmix64 - intermittent faults must repeat run to run, so no OS entropy
+    fn roll(&mut self) -> f32 {
+        self.rng = self.rng.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// Shared fault schedule for every wrapped device; clones share state, so a
+/// test can keep one to add faults mid-run and assert on what happened
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjector {
+    state: Arc<Mutex<InjectorState>>,
+}
+
+impl FaultInjector {
+    pub fn new(plan: FaultPlan) -> Self {
+        let injector = Self::default();
+        {
+            let mut state = injector.lock();
+            state.rng = plan.seed;
+            state.rules = plan.faults.into_iter().map(|rule| (rule, 0, 0)).collect();
+        }
+        injector
+    }
+
+    /// Wrap a device so its calls go through this injector
+    pub fn wrap<T>(&self, device: &str, inner: T) -> Faulty<T> {
+        Faulty {
+            inner,
+            device: device.to_string(),
+            injector: self.clone(),
+            last_good: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start injecting a fault from the next call on
+    pub fn inject(&self, rule: FaultRule) {
+        tracing::info!("💥 Fault armed: {:?} on {}", rule.fault, rule.device);
+        self.lock().rules.push((rule, 0, 0));
+    }
+
+    /// Let every device behave again
+    pub fn heal(&self) {
+        self.lock().rules.clear();
+        tracing::info!("💥 All faults cleared");
+    }
+
+    /// Decide the fate of one call, counting it
+    pub fn decide(&self, device: &str, operation: &str) -> Option<FaultRule> {
+        let mut state = self.lock();
+        let call = {
+            let calls = state.calls.entry((device.to_string(), operation.to_string())).or_insert(0);
+            *calls += 1;
+            *calls
+        };
+        for index in 0..state.rules.len() {
+            let (rule, matched, injected) = &mut state.rules[index];
+            if !rule.matches(device, operation) {
+                continue;
+            }
+            *matched += 1;
+            if *matched <= rule.after_calls || rule.times.is_some_and(|times| *injected >= times) {
+                continue;
+            }
+            let rule = rule.clone();
+            if rule.probability < 1.0 && state.roll() >= rule.probability {
+                continue;
+            }
+            state.rules[index].2 += 1;
+            state.injected.push(FaultRecord {
+                device: device.to_string(),
+                operation: operation.to_string(),
+                fault: rule.fault,
+                call,
+            });
+            tracing::warn!("💥 Injecting {:?} into {} {} (call {})", rule.fault, device, operation, call);
+            return Some(rule);
+        }
+        None
+    }
+
+    /// Calls made to one operation of a device, faulty or not
+    pub fn calls(&self, device: &str, operation: &str) -> u32 {
+        self.lock().calls.get(&(device.to_string(), operation.to_string())).copied().unwrap_or(0)
+    }
+
+    /// Every fault injected so far, oldest first
+    pub fn injected(&self) -> Vec<FaultRecord> {
+        self.lock().injected.clone()
+    }
+
+    /// Panic unless `device` was asked to do `operation` at least once
+    pub fn assert_called(&self, device: &str, operation: &str) {
+        assert!(self.calls(device, operation) > 0, "{} was never asked to {}", device, operation);
+    }
+
+    /// Panic if `device` was asked to do `operation`
+    pub fn assert_not_called(&self, device: &str, operation: &str) {
+        let calls = self.calls(device, operation);
+        assert_eq!(calls, 0, "{} was asked to {} {} times", device, operation, calls);
+    }
+
+    /// Panic unless a `fault` was injected into `device`
+    pub fn assert_injected(&self, device: &str, fault: FaultKind) {
+        let injected = self.injected();
+        assert!(
+            injected.iter().any(|record| record.device == device && record.fault == fault),
+            "no {:?} was injected into {}; injected: {:?}",
+            fault,
+            device,
+            injected
+        );
+    }
+
+    /// Panic if any fault was injected into `device`
+    pub fn assert_untouched(&self, device: &str) {
+        let injected: Vec<FaultRecord> = self.injected().into_iter().filter(|record| record.device == device).collect();
+        assert!(injected.is_empty(), "faults were injected into {}: {:?}", device, injected);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, InjectorState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A device whose calls can be made to fail, hang, stick or return garbage
+///
+/// Modules implement their hardware traits for `Faulty<Box<dyn Trait>>` by
+/// routing each read through [`Faulty::read`] and each command through
+/// [`Faulty::command`].
+pub struct Faulty<T> {
+    inner: T,
+    device: String,
+    injector: FaultInjector,
+    /// Last value each read operation returned cleanly, for stuck faults
+    last_good: Mutex<HashMap<String, Box<dyn Any + Send>>>,
+}
+
+impl<T> Faulty<T> {
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn device(&self) -> &str {
+        &self.device
+    }
+
+    /// Run a read through the injector; `garbage` makes the nonsense value
+    pub async fn read<V, F>(&self, operation: &str, read: F, garbage: impl FnOnce() -> V) -> Result<V, Box<dyn std::error::Error>>
+    where
+        V: Clone + Send + 'static,
+        F: Future<Output = Result<V, Box<dyn std::error::Error>>>,
+    {
+        match self.injector.decide(&self.device, operation) {
+            Some(rule) => match rule.fault {
+                FaultKind::Error | FaultKind::Timeout => Err(self.failure(operation, &rule).await),
+                FaultKind::Garbage => Ok(garbage()),
+                FaultKind::Stuck => match self.last_good(operation) {
+                    Some(value) => Ok(value),
+                    // Stuck from the first reading on
+                    None => self.read_through(operation, read).await,
+                },
+            },
+            None => self.read_through(operation, read).await,
+        }
+    }
+
+    /// Run a command through the injector; stuck and garbage faults
+    /// acknowledge it without passing it on
+    pub async fn command<F>(&self, operation: &str, command: F) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: Future<Output = Result<(), Box<dyn std::error::Error>>>,
+    {
+        match self.injector.decide(&self.device, operation) {
+            Some(rule) => match rule.fault {
+                FaultKind::Error | FaultKind::Timeout => Err(self.failure(operation, &rule).await),
+                FaultKind::Stuck | FaultKind::Garbage => Ok(()),
+            },
+            None => command.await,
+        }
+    }
+
+    async fn read_through<V, F>(&self, operation: &str, read: F) -> Result<V, Box<dyn std::error::Error>>
+    where
+        V: Clone + Send + 'static,
+        F: Future<Output = Result<V, Box<dyn std::error::Error>>>,
+    {
+        let value = read.await?;
+        self.last_good
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(operation.to_string(), Box::new(value.clone()));
+        Ok(value)
+    }
+
+    fn last_good<V: Clone + 'static>(&self, operation: &str) -> Option<V> {
+        let last_good = self.last_good.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        last_good.get(operation).and_then(|value| value.downcast_ref::<V>()).cloned()
+    }
+
+    async fn failure(&self, operation: &str, rule: &FaultRule) -> Box<dyn std::error::Error> {
+        let device = self.device.clone();
+        let operation = operation.to_string();
+        if rule.fault == FaultKind::Timeout {
+            crate::runtime::sleep(Duration::from_millis(rule.hang_ms)).await;
+            return Box::new(FaultError::TimedOut {
+                device,
+                operation,
+                hang_ms: rule.hang_ms,
+            });
+        }
+        Box::new(FaultError::Failed { device, operation })
+    }
+}
@@ -0,0 +1,237 @@
+//! Hard flight boundaries: zones the drone must stay inside or out of, and an
+//! altitude ceiling and floor, enforced whatever the mission asks for
+
+use crate::{FlightAction, Position};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A fence vertex
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct GeoPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoneKind {
+    /// The drone must stay inside (inside any one, when there are several)
+    #[default]
+    Include,
+    /// The drone must stay out, e.g. a neighbour's garden or an airfield
+    Exclude,
+}
+
+/// One polygon of the fence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeofenceZone {
+    pub name: String,
+    #[serde(default)]
+    pub kind: ZoneKind,
+    /// At least three, in order around the edge; the polygon closes itself
+    pub vertices: Vec<GeoPoint>,
+}
+
+/// Boundaries the drone never crosses, regardless of mission commands
+///
+/// An empty fence (no zones, no altitude limits) allows everything.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Geofence {
+    pub zones: Vec<GeofenceZone>,
+    /// Highest altitude above home (absent = no ceiling)
+    pub ceiling_m: Option<f64>,
+    /// Lowest altitude above home while flying (absent = no floor)
+    pub floor_m: Option<f64>,
+    /// How close to a boundary counts as approaching it
+    pub warning_margin_m: f64,
+    pub on_approach: FlightAction,
+    pub on_breach: FlightAction,
+}
+
+impl Default for Geofence {
+    fn default() -> Self {
+        Self {
+            zones: Vec::new(),
+            ceiling_m: None,
+            floor_m: None,
+            warning_margin_m: 10.0,
+            on_approach: FlightAction::Loiter,
+            on_breach: FlightAction::ReturnToHome,
+        }
+    }
+}
+
+/// Which boundary the drone is near or across
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeofenceBoundary {
+    Zone(String),
+    Ceiling,
+    Floor,
+}
+
+impl fmt::Display for GeofenceBoundary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeofenceBoundary::Zone(name) => write!(f, "zone '{}'", name),
+            GeofenceBoundary::Ceiling => f.write_str("altitude ceiling"),
+            GeofenceBoundary::Floor => f.write_str("altitude floor"),
+        }
+    }
+}
+
+/// Where the drone stands against the fence, worst first when it is near
+/// several boundaries at once
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum GeofenceStatus {
+    #[default]
+    Inside,
+    /// Within the warning margin of a boundary
+    Approaching { boundary: GeofenceBoundary, distance_m: f64 },
+    /// Across a boundary by `distance_m`
+    Breached { boundary: GeofenceBoundary, distance_m: f64 },
+}
+
+impl GeofenceStatus {
+    /// 0 inside, 1 approaching, 2 breached
+    pub fn severity(&self) -> u8 {
+        match self {
+            GeofenceStatus::Inside => 0,
+            GeofenceStatus::Approaching { .. } => 1,
+            GeofenceStatus::Breached { .. } => 2,
+        }
+    }
+}
+
+impl Geofence {
+    pub fn is_empty(&self) -> bool {
+        self.zones.is_empty() && self.ceiling_m.is_none() && self.floor_m.is_none()
+    }
+
+    /// Where `position` stands against every zone and altitude limit
+    pub fn check(&self, position: &Position) -> GeofenceStatus {
+        let mut worst = GeofenceStatus::Inside;
+        let mut consider = |status: GeofenceStatus| {
+            let worse = match (&status, &worst) {
+                (a, b) if a.severity() != b.severity() => a.severity() > b.severity(),
+                // Same severity: further across, or nearer the edge, is worse
+                (GeofenceStatus::Breached { distance_m: a, .. }, GeofenceStatus::Breached { distance_m: b, .. }) => a > b,
+                (GeofenceStatus::Approaching { distance_m: a, .. }, GeofenceStatus::Approaching { distance_m: b, .. }) => a < b,
+                _ => false,
+            };
+            if worse {
+                worst = status;
+            }
+        };
+
+        if let Some(ceiling) = self.ceiling_m {
+            consider(self.classify(GeofenceBoundary::Ceiling, ceiling - position.altitude));
+        }
+        if let Some(floor) = self.floor_m {
+            consider(self.classify(GeofenceBoundary::Floor, position.altitude - floor));
+        }
+
+        // Signed distance to each zone edge: positive on the allowed side
+        let mut includes = Vec::new();
+        for zone in self.zones.iter().filter(|zone| zone.vertices.len() >= 3) {
+            let (inside, edge_m) = locate(position, &zone.vertices);
+            match zone.kind {
+                ZoneKind::Include => includes.push((zone, if inside { edge_m } else { -edge_m })),
+                ZoneKind::Exclude => {
+                    consider(self.classify(GeofenceBoundary::Zone(zone.name.clone()), if inside { -edge_m } else { edge_m }))
+                },
+            }
+        }
+        // Inside any include zone will do, so only the best placed one counts
+        if let Some((zone, clearance)) = includes.into_iter().max_by(|a, b| a.1.total_cmp(&b.1)) {
+            consider(self.classify(GeofenceBoundary::Zone(zone.name.clone()), clearance));
+        }
+
+        worst
+    }
+
+    /// Names of the zones `position` is inside, whether include or exclude
+    pub fn zones_at(&self, position: &Position) -> Vec<&str> {
+        self.zones
+            .iter()
+            .filter(|zone| zone.vertices.len() >= 3 && locate(position, &zone.vertices).0)
+            .map(|zone| zone.name.as_str())
+            .collect()
+    }
+
+    /// The action to take on moving into `status`
+    pub fn action_for(&self, status: &GeofenceStatus) -> Option<FlightAction> {
+        match status {
+            GeofenceStatus::Inside => None,
+            GeofenceStatus::Approaching { .. } => Some(self.on_approach),
+            GeofenceStatus::Breached { .. } => Some(self.on_breach),
+        }
+    }
+
+    /// Problems with the fence itself, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for zone in &self.zones {
+            if zone.vertices.len() < 3 {
+                problems.push(format!("geofence zone '{}' needs at least 3 vertices", zone.name));
+            }
+            if let Some(vertex) = zone.vertices.iter().find(|v| v.latitude.abs() > 90.0 || v.longitude.abs() > 180.0) {
+                problems.push(format!(
+                    "geofence zone '{}' has an invalid vertex ({}, {})",
+                    zone.name, vertex.latitude, vertex.longitude
+                ));
+            }
+        }
+        if let (Some(floor), Some(ceiling)) = (self.floor_m, self.ceiling_m) {
+            if floor >= ceiling {
+                problems.push(format!("geofence floor {} m must be below the ceiling {} m", floor, ceiling));
+            }
+        }
+        if self.warning_margin_m < 0.0 {
+            problems.push("geofence.warning_margin_m must not be negative".to_string());
+        }
+        problems
+    }
+
+    fn classify(&self, boundary: GeofenceBoundary, clearance_m: f64) -> GeofenceStatus {
+        if clearance_m < 0.0 {
+            GeofenceStatus::Breached { boundary, distance_m: -clearance_m }
+        } else if clearance_m < self.warning_margin_m {
+            GeofenceStatus::Approaching { boundary, distance_m: clearance_m }
+        } else {
+            GeofenceStatus::Inside
+        }
+    }
+}
+
+/// Whether `position` is inside the polygon, and its distance to the nearest edge
+fn locate(position: &Position, vertices: &[GeoPoint]) -> (bool, f64) {
+    // East/north metres from the drone, which sits at the origin
+    let east_scale = Position::EARTH_RADIUS_M * position.latitude.to_radians().cos();
+    let local: Vec<(f64, f64)> = vertices
+        .iter()
+        .map(|v| {
+            (
+                (v.longitude - position.longitude).to_radians() * east_scale,
+                (v.latitude - position.latitude).to_radians() * Position::EARTH_RADIUS_M,
+            )
+        })
+        .collect();
+
+    let mut inside = false;
+    let mut nearest = f64::INFINITY;
+    for (i, &(x1, y1)) in local.iter().enumerate() {
+        let (x2, y2) = local[(i + 1) % local.len()];
+        // Ray cast along +x
+        if (y1 > 0.0) != (y2 > 0.0) && x1 + (0.0 - y1) * (x2 - x1) / (y2 - y1) > 0.0 {
+            inside = !inside;
+        }
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let length_sq = dx * dx + dy * dy;
+        let t = if length_sq > 0.0 { (-(x1 * dx + y1 * dy) / length_sq).clamp(0.0, 1.0) } else { 0.0 };
+        nearest = nearest.min((x1 + t * dx).hypot(y1 + t * dy));
+    }
+    (inside, nearest)
+}
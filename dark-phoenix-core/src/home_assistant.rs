@@ -0,0 +1,174 @@
+//! Home Assistant MQTT discovery (`mqtt` feature)
+
+use crate::{DroneState, MqttConfig, ThreatLevel};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HomeAssistantConfig {
+    /// Topic prefix Home Assistant watches for discovery configs
+    pub discovery_prefix: String,
+    /// Device identifier and entity id prefix (absent = the MQTT client id);
+    /// keep it fixed, or Home Assistant sees a new device
+    pub node_id: Option<String>,
+    /// Access token the switch and button send with their commands; it sits
+    /// in a retained config anyone on the broker can read, so give it a role
+    /// that may arm, disarm and test, and nothing else
+    pub token: Option<String>,
+}
+
+impl Default for HomeAssistantConfig {
+    fn default() -> Self {
+        Self {
+            discovery_prefix: "homeassistant".to_string(),
+            node_id: None,
+            token: None,
+        }
+    }
+}
+
+impl HomeAssistantConfig {
+    /// Where Home Assistant announces it has (re)started
+    pub fn status_topic(&self) -> String {
+        format!("{}/status", self.discovery_prefix)
+    }
+
+    /// The retained discovery config of every entity, as topic and payload
+    pub fn discovery(&self, mqtt: &MqttConfig, drone: &DroneState) -> Vec<(String, Value)> {
+        let node = self.node_id.clone().unwrap_or_else(|| {
+            mqtt.client_id.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' }).collect()
+        });
+        let state = &mqtt.state.topic;
+        let fire = fire_suppression_topic(mqtt);
+        let device = json!({
+            "identifiers": [node],
+            "name": drone.name,
+            "manufacturer": "Dark Phoenix",
+            "model": "Phoenix Drone",
+            "sw_version": env!("CARGO_PKG_VERSION"),
+        });
+        let command = |mut command: Value| {
+            if let Some(token) = &self.token {
+                command["token"] = json!(token);
+            }
+            command.to_string()
+        };
+        let levels = json!([ThreatLevel::Green, ThreatLevel::Yellow, ThreatLevel::Orange, ThreatLevel::Red, ThreatLevel::Omega]);
+        let entities = [
+            (
+                "binary_sensor",
+                "threat",
+                json!({
+                    "name": "Threat",
+                    "device_class": "safety",
+                    "state_topic": state,
+                    "value_template": "{{ 'ON' if value_json.threat.level != 'Green' else 'OFF' }}",
+                }),
+            ),
+            (
+                "binary_sensor",
+                "fire",
+                json!({
+                    "name": "Fire",
+                    "device_class": "heat",
+                    "state_topic": state,
+                    "value_template": "{{ 'ON' if value_json.fire_discharging else 'OFF' }}",
+                }),
+            ),
+            (
+                "sensor",
+                "threat_level",
+                json!({
+                    "name": "Threat level",
+                    "device_class": "enum",
+                    "options": levels,
+                    "state_topic": state,
+                    "value_template": "{{ value_json.threat.level }}",
+                }),
+            ),
+            (
+                "sensor",
+                "battery",
+                json!({
+                    "name": "Battery",
+                    "device_class": "battery",
+                    "state_class": "measurement",
+                    "unit_of_measurement": "%",
+                    "state_topic": state,
+                    "value_template": "{{ value_json.system_health.battery_level }}",
+                }),
+            ),
+            (
+                "sensor",
+                "extinguisher_pressure",
+                json!({
+                    "name": "Extinguisher pressure",
+                    "device_class": "pressure",
+                    "state_class": "measurement",
+                    "unit_of_measurement": "psi",
+                    "state_topic": fire,
+                    "value_template": "{{ value_json.pressure | round(1) }}",
+                }),
+            ),
+            (
+                "switch",
+                "armed",
+                json!({
+                    "name": "Armed",
+                    "icon": "mdi:shield-lock",
+                    "command_topic": mqtt.command.topic,
+                    "payload_on": command(json!({"command": "arm"})),
+                    "payload_off": command(json!({"command": "disarm"})),
+                    "state_topic": fire,
+                    "value_template": "{{ 'ON' if value_json.armed else 'OFF' }}",
+                    "state_on": "ON",
+                    "state_off": "OFF",
+                }),
+            ),
+            (
+                "button",
+                "siren_test",
+                json!({
+                    "name": "Siren test",
+                    "icon": "mdi:bullhorn",
+                    "command_topic": mqtt.command.topic,
+                    "payload_press": command(json!({"command": "test", "module": "deterrence"})),
+                }),
+            ),
+        ];
+        entities
+            .into_iter()
+            .map(|(component, object, mut config)| {
+                config["unique_id"] = json!(format!("{}_{}", node, object));
+                config["object_id"] = json!(format!("{}_{}", node, object));
+                config["device"] = device.clone();
+                config["availability_topic"] = json!(availability_topic(mqtt));
+                (format!("{}/{}/{}/{}/config", self.discovery_prefix, component, node, object), config)
+            })
+            .collect()
+    }
+
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.discovery_prefix.trim().is_empty() || self.discovery_prefix.contains(['+', '#']) {
+            problems.push(format!("home_assistant.discovery_prefix '{}' is not a topic prefix", self.discovery_prefix));
+        }
+        if let Some(node) = &self.node_id {
+            if node.is_empty() || !node.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                problems.push(format!("home_assistant.node_id '{}' must be letters, digits, '_' and '-'", node));
+            }
+        }
+        problems
+    }
+}
+
+/// Retained `online`, or `offline` as the last will
+pub fn availability_topic(mqtt: &MqttConfig) -> String {
+    format!("{}/availability", mqtt.state.topic)
+}
+
+/// Retained fire suppression telemetry, for the pressure sensor and the arm switch
+pub fn fire_suppression_topic(mqtt: &MqttConfig) -> String {
+    format!("{}/fire_suppression", mqtt.state.topic)
+}
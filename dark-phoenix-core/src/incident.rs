@@ -0,0 +1,863 @@
+//! Incident reports for police and insurers
+
+use crate::audit::AUDIT_STREAM;
+use crate::{AuditConfig, DecisionTrace, EventStore, Keyring, MissionEvent, StoreError, ThreatLevel};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Event store stream the threat assessments are written to
+pub const ASSESSMENT_STREAM: &str = "threat_assessments";
+
+/// Name of the custody manifest in each evidence capture directory
+const MANIFEST_FILE: &str = "manifest.json";
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S UTC";
+
+/// Where reports find what the modules recorded
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportConfig {
+    /// Event store the threat assessments are written to (absent = none in reports)
+    pub assessment_store_dir: Option<PathBuf>,
+    /// Directory of evidence captures (absent = none in reports)
+    pub evidence_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Error)]
+pub enum ReportError {
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error("evidence manifest {0} could not be read: {1}")]
+    Evidence(PathBuf, String),
+    #[error("no incident {0}")]
+    UnknownIncident(Uuid),
+    #[error("the report starts after it ends")]
+    BadRange,
+    #[error("PDF reports need a build with the pdf-report feature")]
+    PdfUnavailable,
+    #[error("unknown report format '{0}' (markdown, html or pdf)")]
+    UnknownFormat(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Html,
+    Pdf,
+}
+
+impl ReportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "text/markdown; charset=utf-8",
+            ReportFormat::Html => "text/html; charset=utf-8",
+            ReportFormat::Pdf => "application/pdf",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+            ReportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = ReportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "html" => Ok(ReportFormat::Html),
+            "pdf" => Ok(ReportFormat::Pdf),
+            _ => Err(ReportError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ReportFormat::Markdown => "markdown",
+            ReportFormat::Html => "html",
+            ReportFormat::Pdf => "pdf",
+        })
+    }
+}
+
+/// A stretch of the mission log spent above Green
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Incident {
+    /// Id of the event that opened it
+    pub id: Uuid,
+    pub started: DateTime<Utc>,
+    /// Absent while the drone is still above Green
+    pub ended: Option<DateTime<Utc>>,
+    pub peak: ThreatLevel,
+    /// The event that opened it
+    pub description: String,
+    pub events: usize,
+}
+
+/// Every incident in `events`, oldest first
+pub fn incidents(events: &[MissionEvent]) -> Vec<Incident> {
+    let mut incidents: Vec<Incident> = Vec::new();
+    let mut open = false;
+    for event in events {
+        if open {
+            let incident = incidents.last_mut().expect("an open incident was pushed");
+            incident.events += 1;
+            incident.peak = incident.peak.max(event.threat_level);
+            if event.threat_level == ThreatLevel::Green {
+                incident.ended = Some(event.timestamp);
+                open = false;
+            }
+        } else if event.threat_level > ThreatLevel::Green {
+            incidents.push(Incident {
+                id: event.id,
+                started: event.timestamp,
+                ended: None,
+                peak: event.threat_level,
+                description: event.description.clone(),
+                events: 1,
+            });
+            open = true;
+        }
+    }
+    incidents
+}
+
+/// What a report covers
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportScope {
+    Incident(Uuid),
+    /// Every event from `since` to `until` (absent = unbounded)
+    Range {
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    },
+}
+
+/// The parts of a threat assessment a report shows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssessmentRecord {
+    pub id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub threat_level: ThreatLevel,
+    pub confidence: f32,
+    #[serde(default)]
+    pub threat_types: Vec<String>,
+    pub description: String,
+    #[serde(default)]
+    pub recommended_actions: Vec<String>,
+    #[serde(default)]
+    pub zone: Option<String>,
+    #[serde(default)]
+    pub trace: DecisionTrace,
+}
+
+/// One file of an evidence capture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvidenceFile {
+    pub file: String,
+    pub kind: String,
+    #[serde(default)]
+    pub sensor: Option<String>,
+    pub sha256: String,
+    pub bytes: u64,
+    pub started: DateTime<Utc>,
+    pub ended: DateTime<Utc>,
+}
+
+/// One entry in a capture's chain of custody
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustodyRecord {
+    pub timestamp: DateTime<Utc>,
+    pub actor: String,
+    pub action: String,
+    pub detail: String,
+}
+
+/// The parts of an evidence capture's manifest a report shows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvidenceCapture {
+    pub capture_id: Uuid,
+    pub threat_level: ThreatLevel,
+    #[serde(default)]
+    pub assessment_ids: Vec<Uuid>,
+    pub opened: DateTime<Utc>,
+    pub closed: Option<DateTime<Utc>>,
+    #[serde(rename = "items")]
+    pub files: Vec<EvidenceFile>,
+    pub custody: Vec<CustodyRecord>,
+    /// When the retention policy deleted the files
+    #[serde(default)]
+    pub purged: Option<DateTime<Utc>>,
+    /// Directory the capture is in
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+/// Where a timeline entry came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineSource {
+    Event,
+    Assessment,
+    Evidence,
+}
+
+impl fmt::Display for TimelineSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TimelineSource::Event => "event",
+            TimelineSource::Assessment => "assessment",
+            TimelineSource::Evidence => "evidence",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    pub at: DateTime<Utc>,
+    pub source: TimelineSource,
+    pub threat_level: Option<ThreatLevel>,
+    pub detail: String,
+}
+
+/// Everything known about an incident or time range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentReport {
+    pub drone: String,
+    /// Absent for a report over a time range
+    pub incident: Option<Incident>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub generated: DateTime<Utc>,
+    pub peak: ThreatLevel,
+    pub events: Vec<MissionEvent>,
+    /// Assessments above Green; the rest are only counted
+    pub assessments: Vec<AssessmentRecord>,
+    pub green_assessments: usize,
+    pub evidence: Vec<EvidenceCapture>,
+    pub timeline: Vec<TimelineEntry>,
+}
+
+impl IncidentReport {
+    /// Pick out what falls within `scope`; evidence counts when its
+    /// recording overlaps the period
+    pub fn build(
+        drone: &str,
+        scope: ReportScope,
+        events: &[MissionEvent],
+        assessments: Vec<AssessmentRecord>,
+        evidence: Vec<EvidenceCapture>,
+        now: DateTime<Utc>,
+    ) -> Result<Self, ReportError> {
+        let (incident, since, until) = match scope {
+            ReportScope::Incident(id) => {
+                let incident = incidents(events).into_iter().find(|incident| incident.id == id).ok_or(ReportError::UnknownIncident(id))?;
+                let (since, until) = (incident.started, incident.ended);
+                (Some(incident), Some(since), until)
+            },
+            ReportScope::Range { since, until } => {
+                if since.zip(until).is_some_and(|(since, until)| since > until) {
+                    return Err(ReportError::BadRange);
+                }
+                (None, since, until)
+            },
+        };
+        let within = |at: DateTime<Utc>| since.is_none_or(|since| at >= since) && until.is_none_or(|until| at <= until);
+
+        let events: Vec<MissionEvent> = events.iter().filter(|event| within(event.timestamp)).cloned().collect();
+        let (assessments, green): (Vec<_>, Vec<_>) = assessments
+            .into_iter()
+            .filter(|assessment| within(assessment.timestamp))
+            .partition(|assessment| assessment.threat_level > ThreatLevel::Green);
+        let mut evidence: Vec<EvidenceCapture> = evidence
+            .into_iter()
+            .filter(|capture| {
+                until.is_none_or(|until| capture.opened <= until) && since.is_none_or(|since| capture.closed.unwrap_or(now) >= since)
+            })
+            .collect();
+        evidence.sort_by_key(|capture| capture.opened);
+
+        let mut timeline: Vec<TimelineEntry> = events
+            .iter()
+            .map(|event| TimelineEntry {
+                at: event.timestamp,
+                source: TimelineSource::Event,
+                threat_level: Some(event.threat_level),
+                detail: format!("{:?}: {}", event.event_type, event.description),
+            })
+            .collect();
+        timeline.extend(assessments.iter().map(|assessment| TimelineEntry {
+            at: assessment.timestamp,
+            source: TimelineSource::Assessment,
+            threat_level: Some(assessment.threat_level),
+            detail: format!("{} ({:.0}% confidence)", assessment.description, assessment.confidence * 100.0),
+        }));
+        for capture in &evidence {
+            timeline.push(TimelineEntry {
+                at: capture.opened,
+                source: TimelineSource::Evidence,
+                threat_level: None,
+                detail: format!("Evidence capture {} started recording", capture.capture_id),
+            });
+            if let Some(closed) = capture.closed {
+                timeline.push(TimelineEntry {
+                    at: closed,
+                    source: TimelineSource::Evidence,
+                    threat_level: Some(capture.threat_level),
+                    detail: format!("Evidence capture {} closed with {} files", capture.capture_id, capture.files.len()),
+                });
+            }
+        }
+        timeline.sort_by_key(|entry| entry.at);
+
+        let peak = events
+            .iter()
+            .map(|event| event.threat_level)
+            .chain(assessments.iter().map(|assessment| assessment.threat_level))
+            .max()
+            .unwrap_or(ThreatLevel::Green);
+        Ok(Self {
+            drone: drone.to_string(),
+            incident,
+            since,
+            until,
+            generated: now,
+            peak,
+            events,
+            assessments,
+            green_assessments: green.len(),
+            evidence,
+            timeline,
+        })
+    }
+
+    pub fn render(&self, format: ReportFormat) -> Result<Vec<u8>, ReportError> {
+        match format {
+            ReportFormat::Markdown => Ok(self.to_markdown().into_bytes()),
+            ReportFormat::Html => Ok(self.to_html().into_bytes()),
+            #[cfg(feature = "pdf-report")]
+            ReportFormat::Pdf => Ok(pdf::render(&self.title(), &self.blocks())),
+            #[cfg(not(feature = "pdf-report"))]
+            ReportFormat::Pdf => Err(ReportError::PdfUnavailable),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        for block in self.blocks() {
+            match block {
+                Block::Heading(level, text) => out.push_str(&format!("{} {}\n\n", "#".repeat(level), text)),
+                Block::Fields(fields) => {
+                    for (label, value) in fields {
+                        out.push_str(&format!("- **{}:** {}\n", label, value));
+                    }
+                    out.push('\n');
+                },
+                Block::Paragraph(text) => out.push_str(&format!("{}\n\n", text)),
+                Block::Table { columns, rows } => {
+                    out.push_str(&format!("| {} |\n", columns.join(" | ")));
+                    out.push_str(&format!("|{}\n", "---|".repeat(columns.len())));
+                    for row in rows {
+                        let cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|").replace('\n', " ")).collect();
+                        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                    }
+                    out.push('\n');
+                },
+            }
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n\
+             body {{ font-family: sans-serif; max-width: 60rem; margin: 2rem auto; color: #222; }}\n\
+             table {{ border-collapse: collapse; width: 100%; margin-bottom: 1.5rem; font-size: 0.9rem; }}\n\
+             th, td {{ border: 1px solid #ccc; padding: 0.3rem 0.5rem; text-align: left; vertical-align: top; }}\n\
+             th {{ background: #f2f2f2; }}\n\
+             td.hash {{ font-family: monospace; word-break: break-all; }}\n\
+             </style>\n</head>\n<body>\n",
+            escape_html(&self.title())
+        );
+        for block in self.blocks() {
+            match block {
+                Block::Heading(level, text) => out.push_str(&format!("<h{0}>{1}</h{0}>\n", level, escape_html(&text))),
+                Block::Fields(fields) => {
+                    out.push_str("<ul>\n");
+                    for (label, value) in fields {
+                        out.push_str(&format!("<li><strong>{}:</strong> {}</li>\n", escape_html(label), escape_html(&value)));
+                    }
+                    out.push_str("</ul>\n");
+                },
+                Block::Paragraph(text) => out.push_str(&format!("<p>{}</p>\n", escape_html(&text))),
+                Block::Table { columns, rows } => {
+                    out.push_str("<table>\n<tr>");
+                    for column in &columns {
+                        out.push_str(&format!("<th>{}</th>", escape_html(column)));
+                    }
+                    out.push_str("</tr>\n");
+                    for row in rows {
+                        out.push_str("<tr>");
+                        for (column, cell) in columns.iter().zip(&row) {
+                            let class = if *column == "SHA-256" { " class=\"hash\"" } else { "" };
+                            out.push_str(&format!("<td{}>{}</td>", class, escape_html(cell)));
+                        }
+                        out.push_str("</tr>\n");
+                    }
+                    out.push_str("</table>\n");
+                },
+            }
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    fn title(&self) -> String {
+        format!("Incident report: {}", self.drone)
+    }
+
+    /// The report's content, for each format to lay out
+    fn blocks(&self) -> Vec<Block> {
+        let mut blocks = vec![Block::Heading(1, self.title())];
+        let mut fields = Vec::new();
+        if let Some(incident) = &self.incident {
+            fields.push(("Incident", incident.id.to_string()));
+            fields.push(("Opened by", incident.description.clone()));
+        }
+        let period = format!(
+            "{} to {}",
+            self.since.map_or("the start of the log".to_string(), |since| since.format(TIME_FORMAT).to_string()),
+            self.until.map_or("the time of this report".to_string(), |until| until.format(TIME_FORMAT).to_string())
+        );
+        fields.push(("Period", period));
+        fields.push(("Highest threat level", self.peak.as_str().to_string()));
+        if let Some(event) = self.events.first() {
+            fields.push((
+                "Location",
+                format!("{:.6}, {:.6} at {:.0} m", event.position.latitude, event.position.longitude, event.position.altitude),
+            ));
+        }
+        fields.push(("Generated", self.generated.format(TIME_FORMAT).to_string()));
+        blocks.push(Block::Fields(fields));
+
+        blocks.push(Block::Heading(2, "Summary".to_string()));
+        let summary = format!(
+            "{} mission events, {} threat assessments above Green ({} at Green not listed) and {} evidence captures.",
+            self.events.len(),
+            self.assessments.len(),
+            self.green_assessments,
+            self.evidence.len()
+        );
+        blocks.push(Block::Paragraph(summary));
+
+        blocks.push(Block::Heading(2, "Timeline".to_string()));
+        blocks.push(Block::Table {
+            columns: vec!["Time", "Source", "Level", "Detail"],
+            rows: self
+                .timeline
+                .iter()
+                .map(|entry| {
+                    vec![
+                        entry.at.format(TIME_FORMAT).to_string(),
+                        entry.source.to_string(),
+                        entry.threat_level.map_or(String::new(), |level| level.as_str().to_string()),
+                        entry.detail.clone(),
+                    ]
+                })
+                .collect(),
+        });
+
+        blocks.push(Block::Heading(2, "Mission events".to_string()));
+        blocks.push(Block::Table {
+            columns: vec!["#", "Time", "Event", "Level", "Description", "Response"],
+            rows: self
+                .events
+                .iter()
+                .map(|event| {
+                    vec![
+                        event.sequence.to_string(),
+                        event.timestamp.format(TIME_FORMAT).to_string(),
+                        format!("{:?}", event.event_type),
+                        event.threat_level.as_str().to_string(),
+                        event.description.clone(),
+                        event.response_actions.join("; "),
+                    ]
+                })
+                .collect(),
+        });
+
+        blocks.push(Block::Heading(2, "Threat assessments".to_string()));
+        if self.assessments.is_empty() {
+            blocks.push(Block::Paragraph("No assessment rose above Green.".to_string()));
+        } else {
+            blocks.push(Block::Table {
+                columns: vec!["Time", "Level", "Confidence", "Threats", "Zone", "Description", "Decided by", "Recommended"],
+                rows: self
+                    .assessments
+                    .iter()
+                    .map(|assessment| {
+                        vec![
+                            assessment.timestamp.format(TIME_FORMAT).to_string(),
+                            assessment.threat_level.as_str().to_string(),
+                            format!("{:.0}%", assessment.confidence * 100.0),
+                            assessment.threat_types.join(", "),
+                            assessment.zone.clone().unwrap_or_default(),
+                            assessment.description.clone(),
+                            assessment
+                                .trace
+                                .decisive()
+                                .map_or(String::new(), |step| format!("{}: {}", step.rule, step.detail)),
+                            assessment.recommended_actions.join("; "),
+                        ]
+                    })
+                    .collect(),
+            });
+        }
+
+        blocks.push(Block::Heading(2, "Threat level decisions".to_string()));
+        let decisions: Vec<(&MissionEvent, &DecisionTrace)> =
+            self.events.iter().filter_map(|event| Some((event, event.decision.as_ref()?))).collect();
+        if decisions.is_empty() {
+            blocks.push(Block::Paragraph("No threat level change in this period recorded why it was made.".to_string()));
+        }
+        for (event, trace) in decisions {
+            blocks.push(Block::Heading(3, format!("{}: {}", event.timestamp.format(TIME_FORMAT), event.description)));
+            if !trace.inputs.is_empty() {
+                blocks.push(Block::Table {
+                    columns: vec!["Input", "Value", "Weight"],
+                    rows: trace
+                        .inputs
+                        .iter()
+                        .map(|input| {
+                            vec![
+                                input.name.clone(),
+                                format!("{:.2}", input.value),
+                                input.weight.map_or(String::new(), |weight| format!("{:.2}", weight)),
+                            ]
+                        })
+                        .collect(),
+                });
+            }
+            blocks.push(Block::Table {
+                columns: vec!["Rule", "Level", "Detail"],
+                rows: trace
+                    .steps
+                    .iter()
+                    .map(|step| vec![step.rule.clone(), step.level.as_str().to_string(), step.detail.clone()])
+                    .collect(),
+            });
+        }
+
+        blocks.push(Block::Heading(2, "Evidence".to_string()));
+        if self.evidence.is_empty() {
+            blocks.push(Block::Paragraph("No evidence was recorded in this period.".to_string()));
+        }
+        for capture in &self.evidence {
+            blocks.push(Block::Heading(3, format!("Capture {}", capture.capture_id)));
+            let mut fields = vec![
+                ("Directory", capture.dir.display().to_string()),
+                ("Threat level", capture.threat_level.as_str().to_string()),
+                ("Opened", capture.opened.format(TIME_FORMAT).to_string()),
+                (
+                    "Closed",
+                    capture.closed.map_or("still recording".to_string(), |closed| closed.format(TIME_FORMAT).to_string()),
+                ),
+            ];
+            if let Some(purged) = capture.purged {
+                fields.push(("Files deleted by retention policy", purged.format(TIME_FORMAT).to_string()));
+            }
+            blocks.push(Block::Fields(fields));
+            blocks.push(Block::Table {
+                columns: vec!["File", "Kind", "Sensor", "Recorded", "Bytes", "SHA-256"],
+                rows: capture
+                    .files
+                    .iter()
+                    .map(|file| {
+                        vec![
+                            file.file.clone(),
+                            file.kind.clone(),
+                            file.sensor.clone().unwrap_or_default(),
+                            format!("{} to {}", file.started.format("%H:%M:%S"), file.ended.format("%H:%M:%S")),
+                            file.bytes.to_string(),
+                            file.sha256.clone(),
+                        ]
+                    })
+                    .collect(),
+            });
+            blocks.push(Block::Table {
+                columns: vec!["Custody time", "Actor", "Action", "Detail"],
+                rows: capture
+                    .custody
+                    .iter()
+                    .map(|entry| vec![entry.timestamp.format(TIME_FORMAT).to_string(), entry.actor.clone(), entry.action.clone(), entry.detail.clone()])
+                    .collect(),
+            });
+        }
+
+        blocks.push(Block::Heading(2, "Integrity".to_string()));
+        let mut integrity = Vec::new();
+        if let (Some(first), Some(last)) = (self.events.first(), self.events.last()) {
+            integrity.push(format!(
+                "The mission events are entries {} to {} of the drone's hash-chained audit log; an audit export (`phoenix audit export`) lets anyone check they are unaltered.",
+                first.sequence, last.sequence
+            ));
+        }
+        if !self.evidence.is_empty() {
+            integrity.push("Evidence hashes are of the files as stored; each capture's manifest.json carries them with its custody log.".to_string());
+        }
+        if integrity.is_empty() {
+            integrity.push("Nothing was recorded in this period.".to_string());
+        }
+        blocks.push(Block::Paragraph(integrity.join(" ")));
+        blocks
+    }
+}
+
+/// A piece of report content, laid out by each format
+enum Block {
+    Heading(usize, String),
+    Fields(Vec<(&'static str, String)>),
+    Paragraph(String),
+    Table { columns: Vec<&'static str>, rows: Vec<Vec<String>> },
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// The stores and directories a report reads from
+#[derive(Debug, Clone, Default)]
+pub struct ReportSources {
+    /// The audit store, whose log outlives restarts (absent = the live log only)
+    pub audit: Option<EventStore>,
+    pub assessments: Option<EventStore>,
+    pub evidence_dir: Option<PathBuf>,
+}
+
+impl ReportSources {
+    /// The stores named in the settings, decrypted with `keyring`
+    pub fn open(config: &ReportConfig, audit: Option<&AuditConfig>, keyring: Option<Arc<Keyring>>) -> Result<Self, StoreError> {
+        let open = |dir: &Path| EventStore::open(dir).map(|store| store.with_keyring(keyring.clone()));
+        Ok(Self {
+            audit: audit.map(|audit| open(&audit.store_dir)).transpose()?,
+            assessments: config.assessment_store_dir.as_deref().map(open).transpose()?,
+            evidence_dir: config.evidence_dir.clone(),
+        })
+    }
+
+    /// The audit store's events, or `live` without one
+    pub fn events(&self, live: &[MissionEvent]) -> Result<Vec<MissionEvent>, ReportError> {
+        match &self.audit {
+            Some(store) => Ok(store.read(AUDIT_STREAM)?),
+            None => Ok(live.to_vec()),
+        }
+    }
+
+    pub fn assessments(&self) -> Result<Vec<AssessmentRecord>, ReportError> {
+        match &self.assessments {
+            Some(store) => Ok(store.read(ASSESSMENT_STREAM)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Every capture with a manifest, whether or not its files remain
+    pub fn captures(&self) -> Result<Vec<EvidenceCapture>, ReportError> {
+        let Some(dir) = &self.evidence_dir else { return Ok(Vec::new()) };
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let entries = std::fs::read_dir(dir).map_err(|e| ReportError::Evidence(dir.clone(), e.to_string()))?;
+        let mut captures = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path().join(MANIFEST_FILE);
+            if !path.is_file() {
+                continue;
+            }
+            let text = std::fs::read_to_string(&path).map_err(|e| ReportError::Evidence(path.clone(), e.to_string()))?;
+            let mut capture: EvidenceCapture = serde_json::from_str(&text).map_err(|e| ReportError::Evidence(path.clone(), e.to_string()))?;
+            capture.dir = entry.path();
+            captures.push(capture);
+        }
+        Ok(captures)
+    }
+
+    /// Build a report for `scope`, taking events from the audit store when
+    /// there is one and from `live` otherwise
+    pub fn report(&self, drone: &str, scope: ReportScope, live: &[MissionEvent], now: DateTime<Utc>) -> Result<IncidentReport, ReportError> {
+        IncidentReport::build(drone, scope, &self.events(live)?, self.assessments()?, self.captures()?, now)
+    }
+}
+
+/// PDF layout: A4 pages of wrapped Helvetica text, tables as one paragraph
+/// per row
+#[cfg(feature = "pdf-report")]
+mod pdf {
+    use super::Block;
+    use pdf_writer::{Content, Name, Pdf, Rect, Ref, Str, TextStr};
+
+    const WIDTH: f32 = 595.0;
+    const HEIGHT: f32 = 842.0;
+    const MARGIN: f32 = 50.0;
+    /// Average Helvetica advance, as a fraction of the font size
+    const CHAR_WIDTH: f32 = 0.52;
+
+    const REGULAR: Name = Name(b"F1");
+    const BOLD: Name = Name(b"F2");
+
+    struct Layout {
+        pages: Vec<Content>,
+        y: f32,
+    }
+
+    impl Layout {
+        fn new() -> Self {
+            let mut layout = Self { pages: Vec::new(), y: 0.0 };
+            layout.new_page();
+            layout
+        }
+
+        fn new_page(&mut self) {
+            self.pages.push(Content::new());
+            self.y = HEIGHT - MARGIN;
+        }
+
+        /// Wrapped text, `indent` points in from the margin
+        fn text(&mut self, text: &str, font: Name, size: f32, indent: f32) {
+            let per_line = (((WIDTH - 2.0 * MARGIN - indent) / (size * CHAR_WIDTH)) as usize).max(10);
+            for line in wrap(text, per_line) {
+                let leading = size * 1.35;
+                if self.y - leading < MARGIN {
+                    self.new_page();
+                }
+                self.y -= leading;
+                let page = self.pages.last_mut().expect("a page was started");
+                page.begin_text();
+                page.set_font(font, size);
+                page.next_line(MARGIN + indent, self.y);
+                page.show(Str(&win_ansi(&line)));
+                page.end_text();
+            }
+        }
+
+        fn gap(&mut self, points: f32) {
+            self.y -= points;
+        }
+    }
+
+    pub(super) fn render(title: &str, blocks: &[Block]) -> Vec<u8> {
+        let mut layout = Layout::new();
+        for block in blocks {
+            match block {
+                Block::Heading(level, text) => {
+                    layout.gap(if *level == 1 { 0.0 } else { 8.0 });
+                    layout.text(text, BOLD, [18.0, 14.0, 12.0][(*level).clamp(1, 3) - 1], 0.0);
+                    layout.gap(4.0);
+                },
+                Block::Fields(fields) => {
+                    for (label, value) in fields {
+                        layout.text(&format!("{}: {}", label, value), REGULAR, 10.0, 0.0);
+                    }
+                    layout.gap(6.0);
+                },
+                Block::Paragraph(text) => {
+                    layout.text(text, REGULAR, 10.0, 0.0);
+                    layout.gap(6.0);
+                },
+                Block::Table { columns, rows } => {
+                    layout.text(&columns.join(" | "), BOLD, 9.0, 0.0);
+                    for row in rows {
+                        let cells: Vec<&str> = row.iter().map(String::as_str).filter(|cell| !cell.is_empty()).collect();
+                        layout.text(&cells.join(" | "), REGULAR, 9.0, 8.0);
+                        layout.gap(2.0);
+                    }
+                    layout.gap(6.0);
+                },
+            }
+        }
+
+        let catalog = Ref::new(1);
+        let tree = Ref::new(2);
+        let (regular, bold, info) = (Ref::new(3), Ref::new(4), Ref::new(5));
+        let page_ids: Vec<(Ref, Ref)> = (0..layout.pages.len() as i32).map(|i| (Ref::new(6 + 2 * i), Ref::new(7 + 2 * i))).collect();
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog).pages(tree);
+        pdf.pages(tree).kids(page_ids.iter().map(|(page, _)| *page)).count(page_ids.len() as i32);
+        pdf.type1_font(regular).base_font(Name(b"Helvetica")).encoding_predefined(Name(b"WinAnsiEncoding"));
+        pdf.type1_font(bold).base_font(Name(b"Helvetica-Bold")).encoding_predefined(Name(b"WinAnsiEncoding"));
+        pdf.document_info(info).title(TextStr(title)).producer(TextStr(concat!("Dark Phoenix ", env!("CARGO_PKG_VERSION"))));
+        for ((page_id, content_id), content) in page_ids.iter().zip(layout.pages) {
+            let mut page = pdf.page(*page_id);
+            page.media_box(Rect::new(0.0, 0.0, WIDTH, HEIGHT)).parent(tree).contents(*content_id);
+            page.resources().fonts().pair(REGULAR, regular).pair(BOLD, bold);
+            drop(page);
+            pdf.stream(*content_id, &content.finish());
+        }
+        pdf.finish()
+    }
+
+    /// Break `text` into lines of at most `width` characters, at spaces
+    /// where possible
+    fn wrap(text: &str, width: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        for word in text.split_whitespace() {
+            let mut word = word.to_string();
+            while word.chars().count() > width {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                let split = word.char_indices().nth(width).map_or(word.len(), |(at, _)| at);
+                lines.push(word[..split].to_string());
+                word = word[split..].to_string();
+            }
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        if !line.is_empty() || lines.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// Encode for the standard fonts' WinAnsi encoding; anything outside it
+    /// becomes '?'
+    fn win_ansi(text: &str) -> Vec<u8> {
+        text.chars()
+            .map(|c| match c {
+                ' '..='~' | '\u{a0}'..='\u{ff}' => c as u8,
+                '€' => 0x80,
+                '…' => 0x85,
+                '‘' => 0x91,
+                '’' => 0x92,
+                '“' => 0x93,
+                '”' => 0x94,
+                '•' => 0x95,
+                '–' => 0x96,
+                '—' => 0x97,
+                _ => b'?',
+            })
+            .collect()
+    }
+}
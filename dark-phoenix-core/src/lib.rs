@@ -1,62 +1,220 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
+use sha2::Digest;
 use uuid::Uuid;
+#[cfg(feature = "otel")]
+use otel::current_trace_id;
 
-/// Core threat level classification system
+#[cfg(feature = "api-server")]
+pub mod api;
+pub mod audit;
+pub mod auth;
+#[cfg(feature = "mavlink")]
+pub mod autopilot;
+pub mod battery;
+#[cfg(feature = "socketcan")]
+pub mod can;
+pub mod client;
+pub mod control;
+pub mod decision;
+pub mod delta;
+#[cfg(feature = "mdns")]
+pub mod discovery;
+pub mod envelope;
+#[cfg(feature = "fault-injection")]
+pub mod fault;
+pub mod failsafe;
+pub mod geofence;
+#[cfg(feature = "mqtt")]
+pub mod home_assistant;
+pub mod incident;
+pub mod link;
+pub mod link_manager;
+#[cfg(feature = "lora")]
+pub mod lora;
+pub mod metrics;
+#[cfg(feature = "modbus")]
+pub mod modbus;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod outbox;
+pub mod pairing;
+pub mod panic_button;
+pub mod patrol;
+#[cfg(feature = "wasm-plugins")]
+pub mod plugin;
+pub mod power;
+pub mod preflight;
+pub mod protectee;
+pub mod ranging;
+pub mod ring;
+#[cfg(feature = "rpi-hw")]
+pub mod rpi;
+pub mod schedule;
+pub mod settings;
+pub mod shutdown;
+#[cfg(feature = "siem")]
+pub mod siem;
+#[cfg(feature = "simulation")]
+pub mod simulation;
+#[cfg(feature = "sitl")]
+pub mod sitl;
+pub mod situation;
+pub mod store;
+pub mod supervisor;
+pub mod telemetry;
+#[cfg(feature = "thermal")]
+pub mod thermal;
+pub mod threat_state;
+#[cfg(feature = "phoenix-tui")]
+pub mod tui;
+pub mod units;
+pub mod vault;
+#[cfg(feature = "ble")]
+pub mod vitals;
+pub mod watchdog;
+#[cfg(feature = "ble")]
+pub mod wearable;
+pub mod webhook;
+#[cfg(feature = "binary-wire")]
+pub mod wire;
+
+// Shared with firmware, which builds them without std
+pub use dark_phoenix_types::{EventType, Position, ThreatLevel, VitalSigns};
+// Shared with the flight link, which the core depends on
+pub use phoenix_runtime as runtime;
+#[cfg(feature = "api-server")]
+pub use api::{ApiConfig, ThreatLevelRequest};
+pub use audit::{AuditConfig, AuditError, AuditExport, AuditHead, AuditLog, ChainError, ChainSummary, Checkpoint};
+pub use auth::{AccessToken, Action, AuthConfig, AuthContext, AuthError, CommandSource, Role};
+#[cfg(feature = "mavlink")]
+pub use autopilot::{FlightConfig, FlightController, FlightError, VehicleStatus};
+pub use battery::{BatteryConfig, BatteryGrade, BatteryHealth, BatteryHealthReport, BatterySample, ChargeCycle};
+#[cfg(feature = "socketcan")]
+pub use can::{ActuatorCommand, ActuatorReply, CanBus, CanConfig, CanError, CanLink, CanState, LinkFrame, SocketCanLink};
+pub use client::{ApiClient, ClientError};
+#[cfg(feature = "ws-client")]
+pub use client::TelemetryStream;
+pub use control::ModuleControl;
+#[cfg(feature = "mavlink")]
+pub use control::FlightControl;
+pub use decision::{DecisionTrace, TraceInput, TraceStep};
+pub use delta::{DecodedFrame, DeltaChannel, DeltaConfig, DeltaDecoder, DeltaEncoder, DeltaError, DeltaFrame};
+#[cfg(feature = "mdns")]
+pub use discovery::{Advertisement, DiscoveredDrone, DiscoveryConfig, DiscoveryError};
+pub use envelope::{CommandEnvelope, CommandKey, CommandVerifier, EnvelopeError, SignedCommandConfig};
+#[cfg(feature = "fault-injection")]
+pub use fault::{FaultError, FaultInjector, FaultKind, FaultPlan, FaultPlanError, FaultRecord, FaultRule, Faulty};
+pub use failsafe::{Failsafe, FailsafeConfig, FailsafeTrigger};
+pub use geofence::{GeoPoint, Geofence, GeofenceBoundary, GeofenceStatus, GeofenceZone, ZoneKind};
+#[cfg(feature = "mqtt")]
+pub use home_assistant::HomeAssistantConfig;
+pub use incident::{Incident, IncidentReport, ReportConfig, ReportError, ReportFormat, ReportScope, ReportSources};
+pub use link::{LinkConfig, LinkLossFlight, LinkMonitor, LinkStatus};
+pub use link_manager::{LinkManager, LinkManagerConfig, LinkRoute, TrafficClass, TransportConfig, TransportKind, TransportState};
+#[cfg(feature = "lora")]
+pub use lora::{AtModem, CommandAck, LoraCommand, LoraCommandFrame, LoraConfig, LoraError, LoraModem, LoraRequest};
+pub use metrics::{Counter, Gauge, Histogram, Metrics};
+#[cfg(feature = "modbus")]
+pub use modbus::{AlarmCoil, BuildingPanel, DetectorKind, DetectorReading, DetectorRegister, ModbusBridge, ModbusConfig, ModbusError, ModbusTransport, RegisterTable};
+#[cfg(feature = "mqtt")]
+pub use mqtt::{MqttCommand, MqttConfig, MqttError, MqttPublisher};
+#[cfg(feature = "otel")]
+pub use otel::{OtelConfig, OtelGuard};
+pub use outbox::{OutboundMessage, Outbox, OutboxConfig, OutboxPriority};
+pub use pairing::{PairedController, Pairing, PairingCode, PairingConfig, PairingError, PairingRequest, PairingResponse};
+pub use panic_button::{PanicAction, PanicButton, PanicCommand, PanicConfig, PanicDevice, PanicError, PanicOutcome};
+pub use patrol::{PatrolConfig, PatrolError, PatrolPlanner, PatrolRoute, PatrolStatus, Waypoint};
+#[cfg(feature = "wasm-plugins")]
+pub use plugin::{PluginCommand, PluginError, PluginHost, PluginView, PluginsConfig};
+pub use power::{LoadChange, LoadPriority, PowerConfig, PowerLoad, PowerManager};
+pub use preflight::{ArmError, CheckOutcome, CheckResult, CheckStatus, PreflightCheck, PreflightChecklist, PreflightConfig, PreflightReport};
+pub use protectee::{BeaconReading, EscortEnvelope, Protectee, ProtecteeConfig, ProtecteeFix};
+pub use ranging::{ProximityConfig, ProximityFeed, ProximityMap, RangeKind, RangeReading, RangeSensor, RangingError};
+pub use ring::RingBuffer;
+#[cfg(feature = "rpi-hw")]
+pub use rpi::{GpioRelay, HardwareError, HardwarePins, NozzleServos, PwmOutput, PwmPin, Relay, RelayPin, Servo, ServoPin, StrobeDriver, StrobePin, Ws2812};
+pub use schedule::TimeWindow;
+pub use settings::{Settings, SettingsError};
+pub use shutdown::{ShutdownCoordinator, ShutdownHandle, ShutdownPhase, ShutdownReport, StepOutcome, StepReport};
+#[cfg(feature = "siem")]
+pub use siem::{SiemConfig, SiemConnection, SiemError, SiemField, SiemFormat, SiemForwarder, SiemRecord, SiemTls, SiemTransport};
+#[cfg(feature = "simulation")]
+pub use simulation::{Scenario, ScenarioError, ScenarioPlayer, ScenarioReport, ScenarioRunner, Step};
+#[cfg(feature = "sitl")]
+pub use sitl::{ActuatorIntents, ActuatorSlots, NozzleIntent, SitlActuators, SitlConfig};
+pub use situation::{Situation, UnknownSituation};
+pub use store::{EventStore, StoreError};
+pub use supervisor::{ModuleHealth, ModuleReport, ModuleRestarter, ModuleResult, RestartPolicy, Supervisor};
+pub use telemetry::{
+    DeterrenceTelemetry, FireSuppressionTelemetry, FlightTelemetry, LinkQuality, LinkTelemetry, LoadDraw, PowerTelemetry, ShieldTelemetry,
+    TelemetryMessage,
+};
+#[cfg(feature = "thermal")]
+pub use thermal::{Lepton, Mlx90640, ThermalCamera, ThermalConfig, ThermalError, ThermalFeed, ThermalFrame, ThermalRegion, ThermalSensor};
+pub use threat_state::{OmegaAuthorization, ThreatStateMachine, ThreatTransition, TransitionError, TransitionRules};
+pub use units::{Bar, Celsius, Fahrenheit, Psi};
+pub use vault::{EncryptionConfig, Keyring, VaultError};
+#[cfg(feature = "ble")]
+pub use vitals::{DistressThresholds, GattClient, HeartRateMeasurement, VitalsConfig, VitalsMonitor};
+#[cfg(feature = "ble")]
+pub use wearable::{BleWearable, WearableConfig, WearableError};
+pub use watchdog::{Heartbeat, HeartbeatEvent, HeartbeatStatus, Watchdog, WatchdogAction};
+pub use webhook::{EventClass, WebhookConfig, WebhookDelivery, WebhookError, Webhooks, WebhooksConfig};
+#[cfg(feature = "binary-wire")]
+pub use wire::{EventFrame, StatusFrame, WireError, WireFormat, WireHeader, WireKind, WireSchema};
+
+/// Direction recent threat risk is heading
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskTrend {
+    Rising,
+    #[default]
+    Stable,
+    Falling,
+}
+
+/// A corrective flight manoeuvre, ordered from least to most drastic
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
-pub enum ThreatLevel {
-    /// No threats detected - all systems nominal
-    Green = 0,
-    /// Minor anomaly - increased awareness mode
-    Yellow = 1,
-    /// Moderate threat - defensive measures activated
-    Orange = 2,
-    /// High threat - all deterrence systems online
-    Red = 3,
-    /// Critical threat - lethal force authorized, maximum protection
-    Omega = 4,
+#[serde(rename_all = "snake_case")]
+pub enum FlightAction {
+    /// Stop and hold position
+    Loiter,
+    ReturnToHome,
+    /// Land where the drone is
+    Land,
 }
 
-impl ThreatLevel {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            ThreatLevel::Green => "GREEN",
-            ThreatLevel::Yellow => "YELLOW",
-            ThreatLevel::Orange => "ORANGE", 
-            ThreatLevel::Red => "RED",
-            ThreatLevel::Omega => "OMEGA",
-        }
-    }
-
-    pub fn description(&self) -> &'static str {
-        match self {
-            ThreatLevel::Green => "All systems nominal. Guardian mode active.",
-            ThreatLevel::Yellow => "Anomaly detected. Heightened awareness engaged.",
-            ThreatLevel::Orange => "Moderate threat identified. Defensive protocols online.",
-            ThreatLevel::Red => "High threat confirmed. All deterrence systems activated.",
-            ThreatLevel::Omega => "Critical threat. Dark Phoenix rising. Maximum protection authorized.",
-        }
+impl std::fmt::Display for FlightAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            FlightAction::Loiter => "loiter",
+            FlightAction::ReturnToHome => "return to home",
+            FlightAction::Land => "land",
+        })
     }
 }
 
-/// Position and movement data
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Position {
-    pub latitude: f64,
-    pub longitude: f64,
-    pub altitude: f64,
-    pub timestamp: DateTime<Utc>,
+/// An order for the flight controller
+#[derive(Debug, Clone)]
+pub enum FlightCommand {
+    /// Fly to `target` (altitude above home), facing `heading_deg` when given
+    FlyTo { target: Position, heading_deg: Option<f64> },
+    Action(FlightAction),
 }
 
-/// Vitals and health monitoring
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VitalSigns {
-    pub heart_rate: Option<u16>,
-    pub blood_oxygen: Option<u8>,
-    pub temperature: Option<f32>,
-    pub stress_level: Option<u8>, // 0-100
-    pub timestamp: DateTime<Utc>,
+impl std::fmt::Display for FlightCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FlightCommand::FlyTo { target, .. } => {
+                write!(f, "fly to {:.6}, {:.6} at {:.0} m", target.latitude, target.longitude, target.altitude)
+            },
+            FlightCommand::Action(action) => action.fmt(f),
+        }
+    }
 }
 
 /// System health status
@@ -65,10 +223,26 @@ pub struct SystemHealth {
     pub battery_level: u8,
     pub flight_time_remaining: u32, // seconds
     pub shield_integrity: u8,       // 0-100%
+    #[serde(default)]
+    pub shield_deployed: bool,
     pub fire_suppression_ready: bool,
     pub medical_supplies: u8,       // 0-100%
     pub communication_status: bool,
     pub gps_lock: bool,
+    #[serde(default)]
+    pub degraded_sensors: Vec<String>, // Key sensors that are stale, missing or unusable
+    #[serde(default)]
+    pub modules: HashMap<String, ModuleHealth>, // Supervised modules that are not running normally
+    #[serde(default)]
+    pub power_draw_w: f64,
+    #[serde(default)]
+    pub shed_loads: Vec<String>, // Switched off to save power
+    #[serde(default)]
+    pub battery_state_of_health: Option<u8>, // 0-100% of rated capacity
+    #[serde(default)]
+    pub last_ground_contact: Option<DateTime<Utc>>, // Last authorized command over a link
+    #[serde(default)]
+    pub active_link: Option<String>, // Network link telemetry goes over, when the link manager picks one
     pub timestamp: DateTime<Utc>,
 }
 
@@ -77,13 +251,46 @@ pub struct SystemHealth {
 pub struct DroneState {
     pub id: Uuid,
     pub name: String,
-    pub threat_level: ThreatLevel,
+    /// Changed only through the guarded methods below
+    threat: ThreatStateMachine,
     pub position: Position,
     pub target_vitals: Option<VitalSigns>,
+    /// Where the person being protected is, while their beacon is tracked
+    #[serde(default)]
+    pub protectee: Option<ProtecteeFix>,
     pub system_health: SystemHealth,
     pub active_modules: HashMap<String, bool>,
     pub mission_log: Vec<MissionEvent>,
     pub last_update: DateTime<Utc>,
+    /// Boundaries checked every protection cycle
+    #[serde(skip)]
+    geofence: Geofence,
+    #[serde(default)]
+    geofence_status: GeofenceStatus,
+    /// Where the next mission event joins the audit chain
+    #[serde(default)]
+    audit_head: AuditHead,
+    /// Whether fire suppression was discharging at its last report
+    #[serde(default)]
+    fire_discharging: bool,
+    /// Response modules stood down by an operator until re-armed
+    #[serde(default)]
+    disarmed: bool,
+    /// Nearest obstacle inside the minimum safe distance at the last ranging report
+    #[serde(default)]
+    obstacle: Option<RangeReading>,
+    #[serde(skip, default = "telemetry_channel")]
+    telemetry: tokio::sync::broadcast::Sender<TelemetryMessage>,
+}
+
+fn telemetry_channel() -> tokio::sync::broadcast::Sender<TelemetryMessage> {
+    tokio::sync::broadcast::channel(telemetry::TELEMETRY_BUFFER).0
+}
+
+/// Without trace export there is no trace to point at
+#[cfg(not(feature = "otel"))]
+fn current_trace_id() -> Option<String> {
+    None
 }
 
 /// Mission event logging for ceremonial record-keeping
@@ -96,20 +303,29 @@ pub struct MissionEvent {
     pub threat_level: ThreatLevel,
     pub position: Position,
     pub response_actions: Vec<String>,
+    /// OpenTelemetry trace the event was logged in, for correlating an
+    /// incident across logs, metrics and traces
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+    /// Position in the audit chain, counting from the drone's first event
+    #[serde(default)]
+    pub sequence: u64,
+    /// `MissionEvent::digest` of the event before this one (`audit::GENESIS_HASH` for the first)
+    #[serde(default)]
+    pub prev_hash: String,
+    /// Why the threat level changed, on threat level change events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<DecisionTrace>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum EventType {
-    ThreatDetected,
-    TerrenceActivated,
-    PoliceContacted,
-    ShieldDeployed,
-    FireSuppressed,
-    MedicalAidDeployed,
-    HackingAttempt,
-    SystemMalfunction,
-    MissionComplete,
-    PhoenixRising, // Special ceremonial event
+impl MissionEvent {
+    /// SHA-256 (hex) of the event's JSON line, which the next event carries
+    /// as its `prev_hash`
+    pub fn digest(&self) -> String {
+        // A struct of strings, numbers and enums always serializes
+        let line = serde_json::to_vec(self).unwrap_or_default();
+        hex::encode(sha2::Sha256::digest(&line))
+    }
 }
 
 impl DroneState {
@@ -117,7 +333,7 @@ impl DroneState {
         Self {
             id: Uuid::new_v4(),
             name,
-            threat_level: ThreatLevel::Green,
+            threat: ThreatStateMachine::default(),
             position: Position {
                 latitude: 0.0,
                 longitude: 0.0,
@@ -125,61 +341,445 @@ impl DroneState {
                 timestamp: Utc::now(),
             },
             target_vitals: None,
+            protectee: None,
             system_health: SystemHealth {
                 battery_level: 100,
                 flight_time_remaining: 3600, // 1 hour
                 shield_integrity: 100,
+                shield_deployed: false,
                 fire_suppression_ready: true,
                 medical_supplies: 100,
                 communication_status: true,
                 gps_lock: true,
+                degraded_sensors: Vec::new(),
+                modules: HashMap::new(),
+                power_draw_w: 0.0,
+                shed_loads: Vec::new(),
+                battery_state_of_health: None,
+                last_ground_contact: None,
+                active_link: None,
                 timestamp: Utc::now(),
             },
             active_modules: HashMap::new(),
             mission_log: Vec::new(),
             last_update: Utc::now(),
+            geofence: Geofence::default(),
+            geofence_status: GeofenceStatus::Inside,
+            audit_head: AuditHead::default(),
+            fire_discharging: false,
+            disarmed: false,
+            obstacle: None,
+            telemetry: telemetry_channel(),
         }
     }
 
+    /// Continue an audit chain persisted by an earlier run, e.g. from
+    /// `AuditLog::head`, so the new events link onto it
+    pub fn with_audit_head(mut self, head: AuditHead) -> Self {
+        self.audit_head = head;
+        self
+    }
+
+    pub fn audit_head(&self) -> &AuditHead {
+        &self.audit_head
+    }
+
+    /// Apply site-specific threat transition rules, e.g. from `Settings`
+    pub fn with_transition_rules(mut self, rules: TransitionRules) -> Self {
+        self.threat = ThreatStateMachine::new(rules);
+        self
+    }
+
+    /// Enforce site boundaries, e.g. from `Settings`
+    pub fn with_geofence(mut self, geofence: Geofence) -> Self {
+        self.geofence = geofence;
+        self
+    }
+
     /// Log a mission event with ceremonial significance
     pub fn log_event(&mut self, event_type: EventType, description: String, response_actions: Vec<String>) {
+        self.record_event(event_type, description, response_actions, None);
+    }
+
+    fn record_event(&mut self, event_type: EventType, description: String, response_actions: Vec<String>, decision: Option<DecisionTrace>) {
         let event = MissionEvent {
             id: Uuid::new_v4(),
             timestamp: Utc::now(),
             event_type,
             description,
-            threat_level: self.threat_level,
+            threat_level: self.threat.level(),
             position: self.position.clone(),
             response_actions,
+            trace_id: current_trace_id(),
+            sequence: self.audit_head.sequence,
+            prev_hash: self.audit_head.hash.clone(),
+            decision,
+        };
+        self.audit_head = AuditHead {
+            sequence: event.sequence + 1,
+            hash: event.digest(),
         };
-        
+
+        self.publish(TelemetryMessage::Event(event.clone()));
         self.mission_log.push(event);
         self.last_update = Utc::now();
     }
 
-    /// Escalate threat level with proper ceremonial protocol
+    pub fn threat_level(&self) -> ThreatLevel {
+        self.threat.level()
+    }
+
+    pub fn threat_state(&self) -> &ThreatStateMachine {
+        &self.threat
+    }
+
+    /// Every threat level change from now on, for modules that react to it
+    pub fn subscribe_threat_transitions(&self) -> tokio::sync::broadcast::Receiver<ThreatTransition> {
+        self.threat.subscribe()
+    }
+
+    /// Escalate threat level with proper ceremonial protocol; without an
+    /// Omega authorization the drone holds at Red
     pub fn escalate_threat(&mut self, new_level: ThreatLevel, reason: String) {
-        if new_level > self.threat_level {
-            self.threat_level = new_level;
-            self.log_event(
+        if new_level <= self.threat.level() {
+            return;
+        }
+        let now = Utc::now();
+        let transition = match self.threat.request(new_level, &reason, now) {
+            Err(TransitionError::OmegaNotAuthorized) if self.threat.level() < ThreatLevel::Red => self
+                .threat
+                .request(ThreatLevel::Red, &format!("{} (Omega not authorized)", reason), now)
+                .ok(),
+            result => result.ok(),
+        };
+        if let Some(transition) = transition {
+            self.log_transition(&transition);
+        }
+    }
+
+    /// Follow a fresh assessment: escalate at once, but only step down one
+    /// level per quiet period and never while risk is rising, so a brief lull
+    /// in a developing threat does not stand the drone down. `cause` is the
+    /// assessment's decision trace, kept with any change it brings.
+    pub fn update_threat(&mut self, assessed: ThreatLevel, trend: RiskTrend, reason: String, cause: &DecisionTrace) {
+        if let Some(transition) = self.threat.observe(assessed, trend, &reason, cause, Utc::now()) {
+            self.log_transition(&transition);
+        }
+    }
+
+    /// Operator-requested level change, subject to the transition rules
+    pub fn request_threat_level(&mut self, level: ThreatLevel, reason: String) -> Result<(), TransitionError> {
+        let transition = self.threat.request(level, &reason, Utc::now())?;
+        self.log_transition(&transition);
+        Ok(())
+    }
+
+    /// Drop straight back to `level` after a false alarm, e.g. a cancelled
+    /// panic, bypassing the de-escalation rules; the override is logged
+    pub fn cancel_false_alarm(&mut self, level: ThreatLevel, reason: String) -> Result<(), TransitionError> {
+        let transition = self.threat.cancel_false_alarm(level, &reason, Utc::now())?;
+        self.log_transition(&transition);
+        Ok(())
+    }
+
+    /// Allow Omega for the authorization window
+    pub fn authorize_omega(&mut self, authorized_by: &str) {
+        let expires_at = self.threat.authorize_omega(authorized_by, Utc::now()).expires_at;
+        self.log_event(
+            EventType::OmegaAuthorized,
+            format!("Omega authorized by {} until {}", authorized_by, expires_at.format("%H:%M:%S UTC")),
+            Vec::new(),
+        );
+    }
+
+    pub fn revoke_omega(&mut self) {
+        if let Some(transition) = self.threat.revoke_omega(Utc::now()) {
+            self.log_transition(&transition);
+        }
+    }
+
+    /// An operator has seen the current threat; the level is left as it is
+    pub fn acknowledge_threat(&mut self, operator: &str, note: &str) {
+        let mut description = format!("{} threat acknowledged by {}", self.threat.level().as_str(), operator);
+        if !note.is_empty() {
+            description.push_str(&format!(": {}", note));
+        }
+        self.log_event(EventType::ThreatAcknowledged, description, Vec::new());
+    }
+
+    /// Arm or disarm the response modules; while disarmed they stand down
+    /// whatever the threat level
+    pub fn set_armed(&mut self, armed: bool) {
+        if armed == self.is_armed() {
+            return;
+        }
+        self.disarmed = !armed;
+        if armed {
+            self.log_event(EventType::Armed, "Response modules armed".to_string(), Vec::new());
+        } else {
+            self.log_event(EventType::Disarmed, "Response modules disarmed".to_string(), vec!["Deterrence and fire suppression stood down".to_string()]);
+        }
+    }
+
+    pub fn is_armed(&self) -> bool {
+        !self.disarmed
+    }
+
+    fn log_transition(&mut self, transition: &ThreatTransition) {
+        self.publish(TelemetryMessage::ThreatTransition(transition.clone()));
+        let (event_type, description) = if transition.is_escalation() {
+            (
                 EventType::ThreatDetected,
-                format!("Threat level escalated to {}: {}", new_level.as_str(), reason),
-                vec![format!("Threat assessment: {}", new_level.description())],
+                format!("Threat level escalated to {}: {}", transition.to.as_str(), transition.reason),
+            )
+        } else {
+            (
+                EventType::ThreatDeEscalated,
+                format!(
+                    "Threat level lowered from {} to {}: {}",
+                    transition.from.as_str(),
+                    transition.to.as_str(),
+                    transition.reason
+                ),
+            )
+        };
+        let decision = (!transition.trace.is_empty()).then(|| transition.trace.clone());
+        self.record_event(
+            event_type,
+            description,
+            vec![format!("Threat assessment: {}", transition.to.description())],
+            decision,
+        );
+    }
+
+    /// Record which key sensors threat detection is running without
+    pub fn update_sensor_health(&mut self, degraded: Vec<String>) {
+        if degraded == self.system_health.degraded_sensors {
+            return;
+        }
+        if degraded.is_empty() {
+            self.log_event(EventType::SensorRestored, "All key sensors restored".to_string(), Vec::new());
+        } else {
+            self.log_event(
+                EventType::SensorDegraded,
+                format!("Threat detection degraded: {} unavailable", degraded.join(", ")),
+                vec!["Detection confidence capped".to_string()],
             );
         }
+        self.system_health.degraded_sensors = degraded;
+        self.system_health.timestamp = Utc::now();
+        self.publish(TelemetryMessage::Health(self.system_health.clone()));
+    }
+
+    /// Record a supervised module flapping, hanging, failing for good or recovering
+    pub fn update_module_health(&mut self, module: &str, health: ModuleHealth) {
+        let previous = match health {
+            ModuleHealth::Running | ModuleHealth::Stopped => self.system_health.modules.remove(module),
+            _ => self.system_health.modules.insert(module.to_string(), health),
+        };
+        if previous == Some(health) {
+            return;
+        }
+        match health {
+            ModuleHealth::Degraded => tracing::warn!("⚠️ Module '{}' degraded", module),
+            ModuleHealth::Failed => tracing::error!("💀 Module '{}' failed", module),
+            ModuleHealth::Unresponsive => tracing::error!("🐕 Module '{}' missed its heartbeat", module),
+            _ if previous.is_some() => tracing::info!("Module '{}' recovered", module),
+            _ => {},
+        }
+        self.system_health.timestamp = Utc::now();
+        self.publish(TelemetryMessage::ModuleHealth {
+            module: module.to_string(),
+            health,
+            timestamp: self.system_health.timestamp,
+        });
+    }
+
+    /// Latest risk score from threat detection, for dashboards
+    pub fn report_risk(&self, score: f32) {
+        self.publish(TelemetryMessage::Risk {
+            score: score.clamp(0.0, 1.0),
+            timestamp: Utc::now(),
+        });
+    }
+
+    /// Latest extinguisher state from the fire suppression module; the
+    /// start of a discharge goes into the mission log
+    pub fn report_fire_suppression(&mut self, status: FireSuppressionTelemetry) {
+        self.system_health.fire_suppression_ready = status.armed && status.capacity > 0.0;
+        if status.discharging && !self.fire_discharging {
+            self.log_event(
+                EventType::FireSuppressed,
+                "Fire suppression discharging".to_string(),
+                vec![format!("{:.0}% agent left at {:.0} psi", status.capacity, status.pressure.0)],
+            );
+        }
+        self.fire_discharging = status.discharging;
+        self.publish(TelemetryMessage::FireSuppression(status));
+    }
+
+    /// Latest output state from the deterrence module
+    pub fn report_deterrence(&self, status: DeterrenceTelemetry) {
+        self.publish(TelemetryMessage::Deterrence(status));
+    }
+
+    /// Latest shield position and integrity from the shield module
+    pub fn report_shield(&mut self, status: ShieldTelemetry) {
+        let deployed = status.deployed && !self.system_health.shield_deployed;
+        let changed = status.deployed != self.system_health.shield_deployed || status.integrity != self.system_health.shield_integrity;
+        self.system_health.shield_deployed = status.deployed;
+        self.system_health.shield_integrity = status.integrity;
+        if deployed {
+            self.log_event(
+                EventType::ShieldDeployed,
+                format!("Shield deployed at {:.0}° ({}% integrity)", status.angle_deg, status.integrity),
+                Vec::new(),
+            );
+        }
+        if changed {
+            self.system_health.timestamp = Utc::now();
+            self.publish(TelemetryMessage::Health(self.system_health.clone()));
+        }
+        self.publish(TelemetryMessage::Shield(status));
+    }
+
+    /// Nearest obstacle inside the minimum safe distance, from `ProximityFeed::report_to`;
+    /// its arrival and clearing go into the mission log
+    pub fn report_obstacle(&mut self, obstacle: Option<RangeReading>) {
+        match (self.obstacle.is_some(), &obstacle) {
+            (false, Some(reading)) => self.log_event(
+                EventType::ObstacleDetected,
+                format!("{:?} obstacle {:.1} m away at {:.0}°", reading.kind, reading.distance_m, reading.bearing_deg),
+                vec!["Hold discharge and shield deployment".to_string()],
+            ),
+            (true, None) => self.log_event(EventType::ObstacleCleared, "Obstacles clear of the safe distance".to_string(), Vec::new()),
+            _ => {},
+        }
+        self.obstacle = obstacle;
+    }
+
+    pub fn nearest_obstacle(&self) -> Option<&RangeReading> {
+        self.obstacle.as_ref()
+    }
+
+    /// Latest position, battery and fix from the flight controller
+    pub fn report_flight(&mut self, status: FlightTelemetry) {
+        if let Some(position) = &status.position {
+            self.position = position.clone();
+        }
+        let health = &mut self.system_health;
+        let battery_level = status.battery_level.unwrap_or(health.battery_level);
+        let changed = battery_level != health.battery_level || status.gps_lock != health.gps_lock;
+        health.battery_level = battery_level;
+        health.gps_lock = status.gps_lock;
+        if changed {
+            health.timestamp = Utc::now();
+            self.publish(TelemetryMessage::Health(self.system_health.clone()));
+        }
+        self.publish(TelemetryMessage::Flight(status));
+    }
+
+    /// Latest power budget from the power manager
+    pub fn report_power(&mut self, status: PowerTelemetry) {
+        let health = &mut self.system_health;
+        let shed_loads: Vec<String> = status.loads.iter().filter(|load| load.shed).map(|load| load.name.clone()).collect();
+        // Only news is published; the budget is recomputed every cycle
+        let changed = status.battery_level != health.battery_level || shed_loads != health.shed_loads;
+        let draw_changed = (status.draw_w - health.power_draw_w).abs() >= 1.0;
+        health.battery_level = status.battery_level;
+        health.flight_time_remaining = status.flight_time_remaining;
+        health.power_draw_w = status.draw_w;
+        health.shed_loads = shed_loads;
+        if changed {
+            health.timestamp = Utc::now();
+            self.publish(TelemetryMessage::Health(self.system_health.clone()));
+        }
+        if changed || draw_changed {
+            self.publish(TelemetryMessage::Power(status));
+        }
+    }
+
+    /// Latest link assessment from the link manager
+    pub fn report_links(&mut self, status: LinkTelemetry) {
+        if status.active != self.system_health.active_link {
+            self.system_health.active_link = status.active.clone();
+            self.system_health.timestamp = Utc::now();
+            self.publish(TelemetryMessage::Health(self.system_health.clone()));
+        }
+        self.publish(TelemetryMessage::Links(status));
+    }
+
+    /// Battery state of health (0-100%), from `BatteryHealth::update`
+    pub fn report_battery_health(&mut self, state_of_health: u8) {
+        let health = &mut self.system_health;
+        if health.battery_state_of_health == Some(state_of_health) {
+            return;
+        }
+        health.battery_state_of_health = Some(state_of_health);
+        health.timestamp = Utc::now();
+        self.publish(TelemetryMessage::Health(self.system_health.clone()));
+    }
+
+    pub fn geofence(&self) -> &Geofence {
+        &self.geofence
+    }
+
+    pub fn geofence_status(&self) -> &GeofenceStatus {
+        &self.geofence_status
+    }
+
+    /// Check the current position against the geofence; returns the
+    /// corrective action when the drone has just moved closer to or across a
+    /// boundary, and `None` while it holds or comes back
+    pub fn check_geofence(&mut self) -> Option<FlightAction> {
+        if self.geofence.is_empty() || !self.system_health.gps_lock {
+            return None;
+        }
+        let status = self.geofence.check(&self.position);
+        let previous = std::mem::replace(&mut self.geofence_status, status.clone());
+        if status.severity() <= previous.severity() {
+            if status == GeofenceStatus::Inside && previous != GeofenceStatus::Inside {
+                tracing::info!("🚧 Back inside the geofence");
+            }
+            return None;
+        }
+        let action = self.geofence.action_for(&status)?;
+        let description = match &status {
+            GeofenceStatus::Approaching { boundary, distance_m } => {
+                format!("Approaching geofence {}: {:.1} m from the boundary", boundary, distance_m)
+            },
+            GeofenceStatus::Breached { boundary, distance_m } => {
+                format!("Geofence {} crossed by {:.1} m", boundary, distance_m)
+            },
+            GeofenceStatus::Inside => return None,
+        };
+        tracing::warn!("🚧 {}", description);
+        self.log_event(EventType::GeofenceBreach, description, vec![format!("Corrective action: {}", action)]);
+        Some(action)
+    }
+
+    /// Mission events, threat transitions and health changes as they happen
+    pub fn subscribe_telemetry(&self) -> tokio::sync::broadcast::Receiver<TelemetryMessage> {
+        self.telemetry.subscribe()
+    }
+
+    fn publish(&self, message: TelemetryMessage) {
+        // No subscribers is fine - nobody is watching yet
+        let _ = self.telemetry.send(message);
     }
 
     /// Check if the drone is in a critical state requiring immediate intervention
     pub fn is_critical(&self) -> bool {
-        self.threat_level >= ThreatLevel::Red || 
+        self.threat.level() >= ThreatLevel::Red || 
         self.system_health.battery_level < 20 ||
         !self.system_health.communication_status ||
-        self.system_health.shield_integrity < 50
+        self.system_health.shield_integrity < 50 ||
+        self.system_health.modules.values().any(|health| *health == ModuleHealth::Failed)
     }
 
     /// Generate mythic status report
     pub fn mythic_status(&self) -> String {
-        let status_emoji = match self.threat_level {
+        let status_emoji = match self.threat.level() {
             ThreatLevel::Green => "🕊️",
             ThreatLevel::Yellow => "⚠️",
             ThreatLevel::Orange => "🔥",
@@ -191,12 +791,12 @@ impl DroneState {
             "{} Dark Phoenix {} - Status: {} {}\nBattery: {}% | Shield: {}% | Flight Time: {}min\n{}",
             status_emoji,
             self.name,
-            self.threat_level.as_str(),
+            self.threat.level().as_str(),
             status_emoji,
             self.system_health.battery_level,
             self.system_health.shield_integrity,
             self.system_health.flight_time_remaining / 60,
-            self.threat_level.description()
+            self.threat.level().description()
         )
     }
 }
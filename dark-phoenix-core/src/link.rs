@@ -0,0 +1,185 @@
+//! Command link monitoring and what the drone does while it is cut off
+
+use crate::{DroneState, EventType, FlightAction};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// State of the command link, by time since the last contact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkStatus {
+    #[default]
+    Up,
+    Degraded,
+    Lost,
+}
+
+impl fmt::Display for LinkStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LinkStatus::Up => "up",
+            LinkStatus::Degraded => "degraded",
+            LinkStatus::Lost => "lost",
+        })
+    }
+}
+
+/// How the drone flies once the link is lost, until it returns home
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkLossFlight {
+    /// Carry on with the patrol or escort
+    ContinuePatrol,
+    /// Stop and hover where the link was lost
+    HoldPosition,
+}
+
+impl fmt::Display for LinkLossFlight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LinkLossFlight::ContinuePatrol => "continue patrol",
+            LinkLossFlight::HoldPosition => "hold position",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkConfig {
+    /// Silence after which the link is reported degraded
+    pub degraded_secs: u64,
+    /// Silence after which the link is lost and the policy applies
+    pub lost_secs: u64,
+    pub on_loss: LinkLossFlight,
+    /// Silence the siren while nobody can call it off
+    pub restrain_deterrence: bool,
+    /// How long after the last contact to return home (absent = never)
+    pub return_home_after_mins: Option<u64>,
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        Self {
+            degraded_secs: 5,
+            lost_secs: 15,
+            on_loss: LinkLossFlight::ContinuePatrol,
+            restrain_deterrence: true,
+            return_home_after_mins: Some(5),
+        }
+    }
+}
+
+impl LinkConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.lost_secs == 0 {
+            problems.push("link.lost_secs must be positive".to_string());
+        }
+        if self.degraded_secs > self.lost_secs {
+            problems.push(format!("link.degraded_secs {} is above lost_secs {}", self.degraded_secs, self.lost_secs));
+        }
+        problems
+    }
+
+    /// The policy as it goes into the mission log
+    fn describe(&self) -> Vec<String> {
+        let mut policy = vec![format!("Flight: {}", self.on_loss)];
+        if self.restrain_deterrence {
+            policy.push("Deterrence: siren silenced, strobe and voice only".to_string());
+        }
+        if let Some(mins) = self.return_home_after_mins {
+            policy.push(format!("Return home after {} min without contact", mins));
+        }
+        policy
+    }
+}
+
+/// Tracks the command link across protection cycles and applies the
+/// comms-loss policy
+#[derive(Debug, Clone)]
+pub struct LinkMonitor {
+    config: LinkConfig,
+    status: LinkStatus,
+    /// Action taken under the policy since the link was lost
+    engaged: Option<FlightAction>,
+}
+
+impl LinkMonitor {
+    pub fn new(config: LinkConfig) -> Self {
+        Self {
+            config,
+            status: LinkStatus::Up,
+            engaged: None,
+        }
+    }
+
+    pub fn status(&self) -> LinkStatus {
+        self.status
+    }
+
+    /// Action taken under the comms-loss policy, while it is in force
+    pub fn engaged(&self) -> Option<FlightAction> {
+        self.engaged
+    }
+
+    /// Whether deterrence modules should keep the siren silent
+    pub fn restrains_deterrence(&self) -> bool {
+        self.status == LinkStatus::Lost && self.config.restrain_deterrence
+    }
+
+    /// Check the time since the last contact, keeping
+    /// `communication_status` current; returns the action the policy calls
+    /// for now
+    pub fn check(&mut self, drone: &mut DroneState, now: DateTime<Utc>) -> Option<FlightAction> {
+        let last_contact = drone.system_health.last_ground_contact?;
+        let silent = now - last_contact;
+        let status = if silent >= Duration::seconds(self.config.lost_secs as i64) {
+            LinkStatus::Lost
+        } else if silent >= Duration::seconds(self.config.degraded_secs as i64) {
+            LinkStatus::Degraded
+        } else {
+            LinkStatus::Up
+        };
+        let previous = std::mem::replace(&mut self.status, status);
+        drone.system_health.communication_status = status != LinkStatus::Lost;
+
+        match (previous, status) {
+            (LinkStatus::Up, LinkStatus::Degraded) => tracing::warn!("📡 Command link degraded: nothing for {}s", silent.num_seconds()),
+            (LinkStatus::Lost, LinkStatus::Lost) => {},
+            (_, LinkStatus::Lost) => {
+                tracing::warn!("📡 Command link lost: nothing for {}s, flying autonomously ({})", silent.num_seconds(), self.config.on_loss);
+                drone.log_event(
+                    EventType::LinkLost,
+                    format!("Command link lost: no contact since {}", last_contact.format("%H:%M:%S UTC")),
+                    self.config.describe(),
+                );
+                if self.config.on_loss == LinkLossFlight::HoldPosition {
+                    self.engaged = Some(FlightAction::Loiter);
+                    return self.engaged;
+                }
+            },
+            (LinkStatus::Lost, _) => {
+                tracing::info!("📡 Command link restored, autonomous policy lifted");
+                drone.log_event(EventType::LinkRestored, "Command link restored".to_string(), vec!["Autonomous policy lifted".to_string()]);
+                self.engaged = None;
+            },
+            (LinkStatus::Degraded, LinkStatus::Up) => tracing::info!("📡 Command link recovered"),
+            _ => {},
+        }
+
+        let return_home = self.config.return_home_after_mins.map(|mins| Duration::minutes(mins as i64));
+        if status == LinkStatus::Lost && return_home.is_some_and(|after| silent >= after) && self.engaged < Some(FlightAction::ReturnToHome) {
+            tracing::warn!("📡 No contact for {} min: returning home", silent.num_minutes());
+            drone.log_event(
+                EventType::FailsafeEngaged,
+                format!("Command link lost for {} min", silent.num_minutes()),
+                vec![format!("Corrective action: {}", FlightAction::ReturnToHome)],
+            );
+            self.engaged = Some(FlightAction::ReturnToHome);
+            return self.engaged;
+        }
+        None
+    }
+}
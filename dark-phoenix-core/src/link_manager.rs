@@ -0,0 +1,441 @@
+//! Choosing between the drone's network links: Wi-Fi, cellular and satellite
+
+use crate::{DroneState, EventType, LinkQuality, LinkTelemetry, ModuleResult, RingBuffer};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpSocket;
+use tokio::sync::{watch, RwLock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportKind {
+    Wifi,
+    Cellular,
+    /// e.g. Iridium; slow and paid by the byte
+    Satellite,
+}
+
+impl fmt::Display for TransportKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TransportKind::Wifi => "Wi-Fi",
+            TransportKind::Cellular => "cellular",
+            TransportKind::Satellite => "satellite",
+        })
+    }
+}
+
+/// One network link and how to reach the ground through it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransportConfig {
+    /// e.g. `wifi`, `lte`, `iridium`
+    pub name: String,
+    pub kind: TransportKind,
+    /// Network device to send from, e.g. `wlan0` (needs CAP_NET_RAW)
+    #[serde(default)]
+    pub interface: Option<String>,
+    /// Local address to send from, e.g. the modem's
+    #[serde(default)]
+    pub local_address: Option<IpAddr>,
+    /// `host:port` the probes connect to, e.g. the MQTT broker
+    pub probe: String,
+    /// Multiplies the score, so a metered link is only used when it is
+    /// clearly better
+    #[serde(default = "default_cost")]
+    pub cost: f64,
+    /// Carry emergency notifications only, never routine telemetry
+    #[serde(default)]
+    pub emergency_only: bool,
+}
+
+fn default_cost() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkManagerConfig {
+    /// Empty = the system's default route for everything
+    pub transports: Vec<TransportConfig>,
+    pub probe_interval_secs: u64,
+    pub probe_timeout_ms: u64,
+    /// Probes the latency and loss are taken over
+    pub window: usize,
+    /// Probes needed before a link is scored
+    pub min_samples: usize,
+    /// Share of probes lost at which a link is degraded
+    pub degraded_loss: f64,
+    /// Share of probes lost at which a link is down
+    pub down_loss: f64,
+    /// Mean latency above which a link is degraded
+    pub degraded_latency_ms: f64,
+    /// How much better (0.25 = a quarter lower score) another link must be
+    /// before telemetry moves to it
+    pub switch_margin: f64,
+}
+
+impl Default for LinkManagerConfig {
+    fn default() -> Self {
+        Self {
+            transports: Vec::new(),
+            probe_interval_secs: 5,
+            probe_timeout_ms: 2000,
+            window: 12,
+            min_samples: 3,
+            degraded_loss: 0.2,
+            down_loss: 0.6,
+            degraded_latency_ms: 1500.0,
+            switch_margin: 0.25,
+        }
+    }
+}
+
+impl LinkManagerConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (i, transport) in self.transports.iter().enumerate() {
+            if transport.name.trim().is_empty() {
+                problems.push(format!("links.transports[{}] has no name", i));
+            } else if self.transports[..i].iter().any(|other| other.name == transport.name) {
+                problems.push(format!("links.transports has '{}' twice", transport.name));
+            }
+            if !transport.probe.contains(':') {
+                problems.push(format!("links transport '{}' probe '{}' must be host:port", transport.name, transport.probe));
+            }
+            if transport.cost.is_nan() || transport.cost <= 0.0 {
+                problems.push(format!("links transport '{}' cost must be positive", transport.name));
+            }
+        }
+        if !self.transports.is_empty() && self.transports.iter().all(|transport| transport.emergency_only) {
+            problems.push("links.transports are all emergency_only; telemetry has no link".to_string());
+        }
+        if self.probe_interval_secs == 0 {
+            problems.push("links.probe_interval_secs must be positive".to_string());
+        }
+        if self.min_samples == 0 || self.min_samples > self.window {
+            problems.push(format!("links.min_samples {} must be between 1 and window {}", self.min_samples, self.window));
+        }
+        if !(0.0..=1.0).contains(&self.degraded_loss) || !(0.0..=1.0).contains(&self.down_loss) || self.degraded_loss > self.down_loss {
+            problems.push(format!("links.degraded_loss {} and down_loss {} must be fractions, degraded first", self.degraded_loss, self.down_loss));
+        }
+        if !(0.0..1.0).contains(&self.switch_margin) {
+            problems.push(format!("links.switch_margin {} must be at least 0 and below 1", self.switch_margin));
+        }
+        problems
+    }
+}
+
+/// How a link is doing over the last `window` probes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportState {
+    Up,
+    Degraded,
+    Down,
+    /// Not probed often enough yet
+    #[default]
+    Unknown,
+}
+
+impl fmt::Display for TransportState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TransportState::Up => "up",
+            TransportState::Degraded => "degraded",
+            TransportState::Down => "down",
+            TransportState::Unknown => "unknown",
+        })
+    }
+}
+
+/// What a route is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrafficClass {
+    Telemetry,
+    Emergency,
+}
+
+/// Where to send a class of traffic from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkRoute {
+    pub name: String,
+    pub kind: TransportKind,
+    pub interface: Option<String>,
+    pub local_address: Option<IpAddr>,
+}
+
+impl LinkRoute {
+    fn of(config: &TransportConfig) -> Self {
+        Self {
+            name: config.name.clone(),
+            kind: config.kind,
+            interface: config.interface.clone(),
+            local_address: config.local_address,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Transport {
+    config: TransportConfig,
+    /// Round trip of each probe, `None` where it failed
+    probes: RingBuffer<Option<Duration>>,
+    state: TransportState,
+}
+
+impl Transport {
+    fn loss(&self) -> f64 {
+        if self.probes.is_empty() {
+            return 0.0;
+        }
+        self.probes.iter().filter(|probe| probe.is_none()).count() as f64 / self.probes.len() as f64
+    }
+
+    fn latency_ms(&self) -> Option<f64> {
+        let answered: Vec<f64> = self.probes.iter().flatten().map(|rtt| rtt.as_secs_f64() * 1000.0).collect();
+        (!answered.is_empty()).then(|| answered.iter().sum::<f64>() / answered.len() as f64)
+    }
+
+    fn assess(&self, config: &LinkManagerConfig) -> TransportState {
+        if self.probes.len() < config.min_samples {
+            return TransportState::Unknown;
+        }
+        let loss = self.loss();
+        if loss >= config.down_loss {
+            TransportState::Down
+        } else if loss >= config.degraded_loss || self.latency_ms().is_some_and(|latency| latency > config.degraded_latency_ms) {
+            TransportState::Degraded
+        } else {
+            TransportState::Up
+        }
+    }
+
+    /// Lower is better; `None` while the link cannot carry traffic
+    fn score(&self) -> Option<f64> {
+        if !matches!(self.state, TransportState::Up | TransportState::Degraded) {
+            return None;
+        }
+        Some(self.latency_ms()? * (1.0 + 10.0 * self.loss()) * self.config.cost)
+    }
+
+    fn quality(&self) -> LinkQuality {
+        LinkQuality {
+            name: self.config.name.clone(),
+            kind: self.config.kind,
+            state: self.state,
+            latency_ms: self.latency_ms(),
+            loss: self.loss() as f32,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self.latency_ms() {
+            Some(latency) => format!("{} ({}): {}, {:.0} ms, {:.0}% lost", self.config.name, self.config.kind, self.state, latency, self.loss() * 100.0),
+            None => format!("{} ({}): {}", self.config.name, self.config.kind, self.state),
+        }
+    }
+}
+
+/// Scores the configured links and picks the ones traffic goes over
+#[derive(Debug)]
+pub struct LinkManager {
+    config: LinkManagerConfig,
+    transports: Vec<Transport>,
+    /// Index of the link telemetry goes over
+    telemetry: Option<usize>,
+    routes: watch::Sender<Option<LinkRoute>>,
+}
+
+impl LinkManager {
+    pub fn new(config: LinkManagerConfig) -> Self {
+        let transports = config
+            .transports
+            .iter()
+            .map(|transport| Transport {
+                config: transport.clone(),
+                probes: RingBuffer::new(config.window),
+                state: TransportState::Unknown,
+            })
+            .collect();
+        Self {
+            config,
+            transports,
+            telemetry: None,
+            routes: watch::channel(None).0,
+        }
+    }
+
+    pub fn config(&self) -> &LinkManagerConfig {
+        &self.config
+    }
+
+    /// Record one probe of `name`: its round trip, or `None` if it failed
+    pub fn record(&mut self, name: &str, rtt: Option<Duration>) {
+        if let Some(transport) = self.transports.iter_mut().find(|transport| transport.config.name == name) {
+            transport.probes.push(rtt);
+        }
+    }
+
+    /// Reassess every link and move telemetry if it calls for it, logging
+    /// failovers and publishing the links' quality when anything changed;
+    /// returns whether telemetry moved
+    pub fn check(&mut self, drone: &mut DroneState, now: DateTime<Utc>) -> bool {
+        let mut changed = false;
+        for transport in &mut self.transports {
+            let state = transport.assess(&self.config);
+            if state != transport.state {
+                tracing::info!("📡 {} link {} is {}", transport.config.kind, transport.config.name, state);
+                transport.state = state;
+                changed = true;
+            }
+        }
+
+        let previous = self.telemetry;
+        self.telemetry = self.pick_telemetry();
+        let moved = self.telemetry != previous;
+        if moved {
+            self.log_failover(drone, previous);
+            self.routes.send_replace(self.route(TrafficClass::Telemetry));
+        }
+        if changed || moved {
+            drone.report_links(LinkTelemetry {
+                active: self.telemetry.map(|i| self.transports[i].config.name.clone()),
+                links: self.links(),
+                timestamp: now,
+            });
+        }
+        moved
+    }
+
+    /// Where to send `class` from; `None` = the system's default route
+    pub fn route(&self, class: TrafficClass) -> Option<LinkRoute> {
+        match class {
+            TrafficClass::Telemetry => self.telemetry.map(|i| LinkRoute::of(&self.transports[i].config)),
+            // Any link that gets through will do, however much it costs
+            TrafficClass::Emergency => self
+                .best(|_| true)
+                .or(self.telemetry)
+                .map(|i| LinkRoute::of(&self.transports[i].config)),
+        }
+    }
+
+    /// Follows the telemetry route as it changes
+    pub fn subscribe(&self) -> watch::Receiver<Option<LinkRoute>> {
+        self.routes.subscribe()
+    }
+
+    /// Every link's state, latency and loss
+    pub fn links(&self) -> Vec<LinkQuality> {
+        self.transports.iter().map(Transport::quality).collect()
+    }
+
+    /// Lowest-scoring link among those `eligible`, preferring any that is up
+    fn best(&self, eligible: impl Fn(&Transport) -> bool) -> Option<usize> {
+        self.transports
+            .iter()
+            .enumerate()
+            .filter(|(_, transport)| eligible(transport))
+            .filter_map(|(i, transport)| transport.score().map(|score| (i, transport.state, score)))
+            .min_by(|a, b| a.1.cmp(&b.1).then(a.2.total_cmp(&b.2)))
+            .map(|(i, _, _)| i)
+    }
+
+    fn pick_telemetry(&self) -> Option<usize> {
+        let best = self.best(|transport| !transport.config.emergency_only);
+        // With every link down the default route may still get through
+        let (Some(current), Some(best)) = (self.telemetry, best) else { return best };
+        let (current_link, best_link) = (&self.transports[current], &self.transports[best]);
+        let Some(current_score) = current_link.score() else { return Some(best) };
+        let best_score = best_link.score().unwrap_or(f64::INFINITY);
+        if best_link.state < current_link.state || best_score < current_score * (1.0 - self.config.switch_margin) {
+            Some(best)
+        } else {
+            Some(current)
+        }
+    }
+
+    fn log_failover(&self, drone: &mut DroneState, previous: Option<usize>) {
+        let name = |i: Option<usize>| i.map_or("the default route".to_string(), |i| self.transports[i].config.name.clone());
+        let (from, to) = (name(previous), name(self.telemetry));
+        let description = match previous {
+            Some(previous) => format!("Telemetry moved from {} ({}) to {}", from, self.transports[previous].state, to),
+            None => format!("Telemetry going over {}", to),
+        };
+        tracing::warn!("📡 {}", description);
+        drone.log_event(EventType::LinkFailover, description, self.transports.iter().map(Transport::describe).collect());
+    }
+}
+
+/// Time a TCP connect to `transport`'s probe target out of its interface;
+/// `None` if it failed or took longer than `timeout`
+pub async fn probe(transport: &TransportConfig, timeout: Duration) -> Option<Duration> {
+    let started = Instant::now();
+    let connected = crate::runtime::timeout(timeout, async {
+        let target = tokio::net::lookup_host(&transport.probe)
+            .await?
+            .find(|target| transport.local_address.is_none_or(|local| local.is_ipv4() == target.is_ipv4()))
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no address for the probe target"))?;
+        let socket = if target.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+        #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+        if let Some(interface) = &transport.interface {
+            socket.bind_device(Some(interface.as_bytes()))?;
+        }
+        if let Some(local) = transport.local_address {
+            socket.bind(SocketAddr::new(local, 0))?;
+        }
+        socket.connect(target).await
+    })
+    .await;
+    match connected {
+        Ok(Ok(_)) => Some(started.elapsed()),
+        Ok(Err(e)) => {
+            tracing::debug!("📡 Probe of {} over {} failed: {}", transport.probe, transport.name, e);
+            None
+        },
+        Err(_) => None,
+    }
+}
+
+/// Probe every link on the interval and keep the routes current; runs until
+/// aborted
+pub async fn run(manager: Arc<Mutex<LinkManager>>, drone: Arc<RwLock<DroneState>>) -> ModuleResult {
+    let config = lock(&manager).config.clone();
+    let timeout = Duration::from_millis(config.probe_timeout_ms);
+    let mut ticker = crate::runtime::interval(Duration::from_secs(config.probe_interval_secs.max(1)));
+    loop {
+        ticker.tick().await;
+        let probes: Vec<_> = config
+            .transports
+            .iter()
+            .cloned()
+            .map(|transport| {
+                crate::runtime::spawn(async move {
+                    let rtt = probe(&transport, timeout).await;
+                    (transport.name, rtt)
+                })
+            })
+            .collect();
+        let mut results = Vec::with_capacity(probes.len());
+        for probe in probes {
+            // A probe that panicked has nothing to report
+            if let Ok(result) = probe.await {
+                results.push(result);
+            }
+        }
+        let mut state = drone.write().await;
+        let mut manager = lock(&manager);
+        for (name, rtt) in results {
+            manager.record(&name, rtt);
+        }
+        manager.check(&mut state, Utc::now());
+    }
+}
+
+fn lock(manager: &Mutex<LinkManager>) -> std::sync::MutexGuard<'_, LinkManager> {
+    manager.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,517 @@
+//! LoRa status beacon and command link (`lora` feature)
+
+use crate::wire::{self, WireFormat, WireKind, WireSchema};
+use crate::{Action, AuthConfig, AuthContext, CommandEnvelope, CommandSource, CommandVerifier, DroneState, ModuleResult, StatusFrame, ThreatLevel};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use ed25519_dalek::{Signer, SigningKey};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{mpsc, RwLock};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// How long the modem has to answer an AT command
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a transmission may take to go out, at the slowest settings
+const TRANSMIT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest reason a `CommandAck` carries, to keep it one small packet
+const ACK_ERROR_LEN: usize = 48;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoraConfig {
+    /// Serial device the modem is attached to
+    pub port: String,
+    pub baud_rate: u32,
+    pub frequency_hz: u32,
+    /// 7-12; a command frame needs 9 or lower at 125 kHz
+    pub spreading_factor: u8,
+    /// 125, 250 or 500
+    pub bandwidth_khz: u16,
+    pub tx_power_dbm: u8,
+    pub beacon_interval_secs: u64,
+    /// Least time between transmissions
+    pub min_gap_secs: u64,
+}
+
+impl Default for LoraConfig {
+    fn default() -> Self {
+        Self {
+            port: "/dev/ttyUSB0".to_string(),
+            baud_rate: 115_200,
+            frequency_hz: 868_100_000,
+            spreading_factor: 9,
+            bandwidth_khz: 125,
+            tx_power_dbm: 14,
+            beacon_interval_secs: 60,
+            min_gap_secs: 10,
+        }
+    }
+}
+
+impl LoraConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.port.trim().is_empty() {
+            problems.push("lora.port must not be empty".to_string());
+        }
+        if !(7..=12).contains(&self.spreading_factor) {
+            problems.push(format!("lora.spreading_factor {} is not 7-12", self.spreading_factor));
+        }
+        if ![125, 250, 500].contains(&self.bandwidth_khz) {
+            problems.push(format!("lora.bandwidth_khz {} is not 125, 250 or 500", self.bandwidth_khz));
+        }
+        if self.beacon_interval_secs == 0 {
+            problems.push("lora.beacon_interval_secs must be positive".to_string());
+        }
+        if self.min_gap_secs > self.beacon_interval_secs {
+            problems.push("lora.min_gap_secs is longer than the beacon interval".to_string());
+        }
+        problems
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LoraError {
+    #[error("serial port failed: {0}")]
+    Serial(String),
+    #[error("modem I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("modem closed the connection")]
+    Closed,
+    #[error("modem refused '{command}': {reply}")]
+    Refused { command: String, reply: String },
+    #[error("modem did not answer '{0}'")]
+    Timeout(String),
+    #[error("status frame could not be encoded: {0}")]
+    Encode(#[from] wire::WireError),
+}
+
+/// A command that fits a LoRa packet; variants are only ever added at the end
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoraCommand {
+    /// Answered with a beacon
+    Ping,
+    ReturnToHome,
+    Disarm,
+}
+
+impl LoraCommand {
+    pub fn action(self) -> Action {
+        match self {
+            LoraCommand::Ping => Action::ViewStatus,
+            LoraCommand::ReturnToHome => Action::ReturnToHome,
+            LoraCommand::Disarm => Action::ArmDisarm,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LoraCommand::Ping => "ping",
+            LoraCommand::ReturnToHome => "rth",
+            LoraCommand::Disarm => "disarm",
+        }
+    }
+}
+
+impl std::str::FromStr for LoraCommand {
+    type Err = String;
+
+    fn from_str(command: &str) -> Result<Self, Self::Err> {
+        match command {
+            "ping" => Ok(LoraCommand::Ping),
+            "rth" | "return_to_home" => Ok(LoraCommand::ReturnToHome),
+            "disarm" => Ok(LoraCommand::Disarm),
+            _ => Err(format!("unknown LoRa command '{}' (expected ping, rth or disarm)", command)),
+        }
+    }
+}
+
+/// A signed command as it goes over the air
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoraCommandFrame {
+    pub key_id: String,
+    pub nonce: u32,
+    /// Unix milliseconds
+    pub timestamp: i64,
+    pub command: LoraCommand,
+    /// The drone the command is for; `None` for every drone in range
+    pub drone: Option<Uuid>,
+    /// Raw Ed25519 signature of the envelope's signed payload
+    pub signature: Vec<u8>,
+}
+
+impl LoraCommandFrame {
+    /// Sign `command` for `drone`, as a ground station would
+    pub fn sign(key_id: &str, command: LoraCommand, drone: Option<Uuid>, timestamp: DateTime<Utc>, key: &SigningKey) -> Self {
+        let mut frame = Self {
+            key_id: key_id.to_string(),
+            nonce: rand::random(),
+            timestamp: timestamp.timestamp_millis(),
+            command,
+            drone,
+            signature: Vec::new(),
+        };
+        frame.signature = key.sign(frame.envelope().signed_payload().as_bytes()).to_bytes().to_vec();
+        frame
+    }
+
+    /// The envelope the signature vouches for, to check with the
+    /// `CommandVerifier` every other link uses
+    pub fn envelope(&self) -> CommandEnvelope {
+        let drone = self.drone.map_or_else(|| "*".to_string(), |drone| drone.to_string());
+        CommandEnvelope {
+            key_id: self.key_id.clone(),
+            command: format!("lora {} {}", self.command.as_str(), drone),
+            nonce: format!("{:08x}", self.nonce),
+            timestamp: Utc.timestamp_millis_opt(self.timestamp).single().unwrap_or_default(),
+            signature: hex::encode(&self.signature),
+        }
+    }
+}
+
+impl WireSchema for LoraCommandFrame {
+    const KIND: WireKind = WireKind::SignedCommand;
+    const VERSION: u8 = 1;
+    const FORMATS: &'static [WireFormat] = &[WireFormat::Postcard];
+}
+
+/// The drone's answer to a command once it has been carried out or refused
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandAck {
+    /// The drone answering
+    pub drone: Uuid,
+    /// Nonce of the command answered
+    pub nonce: u32,
+    pub command: LoraCommand,
+    /// Why the command was not carried out; `None` when it was
+    pub error: Option<String>,
+}
+
+impl WireSchema for CommandAck {
+    const KIND: WireKind = WireKind::CommandAck;
+    const VERSION: u8 = 1;
+    const FORMATS: &'static [WireFormat] = &[WireFormat::Postcard];
+}
+
+/// A command handed on by the link, to be answered over the air; one
+/// dropped without an answer is acknowledged as not handled
+pub struct LoraRequest {
+    pub caller: AuthContext,
+    pub command: LoraCommand,
+    ack: Option<CommandAck>,
+    acks: mpsc::UnboundedSender<CommandAck>,
+}
+
+impl LoraRequest {
+    /// Acknowledge the command with the outcome of carrying it out
+    pub fn answer(mut self, result: ModuleResult) {
+        self.send_ack(result.err().map(|e| e.to_string()));
+    }
+
+    fn send_ack(&mut self, error: Option<String>) {
+        if let Some(mut ack) = self.ack.take() {
+            ack.error = error.map(|mut error| {
+                let mut end = error.len().min(ACK_ERROR_LEN);
+                while !error.is_char_boundary(end) {
+                    end -= 1;
+                }
+                error.truncate(end);
+                error
+            });
+            // The link has stopped, so there is no one to answer
+            let _ = self.acks.send(ack);
+        }
+    }
+}
+
+impl Drop for LoraRequest {
+    fn drop(&mut self) {
+        self.send_ack(Some("not handled".to_string()));
+    }
+}
+
+/// A LoRa radio
+#[async_trait]
+pub trait LoraModem: Send {
+    /// Send one packet, returning once it is on air
+    async fn transmit(&mut self, payload: &[u8]) -> Result<(), LoraError>;
+
+    /// Wait for the next packet heard. Raced against the beacon timer, so
+    /// it must lose nothing when dropped before it completes.
+    async fn receive(&mut self) -> Result<Vec<u8>, LoraError>;
+}
+
+/// A RAK3172 speaking RUI3 AT commands in P2P mode (`AT+NWM=0`, which the
+/// modem keeps across restarts), over a serial port or any other stream
+pub struct AtModem<S> {
+    stream: S,
+    /// Bytes read but not yet a whole line
+    buffer: Vec<u8>,
+    /// Packets that arrived while waiting for a command to be answered
+    heard: VecDeque<Vec<u8>>,
+}
+
+/// Open the modem on the configured serial port and set up the radio
+pub async fn open(config: &LoraConfig) -> Result<AtModem<tokio_serial::SerialStream>, LoraError> {
+    use tokio_serial::SerialPortBuilderExt;
+    let stream = tokio_serial::new(&config.port, config.baud_rate).open_native_async().map_err(|e| LoraError::Serial(e.to_string()))?;
+    AtModem::configure(stream, config).await
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> AtModem<S> {
+    /// Apply the radio settings and start listening
+    pub async fn configure(stream: S, config: &LoraConfig) -> Result<Self, LoraError> {
+        let mut modem = Self {
+            stream,
+            buffer: Vec::new(),
+            heard: VecDeque::new(),
+        };
+        // Frequency, spreading factor, bandwidth, coding rate 4/5, 8-symbol preamble, power
+        let radio = format!(
+            "AT+P2P={}:{}:{}:0:8:{}",
+            config.frequency_hz, config.spreading_factor, config.bandwidth_khz, config.tx_power_dbm
+        );
+        modem.command(&radio).await?;
+        modem.listen().await?;
+        Ok(modem)
+    }
+
+    /// Receive until told otherwise
+    async fn listen(&mut self) -> Result<(), LoraError> {
+        self.command("AT+PRECV=65534").await
+    }
+
+    /// Send an AT command and wait for its `OK`
+    async fn command(&mut self, command: &str) -> Result<(), LoraError> {
+        self.stream.write_all(format!("{}\r\n", command).as_bytes()).await?;
+        self.wait_for(command, COMMAND_TIMEOUT, |line| line == "OK").await
+    }
+
+    /// Read lines until one satisfies `done`, keeping packets heard meanwhile
+    async fn wait_for(&mut self, command: &str, timeout: Duration, done: impl Fn(&str) -> bool) -> Result<(), LoraError> {
+        let waiting = async {
+            loop {
+                let line = self.read_line().await?;
+                if done(&line) {
+                    return Ok(());
+                }
+                if line.starts_with("AT_") && line.ends_with("ERROR") {
+                    return Err(LoraError::Refused {
+                        command: command.to_string(),
+                        reply: line,
+                    });
+                }
+                if let Some(packet) = parse_received(&line) {
+                    self.heard.push_back(packet);
+                }
+            }
+        };
+        crate::runtime::timeout(timeout, waiting).await.map_err(|_| LoraError::Timeout(command.to_string()))?
+    }
+
+    /// The next whole line, trimmed; cancel-safe, as a partial line stays
+    /// in the buffer
+    async fn read_line(&mut self) -> Result<String, LoraError> {
+        loop {
+            if let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line).trim().to_string();
+                if !line.is_empty() {
+                    return Ok(line);
+                }
+                continue;
+            }
+            let mut chunk = [0u8; 256];
+            let read = self.stream.read(&mut chunk).await?;
+            if read == 0 {
+                return Err(LoraError::Closed);
+            }
+            self.buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+}
+
+/// The payload of a `+EVT:RXP2P:<rssi>:<snr>:<hex>` line
+fn parse_received(line: &str) -> Option<Vec<u8>> {
+    let payload = line.strip_prefix("+EVT:RXP2P:")?.rsplit(':').next()?;
+    hex::decode(payload).ok()
+}
+
+#[async_trait]
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> LoraModem for AtModem<S> {
+    async fn transmit(&mut self, payload: &[u8]) -> Result<(), LoraError> {
+        // The radio is half duplex: stop listening, send, listen again
+        self.command("AT+PRECV=0").await?;
+        let send = format!("AT+PSEND={}", hex::encode(payload));
+        self.command(&send).await?;
+        self.wait_for(&send, TRANSMIT_TIMEOUT, |line| line.starts_with("+EVT:TXP2P")).await?;
+        self.listen().await
+    }
+
+    async fn receive(&mut self) -> Result<Vec<u8>, LoraError> {
+        if let Some(packet) = self.heard.pop_front() {
+            return Ok(packet);
+        }
+        loop {
+            if let Some(packet) = parse_received(&self.read_line().await?) {
+                return Ok(packet);
+            }
+        }
+    }
+}
+
+/// Beacon the drone's status and take commands over `modem` until it
+/// fails; ping is answered here, permitted commands go to `commands` and
+/// are acknowledged once answered
+pub async fn run(
+    config: LoraConfig,
+    mut modem: impl LoraModem,
+    drone: Arc<RwLock<DroneState>>,
+    auth: Arc<AuthConfig>,
+    verifier: Arc<CommandVerifier>,
+    commands: mpsc::Sender<LoraRequest>,
+) -> ModuleResult {
+    let interval = Duration::from_secs(config.beacon_interval_secs.max(1));
+    let gap = Duration::from_secs(config.min_gap_secs);
+    let mut tick = crate::runtime::interval(Duration::from_secs(1));
+    let (acks, mut answered) = mpsc::unbounded_channel();
+    let mut pending_acks = VecDeque::new();
+    let mut last_transmit: Option<Instant> = None;
+    let mut last_beacon: Option<(Instant, ThreatLevel)> = None;
+    let mut pinged = false;
+    info!("📡 LoRa beacon on {} at {} Hz, every {}s", config.port, config.frequency_hz, interval.as_secs());
+    loop {
+        tokio::select! {
+            _ = tick.tick() => {},
+            Some(ack) = answered.recv() => pending_acks.push_back(ack),
+            packet = modem.receive() => {
+                if let Some((caller, frame)) = accept(&packet?, &drone, &auth, &verifier).await {
+                    info!("📡 LoRa command from {}: {:?}", caller.principal, frame.command);
+                    if frame.command == LoraCommand::Ping {
+                        pinged = true;
+                    } else {
+                        let ack = CommandAck {
+                            drone: drone.read().await.id,
+                            nonce: frame.nonce,
+                            command: frame.command,
+                            error: None,
+                        };
+                        let request = LoraRequest {
+                            caller,
+                            command: frame.command,
+                            ack: Some(ack),
+                            acks: acks.clone(),
+                        };
+                        // A request that cannot be handed on is acknowledged as not handled
+                        if commands.try_send(request).is_err() {
+                            warn!("📡 LoRa command dropped: no one is handling commands");
+                        }
+                    }
+                }
+            },
+        }
+
+        // Acknowledgements and beacons share the band's duty cycle, answers first
+        if last_transmit.is_some_and(|at| at.elapsed() < gap) {
+            continue;
+        }
+        if let Some(ack) = pending_acks.pop_front() {
+            modem.transmit(&wire::encode(&ack, WireFormat::Postcard)?).await?;
+            debug!("📡 LoRa acknowledgement sent for {:?}", ack.command);
+            last_transmit = Some(Instant::now());
+            continue;
+        }
+        let (frame, level) = {
+            let drone = drone.read().await;
+            (StatusFrame::of(&drone, Utc::now()), drone.threat_level())
+        };
+        let due = match last_beacon {
+            None => true,
+            Some((at, sent_level)) => pinged || level != sent_level || at.elapsed() >= interval,
+        };
+        if due {
+            modem.transmit(&wire::encode(&frame, WireFormat::Postcard)?).await?;
+            debug!("📡 LoRa beacon sent at {:?}", level);
+            last_beacon = Some((Instant::now(), level));
+            last_transmit = Some(Instant::now());
+            pinged = false;
+        }
+    }
+}
+
+/// The command frame in `packet`, if it is one for this drone that verifies
+/// and that its signer may give
+async fn accept(packet: &[u8], drone: &RwLock<DroneState>, auth: &AuthConfig, verifier: &CommandVerifier) -> Option<(AuthContext, LoraCommandFrame)> {
+    if wire::peek(packet).ok()?.kind != WireKind::SignedCommand {
+        return None;
+    }
+    let frame: LoraCommandFrame = match wire::decode(packet) {
+        Ok(frame) => frame,
+        Err(e) => {
+            warn!("📡 Ignoring malformed LoRa command: {}", e);
+            return None;
+        },
+    };
+    let mut drone = drone.write().await;
+    if frame.drone.is_some_and(|id| id != drone.id) {
+        return None;
+    }
+    let envelope = frame.envelope();
+    let caller = verifier.authorize(Some(&envelope), None, auth, CommandSource::Lora, frame.command.action(), &mut drone).ok()?;
+    Some((caller, frame))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Role;
+
+    fn request(acks: mpsc::UnboundedSender<CommandAck>) -> LoraRequest {
+        let ack = CommandAck {
+            drone: Uuid::new_v4(),
+            nonce: 7,
+            command: LoraCommand::Disarm,
+            error: None,
+        };
+        LoraRequest {
+            caller: AuthContext::new("ground", Role::Operator, CommandSource::Lora),
+            command: LoraCommand::Disarm,
+            ack: Some(ack),
+            acks,
+        }
+    }
+
+    #[test]
+    fn answered_request_is_acknowledged_once() {
+        let (acks, mut answered) = mpsc::unbounded_channel();
+        request(acks).answer(Ok(()));
+        let ack = answered.try_recv().unwrap();
+        assert_eq!((ack.nonce, ack.error), (7, None));
+        assert!(answered.try_recv().is_err());
+    }
+
+    #[test]
+    fn dropped_request_is_acknowledged_as_not_handled() {
+        let (acks, mut answered) = mpsc::unbounded_channel();
+        drop(request(acks));
+        assert_eq!(answered.try_recv().unwrap().error.as_deref(), Some("not handled"));
+    }
+
+    #[test]
+    fn long_refusal_is_cut_to_fit_a_packet() {
+        let (acks, mut answered) = mpsc::unbounded_channel();
+        request(acks).answer(Err("é".repeat(ACK_ERROR_LEN).into()));
+        let ack = answered.try_recv().unwrap();
+        assert_eq!(ack.error.as_ref().unwrap().len(), ACK_ERROR_LEN);
+        let decoded: CommandAck = wire::decode(&wire::encode(&ack, WireFormat::Postcard).unwrap()).unwrap();
+        assert_eq!(decoded, ack);
+    }
+}
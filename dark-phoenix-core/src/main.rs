@@ -1,169 +0,0 @@
-use dark_phoenix_core::{DroneState, ThreatLevel, EventType};
-use tokio::time::{sleep, Duration};
-use tracing::{info, warn, error};
-use std::sync::Arc;
-use tokio::sync::RwLock;
-
-/// Main orchestration engine for the Dark Phoenix drone
-pub struct DarkPhoenixCore {
-    state: Arc<RwLock<DroneState>>,
-    // Module interfaces will be added as we build them
-}
-
-impl DarkPhoenixCore {
-    pub fn new(drone_name: String) -> Self {
-        let state = Arc::new(RwLock::new(DroneState::new(drone_name)));
-        
-        Self {
-            state,
-        }
-    }
-
-    /// Start the main protection loop
-    pub async fn ignite(&self) -> Result<(), Box<dyn std::error::Error>> {
-        info!("🔥 Dark Phoenix igniting... 🔥");
-        
-        // Log the ceremonial awakening
-        {
-            let mut state = self.state.write().await;
-            state.log_event(
-                EventType::PhoenixRising,
-                "Dark Phoenix has awakened. Guardian protocols active.".to_string(),
-                vec!["All systems online".to_string(), "Protection mode engaged".to_string()],
-            );
-        }
-
-        // Main protection loop
-        loop {
-            self.protection_cycle().await?;
-            sleep(Duration::from_millis(100)).await; // 10Hz update rate
-        }
-    }
-
-    /// Single cycle of the protection algorithm
-    async fn protection_cycle(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut state = self.state.write().await;
-        
-        // System health check
-        self.update_system_health(&mut state).await;
-        
-        // Threat assessment (placeholder - will integrate with threat-detection module)
-        self.assess_threats(&mut state).await;
-        
-        // Response coordination (placeholder - will integrate with all modules)
-        self.coordinate_response(&mut state).await;
-        
-        Ok(())
-    }
-
-    async fn update_system_health(&self, state: &mut DroneState) {
-        // Placeholder for real sensor data
-        // In reality, this would interface with flight controller, sensors, etc.
-        
-        if state.system_health.battery_level > 0 {
-            state.system_health.battery_level = state.system_health.battery_level.saturating_sub(1);
-        }
-        
-        if state.system_health.battery_level < 20 && state.threat_level < ThreatLevel::Orange {
-            warn!("⚠️ Battery critical: {}%", state.system_health.battery_level);
-            state.escalate_threat(ThreatLevel::Orange, "Critical battery level detected".to_string());
-        }
-    }
-
-    async fn assess_threats(&self, state: &mut DroneState) {
-        // Placeholder for Ultra Seeker integration
-        // This will eventually call into the threat-detection module
-        
-        // Simulated threat detection for demo
-        if state.mission_log.len() % 100 == 0 && state.threat_level == ThreatLevel::Green {
-            info!("🔍 Scanning for threats...");
-            // In real implementation, this would analyze camera feeds, audio, movement patterns
-        }
-    }
-
-    async fn coordinate_response(&self, state: &mut DroneState) {
-        // Placeholder for module coordination
-        // This will orchestrate all response modules based on threat level
-        
-        match state.threat_level {
-            ThreatLevel::Green => {
-                // Passive monitoring mode
-            },
-            ThreatLevel::Yellow => {
-                // Increased sensor sensitivity
-                info!("🟡 Heightened awareness mode active");
-            },
-            ThreatLevel::Orange => {
-                // Defensive posture, prepare deterrence
-                warn!("🟠 Defensive protocols engaged");
-            },
-            ThreatLevel::Red => {
-                // All deterrence systems active
-                error!("🔴 High threat - all systems active");
-            },
-            ThreatLevel::Omega => {
-                // Maximum protection, all systems deployed
-                error!("💀 OMEGA PROTOCOL - DARK PHOENIX RISING 💀");
-            },
-        }
-    }
-
-    /// Get current drone status for external monitoring
-    pub async fn get_status(&self) -> String {
-        let state = self.state.read().await;
-        state.mythic_status()
-    }
-
-    /// Emergency shutdown protocol
-    pub async fn emergency_landing(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut state = self.state.write().await;
-        
-        state.log_event(
-            EventType::SystemMalfunction,
-            "Emergency landing initiated".to_string(),
-            vec!["All systems shutting down safely".to_string()],
-        );
-        
-        error!("🚨 EMERGENCY LANDING PROTOCOL ACTIVATED 🚨");
-        
-        // In real implementation:
-        // - Retract shield
-        // - Disable deterrence systems
-        // - Activate emergency beacon
-        // - Land safely
-        
-        Ok(())
-    }
-}
-
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize logging
-    tracing_subscriber::fmt::init();
-
-    // Create the Dark Phoenix instance
-    let phoenix = DarkPhoenixCore::new("Dark Phoenix Alpha".to_string());
-    
-    // Display startup banner
-    println!(r#"
-    🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥
-    
-    ██████╗  █████╗ ██████╗ ██╗  ██╗    ██████╗ ██╗  ██╗ ██████╗ ███████╗███╗   ██╗██╗██╗  ██╗
-    ██╔══██╗██╔══██╗██╔══██╗██║ ██╔╝    ██╔══██╗██║  ██║██╔═══██╗██╔════╝████╗  ██║██║╚██╗██╔╝
-    ██║  ██║███████║██████╔╝█████╔╝     ██████╔╝███████║██║   ██║█████╗  ██╔██╗ ██║██║ ╚███╔╝ 
-    ██║  ██║██╔══██║██╔══██╗██╔═██╗     ██╔═══╝ ██╔══██║██║   ██║██╔══╝  ██║╚██╗██║██║ ██╔██╗ 
-    ██████╔╝██║  ██║██║  ██║██║  ██╗    ██║     ██║  ██║╚██████╔╝███████╗██║ ╚████║██║██╔╝ ██╗
-    ╚═════╝ ╚═╝  ╚═╝╚═╝  ╚═╝╚═╝  ╚═╝    ╚═╝     ╚═╝  ╚═╝ ╚═════╝ ╚══════╝╚═╝  ╚═══╝╚═╝╚═╝  ╚═╝
-    
-    MYTHIC-GRADE AUTONOMOUS PROTECTION SYSTEM
-    "From the ashes of danger, the Phoenix rises to protect."
-    
-    🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥
-    "#);
-
-    println!("\n{}", phoenix.get_status().await);
-    println!("\n🚀 Initiating protection protocols...\n");
-
-    // Start the protection system
-    phoenix.ignite().await
-}
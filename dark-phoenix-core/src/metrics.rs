@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{info, warn};
+
+/// Bucket bounds for loop and actuation timings (seconds)
+pub const LATENCY_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5];
+
+/// Bucket bounds for extinguisher discharges (seconds)
+pub const DISCHARGE_BUCKETS: &[f64] = &[1.0, 2.0, 5.0, 10.0, 15.0, 20.0, 30.0, 60.0];
+
+/// Content type of [`Metrics::render`]
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Monotonic count, e.g. activations
+#[derive(Debug, Clone, Default)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    pub fn inc_by(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Current value, e.g. battery level
+#[derive(Debug, Clone, Default)]
+pub struct Gauge(Arc<AtomicU64>);
+
+impl Gauge {
+    pub fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+#[derive(Debug)]
+struct HistogramState {
+    bounds: Vec<f64>,
+    /// Per bucket, not cumulative; the last is +Inf
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+/// Distribution of observations, e.g. loop latency
+#[derive(Debug, Clone)]
+pub struct Histogram(Arc<Mutex<HistogramState>>);
+
+impl Histogram {
+    fn new(buckets: &[f64]) -> Self {
+        let mut bounds: Vec<f64> = buckets.iter().copied().filter(|bound| bound.is_finite()).collect();
+        bounds.sort_by(f64::total_cmp);
+        bounds.dedup();
+        Self(Arc::new(Mutex::new(HistogramState {
+            counts: vec![0; bounds.len() + 1],
+            bounds,
+            sum: 0.0,
+            count: 0,
+        })))
+    }
+
+    pub fn observe(&self, value: f64) {
+        let mut state = self.state();
+        let bucket = state.bounds.iter().position(|bound| value <= *bound).unwrap_or(state.bounds.len());
+        state.counts[bucket] += 1;
+        state.sum += value;
+        state.count += 1;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.state().count
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, HistogramState> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Series {
+    Counter(Counter),
+    Gauge(Gauge),
+    Histogram(Histogram),
+}
+
+impl Series {
+    fn kind(&self) -> &'static str {
+        match self {
+            Series::Counter(_) => "counter",
+            Series::Gauge(_) => "gauge",
+            Series::Histogram(_) => "histogram",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Family {
+    help: String,
+    kind: &'static str,
+    /// Keyed by rendered label set, e.g. `{sensor="camera"}`
+    series: BTreeMap<String, Series>,
+}
+
+/// Registry of every metric the drone exports, rendered in the Prometheus
+/// text format; cheap to clone into each module
+///
+/// Asking for a metric that already exists returns the existing one, so
+/// modules can look metrics up by name and labels as they go.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    families: Arc<Mutex<BTreeMap<String, Family>>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn counter(&self, name: &str, help: &str, labels: &[(&str, &str)]) -> Counter {
+        match self.series(name, help, labels, || Series::Counter(Counter::default())) {
+            Series::Counter(counter) => counter,
+            _ => Counter::default(),
+        }
+    }
+
+    pub fn gauge(&self, name: &str, help: &str, labels: &[(&str, &str)]) -> Gauge {
+        match self.series(name, help, labels, || Series::Gauge(Gauge::default())) {
+            Series::Gauge(gauge) => gauge,
+            _ => Gauge::default(),
+        }
+    }
+
+    /// `buckets` are upper bounds; only used when the histogram is first created
+    pub fn histogram(&self, name: &str, help: &str, labels: &[(&str, &str)], buckets: &[f64]) -> Histogram {
+        match self.series(name, help, labels, || Series::Histogram(Histogram::new(buckets))) {
+            Series::Histogram(histogram) => histogram,
+            _ => Histogram::new(buckets),
+        }
+    }
+
+    /// Get or create a series; a name reused with another kind gets a detached
+    /// series that is never exported
+    fn series(&self, name: &str, help: &str, labels: &[(&str, &str)], create: impl FnOnce() -> Series) -> Series {
+        let created = create();
+        let mut families = self.families.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let family = families.entry(name.to_string()).or_insert_with(|| Family {
+            help: help.to_string(),
+            kind: created.kind(),
+            series: BTreeMap::new(),
+        });
+        if family.kind != created.kind() {
+            warn!("📈 Metric '{}' is a {}, not a {}; not exported", name, family.kind, created.kind());
+            return created;
+        }
+        family.series.entry(render_labels(labels)).or_insert(created).clone()
+    }
+
+    /// Every metric in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let families = self.families.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut out = String::new();
+        for (name, family) in families.iter() {
+            let _ = writeln!(out, "# HELP {} {}", name, family.help.replace('\\', "\\\\").replace('\n', "\\n"));
+            let _ = writeln!(out, "# TYPE {} {}", name, family.kind);
+            for (labels, series) in &family.series {
+                match series {
+                    Series::Counter(counter) => {
+                        let _ = writeln!(out, "{}{} {}", name, labels, counter.get());
+                    },
+                    Series::Gauge(gauge) => {
+                        let _ = writeln!(out, "{}{} {}", name, labels, format_value(gauge.get()));
+                    },
+                    Series::Histogram(histogram) => render_histogram(&mut out, name, labels, &histogram.state()),
+                }
+            }
+        }
+        out
+    }
+}
+
+fn render_histogram(out: &mut String, name: &str, labels: &str, state: &HistogramState) {
+    let mut cumulative = 0;
+    let bounds = state.bounds.iter().map(|bound| format_value(*bound)).chain(std::iter::once("+Inf".to_string()));
+    for (bound, count) in bounds.zip(&state.counts) {
+        cumulative += count;
+        let _ = writeln!(out, "{}_bucket{} {}", name, with_label(labels, "le", &bound), cumulative);
+    }
+    let _ = writeln!(out, "{}_sum{} {}", name, labels, format_value(state.sum));
+    let _ = writeln!(out, "{}_count{} {}", name, labels, state.count);
+}
+
+fn render_labels(labels: &[(&str, &str)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let pairs: Vec<String> = labels.iter().map(|(key, value)| format!("{}=\"{}\"", key, escape(value))).collect();
+    format!("{{{}}}", pairs.join(","))
+}
+
+/// Add one more label to an already rendered label set
+fn with_label(labels: &str, key: &str, value: &str) -> String {
+    match labels.strip_suffix('}') {
+        Some(open) => format!("{},{}=\"{}\"}}", open, key, value),
+        None => format!("{{{}=\"{}\"}}", key, value),
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Serve `GET /metrics` on its own port until `shutdown` completes, for
+/// builds without the API server
+pub async fn serve_exporter(
+    bind: SocketAddr,
+    metrics: Metrics,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(bind).await?;
+    info!("📈 Metrics exporter listening on {}", bind);
+    tokio::pin!(shutdown);
+    loop {
+        let (mut socket, _) = tokio::select! {
+            accepted = listener.accept() => accepted?,
+            _ = &mut shutdown => return Ok(()),
+        };
+        let metrics = metrics.clone();
+        crate::runtime::spawn(async move {
+            let mut request = [0u8; 1024];
+            let Ok(read) = socket.read(&mut request).await else { return };
+            let request = String::from_utf8_lossy(&request[..read]);
+            let path = request.split_whitespace().nth(1).unwrap_or("");
+            let response = if request.starts_with("GET ") && (path == "/metrics" || path.starts_with("/metrics?")) {
+                let body = metrics.render();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    CONTENT_TYPE,
+                    body.len(),
+                    body
+                )
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+            };
+            let _ = socket.write_all(response.as_bytes()).await;
+        });
+    }
+}
@@ -0,0 +1,373 @@
+//! Building fire panel over Modbus TCP or RTU (`modbus` feature)
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio_modbus::client::{Client, Context, Reader, Writer};
+use tokio_modbus::prelude::{Slave, SlaveContext};
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ModbusTransport {
+    /// `host:port` of the panel or its gateway
+    Tcp { address: String },
+    Rtu { device: String, baud_rate: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectorKind {
+    Smoke,
+    Heat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegisterTable {
+    Coil,
+    DiscreteInput,
+    HoldingRegister,
+    InputRegister,
+}
+
+impl RegisterTable {
+    /// Whether the table holds on/off bits rather than 16-bit words
+    pub fn is_bit(&self) -> bool {
+        matches!(self, RegisterTable::Coil | RegisterTable::DiscreteInput)
+    }
+}
+
+/// Where the panel keeps one detector's state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectorRegister {
+    pub name: String,
+    pub kind: DetectorKind,
+    pub table: RegisterTable,
+    pub address: u16,
+    /// Unit id when it differs from the bridge's
+    #[serde(default)]
+    pub unit_id: Option<u8>,
+    /// Multiplier from the raw word to smoke obscuration (0.0-1.0) or °C
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Scaled value at which a word register counts as in alarm; a bit is
+    /// in alarm when set
+    #[serde(default)]
+    pub alarm_at: Option<f32>,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+/// A panel relay switched on while suppressing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlarmCoil {
+    pub name: String,
+    pub address: u16,
+    #[serde(default)]
+    pub unit_id: Option<u8>,
+    /// Write 0 to sound the alarm and 1 to silence it
+    #[serde(default)]
+    pub inverted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModbusConfig {
+    pub transport: ModbusTransport,
+    /// Unit id of the panel
+    pub unit_id: u8,
+    pub timeout_ms: u64,
+    pub poll_ms: u64,
+    /// Panel readings older than this are ignored
+    pub max_age_ms: u64,
+    pub detectors: Vec<DetectorRegister>,
+    pub alarm_coils: Vec<AlarmCoil>,
+}
+
+impl Default for ModbusConfig {
+    fn default() -> Self {
+        Self {
+            transport: ModbusTransport::Tcp { address: "127.0.0.1:502".to_string() },
+            unit_id: 1,
+            timeout_ms: 1000,
+            poll_ms: 1000,
+            max_age_ms: 5000,
+            detectors: Vec::new(),
+            alarm_coils: Vec::new(),
+        }
+    }
+}
+
+impl ModbusConfig {
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        match &self.transport {
+            ModbusTransport::Tcp { address } if address.is_empty() => problems.push("modbus.transport.address is empty".to_string()),
+            ModbusTransport::Rtu { device, .. } if device.is_empty() => problems.push("modbus.transport.device is empty".to_string()),
+            ModbusTransport::Rtu { baud_rate: 0, .. } => problems.push("modbus.transport.baud_rate must be positive".to_string()),
+            _ => {},
+        }
+        if self.timeout_ms == 0 {
+            problems.push("modbus.timeout_ms must be positive".to_string());
+        }
+        if self.max_age_ms <= self.poll_ms {
+            problems.push(format!("modbus.max_age_ms {} must be longer than poll_ms {}", self.max_age_ms, self.poll_ms));
+        }
+        for detector in &self.detectors {
+            if !detector.scale.is_finite() || detector.scale == 0.0 {
+                problems.push(format!("modbus detector {}: scale {} must be non-zero", detector.name, detector.scale));
+            }
+            if detector.table.is_bit() && detector.alarm_at.is_some() {
+                problems.push(format!("modbus detector {}: alarm_at has no meaning for a {:?}", detector.name, detector.table));
+            }
+            if !detector.table.is_bit() && detector.alarm_at.is_none() && detector.kind == DetectorKind::Heat {
+                problems.push(format!("modbus detector {}: heat register needs an alarm_at temperature", detector.name));
+            }
+        }
+        for (index, coil) in self.alarm_coils.iter().enumerate() {
+            let unit = coil.unit_id.unwrap_or(self.unit_id);
+            if self.alarm_coils[..index].iter().any(|other| other.address == coil.address && other.unit_id.unwrap_or(self.unit_id) == unit) {
+                problems.push(format!("modbus alarm coil {} reuses coil {} on unit {}", coil.name, coil.address, unit));
+            }
+        }
+        problems
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ModbusError {
+    #[error("cannot reach the fire panel at {target}: {reason}")]
+    Connect { target: String, reason: String },
+    #[error("fire panel did not answer within {0:?}")]
+    Timeout(Duration),
+    #[error("fire panel transport failed: {0}")]
+    Transport(String),
+    #[error("fire panel unit {unit} refused address {address}: {code}")]
+    Exception { unit: u8, address: u16, code: String },
+}
+
+/// One detector as last read from the panel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectorReading {
+    pub name: String,
+    pub kind: DetectorKind,
+    /// Scaled smoke obscuration or °C; absent for on/off detectors
+    pub value: Option<f32>,
+    pub alarm: bool,
+}
+
+/// The panel's detectors at one poll
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildingPanel {
+    pub readings: Vec<DetectorReading>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl BuildingPanel {
+    /// Densest smoke reported; an on/off smoke detector in alarm counts as dense smoke
+    pub fn smoke_level(&self) -> Option<f32> {
+        self.of_kind(DetectorKind::Smoke)
+            .map(|reading| match reading.value {
+                Some(value) => value.clamp(0.0, 1.0),
+                None if reading.alarm => 1.0,
+                None => 0.0,
+            })
+            .reduce(f32::max)
+    }
+
+    /// Hottest measured temperature
+    pub fn temperature(&self) -> Option<crate::Celsius> {
+        self.of_kind(DetectorKind::Heat).filter_map(|reading| reading.value).reduce(f32::max).map(crate::Celsius)
+    }
+
+    /// Detectors in alarm
+    pub fn alarms(&self) -> impl Iterator<Item = &DetectorReading> {
+        self.readings.iter().filter(|reading| reading.alarm)
+    }
+
+    fn of_kind(&self, kind: DetectorKind) -> impl Iterator<Item = &DetectorReading> {
+        self.readings.iter().filter(move |reading| reading.kind == kind)
+    }
+}
+
+/// Handle on the panel connection; clones share it
+#[derive(Clone)]
+pub struct ModbusBridge {
+    panel: watch::Receiver<Option<Arc<BuildingPanel>>>,
+    alarm: Arc<watch::Sender<bool>>,
+    max_age: chrono::Duration,
+}
+
+impl ModbusBridge {
+    /// Connect and poll until every copy of the bridge is dropped
+    pub fn start(config: ModbusConfig) -> Self {
+        let (panel_tx, panel) = watch::channel(None);
+        let (alarm, alarm_rx) = watch::channel(false);
+        let max_age = chrono::Duration::milliseconds(config.max_age_ms as i64);
+        crate::runtime::spawn(serve(config, panel_tx, alarm_rx));
+        Self { panel, alarm: Arc::new(alarm), max_age }
+    }
+
+    /// The latest poll, unless it is too old to trust
+    pub fn latest(&self) -> Option<Arc<BuildingPanel>> {
+        let panel = self.panel.borrow().clone()?;
+        (Utc::now().signed_duration_since(panel.timestamp) <= self.max_age).then_some(panel)
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Option<Arc<BuildingPanel>>> {
+        self.panel.clone()
+    }
+
+    /// Switch the alarm coils; written as soon as the panel is reachable
+    pub fn set_alarm(&self, on: bool) {
+        self.alarm.send_if_modified(|alarm| std::mem::replace(alarm, on) != on);
+    }
+}
+
+async fn serve(config: ModbusConfig, panel: watch::Sender<Option<Arc<BuildingPanel>>>, mut alarm: watch::Receiver<bool>) {
+    let poll = Duration::from_millis(config.poll_ms.max(50));
+    let timeout = Duration::from_millis(config.timeout_ms.max(1));
+    let mut backoff = poll;
+    while !panel.is_closed() {
+        let mut context = match crate::runtime::timeout(timeout, connect(&config)).await {
+            Ok(Ok(context)) => context,
+            Ok(Err(e)) => {
+                warn!("🏢 {}; retrying in {:?}", e, backoff);
+                crate::runtime::sleep(backoff).await;
+                backoff = (backoff * 2).min(Duration::from_secs(30));
+                continue;
+            },
+            Err(_) => {
+                warn!("🏢 {}; retrying in {:?}", ModbusError::Timeout(timeout), backoff);
+                crate::runtime::sleep(backoff).await;
+                backoff = (backoff * 2).min(Duration::from_secs(30));
+                continue;
+            },
+        };
+        info!("🏢 Fire panel connected ({} detectors, {} alarm coils)", config.detectors.len(), config.alarm_coils.len());
+        backoff = poll;
+
+        // Coils may have been reset while the link was down, so write them again
+        let mut written = None;
+        let mut first_poll = true;
+        let failure = loop {
+            if panel.is_closed() {
+                return;
+            }
+            let on = *alarm.borrow_and_update();
+            if written != Some(on) {
+                if let Err(e) = write_alarm(&mut context, &config, on, timeout).await {
+                    break e;
+                }
+                info!("🏢 Building alarm relays {}", if on { "ON" } else { "off" });
+                written = Some(on);
+            }
+            match read_panel(&mut context, &config, timeout, first_poll).await {
+                Ok(readings) => {
+                    first_poll = false;
+                    panel.send_replace(Some(Arc::new(BuildingPanel { readings, timestamp: Utc::now() })));
+                },
+                Err(e) => break e,
+            }
+            tokio::select! {
+                _ = crate::runtime::sleep(poll) => {},
+                Ok(()) = alarm.changed() => {},
+            }
+        };
+        warn!("🏢 Fire panel link lost: {}", failure);
+        let _ = context.disconnect().await;
+    }
+}
+
+async fn connect(config: &ModbusConfig) -> Result<Context, ModbusError> {
+    let slave = Slave(config.unit_id);
+    match &config.transport {
+        ModbusTransport::Tcp { address } => {
+            let stream = tokio::net::TcpStream::connect(address)
+                .await
+                .map_err(|e| ModbusError::Connect { target: address.clone(), reason: e.to_string() })?;
+            Ok(tokio_modbus::client::tcp::attach_slave(stream, slave))
+        },
+        ModbusTransport::Rtu { device, baud_rate } => {
+            use tokio_serial::SerialPortBuilderExt;
+            let stream = tokio_serial::new(device, *baud_rate)
+                .open_native_async()
+                .map_err(|e| ModbusError::Connect { target: device.clone(), reason: e.to_string() })?;
+            Ok(tokio_modbus::client::rtu::attach_slave(stream, slave))
+        },
+    }
+}
+
+async fn read_panel(context: &mut Context, config: &ModbusConfig, timeout: Duration, report_exceptions: bool) -> Result<Vec<DetectorReading>, ModbusError> {
+    let mut readings = Vec::with_capacity(config.detectors.len());
+    for detector in &config.detectors {
+        let unit = detector.unit_id.unwrap_or(config.unit_id);
+        context.set_slave(Slave(unit));
+        let call = async {
+            match detector.table {
+                RegisterTable::Coil => context.read_coils(detector.address, 1).await.map(|bits| bits.map(|bits| Word::Bit(bits.first().copied().unwrap_or(false)))),
+                RegisterTable::DiscreteInput => context.read_discrete_inputs(detector.address, 1).await.map(|bits| bits.map(|bits| Word::Bit(bits.first().copied().unwrap_or(false)))),
+                RegisterTable::HoldingRegister => context.read_holding_registers(detector.address, 1).await.map(|words| words.map(|words| Word::Value(words.first().copied().unwrap_or(0)))),
+                RegisterTable::InputRegister => context.read_input_registers(detector.address, 1).await.map(|words| words.map(|words| Word::Value(words.first().copied().unwrap_or(0)))),
+            }
+        };
+        let word = match answer(call, unit, detector.address, timeout).await {
+            Ok(word) => word,
+            // A wrongly mapped register leaves the detector out rather than dropping the link
+            Err(e @ ModbusError::Exception { .. }) => {
+                if report_exceptions {
+                    warn!("🏢 Detector {} unreadable: {}", detector.name, e);
+                }
+                continue;
+            },
+            Err(e) => return Err(e),
+        };
+        let reading = match word {
+            Word::Bit(alarm) => DetectorReading { name: detector.name.clone(), kind: detector.kind, value: None, alarm },
+            Word::Value(raw) => {
+                let value = raw as f32 * detector.scale;
+                let alarm = detector.alarm_at.is_some_and(|at| value >= at);
+                DetectorReading { name: detector.name.clone(), kind: detector.kind, value: Some(value), alarm }
+            },
+        };
+        readings.push(reading);
+    }
+    Ok(readings)
+}
+
+async fn write_alarm(context: &mut Context, config: &ModbusConfig, on: bool, timeout: Duration) -> Result<(), ModbusError> {
+    for coil in &config.alarm_coils {
+        let unit = coil.unit_id.unwrap_or(config.unit_id);
+        context.set_slave(Slave(unit));
+        match answer(context.write_single_coil(coil.address, on != coil.inverted), unit, coil.address, timeout).await {
+            Err(e @ ModbusError::Exception { .. }) => warn!("🏢 Alarm coil {} not switched: {}", coil.name, e),
+            other => other?,
+        }
+    }
+    Ok(())
+}
+
+enum Word {
+    Bit(bool),
+    Value(u16),
+}
+
+/// Flatten a Modbus call's timeout, transport and exception failures
+async fn answer<T>(
+    call: impl std::future::Future<Output = tokio_modbus::Result<T>>,
+    unit: u8,
+    address: u16,
+    timeout: Duration,
+) -> Result<T, ModbusError> {
+    match crate::runtime::timeout(timeout, call).await {
+        Ok(Ok(Ok(value))) => Ok(value),
+        Ok(Ok(Err(code))) => Err(ModbusError::Exception { unit, address, code: code.to_string() }),
+        Ok(Err(e)) => Err(ModbusError::Transport(e.to_string())),
+        Err(_) => Err(ModbusError::Timeout(timeout)),
+    }
+}
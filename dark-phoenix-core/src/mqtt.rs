@@ -0,0 +1,545 @@
+//! MQTT telemetry publisher and command subscriber (`mqtt` feature)
+
+use crate::home_assistant::{self, HomeAssistantConfig};
+use crate::runtime::Task;
+use crate::{
+    Action, AuthConfig, AuthContext, CommandEnvelope, CommandSource, CommandVerifier, DeltaConfig, DeltaEncoder, DroneState, Keyring, LinkRoute, OutboundMessage, Outbox,
+    OutboxConfig, OutboxPriority, StoreError, TelemetryMessage,
+};
+use rumqttc::{AsyncClient, Event, EventLoop, LastWill, MqttOptions, NetworkOptions, Packet, QoS, TlsConfiguration, Transport};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::{broadcast, mpsc, watch, RwLock};
+use tracing::{error, info, warn};
+
+/// Where and how one kind of message is published
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicConfig {
+    pub topic: String,
+    /// 0, 1 or 2
+    pub qos: u8,
+    /// Keep the last message on the broker for late subscribers
+    pub retain: bool,
+}
+
+impl TopicConfig {
+    fn new(topic: &str, qos: u8, retain: bool) -> Self {
+        Self {
+            topic: topic.to_string(),
+            qos,
+            retain,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MqttTls {
+    /// PEM CA certificate for the broker
+    pub ca_path: PathBuf,
+    /// PEM client certificate and key, for brokers that require client auth
+    pub client_cert_path: Option<PathBuf>,
+    pub client_key_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MqttConfig {
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub tls: Option<MqttTls>,
+    pub keep_alive_secs: u64,
+    /// Largest packet either way; a full `DroneState` carries the mission log
+    pub max_packet_bytes: usize,
+    pub state: TopicConfig,
+    pub state_interval_ms: u64,
+    pub threat: TopicConfig,
+    /// Per-module health is published to `<topic>/<module>`
+    pub module_health: TopicConfig,
+    /// Subscribed to; `retain` is ignored
+    pub command: TopicConfig,
+    /// Queue on disk what cannot be sent while the broker is unreachable
+    /// (absent = drop it)
+    #[serde(default)]
+    pub outbox: Option<OutboxConfig>,
+    /// Also publish the state as delta-encoded channels (absent = off)
+    #[serde(default)]
+    pub delta: Option<DeltaConfig>,
+    /// Announce the drone to Home Assistant through MQTT discovery (absent = not announced)
+    #[serde(default)]
+    pub home_assistant: Option<HomeAssistantConfig>,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 1883,
+            client_id: "dark-phoenix".to_string(),
+            username: None,
+            password: None,
+            tls: None,
+            keep_alive_secs: 30,
+            max_packet_bytes: 1024 * 1024,
+            state: TopicConfig::new("dark-phoenix/state", 0, true),
+            state_interval_ms: 5000,
+            threat: TopicConfig::new("dark-phoenix/threat", 1, false),
+            module_health: TopicConfig::new("dark-phoenix/modules", 1, true),
+            command: TopicConfig::new("dark-phoenix/command", 1, false),
+            outbox: Some(OutboxConfig {
+                dir: PathBuf::from("outbox/mqtt"),
+                ..OutboxConfig::default()
+            }),
+            delta: None,
+            home_assistant: None,
+        }
+    }
+}
+
+/// A command received on the command topic, for the integrator to carry out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum MqttCommand {
+    Arm,
+    Disarm,
+    /// Self-test a module, e.g. "deterrence"
+    Test { module: String },
+}
+
+impl MqttCommand {
+    pub fn action(&self) -> Action {
+        match self {
+            MqttCommand::Arm | MqttCommand::Disarm => Action::ArmDisarm,
+            MqttCommand::Test { .. } => Action::TestModule,
+        }
+    }
+}
+
+/// A command as published, with the sender's credentials
+#[derive(Debug, Deserialize)]
+struct CommandRequest {
+    #[serde(flatten)]
+    command: MqttCommand,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+/// Parse a command payload: a signed envelope, or a command with an optional token
+fn parse_command(payload: &[u8]) -> Result<(MqttCommand, Option<CommandEnvelope>, Option<String>), serde_json::Error> {
+    if let Ok(envelope) = serde_json::from_slice::<CommandEnvelope>(payload) {
+        let command = serde_json::from_str(&envelope.command)?;
+        return Ok((command, Some(envelope), None));
+    }
+    let CommandRequest { command, token } = serde_json::from_slice(payload)?;
+    Ok((command, None, token))
+}
+
+#[derive(Debug, Error)]
+pub enum MqttError {
+    #[error("invalid QoS {0} for topic '{1}' (expected 0, 1 or 2)")]
+    InvalidQos(u8, String),
+    #[error("failed to read TLS file {0}: {1}")]
+    Tls(PathBuf, std::io::Error),
+    #[error("TLS client certificate and key must be given together")]
+    IncompleteClientAuth,
+    #[error("MQTT client error: {0}")]
+    Client(#[from] rumqttc::ClientError),
+    #[error("failed to encode payload: {0}")]
+    Encode(#[from] serde_json::Error),
+    #[error("outbox failed: {0}")]
+    Outbox(#[from] StoreError),
+}
+
+struct Qos {
+    state: QoS,
+    threat: QoS,
+    module_health: QoS,
+    command: QoS,
+}
+
+/// Publishes to the broker; cheap to clone into other tasks
+#[derive(Clone)]
+pub struct MqttPublisher {
+    client: AsyncClient,
+    config: Arc<MqttConfig>,
+    qos: Arc<Qos>,
+    outbox: Option<Outbox>,
+    /// Whether the broker has acknowledged the current connection
+    connected: Arc<AtomicBool>,
+    /// Whether queued messages are being sent
+    draining: Arc<AtomicBool>,
+}
+
+/// The broker connection; nothing is sent or received until it is run
+pub struct MqttConnection {
+    events: EventLoop,
+    publisher: MqttPublisher,
+    /// Telemetry route from the link manager
+    routes: Option<watch::Receiver<Option<LinkRoute>>>,
+}
+
+/// Set up a client for `config`, sealing any queued messages with
+/// `keyring`; connecting happens in [`MqttConnection::run`]
+pub fn connect(config: MqttConfig, keyring: Option<Arc<Keyring>>) -> Result<(MqttPublisher, MqttConnection), MqttError> {
+    let qos = Qos {
+        state: qos(&config.state)?,
+        threat: qos(&config.threat)?,
+        module_health: qos(&config.module_health)?,
+        command: qos(&config.command)?,
+    };
+
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options
+        .set_keep_alive(Duration::from_secs(config.keep_alive_secs.max(5)))
+        .set_max_packet_size(config.max_packet_bytes, config.max_packet_bytes);
+    if let Some(username) = &config.username {
+        options.set_credentials(username, config.password.clone().unwrap_or_default());
+    }
+    if let Some(tls) = &config.tls {
+        options.set_transport(Transport::tls_with_config(tls_configuration(tls)?));
+    }
+    if config.home_assistant.is_some() {
+        options.set_last_will(LastWill::new(home_assistant::availability_topic(&config), "offline", QoS::AtLeastOnce, true));
+    }
+
+    let outbox = config.outbox.clone().map(|outbox| Outbox::open(outbox, keyring)).transpose()?;
+    let (client, events) = AsyncClient::new(options, 64);
+    let publisher = MqttPublisher {
+        client,
+        config: Arc::new(config),
+        qos: Arc::new(qos),
+        outbox,
+        connected: Arc::new(AtomicBool::new(false)),
+        draining: Arc::new(AtomicBool::new(false)),
+    };
+    let connection = MqttConnection {
+        events,
+        publisher: publisher.clone(),
+        routes: None,
+    };
+    Ok((publisher, connection))
+}
+
+fn qos(topic: &TopicConfig) -> Result<QoS, MqttError> {
+    rumqttc::qos(topic.qos).map_err(|_| MqttError::InvalidQos(topic.qos, topic.topic.clone()))
+}
+
+fn tls_configuration(tls: &MqttTls) -> Result<TlsConfiguration, MqttError> {
+    let read = |path: &PathBuf| std::fs::read(path).map_err(|e| MqttError::Tls(path.clone(), e));
+    let client_auth = match (&tls.client_cert_path, &tls.client_key_path) {
+        (Some(cert), Some(key)) => Some((read(cert)?, read(key)?)),
+        (None, None) => None,
+        _ => return Err(MqttError::IncompleteClientAuth),
+    };
+    Ok(TlsConfiguration::Simple {
+        ca: read(&tls.ca_path)?,
+        alpn: None,
+        client_auth,
+    })
+}
+
+impl MqttPublisher {
+    /// Publish the drone state, or queue it in place of the last one queued
+    /// while the broker is unreachable
+    pub async fn publish_state(&self, state: &DroneState) -> Result<(), MqttError> {
+        let message = OutboundMessage::new(&self.config.state.topic, OutboxPriority::Routine, serde_json::to_vec(state)?).with_dedup_key("state");
+        self.publish(message).await
+    }
+
+    /// Publish a threat assessment (or anything else serializable) on the
+    /// threat topic, queued by its `threat_level` while the broker is
+    /// unreachable
+    pub async fn publish_threat<T: Serialize>(&self, assessment: &T) -> Result<(), MqttError> {
+        let value = serde_json::to_value(assessment)?;
+        let mut message = OutboundMessage::new(&self.config.threat.topic, OutboxPriority::of_json(&value), serde_json::to_vec(&value)?);
+        if let Some(id) = value.get("id").and_then(|id| id.as_str()) {
+            message = message.with_dedup_key(id);
+        }
+        self.publish(message).await
+    }
+
+    /// Messages waiting in the outbox for the broker
+    pub fn queued(&self) -> usize {
+        self.outbox.as_ref().map_or(0, Outbox::len)
+    }
+
+    async fn publish(&self, message: OutboundMessage) -> Result<(), MqttError> {
+        if let Some(outbox) = self.offline_outbox() {
+            outbox.push(message)?;
+            return Ok(());
+        }
+        let (qos, retain) = self.options_for(&message.destination);
+        self.client.publish(&message.destination, qos, retain, message.payload).await?;
+        Ok(())
+    }
+
+    /// The outbox, while there is one and the broker is unreachable
+    fn offline_outbox(&self) -> Option<&Outbox> {
+        self.outbox.as_ref().filter(|_| !self.connected.load(Ordering::Relaxed))
+    }
+
+    /// QoS and retain flag of the topic a message goes to
+    fn options_for(&self, topic: &str) -> (QoS, bool) {
+        let config = &self.config;
+        if topic == config.state.topic {
+            (self.qos.state, config.state.retain)
+        } else if config.home_assistant.is_some() && topic == home_assistant::fire_suppression_topic(config) {
+            (self.qos.state, true)
+        } else if topic.starts_with(&format!("{}/", config.module_health.topic)) {
+            (self.qos.module_health, config.module_health.retain)
+        } else {
+            (self.qos.threat, config.threat.retain)
+        }
+    }
+
+    /// Send what queued up while the broker was unreachable, unless a drain
+    /// is already running; stops if the connection drops again
+    fn drain(&self) {
+        let Some(outbox) = self.outbox.clone().filter(|outbox| !outbox.is_empty()) else { return };
+        if self.draining.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let publisher = self.clone();
+        crate::runtime::spawn(async move {
+            let sender = &publisher;
+            outbox
+                .drain(|message| async move {
+                    if !sender.connected.load(Ordering::Relaxed) {
+                        return Err("broker connection lost".to_string());
+                    }
+                    let (qos, retain) = sender.options_for(&message.destination);
+                    sender.client.publish(&message.destination, qos, retain, message.payload).await.map_err(|e| e.to_string())
+                })
+                .await;
+            publisher.draining.store(false, Ordering::Release);
+        });
+    }
+
+    /// Publish every item from `receiver` on the threat topic, e.g.
+    /// `publisher.forward(engine.subscribe())`, until the sender is dropped
+    pub fn forward<T>(&self, mut receiver: broadcast::Receiver<T>) -> Task<()>
+    where
+        T: Serialize + Clone + Send + Sync + 'static,
+    {
+        let publisher = self.clone();
+        crate::runtime::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(item) => {
+                        if let Err(e) = publisher.publish_threat(&item).await {
+                            warn!("📡 MQTT threat publish failed: {}", e);
+                        }
+                    },
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("📡 MQTT threat forwarding fell behind, skipped {}", skipped)
+                    },
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Queue without waiting, for the connection loop, which must keep
+    /// polling; into the outbox while the broker is unreachable
+    fn try_publish(&self, message: OutboundMessage) {
+        if let Some(outbox) = self.offline_outbox() {
+            if let Err(e) = outbox.push(message) {
+                warn!("📡 MQTT outbox write failed: {}", e);
+            }
+            return;
+        }
+        let (qos, retain) = self.options_for(&message.destination);
+        if let Err(e) = self.client.try_publish(&message.destination, qos, retain, message.payload) {
+            warn!("📡 MQTT publish to '{}' dropped: {}", message.destination, e);
+        }
+    }
+}
+
+impl MqttConnection {
+    /// Connect over the link telemetry is routed over, following `routes`
+    /// from [`crate::LinkManager::subscribe`]
+    pub fn with_links(mut self, mut routes: watch::Receiver<Option<LinkRoute>>) -> Self {
+        let route = routes.borrow_and_update().clone();
+        self.bind(route.as_ref());
+        self.routes = Some(routes);
+        self
+    }
+
+    /// Keep the broker connection up, publish state and module health, and
+    /// send received commands the sender may give to `commands`; runs until aborted
+    pub async fn run(
+        mut self,
+        drone: Arc<RwLock<DroneState>>,
+        auth: Arc<AuthConfig>,
+        verifier: Arc<CommandVerifier>,
+        commands: mpsc::Sender<(AuthContext, MqttCommand)>,
+    ) {
+        let config = Arc::clone(&self.publisher.config);
+        let mut telemetry = drone.read().await.subscribe_telemetry();
+        let mut ticker = crate::runtime::interval(Duration::from_millis(config.state_interval_ms.max(100)));
+        let mut delta = config.delta.clone().map(DeltaEncoder::new);
+        let mut delta_ticker = crate::runtime::interval(delta.as_ref().map_or(Duration::from_secs(3600), DeltaEncoder::tick_interval));
+        let mut routes = self.routes.take();
+        loop {
+            tokio::select! {
+                event = self.events.poll() => match event {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        info!("📡 Connected to MQTT broker {}:{}", config.host, config.port);
+                        self.publisher.connected.store(true, Ordering::Relaxed);
+                        self.publisher.drain();
+                        if let Some(delta) = &mut delta {
+                            delta.reset();
+                        }
+                        // Subscriptions do not survive a reconnect with a clean session
+                        if let Err(e) = self.publisher.client.try_subscribe(&config.command.topic, self.publisher.qos.command) {
+                            error!("📡 MQTT subscribe to '{}' failed: {}", config.command.topic, e);
+                        }
+                        if let Some(home_assistant) = &config.home_assistant {
+                            if let Err(e) = self.publisher.client.try_subscribe(home_assistant.status_topic(), QoS::AtLeastOnce) {
+                                error!("📡 MQTT subscribe to '{}' failed: {}", home_assistant.status_topic(), e);
+                            }
+                            let state = drone.read().await;
+                            self.announce(home_assistant, &state);
+                        }
+                    },
+                    Ok(Event::Incoming(Packet::Publish(publish)))
+                        if config.home_assistant.as_ref().is_some_and(|home_assistant| publish.topic == home_assistant.status_topic()) =>
+                    {
+                        // Home Assistant forgets devices whose discovery it missed while restarting
+                        if let Some(home_assistant) = config.home_assistant.as_ref().filter(|_| &publish.payload[..] == b"online") {
+                            let state = drone.read().await;
+                            self.announce(home_assistant, &state);
+                        }
+                    },
+                    Ok(Event::Incoming(Packet::Publish(publish))) if publish.topic == config.command.topic => {
+                        match parse_command(&publish.payload) {
+                            Ok((command, envelope, token)) => {
+                                let authorized =
+                                    verifier.authorize(envelope.as_ref(), token.as_deref(), &auth, CommandSource::Mqtt, command.action(), &mut *drone.write().await);
+                                let Ok(caller) = authorized else { continue };
+                                info!("📡 MQTT command from {}: {:?}", caller.principal, command);
+                                if commands.try_send((caller, command)).is_err() {
+                                    warn!("📡 MQTT command dropped: no one is handling commands");
+                                }
+                            },
+                            Err(e) => warn!("📡 Ignoring malformed MQTT command: {}", e),
+                        }
+                    },
+                    Ok(_) => {},
+                    Err(e) => {
+                        // The next poll reconnects
+                        warn!("📡 MQTT connection error: {}", e);
+                        self.publisher.connected.store(false, Ordering::Relaxed);
+                        crate::runtime::sleep(Duration::from_secs(1)).await;
+                    },
+                },
+                _ = ticker.tick() => {
+                    match serde_json::to_vec(&*drone.read().await) {
+                        Ok(payload) => {
+                            self.publisher.try_publish(OutboundMessage::new(&config.state.topic, OutboxPriority::Routine, payload).with_dedup_key("state"))
+                        },
+                        Err(e) => error!("📡 Failed to encode drone state: {}", e),
+                    }
+                },
+                _ = delta_ticker.tick(), if delta.is_some() => {
+                    let state = drone.read().await;
+                    if let Some(delta) = &mut delta {
+                        self.publish_deltas(delta, &state);
+                    }
+                },
+                changed = async { routes.as_mut()?.changed().await.ok() }, if routes.is_some() => match changed {
+                    Some(()) => {
+                        let route = routes.as_mut().and_then(|routes| routes.borrow_and_update().clone());
+                        self.bind(route.as_ref());
+                        // The next poll connects over the new route
+                        self.events.clean();
+                        self.publisher.connected.store(false, Ordering::Relaxed);
+                    },
+                    // The link manager is gone; stay on the last route
+                    None => routes = None,
+                },
+                received = telemetry.recv() => match received {
+                    Ok(message) => self.publish_telemetry(&message),
+                    Err(broadcast::error::RecvError::Lagged(_)) => {},
+                    // The drone state is gone; nothing left to publish
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            }
+        }
+    }
+
+    /// Make the next connection over `route` (absent = the default route)
+    fn bind(&mut self, route: Option<&LinkRoute>) {
+        let mut options = NetworkOptions::new();
+        #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+        if let Some(interface) = route.and_then(|route| route.interface.as_deref()) {
+            options.set_bind_device(interface);
+        }
+        if let Some(route) = route {
+            info!("📡 MQTT connecting over {} ({})", route.name, route.kind);
+        }
+        self.events.network_options = options;
+    }
+
+    /// Send the delta channels that are due; a frame that cannot be sent
+    /// puts every channel back to keyframes, as the ground would otherwise
+    /// be left out of step
+    fn publish_deltas(&self, delta: &mut DeltaEncoder, drone: &DroneState) {
+        if !self.publisher.connected.load(Ordering::Relaxed) {
+            return;
+        }
+        let frames = match delta.encode(drone, chrono::Utc::now()) {
+            Ok(frames) => frames,
+            Err(e) => {
+                error!("📡 Failed to encode delta telemetry: {}", e);
+                return;
+            },
+        };
+        let config = &self.publisher.config;
+        for frame in frames {
+            let topic = format!("{}/delta/{}", config.state.topic, frame.channel);
+            if let Err(e) = self.publisher.client.try_publish(&topic, self.publisher.qos.state, false, frame.bytes) {
+                warn!("📡 MQTT delta frame on '{}' dropped, resending keyframes: {}", topic, e);
+                delta.reset();
+                return;
+            }
+        }
+    }
+
+    /// Module health goes to a retained per-module topic, and fire
+    /// suppression readiness to its own for Home Assistant; other telemetry
+    /// is not published
+    fn publish_telemetry(&self, message: &TelemetryMessage) {
+        let config = &self.publisher.config;
+        let (topic, payload) = match message {
+            TelemetryMessage::ModuleHealth { module, .. } => (format!("{}/{}", config.module_health.topic, module), serde_json::to_vec(message)),
+            TelemetryMessage::FireSuppression(status) if config.home_assistant.is_some() => {
+                (home_assistant::fire_suppression_topic(config), serde_json::to_vec(status))
+            },
+            _ => return,
+        };
+        match payload {
+            Ok(payload) => self.publisher.try_publish(OutboundMessage::new(&topic, OutboxPriority::Routine, payload).with_dedup_key(&topic)),
+            Err(e) => error!("📡 Failed to encode telemetry for '{}': {}", topic, e),
+        }
+    }
+
+    /// Mark the drone online and publish its Home Assistant discovery configs
+    fn announce(&self, home_assistant: &HomeAssistantConfig, drone: &DroneState) {
+        let config = &self.publisher.config;
+        let client = &self.publisher.client;
+        let mut messages = vec![(home_assistant::availability_topic(config), b"online".to_vec())];
+        messages.extend(home_assistant.discovery(config, drone).into_iter().map(|(topic, payload)| (topic, payload.to_string().into_bytes())));
+        for (topic, payload) in messages {
+            if let Err(e) = client.try_publish(&topic, QoS::AtLeastOnce, true, payload) {
+                warn!("📡 Home Assistant discovery on '{}' dropped: {}", topic, e);
+            }
+        }
+        info!("📡 Announced to Home Assistant under '{}'", home_assistant.discovery_prefix);
+    }
+}
@@ -0,0 +1,90 @@
+//! OTLP trace export (`otel` feature)
+
+use opentelemetry::trace::{TraceContextExt, TracerProvider as _};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OtelConfig {
+    /// OTLP gRPC collector endpoint
+    pub endpoint: String,
+    pub service_name: String,
+}
+
+impl Default for OtelConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "http://localhost:4317".to_string(),
+            service_name: "dark-phoenix".to_string(),
+        }
+    }
+}
+
+impl OtelConfig {
+    /// From the standard `OTEL_EXPORTER_OTLP_ENDPOINT` and `OTEL_SERVICE_NAME`
+    /// variables; absent when no endpoint is set
+    pub fn from_env() -> Option<Self> {
+        let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok()?;
+        let service_name = std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| Self::default().service_name);
+        Some(Self { endpoint, service_name })
+    }
+}
+
+/// Flushes buffered spans when dropped; keep it alive until exit
+pub struct OtelGuard {
+    provider: SdkTracerProvider,
+}
+
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            warn!("Failed to flush traces: {}", e);
+        }
+    }
+}
+
+/// Install a subscriber that logs to stdout and exports spans over OTLP;
+/// call from within the Tokio runtime, in place of `tracing_subscriber::fmt::init`
+pub fn init_tracing(config: &OtelConfig) -> Result<OtelGuard, Box<dyn std::error::Error + Send + Sync>> {
+    init_tracing_to(config, std::io::stdout)
+}
+
+/// As [`init_tracing`], logging to `writer`, e.g. a file while the dashboard
+/// owns the terminal
+pub fn init_tracing_to<W>(config: &OtelConfig, writer: W) -> Result<OtelGuard, Box<dyn std::error::Error + Send + Sync>>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(&config.endpoint)
+        .build()?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(config.service_name.clone()).build())
+        .build();
+    let tracer = provider.tracer("dark-phoenix");
+
+    tracing_subscriber::registry()
+        .with(LevelFilter::INFO)
+        .with(tracing_subscriber::fmt::layer().with_writer(writer))
+        .with(tracing_opentelemetry::layer().with_tracer(tracer))
+        .try_init()?;
+    Ok(OtelGuard { provider })
+}
+
+/// Trace ID of the current span, if it is being exported
+pub fn current_trace_id() -> Option<String> {
+    let context = tracing::Span::current().context();
+    let span = context.span();
+    let span_context = span.span_context();
+    span_context.is_valid().then(|| span_context.trace_id().to_string())
+}
@@ -0,0 +1,342 @@
+//! Store-and-forward queue for telemetry that cannot be sent yet
+
+use crate::{Keyring, StoreError, TelemetryMessage, ThreatLevel, VaultError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutboxConfig {
+    /// Directory the queued messages are kept in
+    pub dir: PathBuf,
+    pub max_messages: usize,
+    /// Total size of the queued payloads
+    pub max_bytes: u64,
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("data/outbox"),
+            max_messages: 10_000,
+            max_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+impl OutboxConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.max_messages == 0 {
+            problems.push("outbox.max_messages must be positive".to_string());
+        }
+        if self.max_bytes == 0 {
+            problems.push("outbox.max_bytes must be positive".to_string());
+        }
+        problems
+    }
+}
+
+/// How urgently a queued message goes out on reconnection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxPriority {
+    /// Periodic status, superseded by the next snapshot
+    Routine,
+    Event,
+    /// Anything at Red or above
+    Alert,
+}
+
+impl OutboxPriority {
+    pub(crate) fn of_level(level: ThreatLevel) -> Self {
+        if level >= ThreatLevel::Red {
+            OutboxPriority::Alert
+        } else {
+            OutboxPriority::Event
+        }
+    }
+
+    /// OutboxPriority of a serialized event by its `threat_level` field, e.g. a
+    /// threat assessment
+    pub fn of_json(value: &serde_json::Value) -> Self {
+        value
+            .get("threat_level")
+            .and_then(|level| serde_json::from_value(level.clone()).ok())
+            .map_or(OutboxPriority::Event, Self::of_level)
+    }
+
+    /// OutboxPriority of a telemetry message and the key it is deduplicated by:
+    /// status-like messages are superseded by the next of their kind,
+    /// events are kept one by one
+    pub fn of_telemetry(message: &TelemetryMessage) -> (Self, Option<String>) {
+        match message {
+            TelemetryMessage::Event(event) => (Self::of_level(event.threat_level), Some(format!("event/{}", event.id))),
+            TelemetryMessage::ThreatTransition(transition) => (Self::of_level(transition.to), None),
+            TelemetryMessage::ModuleHealth { module, .. } => (OutboxPriority::Routine, Some(format!("module_health/{}", module))),
+            TelemetryMessage::Status { .. } => (OutboxPriority::Routine, Some("status".to_string())),
+            TelemetryMessage::Health(_) => (OutboxPriority::Routine, Some("health".to_string())),
+            TelemetryMessage::Risk { .. } => (OutboxPriority::Routine, Some("risk".to_string())),
+            TelemetryMessage::FireSuppression(_) => (OutboxPriority::Routine, Some("fire_suppression".to_string())),
+            TelemetryMessage::Deterrence(_) => (OutboxPriority::Routine, Some("deterrence".to_string())),
+            TelemetryMessage::Shield(_) => (OutboxPriority::Routine, Some("shield".to_string())),
+            TelemetryMessage::Flight(_) => (OutboxPriority::Routine, Some("flight".to_string())),
+            TelemetryMessage::Power(_) => (OutboxPriority::Routine, Some("power".to_string())),
+            TelemetryMessage::Links(_) => (OutboxPriority::Routine, Some("links".to_string())),
+        }
+    }
+}
+
+impl fmt::Display for OutboxPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OutboxPriority::Routine => "routine",
+            OutboxPriority::Event => "event",
+            OutboxPriority::Alert => "alert",
+        })
+    }
+}
+
+/// One message waiting to be sent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutboundMessage {
+    /// Where the publisher sends it, e.g. an MQTT topic
+    pub destination: String,
+    pub priority: OutboxPriority,
+    /// Replaces any queued message with the same key
+    pub dedup_key: Option<String>,
+    pub queued_at: DateTime<Utc>,
+    pub payload: Vec<u8>,
+}
+
+impl OutboundMessage {
+    pub fn new(destination: impl Into<String>, priority: OutboxPriority, payload: Vec<u8>) -> Self {
+        Self {
+            destination: destination.into(),
+            priority,
+            dedup_key: None,
+            queued_at: Utc::now(),
+            payload,
+        }
+    }
+
+    pub fn with_dedup_key(mut self, key: impl Into<String>) -> Self {
+        self.dedup_key = Some(key.into());
+        self
+    }
+}
+
+/// What the queue keeps in memory of each file
+#[derive(Debug, Clone)]
+struct Entry {
+    seq: u64,
+    priority: OutboxPriority,
+    /// Destination and dedup key together, as a key is per destination
+    dedup_key: Option<(String, String)>,
+    bytes: u64,
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    entries: Vec<Entry>,
+    next_seq: u64,
+    bytes: u64,
+}
+
+impl Queue {
+    /// The next to send: most urgent, then oldest
+    fn next(&self) -> Option<&Entry> {
+        self.entries.iter().max_by(|a, b| a.priority.cmp(&b.priority).then(b.seq.cmp(&a.seq)))
+    }
+
+    /// The first to drop: least urgent, then oldest
+    fn victim(&self) -> Option<&Entry> {
+        self.entries.iter().min_by(|a, b| a.priority.cmp(&b.priority).then(a.seq.cmp(&b.seq)))
+    }
+
+    fn remove(&mut self, seq: u64) -> Option<Entry> {
+        let index = self.entries.iter().position(|entry| entry.seq == seq)?;
+        let entry = self.entries.remove(index);
+        self.bytes -= entry.bytes;
+        Some(entry)
+    }
+}
+
+/// A persistent queue of outbound messages; clones share it
+#[derive(Debug, Clone)]
+pub struct Outbox {
+    config: Arc<OutboxConfig>,
+    keyring: Option<Arc<Keyring>>,
+    queue: Arc<Mutex<Queue>>,
+}
+
+impl Outbox {
+    /// Open the queue in `config.dir`, picking up what an earlier run left
+    pub fn open(config: OutboxConfig, keyring: Option<Arc<Keyring>>) -> Result<Self, StoreError> {
+        std::fs::create_dir_all(&config.dir)?;
+        let outbox = Self {
+            config: Arc::new(config),
+            keyring,
+            queue: Arc::new(Mutex::new(Queue::default())),
+        };
+        let mut queue = Queue::default();
+        for file in std::fs::read_dir(&outbox.config.dir)? {
+            let path = file?.path();
+            let Some(seq) = seq_of(&path) else { continue };
+            // A torn write from a crash is skipped and removed
+            match outbox.read(seq) {
+                Ok(message) => {
+                    let bytes = message.payload.len() as u64;
+                    queue.entries.push(Entry {
+                        seq,
+                        priority: message.priority,
+                        dedup_key: message.dedup_key.map(|key| (message.destination, key)),
+                        bytes,
+                    });
+                    queue.bytes += bytes;
+                },
+                Err(StoreError::Vault(e)) => return Err(e.into()),
+                Err(e) => {
+                    tracing::warn!("📦 Discarding unreadable queued message {}: {}", path.display(), e);
+                    std::fs::remove_file(&path)?;
+                },
+            }
+            queue.next_seq = queue.next_seq.max(seq + 1);
+        }
+        if !queue.entries.is_empty() {
+            tracing::info!("📦 {} messages waiting in the outbox", queue.entries.len());
+        }
+        *outbox.lock() = queue;
+        Ok(outbox)
+    }
+
+    /// Queue a message, replacing one with the same dedup key and dropping
+    /// the least urgent to stay within the caps
+    pub fn push(&self, message: OutboundMessage) -> Result<(), StoreError> {
+        let bytes = message.payload.len() as u64;
+        let dedup_key = message.dedup_key.clone().map(|key| (message.destination.clone(), key));
+        let mut encoded = serde_json::to_vec(&message)?;
+        if let Some(keyring) = &self.keyring {
+            encoded = keyring.seal(&encoded);
+        }
+
+        let mut queue = self.lock();
+        let seq = queue.next_seq;
+        queue.next_seq += 1;
+        // Written to a temporary name first so a crash never leaves half a message
+        let path = self.path(seq);
+        let temp = path.with_extension("tmp");
+        std::fs::write(&temp, &encoded)?;
+        std::fs::rename(&temp, &path)?;
+
+        if let Some(key) = &dedup_key {
+            let superseded: Vec<u64> = queue.entries.iter().filter(|entry| entry.dedup_key.as_ref() == Some(key)).map(|entry| entry.seq).collect();
+            for seq in superseded {
+                queue.remove(seq);
+                self.delete(seq);
+            }
+        }
+        queue.entries.push(Entry {
+            seq,
+            priority: message.priority,
+            dedup_key,
+            bytes,
+        });
+        queue.bytes += bytes;
+
+        while queue.entries.len() > self.config.max_messages || queue.bytes > self.config.max_bytes {
+            let Some(victim) = queue.victim().map(|entry| entry.seq) else { break };
+            if let Some(dropped) = queue.remove(victim) {
+                tracing::warn!("📦 Outbox full: dropped a queued {} message", dropped.priority);
+                self.delete(victim);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().entries.is_empty()
+    }
+
+    /// Total size of the queued payloads
+    pub fn bytes(&self) -> u64 {
+        self.lock().bytes
+    }
+
+    /// Hand queued messages to `send`, most urgent first, removing each one
+    /// it accepts; stops at the first failure and leaves the rest queued.
+    /// Returns how many went out.
+    pub async fn drain<F, Fut, E>(&self, mut send: F) -> usize
+    where
+        F: FnMut(OutboundMessage) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+        E: fmt::Display,
+    {
+        let mut sent = 0;
+        loop {
+            let Some(seq) = self.lock().next().map(|entry| entry.seq) else { break };
+            let message = match self.read(seq) {
+                Ok(message) => message,
+                Err(e) => {
+                    tracing::warn!("📦 Discarding unreadable queued message {}: {}", seq, e);
+                    self.lock().remove(seq);
+                    self.delete(seq);
+                    continue;
+                },
+            };
+            if let Err(e) = send(message).await {
+                tracing::warn!("📦 Outbox drain stopped after {} messages: {}", sent, e);
+                break;
+            }
+            // A push may have superseded it meanwhile, which removed it already
+            if self.lock().remove(seq).is_some() {
+                self.delete(seq);
+            }
+            sent += 1;
+        }
+        if sent > 0 {
+            tracing::info!("📦 Sent {} queued messages, {} still waiting", sent, self.len());
+        }
+        sent
+    }
+
+    fn read(&self, seq: u64) -> Result<OutboundMessage, StoreError> {
+        let mut data = std::fs::read(self.path(seq))?;
+        match &self.keyring {
+            Some(keyring) => data = keyring.open(&data)?,
+            None if crate::vault::is_sealed(&data) => return Err(VaultError::Locked.into()),
+            None => {},
+        }
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    fn delete(&self, seq: u64) {
+        if let Err(e) = std::fs::remove_file(self.path(seq)) {
+            tracing::warn!("📦 Failed to remove queued message {}: {}", seq, e);
+        }
+    }
+
+    fn path(&self, seq: u64) -> PathBuf {
+        self.config.dir.join(format!("{:016}.msg", seq))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn seq_of(path: &Path) -> Option<u64> {
+    if path.extension()? != "msg" {
+        return None;
+    }
+    path.file_stem()?.to_str()?.parse().ok()
+}
@@ -0,0 +1,336 @@
+//! Pairing ground stations with the drone
+
+use crate::envelope::verifying_key;
+use crate::{CommandKey, CommandVerifier, DroneState, EventStore, EventType, Keyring, Role, StoreError};
+use chrono::{DateTime, Duration, Utc};
+use ed25519_dalek::SigningKey;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const PAIRING_STREAM: &str = "paired_controllers";
+
+/// Scheme of the link a pairing QR code carries
+pub const PAIRING_URI_SCHEME: &str = "phoenix-pair://";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PairingConfig {
+    /// Where paired controllers are kept
+    pub store_dir: PathBuf,
+    /// Role every paired controller gets
+    pub role: Role,
+    /// How long a PIN stays good
+    pub code_ttl_secs: u64,
+    /// Wrong PINs before the code is void
+    pub max_attempts: u32,
+}
+
+impl Default for PairingConfig {
+    fn default() -> Self {
+        Self {
+            store_dir: PathBuf::from("pairing"),
+            role: Role::Operator,
+            code_ttl_secs: 120,
+            max_attempts: 5,
+        }
+    }
+}
+
+impl PairingConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.code_ttl_secs == 0 {
+            problems.push("pairing.code_ttl_secs must be positive".to_string());
+        }
+        if self.max_attempts == 0 {
+            problems.push("pairing.max_attempts must be at least 1".to_string());
+        }
+        problems
+    }
+}
+
+/// An open invitation to pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairingCode {
+    pub pin: String,
+    pub drone: Uuid,
+    pub expires: DateTime<Utc>,
+}
+
+impl PairingCode {
+    /// What the QR code carries: `phoenix-pair://<drone id>?pin=<pin>`
+    pub fn uri(&self) -> String {
+        format!("{}{}?pin={}", PAIRING_URI_SCHEME, self.drone, self.pin)
+    }
+
+    /// The drone id and PIN in a scanned pairing link
+    pub fn parse_uri(uri: &str) -> Option<(Uuid, String)> {
+        let (drone, pin) = uri.strip_prefix(PAIRING_URI_SCHEME)?.split_once("?pin=")?;
+        Some((drone.parse().ok()?, pin.to_string()))
+    }
+}
+
+/// A controller asking to pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairingRequest {
+    /// Becomes the key id, and the caller in the mission log
+    pub name: String,
+    /// Hex Ed25519 public key
+    pub public_key: String,
+    /// Hex HMAC-SHA256 of the name and key, keyed by the PIN
+    pub proof: String,
+}
+
+impl PairingRequest {
+    /// Ask to pair `key` as `name`, as a controller would
+    pub fn new(name: &str, key: &SigningKey, pin: &str) -> Self {
+        let public_key = hex::encode(key.verifying_key().to_bytes());
+        let proof = hex::encode(mac(pin, &request_payload(name, &public_key)).finalize().into_bytes());
+        Self {
+            name: name.to_string(),
+            public_key,
+            proof,
+        }
+    }
+}
+
+/// The drone's answer to a successful pairing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairingResponse {
+    pub drone: Uuid,
+    pub drone_name: String,
+    /// Id to sign commands with
+    pub key_id: String,
+    pub role: Role,
+    /// Hex HMAC-SHA256 of the drone id and the pairing, keyed by the PIN
+    pub proof: String,
+}
+
+impl PairingResponse {
+    /// Whether the answer came from a drone that knew `pin`
+    pub fn verify(&self, request: &PairingRequest, pin: &str) -> bool {
+        let Ok(proof) = hex::decode(&self.proof) else { return false };
+        mac(pin, &response_payload(self.drone, &request.name, &request.public_key, self.role))
+            .verify_slice(&proof)
+            .is_ok()
+    }
+}
+
+/// A controller paired with the drone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairedController {
+    pub name: String,
+    pub role: Role,
+    pub public_key: String,
+    pub paired_at: DateTime<Utc>,
+}
+
+impl PairedController {
+    pub fn key(&self) -> CommandKey {
+        CommandKey {
+            id: self.name.clone(),
+            role: self.role,
+            public_key: self.public_key.clone(),
+        }
+    }
+}
+
+/// One line of the pairing stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum PairingRecord {
+    Paired(PairedController),
+    Revoked { name: String, by: String, at: DateTime<Utc> },
+}
+
+#[derive(Debug, Error)]
+pub enum PairingError {
+    #[error("no pairing code is open; open one on the drone first")]
+    NoCode,
+    #[error("the pairing code has expired")]
+    Expired,
+    #[error("wrong PIN")]
+    WrongPin,
+    #[error("too many wrong PINs; the pairing code is void")]
+    TooManyAttempts,
+    #[error("controller name '{0}' must be 1-32 letters, digits, '.', '-' or '_'")]
+    BadName(String),
+    #[error("not a 64-hex-digit Ed25519 public key")]
+    BadKey,
+    #[error("a key named '{0}' is already registered")]
+    NameTaken(String),
+    #[error("no paired controller named '{0}'")]
+    UnknownController(String),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+#[derive(Debug)]
+struct OpenCode {
+    code: PairingCode,
+    attempts: u32,
+}
+
+/// Paired controllers and the pairing code, if one is open
+#[derive(Debug)]
+pub struct Pairing {
+    config: PairingConfig,
+    store: EventStore,
+    controllers: BTreeMap<String, PairedController>,
+    code: Option<OpenCode>,
+}
+
+impl Pairing {
+    /// Open the store under `store_dir`, encrypted with `keyring` when
+    /// given, and replay the pairings in it
+    pub fn open(config: PairingConfig, keyring: Option<Arc<Keyring>>) -> Result<Self, StoreError> {
+        let store = EventStore::open(&config.store_dir)?.with_keyring(keyring);
+        let mut controllers = BTreeMap::new();
+        for record in store.read::<PairingRecord>(PAIRING_STREAM)? {
+            match record {
+                PairingRecord::Paired(controller) => {
+                    controllers.insert(controller.name.clone(), controller);
+                },
+                PairingRecord::Revoked { name, .. } => {
+                    controllers.remove(&name);
+                },
+            }
+        }
+        Ok(Self {
+            config,
+            store,
+            controllers,
+            code: None,
+        })
+    }
+
+    pub fn controllers(&self) -> Vec<PairedController> {
+        self.controllers.values().cloned().collect()
+    }
+
+    /// Keys of every paired controller, for the command verifier
+    pub fn keys(&self) -> Vec<CommandKey> {
+        self.controllers.values().map(PairedController::key).collect()
+    }
+
+    /// Open a fresh code, voiding any earlier one
+    pub fn start(&mut self, drone: &DroneState, now: DateTime<Utc>) -> PairingCode {
+        let code = PairingCode {
+            pin: format!("{:06}", rand::random::<u32>() % 1_000_000),
+            drone: drone.id,
+            expires: now + Duration::seconds(self.config.code_ttl_secs as i64),
+        };
+        tracing::info!("🔐 Pairing open until {}", code.expires.format("%H:%M:%S UTC"));
+        self.code = Some(OpenCode { code: code.clone(), attempts: 0 });
+        code
+    }
+
+    /// Check `request` against the open code and, if it proves the PIN,
+    /// register its key with `verifier` and keep it; the code is used up
+    pub fn pair(
+        &mut self,
+        request: &PairingRequest,
+        verifier: &CommandVerifier,
+        drone: &mut DroneState,
+        now: DateTime<Utc>,
+    ) -> Result<PairingResponse, PairingError> {
+        let open = self.code.as_mut().ok_or(PairingError::NoCode)?;
+        if now > open.code.expires {
+            self.code = None;
+            return Err(PairingError::Expired);
+        }
+        let proof = hex::decode(&request.proof).unwrap_or_default();
+        if mac(&open.code.pin, &request_payload(&request.name, &request.public_key)).verify_slice(&proof).is_err() {
+            open.attempts += 1;
+            let void = open.attempts >= self.config.max_attempts;
+            let error = if void { PairingError::TooManyAttempts } else { PairingError::WrongPin };
+            tracing::warn!("🔐 Pairing as '{}' refused: {}", request.name, error);
+            drone.log_event(EventType::AccessDenied, format!("Pairing as '{}' refused: {}", request.name, error), Vec::new());
+            if void {
+                self.code = None;
+            }
+            return Err(error);
+        }
+        let valid_name = (1..=32).contains(&request.name.len())
+            && request.name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+        if !valid_name {
+            return Err(PairingError::BadName(request.name.clone()));
+        }
+        if verifying_key(&request.public_key).is_none() {
+            return Err(PairingError::BadKey);
+        }
+        if verifier.has_key(&request.name) {
+            return Err(PairingError::NameTaken(request.name.clone()));
+        }
+
+        let pin = self.code.take().map(|open| open.code.pin).unwrap_or_default();
+        let controller = PairedController {
+            name: request.name.clone(),
+            role: self.config.role,
+            public_key: request.public_key.clone(),
+            paired_at: now,
+        };
+        self.store.append(PAIRING_STREAM, &PairingRecord::Paired(controller.clone()))?;
+        self.store.flush()?;
+        verifier.add_key(&controller.key());
+        tracing::info!("🔐 Paired controller '{}' as {}", controller.name, controller.role);
+        drone.log_event(
+            EventType::ControllerPaired,
+            format!("Paired controller '{}' as {}", controller.name, controller.role),
+            vec![format!("Public key {}", controller.public_key)],
+        );
+        let proof = mac(&pin, &response_payload(drone.id, &controller.name, &controller.public_key, controller.role)).finalize();
+        self.controllers.insert(controller.name.clone(), controller.clone());
+        Ok(PairingResponse {
+            drone: drone.id,
+            drone_name: drone.name.clone(),
+            key_id: controller.name,
+            role: controller.role,
+            proof: hex::encode(proof.into_bytes()),
+        })
+    }
+
+    /// Forget a paired controller and refuse its commands from now on
+    pub fn revoke(&mut self, name: &str, by: &str, verifier: &CommandVerifier, drone: &mut DroneState, now: DateTime<Utc>) -> Result<(), PairingError> {
+        if !self.controllers.contains_key(name) {
+            return Err(PairingError::UnknownController(name.to_string()));
+        }
+        self.store.append(
+            PAIRING_STREAM,
+            &PairingRecord::Revoked {
+                name: name.to_string(),
+                by: by.to_string(),
+                at: now,
+            },
+        )?;
+        self.store.flush()?;
+        self.controllers.remove(name);
+        verifier.remove_key(name);
+        tracing::warn!("🔐 Controller '{}' revoked by {}", name, by);
+        drone.log_event(EventType::ControllerRevoked, format!("Controller '{}' revoked by {}", name, by), Vec::new());
+        Ok(())
+    }
+}
+
+fn request_payload(name: &str, public_key: &str) -> String {
+    format!("dark-phoenix-pair\n{}\n{}", name, public_key)
+}
+
+fn response_payload(drone: Uuid, name: &str, public_key: &str, role: Role) -> String {
+    format!("dark-phoenix-paired\n{}\n{}\n{}\n{}", drone, name, public_key, role)
+}
+
+fn mac(pin: &str, payload: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(pin.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(payload.as_bytes());
+    mac
+}
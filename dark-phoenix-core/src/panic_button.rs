@@ -0,0 +1,315 @@
+//! Panic button: the protectee summons the full response by hand, from their
+//! wearable, the phone app or the API
+
+use crate::{DroneState, EventType, ThreatLevel};
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fmt;
+use thiserror::Error;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A device allowed to send panic commands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanicDevice {
+    pub id: String,
+    /// Shared signing secret
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanicConfig {
+    pub devices: Vec<PanicDevice>,
+    /// Oldest command accepted
+    pub max_age_secs: u64,
+    /// How far ahead of the drone's clock a device may be
+    pub max_clock_skew_secs: u64,
+}
+
+impl Default for PanicConfig {
+    fn default() -> Self {
+        Self {
+            devices: Vec::new(),
+            max_age_secs: 30,
+            max_clock_skew_secs: 5,
+        }
+    }
+}
+
+impl PanicConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (i, device) in self.devices.iter().enumerate() {
+            if self.devices[..i].iter().any(|earlier| earlier.id == device.id) {
+                problems.push(format!("panic device '{}' is defined twice", device.id));
+            }
+            if device.secret.len() < 16 {
+                problems.push(format!("panic device '{}' needs a secret of at least 16 bytes", device.id));
+            }
+        }
+        if self.max_age_secs == 0 {
+            problems.push("panic.max_age_secs must be positive".to_string());
+        }
+        problems
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PanicAction {
+    Activate,
+    /// False alarm
+    Cancel,
+}
+
+impl fmt::Display for PanicAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PanicAction::Activate => "activate",
+            PanicAction::Cancel => "cancel",
+        })
+    }
+}
+
+/// A signed press or cancel, as sent by a device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanicCommand {
+    pub device_id: String,
+    pub action: PanicAction,
+    /// Increases with every command the device sends
+    pub sequence: u64,
+    pub timestamp: DateTime<Utc>,
+    /// Hex HMAC-SHA256 of `signed_payload()`
+    pub signature: String,
+}
+
+impl PanicCommand {
+    /// Build and sign a command, as a device or test client would
+    pub fn sign(device_id: &str, action: PanicAction, sequence: u64, timestamp: DateTime<Utc>, secret: &str) -> Self {
+        let mut command = Self {
+            device_id: device_id.to_string(),
+            action,
+            sequence,
+            timestamp,
+            signature: String::new(),
+        };
+        command.signature = hex::encode(mac(secret, &command.signed_payload()).finalize().into_bytes());
+        command
+    }
+
+    /// `device_id`, action, sequence and timestamp (Unix milliseconds), one per line
+    pub fn signed_payload(&self) -> String {
+        format!("{}\n{}\n{}\n{}", self.device_id, self.action, self.sequence, self.timestamp.timestamp_millis())
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PanicError {
+    #[error("unknown panic device '{0}'")]
+    UnknownDevice(String),
+    #[error("bad signature from panic device '{0}'")]
+    BadSignature(String),
+    #[error("panic command is {0}s old")]
+    Expired(i64),
+    #[error("panic command is timestamped {0}s in the future")]
+    FromTheFuture(i64),
+    #[error("panic command {sequence} replayed (last accepted {last})")]
+    Replayed { sequence: u64, last: u64 },
+    #[error("no panic to cancel")]
+    NotActive,
+}
+
+/// What an accepted command did
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum PanicOutcome {
+    Activated { device_id: String, previous_level: ThreatLevel },
+    /// Pressed again while already active; nothing more to do
+    AlreadyActive { device_id: String },
+    /// `threat_level` is where the drone stands after the cancel
+    Cancelled { device_id: String, threat_level: ThreatLevel },
+}
+
+#[derive(Debug, Clone)]
+struct ActivePanic {
+    device_id: String,
+    previous_level: ThreatLevel,
+}
+
+/// Verifies panic commands and applies them to the drone
+#[derive(Debug, Clone)]
+pub struct PanicButton {
+    config: PanicConfig,
+    last_sequence: HashMap<String, u64>,
+    active: Option<ActivePanic>,
+}
+
+impl PanicButton {
+    pub fn new(config: PanicConfig) -> Self {
+        Self {
+            config,
+            last_sequence: HashMap::new(),
+            active: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Check the signature, age and sequence; an accepted command's sequence
+    /// is used up, whatever it goes on to do
+    pub fn verify(&mut self, command: &PanicCommand, now: DateTime<Utc>) -> Result<(), PanicError> {
+        let device = self
+            .config
+            .devices
+            .iter()
+            .find(|device| device.id == command.device_id)
+            .ok_or_else(|| PanicError::UnknownDevice(command.device_id.clone()))?;
+        let signature = hex::decode(&command.signature).map_err(|_| PanicError::BadSignature(device.id.clone()))?;
+        mac(&device.secret, &command.signed_payload())
+            .verify_slice(&signature)
+            .map_err(|_| PanicError::BadSignature(device.id.clone()))?;
+
+        let age = now - command.timestamp;
+        if age > Duration::seconds(self.config.max_age_secs as i64) {
+            return Err(PanicError::Expired(age.num_seconds()));
+        }
+        if -age > Duration::seconds(self.config.max_clock_skew_secs as i64) {
+            return Err(PanicError::FromTheFuture(-age.num_seconds()));
+        }
+        if let Some(&last) = self.last_sequence.get(&device.id).filter(|last| command.sequence <= **last) {
+            return Err(PanicError::Replayed { sequence: command.sequence, last });
+        }
+        self.last_sequence.insert(device.id.clone(), command.sequence);
+        Ok(())
+    }
+
+    /// Verify `command` and carry it out on the drone
+    pub fn handle(&mut self, command: &PanicCommand, drone: &mut DroneState, now: DateTime<Utc>) -> Result<PanicOutcome, PanicError> {
+        self.verify(command, now)?;
+        let device_id = command.device_id.clone();
+        match command.action {
+            PanicAction::Activate if self.active.is_some() => Ok(PanicOutcome::AlreadyActive { device_id }),
+            PanicAction::Activate => {
+                let previous_level = drone.threat_level();
+                tracing::error!("🆘 Panic button pressed on {}", device_id);
+                drone.log_event(
+                    EventType::PanicActivated,
+                    format!("Panic button pressed on {}", device_id),
+                    vec![
+                        "Escalating to RED".to_string(),
+                        "Deterrence: imminent danger".to_string(),
+                        "Notifying emergency contacts".to_string(),
+                    ],
+                );
+                drone.escalate_threat(ThreatLevel::Red, format!("Panic button pressed on {}", device_id));
+                self.active = Some(ActivePanic {
+                    device_id: device_id.clone(),
+                    previous_level,
+                });
+                Ok(PanicOutcome::Activated { device_id, previous_level })
+            },
+            PanicAction::Cancel => {
+                let active = self.active.take().ok_or(PanicError::NotActive)?;
+                tracing::warn!("🆘 Panic from {} cancelled by {}: false alarm", active.device_id, device_id);
+                drone.log_event(
+                    EventType::PanicCancelled,
+                    format!("Panic from {} cancelled by {}: false alarm", active.device_id, device_id),
+                    vec!["Standing down deterrence".to_string(), "Telling emergency contacts it was a false alarm".to_string()],
+                );
+                if let Err(e) = drone.cancel_false_alarm(active.previous_level, "Panic cancelled: false alarm".to_string()) {
+                    tracing::info!("🆘 Threat level stays at {} for now: {}", drone.threat_level().as_str(), e);
+                }
+                Ok(PanicOutcome::Cancelled {
+                    device_id,
+                    threat_level: drone.threat_level(),
+                })
+            },
+        }
+    }
+}
+
+fn mac(secret: &str, payload: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "wearable-secret-0001";
+
+    fn button() -> PanicButton {
+        PanicButton::new(PanicConfig {
+            devices: vec![PanicDevice {
+                id: "wearable".to_string(),
+                secret: SECRET.to_string(),
+            }],
+            ..PanicConfig::default()
+        })
+    }
+
+    fn command(action: PanicAction, sequence: u64, timestamp: DateTime<Utc>) -> PanicCommand {
+        PanicCommand::sign("wearable", action, sequence, timestamp, SECRET)
+    }
+
+    #[test]
+    fn rejects_a_bad_signature() {
+        let now = Utc::now();
+        let forged = PanicCommand::sign("wearable", PanicAction::Activate, 1, now, "not-the-wearable-secret");
+        assert_eq!(button().verify(&forged, now), Err(PanicError::BadSignature("wearable".to_string())));
+
+        let mut tampered = command(PanicAction::Cancel, 1, now);
+        tampered.action = PanicAction::Activate;
+        assert_eq!(button().verify(&tampered, now), Err(PanicError::BadSignature("wearable".to_string())));
+    }
+
+    #[test]
+    fn rejects_a_replayed_sequence() {
+        let now = Utc::now();
+        let mut button = button();
+        button.verify(&command(PanicAction::Activate, 2, now), now).unwrap();
+        assert_eq!(button.verify(&command(PanicAction::Activate, 2, now), now), Err(PanicError::Replayed { sequence: 2, last: 2 }));
+        assert_eq!(button.verify(&command(PanicAction::Activate, 1, now), now), Err(PanicError::Replayed { sequence: 1, last: 2 }));
+    }
+
+    #[test]
+    fn rejects_stale_and_skewed_timestamps() {
+        let now = Utc::now();
+        let mut button = button();
+        assert_eq!(button.verify(&command(PanicAction::Activate, 1, now - Duration::seconds(31)), now), Err(PanicError::Expired(31)));
+        assert_eq!(button.verify(&command(PanicAction::Activate, 2, now + Duration::seconds(6)), now), Err(PanicError::FromTheFuture(6)));
+        assert_eq!(button.verify(&command(PanicAction::Activate, 3, now + Duration::seconds(4)), now), Ok(()));
+    }
+
+    #[test]
+    fn activation_goes_to_red_and_cancel_restores_the_previous_level() {
+        let now = Utc::now();
+        let mut button = button();
+        let mut drone = DroneState::new("test".to_string());
+        drone.escalate_threat(ThreatLevel::Yellow, "Loitering".to_string());
+
+        let outcome = button.handle(&command(PanicAction::Activate, 1, now), &mut drone, now).unwrap();
+        assert_eq!(outcome, PanicOutcome::Activated { device_id: "wearable".to_string(), previous_level: ThreatLevel::Yellow });
+        assert_eq!(drone.threat_level(), ThreatLevel::Red);
+        assert_eq!(
+            button.handle(&command(PanicAction::Activate, 2, now), &mut drone, now),
+            Ok(PanicOutcome::AlreadyActive { device_id: "wearable".to_string() })
+        );
+
+        // Straight back, without waiting out the quiet period
+        let outcome = button.handle(&command(PanicAction::Cancel, 3, now), &mut drone, now).unwrap();
+        assert_eq!(outcome, PanicOutcome::Cancelled { device_id: "wearable".to_string(), threat_level: ThreatLevel::Yellow });
+        assert_eq!(drone.threat_level(), ThreatLevel::Yellow);
+        assert!(!button.is_active());
+        assert_eq!(button.handle(&command(PanicAction::Cancel, 4, now), &mut drone, now), Err(PanicError::NotActive));
+    }
+}
@@ -0,0 +1,366 @@
+//! Patrol missions: named routes of waypoints flown on a time-of-day
+//! schedule, paused while a threat is being dealt with
+
+use crate::{DroneState, EventType, FlightAction, FlightCommand, Geofence, GeofenceStatus, Position, ThreatLevel, TimeWindow};
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// One stop on a patrol route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Waypoint {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Above home
+    pub altitude_m: f64,
+    /// How long to hold here once reached
+    #[serde(default)]
+    pub loiter_secs: u64,
+    /// Where to point the camera (clockwise from north; absent = keep heading)
+    #[serde(default)]
+    pub camera_heading_deg: Option<f64>,
+}
+
+impl Waypoint {
+    pub fn position(&self) -> Position {
+        Position {
+            latitude: self.latitude,
+            longitude: self.longitude,
+            altitude: self.altitude_m,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+/// A named, looping patrol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatrolRoute {
+    pub name: String,
+    pub waypoints: Vec<Waypoint>,
+    /// Local times the route is flown (empty = only when started by hand)
+    #[serde(default)]
+    pub schedule: Vec<TimeWindow>,
+}
+
+impl PatrolRoute {
+    fn scheduled_at(&self, local_time: NaiveTime) -> bool {
+        self.schedule.iter().any(|window| window.contains(local_time))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PatrolConfig {
+    pub routes: Vec<PatrolRoute>,
+    /// Threat level at which patrols pause, resuming once it drops again
+    pub pause_at: ThreatLevel,
+    /// How close counts as having reached a waypoint
+    pub arrival_radius_m: f64,
+}
+
+impl Default for PatrolConfig {
+    fn default() -> Self {
+        Self {
+            routes: Vec::new(),
+            pause_at: ThreatLevel::Orange,
+            arrival_radius_m: 3.0,
+        }
+    }
+}
+
+impl PatrolConfig {
+    /// Problems with the routes, checked against the geofence they fly in
+    pub fn problems(&self, geofence: &Geofence) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (i, route) in self.routes.iter().enumerate() {
+            if self.routes[..i].iter().any(|earlier| earlier.name == route.name) {
+                problems.push(format!("patrol route '{}' is defined twice", route.name));
+            }
+            if let Err(e) = check_route(route, geofence) {
+                problems.push(e.to_string());
+            }
+        }
+        if self.arrival_radius_m <= 0.0 {
+            problems.push("patrol.arrival_radius_m must be positive".to_string());
+        }
+        problems
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PatrolError {
+    #[error("no patrol route named '{0}'")]
+    UnknownRoute(String),
+    #[error("patrol route '{0}' is already defined")]
+    DuplicateRoute(String),
+    #[error("patrol route '{0}' has no waypoints")]
+    EmptyRoute(String),
+    #[error("waypoint {waypoint} of patrol route '{route}' is outside the geofence")]
+    OutsideGeofence { route: String, waypoint: usize },
+}
+
+/// Where the patrol is along its route
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum PatrolStatus {
+    Idle,
+    EnRoute { route: String, waypoint: usize },
+    Loitering { route: String, waypoint: usize, until: DateTime<Utc> },
+    Paused { route: String, waypoint: usize },
+}
+
+#[derive(Debug, Clone)]
+enum Leg {
+    EnRoute { commanded: bool },
+    Loitering { until: DateTime<Utc> },
+}
+
+#[derive(Debug, Clone)]
+struct ActivePatrol {
+    route: usize,
+    waypoint: usize,
+    leg: Leg,
+    /// Started by hand rather than by schedule, so it ignores the schedule
+    manual: bool,
+    laps: u32,
+    announced: bool,
+}
+
+/// Plans and tracks patrols across protection cycles
+#[derive(Debug, Clone)]
+pub struct PatrolPlanner {
+    config: PatrolConfig,
+    geofence: Geofence,
+    active: Option<ActivePatrol>,
+    paused: bool,
+    /// Manually stopped route, not restarted until its window closes
+    stopped: Option<String>,
+}
+
+impl PatrolPlanner {
+    pub fn new(config: PatrolConfig) -> Self {
+        Self {
+            config,
+            geofence: Geofence::default(),
+            active: None,
+            paused: false,
+            stopped: None,
+        }
+    }
+
+    /// Refuse routes that leave the geofence
+    pub fn with_geofence(mut self, geofence: Geofence) -> Self {
+        self.geofence = geofence;
+        self
+    }
+
+    pub fn routes(&self) -> &[PatrolRoute] {
+        &self.config.routes
+    }
+
+    pub fn add_route(&mut self, route: PatrolRoute) -> Result<(), PatrolError> {
+        if self.config.routes.iter().any(|existing| existing.name == route.name) {
+            return Err(PatrolError::DuplicateRoute(route.name));
+        }
+        check_route(&route, &self.geofence)?;
+        self.config.routes.push(route);
+        Ok(())
+    }
+
+    /// Remove a route, ending its patrol if it is being flown
+    pub fn remove_route(&mut self, name: &str) -> Result<PatrolRoute, PatrolError> {
+        let index = self.route_index(name)?;
+        match &mut self.active {
+            Some(active) if active.route == index => self.active = None,
+            Some(active) if active.route > index => active.route -= 1,
+            _ => {},
+        }
+        Ok(self.config.routes.remove(index))
+    }
+
+    /// Fly `name` now, whatever the schedule, until stopped
+    pub fn start(&mut self, name: &str) -> Result<(), PatrolError> {
+        let route = self.route_index(name)?;
+        self.stopped = None;
+        self.paused = false;
+        self.active = Some(ActivePatrol {
+            route,
+            waypoint: 0,
+            leg: Leg::EnRoute { commanded: false },
+            manual: true,
+            laps: 0,
+            announced: false,
+        });
+        Ok(())
+    }
+
+    /// End the current patrol; a scheduled route stays down until its
+    /// window next opens
+    pub fn stop(&mut self) {
+        self.paused = false;
+        if let Some(active) = self.active.take() {
+            self.stopped = Some(self.config.routes[active.route].name.clone());
+        }
+    }
+
+    /// Something else has taken the drone off its route, e.g. a failsafe;
+    /// the current waypoint is sent again on the next tick
+    pub fn interrupt(&mut self) {
+        if let Some(active) = &mut self.active {
+            active.leg = Leg::EnRoute { commanded: false };
+        }
+    }
+
+    pub fn status(&self) -> PatrolStatus {
+        let Some(active) = &self.active else {
+            return PatrolStatus::Idle;
+        };
+        let route = self.config.routes[active.route].name.clone();
+        let waypoint = active.waypoint;
+        match active.leg {
+            _ if self.paused => PatrolStatus::Paused { route, waypoint },
+            Leg::EnRoute { .. } => PatrolStatus::EnRoute { route, waypoint },
+            Leg::Loitering { until } => PatrolStatus::Loitering { route, waypoint, until },
+        }
+    }
+
+    /// Advance the patrol; returns the flight command to send, if any
+    pub fn tick(&mut self, drone: &mut DroneState, now: DateTime<Utc>, local_time: NaiveTime) -> Option<FlightCommand> {
+        if self.stopped.as_ref().is_some_and(|name| {
+            !self.config.routes.iter().any(|route| &route.name == name && route.scheduled_at(local_time))
+        }) {
+            self.stopped = None;
+        }
+
+        // A scheduled patrol ends with its window
+        if let Some(active) = &self.active {
+            let route = &self.config.routes[active.route];
+            if !active.manual && !route.scheduled_at(local_time) {
+                let description = format!("Patrol '{}' ended after {} laps: outside its schedule", route.name, active.laps);
+                drone.log_event(EventType::MissionComplete, description, vec!["Returning home".to_string()]);
+                self.active = None;
+                self.paused = false;
+                return Some(FlightCommand::Action(FlightAction::ReturnToHome));
+            }
+        }
+
+        if self.active.is_none() {
+            let route = self.config.routes.iter().position(|route| {
+                route.scheduled_at(local_time) && self.stopped.as_ref() != Some(&route.name)
+            })?;
+            self.active = Some(ActivePatrol {
+                route,
+                waypoint: 0,
+                leg: Leg::EnRoute { commanded: false },
+                manual: false,
+                laps: 0,
+                announced: false,
+            });
+        }
+        let active = self.active.as_mut()?;
+        let route = &self.config.routes[active.route];
+
+        if drone.threat_level() >= self.config.pause_at {
+            if self.paused {
+                return None;
+            }
+            self.paused = true;
+            drone.log_event(
+                EventType::PatrolPaused,
+                format!("Patrol '{}' paused at waypoint {}: {} threat", route.name, active.waypoint + 1, drone.threat_level().as_str()),
+                vec!["Holding position".to_string()],
+            );
+            return Some(FlightCommand::Action(FlightAction::Loiter));
+        }
+        if self.paused {
+            self.paused = false;
+            // Pick up the leg from the top, re-sending the waypoint
+            active.leg = Leg::EnRoute { commanded: false };
+            drone.log_event(
+                EventType::PatrolResumed,
+                format!("Patrol '{}' resumed towards waypoint {}", route.name, active.waypoint + 1),
+                Vec::new(),
+            );
+        }
+
+        let waypoint = &route.waypoints[active.waypoint];
+        match active.leg {
+            Leg::EnRoute { commanded: false } => {
+                if !active.announced {
+                    active.announced = true;
+                    drone.log_event(
+                        EventType::PatrolStarted,
+                        format!("Patrol '{}' started ({} waypoints)", route.name, route.waypoints.len()),
+                        Vec::new(),
+                    );
+                }
+                active.leg = Leg::EnRoute { commanded: true };
+                Some(FlightCommand::FlyTo {
+                    target: waypoint.position(),
+                    heading_deg: waypoint.camera_heading_deg,
+                })
+            },
+            Leg::EnRoute { commanded: true } => {
+                let target = waypoint.position();
+                let arrived = drone.position.distance_m(&target) <= self.config.arrival_radius_m
+                    && (drone.position.altitude - target.altitude).abs() <= self.config.arrival_radius_m;
+                if arrived {
+                    drone.log_event(
+                        EventType::WaypointReached,
+                        format!("Patrol '{}' reached waypoint {}/{}", route.name, active.waypoint + 1, route.waypoints.len()),
+                        match waypoint.loiter_secs {
+                            0 => Vec::new(),
+                            secs => vec![format!("Holding for {}s", secs)],
+                        },
+                    );
+                    active.leg = Leg::Loitering {
+                        until: now + Duration::seconds(waypoint.loiter_secs as i64),
+                    };
+                }
+                None
+            },
+            Leg::Loitering { until } if now >= until => {
+                active.waypoint += 1;
+                if active.waypoint == route.waypoints.len() {
+                    active.waypoint = 0;
+                    active.laps += 1;
+                    drone.log_event(
+                        EventType::MissionComplete,
+                        format!("Patrol '{}' lap {} complete", route.name, active.laps),
+                        Vec::new(),
+                    );
+                }
+                let next = &route.waypoints[active.waypoint];
+                active.leg = Leg::EnRoute { commanded: true };
+                Some(FlightCommand::FlyTo {
+                    target: next.position(),
+                    heading_deg: next.camera_heading_deg,
+                })
+            },
+            Leg::Loitering { .. } => None,
+        }
+    }
+
+    fn route_index(&self, name: &str) -> Result<usize, PatrolError> {
+        self.config
+            .routes
+            .iter()
+            .position(|route| route.name == name)
+            .ok_or_else(|| PatrolError::UnknownRoute(name.to_string()))
+    }
+}
+
+fn check_route(route: &PatrolRoute, geofence: &Geofence) -> Result<(), PatrolError> {
+    if route.waypoints.is_empty() {
+        return Err(PatrolError::EmptyRoute(route.name.clone()));
+    }
+    for (i, waypoint) in route.waypoints.iter().enumerate() {
+        if matches!(geofence.check(&waypoint.position()), GeofenceStatus::Breached { .. }) {
+            return Err(PatrolError::OutsideGeofence {
+                route: route.name.clone(),
+                waypoint: i + 1,
+            });
+        }
+    }
+    Ok(())
+}
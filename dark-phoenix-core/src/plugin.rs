@@ -0,0 +1,330 @@
+//! Sandboxed WASM plugins in response coordination (`wasm-plugins` feature)
+
+use crate::{DroneState, EventType, Position, SystemHealth, ThreatLevel, VitalSigns};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::{debug, error, info, trace, warn};
+use wasmtime::{Caller, Config, Engine, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc};
+
+/// Version of the host API described above
+pub const API_VERSION: u32 = 1;
+
+/// Assessments kept for the next cycle when plugins fall behind
+const MAX_PENDING_ASSESSMENTS: usize = 64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginsConfig {
+    /// Every `.wasm` file here is loaded, in file name order
+    pub dir: Option<PathBuf>,
+    /// Further plugins, loaded after those in `dir`
+    pub paths: Vec<PathBuf>,
+    /// Wasmtime fuel (roughly instructions) a plugin may use per cycle
+    pub fuel_per_cycle: u64,
+    pub max_memory_mb: u32,
+    pub max_commands_per_cycle: usize,
+    /// Failed cycles in a row before a plugin is unloaded
+    pub max_failures: u32,
+}
+
+impl Default for PluginsConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            paths: Vec::new(),
+            fuel_per_cycle: 10_000_000,
+            max_memory_mb: 16,
+            max_commands_per_cycle: 8,
+            max_failures: 3,
+        }
+    }
+}
+
+impl PluginsConfig {
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if let Some(dir) = &self.dir {
+            if !dir.is_dir() {
+                problems.push(format!("plugins.dir {} is not a directory", dir.display()));
+            }
+        }
+        for path in &self.paths {
+            if !path.is_file() {
+                problems.push(format!("plugin {} does not exist", path.display()));
+            }
+        }
+        if self.fuel_per_cycle == 0 {
+            problems.push("plugins.fuel_per_cycle must be above zero".to_string());
+        }
+        if self.max_memory_mb == 0 {
+            problems.push("plugins.max_memory_mb must be above zero".to_string());
+        }
+        if self.max_failures == 0 {
+            problems.push("plugins.max_failures must be above zero".to_string());
+        }
+        problems
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PluginError {
+    #[error("cannot read plugin directory {0}: {1}")]
+    Dir(PathBuf, #[source] std::io::Error),
+    #[error("cannot start the WASM engine: {0}")]
+    Engine(String),
+    #[error("cannot load plugin {0}: {1}")]
+    Load(String, String),
+    #[error("plugin {plugin} speaks host API {found}, this build speaks {API_VERSION}")]
+    ApiVersion { plugin: String, found: i32 },
+    #[error("plugin {0} failed: {1}")]
+    Trap(String, String),
+}
+
+/// What a plugin sees each cycle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginView {
+    pub api_version: u32,
+    pub drone: String,
+    pub threat_level: ThreatLevel,
+    pub position: Position,
+    pub target_vitals: Option<VitalSigns>,
+    pub system_health: SystemHealth,
+    /// Threat assessments made since the previous cycle, oldest first
+    pub assessments: Vec<serde_json::Value>,
+}
+
+/// What a plugin can ask of the drone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum PluginCommand {
+    /// Raise the threat level, subject to the transition rules; plugins
+    /// cannot lower it
+    Escalate { level: ThreatLevel, reason: String },
+    /// Record a mission event
+    LogEvent {
+        description: String,
+        #[serde(default)]
+        actions: Vec<String>,
+    },
+}
+
+struct HostState {
+    plugin: String,
+    limits: StoreLimits,
+    commands: Vec<PluginCommand>,
+    max_commands: usize,
+}
+
+struct Plugin {
+    name: String,
+    store: Store<HostState>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    on_cycle: TypedFunc<(i32, i32), ()>,
+    failures: u32,
+}
+
+/// The loaded plugins
+pub struct PluginHost {
+    config: PluginsConfig,
+    engine: Engine,
+    linker: Linker<HostState>,
+    plugins: Vec<Plugin>,
+    assessments: Vec<serde_json::Value>,
+}
+
+impl PluginHost {
+    /// Load every configured plugin; one that fails to load is left out
+    /// with an error logged
+    pub fn open(config: PluginsConfig) -> Result<Self, PluginError> {
+        let mut engine_config = Config::new();
+        engine_config.consume_fuel(true);
+        let engine = Engine::new(&engine_config).map_err(|e| PluginError::Engine(e.to_string()))?;
+        let mut linker = Linker::new(&engine);
+        link_host_api(&mut linker).map_err(|e| PluginError::Engine(e.to_string()))?;
+        let mut host = Self { config, engine, linker, plugins: Vec::new(), assessments: Vec::new() };
+
+        let mut paths = Vec::new();
+        if let Some(dir) = &host.config.dir {
+            let entries = std::fs::read_dir(dir).map_err(|e| PluginError::Dir(dir.clone(), e))?;
+            let mut found: Vec<PathBuf> = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == "wasm"))
+                .collect();
+            found.sort();
+            paths.extend(found);
+        }
+        paths.extend(host.config.paths.iter().cloned());
+        for path in paths {
+            match host.load(&path) {
+                Ok(()) => info!("🧩 Loaded plugin {}", path.display()),
+                Err(e) => error!("🧩 {}", e),
+            }
+        }
+        Ok(host)
+    }
+
+    fn load(&mut self, path: &Path) -> Result<(), PluginError> {
+        let name = path.file_stem().map_or_else(|| path.display().to_string(), |stem| stem.to_string_lossy().into_owned());
+        let load_error = |e: wasmtime::Error| PluginError::Load(name.clone(), e.to_string());
+        let module = Module::from_file(&self.engine, path).map_err(load_error)?;
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.config.max_memory_mb as usize * 1024 * 1024)
+            .instances(1)
+            .build();
+        let state = HostState {
+            plugin: name.clone(),
+            limits,
+            commands: Vec::new(),
+            max_commands: self.config.max_commands_per_cycle,
+        };
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(self.config.fuel_per_cycle).map_err(load_error)?;
+        let instance = self.linker.instantiate(&mut store, &module).map_err(load_error)?;
+
+        let version: TypedFunc<(), i32> = instance.get_typed_func(&mut store, "phoenix_api_version").map_err(load_error)?;
+        let found = version.call(&mut store, ()).map_err(load_error)?;
+        if found != API_VERSION as i32 {
+            return Err(PluginError::ApiVersion { plugin: name.clone(), found });
+        }
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| PluginError::Load(name.clone(), "no exported memory".to_string()))?;
+        let alloc = instance.get_typed_func(&mut store, "phoenix_alloc").map_err(load_error)?;
+        let on_cycle = instance.get_typed_func(&mut store, "phoenix_on_cycle").map_err(load_error)?;
+        self.plugins.push(Plugin { name, store, memory, alloc, on_cycle, failures: 0 });
+        Ok(())
+    }
+
+    /// Names of the plugins still loaded
+    pub fn plugins(&self) -> Vec<&str> {
+        self.plugins.iter().map(|plugin| plugin.name.as_str()).collect()
+    }
+
+    /// Show `assessment` to the plugins on their next cycle
+    pub fn record_assessment<T: Serialize>(&mut self, assessment: &T) {
+        let Ok(value) = serde_json::to_value(assessment) else { return };
+        if self.assessments.len() == MAX_PENDING_ASSESSMENTS {
+            self.assessments.remove(0);
+        }
+        self.assessments.push(value);
+    }
+
+    /// Run every plugin against `state` and apply what they emit
+    pub fn coordinate(&mut self, state: &mut DroneState) {
+        if self.plugins.is_empty() {
+            self.assessments.clear();
+            return;
+        }
+        let view = PluginView {
+            api_version: API_VERSION,
+            drone: state.name.clone(),
+            threat_level: state.threat_level(),
+            position: state.position.clone(),
+            target_vitals: state.target_vitals.clone(),
+            system_health: state.system_health.clone(),
+            assessments: std::mem::take(&mut self.assessments),
+        };
+        // A struct of plain data and JSON values always serializes
+        let input = serde_json::to_vec(&view).unwrap_or_default();
+
+        let max_failures = self.config.max_failures;
+        let mut unloaded = Vec::new();
+        for plugin in &mut self.plugins {
+            match plugin.run(&input, self.config.fuel_per_cycle) {
+                Ok(commands) => {
+                    plugin.failures = 0;
+                    for command in commands {
+                        apply(&plugin.name, command, state);
+                    }
+                },
+                Err(e) => {
+                    plugin.failures += 1;
+                    warn!("🧩 {} ({} of {} in a row)", e, plugin.failures, max_failures);
+                    if plugin.failures >= max_failures {
+                        unloaded.push(plugin.name.clone());
+                    }
+                },
+            }
+        }
+        for name in unloaded {
+            error!("🧩 Plugin {} unloaded after {} failed cycles", name, max_failures);
+            state.log_event(
+                EventType::SystemMalfunction,
+                format!("Plugin {} unloaded after {} failed cycles", name, max_failures),
+                Vec::new(),
+            );
+            self.plugins.retain(|plugin| plugin.name != name);
+        }
+    }
+}
+
+impl Plugin {
+    fn run(&mut self, input: &[u8], fuel: u64) -> Result<Vec<PluginCommand>, PluginError> {
+        // The root cause says why, e.g. that the fuel ran out, without the backtrace
+        let trap = |e: wasmtime::Error| PluginError::Trap(self.name.clone(), e.root_cause().to_string());
+        self.store.set_fuel(fuel).map_err(trap)?;
+        self.store.data_mut().commands.clear();
+        let len = input.len() as i32;
+        let ptr = self.alloc.call(&mut self.store, len).map_err(trap)?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, input)
+            .map_err(|e| PluginError::Trap(self.name.clone(), e.to_string()))?;
+        self.on_cycle.call(&mut self.store, (ptr, len)).map_err(trap)?;
+        Ok(std::mem::take(&mut self.store.data_mut().commands))
+    }
+}
+
+fn apply(plugin: &str, command: PluginCommand, state: &mut DroneState) {
+    debug!("🧩 Plugin {} emitted {:?}", plugin, command);
+    match command {
+        PluginCommand::Escalate { level, reason } => {
+            state.escalate_threat(level, format!("{} (plugin {})", reason, plugin));
+        },
+        PluginCommand::LogEvent { description, actions } => {
+            state.log_event(EventType::PluginAction, format!("{}: {}", plugin, description), actions);
+        },
+    }
+}
+
+/// The `len` bytes at `ptr` in the calling plugin's memory
+fn guest_bytes(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> Option<Vec<u8>> {
+    let memory = caller.get_export("memory")?.into_memory()?;
+    let start = ptr as u32 as usize;
+    let end = start.checked_add(len as u32 as usize)?;
+    memory.data(&caller).get(start..end).map(<[u8]>::to_vec)
+}
+
+fn link_host_api(linker: &mut Linker<HostState>) -> wasmtime::Result<()> {
+    linker.func_wrap("phoenix", "emit", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> i32 {
+        let Some(bytes) = guest_bytes(&mut caller, ptr, len) else { return -1 };
+        let command = match serde_json::from_slice(&bytes) {
+            Ok(command) => command,
+            Err(e) => {
+                warn!("🧩 Plugin {} emitted a command that does not parse: {}", caller.data().plugin, e);
+                return -1;
+            },
+        };
+        let state = caller.data_mut();
+        if state.commands.len() >= state.max_commands {
+            return -2;
+        }
+        state.commands.push(command);
+        0
+    })?;
+    linker.func_wrap("phoenix", "log", |mut caller: Caller<'_, HostState>, level: i32, ptr: i32, len: i32| {
+        let Some(bytes) = guest_bytes(&mut caller, ptr, len) else { return };
+        let message = String::from_utf8_lossy(&bytes);
+        let plugin = &caller.data().plugin;
+        match level {
+            0 => error!("🧩 [{}] {}", plugin, message),
+            1 => warn!("🧩 [{}] {}", plugin, message),
+            2 => info!("🧩 [{}] {}", plugin, message),
+            3 => debug!("🧩 [{}] {}", plugin, message),
+            _ => trace!("🧩 [{}] {}", plugin, message),
+        }
+    })?;
+    Ok(())
+}
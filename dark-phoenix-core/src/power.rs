@@ -0,0 +1,270 @@
+//! Battery and power budget: what every load draws, how long the charge
+//! lasts at that rate, and which loads go dark as it runs down
+
+use crate::{DroneState, EventType, LoadDraw, PowerTelemetry};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadPriority {
+    /// Never shed: flight, comms
+    Critical,
+    #[default]
+    Essential,
+    /// First to go: strobes, extra sensors
+    Optional,
+}
+
+/// Something on the drone that draws power
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerLoad {
+    pub name: String,
+    /// Estimated draw while powered (watts)
+    pub draw_w: f64,
+    #[serde(default)]
+    pub priority: LoadPriority,
+    /// Charge below which the load is switched off (ignored for critical loads)
+    #[serde(default)]
+    pub shed_below_percent: Option<u8>,
+}
+
+impl PowerLoad {
+    fn new(name: &str, draw_w: f64, priority: LoadPriority, shed_below_percent: Option<u8>) -> Self {
+        Self {
+            name: name.to_string(),
+            draw_w,
+            priority,
+            shed_below_percent,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerConfig {
+    /// Usable battery energy when full (watt-hours)
+    pub capacity_wh: f64,
+    /// Motors and flight controller in a hover (watts)
+    pub hover_draw_w: f64,
+    /// Charge kept back for landing, not counted in the time remaining
+    pub reserve_percent: u8,
+    /// How far above its threshold the charge must climb before a shed load returns
+    pub restore_margin_percent: u8,
+    pub loads: Vec<PowerLoad>,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            capacity_wh: 222.0, // 6S 10 Ah
+            hover_draw_w: 380.0,
+            reserve_percent: 10,
+            restore_margin_percent: 5,
+            loads: vec![
+                PowerLoad::new("comms", 4.0, LoadPriority::Critical, None),
+                PowerLoad::new("cameras", 6.0, LoadPriority::Essential, None),
+                PowerLoad::new("siren", 20.0, LoadPriority::Essential, Some(15)),
+                PowerLoad::new("strobe", 25.0, LoadPriority::Optional, Some(40)),
+                PowerLoad::new("thermal_camera", 3.5, LoadPriority::Optional, Some(30)),
+                PowerLoad::new("lidar", 8.0, LoadPriority::Optional, Some(30)),
+            ],
+        }
+    }
+}
+
+impl PowerConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.capacity_wh <= 0.0 {
+            problems.push("power.capacity_wh must be positive".to_string());
+        }
+        if self.hover_draw_w < 0.0 {
+            problems.push("power.hover_draw_w must not be negative".to_string());
+        }
+        if self.reserve_percent >= 100 {
+            problems.push("power.reserve_percent must be below 100".to_string());
+        }
+        for (i, load) in self.loads.iter().enumerate() {
+            if self.loads[..i].iter().any(|earlier| earlier.name == load.name) {
+                problems.push(format!("power load '{}' is defined twice", load.name));
+            }
+            if load.draw_w < 0.0 {
+                problems.push(format!("power load '{}' must not draw negative power", load.name));
+            }
+            if load.shed_below_percent.is_some_and(|percent| percent > 100) {
+                problems.push(format!("power load '{}' shed_below_percent must be at most 100", load.name));
+            }
+        }
+        problems
+    }
+}
+
+/// A load switched off or back on by `PowerManager::update`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LoadChange {
+    pub load: String,
+    pub shed: bool,
+}
+
+#[derive(Debug, Clone)]
+struct LoadState {
+    config: PowerLoad,
+    /// Latest measurement, in place of the estimate
+    measured_w: Option<f64>,
+    shed: bool,
+}
+
+impl LoadState {
+    fn draw_w(&self) -> f64 {
+        if self.shed {
+            0.0
+        } else {
+            self.measured_w.unwrap_or(self.config.draw_w)
+        }
+    }
+}
+
+/// Keeps the power budget across protection cycles
+#[derive(Debug, Clone)]
+pub struct PowerManager {
+    config: PowerConfig,
+    loads: Vec<LoadState>,
+    /// Rated capacity scaled by the pack's state of health
+    capacity_wh: f64,
+    remaining_wh: f64,
+    /// Charge last written to the drone, to spot the flight controller's own readings
+    reported_level: Option<u8>,
+    last_update: Option<DateTime<Utc>>,
+}
+
+impl PowerManager {
+    pub fn new(config: PowerConfig) -> Self {
+        Self {
+            loads: config
+                .loads
+                .iter()
+                .map(|load| LoadState {
+                    config: load.clone(),
+                    measured_w: None,
+                    shed: false,
+                })
+                .collect(),
+            capacity_wh: config.capacity_wh,
+            remaining_wh: config.capacity_wh,
+            config,
+            reported_level: None,
+            last_update: None,
+        }
+    }
+
+    /// A module's measured draw; an unknown load is added as essential
+    pub fn report_draw(&mut self, load: &str, watts: f64) {
+        match self.loads.iter_mut().find(|state| state.config.name == load) {
+            Some(state) => state.measured_w = Some(watts.max(0.0)),
+            None => self.loads.push(LoadState {
+                config: PowerLoad::new(load, watts.max(0.0), LoadPriority::Essential, None),
+                measured_w: None,
+                shed: false,
+            }),
+        }
+    }
+
+    /// Whether `load` has been switched off to save power
+    pub fn is_shed(&self, load: &str) -> bool {
+        self.loads.iter().any(|state| state.config.name == load && state.shed)
+    }
+
+    /// Total draw right now (watts)
+    pub fn draw_w(&self) -> f64 {
+        self.config.hover_draw_w + self.loads.iter().map(LoadState::draw_w).sum::<f64>()
+    }
+
+    /// Seconds of flight left above the reserve at the current draw
+    pub fn time_remaining_secs(&self) -> u32 {
+        let reserve_wh = self.capacity_wh * f64::from(self.config.reserve_percent) / 100.0;
+        let draw_w = self.draw_w();
+        if draw_w <= 0.0 {
+            return u32::MAX;
+        }
+        ((self.remaining_wh - reserve_wh).max(0.0) / draw_w * 3600.0) as u32
+    }
+
+    pub fn telemetry(&self, battery_level: u8) -> PowerTelemetry {
+        PowerTelemetry {
+            battery_level,
+            remaining_wh: self.remaining_wh,
+            draw_w: self.draw_w(),
+            flight_time_remaining: self.time_remaining_secs(),
+            loads: self
+                .loads
+                .iter()
+                .map(|state| LoadDraw {
+                    name: state.config.name.clone(),
+                    draw_w: state.draw_w(),
+                    shed: state.shed,
+                })
+                .collect(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Account for the energy used since the last update, shed or restore
+    /// loads against the new charge, and report the budget into the drone;
+    /// returns the loads whose modules must switch off or back on
+    pub fn update(&mut self, drone: &mut DroneState, now: DateTime<Utc>) -> Vec<LoadChange> {
+        let health = drone.system_health.battery_state_of_health.map_or(100, |percent| percent.clamp(1, 100));
+        let capacity = self.config.capacity_wh * f64::from(health) / 100.0;
+        if capacity != self.capacity_wh {
+            // Same charge, smaller tank
+            self.remaining_wh *= capacity / self.capacity_wh;
+            self.capacity_wh = capacity;
+        }
+        let level = drone.system_health.battery_level;
+        if self.reported_level != Some(level) {
+            // Someone else, i.e. the flight controller, measured the battery
+            self.remaining_wh = capacity * f64::from(level.min(100)) / 100.0;
+        } else if let Some(last) = self.last_update {
+            let hours = (now - last).num_milliseconds().max(0) as f64 / 3_600_000.0;
+            self.remaining_wh = (self.remaining_wh - self.draw_w() * hours).max(0.0);
+        }
+        self.last_update = Some(now);
+        let level = (self.remaining_wh / capacity * 100.0).round() as u8;
+        self.reported_level = Some(level);
+
+        let mut changes = Vec::new();
+        for state in &mut self.loads {
+            let Some(threshold) = state.config.shed_below_percent.filter(|_| state.config.priority != LoadPriority::Critical) else {
+                continue;
+            };
+            let shed = if state.shed {
+                level < threshold.saturating_add(self.config.restore_margin_percent)
+            } else {
+                level < threshold
+            };
+            if shed != state.shed {
+                state.shed = shed;
+                changes.push(LoadChange {
+                    load: state.config.name.clone(),
+                    shed,
+                });
+            }
+        }
+        for change in &changes {
+            if change.shed {
+                tracing::warn!("🔋 Shedding {} at {}% battery", change.load, level);
+                drone.log_event(
+                    EventType::LoadShed,
+                    format!("Switched off {} to save power at {}% battery", change.load, level),
+                    vec![format!("{:.0} W total draw, {} min remaining", self.draw_w(), self.time_remaining_secs() / 60)],
+                );
+            } else {
+                tracing::info!("🔋 Restoring {} at {}% battery", change.load, level);
+            }
+        }
+
+        drone.report_power(self.telemetry(level));
+        changes
+    }
+}
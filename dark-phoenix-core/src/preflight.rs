@@ -0,0 +1,338 @@
+//! Preflight checklist and arming
+
+use crate::{BatteryGrade, BatteryHealth, DroneState, EventType};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreflightConfig {
+    /// Charge below which the drone will not take off
+    pub min_battery_percent: u8,
+    /// Checks whose failure only warns, by name
+    pub waive: Vec<String>,
+    /// How long a single check may take before it counts as failed
+    pub check_timeout_secs: u64,
+}
+
+impl Default for PreflightConfig {
+    fn default() -> Self {
+        Self {
+            min_battery_percent: 80,
+            waive: Vec::new(),
+            check_timeout_secs: 30,
+        }
+    }
+}
+
+impl PreflightConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.min_battery_percent > 100 {
+            problems.push("preflight.min_battery_percent must be at most 100".to_string());
+        }
+        if self.check_timeout_secs == 0 {
+            problems.push("preflight.check_timeout_secs must be positive".to_string());
+        }
+        problems
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    /// Fit to fly, but worth a look
+    Warn,
+    Fail,
+}
+
+/// What one check found
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckOutcome {
+    pub status: CheckStatus,
+    pub detail: String,
+    /// What to do about a warning or failure
+    pub remediation: Option<String>,
+}
+
+impl CheckOutcome {
+    pub fn pass(detail: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            remediation: None,
+        }
+    }
+
+    pub fn warn(detail: impl Into<String>, remediation: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            remediation: Some(remediation.into()),
+        }
+    }
+
+    pub fn fail(detail: impl Into<String>, remediation: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            remediation: Some(remediation.into()),
+        }
+    }
+}
+
+/// One item on the checklist
+#[async_trait]
+pub trait PreflightCheck: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn run(&self, drone: &DroneState) -> CheckOutcome;
+}
+
+/// A check's outcome in the report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub name: String,
+    #[serde(flatten)]
+    pub outcome: CheckOutcome,
+    /// Failed, but listed in `PreflightConfig::waive`
+    pub waived: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreflightReport {
+    pub results: Vec<CheckResult>,
+    pub completed: DateTime<Utc>,
+}
+
+impl PreflightReport {
+    pub fn passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &CheckResult> {
+        self.results.iter().filter(|result| result.outcome.status == CheckStatus::Fail)
+    }
+
+    fn summary(&self) -> String {
+        self.failures().map(|result| format!("{}: {}", result.name, result.outcome.detail)).collect::<Vec<_>>().join("; ")
+    }
+}
+
+impl fmt::Display for PreflightReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in &self.results {
+            let mark = match result.outcome.status {
+                CheckStatus::Pass => "✅",
+                CheckStatus::Warn => "⚠️",
+                CheckStatus::Fail => "❌",
+            };
+            write!(f, "{} {}: {}", mark, result.name, result.outcome.detail)?;
+            if result.waived {
+                write!(f, " (waived)")?;
+            }
+            if let Some(remediation) = &result.outcome.remediation {
+                write!(f, "\n     → {}", remediation)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ArmError {
+    #[error("preflight failed, not arming ({}); fix it or force-arm", .0.summary())]
+    PreflightFailed(PreflightReport),
+}
+
+/// The ordered checks the drone must pass before it arms
+pub struct PreflightChecklist {
+    config: PreflightConfig,
+    checks: Vec<Box<dyn PreflightCheck>>,
+}
+
+impl PreflightChecklist {
+    pub fn new(config: PreflightConfig) -> Self {
+        Self { config, checks: Vec::new() }
+    }
+
+    pub fn with_check(mut self, check: Box<dyn PreflightCheck>) -> Self {
+        self.add_check(check);
+        self
+    }
+
+    pub fn add_check(&mut self, check: Box<dyn PreflightCheck>) {
+        self.checks.push(check);
+    }
+
+    /// The core's own checks: sensor liveness, GPS lock, battery and geofence
+    pub fn with_standard_checks(self, battery: Arc<Mutex<BatteryHealth>>) -> Self {
+        let min_percent = self.config.min_battery_percent;
+        self.with_check(Box::new(SensorLiveness))
+            .with_check(Box::new(GpsLock))
+            .with_check(Box::new(BatteryCheck { battery, min_percent }))
+            .with_check(Box::new(GeofenceLoaded))
+    }
+
+    /// Run every check in order
+    pub async fn run(&self, drone: &Arc<RwLock<DroneState>>) -> PreflightReport {
+        // Checks may take seconds; the drone must not be held up meanwhile
+        let snapshot = drone.read().await.clone();
+        let timeout = std::time::Duration::from_secs(self.config.check_timeout_secs);
+        let mut results = Vec::with_capacity(self.checks.len());
+        for check in &self.checks {
+            let mut outcome = match crate::runtime::timeout(timeout, check.run(&snapshot)).await {
+                Ok(outcome) => outcome,
+                Err(_) => CheckOutcome::fail(
+                    format!("did not finish within {}s", self.config.check_timeout_secs),
+                    "check the hardware behind it is connected and responding",
+                ),
+            };
+            let waived = outcome.status == CheckStatus::Fail && self.config.waive.iter().any(|name| name == check.name());
+            if waived {
+                outcome.status = CheckStatus::Warn;
+            }
+            results.push(CheckResult {
+                name: check.name().to_string(),
+                outcome,
+                waived,
+            });
+        }
+        PreflightReport {
+            results,
+            completed: Utc::now(),
+        }
+    }
+
+    /// Run the checklist and arm if it passes, or anyway when `force` gives
+    /// the operator's reason; either way the result goes into the mission log
+    pub async fn arm(&self, drone: &Arc<RwLock<DroneState>>, force: Option<&str>) -> Result<PreflightReport, ArmError> {
+        let report = self.run(drone).await;
+        let details = report
+            .results
+            .iter()
+            .filter(|result| result.outcome.status != CheckStatus::Pass)
+            .map(|result| format!("{}: {}", result.name, result.outcome.detail))
+            .collect();
+        let mut drone = drone.write().await;
+        if report.passed() {
+            tracing::info!("🛫 Preflight passed, arming");
+            drone.log_event(
+                EventType::PreflightPassed,
+                format!("Preflight passed ({} checks)", report.results.len()),
+                details,
+            );
+            return Ok(report);
+        }
+        match force {
+            Some(reason) => {
+                tracing::warn!("🛫 Preflight failed, force-arming: {}", reason);
+                drone.log_event(EventType::ForceArmed, format!("Force-armed despite failed preflight: {}", reason), details);
+                Ok(report)
+            },
+            None => {
+                tracing::error!("🛫 Preflight failed, not arming");
+                drone.log_event(EventType::PreflightFailed, format!("Preflight failed: {}", report.summary()), details);
+                Err(ArmError::PreflightFailed(report))
+            },
+        }
+    }
+}
+
+/// Every key sensor reporting
+struct SensorLiveness;
+
+#[async_trait]
+impl PreflightCheck for SensorLiveness {
+    fn name(&self) -> &str {
+        "sensors"
+    }
+
+    async fn run(&self, drone: &DroneState) -> CheckOutcome {
+        let degraded = &drone.system_health.degraded_sensors;
+        if degraded.is_empty() {
+            CheckOutcome::pass("all sensors reporting")
+        } else {
+            CheckOutcome::fail(
+                format!("not reporting: {}", degraded.join(", ")),
+                "check the sensors' cabling and power, then restart their modules",
+            )
+        }
+    }
+}
+
+struct GpsLock;
+
+#[async_trait]
+impl PreflightCheck for GpsLock {
+    fn name(&self) -> &str {
+        "gps"
+    }
+
+    async fn run(&self, drone: &DroneState) -> CheckOutcome {
+        if drone.system_health.gps_lock {
+            CheckOutcome::pass("3D fix")
+        } else {
+            CheckOutcome::fail("no GPS fix", "move to open sky and wait for the flight controller to report a 3D fix")
+        }
+    }
+}
+
+/// Enough charge, and a pack fit to fly
+struct BatteryCheck {
+    battery: Arc<Mutex<BatteryHealth>>,
+    min_percent: u8,
+}
+
+#[async_trait]
+impl PreflightCheck for BatteryCheck {
+    fn name(&self) -> &str {
+        "battery"
+    }
+
+    async fn run(&self, drone: &DroneState) -> CheckOutcome {
+        let level = drone.system_health.battery_level;
+        let health = self.battery.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).report();
+        let detail = format!(
+            "{}% charge, pack {} ({}% health, {:.0} cycles)",
+            level, health.grade, health.state_of_health, health.cycles
+        );
+        if level < self.min_percent {
+            return CheckOutcome::fail(detail, format!("charge the battery to at least {}%", self.min_percent));
+        }
+        match health.grade {
+            BatteryGrade::Retire if health.overheated => {
+                CheckOutcome::fail(detail, "the pack has overheated; swap it and have it inspected before flying it again")
+            },
+            BatteryGrade::Retire => CheckOutcome::fail(detail, "swap the pack and retire it"),
+            BatteryGrade::Poor => CheckOutcome::warn(detail, "flight time is reduced; plan shorter missions and replace the pack soon"),
+            BatteryGrade::Fair | BatteryGrade::Good => CheckOutcome::pass(detail),
+        }
+    }
+}
+
+struct GeofenceLoaded;
+
+#[async_trait]
+impl PreflightCheck for GeofenceLoaded {
+    fn name(&self) -> &str {
+        "geofence"
+    }
+
+    async fn run(&self, drone: &DroneState) -> CheckOutcome {
+        let geofence = drone.geofence();
+        if geofence.is_empty() {
+            CheckOutcome::warn("no geofence, the drone may fly anywhere", "add a [geofence] section with the site boundary")
+        } else {
+            CheckOutcome::pass(format!("{} zones loaded", geofence.zones.len()))
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Protectee tracking: pair with the person's BLE wearable or phone, range
+//! it, and keep the drone within an escort envelope around them
+
+use crate::{DroneState, EventType, FlightAction, FlightCommand, Position, VitalSigns};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One advertisement heard from a beacon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeaconReading {
+    /// MAC address or beacon UUID
+    pub beacon_id: String,
+    pub rssi_dbm: i16,
+    /// Bearing from the drone, clockwise from north, from direction finding
+    /// combined with the drone's heading
+    pub bearing_deg: Option<f64>,
+    /// Fix the beacon itself advertises (phones)
+    pub position: Option<Position>,
+    /// From a wearable's heart rate service
+    pub heart_rate: Option<u16>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Where the drone keeps itself relative to the protectee
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscortEnvelope {
+    /// Closer than this crowds the protectee
+    pub min_distance_m: f64,
+    /// Further than this the drone cannot cover them
+    pub max_distance_m: f64,
+    /// Above home
+    pub altitude_m: f64,
+    /// Least time between repositioning commands while outside the envelope
+    pub reposition_interval_secs: u64,
+}
+
+impl Default for EscortEnvelope {
+    fn default() -> Self {
+        Self {
+            min_distance_m: 3.0,
+            max_distance_m: 15.0,
+            altitude_m: 8.0,
+            reposition_interval_secs: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProtecteeConfig {
+    /// Beacon to follow (absent = pair with the first one held close)
+    pub beacon_id: Option<String>,
+    /// How strong a beacon's signal must be to pair, i.e. held next to the drone
+    pub pairing_rssi_dbm: i16,
+    /// Beacon signal strength at one metre
+    pub tx_power_dbm: i16,
+    /// 2.0 in open air, up to 4.0 indoors or through bodies
+    pub path_loss_exponent: f64,
+    /// Weight of each new reading in the smoothed signal strength (0-1)
+    pub smoothing: f64,
+    /// Silence before the protectee counts as lost
+    pub lost_after_secs: u64,
+    pub escort: EscortEnvelope,
+}
+
+impl Default for ProtecteeConfig {
+    fn default() -> Self {
+        Self {
+            beacon_id: None,
+            pairing_rssi_dbm: -45,
+            tx_power_dbm: -59,
+            path_loss_exponent: 2.0,
+            smoothing: 0.3,
+            lost_after_secs: 10,
+            escort: EscortEnvelope::default(),
+        }
+    }
+}
+
+impl ProtecteeConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let escort = &self.escort;
+        if escort.min_distance_m < 0.0 || escort.min_distance_m >= escort.max_distance_m {
+            problems.push(format!(
+                "protectee.escort distances must satisfy 0 <= min ({}) < max ({})",
+                escort.min_distance_m, escort.max_distance_m
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.smoothing) || self.smoothing == 0.0 {
+            problems.push("protectee.smoothing must be in (0, 1]".to_string());
+        }
+        if self.path_loss_exponent <= 0.0 {
+            problems.push("protectee.path_loss_exponent must be positive".to_string());
+        }
+        problems
+    }
+}
+
+/// Best estimate of where the protectee is
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtecteeFix {
+    pub beacon_id: String,
+    pub range_m: f64,
+    pub bearing_deg: Option<f64>,
+    /// Known when there is a bearing or the beacon's own fix
+    pub position: Option<Position>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Pairs with and tracks the protectee's beacon across protection cycles
+#[derive(Debug, Clone)]
+pub struct Protectee {
+    config: ProtecteeConfig,
+    paired: Option<String>,
+    announced: bool,
+    smoothed_rssi: Option<f64>,
+    last: Option<BeaconReading>,
+    lost: bool,
+    last_reposition: Option<DateTime<Utc>>,
+}
+
+impl Protectee {
+    pub fn new(config: ProtecteeConfig) -> Self {
+        Self {
+            paired: config.beacon_id.clone(),
+            config,
+            announced: false,
+            smoothed_rssi: None,
+            last: None,
+            lost: false,
+            last_reposition: None,
+        }
+    }
+
+    pub fn paired(&self) -> Option<&str> {
+        self.paired.as_deref()
+    }
+
+    /// Forget the paired beacon, ready to pair another
+    pub fn unpair(&mut self) {
+        *self = Self::new(ProtecteeConfig {
+            beacon_id: None,
+            ..self.config.clone()
+        });
+    }
+
+    /// Take in an advertisement; pairs with the beacon when none is paired
+    /// and it is held close enough. Returns whether it came from the protectee
+    pub fn observe(&mut self, reading: BeaconReading) -> bool {
+        match &self.paired {
+            Some(id) if *id == reading.beacon_id => {},
+            Some(_) => return false,
+            None if reading.rssi_dbm >= self.config.pairing_rssi_dbm => {
+                self.paired = Some(reading.beacon_id.clone());
+            },
+            None => return false,
+        }
+        let rssi = f64::from(reading.rssi_dbm);
+        let alpha = self.config.smoothing;
+        self.smoothed_rssi = Some(self.smoothed_rssi.map_or(rssi, |smoothed| smoothed + alpha * (rssi - smoothed)));
+        self.last = Some(reading);
+        true
+    }
+
+    /// Current estimate, relative to the drone at `drone`
+    pub fn fix(&self, drone: &Position) -> Option<ProtecteeFix> {
+        let reading = self.last.as_ref()?;
+        let rssi_range_m = self.range_m(self.smoothed_rssi?);
+        // The beacon's own fix beats ranging when it has one
+        let (range_m, position) = match (&reading.position, reading.bearing_deg) {
+            (Some(position), _) => (drone.distance_m(position), Some(position.clone())),
+            (None, Some(bearing)) => (rssi_range_m, Some(drone.destination(bearing, rssi_range_m))),
+            (None, None) => (rssi_range_m, None),
+        };
+        Some(ProtecteeFix {
+            beacon_id: reading.beacon_id.clone(),
+            range_m,
+            bearing_deg: reading.bearing_deg,
+            position,
+            timestamp: reading.timestamp,
+        })
+    }
+
+    /// Report the protectee into the drone and keep the escort envelope;
+    /// returns the flight command when the drone needs to move
+    pub fn escort(&mut self, drone: &mut DroneState, now: DateTime<Utc>) -> Option<FlightCommand> {
+        let paired = self.paired.clone()?;
+        if !self.announced {
+            self.announced = true;
+            drone.log_event(EventType::ProtecteePaired, format!("Paired with protectee beacon {}", paired), Vec::new());
+        }
+        let fresh = self
+            .last
+            .as_ref()
+            .is_some_and(|reading| now - reading.timestamp < Duration::seconds(self.config.lost_after_secs as i64));
+        if !fresh {
+            if self.last.is_some() && !self.lost {
+                self.lost = true;
+                drone.log_event(
+                    EventType::ProtecteeLost,
+                    format!("Protectee beacon {} silent for {}s", paired, self.config.lost_after_secs),
+                    vec!["Holding position".to_string()],
+                );
+                return Some(FlightCommand::Action(FlightAction::Loiter));
+            }
+            return None;
+        }
+        if self.lost {
+            self.lost = false;
+            tracing::info!("🛡️ Protectee beacon {} back in range", paired);
+        }
+
+        let fix = self.fix(&drone.position)?;
+        if let Some(reading) = self.last.as_ref().filter(|reading| reading.heart_rate.is_some()) {
+            let vitals = drone.target_vitals.get_or_insert(VitalSigns {
+                heart_rate: None,
+                blood_oxygen: None,
+                temperature: None,
+                stress_level: None,
+                timestamp: reading.timestamp,
+            });
+            vitals.heart_rate = reading.heart_rate;
+            vitals.timestamp = reading.timestamp;
+        }
+        drone.protectee = Some(fix.clone());
+
+        let escort = &self.config.escort;
+        if (escort.min_distance_m..=escort.max_distance_m).contains(&fix.range_m) {
+            return None;
+        }
+        let protectee = fix.position?;
+        let due = self
+            .last_reposition
+            .is_none_or(|last| now - last >= Duration::seconds(escort.reposition_interval_secs as i64));
+        if !due {
+            return None;
+        }
+        self.last_reposition = Some(now);
+
+        // Mid-envelope, on the protectee's side facing the drone, looking at them
+        let back_bearing = protectee.bearing_deg(&drone.position);
+        let mut target = protectee.destination(back_bearing, (escort.min_distance_m + escort.max_distance_m) / 2.0);
+        target.altitude = escort.altitude_m;
+        tracing::info!("🛡️ Protectee {:.1} m away, repositioning", fix.range_m);
+        Some(FlightCommand::FlyTo {
+            target,
+            heading_deg: Some((back_bearing + 180.0) % 360.0),
+        })
+    }
+
+    fn range_m(&self, rssi_dbm: f64) -> f64 {
+        10f64.powf((f64::from(self.config.tx_power_dbm) - rssi_dbm) / (10.0 * self.config.path_loss_exponent))
+    }
+}
@@ -0,0 +1,254 @@
+//! LiDAR and ultrasonic ranging: a proximity map of what is around the drone
+
+use crate::DroneState;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, RwLock};
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RangeKind {
+    Lidar,
+    Ultrasonic,
+}
+
+/// One distance measured by a range sensor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RangeReading {
+    /// Clockwise from the drone's heading
+    pub bearing_deg: f32,
+    /// Metres; `f32::INFINITY` when nothing is in range on this bearing
+    pub distance_m: f32,
+    pub kind: RangeKind,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RangingError {
+    #[error("range sensor failed: {0}")]
+    Sensor(String),
+    #[error("obstacle {distance_m:.1} m away at {bearing_deg:.0}°, inside the {min_safe_m:.1} m safe distance")]
+    TooClose { distance_m: f32, bearing_deg: f32, min_safe_m: f32 },
+    #[error("no recent range readings")]
+    Blind,
+}
+
+/// A LiDAR or ultrasonic ranger
+#[async_trait]
+pub trait RangeSensor: Send + Sync {
+    /// One scan; a single-beam sensor returns one reading
+    async fn read_ranges(&self) -> Result<Vec<RangeReading>, RangingError>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProximityConfig {
+    /// Nothing may be closer than this when the nozzle discharges or the shield deploys (metres)
+    pub min_safe_distance_m: f32,
+    /// Width of each sector of the map (degrees)
+    pub sector_deg: f32,
+    /// Returns beyond this count as clear (metres)
+    pub max_range_m: f32,
+    /// Readings older than this are forgotten
+    pub max_age_ms: u64,
+    /// Refuse to discharge or deploy when the map holds no recent readings
+    pub block_when_blind: bool,
+    pub poll_ms: u64,
+}
+
+impl Default for ProximityConfig {
+    fn default() -> Self {
+        Self {
+            min_safe_distance_m: 1.5,
+            sector_deg: 15.0,
+            max_range_m: 12.0,
+            max_age_ms: 1000,
+            block_when_blind: false,
+            poll_ms: 100,
+        }
+    }
+}
+
+impl ProximityConfig {
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !self.min_safe_distance_m.is_finite() || self.min_safe_distance_m < 0.0 {
+            problems.push(format!("proximity.min_safe_distance_m {} must not be negative", self.min_safe_distance_m));
+        }
+        if !(1.0..=360.0).contains(&self.sector_deg) {
+            problems.push(format!("proximity.sector_deg {} must be between 1 and 360", self.sector_deg));
+        }
+        if !self.max_range_m.is_finite() || self.max_range_m <= self.min_safe_distance_m {
+            problems.push(format!("proximity.max_range_m {} must be beyond the safe distance", self.max_range_m));
+        }
+        if self.max_age_ms == 0 {
+            problems.push("proximity.max_age_ms must be positive".to_string());
+        }
+        problems
+    }
+}
+
+/// Nearest return in each sector around the drone
+#[derive(Debug, Clone)]
+pub struct ProximityMap {
+    config: ProximityConfig,
+    sectors: Vec<Option<RangeReading>>,
+}
+
+impl ProximityMap {
+    pub fn new(config: ProximityConfig) -> Self {
+        let count = (360.0 / config.sector_deg.clamp(1.0, 360.0)).ceil() as usize;
+        Self { config, sectors: vec![None; count] }
+    }
+
+    pub fn config(&self) -> &ProximityConfig {
+        &self.config
+    }
+
+    /// Take in one scan; each sector it covers is replaced by the scan's nearest return
+    pub fn update(&mut self, readings: &[RangeReading]) {
+        let mut scan: Vec<Option<RangeReading>> = vec![None; self.sectors.len()];
+        for reading in readings.iter().filter(|reading| reading.bearing_deg.is_finite() && !reading.distance_m.is_nan()) {
+            let mut reading = reading.clone();
+            reading.bearing_deg = reading.bearing_deg.rem_euclid(360.0);
+            if reading.distance_m > self.config.max_range_m {
+                reading.distance_m = f32::INFINITY;
+            }
+            let slot = &mut scan[self.sector(reading.bearing_deg)];
+            if slot.as_ref().is_none_or(|nearest| reading.distance_m < nearest.distance_m) {
+                *slot = Some(reading);
+            }
+        }
+        for (sector, reading) in self.sectors.iter_mut().zip(scan) {
+            if reading.is_some() {
+                *sector = reading;
+            }
+        }
+    }
+
+    /// Returns recent enough to trust, including clear ones
+    fn fresh(&self, now: DateTime<Utc>) -> impl Iterator<Item = &RangeReading> {
+        let max_age = self.config.max_age_ms as i64;
+        self.sectors
+            .iter()
+            .flatten()
+            .filter(move |reading| now.signed_duration_since(reading.timestamp).num_milliseconds() <= max_age)
+    }
+
+    /// Whether no sector has been seen recently
+    pub fn is_blind(&self, now: DateTime<Utc>) -> bool {
+        self.fresh(now).next().is_none()
+    }
+
+    /// Recent obstacles, nearest first
+    pub fn obstacles(&self, now: DateTime<Utc>) -> Vec<RangeReading> {
+        let mut obstacles: Vec<RangeReading> = self.fresh(now).filter(|reading| reading.distance_m.is_finite()).cloned().collect();
+        obstacles.sort_by(|a, b| a.distance_m.total_cmp(&b.distance_m));
+        obstacles
+    }
+
+    /// The nearest obstacle in `arc` (bearing, half-width in degrees), or
+    /// all round when no arc is given
+    pub fn nearest(&self, arc: Option<(f32, f32)>, now: DateTime<Utc>) -> Option<RangeReading> {
+        self.obstacles(now).into_iter().find(|reading| match arc {
+            Some((bearing, half_width)) => {
+                let turn = (reading.bearing_deg - bearing).rem_euclid(360.0);
+                // Count a sector in if any part of it overlaps the arc
+                turn.min(360.0 - turn) <= half_width + self.config.sector_deg / 2.0
+            },
+            None => true,
+        })
+    }
+
+    /// The nearest obstacle inside the minimum safe distance, if any
+    pub fn intrusion(&self, now: DateTime<Utc>) -> Option<RangeReading> {
+        self.nearest(None, now).filter(|reading| reading.distance_m < self.config.min_safe_distance_m)
+    }
+
+    /// Whether it is safe to act along `arc`, or all round
+    pub fn check(&self, arc: Option<(f32, f32)>, now: DateTime<Utc>) -> Result<(), RangingError> {
+        if self.config.block_when_blind && self.is_blind(now) {
+            return Err(RangingError::Blind);
+        }
+        match self.nearest(arc, now) {
+            Some(reading) if reading.distance_m < self.config.min_safe_distance_m => Err(RangingError::TooClose {
+                distance_m: reading.distance_m,
+                bearing_deg: reading.bearing_deg,
+                min_safe_m: self.config.min_safe_distance_m,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn sector(&self, bearing_deg: f32) -> usize {
+        ((bearing_deg / self.config.sector_deg) as usize).min(self.sectors.len() - 1)
+    }
+}
+
+/// The latest proximity map from a sensor polled on its own task
+#[derive(Clone)]
+pub struct ProximityFeed {
+    maps: watch::Receiver<Arc<ProximityMap>>,
+}
+
+impl ProximityFeed {
+    /// Poll `sensor` until every copy of the feed is dropped, backing off
+    /// while it fails
+    pub fn start(sensor: Box<dyn RangeSensor>, config: ProximityConfig) -> Self {
+        let poll = Duration::from_millis(config.poll_ms.max(10));
+        let mut map = ProximityMap::new(config);
+        let (tx, maps) = watch::channel(Arc::new(map.clone()));
+        crate::runtime::spawn(async move {
+            let mut backoff = poll;
+            let mut failing = false;
+            while !tx.is_closed() {
+                match sensor.read_ranges().await {
+                    Ok(readings) => {
+                        if failing {
+                            info!("📏 Range sensor recovered");
+                            failing = false;
+                        }
+                        backoff = poll;
+                        map.update(&readings);
+                        tx.send_replace(Arc::new(map.clone()));
+                    },
+                    Err(e) => {
+                        if !failing {
+                            warn!("📏 Range sensor failed: {}", e);
+                            failing = true;
+                        }
+                        backoff = (backoff * 2).min(Duration::from_secs(10));
+                    },
+                }
+                crate::runtime::sleep(backoff).await;
+            }
+        });
+        Self { maps }
+    }
+
+    pub fn latest(&self) -> Arc<ProximityMap> {
+        self.maps.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Arc<ProximityMap>> {
+        self.maps.clone()
+    }
+
+    /// Keep the drone's nearest obstacle up to date; returns once the sensor task ends
+    pub async fn report_to(&self, drone: Arc<RwLock<DroneState>>) {
+        let mut maps = self.subscribe();
+        loop {
+            let intrusion = maps.borrow_and_update().intrusion(Utc::now());
+            drone.write().await.report_obstacle(intrusion);
+            // Also wake up to notice a reading going stale
+            let age = Duration::from_millis(maps.borrow().config.max_age_ms);
+            if let Ok(Err(_)) = crate::runtime::timeout(age, maps.changed()).await {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::vec_deque::{self, VecDeque};
+
+/// Fixed-capacity history that overwrites its oldest entry when full
+///
+/// Storage is allocated once up front, so pushing at sensor rate never
+/// reallocates or shifts the remaining entries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RingBuffer<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.items.len() == self.capacity
+    }
+
+    /// Append an item, returning the oldest one if it was evicted to make room
+    pub fn push(&mut self, item: T) -> Option<T> {
+        let evicted = if self.is_full() { self.items.pop_front() } else { None };
+        self.items.push_back(item);
+        evicted
+    }
+
+    /// Oldest entry
+    pub fn first(&self) -> Option<&T> {
+        self.items.front()
+    }
+
+    /// Newest entry
+    pub fn last(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    /// Oldest first; `.rev()` walks back from the newest
+    pub fn iter(&self) -> vec_deque::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    /// The newest `count` entries, oldest first
+    pub fn recent(&self, count: usize) -> vec_deque::Iter<'_, T> {
+        self.items.range(self.items.len().saturating_sub(count)..)
+    }
+
+    /// The newest run of entries matching `keep`, oldest first, e.g. every
+    /// event from the last minute
+    pub fn recent_while(&self, keep: impl Fn(&T) -> bool) -> vec_deque::Iter<'_, T> {
+        let count = self.items.iter().rev().take_while(|item| keep(item)).count();
+        self.recent(count)
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+impl<T> Extend<T> for RingBuffer<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        for item in items {
+            self.push(item);
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RingBuffer<T> {
+    type Item = &'a T;
+    type IntoIter = vec_deque::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+#[derive(Serialize)]
+struct RingBufferRef<'a, T> {
+    capacity: usize,
+    items: &'a VecDeque<T>,
+}
+
+#[derive(Deserialize)]
+struct RingBufferOwned<T> {
+    capacity: usize,
+    items: Vec<T>,
+}
+
+impl<T: Serialize> Serialize for RingBuffer<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RingBufferRef {
+            capacity: self.capacity,
+            items: &self.items,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for RingBuffer<T> {
+    /// Entries beyond the capacity keep only the newest
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let owned = RingBufferOwned::deserialize(deserializer)?;
+        let mut buffer = RingBuffer::new(owned.capacity);
+        buffer.extend(owned.items);
+        Ok(buffer)
+    }
+}
@@ -0,0 +1,387 @@
+//! Raspberry Pi outputs through rppal (`rpi-hw` feature)
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal::pwm::SetDutyCycle;
+use embedded_hal::spi::SpiBus;
+use serde::{Deserialize, Serialize};
+
+/// Where a PWM signal comes out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PwmPin {
+    /// Hardware PWM channel (0 or 1)
+    Hardware(u8),
+    /// Software PWM on a BCM GPIO number
+    Software(u8),
+}
+
+/// A GPIO switching a relay or MOSFET
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayPin {
+    /// BCM GPIO number
+    pub gpio: u8,
+    /// Relay boards that switch on when the pin is pulled low
+    #[serde(default)]
+    pub active_low: bool,
+}
+
+/// A hobby servo on a 50 Hz PWM signal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServoPin {
+    pub pwm: PwmPin,
+    /// Pulse widths at either end of the travel (microseconds)
+    #[serde(default = "default_min_pulse_us")]
+    pub min_pulse_us: u32,
+    #[serde(default = "default_max_pulse_us")]
+    pub max_pulse_us: u32,
+    /// Travel either side of centre (degrees)
+    #[serde(default = "default_range_deg")]
+    pub range_deg: f32,
+    /// Where the servo sits when stowed, off centre (degrees)
+    #[serde(default)]
+    pub rest_deg: f32,
+}
+
+fn default_min_pulse_us() -> u32 {
+    1000
+}
+
+fn default_max_pulse_us() -> u32 {
+    2000
+}
+
+fn default_range_deg() -> f32 {
+    90.0
+}
+
+/// How the strobe is wired
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrobePin {
+    /// A single-colour strobe switched on and off
+    Gpio(RelayPin),
+    /// A WS2812 strip on the MOSI pin of an SPI bus (GPIO 10 for bus 0)
+    Ws2812 { spi_bus: u8, leds: usize },
+}
+
+/// Which pins drive which output (absent = that output stays a placeholder)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HardwarePins {
+    /// Piezo or amplifier input, driven at the siren's pitch
+    pub siren: Option<PwmPin>,
+    pub strobe: Option<StrobePin>,
+    /// Relay that opens the extinguisher valve
+    pub valve: Option<RelayPin>,
+    /// Nozzle servos: pan is positive to the right, tilt positive upwards
+    pub nozzle_pan: Option<ServoPin>,
+    pub nozzle_tilt: Option<ServoPin>,
+}
+
+impl HardwarePins {
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut gpios = Vec::new();
+        let mut channels = Vec::new();
+        let mut claim = |name: &str, pwm: PwmPin, problems: &mut Vec<String>| match pwm {
+            PwmPin::Hardware(channel) if channel > 1 => problems.push(format!("hardware.{} PWM channel {} must be 0 or 1", name, channel)),
+            PwmPin::Hardware(channel) if channels.contains(&channel) => problems.push(format!("hardware.{} reuses PWM channel {}", name, channel)),
+            PwmPin::Hardware(channel) => channels.push(channel),
+            PwmPin::Software(gpio) => gpios.push((name.to_string(), gpio)),
+        };
+        if let Some(pwm) = self.siren {
+            claim("siren", pwm, &mut problems);
+        }
+        for (name, servo) in [("nozzle_pan", &self.nozzle_pan), ("nozzle_tilt", &self.nozzle_tilt)] {
+            let Some(servo) = servo else { continue };
+            claim(name, servo.pwm, &mut problems);
+            if servo.min_pulse_us >= servo.max_pulse_us || servo.max_pulse_us > SERVO_PERIOD_US {
+                problems.push(format!("hardware.{} pulse range {}..{} µs is not within one 20 ms period", name, servo.min_pulse_us, servo.max_pulse_us));
+            }
+            if !(servo.range_deg > 0.0 && servo.rest_deg.abs() <= servo.range_deg) {
+                problems.push(format!("hardware.{} rest {}° must lie within its {}° range", name, servo.rest_deg, servo.range_deg));
+            }
+        }
+        if let Some(valve) = self.valve {
+            gpios.push(("valve".to_string(), valve.gpio));
+        }
+        match self.strobe {
+            Some(StrobePin::Gpio(relay)) => gpios.push(("strobe".to_string(), relay.gpio)),
+            Some(StrobePin::Ws2812 { spi_bus, leds }) => {
+                if spi_bus > 6 {
+                    problems.push(format!("hardware.strobe SPI bus {} does not exist", spi_bus));
+                }
+                if leds == 0 {
+                    problems.push("hardware.strobe has no LEDs".to_string());
+                }
+            },
+            None => {},
+        }
+        for (index, (name, gpio)) in gpios.iter().enumerate() {
+            if *gpio > 27 {
+                problems.push(format!("hardware.{} GPIO {} is not on the header", name, gpio));
+            }
+            if let Some((other, _)) = gpios[..index].iter().find(|(_, taken)| taken == gpio) {
+                problems.push(format!("hardware.{} and hardware.{} share GPIO {}", other, name, gpio));
+            }
+        }
+        problems
+    }
+
+    pub fn open_siren(&self) -> Result<Option<PwmOutput>, HardwareError> {
+        self.siren.map(|pin| PwmOutput::open(pin, 1000.0)).transpose()
+    }
+
+    pub fn open_strobe(&self) -> Result<Option<StrobeDriver>, HardwareError> {
+        Ok(match self.strobe {
+            Some(StrobePin::Gpio(pin)) => Some(StrobeDriver::Gpio(Relay::new(open_gpio(pin.gpio)?, pin.active_low)?)),
+            Some(StrobePin::Ws2812 { spi_bus, leds }) => {
+                let bus = match spi_bus {
+                    0 => rppal::spi::Bus::Spi0,
+                    1 => rppal::spi::Bus::Spi1,
+                    2 => rppal::spi::Bus::Spi2,
+                    3 => rppal::spi::Bus::Spi3,
+                    4 => rppal::spi::Bus::Spi4,
+                    5 => rppal::spi::Bus::Spi5,
+                    6 => rppal::spi::Bus::Spi6,
+                    _ => return Err(HardwareError::Open { what: format!("SPI bus {}", spi_bus), reason: "no such bus".to_string() }),
+                };
+                let spi = rppal::spi::Spi::new(bus, rppal::spi::SlaveSelect::Ss0, WS2812_SPI_HZ, rppal::spi::Mode::Mode0)
+                    .map_err(|e| HardwareError::Open { what: format!("SPI bus {}", spi_bus), reason: e.to_string() })?;
+                Some(StrobeDriver::Ws2812(Ws2812::new(spi, leds)))
+            },
+            None => None,
+        })
+    }
+
+    pub fn open_valve(&self) -> Result<Option<GpioRelay>, HardwareError> {
+        self.valve.map(|pin| Relay::new(open_gpio(pin.gpio)?, pin.active_low)).transpose()
+    }
+
+    /// Pan and tilt servos, once both are mapped
+    pub fn open_nozzle(&self) -> Result<Option<NozzleServos>, HardwareError> {
+        let (Some(pan), Some(tilt)) = (&self.nozzle_pan, &self.nozzle_tilt) else { return Ok(None) };
+        let open = |servo: &ServoPin| Servo::new(PwmOutput::open(servo.pwm, 1_000_000.0 / SERVO_PERIOD_US as f64)?, servo.clone());
+        Ok(Some(NozzleServos { pan: open(pan)?, tilt: open(tilt)? }))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum HardwareError {
+    #[error("failed to open {what}: {reason}")]
+    Open { what: String, reason: String },
+    #[error("output failed: {0}")]
+    Output(String),
+}
+
+impl embedded_hal::pwm::Error for HardwareError {
+    fn kind(&self) -> embedded_hal::pwm::ErrorKind {
+        embedded_hal::pwm::ErrorKind::Other
+    }
+}
+
+fn open_gpio(gpio: u8) -> Result<rppal::gpio::OutputPin, HardwareError> {
+    let open_error = |e: rppal::gpio::Error| HardwareError::Open { what: format!("GPIO {}", gpio), reason: e.to_string() };
+    Ok(rppal::gpio::Gpio::new().map_err(open_error)?.get(gpio).map_err(open_error)?.into_output_low())
+}
+
+/// A PWM signal whose frequency can change, as a siren's pitch does
+pub struct PwmOutput {
+    signal: PwmSignal,
+    frequency_hz: f64,
+    duty: f64,
+}
+
+enum PwmSignal {
+    Hardware(rppal::pwm::Pwm),
+    Software(rppal::gpio::OutputPin),
+}
+
+impl PwmOutput {
+    /// Open `pin` idle (0% duty) at `frequency_hz`
+    pub fn open(pin: PwmPin, frequency_hz: f64) -> Result<Self, HardwareError> {
+        let signal = match pin {
+            PwmPin::Hardware(channel) => {
+                let channel = match channel {
+                    0 => rppal::pwm::Channel::Pwm0,
+                    1 => rppal::pwm::Channel::Pwm1,
+                    _ => return Err(HardwareError::Open { what: format!("PWM channel {}", channel), reason: "no such channel".to_string() }),
+                };
+                let pwm = rppal::pwm::Pwm::with_frequency(channel, frequency_hz, 0.0, rppal::pwm::Polarity::Normal, true)
+                    .map_err(|e| HardwareError::Open { what: format!("PWM channel {:?}", channel), reason: e.to_string() })?;
+                PwmSignal::Hardware(pwm)
+            },
+            PwmPin::Software(gpio) => PwmSignal::Software(open_gpio(gpio)?),
+        };
+        Ok(Self { signal, frequency_hz, duty: 0.0 })
+    }
+
+    /// Output `frequency_hz` at `duty` (0.0-1.0)
+    pub fn set(&mut self, frequency_hz: f64, duty: f64) -> Result<(), HardwareError> {
+        let duty = duty.clamp(0.0, 1.0);
+        match &mut self.signal {
+            PwmSignal::Hardware(pwm) => pwm.set_frequency(frequency_hz, duty).map_err(|e| HardwareError::Output(e.to_string()))?,
+            PwmSignal::Software(pin) if duty == 0.0 => {
+                pin.clear_pwm().map_err(|e| HardwareError::Output(e.to_string()))?;
+                pin.set_low();
+            },
+            PwmSignal::Software(pin) => pin.set_pwm_frequency(frequency_hz, duty).map_err(|e| HardwareError::Output(e.to_string()))?,
+        }
+        self.frequency_hz = frequency_hz;
+        self.duty = duty;
+        Ok(())
+    }
+
+    pub fn frequency_hz(&self) -> f64 {
+        self.frequency_hz
+    }
+}
+
+impl embedded_hal::pwm::ErrorType for PwmOutput {
+    type Error = HardwareError;
+}
+
+impl SetDutyCycle for PwmOutput {
+    fn max_duty_cycle(&self) -> u16 {
+        u16::MAX
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), HardwareError> {
+        self.set(self.frequency_hz, duty as f64 / u16::MAX as f64)
+    }
+}
+
+/// An on/off output such as a relay or a MOSFET-switched strobe
+pub struct Relay<P> {
+    pin: P,
+    active_low: bool,
+    on: bool,
+}
+
+impl<P: OutputPin> Relay<P> {
+    /// Take `pin` and switch it off
+    pub fn new(pin: P, active_low: bool) -> Result<Self, HardwareError> {
+        let mut relay = Self { pin, active_low, on: true };
+        relay.set(false)?;
+        Ok(relay)
+    }
+
+    pub fn set(&mut self, on: bool) -> Result<(), HardwareError> {
+        let result = if on != self.active_low { self.pin.set_high() } else { self.pin.set_low() };
+        result.map_err(|e| HardwareError::Output(format!("{:?}", e)))?;
+        self.on = on;
+        Ok(())
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+}
+
+/// A relay on one of the Pi's GPIOs
+pub type GpioRelay = Relay<rppal::gpio::OutputPin>;
+
+const SERVO_PERIOD_US: u32 = 20_000;
+
+/// A hobby servo positioned by pulse width
+pub struct Servo<P> {
+    pwm: P,
+    pin: ServoPin,
+    angle_deg: f32,
+}
+
+impl<P: SetDutyCycle> Servo<P> {
+    /// `pwm` must already run at 50 Hz; the servo is moved to rest
+    pub fn new(pwm: P, pin: ServoPin) -> Result<Self, HardwareError> {
+        let mut servo = Self { pwm, angle_deg: pin.rest_deg, pin };
+        servo.rest()?;
+        Ok(servo)
+    }
+
+    /// Move to `angle_deg` off centre, within the travel
+    pub fn set_angle(&mut self, angle_deg: f32) -> Result<(), HardwareError> {
+        let range = self.pin.range_deg.max(f32::EPSILON);
+        let angle = angle_deg.clamp(-range, range);
+        let (min, max) = (self.pin.min_pulse_us as f32, self.pin.max_pulse_us as f32);
+        let pulse_us = (min + max) / 2.0 + angle / range * (max - min) / 2.0;
+        self.pwm
+            .set_duty_cycle_fraction(pulse_us.round() as u16, SERVO_PERIOD_US as u16)
+            .map_err(|e| HardwareError::Output(format!("{:?}", e)))?;
+        self.angle_deg = angle;
+        Ok(())
+    }
+
+    pub fn rest(&mut self) -> Result<(), HardwareError> {
+        self.set_angle(self.pin.rest_deg)
+    }
+
+    pub fn angle_deg(&self) -> f32 {
+        self.angle_deg
+    }
+}
+
+/// SPI clock at which three bits make one WS2812 bit (1.25 µs)
+const WS2812_SPI_HZ: u32 = 2_400_000;
+/// Low time that latches the colours: 300 µs covers the newer WS2812B parts
+const WS2812_RESET_BYTES: usize = 90;
+
+/// A WS2812 ("NeoPixel") strip driven from SPI MOSI
+pub struct Ws2812<S> {
+    spi: S,
+    leds: usize,
+    buffer: Vec<u8>,
+}
+
+impl<S: SpiBus<u8>> Ws2812<S> {
+    /// `spi` must be clocked at 2.4 MHz
+    pub fn new(spi: S, leds: usize) -> Self {
+        Self { spi, leds, buffer: Vec::with_capacity(leds * 9 + WS2812_RESET_BYTES) }
+    }
+
+    /// Light every LED in one colour
+    pub fn fill(&mut self, red: u8, green: u8, blue: u8) -> Result<(), HardwareError> {
+        self.write(&vec![(red, green, blue); self.leds])
+    }
+
+    /// Set the strip from the first LED on
+    pub fn write(&mut self, colours: &[(u8, u8, u8)]) -> Result<(), HardwareError> {
+        self.buffer.clear();
+        for &(red, green, blue) in colours.iter().take(self.leds) {
+            for byte in [green, blue, red] {
+                // 1 -> 110, 0 -> 100, most significant bit first
+                let bits = (0..8).rev().fold(0u32, |bits, bit| bits << 3 | if byte >> bit & 1 == 1 { 0b110 } else { 0b100 });
+                self.buffer.extend_from_slice(&bits.to_be_bytes()[1..]);
+            }
+        }
+        self.buffer.resize(self.buffer.len() + WS2812_RESET_BYTES, 0);
+        self.spi.write(&self.buffer).map_err(|e| HardwareError::Output(format!("{:?}", e)))?;
+        self.spi.flush().map_err(|e| HardwareError::Output(format!("{:?}", e)))
+    }
+}
+
+/// The nozzle's pan and tilt servos, as mapped in `HardwarePins`
+pub struct NozzleServos {
+    pub pan: Servo<PwmOutput>,
+    pub tilt: Servo<PwmOutput>,
+}
+
+/// A strobe as mapped in `HardwarePins::strobe`
+pub enum StrobeDriver {
+    Gpio(GpioRelay),
+    Ws2812(Ws2812<rppal::spi::Spi>),
+}
+
+impl StrobeDriver {
+    /// Show `red`, `green`, `blue` at `intensity` (0.0-1.0); a single-colour
+    /// strobe lights at half intensity or more
+    pub fn set(&mut self, (red, green, blue): (u8, u8, u8), intensity: f32) -> Result<(), HardwareError> {
+        let intensity = intensity.clamp(0.0, 1.0);
+        match self {
+            StrobeDriver::Gpio(relay) => relay.set(intensity >= 0.5),
+            StrobeDriver::Ws2812(strip) => {
+                let scale = |channel: u8| (channel as f32 * intensity).round() as u8;
+                strip.fill(scale(red), scale(green), scale(blue))
+            },
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+
+/// Local time-of-day window, wrapping past midnight when `end < start`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TimeWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TimeWindow {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
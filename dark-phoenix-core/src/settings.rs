@@ -0,0 +1,274 @@
+use crate::{
+    AuditConfig, AuthConfig, BatteryConfig, EncryptionConfig, FailsafeConfig, Geofence, LinkConfig, LinkManagerConfig, PairingConfig, PanicConfig, PatrolConfig, PowerConfig, PreflightConfig, ProtecteeConfig, ReportConfig, SignedCommandConfig, TransitionRules, WebhooksConfig,
+};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::path::Path;
+use thiserror::Error;
+
+/// Everything `phoenix run` reads from its config file
+///
+/// Sections for features this build lacks are ignored, so one file can
+/// serve every build of the drone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub name: String,
+    pub threat_rules: TransitionRules,
+    /// Site boundaries (empty = fly anywhere)
+    pub geofence: Geofence,
+    /// When to hover, return home or land regardless of the mission
+    pub failsafe: FailsafeConfig,
+    /// When the command link counts as lost, and what the drone does then
+    pub link: LinkConfig,
+    /// Wi-Fi, cellular and satellite links to route telemetry and
+    /// emergency calls over (none = the system's default route)
+    pub links: LinkManagerConfig,
+    /// Patrol routes and their schedules
+    pub patrol: PatrolConfig,
+    /// Beacon pairing and escort envelope for the person being protected
+    pub protectee: ProtecteeConfig,
+    /// Battery capacity, load draws and when to shed them
+    pub power: PowerConfig,
+    /// Pack identity, rated cycles and where its charge-cycle history is kept
+    pub battery: BatteryConfig,
+    /// What must pass before the drone arms
+    pub preflight: PreflightConfig,
+    /// Access tokens and the roles they grant, for every command entry point
+    pub auth: AuthConfig,
+    /// Keys commands may be signed with instead, and whether they must be
+    pub signed_commands: SignedCommandConfig,
+    /// Ground stations pairing their own command keys with a PIN or QR
+    /// code, and where those keys are kept (absent = configured keys only)
+    pub pairing: Option<PairingConfig>,
+    /// Devices allowed to press the panic button, and their secrets
+    pub panic: PanicConfig,
+    /// Hash-chained mission log and its signing key (absent = not persisted)
+    pub audit: Option<AuditConfig>,
+    /// Device keyring every store is encrypted with (absent = plaintext)
+    pub encryption: Option<EncryptionConfig>,
+    /// HTTP endpoints notified of mission events, e.g. Slack, Discord or ntfy
+    pub webhooks: WebhooksConfig,
+    /// Where incident reports find threat assessments and evidence
+    pub reports: ReportConfig,
+    /// Standalone Prometheus exporter (absent = only on the API server)
+    pub metrics_bind: Option<SocketAddr>,
+    #[cfg(feature = "api-server")]
+    pub api: crate::ApiConfig,
+    /// mDNS announcement of the API (absent = not announced)
+    #[cfg(feature = "mdns")]
+    pub discovery: Option<crate::DiscoveryConfig>,
+    #[cfg(feature = "mqtt")]
+    pub mqtt: Option<crate::MqttConfig>,
+    /// SIEM collector for CEF or syslog forwarding (absent = not forwarded)
+    #[cfg(feature = "siem")]
+    pub siem: Option<crate::SiemConfig>,
+    /// LoRa status beacon and command link (absent = no modem)
+    #[cfg(feature = "lora")]
+    pub lora: Option<crate::LoraConfig>,
+    /// Wearable vitals processing and distress limits
+    #[cfg(feature = "ble")]
+    pub vitals: crate::VitalsConfig,
+    /// MAVLink flight controller (absent = fly without one)
+    #[cfg(feature = "mavlink")]
+    pub flight: Option<crate::FlightConfig>,
+    /// PX4 SITL + Gazebo in place of a real flight controller and actuators
+    #[cfg(feature = "sitl")]
+    pub sitl: Option<crate::SitlConfig>,
+    /// GPIO, PWM and SPI pins of the siren, strobe, valve and nozzle servos
+    #[cfg(feature = "rpi-hw")]
+    pub hardware: crate::HardwarePins,
+    /// CAN bus carrying the valve, nozzle and siren controllers (absent = no bus)
+    #[cfg(feature = "socketcan")]
+    pub can: Option<crate::CanConfig>,
+    /// Building fire panel read and alarmed over Modbus (absent = no panel)
+    #[cfg(feature = "modbus")]
+    pub modbus: Option<crate::ModbusConfig>,
+    /// WASM plugins taking part in response coordination (absent = none)
+    #[cfg(feature = "wasm-plugins")]
+    pub plugins: Option<crate::PluginsConfig>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            name: "Dark Phoenix Alpha".to_string(),
+            threat_rules: TransitionRules::default(),
+            geofence: Geofence::default(),
+            failsafe: FailsafeConfig::default(),
+            link: LinkConfig::default(),
+            links: LinkManagerConfig::default(),
+            patrol: PatrolConfig::default(),
+            protectee: ProtecteeConfig::default(),
+            power: PowerConfig::default(),
+            battery: BatteryConfig::default(),
+            preflight: PreflightConfig::default(),
+            auth: AuthConfig::default(),
+            signed_commands: SignedCommandConfig::default(),
+            pairing: None,
+            panic: PanicConfig::default(),
+            audit: None,
+            encryption: None,
+            webhooks: WebhooksConfig::default(),
+            reports: ReportConfig::default(),
+            metrics_bind: None,
+            #[cfg(feature = "api-server")]
+            api: crate::ApiConfig::default(),
+            #[cfg(feature = "mdns")]
+            discovery: None,
+            #[cfg(feature = "mqtt")]
+            mqtt: None,
+            #[cfg(feature = "siem")]
+            siem: None,
+            #[cfg(feature = "lora")]
+            lora: None,
+            #[cfg(feature = "ble")]
+            vitals: crate::VitalsConfig::default(),
+            #[cfg(feature = "mavlink")]
+            flight: None,
+            #[cfg(feature = "sitl")]
+            sitl: None,
+            #[cfg(feature = "rpi-hw")]
+            hardware: crate::HardwarePins::default(),
+            #[cfg(feature = "socketcan")]
+            can: None,
+            #[cfg(feature = "modbus")]
+            modbus: None,
+            #[cfg(feature = "wasm-plugins")]
+            plugins: None,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error("failed to load settings: {0}")]
+    Load(#[from] config::ConfigError),
+    #[error("invalid settings:\n  - {}", .0.join("\n  - "))]
+    Invalid(Vec<String>),
+}
+
+impl Settings {
+    /// Read a TOML, YAML or JSON file (by extension), with `PHOENIX__`
+    /// environment variables overriding it, e.g. `PHOENIX__NAME`
+    pub fn load(path: &Path) -> Result<Self, SettingsError> {
+        let settings: Settings = config::Config::builder()
+            .add_source(config::File::from(path))
+            .add_source(config::Environment::with_prefix("PHOENIX").prefix_separator("__").separator("__"))
+            .build()?
+            .try_deserialize()?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Check what serde cannot: ranges, clashing ports, missing files
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let mut problems = Vec::new();
+        if self.name.trim().is_empty() {
+            problems.push("name must not be empty".to_string());
+        }
+        if self.threat_rules.omega_requires_authorization && self.threat_rules.authorization_ttl_secs == 0 {
+            problems.push("threat_rules.authorization_ttl_secs must be positive when Omega needs authorization".to_string());
+        }
+        problems.extend(self.geofence.problems());
+        problems.extend(self.failsafe.problems());
+        problems.extend(self.link.problems());
+        problems.extend(self.links.problems());
+        problems.extend(self.patrol.problems(&self.geofence));
+        problems.extend(self.protectee.problems());
+        problems.extend(self.power.problems());
+        problems.extend(self.battery.problems());
+        problems.extend(self.preflight.problems());
+        problems.extend(self.auth.problems());
+        problems.extend(self.signed_commands.problems());
+        if self.signed_commands.required && self.signed_commands.keys.is_empty() && self.pairing.is_none() {
+            problems.push("signed_commands.required needs at least one key or pairing, or no command is ever accepted".to_string());
+        }
+        problems.extend(self.pairing.iter().flat_map(|pairing| pairing.problems()));
+        problems.extend(self.panic.problems());
+        problems.extend(self.webhooks.problems());
+        if let Some(audit) = &self.audit {
+            problems.extend(audit.problems());
+        }
+
+        let mut binds: Vec<(&str, SocketAddr)> = Vec::new();
+        if let Some(bind) = self.metrics_bind {
+            binds.push(("metrics_bind", bind));
+        }
+        #[cfg(feature = "api-server")]
+        binds.push(("api.bind", self.api.bind));
+        for (i, (name, bind)) in binds.iter().enumerate() {
+            if let Some((other, _)) = binds[..i].iter().find(|(_, earlier)| earlier == bind) {
+                problems.push(format!("{} and {} both use {}", other, name, bind));
+            }
+        }
+
+        #[cfg(feature = "mqtt")]
+        if let Some(mqtt) = &self.mqtt {
+            if mqtt.host.trim().is_empty() {
+                problems.push("mqtt.host must not be empty".to_string());
+            }
+            for topic in [&mqtt.state, &mqtt.threat, &mqtt.module_health, &mqtt.command] {
+                if topic.qos > 2 {
+                    problems.push(format!("mqtt topic '{}' has QoS {} (expected 0, 1 or 2)", topic.topic, topic.qos));
+                }
+            }
+            if let Some(tls) = &mqtt.tls {
+                let files = std::iter::once(&tls.ca_path).chain(&tls.client_cert_path).chain(&tls.client_key_path);
+                for file in files.filter(|file| !file.exists()) {
+                    problems.push(format!("mqtt TLS file {} does not exist", file.display()));
+                }
+            }
+            problems.extend(mqtt.outbox.iter().flat_map(|outbox| outbox.problems()).map(|problem| format!("mqtt.{}", problem)));
+            problems.extend(mqtt.delta.iter().flat_map(|delta| delta.problems()).map(|problem| format!("mqtt.{}", problem)));
+            problems.extend(mqtt.home_assistant.iter().flat_map(|home_assistant| home_assistant.problems()).map(|problem| format!("mqtt.{}", problem)));
+        }
+        #[cfg(feature = "siem")]
+        if let Some(siem) = &self.siem {
+            problems.extend(siem.problems());
+            let files = siem.tls.ca_path.iter().chain(&siem.tls.client_cert_path).chain(&siem.tls.client_key_path);
+            for file in files.filter(|file| !file.exists()) {
+                problems.push(format!("siem TLS file {} does not exist", file.display()));
+            }
+        }
+        #[cfg(feature = "lora")]
+        problems.extend(self.lora.iter().flat_map(|lora| lora.problems()));
+        #[cfg(feature = "ble")]
+        problems.extend(self.vitals.wearable.iter().flat_map(|wearable| wearable.problems()));
+
+        #[cfg(feature = "mavlink")]
+        if let Some(flight) = &self.flight {
+            let scheme = flight.connection.split(':').next().unwrap_or_default();
+            if !["udpin", "udpout", "tcpout"].contains(&scheme) {
+                problems.push(format!(
+                    "flight.connection '{}' must start with udpin:, udpout: or tcpout:",
+                    flight.connection
+                ));
+            }
+            if flight.command_attempts == 0 {
+                problems.push("flight.command_attempts must be at least 1".to_string());
+            }
+        }
+        #[cfg(feature = "sitl")]
+        if let Some(sitl) = &self.sitl {
+            problems.extend(sitl.problems());
+            if self.flight.is_some() {
+                problems.push("flight and sitl are both set; with sitl the flight link goes to the simulator".to_string());
+            }
+        }
+        #[cfg(feature = "rpi-hw")]
+        problems.extend(self.hardware.problems());
+        #[cfg(feature = "socketcan")]
+        problems.extend(self.can.iter().flat_map(|can| can.problems()));
+        #[cfg(feature = "modbus")]
+        problems.extend(self.modbus.iter().flat_map(|modbus| modbus.problems()));
+        #[cfg(feature = "wasm-plugins")]
+        problems.extend(self.plugins.iter().flat_map(|plugins| plugins.problems()));
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(SettingsError::Invalid(problems))
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use crate::ModuleResult;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+/// Order shutdown steps run in, whatever order they were registered in:
+/// anything that can hurt someone is made safe first, records last
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownPhase {
+    /// Close valves, retract nozzles
+    Actuators,
+    /// Strobes, sirens, speakers
+    Outputs,
+    /// Event stores and logs
+    Flush,
+    Final,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepOutcome {
+    Completed,
+    Failed(String),
+    /// Aborted when its timeout ran out
+    TimedOut,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepReport {
+    pub name: String,
+    pub phase: ShutdownPhase,
+    pub outcome: StepOutcome,
+    pub elapsed_ms: u64,
+}
+
+/// What happened to each step of a shutdown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    pub reason: String,
+    pub steps: Vec<StepReport>,
+}
+
+impl ShutdownReport {
+    /// Every step completed in time
+    pub fn is_clean(&self) -> bool {
+        self.steps.iter().all(|step| step.outcome == StepOutcome::Completed)
+    }
+}
+
+type StepFuture = Pin<Box<dyn Future<Output = ModuleResult> + Send>>;
+
+struct ShutdownStep {
+    name: String,
+    phase: ShutdownPhase,
+    timeout: Duration,
+    run: Box<dyn FnOnce() -> StepFuture + Send>,
+}
+
+/// Requests a shutdown from anywhere: an operator command, a failed module
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle {
+    requested: Arc<watch::Sender<Option<String>>>,
+}
+
+impl ShutdownHandle {
+    pub fn request(&self, reason: &str) {
+        self.requested.send_if_modified(|requested| {
+            if requested.is_some() {
+                return false;
+            }
+            *requested = Some(reason.to_string());
+            true
+        });
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.borrow().is_some()
+    }
+
+    /// Completes once a shutdown has been requested, e.g. to stop a server
+    pub async fn wait(&self) {
+        let mut requested = self.requested.subscribe();
+        let _ = requested.wait_for(Option::is_some).await;
+    }
+}
+
+/// Brings every module to a safe state, one bounded step at a time, before
+/// the process exits
+///
+/// A step that overruns its timeout is aborted and the sequence moves on, so
+/// a stuck speaker can never keep a valve open.
+pub struct ShutdownCoordinator {
+    steps: Vec<ShutdownStep>,
+    requested: Arc<watch::Sender<Option<String>>>,
+}
+
+impl Default for ShutdownCoordinator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShutdownCoordinator {
+    pub fn new() -> Self {
+        Self {
+            steps: Vec::new(),
+            requested: Arc::new(watch::channel(None).0),
+        }
+    }
+
+    pub fn handle(&self) -> ShutdownHandle {
+        ShutdownHandle {
+            requested: Arc::clone(&self.requested),
+        }
+    }
+
+    /// Add a step; steps run by phase, then in registration order
+    pub fn add_step<F, Fut>(&mut self, name: &str, phase: ShutdownPhase, timeout: Duration, step: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ModuleResult> + Send + 'static,
+    {
+        self.steps.push(ShutdownStep {
+            name: name.to_string(),
+            phase,
+            timeout,
+            run: Box::new(move || Box::pin(step())),
+        });
+    }
+
+    /// Wait for Ctrl-C, SIGTERM (with tokio's runtime) or a
+    /// `ShutdownHandle::request`; returns the reason
+    pub async fn wait_for_signal(&self) -> String {
+        let mut requested = self.requested.subscribe();
+        let request = async {
+            match requested.wait_for(Option::is_some).await {
+                Ok(reason) => reason.clone().unwrap_or_default(),
+                // The sender lives in `self`, so this never closes while waiting
+                Err(_) => std::future::pending().await,
+            }
+        };
+        #[cfg(feature = "tokio-runtime")]
+        return tokio::select! {
+            reason = request => reason,
+            _ = tokio::signal::ctrl_c() => "interrupt signal".to_string(),
+            _ = terminate() => "terminate signal".to_string(),
+        };
+        #[cfg(not(feature = "tokio-runtime"))]
+        return request.await;
+    }
+
+    /// Run every step in order and report how each went
+    pub async fn run(mut self, reason: &str) -> ShutdownReport {
+        self.handle().request(reason);
+        warn!("🛬 Shutting down: {}", reason);
+        self.steps.sort_by_key(|step| step.phase);
+
+        let mut steps = Vec::new();
+        for step in self.steps {
+            let started = Instant::now();
+            let mut task = crate::runtime::spawn((step.run)());
+            let outcome = match crate::runtime::timeout(step.timeout, &mut task).await {
+                Ok(Ok(Ok(()))) => StepOutcome::Completed,
+                Ok(Ok(Err(e))) => StepOutcome::Failed(e.to_string()),
+                Ok(Err(e)) => StepOutcome::Failed(format!("step panicked: {}", e)),
+                Err(_) => {
+                    task.abort();
+                    StepOutcome::TimedOut
+                },
+            };
+            match &outcome {
+                StepOutcome::Completed => info!("✅ Shutdown step '{}' done", step.name),
+                StepOutcome::Failed(e) => error!("❌ Shutdown step '{}' failed: {}", step.name, e),
+                StepOutcome::TimedOut => {
+                    error!("⏱️ Shutdown step '{}' aborted after {:?}", step.name, step.timeout)
+                },
+            }
+            steps.push(StepReport {
+                name: step.name,
+                phase: step.phase,
+                outcome,
+                elapsed_ms: started.elapsed().as_millis() as u64,
+            });
+        }
+        ShutdownReport {
+            reason: reason.to_string(),
+            steps,
+        }
+    }
+}
+
+#[cfg(all(unix, feature = "tokio-runtime"))]
+async fn terminate() {
+    match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+        Ok(mut signal) => {
+            signal.recv().await;
+        },
+        Err(_) => std::future::pending().await,
+    }
+}
+
+#[cfg(all(not(unix), feature = "tokio-runtime"))]
+async fn terminate() {
+    std::future::pending().await
+}
@@ -0,0 +1,647 @@
+//! SIEM forwarding as RFC 5424 syslog, carrying CEF or structured data
+
+use crate::webhook::EventClass;
+use crate::{Checkpoint, DroneState, MissionEvent, ModuleResult, ThreatLevel};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+/// Record kinds this crate and the module crates forward
+pub const RECORD_KINDS: [&str; 4] = [MissionEvent::KIND, Checkpoint::KIND, "threat_assessment", "fire_event"];
+
+/// Longest CEF Name the collectors are guaranteed to keep
+const MAX_NAME_CHARS: usize = 512;
+
+/// Something worth a SIEM entry
+pub trait SiemRecord: Serialize {
+    /// Picks the field mapping; also the syslog MSGID
+    const KIND: &'static str;
+
+    /// CEF Device Event Class ID, e.g. the event type
+    fn signature_id(&self) -> String;
+
+    /// What happened, in a line
+    fn name(&self) -> String;
+
+    /// 0 (routine) to 10 (critical), as CEF counts it
+    fn severity(&self) -> u8;
+
+    fn timestamp(&self) -> DateTime<Utc>;
+
+    /// Mapping used when `SiemConfig::fields` has none for this kind
+    fn default_fields() -> Vec<SiemField>;
+}
+
+/// CEF severity of a threat level
+pub fn threat_severity(level: ThreatLevel) -> u8 {
+    match level {
+        ThreatLevel::Green => 1,
+        ThreatLevel::Yellow => 3,
+        ThreatLevel::Orange => 5,
+        ThreatLevel::Red => 8,
+        ThreatLevel::Omega => 10,
+    }
+}
+
+/// One record field in the SIEM entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SiemField {
+    /// CEF extension key (`cs1`, `src`, `msg`), or the structured-data
+    /// parameter name in `syslog` format when there is no label
+    pub key: String,
+    /// Dotted path into the record's JSON, e.g. `position.latitude`;
+    /// arrays are joined with "; " and missing, null or empty fields left out
+    pub path: String,
+    /// Sent as `<key>Label` in CEF, and names the structured-data parameter
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+impl SiemField {
+    pub fn new(key: &str, path: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            path: path.to_string(),
+            label: None,
+        }
+    }
+
+    pub fn labelled(key: &str, path: &str, label: &str) -> Self {
+        Self {
+            label: Some(label.to_string()),
+            ..Self::new(key, path)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SiemTransport {
+    Udp,
+    Tcp,
+    #[default]
+    Tls,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SiemFormat {
+    /// CEF as the syslog message
+    #[default]
+    Cef,
+    /// Plain RFC 5424, the fields as structured data
+    Syslog,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SiemTls {
+    /// PEM CA certificates for the collector (absent = the system's roots)
+    pub ca_path: Option<PathBuf>,
+    /// PEM client certificate and key, for collectors that require client auth
+    pub client_cert_path: Option<PathBuf>,
+    pub client_key_path: Option<PathBuf>,
+    /// Name the collector's certificate must carry (absent = the host of `address`)
+    pub server_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SiemConfig {
+    /// Collector, `host:port`
+    pub address: String,
+    pub transport: SiemTransport,
+    pub tls: SiemTls,
+    pub format: SiemFormat,
+    /// Syslog facility, 0-23 (13 = log audit)
+    pub facility: u8,
+    pub app_name: String,
+    /// Syslog HOSTNAME (absent = the drone's name)
+    pub hostname: Option<String>,
+    pub device_vendor: String,
+    pub device_product: String,
+    /// Private enterprise number in the structured-data ID of `syslog` format
+    pub enterprise_id: u32,
+    /// Field mapping per record kind (`mission_event`, `threat_assessment`,
+    /// `fire_event`), replacing that kind's default mapping
+    pub fields: HashMap<String, Vec<SiemField>>,
+    /// Records below this severity (0-10) are not forwarded
+    pub min_severity: u8,
+    /// Lines held while the collector is unreachable
+    pub queue: usize,
+    pub reconnect_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for SiemConfig {
+    fn default() -> Self {
+        Self {
+            address: "localhost:6514".to_string(),
+            transport: SiemTransport::Tls,
+            tls: SiemTls::default(),
+            format: SiemFormat::Cef,
+            facility: 13,
+            app_name: "phoenix".to_string(),
+            hostname: None,
+            device_vendor: "Dark Phoenix".to_string(),
+            device_product: "Phoenix Drone".to_string(),
+            enterprise_id: 32473,
+            fields: HashMap::new(),
+            min_severity: 0,
+            queue: 1000,
+            reconnect_backoff_ms: 1000,
+            max_backoff_ms: 60_000,
+        }
+    }
+}
+
+impl SiemConfig {
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if split_address(&self.address).is_none() {
+            problems.push(format!("siem.address '{}' is not host:port", self.address));
+        }
+        if self.facility > 23 {
+            problems.push(format!("siem.facility {} is out of range (0-23)", self.facility));
+        }
+        if self.min_severity > 10 {
+            problems.push(format!("siem.min_severity {} is out of range (0-10)", self.min_severity));
+        }
+        if self.queue == 0 {
+            problems.push("siem.queue must be greater than zero".to_string());
+        }
+        if self.reconnect_backoff_ms == 0 || self.max_backoff_ms < self.reconnect_backoff_ms {
+            problems.push("siem.reconnect_backoff_ms must be greater than zero and at most siem.max_backoff_ms".to_string());
+        }
+        if self.tls.client_cert_path.is_some() != self.tls.client_key_path.is_some() {
+            problems.push("siem.tls client certificate and key must be given together".to_string());
+        }
+        for (kind, fields) in &self.fields {
+            if !RECORD_KINDS.contains(&kind.as_str()) {
+                problems.push(format!("siem.fields has unknown record kind '{}' (expected one of {})", kind, RECORD_KINDS.join(", ")));
+            }
+            for field in fields {
+                if field.key.is_empty() || !field.key.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    problems.push(format!("siem.fields.{} key '{}' must be letters and digits", kind, field.key));
+                }
+                if field.path.trim().is_empty() {
+                    problems.push(format!("siem.fields.{} '{}' has no path", kind, field.key));
+                }
+            }
+        }
+        problems
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SiemError {
+    #[error("SIEM address '{0}' is not host:port")]
+    Address(String),
+    #[error("failed to read TLS file {0}: {1}")]
+    TlsFile(PathBuf, std::io::Error),
+    #[error("TLS client certificate and key must be given together")]
+    IncompleteClientAuth,
+    #[error("TLS setup failed: {0}")]
+    Tls(String),
+}
+
+/// Formats records and queues them for the connection; cheap to clone
+#[derive(Clone)]
+pub struct SiemForwarder {
+    config: Arc<SiemConfig>,
+    hostname: String,
+    lines: mpsc::Sender<Vec<u8>>,
+    dropped: Arc<AtomicU64>,
+    /// Next mission event `follow` forwards, kept across restarts
+    next_sequence: Arc<AtomicU64>,
+}
+
+/// The collector end, run as a task
+pub struct SiemConnection {
+    config: Arc<SiemConfig>,
+    tls: Option<(TlsConnector, ServerName<'static>)>,
+    lines: mpsc::Receiver<Vec<u8>>,
+}
+
+/// Set up forwarding to the collector; the TLS roots and client key are read
+/// here, so a bad path fails at startup rather than on the first connect
+pub fn connect(config: SiemConfig, drone_name: &str) -> Result<(SiemForwarder, SiemConnection), SiemError> {
+    let (host, _) = split_address(&config.address).ok_or_else(|| SiemError::Address(config.address.clone()))?;
+    let tls = match config.transport {
+        SiemTransport::Tls => {
+            let name = config.tls.server_name.clone().unwrap_or_else(|| host.to_string());
+            let name = ServerName::try_from(name).map_err(|e| SiemError::Tls(e.to_string()))?;
+            Some((tls_connector(&config.tls)?, name))
+        },
+        SiemTransport::Udp | SiemTransport::Tcp => None,
+    };
+    let hostname = header_field(config.hostname.as_deref().unwrap_or(drone_name), 255);
+    let config = Arc::new(config);
+    let (sender, lines) = mpsc::channel(config.queue.max(1));
+    let forwarder = SiemForwarder {
+        config: Arc::clone(&config),
+        hostname,
+        lines: sender,
+        dropped: Arc::new(AtomicU64::new(0)),
+        next_sequence: Arc::new(AtomicU64::new(0)),
+    };
+    Ok((forwarder, SiemConnection { config, tls, lines }))
+}
+
+fn split_address(address: &str) -> Option<(&str, u16)> {
+    let (host, port) = address.rsplit_once(':')?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() {
+        return None;
+    }
+    Some((host, port.parse().ok()?))
+}
+
+fn tls_connector(tls: &SiemTls) -> Result<TlsConnector, SiemError> {
+    let open = |path: &PathBuf| std::fs::File::open(path).map(BufReader::new).map_err(|e| SiemError::TlsFile(path.clone(), e));
+    let mut roots = RootCertStore::empty();
+    match &tls.ca_path {
+        Some(path) => {
+            for cert in rustls_pemfile::certs(&mut open(path)?) {
+                let cert = cert.map_err(|e| SiemError::TlsFile(path.clone(), e))?;
+                roots.add(cert).map_err(|e| SiemError::Tls(e.to_string()))?;
+            }
+        },
+        None => {
+            let certs = rustls_native_certs::load_native_certs().map_err(|e| SiemError::Tls(format!("no system roots: {}", e)))?;
+            roots.add_parsable_certificates(certs);
+        },
+    }
+    let builder = ClientConfig::builder().with_root_certificates(roots);
+    let config = match (&tls.client_cert_path, &tls.client_key_path) {
+        (Some(cert_path), Some(key_path)) => {
+            let certs = rustls_pemfile::certs(&mut open(cert_path)?)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| SiemError::TlsFile(cert_path.clone(), e))?;
+            let key = rustls_pemfile::private_key(&mut open(key_path)?)
+                .map_err(|e| SiemError::TlsFile(key_path.clone(), e))?
+                .ok_or_else(|| SiemError::Tls(format!("no private key in {}", key_path.display())))?;
+            builder.with_client_auth_cert(certs, key).map_err(|e| SiemError::Tls(e.to_string()))?
+        },
+        (None, None) => builder.with_no_client_auth(),
+        _ => return Err(SiemError::IncompleteClientAuth),
+    };
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+impl SiemForwarder {
+    /// Queue a record for the collector; false if it was below
+    /// `min_severity` or dropped because the queue is full
+    pub fn forward<R: SiemRecord>(&self, record: &R) -> bool {
+        if record.severity() < self.config.min_severity {
+            return false;
+        }
+        match self.lines.try_send(self.format(record).into_bytes()) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                if self.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+                    tracing::warn!("🛰️ SIEM queue full, dropping records until the collector catches up");
+                }
+                false
+            },
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        }
+    }
+
+    /// Records dropped on a full queue so far
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Lines waiting for the collector
+    pub fn queued(&self) -> usize {
+        self.lines.max_capacity() - self.lines.capacity()
+    }
+
+    /// The syslog line for a record, without framing
+    pub fn format<R: SiemRecord>(&self, record: &R) -> String {
+        let json = serde_json::to_value(record).unwrap_or_default();
+        let fields = self.config.fields.get(R::KIND).cloned().unwrap_or_else(R::default_fields);
+        let values: Vec<(SiemField, String)> = fields
+            .into_iter()
+            .filter_map(|field| {
+                let value = lookup(&json, &field.path)?;
+                Some((field, value))
+            })
+            .collect();
+        let severity = record.severity().min(10);
+        let timestamp = record.timestamp();
+        let priority = u32::from(self.config.facility.min(23)) * 8 + syslog_severity(severity);
+        let header = format!(
+            "<{}>1 {} {} {} - {}",
+            priority,
+            timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+            self.hostname,
+            header_field(&self.config.app_name, 48),
+            R::KIND,
+        );
+        let name: String = record.name().chars().take(MAX_NAME_CHARS).collect();
+        match self.config.format {
+            SiemFormat::Cef => {
+                let mut extension = vec![format!("rt={}", timestamp.timestamp_millis()), format!("dvchost={}", cef_value(&self.hostname))];
+                for (field, value) in &values {
+                    extension.push(format!("{}={}", field.key, cef_value(value)));
+                    if let Some(label) = &field.label {
+                        extension.push(format!("{}Label={}", field.key, cef_value(label)));
+                    }
+                }
+                format!(
+                    "{} - CEF:0|{}|{}|{}|{}|{}|{}|{}",
+                    header,
+                    cef_header(&self.config.device_vendor),
+                    cef_header(&self.config.device_product),
+                    env!("CARGO_PKG_VERSION"),
+                    cef_header(&record.signature_id()),
+                    cef_header(&name),
+                    severity,
+                    extension.join(" "),
+                )
+            },
+            SiemFormat::Syslog => {
+                let mut data = format!("[{}@{} sev=\"{}\" sig=\"{}\"", R::KIND, self.config.enterprise_id, severity, sd_value(&record.signature_id()));
+                for (field, value) in &values {
+                    data.push_str(&format!(" {}=\"{}\"", sd_name(field.label.as_deref().unwrap_or(&field.key)), sd_value(value)));
+                }
+                data.push(']');
+                format!("{} {} {}", header, data, name.replace(['\r', '\n'], " "))
+            },
+        }
+    }
+}
+
+fn syslog_severity(severity: u8) -> u32 {
+    match severity {
+        9.. => 2,
+        7..=8 => 3,
+        5..=6 => 4,
+        3..=4 => 5,
+        _ => 6,
+    }
+}
+
+fn lookup(json: &Value, path: &str) -> Option<String> {
+    let value = path.split('.').try_fold(json, |value, segment| match value {
+        Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => value.get(segment),
+    })?;
+    text(value)
+}
+
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        Value::Array(items) if items.is_empty() => None,
+        Value::Array(items) => Some(items.iter().filter_map(text).collect::<Vec<_>>().join("; ")),
+        // Most readings are f32; print those without the digits widening adds
+        Value::Number(number) => match number.as_f64() {
+            Some(wide) if !number.is_i64() && !number.is_u64() && f64::from(wide as f32) == wide => Some((wide as f32).to_string()),
+            _ => Some(number.to_string()),
+        },
+        other => Some(other.to_string()),
+    }
+}
+
+/// HOSTNAME and APP-NAME: printable ASCII, no spaces
+fn header_field(value: &str, max: usize) -> String {
+    let field: String = value
+        .chars()
+        .map(|c| if c.is_ascii_graphic() { c } else { '-' })
+        .take(max)
+        .collect();
+    if field.is_empty() {
+        "-".to_string()
+    } else {
+        field
+    }
+}
+
+fn cef_header(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+fn cef_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn sd_name(name: &str) -> String {
+    name.chars().filter(|c| c.is_ascii_graphic() && !matches!(c, '=' | ']' | '"')).take(32).collect()
+}
+
+fn sd_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace(']', "\\]")
+}
+
+enum Link {
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl Link {
+    async fn send(&mut self, line: &[u8]) -> std::io::Result<()> {
+        self.check_open()?;
+        match self {
+            Link::Udp(socket) => socket.send(line).await.map(|_| ()),
+            Link::Tcp(stream) => stream.write_all(&framed(line)).await,
+            Link::Tls(stream) => {
+                stream.write_all(&framed(line)).await?;
+                stream.flush().await
+            },
+        }
+    }
+
+    /// A write to a stream the collector has closed still succeeds once, so
+    /// look for the close first; collectors never send anything back
+    fn check_open(&self) -> std::io::Result<()> {
+        let stream = match self {
+            Link::Udp(_) => return Ok(()),
+            Link::Tcp(stream) => stream,
+            Link::Tls(stream) => stream.get_ref().0,
+        };
+        match stream.try_read(&mut [0; 1]) {
+            Ok(0) => Err(std::io::ErrorKind::ConnectionReset.into()),
+            Err(e) if e.kind() != std::io::ErrorKind::WouldBlock => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Octet-counted: the length in ASCII, a space, then the message
+fn framed(line: &[u8]) -> Vec<u8> {
+    let mut frame = format!("{} ", line.len()).into_bytes();
+    frame.extend_from_slice(line);
+    frame
+}
+
+impl SiemConnection {
+    /// Send queued lines until every forwarder is dropped and the queue is
+    /// empty, reconnecting with backoff whenever the collector goes away
+    pub async fn run(mut self) -> ModuleResult {
+        let initial = Duration::from_millis(self.config.reconnect_backoff_ms.max(1));
+        let mut backoff = initial;
+        let mut pending: Option<Vec<u8>> = None;
+        loop {
+            if pending.is_none() && self.lines.is_closed() && self.lines.is_empty() {
+                return Ok(());
+            }
+            let mut link = match self.open().await {
+                Ok(link) => {
+                    tracing::info!("🛰️ Forwarding to SIEM at {}", self.config.address);
+                    backoff = initial;
+                    link
+                },
+                Err(e) => {
+                    tracing::warn!("🛰️ SIEM collector {} unreachable: {}; retrying in {:?}", self.config.address, e, backoff);
+                    crate::runtime::sleep(backoff).await;
+                    backoff = (backoff * 2).min(Duration::from_millis(self.config.max_backoff_ms).max(initial));
+                    continue;
+                },
+            };
+            loop {
+                let line = match pending.take() {
+                    Some(line) => line,
+                    None => match self.lines.recv().await {
+                        Some(line) => line,
+                        None => return Ok(()),
+                    },
+                };
+                if let Err(e) = link.send(&line).await {
+                    tracing::warn!("🛰️ Lost SIEM collector {}: {}", self.config.address, e);
+                    pending = Some(line);
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn open(&self) -> std::io::Result<Link> {
+        let address = self.config.address.as_str();
+        match (&self.tls, self.config.transport) {
+            (_, SiemTransport::Udp) => {
+                let local = if address.starts_with('[') { "[::]:0" } else { "0.0.0.0:0" };
+                let socket = UdpSocket::bind(local).await?;
+                socket.connect(address).await?;
+                Ok(Link::Udp(socket))
+            },
+            (Some((connector, name)), SiemTransport::Tls) => {
+                let stream = TcpStream::connect(address).await?;
+                Ok(Link::Tls(Box::new(connector.connect(name.clone(), stream).await?)))
+            },
+            _ => Ok(Link::Tcp(TcpStream::connect(address).await?)),
+        }
+    }
+}
+
+/// Forward mission events as they are logged
+pub async fn follow(forwarder: SiemForwarder, drone: Arc<RwLock<DroneState>>) -> ModuleResult {
+    let mut poll = crate::runtime::interval(Duration::from_secs(1));
+    let mut transitions = drone.read().await.subscribe_threat_transitions();
+    loop {
+        tokio::select! {
+            received = transitions.recv() => match received {
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                _ => continue,
+            },
+            _ = poll.tick() => {
+                let drone = drone.read().await;
+                let next = forwarder.next_sequence.load(Ordering::Relaxed);
+                let start = drone.mission_log.partition_point(|event| event.sequence < next);
+                for event in &drone.mission_log[start..] {
+                    forwarder.forward(event);
+                    forwarder.next_sequence.store(event.sequence + 1, Ordering::Relaxed);
+                }
+            },
+        }
+    }
+}
+
+impl SiemRecord for MissionEvent {
+    const KIND: &'static str = "mission_event";
+
+    fn signature_id(&self) -> String {
+        format!("{:?}", self.event_type)
+    }
+
+    fn name(&self) -> String {
+        self.description.clone()
+    }
+
+    fn severity(&self) -> u8 {
+        let floor = match EventClass::of(self) {
+            EventClass::Panic | EventClass::Emergency | EventClass::FireActivation => 8,
+            EventClass::Security | EventClass::Failsafe => 6,
+            _ => 0,
+        };
+        threat_severity(self.threat_level).max(floor)
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn default_fields() -> Vec<SiemField> {
+        vec![
+            SiemField::new("externalId", "id"),
+            SiemField::labelled("cs1", "threat_level", "threatLevel"),
+            SiemField::labelled("cs2", "response_actions", "responseActions"),
+            SiemField::labelled("cs3", "trace_id", "traceId"),
+            SiemField::labelled("cn1", "sequence", "auditSequence"),
+            SiemField::labelled("cfp1", "position.latitude", "latitude"),
+            SiemField::labelled("cfp2", "position.longitude", "longitude"),
+            SiemField::labelled("cfp3", "position.altitude", "altitude"),
+        ]
+    }
+}
+
+impl SiemRecord for Checkpoint {
+    const KIND: &'static str = "audit_checkpoint";
+
+    fn signature_id(&self) -> String {
+        "AuditCheckpoint".to_string()
+    }
+
+    fn name(&self) -> String {
+        format!("Mission log sealed up to event {}", self.sequence)
+    }
+
+    fn severity(&self) -> u8 {
+        1
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn default_fields() -> Vec<SiemField> {
+        vec![
+            SiemField::labelled("cn1", "sequence", "auditSequence"),
+            SiemField::labelled("cs1", "hash", "auditHash"),
+            SiemField::labelled("cs2", "signature", "auditSignature"),
+        ]
+    }
+}
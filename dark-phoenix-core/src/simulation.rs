@@ -0,0 +1,723 @@
+//! Scripted scenarios in place of hardware (`simulation` feature)
+
+use crate::{Celsius, DroneState, EventType, ThreatLevel};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::future::Future;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+/// Where a ramp step starts when its track has no value yet
+const ROOM_CELSIUS: f32 = 22.0;
+const QUIET_ROOM_DB: f32 = 40.0;
+
+/// One point of a value that ramps linearly to the next
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemperatureKey {
+    pub at_secs: f64,
+    pub celsius: f32,
+}
+
+/// One point of a smoke level (0.0-1.0) that ramps linearly to the next
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmokeKey {
+    pub at_secs: f64,
+    pub level: f32,
+}
+
+/// Ambient sound level (dB SPL), ramping linearly to the next point
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmbientKey {
+    pub at_secs: f64,
+    pub db: f32,
+}
+
+/// Flame sensor output from `at_secs` until the next key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlameKey {
+    pub at_secs: f64,
+    pub detected: bool,
+    #[serde(default)]
+    pub confidence: f32,
+}
+
+/// Something the camera's detector reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedObject {
+    /// e.g. "person", "knife", "vehicle"
+    pub object_type: String,
+    pub confidence: f32,
+    /// How threatening the object is on its own (0.0-1.0)
+    #[serde(default)]
+    pub threat_relevance: f32,
+    /// x, y, width, height as fractions of the frame
+    #[serde(default = "whole_frame")]
+    pub bounding_box: (f32, f32, f32, f32),
+    /// Detection zone it stands in, in place of working it out from the box
+    #[serde(default)]
+    pub zone: Option<String>,
+}
+
+fn whole_frame() -> (f32, f32, f32, f32) {
+    (0.0, 0.0, 1.0, 1.0)
+}
+
+/// Camera detections from `at_secs` until the next key; an empty list clears the scene
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectsKey {
+    pub at_secs: f64,
+    #[serde(default)]
+    pub detections: Vec<SimulatedObject>,
+}
+
+/// A classified sound, e.g. "gunshot", "scream" or "glass_break"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedSound {
+    pub at_secs: f64,
+    pub event: String,
+    pub confidence: f32,
+    /// Clockwise from the drone's heading, when a mic array located it
+    #[serde(default)]
+    pub bearing_deg: Option<f32>,
+    /// How long the microphone keeps reporting it
+    #[serde(default = "default_hold_secs", alias = "hold", deserialize_with = "seconds")]
+    pub hold_secs: f64,
+}
+
+fn default_hold_secs() -> f64 {
+    1.0
+}
+
+/// A value moving to `to` over `over` seconds from wherever it was
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ramp<T> {
+    pub to: T,
+    #[serde(default, deserialize_with = "seconds")]
+    pub over: f64,
+}
+
+/// Something coming into the camera's view, optionally armed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Appearance {
+    /// e.g. "person", "vehicle"
+    pub object: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f32,
+    #[serde(default)]
+    pub threat_relevance: f32,
+    /// A weapon seen with it at this confidence
+    #[serde(default)]
+    pub weapon_confidence: Option<f32>,
+    /// What the weapon is detected as
+    #[serde(default = "default_weapon")]
+    pub weapon: String,
+    #[serde(default)]
+    pub zone: Option<String>,
+    #[serde(default = "whole_frame")]
+    pub bounding_box: (f32, f32, f32, f32),
+}
+
+fn default_confidence() -> f32 {
+    0.9
+}
+
+fn default_weapon() -> String {
+    "gun".to_string()
+}
+
+/// A classified sound starting at its step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoundStep {
+    pub event: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f32,
+    #[serde(default)]
+    pub bearing_deg: Option<f32>,
+    #[serde(default = "default_hold_secs", alias = "hold", deserialize_with = "seconds")]
+    pub hold_secs: f64,
+}
+
+/// Flame sensor output from its step on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlameStep {
+    #[serde(default = "detected")]
+    pub detected: bool,
+    #[serde(default = "default_confidence")]
+    pub confidence: f32,
+}
+
+fn detected() -> bool {
+    true
+}
+
+/// One thing happening at `at`; exactly one of the others is set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Step {
+    #[serde(deserialize_with = "seconds")]
+    pub at: f64,
+    /// °C
+    pub temperature: Option<Ramp<f32>>,
+    /// 0.0-1.0
+    pub smoke: Option<Ramp<f32>>,
+    /// dB SPL
+    pub ambient: Option<Ramp<f32>>,
+    pub flame: Option<FlameStep>,
+    pub appears: Option<Appearance>,
+    /// The object type of something that appeared earlier
+    pub leaves: Option<String>,
+    pub sound: Option<SoundStep>,
+}
+
+impl Step {
+    fn actions(&self) -> usize {
+        [
+            self.temperature.is_some(),
+            self.smoke.is_some(),
+            self.ambient.is_some(),
+            self.flame.is_some(),
+            self.appears.is_some(),
+            self.leaves.is_some(),
+            self.sound.is_some(),
+        ]
+        .into_iter()
+        .filter(|set| *set)
+        .count()
+    }
+}
+
+/// Seconds, from a number or a string such as "90", "1m30s" or "500ms"
+fn seconds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Input {
+        Number(f64),
+        Text(String),
+    }
+    match Input::deserialize(deserializer)? {
+        Input::Number(secs) => Ok(secs),
+        Input::Text(text) => parse_seconds(&text).ok_or_else(|| serde::de::Error::custom(format!("'{}' is not a duration like 10s or 1m30s", text))),
+    }
+}
+
+fn parse_seconds(text: &str) -> Option<f64> {
+    let text = text.trim();
+    if let Ok(secs) = text.parse() {
+        return Some(secs);
+    }
+    let mut total = 0.0;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let split = rest.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+        let (number, tail) = rest.split_at(split);
+        let unit_len = tail.find(|c: char| c.is_ascii_digit() || c == '.').unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        let scale = match unit.trim() {
+            "h" => 3600.0,
+            "m" | "min" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        total += number.parse::<f64>().ok()? * scale;
+        rest = tail.trim_start();
+    }
+    Some(total)
+}
+
+/// What the drone must have done by a point in the scenario
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Expectation {
+    #[serde(alias = "by", deserialize_with = "seconds")]
+    pub by_secs: f64,
+    /// Threat level reached (or passed)
+    #[serde(default)]
+    pub threat_level: Option<ThreatLevel>,
+    /// Mission event logged
+    #[serde(default)]
+    pub event: Option<EventType>,
+}
+
+impl Expectation {
+    fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(level) = self.threat_level {
+            parts.push(format!("threat level {}", level.as_str()));
+        }
+        if let Some(event) = &self.event {
+            parts.push(format!("{:?} event", event));
+        }
+        format!("{} by {}s", parts.join(" and "), self.by_secs)
+    }
+
+    fn is_met(&self, state: &DroneState) -> bool {
+        self.threat_level.is_none_or(|level| state.threat_level() >= level)
+            && self
+                .event
+                .as_ref()
+                .is_none_or(|event| state.mission_log.iter().any(|logged| logged.event_type == *event))
+    }
+}
+
+/// A scripted timeline; every track is optional and keys are in time order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scenario {
+    pub name: String,
+    pub description: String,
+    #[serde(alias = "duration", deserialize_with = "seconds")]
+    pub duration_secs: f64,
+    pub temperature: Vec<TemperatureKey>,
+    pub smoke: Vec<SmokeKey>,
+    pub ambient: Vec<AmbientKey>,
+    pub flame: Vec<FlameKey>,
+    pub objects: Vec<ObjectsKey>,
+    pub audio: Vec<SimulatedSound>,
+    /// Written into the tracks above when the scenario is compiled
+    pub steps: Vec<Step>,
+    pub expect: Vec<Expectation>,
+}
+
+#[derive(Debug, Error)]
+pub enum ScenarioError {
+    #[error("failed to load scenario: {0}")]
+    Load(#[from] config::ConfigError),
+    #[error("invalid scenario:\n  - {}", .0.join("\n  - "))]
+    Invalid(Vec<String>),
+    #[error("scenario '{}' failed:\n  - {}", .0.scenario, .0.unmet().join("\n  - "))]
+    Failed(ScenarioReport),
+}
+
+impl Scenario {
+    /// Read a TOML, YAML, RON or JSON scenario file (by extension)
+    pub fn load(path: &Path) -> Result<Self, ScenarioError> {
+        let scenario: Scenario = config::Config::builder()
+            .add_source(config::File::from(path))
+            .build()?
+            .try_deserialize()?;
+        scenario.compile()
+    }
+
+    /// Write the steps into the tracks and validate the result
+    pub fn compile(mut self) -> Result<Self, ScenarioError> {
+        let mut steps = std::mem::take(&mut self.steps);
+        steps.sort_by(|a, b| a.at.total_cmp(&b.at));
+        let mut problems = Vec::new();
+        if !self.objects.is_empty() && steps.iter().any(|step| step.appears.is_some() || step.leaves.is_some()) {
+            problems.push("objects keys cannot be mixed with appears/leaves steps".to_string());
+        }
+        // What is in view, one group per appearance
+        let mut scene: Vec<(String, Vec<SimulatedObject>)> = Vec::new();
+        for step in steps {
+            let at = step.at;
+            match step.actions() {
+                1 => {},
+                0 => {
+                    problems.push(format!("step at {}s does nothing", at));
+                    continue;
+                },
+                _ => {
+                    problems.push(format!("step at {}s does more than one thing; split it", at));
+                    continue;
+                },
+            }
+            if let Some(change) = step.temperature {
+                let from = ramp(self.temperature.iter().map(|key| (key.at_secs, key.celsius)), at).unwrap_or(ROOM_CELSIUS);
+                for (at_secs, celsius) in ramp_keys(at, from, change) {
+                    self.temperature.push(TemperatureKey { at_secs, celsius });
+                }
+            }
+            if let Some(change) = step.smoke {
+                let from = ramp(self.smoke.iter().map(|key| (key.at_secs, key.level)), at).unwrap_or(0.0);
+                for (at_secs, level) in ramp_keys(at, from, change) {
+                    self.smoke.push(SmokeKey { at_secs, level });
+                }
+            }
+            if let Some(change) = step.ambient {
+                let from = ramp(self.ambient.iter().map(|key| (key.at_secs, key.db)), at).unwrap_or(QUIET_ROOM_DB);
+                for (at_secs, db) in ramp_keys(at, from, change) {
+                    self.ambient.push(AmbientKey { at_secs, db });
+                }
+            }
+            if let Some(flame) = step.flame {
+                self.flame.push(FlameKey {
+                    at_secs: at,
+                    detected: flame.detected,
+                    confidence: flame.confidence,
+                });
+            }
+            if let Some(sound) = step.sound {
+                self.audio.push(SimulatedSound {
+                    at_secs: at,
+                    event: sound.event,
+                    confidence: sound.confidence,
+                    bearing_deg: sound.bearing_deg,
+                    hold_secs: sound.hold_secs,
+                });
+            }
+            if let Some(appearance) = step.appears {
+                let carrier = SimulatedObject {
+                    object_type: appearance.object.clone(),
+                    confidence: appearance.confidence,
+                    threat_relevance: appearance.threat_relevance,
+                    bounding_box: appearance.bounding_box,
+                    zone: appearance.zone.clone(),
+                };
+                let mut group = vec![carrier];
+                if let Some(confidence) = appearance.weapon_confidence {
+                    group.push(SimulatedObject {
+                        object_type: appearance.weapon,
+                        confidence,
+                        threat_relevance: 0.9,
+                        bounding_box: appearance.bounding_box,
+                        zone: appearance.zone,
+                    });
+                }
+                scene.push((appearance.object, group));
+                self.objects.push(ObjectsKey {
+                    at_secs: at,
+                    detections: scene.iter().flat_map(|(_, group)| group.clone()).collect(),
+                });
+            }
+            if let Some(object) = step.leaves {
+                let Some(index) = scene.iter().position(|(label, _)| *label == object) else {
+                    problems.push(format!("step at {}s: no {} is in view to leave", at, object));
+                    continue;
+                };
+                scene.remove(index);
+                self.objects.push(ObjectsKey {
+                    at_secs: at,
+                    detections: scene.iter().flat_map(|(_, group)| group.clone()).collect(),
+                });
+            }
+        }
+        if !problems.is_empty() {
+            return Err(ScenarioError::Invalid(problems));
+        }
+
+        // Steps may land between keys written as tracks
+        self.temperature.sort_by(|a, b| a.at_secs.total_cmp(&b.at_secs));
+        self.smoke.sort_by(|a, b| a.at_secs.total_cmp(&b.at_secs));
+        self.ambient.sort_by(|a, b| a.at_secs.total_cmp(&b.at_secs));
+        self.flame.sort_by(|a, b| a.at_secs.total_cmp(&b.at_secs));
+        self.audio.sort_by(|a, b| a.at_secs.total_cmp(&b.at_secs));
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Check what serde cannot: key order and ranges
+    pub fn validate(&self) -> Result<(), ScenarioError> {
+        let mut problems = Vec::new();
+        if self.duration_secs <= 0.0 {
+            problems.push("duration_secs must be positive".to_string());
+        }
+        let tracks: [(&str, Vec<f64>); 6] = [
+            ("temperature", self.temperature.iter().map(|key| key.at_secs).collect()),
+            ("smoke", self.smoke.iter().map(|key| key.at_secs).collect()),
+            ("ambient", self.ambient.iter().map(|key| key.at_secs).collect()),
+            ("flame", self.flame.iter().map(|key| key.at_secs).collect()),
+            ("objects", self.objects.iter().map(|key| key.at_secs).collect()),
+            ("audio", self.audio.iter().map(|sound| sound.at_secs).collect()),
+        ];
+        for (track, times) in &tracks {
+            if times.iter().any(|at| *at < 0.0) {
+                problems.push(format!("{} has a key before the start", track));
+            }
+            if times.windows(2).any(|pair| pair[1] < pair[0]) {
+                problems.push(format!("{} keys are not in time order", track));
+            }
+        }
+        if self.smoke.iter().any(|key| !(0.0..=1.0).contains(&key.level)) {
+            problems.push("smoke levels must be between 0.0 and 1.0".to_string());
+        }
+        for expectation in &self.expect {
+            if expectation.threat_level.is_none() && expectation.event.is_none() {
+                problems.push(format!("expectation at {}s checks nothing", expectation.by_secs));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ScenarioError::Invalid(problems))
+        }
+    }
+}
+
+/// Keys for a ramp starting at `at` from `from`
+fn ramp_keys(at: f64, from: f32, change: Ramp<f32>) -> [(f64, f32); 2] {
+    [(at, from), (at + change.over.max(0.0), change.to)]
+}
+
+/// Linear interpolation between keys, holding the first and last values
+fn ramp(keys: impl Iterator<Item = (f64, f32)>, t: f64) -> Option<f32> {
+    let mut previous: Option<(f64, f32)> = None;
+    for (at, value) in keys {
+        if at >= t {
+            return Some(match previous {
+                Some((from_at, from)) if at > from_at => {
+                    from + (value - from) * ((t - from_at) / (at - from_at)) as f32
+                },
+                _ => value,
+            });
+        }
+        previous = Some((at, value));
+    }
+    previous.map(|(_, value)| value)
+}
+
+#[derive(Debug)]
+enum Clock {
+    /// Wall time since start, scaled
+    Realtime { started: Instant, speed: f64 },
+    /// Moves only when advanced, for runs that must not depend on timing
+    Stepped { elapsed: Duration },
+}
+
+/// How an expectation turned out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpectationResult {
+    pub expectation: String,
+    /// Scenario time it was first seen met
+    pub met_at_secs: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioReport {
+    pub scenario: String,
+    pub results: Vec<ExpectationResult>,
+}
+
+impl ScenarioReport {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|result| result.met_at_secs.is_some())
+    }
+
+    /// The expectations that were not met
+    pub fn unmet(&self) -> Vec<String> {
+        self.results
+            .iter()
+            .filter(|result| result.met_at_secs.is_none())
+            .map(|result| result.expectation.clone())
+            .collect()
+    }
+}
+
+/// Plays a scenario back to the simulated sensors; cheap to clone into each
+/// module so they all share one clock
+#[derive(Debug, Clone)]
+pub struct ScenarioPlayer {
+    scenario: Arc<Scenario>,
+    clock: Arc<Mutex<Clock>>,
+    /// Scenario time each expectation was first met
+    met: Arc<Mutex<Vec<Option<f64>>>>,
+}
+
+impl ScenarioPlayer {
+    /// Play in real time from now
+    pub fn new(scenario: Scenario) -> Self {
+        Self::with_clock(
+            scenario,
+            Clock::Realtime {
+                started: Instant::now(),
+                speed: 1.0,
+            },
+        )
+    }
+
+    /// Play only as fast as [`ScenarioPlayer::advance`] is called
+    pub fn stepped(scenario: Scenario) -> Self {
+        Self::with_clock(scenario, Clock::Stepped { elapsed: Duration::ZERO })
+    }
+
+    fn with_clock(mut scenario: Scenario, clock: Clock) -> Self {
+        if !scenario.steps.is_empty() {
+            scenario = match scenario.clone().compile() {
+                Ok(compiled) => compiled,
+                Err(e) => {
+                    tracing::warn!("🎬 Scenario '{}' steps ignored: {}", scenario.name, e);
+                    Scenario {
+                        steps: Vec::new(),
+                        ..scenario
+                    }
+                },
+            };
+        }
+        Self {
+            met: Arc::new(Mutex::new(vec![None; scenario.expect.len()])),
+            scenario: Arc::new(scenario),
+            clock: Arc::new(Mutex::new(clock)),
+        }
+    }
+
+    /// Play a real-time scenario faster (or slower) than life, e.g. 10.0 for demos
+    pub fn with_speed(self, speed: f64) -> Self {
+        if let Clock::Realtime { speed: current, .. } = &mut *self.lock_clock() {
+            *current = speed.max(f64::EPSILON);
+        }
+        self
+    }
+
+    pub fn scenario(&self) -> &Scenario {
+        &self.scenario
+    }
+
+    /// Move a stepped scenario on; real-time scenarios ignore this
+    pub fn advance(&self, by: Duration) {
+        if let Clock::Stepped { elapsed } = &mut *self.lock_clock() {
+            *elapsed += by;
+        }
+    }
+
+    /// Scenario time so far (seconds)
+    pub fn elapsed_secs(&self) -> f64 {
+        match &*self.lock_clock() {
+            Clock::Realtime { started, speed } => started.elapsed().as_secs_f64() * speed,
+            Clock::Stepped { elapsed } => elapsed.as_secs_f64(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed_secs() >= self.scenario.duration_secs
+    }
+
+    fn lock_clock(&self) -> std::sync::MutexGuard<'_, Clock> {
+        self.clock.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Absent when the scenario has no temperature track
+    pub fn temperature(&self) -> Option<Celsius> {
+        ramp(self.scenario.temperature.iter().map(|key| (key.at_secs, key.celsius)), self.elapsed_secs()).map(Celsius)
+    }
+
+    pub fn smoke_level(&self) -> Option<f32> {
+        ramp(self.scenario.smoke.iter().map(|key| (key.at_secs, key.level)), self.elapsed_secs())
+    }
+
+    pub fn ambient_db(&self) -> Option<f32> {
+        ramp(self.scenario.ambient.iter().map(|key| (key.at_secs, key.db)), self.elapsed_secs())
+    }
+
+    /// Flame sensor output, absent before the first key
+    pub fn flame(&self) -> Option<FlameKey> {
+        let t = self.elapsed_secs();
+        self.scenario.flame.iter().take_while(|key| key.at_secs <= t).last().cloned()
+    }
+
+    /// What the camera sees now
+    pub fn objects(&self) -> Vec<SimulatedObject> {
+        let t = self.elapsed_secs();
+        self.scenario
+            .objects
+            .iter()
+            .take_while(|key| key.at_secs <= t)
+            .last()
+            .map(|key| key.detections.clone())
+            .unwrap_or_default()
+    }
+
+    /// Sounds the microphone is reporting now
+    pub fn sounds(&self) -> Vec<SimulatedSound> {
+        let t = self.elapsed_secs();
+        self.scenario
+            .audio
+            .iter()
+            .filter(|sound| sound.at_secs <= t && t < sound.at_secs + sound.hold_secs)
+            .cloned()
+            .collect()
+    }
+
+    /// Check the expectations against the drone; call every cycle, as each
+    /// one only counts if it is met before its deadline
+    pub fn observe(&self, state: &DroneState) {
+        let t = self.elapsed_secs();
+        let mut met = self.met.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (expectation, met_at) in self.scenario.expect.iter().zip(met.iter_mut()) {
+            if met_at.is_none() && t <= expectation.by_secs && expectation.is_met(state) {
+                tracing::info!("🎬 Scenario expectation met at {:.1}s: {}", t, expectation.describe());
+                *met_at = Some(t);
+            }
+        }
+    }
+
+    /// Which expectations have been met so far
+    pub fn report(&self) -> ScenarioReport {
+        let met = self.met.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        ScenarioReport {
+            scenario: self.scenario.name.clone(),
+            results: self
+                .scenario
+                .expect
+                .iter()
+                .zip(met.iter())
+                .map(|(expectation, met_at)| ExpectationResult {
+                    expectation: expectation.describe(),
+                    met_at_secs: *met_at,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Plays a scenario on a stepped clock against a drone, for integration
+/// tests: sensors see exactly the scripted values at each step, however long
+/// the stack takes to process them
+pub struct ScenarioRunner {
+    player: ScenarioPlayer,
+    drone: Arc<RwLock<DroneState>>,
+    step: Duration,
+}
+
+impl ScenarioRunner {
+    pub fn new(scenario: Scenario, drone: Arc<RwLock<DroneState>>) -> Self {
+        Self {
+            player: ScenarioPlayer::stepped(scenario),
+            drone,
+            step: Duration::from_millis(100),
+        }
+    }
+
+    /// Scenario time per cycle
+    pub fn with_step(mut self, step: Duration) -> Self {
+        self.step = step.max(Duration::from_millis(1));
+        self
+    }
+
+    /// The player to back the mock sensors with
+    pub fn player(&self) -> ScenarioPlayer {
+        self.player.clone()
+    }
+
+    /// Run `cycle` - one pass of sensing and reacting - at every step until
+    /// the scenario ends, checking expectations after each
+    pub async fn run<F, Fut>(&self, mut cycle: F) -> ScenarioReport
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            cycle().await;
+            self.player.observe(&*self.drone.read().await);
+            if self.player.is_finished() {
+                return self.player.report();
+            }
+            self.player.advance(self.step);
+        }
+    }
+
+    /// Like [`ScenarioRunner::run`], failing unless every expectation was met
+    pub async fn assert<F, Fut>(&self, cycle: F) -> Result<ScenarioReport, ScenarioError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let report = self.run(cycle).await;
+        if report.passed() {
+            Ok(report)
+        } else {
+            Err(ScenarioError::Failed(report))
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! PX4 SITL + Gazebo bridge (`sitl` feature)
+
+use crate::{FlightConfig, FlightController, ModuleResult};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// PX4 SITL sends its onboard MAVLink stream to this port plus the instance
+const PX4_ONBOARD_PORT: u16 = 14540;
+
+/// Which of the autopilot's actuator outputs (1-6) carries each intent;
+/// absent = not published
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActuatorSlots {
+    pub valve: Option<u8>,
+    pub nozzle: Option<u8>,
+    pub nozzle_pan: Option<u8>,
+    pub nozzle_tilt: Option<u8>,
+    pub siren: Option<u8>,
+    pub strobe: Option<u8>,
+}
+
+impl Default for ActuatorSlots {
+    fn default() -> Self {
+        Self {
+            valve: Some(1),
+            nozzle: Some(2),
+            nozzle_pan: Some(3),
+            nozzle_tilt: Some(4),
+            siren: Some(5),
+            strobe: Some(6),
+        }
+    }
+}
+
+impl ActuatorSlots {
+    fn named(&self) -> [(&'static str, Option<u8>); 6] {
+        [
+            ("valve", self.valve),
+            ("nozzle", self.nozzle),
+            ("nozzle_pan", self.nozzle_pan),
+            ("nozzle_tilt", self.nozzle_tilt),
+            ("siren", self.siren),
+            ("strobe", self.strobe),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SitlConfig {
+    /// PX4 SITL instance (`px4 -i`), which decides the port
+    pub instance: u16,
+    /// Flight link settings; the connection is replaced by the instance's
+    /// port unless `connection` is given
+    pub flight: FlightConfig,
+    pub connection: Option<String>,
+    pub slots: ActuatorSlots,
+    /// Nozzle angles that map to full actuator travel (degrees)
+    pub nozzle_pan_range_deg: f32,
+    pub nozzle_tilt_range_deg: f32,
+    /// Changes closer together are sent as one
+    pub publish_interval_ms: u64,
+}
+
+impl Default for SitlConfig {
+    fn default() -> Self {
+        Self {
+            instance: 0,
+            flight: FlightConfig::default(),
+            connection: None,
+            slots: ActuatorSlots::default(),
+            nozzle_pan_range_deg: 90.0,
+            nozzle_tilt_range_deg: 90.0,
+            publish_interval_ms: 100,
+        }
+    }
+}
+
+impl SitlConfig {
+    /// The flight link to the simulated autopilot
+    pub fn flight_config(&self) -> FlightConfig {
+        let connection = match &self.connection {
+            Some(connection) => connection.clone(),
+            None => format!("udpin:0.0.0.0:{}", PX4_ONBOARD_PORT + self.instance),
+        };
+        FlightConfig {
+            connection,
+            // PX4 SITL instance N is MAVLink system N + 1
+            target_system: (self.instance + 1).min(255) as u8,
+            ..self.flight.clone()
+        }
+    }
+
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.instance > 254 {
+            problems.push(format!("sitl.instance {} is out of range", self.instance));
+        }
+        let scheme = self.connection.as_deref().map(|connection| connection.split(':').next().unwrap_or_default());
+        if scheme.is_some_and(|scheme| !["udpin", "udpout", "tcpout"].contains(&scheme)) {
+            problems.push("sitl.connection must start with udpin:, udpout: or tcpout:".to_string());
+        }
+        if self.flight.command_attempts == 0 {
+            problems.push("sitl.flight.command_attempts must be at least 1".to_string());
+        }
+        let slots = self.slots.named();
+        for (index, (name, slot)) in slots.iter().enumerate() {
+            let Some(slot) = slot else { continue };
+            if !(1..=6).contains(slot) {
+                problems.push(format!("sitl.slots.{} is {}; PX4 has actuator outputs 1-6", name, slot));
+            }
+            if let Some((other, _)) = slots[..index].iter().find(|(_, taken)| *taken == Some(*slot)) {
+                problems.push(format!("sitl.slots.{} and sitl.slots.{} are both output {}", other, name, slot));
+            }
+        }
+        if self.nozzle_pan_range_deg <= 0.0 || self.nozzle_tilt_range_deg <= 0.0 {
+            problems.push("sitl nozzle ranges must be positive".to_string());
+        }
+        if self.publish_interval_ms == 0 {
+            problems.push("sitl.publish_interval_ms must be positive".to_string());
+        }
+        problems
+    }
+}
+
+/// Nozzle position, as the modules last set it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NozzleIntent {
+    #[default]
+    Stowed,
+    Deployed,
+    /// Opened for maximum coverage
+    Emergency,
+}
+
+/// What the modules want the actuators to do
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActuatorIntents {
+    pub valve_open: bool,
+    pub nozzle: NozzleIntent,
+    /// Degrees right of and above the nozzle's centre
+    pub nozzle_pan_deg: f32,
+    pub nozzle_tilt_deg: f32,
+    /// 0.0-1.0; 0 is silence
+    pub siren_level: f32,
+    /// Kept for viewers of the intents; no output carries it
+    pub siren_frequency_hz: f32,
+    /// 0.0-1.0; 0 is dark
+    pub strobe_intensity: f32,
+}
+
+impl ActuatorIntents {
+    /// Outputs 1-6 in -1.0..1.0, NaN where nothing is mapped
+    pub fn outputs(&self, config: &SitlConfig) -> [f32; 6] {
+        let level = |value: f32| value.clamp(0.0, 1.0) * 2.0 - 1.0;
+        let angle = |deg: f32, range: f32| (deg / range).clamp(-1.0, 1.0);
+        let slots = &config.slots;
+        let values = [
+            (slots.valve, if self.valve_open { 1.0 } else { -1.0 }),
+            (
+                slots.nozzle,
+                match self.nozzle {
+                    NozzleIntent::Stowed => -1.0,
+                    NozzleIntent::Deployed => 0.0,
+                    NozzleIntent::Emergency => 1.0,
+                },
+            ),
+            (slots.nozzle_pan, angle(self.nozzle_pan_deg, config.nozzle_pan_range_deg)),
+            (slots.nozzle_tilt, angle(self.nozzle_tilt_deg, config.nozzle_tilt_range_deg)),
+            (slots.siren, level(self.siren_level)),
+            (slots.strobe, level(self.strobe_intensity)),
+        ];
+        let mut outputs = [f32::NAN; 6];
+        for (slot, value) in values {
+            if let Some(slot @ 1..=6) = slot {
+                outputs[usize::from(slot) - 1] = value;
+            }
+        }
+        outputs
+    }
+}
+
+/// Where modules record their actuator intents; clones share them
+#[derive(Clone)]
+pub struct SitlActuators {
+    intents: Arc<watch::Sender<ActuatorIntents>>,
+}
+
+impl Default for SitlActuators {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SitlActuators {
+    pub fn new() -> Self {
+        Self {
+            intents: Arc::new(watch::Sender::new(ActuatorIntents::default())),
+        }
+    }
+
+    pub fn intents(&self) -> ActuatorIntents {
+        *self.intents.borrow()
+    }
+
+    /// Change the intents; only an actual change is published
+    pub fn update(&self, change: impl FnOnce(&mut ActuatorIntents)) {
+        self.intents.send_if_modified(|intents| {
+            let before = *intents;
+            change(intents);
+            *intents != before
+        });
+    }
+
+    pub fn watch(&self) -> watch::Receiver<ActuatorIntents> {
+        self.intents.subscribe()
+    }
+}
+
+/// Publish intents to the simulated autopilot as they change, at most once
+/// per `publish_interval_ms`; runs until the actuators are dropped
+pub async fn publish(controller: Arc<FlightController>, actuators: SitlActuators, config: SitlConfig) -> ModuleResult {
+    let mut intents = actuators.watch();
+    intents.mark_changed();
+    let interval = Duration::from_millis(config.publish_interval_ms.max(1));
+    info!("🎮 Publishing actuator intents to PX4 SITL instance {}", config.instance);
+    while intents.changed().await.is_ok() {
+        let outputs = intents.borrow_and_update().outputs(&config);
+        if let Err(e) = controller.set_actuators(outputs).await {
+            // The simulator may still be starting; the next change retries
+            warn!("🎮 Actuator intents not accepted by SITL: {}", e);
+            intents.mark_changed();
+        }
+        crate::runtime::sleep(interval).await;
+    }
+    Ok(())
+}
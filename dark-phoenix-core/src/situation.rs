@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// What kind of incident a threat level was raised for
+///
+/// Shared between detection and response so a misspelt situation is a compile
+/// or parse error rather than a silent fall-through to generic wording.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Situation {
+    /// No specific situation - generic wording for the threat level
+    #[default]
+    Unspecified,
+    Anomaly,
+    Proximity,
+    Aggression,
+    Weapon,
+    WeaponDrawn,
+    GroupThreat,
+    ImminentDanger,
+    PhysicalAttack,
+}
+
+impl Situation {
+    pub const ALL: [Situation; 9] = [
+        Situation::Unspecified,
+        Situation::Anomaly,
+        Situation::Proximity,
+        Situation::Aggression,
+        Situation::Weapon,
+        Situation::WeaponDrawn,
+        Situation::GroupThreat,
+        Situation::ImminentDanger,
+        Situation::PhysicalAttack,
+    ];
+
+    /// Stable snake_case name, as used in configs and message catalog keys
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Situation::Unspecified => "unspecified",
+            Situation::Anomaly => "anomaly",
+            Situation::Proximity => "proximity",
+            Situation::Aggression => "aggression",
+            Situation::Weapon => "weapon",
+            Situation::WeaponDrawn => "weapon_drawn",
+            Situation::GroupThreat => "group_threat",
+            Situation::ImminentDanger => "imminent_danger",
+            Situation::PhysicalAttack => "physical_attack",
+        }
+    }
+}
+
+impl fmt::Display for Situation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+#[error("unknown situation '{0}'")]
+pub struct UnknownSituation(pub String);
+
+impl FromStr for Situation {
+    type Err = UnknownSituation;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Situation::ALL
+            .into_iter()
+            .find(|situation| situation.as_str() == s)
+            .ok_or_else(|| UnknownSituation(s.to_string()))
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{Keyring, VaultError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum StoreError {
+    #[error("event store I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("event could not be encoded: {0}")]
+    Encode(#[from] serde_json::Error),
+    #[error(transparent)]
+    Vault(#[from] VaultError),
+}
+
+/// Append-only, one-JSON-record-per-line event log shared by every module
+///
+/// Each stream (`"threat_assessments"`, `"fire_events"`, ...) is its own
+/// `<stream>.jsonl` file under the store directory. Clones share open files.
+/// With a keyring, every line is sealed before it is written.
+#[derive(Debug, Clone)]
+pub struct EventStore {
+    root: PathBuf,
+    files: Arc<Mutex<HashMap<String, File>>>,
+    keyring: Option<Arc<Keyring>>,
+}
+
+impl EventStore {
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, StoreError> {
+        std::fs::create_dir_all(root.as_ref())?;
+        Ok(Self {
+            root: root.as_ref().to_path_buf(),
+            files: Arc::new(Mutex::new(HashMap::new())),
+            keyring: None,
+        })
+    }
+
+    /// Encrypt records from now on and decrypt them on reading; `None`
+    /// leaves the store in plaintext
+    pub fn with_keyring(mut self, keyring: Option<Arc<Keyring>>) -> Self {
+        self.keyring = keyring;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn stream_path(&self, stream: &str) -> PathBuf {
+        self.root.join(format!("{}.jsonl", stream))
+    }
+
+    pub fn append<T: Serialize>(&self, stream: &str, event: &T) -> Result<(), StoreError> {
+        let mut line = serde_json::to_vec(event)?;
+        if let Some(keyring) = &self.keyring {
+            line = keyring.seal_line(&line).into_bytes();
+        }
+        line.push(b'\n');
+
+        let mut files = self.files.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let file = match files.get_mut(stream) {
+            Some(file) => file,
+            None => {
+                let file = OpenOptions::new().create(true).append(true).open(self.stream_path(stream))?;
+                files.entry(stream.to_string()).or_insert(file)
+            },
+        };
+        // One write per record keeps lines whole even if the process dies mid-append
+        file.write_all(&line)?;
+        Ok(())
+    }
+
+    /// Push every appended record to disk, e.g. before shutting down
+    pub fn flush(&self) -> Result<(), StoreError> {
+        let files = self.files.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for file in files.values() {
+            file.sync_data()?;
+        }
+        Ok(())
+    }
+
+    /// Cut off a final record that was only partly written, so the next
+    /// append starts on a line of its own; the torn bytes are kept in
+    /// `<stream>.torn` and their length returned
+    pub fn repair_tail(&self, stream: &str) -> Result<Option<usize>, StoreError> {
+        let path = self.stream_path(stream);
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if data.is_empty() || data.ends_with(b"\n") {
+            return Ok(None);
+        }
+        let whole = data.iter().rposition(|byte| *byte == b'\n').map_or(0, |newline| newline + 1);
+        let mut torn = OpenOptions::new().create(true).append(true).open(self.root.join(format!("{}.torn", stream)))?;
+        torn.write_all(&data[whole..])?;
+        torn.write_all(b"\n")?;
+        OpenOptions::new().write(true).open(&path)?.set_len(whole as u64)?;
+        Ok(Some(data.len() - whole))
+    }
+
+    /// Every record in a stream, oldest first; lines that no longer parse
+    /// (a torn final write, an old schema) are skipped
+    pub fn read<T: DeserializeOwned>(&self, stream: &str) -> Result<Vec<T>, StoreError> {
+        Ok(self.read_lines(stream)?.iter().filter_map(|line| serde_json::from_str(line).ok()).collect())
+    }
+
+    /// Every line in a stream exactly as serialized (decrypted, if sealed),
+    /// oldest first, for checks that need the bytes rather than the records
+    ///
+    /// With a keyring, a line that is not sealed or fails to open is an
+    /// error, except a torn final write, which is skipped; without one,
+    /// sealed lines are.
+    pub fn read_lines(&self, stream: &str) -> Result<Vec<String>, StoreError> {
+        let text = match std::fs::read_to_string(self.stream_path(stream)) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let torn = !text.ends_with('\n');
+        let count = text.lines().count();
+        let mut lines = Vec::with_capacity(count);
+        for (index, line) in text.lines().enumerate() {
+            let opened = match &self.keyring {
+                None if line.starts_with(crate::vault::SEALED_LINE_PREFIX) => Err(VaultError::Locked),
+                None => Ok(line.to_string()),
+                Some(keyring) => keyring.open_line(line),
+            };
+            match opened {
+                Ok(line) => lines.push(line),
+                Err(VaultError::Corrupt | VaultError::Unsealed) if torn && index + 1 == count => {
+                    tracing::warn!("🗄️ Skipping a torn final write in {}", self.stream_path(stream).display());
+                },
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(lines)
+    }
+
+    /// The newest `count` records in a stream, oldest first
+    pub fn read_recent<T: DeserializeOwned>(&self, stream: &str, count: usize) -> Result<Vec<T>, StoreError> {
+        let mut events = self.read(stream)?;
+        let excess = events.len().saturating_sub(count);
+        events.drain(..excess);
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(keyring: Option<Arc<Keyring>>) -> EventStore {
+        EventStore::open(std::env::temp_dir().join(format!("dark-phoenix-store-{}", uuid::Uuid::new_v4())))
+            .unwrap()
+            .with_keyring(keyring)
+    }
+
+    #[test]
+    fn sealed_stream_reads_back() {
+        let store = store(Some(Arc::new(Keyring::generate())));
+        store.append("events", &serde_json::json!({ "n": 1 })).unwrap();
+        store.append("events", &serde_json::json!({ "n": 2 })).unwrap();
+        let raw = std::fs::read_to_string(store.stream_path("events")).unwrap();
+        assert!(raw.lines().all(|line| line.starts_with(crate::vault::SEALED_LINE_PREFIX)));
+        assert_eq!(store.read_lines("events").unwrap(), vec![r#"{"n":1}"#, r#"{"n":2}"#]);
+    }
+
+    #[test]
+    fn unsealed_line_is_an_error_once_encrypted() {
+        let plain = store(None);
+        plain.append("events", &serde_json::json!({ "n": 1 })).unwrap();
+        let sealed = EventStore::open(plain.root()).unwrap().with_keyring(Some(Arc::new(Keyring::generate())));
+        assert!(matches!(sealed.read_lines("events"), Err(StoreError::Vault(VaultError::Unsealed))));
+    }
+
+    #[test]
+    fn undecryptable_line_is_an_error() {
+        let store = store(Some(Arc::new(Keyring::generate())));
+        store.append("events", &serde_json::json!({ "n": 1 })).unwrap();
+        let other = EventStore::open(store.root()).unwrap().with_keyring(Some(Arc::new(Keyring::generate())));
+        other.append("events", &serde_json::json!({ "n": 2 })).unwrap();
+        assert!(matches!(store.read_lines("events"), Err(StoreError::Vault(VaultError::Corrupt))));
+    }
+
+    #[test]
+    fn torn_final_write_is_skipped() {
+        let store = store(Some(Arc::new(Keyring::generate())));
+        store.append("events", &serde_json::json!({ "n": 1 })).unwrap();
+        let mut file = OpenOptions::new().append(true).open(store.stream_path("events")).unwrap();
+        file.write_all(b"dpenc:RFB2MQAAAA").unwrap();
+        assert_eq!(store.read_lines("events").unwrap(), vec![r#"{"n":1}"#]);
+
+        assert_eq!(store.repair_tail("events").unwrap(), Some(16));
+        let raw = std::fs::read_to_string(store.stream_path("events")).unwrap();
+        assert!(raw.ends_with('\n') && raw.lines().count() == 1);
+    }
+}
@@ -0,0 +1,330 @@
+use crate::runtime::{self, Task};
+use crate::{DroneState, EventType};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{Notify, RwLock};
+use tracing::{error, info, warn};
+
+/// What a supervised module's run loop returns when it stops
+pub type ModuleResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+/// How a crashed module is brought back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartPolicy {
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub backoff_multiplier: f32,
+    /// Crashes within this window count towards flapping, and a run this
+    /// long counts as stable again
+    pub flap_window_secs: u64,
+    /// Crashes within the window that mark the module degraded
+    pub flap_threshold: u32,
+    /// Give up after this many restarts (absent = never)
+    pub max_restarts: Option<u32>,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            backoff_multiplier: 2.0,
+            flap_window_secs: 60,
+            flap_threshold: 3,
+            max_restarts: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleHealth {
+    Running,
+    /// Crashed and waiting out its backoff
+    Restarting,
+    /// Crashing repeatedly; still being restarted
+    Degraded,
+    /// Alive but no longer petting the watchdog
+    Unresponsive,
+    /// Out of restarts
+    Failed,
+    /// Returned cleanly
+    Stopped,
+}
+
+/// Crash history of one supervised module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleReport {
+    pub name: String,
+    pub health: ModuleHealth,
+    pub crashes: u32,
+    pub restarts: u32,
+    pub last_error: Option<String>,
+    pub last_crash: Option<DateTime<Utc>>,
+}
+
+/// Runs each module loop as its own task and restarts it with backoff when
+/// it returns an error or panics, so one faulty module cannot take the
+/// drone down
+pub struct Supervisor {
+    state: Arc<RwLock<DroneState>>,
+    reports: Arc<Mutex<HashMap<String, ModuleReport>>>,
+    restarts: Arc<Mutex<HashMap<String, Arc<Notify>>>>,
+    tasks: Vec<Task<()>>,
+}
+
+/// Forces supervised modules to restart, e.g. when the watchdog finds one hung
+#[derive(Debug, Clone)]
+pub struct ModuleRestarter {
+    restarts: Arc<Mutex<HashMap<String, Arc<Notify>>>>,
+}
+
+impl ModuleRestarter {
+    /// Abort the module's current run and restart it as if it had crashed;
+    /// false if no such module is supervised
+    pub fn restart(&self, module: &str) -> bool {
+        let restarts = self.restarts.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(restart) = restarts.get(module) else { return false };
+        restart.notify_one();
+        true
+    }
+}
+
+impl Supervisor {
+    pub fn new(state: Arc<RwLock<DroneState>>) -> Self {
+        Self {
+            state,
+            reports: Arc::new(Mutex::new(HashMap::new())),
+            restarts: Arc::new(Mutex::new(HashMap::new())),
+            tasks: Vec::new(),
+        }
+    }
+
+    /// Run `start()` under supervision; it is called again for every restart
+    pub fn supervise<F, Fut>(&mut self, name: &str, policy: RestartPolicy, start: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ModuleResult> + Send + 'static,
+    {
+        let restart = Arc::new(Notify::new());
+        self.restarts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(name.to_string(), Arc::clone(&restart));
+        let module = SupervisedModule {
+            name: name.to_string(),
+            policy,
+            restart,
+            state: Arc::clone(&self.state),
+            reports: Arc::clone(&self.reports),
+        };
+        module.set_health(ModuleHealth::Running, None);
+        self.tasks.push(runtime::spawn(module.run(start)));
+        info!("🛡️ Supervising module '{}'", name);
+    }
+
+    pub fn restarter(&self) -> ModuleRestarter {
+        ModuleRestarter {
+            restarts: Arc::clone(&self.restarts),
+        }
+    }
+
+    pub fn reports(&self) -> Vec<ModuleReport> {
+        let mut reports: Vec<ModuleReport> =
+            self.reports.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).values().cloned().collect();
+        reports.sort_by(|a, b| a.name.cmp(&b.name));
+        reports
+    }
+
+    pub fn report(&self, name: &str) -> Option<ModuleReport> {
+        self.reports.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).get(name).cloned()
+    }
+
+    /// Wait until every module has stopped or failed for good
+    pub async fn join(&mut self) {
+        for task in self.tasks.drain(..) {
+            let _ = task.await;
+        }
+    }
+
+    /// Stop every module without restarting it
+    pub async fn stop_all(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+        self.join().await;
+        for report in self.reports.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).values_mut() {
+            if report.health != ModuleHealth::Failed {
+                report.health = ModuleHealth::Stopped;
+            }
+        }
+    }
+}
+
+impl Drop for Supervisor {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+struct SupervisedModule {
+    name: String,
+    policy: RestartPolicy,
+    restart: Arc<Notify>,
+    state: Arc<RwLock<DroneState>>,
+    reports: Arc<Mutex<HashMap<String, ModuleReport>>>,
+}
+
+impl SupervisedModule {
+    async fn run<F, Fut>(self, start: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ModuleResult> + Send + 'static,
+    {
+        let flap_window = Duration::from_secs(self.policy.flap_window_secs);
+        let mut backoff = Duration::from_millis(self.policy.initial_backoff_ms);
+        let mut recent_crashes: Vec<Instant> = Vec::new();
+        let mut restarts = 0;
+
+        loop {
+            let started = Instant::now();
+            // Its own task so a panic is caught; aborted with the supervisor
+            let mut task = AbortOnDrop(runtime::spawn(start()));
+            // A degraded module that survives a whole window has recovered
+            let mut degraded = self.health() == ModuleHealth::Degraded;
+            let mut recovery = runtime::sleep(flap_window);
+            let failure = loop {
+                tokio::select! {
+                    outcome = &mut task.0 => break match outcome {
+                        Ok(Ok(())) => {
+                            info!("Module '{}' stopped", self.name);
+                            self.set_health(ModuleHealth::Stopped, None);
+                            return;
+                        },
+                        Ok(Err(e)) => e.to_string(),
+                        Err(e) if e.is_panic() => panic_message(e.into_panic()),
+                        // Aborted from outside: nothing to restart
+                        Err(_) => return,
+                    },
+                    _ = self.restart.notified() => {
+                        task.0.abort();
+                        break "unresponsive, restarted".to_string();
+                    },
+                    _ = &mut recovery, if degraded => {
+                        degraded = false;
+                        self.set_health(ModuleHealth::Running, None);
+                        self.state.write().await.update_module_health(&self.name, ModuleHealth::Running);
+                        info!("🛡️ Module '{}' stable again", self.name);
+                    },
+                }
+            };
+
+            let now = Instant::now();
+            if now - started >= flap_window {
+                backoff = Duration::from_millis(self.policy.initial_backoff_ms);
+            }
+            recent_crashes.retain(|crash| now - *crash < flap_window);
+            recent_crashes.push(now);
+            let flapping = recent_crashes.len() as u32 >= self.policy.flap_threshold;
+            let exhausted = self.policy.max_restarts.is_some_and(|max| restarts >= max);
+
+            let health = if exhausted {
+                ModuleHealth::Failed
+            } else if flapping {
+                ModuleHealth::Degraded
+            } else {
+                ModuleHealth::Restarting
+            };
+            self.record_crash(health, &failure);
+            error!("💥 Module '{}' crashed: {}", self.name, failure);
+            {
+                let mut state = self.state.write().await;
+                state.log_event(
+                    EventType::SystemMalfunction,
+                    format!("Module '{}' crashed: {}", self.name, failure),
+                    match health {
+                        ModuleHealth::Failed => vec!["Restart limit reached - module offline".to_string()],
+                        _ => vec![format!("Restarting in {:.1}s", backoff.as_secs_f32())],
+                    },
+                );
+                if health != ModuleHealth::Restarting {
+                    state.update_module_health(&self.name, health);
+                }
+            }
+            if exhausted {
+                return;
+            }
+            if flapping {
+                warn!("⚠️ Module '{}' is flapping ({} crashes in {}s)", self.name, recent_crashes.len(), flap_window.as_secs());
+            }
+
+            runtime::sleep(backoff).await;
+            backoff = backoff
+                .mul_f32(self.policy.backoff_multiplier.max(1.0))
+                .min(Duration::from_millis(self.policy.max_backoff_ms));
+            restarts += 1;
+            if let Some(report) = self.reports().get_mut(&self.name) {
+                report.restarts = restarts;
+                if report.health == ModuleHealth::Restarting {
+                    report.health = ModuleHealth::Running;
+                }
+            }
+        }
+    }
+
+    fn reports(&self) -> std::sync::MutexGuard<'_, HashMap<String, ModuleReport>> {
+        self.reports.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn health(&self) -> ModuleHealth {
+        self.reports().get(&self.name).map_or(ModuleHealth::Running, |report| report.health)
+    }
+
+    fn set_health(&self, health: ModuleHealth, error: Option<String>) {
+        let mut reports = self.reports();
+        let report = reports.entry(self.name.clone()).or_insert_with(|| ModuleReport {
+            name: self.name.clone(),
+            health,
+            crashes: 0,
+            restarts: 0,
+            last_error: None,
+            last_crash: None,
+        });
+        report.health = health;
+        if error.is_some() {
+            report.last_error = error;
+        }
+    }
+
+    fn record_crash(&self, health: ModuleHealth, failure: &str) {
+        self.set_health(health, Some(failure.to_string()));
+        if let Some(report) = self.reports().get_mut(&self.name) {
+            report.crashes += 1;
+            report.last_crash = Some(Utc::now());
+        }
+    }
+}
+
+struct AbortOnDrop(Task<ModuleResult>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        format!("panicked: {}", message)
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        format!("panicked: {}", message)
+    } else {
+        "panicked".to_string()
+    }
+}
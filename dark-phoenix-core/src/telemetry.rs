@@ -0,0 +1,145 @@
+use crate::{DroneState, MissionEvent, TransportKind, TransportState, ModuleHealth, Position, Psi, SystemHealth, ThreatLevel, ThreatTransition};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One update for dashboards and ground control, tagged by `type` on the wire
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TelemetryMessage {
+    /// Periodic snapshot of the drone
+    Status {
+        name: String,
+        threat_level: ThreatLevel,
+        position: Position,
+        battery_level: u8,
+        timestamp: DateTime<Utc>,
+    },
+    Health(SystemHealth),
+    Event(MissionEvent),
+    ThreatTransition(ThreatTransition),
+    ModuleHealth {
+        module: String,
+        health: ModuleHealth,
+        timestamp: DateTime<Utc>,
+    },
+    /// Time-weighted risk score from threat detection
+    Risk {
+        score: f32, // 0.0 - 1.0
+        timestamp: DateTime<Utc>,
+    },
+    FireSuppression(FireSuppressionTelemetry),
+    Deterrence(DeterrenceTelemetry),
+    Shield(ShieldTelemetry),
+    Flight(FlightTelemetry),
+    Power(PowerTelemetry),
+    Links(LinkTelemetry),
+}
+
+/// Extinguisher readiness, as reported by the fire suppression module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FireSuppressionTelemetry {
+    pub armed: bool,
+    pub pressure: Psi,
+    pub capacity: f32, // Percentage remaining
+    pub discharging: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Which deterrence outputs are live, as reported by the deterrence module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeterrenceTelemetry {
+    /// Outputs are only logged, not driven
+    pub rehearsal: bool,
+    pub siren_active: bool,
+    pub siren_volume: u8,
+    pub strobe_active: bool,
+    pub voice_active: bool,
+    pub current_message: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Shield position and condition, as reported by the shield module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShieldTelemetry {
+    pub deployed: bool,
+    pub angle_deg: f32, // Off the drone's heading, positive to the right
+    pub integrity: u8,  // 0-100%
+    pub impacts: u32,   // Hits absorbed since the shield was fitted
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Attitude, fix and battery, as reported by the flight controller
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlightTelemetry {
+    pub connected: bool, // Autopilot heartbeats arriving
+    pub armed: bool,
+    pub position: Option<Position>, // Absent without a fix; altitude is above home
+    pub roll_deg: f32,
+    pub pitch_deg: f32,
+    pub yaw_deg: f32,
+    pub gps_lock: bool,
+    pub satellites: u8,
+    pub battery_level: Option<u8>, // 0-100%
+    pub battery_voltage: Option<f32>,
+    #[serde(default)]
+    pub battery_current_a: Option<f32>,
+    /// Body-frame acceleration (m/s²) and angular rate (°/s), when streamed
+    #[serde(default)]
+    pub acceleration_mps2: Option<[f32; 3]>,
+    #[serde(default)]
+    pub angular_rate_dps: Option<[f32; 3]>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Power budget, as kept by the power manager
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerTelemetry {
+    pub battery_level: u8, // 0-100%
+    pub remaining_wh: f64,
+    pub draw_w: f64,
+    pub flight_time_remaining: u32, // Seconds above the landing reserve
+    pub loads: Vec<LoadDraw>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// What one load draws; nothing while shed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadDraw {
+    pub name: String,
+    pub draw_w: f64,
+    pub shed: bool,
+}
+
+/// Which network link telemetry goes over and how each is doing, as kept
+/// by the link manager
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkTelemetry {
+    pub active: Option<String>, // Absent = the system's default route
+    pub links: Vec<LinkQuality>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// One link over the last probes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkQuality {
+    pub name: String,
+    pub kind: TransportKind,
+    pub state: TransportState,
+    pub latency_ms: Option<f64>, // Absent until a probe gets through
+    pub loss: f32,               // 0.0 - 1.0
+}
+
+impl TelemetryMessage {
+    pub fn status(state: &DroneState) -> Self {
+        TelemetryMessage::Status {
+            name: state.name.clone(),
+            threat_level: state.threat_level(),
+            position: state.position.clone(),
+            battery_level: state.system_health.battery_level,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+/// Buffered messages per subscriber before slow receivers start lagging
+pub(crate) const TELEMETRY_BUFFER: usize = 256;
@@ -0,0 +1,798 @@
+//! Thermal cameras (`thermal` feature, Linux)
+
+use crate::Celsius;
+use chrono::{DateTime, Utc};
+use embedded_hal::i2c::I2c;
+use embedded_hal::spi::SpiDevice;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+const KELVIN: f32 = 273.15;
+
+/// One thermal image
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThermalFrame {
+    pub width: usize,
+    pub height: usize,
+    /// °C, row by row from the top left
+    pub temperatures: Vec<f32>,
+    pub horizontal_fov_deg: f32,
+    pub vertical_fov_deg: f32,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A connected patch of pixels within a temperature band
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThermalRegion {
+    pub pixels: usize,
+    /// Centre, as fractions of the frame width and height
+    pub centroid: (f32, f32),
+    /// x, y, width, height as fractions of the frame
+    pub bounding_box: (f32, f32, f32, f32),
+    pub peak: Celsius,
+    pub mean: Celsius,
+}
+
+impl ThermalFrame {
+    pub fn get(&self, x: usize, y: usize) -> Option<Celsius> {
+        if x >= self.width {
+            return None;
+        }
+        self.temperatures.get(y * self.width + x).copied().map(Celsius)
+    }
+
+    /// The hottest pixel, as x, y and temperature
+    pub fn hottest(&self) -> Option<(usize, usize, Celsius)> {
+        let (index, &peak) = self.temperatures.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1))?;
+        Some((index % self.width, index / self.width, Celsius(peak)))
+    }
+
+    /// Median temperature, a fair guess at the background
+    pub fn median(&self) -> Option<Celsius> {
+        if self.temperatures.is_empty() {
+            return None;
+        }
+        let mut sorted = self.temperatures.clone();
+        sorted.sort_by(f32::total_cmp);
+        Some(Celsius(sorted[sorted.len() / 2]))
+    }
+
+    /// Connected regions (4-neighbour) of pixels from `min` to `max`, hottest first
+    pub fn regions(&self, min: Celsius, max: Celsius) -> Vec<ThermalRegion> {
+        let inside = |index: usize| (min.0..=max.0).contains(&self.temperatures[index]);
+        let mut seen = vec![false; self.temperatures.len()];
+        let mut regions = Vec::new();
+        for start in 0..self.temperatures.len().min(self.width * self.height) {
+            if seen[start] || !inside(start) {
+                continue;
+            }
+            seen[start] = true;
+            let mut queue = VecDeque::from([start]);
+            let (mut pixels, mut sum, mut sum_x, mut sum_y, mut peak) = (0usize, 0.0f32, 0.0f32, 0.0f32, f32::MIN);
+            let (mut left, mut top, mut right, mut bottom) = (usize::MAX, usize::MAX, 0, 0);
+            while let Some(index) = queue.pop_front() {
+                let (x, y) = (index % self.width, index / self.width);
+                let temperature = self.temperatures[index];
+                pixels += 1;
+                sum += temperature;
+                sum_x += x as f32 + 0.5;
+                sum_y += y as f32 + 0.5;
+                peak = peak.max(temperature);
+                (left, top, right, bottom) = (left.min(x), top.min(y), right.max(x), bottom.max(y));
+                let neighbours = [
+                    (x > 0).then(|| index - 1),
+                    (x + 1 < self.width).then(|| index + 1),
+                    (y > 0).then(|| index - self.width),
+                    (y + 1 < self.height).then(|| index + self.width),
+                ];
+                for next in neighbours.into_iter().flatten() {
+                    if !seen[next] && inside(next) {
+                        seen[next] = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+            let (width, height) = (self.width as f32, self.height as f32);
+            regions.push(ThermalRegion {
+                pixels,
+                centroid: (sum_x / pixels as f32 / width, sum_y / pixels as f32 / height),
+                bounding_box: (
+                    left as f32 / width,
+                    top as f32 / height,
+                    (right - left + 1) as f32 / width,
+                    (bottom - top + 1) as f32 / height,
+                ),
+                peak: Celsius(peak),
+                mean: Celsius(sum / pixels as f32),
+            });
+        }
+        regions.sort_by(|a, b| b.peak.0.total_cmp(&a.peak.0));
+        regions
+    }
+
+    /// Azimuth (right positive) and elevation (up positive) in degrees from
+    /// the camera axis to a point given as fractions of the frame
+    pub fn direction(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        ((x - 0.5) * self.horizontal_fov_deg, (0.5 - y) * self.vertical_fov_deg)
+    }
+
+    /// Little-endian sensor input: width and height (u16), the two fields of
+    /// view (f32), then the temperatures (f32)
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(12 + self.temperatures.len() * 4);
+        bytes.extend_from_slice(&(self.width as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.height as u16).to_le_bytes());
+        bytes.extend_from_slice(&self.horizontal_fov_deg.to_le_bytes());
+        bytes.extend_from_slice(&self.vertical_fov_deg.to_le_bytes());
+        for temperature in &self.temperatures {
+            bytes.extend_from_slice(&temperature.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Reverse of `encode`, stamped with the current time
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let word = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]) as usize;
+        let float = |at: usize| f32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+        if bytes.len() < 12 {
+            return None;
+        }
+        let (width, height) = (word(0), word(2));
+        if width == 0 || bytes.len() != 12 + width * height * 4 {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            horizontal_fov_deg: float(4),
+            vertical_fov_deg: float(8),
+            temperatures: (12..bytes.len()).step_by(4).map(float).collect(),
+            timestamp: Utc::now(),
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ThermalError {
+    #[error("cannot open {path}: {reason}")]
+    Open { path: String, reason: String },
+    #[error("bus error: {0}")]
+    Bus(String),
+    #[error("no frame within {0:?}")]
+    Timeout(Duration),
+    #[error("calibration data is unusable: {0}")]
+    Calibration(String),
+}
+
+/// A sensor that produces thermal frames; `read_frame` blocks until the next one
+pub trait ThermalCamera: Send {
+    fn read_frame(&mut self) -> Result<ThermalFrame, ThermalError>;
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThermalSensor {
+    Mlx90640,
+    Lepton25,
+    Lepton35,
+}
+
+impl ThermalSensor {
+    /// Horizontal and vertical field of view of the standard lens
+    pub fn default_fov_deg(self) -> (f32, f32) {
+        match self {
+            ThermalSensor::Mlx90640 => (55.0, 35.0),
+            ThermalSensor::Lepton25 => (51.0, 38.0),
+            ThermalSensor::Lepton35 => (57.0, 42.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThermalConfig {
+    pub sensor: ThermalSensor,
+    /// I2C bus of the MLX90640
+    pub i2c_bus: String,
+    pub i2c_address: u8,
+    /// VoSPI device of the Lepton
+    pub spi_device: String,
+    pub spi_hz: u32,
+    /// MLX90640 subpage rate: 0.5, 1, 2, 4, 8, 16, 32 or 64 (two subpages make a frame)
+    pub refresh_hz: f32,
+    /// Emissivity the MLX90640 compensates for; a Lepton uses its own setting
+    pub emissivity: f32,
+    /// Horizontal and vertical field of view in degrees, for a non-standard
+    /// lens such as the 110° MLX90640BAB (absent = the sensor's default)
+    pub fov_deg: Option<(f32, f32)>,
+}
+
+impl Default for ThermalConfig {
+    fn default() -> Self {
+        Self {
+            sensor: ThermalSensor::Mlx90640,
+            i2c_bus: "/dev/i2c-1".to_string(),
+            i2c_address: 0x33,
+            spi_device: "/dev/spidev0.0".to_string(),
+            spi_hz: 16_000_000,
+            refresh_hz: 4.0,
+            emissivity: 0.95,
+            fov_deg: None,
+        }
+    }
+}
+
+impl ThermalConfig {
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.sensor == ThermalSensor::Mlx90640 {
+            if refresh_code(self.refresh_hz).is_none() {
+                problems.push(format!("thermal.refresh_hz {} is not a rate the MLX90640 supports", self.refresh_hz));
+            }
+            if !(0.1..=1.0).contains(&self.emissivity) {
+                problems.push(format!("thermal.emissivity {} must be between 0.1 and 1", self.emissivity));
+            }
+            if !(0x01..=0x7F).contains(&self.i2c_address) {
+                problems.push(format!("thermal.i2c_address {:#04x} is not a 7-bit address", self.i2c_address));
+            }
+        } else if !(1_000_000..=20_000_000).contains(&self.spi_hz) {
+            problems.push(format!("thermal.spi_hz {} is outside the Lepton's 1-20 MHz", self.spi_hz));
+        }
+        if let Some((horizontal, vertical)) = self.fov_deg {
+            if !(horizontal > 0.0 && horizontal < 180.0 && vertical > 0.0 && vertical < 180.0) {
+                problems.push(format!("thermal.fov_deg ({}, {}) must be between 0 and 180", horizontal, vertical));
+            }
+        }
+        problems
+    }
+
+    /// Open the configured sensor
+    pub fn open(&self) -> Result<Box<dyn ThermalCamera>, ThermalError> {
+        let fov = self.fov_deg.unwrap_or(self.sensor.default_fov_deg());
+        match self.sensor {
+            ThermalSensor::Mlx90640 => {
+                let bus = linux_embedded_hal::I2cdev::new(&self.i2c_bus)
+                    .map_err(|e| ThermalError::Open { path: self.i2c_bus.clone(), reason: e.to_string() })?;
+                let mut camera = Mlx90640::new(bus, self.i2c_address)?.with_emissivity(self.emissivity).with_fov(fov);
+                camera.set_refresh_hz(self.refresh_hz)?;
+                Ok(Box::new(camera))
+            },
+            ThermalSensor::Lepton25 | ThermalSensor::Lepton35 => {
+                use linux_embedded_hal::spidev::{SpiModeFlags, SpidevOptions};
+                let open = |reason: String| ThermalError::Open { path: self.spi_device.clone(), reason };
+                let mut device = linux_embedded_hal::SpidevDevice::open(&self.spi_device).map_err(|e| open(e.to_string()))?;
+                let options = SpidevOptions::new().bits_per_word(8).max_speed_hz(self.spi_hz).mode(SpiModeFlags::SPI_MODE_3).build();
+                device.configure(&options).map_err(|e| open(e.to_string()))?;
+                let camera = match self.sensor {
+                    ThermalSensor::Lepton35 => Lepton::lepton35(device),
+                    _ => Lepton::lepton25(device),
+                };
+                Ok(Box::new(camera.with_fov(fov)))
+            },
+        }
+    }
+}
+
+/// The latest frame from a camera read on its own thread
+#[derive(Clone)]
+pub struct ThermalFeed {
+    frames: watch::Receiver<Option<Arc<ThermalFrame>>>,
+}
+
+impl ThermalFeed {
+    /// Read `camera` until every copy of the feed is dropped, backing off
+    /// while it fails
+    pub fn start(mut camera: Box<dyn ThermalCamera>) -> Self {
+        let (tx, frames) = watch::channel(None);
+        std::thread::spawn(move || {
+            let mut backoff = Duration::from_millis(500);
+            let mut failing = false;
+            while !tx.is_closed() {
+                match camera.read_frame() {
+                    Ok(frame) => {
+                        if failing {
+                            info!("🌡️ Thermal camera recovered");
+                            failing = false;
+                        }
+                        backoff = Duration::from_millis(500);
+                        tx.send_replace(Some(Arc::new(frame)));
+                    },
+                    Err(e) => {
+                        if !failing {
+                            warn!("🌡️ Thermal camera failed: {}", e);
+                            failing = true;
+                        }
+                        std::thread::sleep(backoff);
+                        backoff = (backoff * 2).min(Duration::from_secs(10));
+                    },
+                }
+            }
+        });
+        Self { frames }
+    }
+
+    pub fn latest(&self) -> Option<Arc<ThermalFrame>> {
+        self.frames.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Option<Arc<ThermalFrame>>> {
+        self.frames.clone()
+    }
+}
+
+// MLX90640 registers
+const MLX_STATUS: u16 = 0x8000;
+const MLX_CONTROL: u16 = 0x800D;
+const MLX_RAM: u16 = 0x0400;
+const MLX_EEPROM: u16 = 0x2400;
+const MLX_WORDS: usize = 832;
+const MLX_WIDTH: usize = 32;
+const MLX_HEIGHT: usize = 24;
+const MLX_PIXELS: usize = MLX_WIDTH * MLX_HEIGHT;
+
+/// Subpage rates in the order of the control register's refresh codes
+const REFRESH_RATES: [f32; 8] = [0.5, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0];
+
+fn refresh_code(hz: f32) -> Option<u16> {
+    REFRESH_RATES.iter().position(|&rate| rate == hz).map(|code| code as u16)
+}
+
+/// Two's complement of the low `bits` bits
+fn signed(value: u16, bits: u32) -> i32 {
+    let value = value as i32 & ((1 << bits) - 1);
+    if value >= 1 << (bits - 1) {
+        value - (1 << bits)
+    } else {
+        value
+    }
+}
+
+/// Signed 4-bit values packed four to a word, lowest nibble first
+fn nibbles(words: &[u16]) -> Vec<i32> {
+    words.iter().flat_map(|&word| (0..4).map(move |n| signed(word >> (4 * n), 4))).collect()
+}
+
+/// Calibration constants from the MLX90640 EEPROM
+struct MlxCalibration {
+    k_vdd: f32,
+    vdd25: f32,
+    kv_ptat: f32,
+    kt_ptat: f32,
+    v_ptat25: f32,
+    alpha_ptat: f32,
+    gain_ee: f32,
+    tgc: f32,
+    ks_ta: f32,
+    resolution_ee: u16,
+    calibration_mode_ee: u16,
+    ks_to: [f32; 5],
+    ct: [f32; 5],
+    cp_offset: [f32; 2],
+    cp_kta: f32,
+    cp_kv: f32,
+    il_chess: [f32; 3],
+    alpha: Vec<f32>,
+    offset: Vec<f32>,
+    kta: Vec<f32>,
+    kv: Vec<f32>,
+    bad_pixels: Vec<usize>,
+}
+
+impl MlxCalibration {
+    fn extract(ee: &[u16]) -> Result<Self, ThermalError> {
+        let k_vdd = signed(ee[51] >> 8, 8) * 32;
+        if k_vdd == 0 || ee[49] == 0 {
+            return Err(ThermalError::Calibration("blank EEPROM".to_string()));
+        }
+        let vdd25 = (((ee[51] & 0xFF) as i32 - 256) << 5) - 8192;
+
+        let step = ((ee[63] & 0x3000) >> 12) as f32 * 10.0;
+        let ct2 = ((ee[63] & 0xF0) >> 4) as f32 * step;
+        let ct3 = ct2 + ((ee[63] & 0xF00) >> 8) as f32 * step;
+        let ks_to_scale = (1u32 << ((ee[63] & 0xF) + 8)) as f32;
+        let ks_to = [
+            signed(ee[61], 8) as f32 / ks_to_scale,
+            signed(ee[61] >> 8, 8) as f32 / ks_to_scale,
+            signed(ee[62], 8) as f32 / ks_to_scale,
+            signed(ee[62] >> 8, 8) as f32 / ks_to_scale,
+            -0.0002,
+        ];
+
+        let kta_scale1 = 2f32.powi((((ee[56] & 0xF0) >> 4) + 8) as i32);
+        let kta_scale2 = 2f32.powi((ee[56] & 0xF) as i32);
+        let kv_scale = 2f32.powi(((ee[56] & 0xF00) >> 8) as i32);
+
+        let cp_alpha_scale = 2f32.powi((((ee[32] & 0xF000) >> 12) + 27) as i32);
+        let cp_alpha0 = signed(ee[57], 10) as f32 / cp_alpha_scale;
+        let cp_alpha = [cp_alpha0, (1.0 + signed(ee[57] >> 10, 6) as f32 / 128.0) * cp_alpha0];
+        let cp_offset0 = signed(ee[58], 10);
+        let cp_offset = [cp_offset0 as f32, (signed(ee[58] >> 10, 6) + cp_offset0) as f32];
+        let tgc = signed(ee[60], 8) as f32 / 32.0;
+
+        let acc_rem_scale = ee[32] & 0xF;
+        let acc_column_scale = (ee[32] >> 4) & 0xF;
+        let acc_row_scale = (ee[32] >> 8) & 0xF;
+        let alpha_scale = 2f32.powi((((ee[32] >> 12) & 0xF) + 30) as i32);
+        let alpha_ref = ee[33] as i32;
+        let acc_row = nibbles(&ee[34..40]);
+        let acc_column = nibbles(&ee[40..48]);
+
+        let occ_rem_scale = ee[16] & 0xF;
+        let occ_column_scale = (ee[16] >> 4) & 0xF;
+        let occ_row_scale = (ee[16] >> 8) & 0xF;
+        let offset_ref = ee[17] as i16 as i32;
+        let occ_row = nibbles(&ee[18..24]);
+        let occ_column = nibbles(&ee[24..32]);
+
+        let kta_rc = [signed(ee[54] >> 8, 8), signed(ee[55] >> 8, 8), signed(ee[54], 8), signed(ee[55], 8)];
+        let kv_t = [signed(ee[52] >> 12, 4), signed(ee[52] >> 4, 4), signed(ee[52] >> 8, 4), signed(ee[52], 4)];
+
+        let mut calibration = Self {
+            k_vdd: k_vdd as f32,
+            vdd25: vdd25 as f32,
+            kv_ptat: signed(ee[50] >> 10, 6) as f32 / 4096.0,
+            kt_ptat: signed(ee[50], 10) as f32 / 8.0,
+            v_ptat25: ee[49] as i16 as f32,
+            alpha_ptat: ((ee[16] & 0xF000) >> 12) as f32 / 4.0 + 8.0,
+            gain_ee: ee[48] as i16 as f32,
+            tgc,
+            ks_ta: signed(ee[60] >> 8, 8) as f32 / 8192.0,
+            resolution_ee: (ee[56] & 0x3000) >> 12,
+            calibration_mode_ee: ((ee[10] & 0x0800) >> 4) ^ 0x80,
+            ks_to,
+            ct: [-40.0, 0.0, ct2, ct3, 400.0],
+            cp_offset,
+            cp_kta: signed(ee[59], 8) as f32 / kta_scale1,
+            cp_kv: signed(ee[59] >> 8, 8) as f32 / kv_scale,
+            il_chess: [
+                signed(ee[53], 6) as f32 / 16.0,
+                signed(ee[53] >> 6, 5) as f32 / 2.0,
+                signed(ee[53] >> 11, 5) as f32 / 8.0,
+            ],
+            alpha: Vec::with_capacity(MLX_PIXELS),
+            offset: Vec::with_capacity(MLX_PIXELS),
+            kta: Vec::with_capacity(MLX_PIXELS),
+            kv: Vec::with_capacity(MLX_PIXELS),
+            bad_pixels: Vec::new(),
+        };
+
+        for p in 0..MLX_PIXELS {
+            let (row, column) = (p / MLX_WIDTH, p % MLX_WIDTH);
+            let word = ee[64 + p];
+            if word == 0 || word & 1 != 0 {
+                calibration.bad_pixels.push(p);
+            }
+            let alpha = (signed(word >> 4, 6) << acc_rem_scale)
+                + alpha_ref
+                + (acc_row[row] << acc_row_scale)
+                + (acc_column[column] << acc_column_scale);
+            calibration.alpha.push(alpha as f32 / alpha_scale - tgc * (cp_alpha[0] + cp_alpha[1]) / 2.0);
+            let offset = (signed(word >> 10, 6) << occ_rem_scale)
+                + offset_ref
+                + (occ_row[row] << occ_row_scale)
+                + (occ_column[column] << occ_column_scale);
+            calibration.offset.push(offset as f32);
+            let split = 2 * (p / 32 - (p / 64) * 2) + p % 2;
+            calibration.kta.push((kta_rc[split] as f32 + signed(word >> 1, 3) as f32 * kta_scale2) / kta_scale1);
+            calibration.kv.push(kv_t[split] as f32 / kv_scale);
+        }
+        if calibration.bad_pixels.len() > 4 {
+            return Err(ThermalError::Calibration(format!("{} defective pixels", calibration.bad_pixels.len())));
+        }
+        Ok(calibration)
+    }
+
+    /// Supply voltage and ambient (die) temperature for a subpage
+    fn vdd_ta(&self, frame: &[u16]) -> (f32, f32) {
+        let resolution_ram = (frame[832] & 0x0C00) >> 10;
+        let correction = 2f32.powi(self.resolution_ee as i32) / 2f32.powi(resolution_ram as i32);
+        let vdd = (correction * frame[810] as i16 as f32 - self.vdd25) / self.k_vdd + 3.3;
+        let ptat = frame[800] as i16 as f32;
+        let ptat_art = ptat / (ptat * self.alpha_ptat + frame[768] as i16 as f32) * 2f32.powi(18);
+        let ta = (ptat_art / (1.0 + self.kv_ptat * (vdd - 3.3)) - self.v_ptat25) / self.kt_ptat + 25.0;
+        (vdd, ta)
+    }
+
+    /// Fill in the pixels of one subpage (`frame[833]`)
+    fn temperatures(&self, frame: &[u16], emissivity: f32, out: &mut [f32]) {
+        let subpage = frame[833] as usize;
+        let (vdd, ta) = self.vdd_ta(frame);
+        let ta4 = (ta + KELVIN).powi(4);
+        // Reflected temperature, per the Melexis recommendation for an open sensor
+        let tr4 = (ta - 8.0 + KELVIN).powi(4);
+        let ta_tr = tr4 - (tr4 - ta4) / emissivity;
+        let ks_to = &self.ks_to;
+        let ct = &self.ct;
+        let alpha_corr_r2 = 1.0 + ks_to[1] * ct[2];
+        let alpha_corr_r = [
+            1.0 / (1.0 + ks_to[0] * 40.0),
+            1.0,
+            alpha_corr_r2,
+            alpha_corr_r2 * (1.0 + ks_to[2] * (ct[3] - ct[2])),
+        ];
+        let gain = self.gain_ee / frame[778] as i16 as f32;
+        let mode = (frame[832] & 0x1000) >> 5;
+        let chess_corrected = mode != self.calibration_mode_ee;
+        let cp_drift = (1.0 + self.cp_kta * (ta - 25.0)) * (1.0 + self.cp_kv * (vdd - 3.3));
+        let cp_offset1 = self.cp_offset[1] + if chess_corrected { self.il_chess[0] } else { 0.0 };
+        let ir_cp = [
+            frame[776] as i16 as f32 * gain - self.cp_offset[0] * cp_drift,
+            frame[808] as i16 as f32 * gain - cp_offset1 * cp_drift,
+        ];
+        for (p, out) in out.iter_mut().enumerate().take(MLX_PIXELS) {
+            let il_pattern = (p / 32 - (p / 64) * 2) as i32;
+            let chess_pattern = il_pattern ^ (p % 2) as i32;
+            let conversion_pattern = ((p + 2) / 4) as i32 - p.div_ceil(4) as i32 + ((p + 1) / 4) as i32 - (p / 4) as i32;
+            let conversion_pattern = conversion_pattern * (1 - 2 * il_pattern);
+            let pattern = if mode == 0 { il_pattern } else { chess_pattern };
+            if pattern as usize != subpage {
+                continue;
+            }
+            let mut ir = frame[p] as i16 as f32 * gain;
+            ir -= self.offset[p] * (1.0 + self.kta[p] * (ta - 25.0)) * (1.0 + self.kv[p] * (vdd - 3.3));
+            if chess_corrected {
+                ir += self.il_chess[2] * (2 * il_pattern - 1) as f32 - self.il_chess[1] * conversion_pattern as f32;
+            }
+            ir -= self.tgc * ir_cp[subpage];
+            ir /= emissivity;
+            let alpha = self.alpha[p] * (1.0 + self.ks_ta * (ta - 25.0));
+            let sx = (alpha.powi(3) * (ir + alpha * ta_tr)).sqrt().sqrt() * ks_to[1];
+            let to = (ir / (alpha * (1.0 - ks_to[1] * KELVIN) + sx) + ta_tr).sqrt().sqrt() - KELVIN;
+            let range = if to < ct[1] {
+                0
+            } else if to < ct[2] {
+                1
+            } else if to < ct[3] {
+                2
+            } else {
+                3
+            };
+            *out = (ir / (alpha * alpha_corr_r[range] * (1.0 + ks_to[range] * (to - ct[range]))) + ta_tr).sqrt().sqrt() - KELVIN;
+        }
+    }
+
+    /// Replace defective pixels with the mean of their good neighbours
+    fn patch(&self, temperatures: &mut [f32]) {
+        for &p in &self.bad_pixels {
+            let (x, y) = (p % MLX_WIDTH, p / MLX_WIDTH);
+            let neighbours = [
+                (x > 0).then(|| p - 1),
+                (x + 1 < MLX_WIDTH).then(|| p + 1),
+                (y > 0).then(|| p - MLX_WIDTH),
+                (y + 1 < MLX_HEIGHT).then(|| p + MLX_WIDTH),
+            ];
+            let good: Vec<f32> =
+                neighbours.into_iter().flatten().filter(|n| !self.bad_pixels.contains(n)).map(|n| temperatures[n]).collect();
+            if !good.is_empty() {
+                temperatures[p] = good.iter().sum::<f32>() / good.len() as f32;
+            }
+        }
+    }
+}
+
+/// Melexis MLX90640 32x24 thermopile array on I2C
+pub struct Mlx90640<I2C> {
+    bus: I2C,
+    address: u8,
+    calibration: MlxCalibration,
+    emissivity: f32,
+    fov: (f32, f32),
+    refresh_hz: f32,
+}
+
+impl<I2C: I2c> Mlx90640<I2C> {
+    /// Read the calibration from the sensor's EEPROM
+    pub fn new(mut bus: I2C, address: u8) -> Result<Self, ThermalError> {
+        let mut eeprom = vec![0u16; MLX_WORDS];
+        read_words(&mut bus, address, MLX_EEPROM, &mut eeprom)?;
+        let calibration = MlxCalibration::extract(&eeprom)?;
+        let mut control = [0u16];
+        read_words(&mut bus, address, MLX_CONTROL, &mut control)?;
+        let refresh_hz = REFRESH_RATES[((control[0] >> 7) & 0x7) as usize];
+        info!("🌡️ MLX90640 at {:#04x}: {} Hz, {} defective pixels", address, refresh_hz, calibration.bad_pixels.len());
+        Ok(Self {
+            bus,
+            address,
+            calibration,
+            emissivity: 0.95,
+            fov: ThermalSensor::Mlx90640.default_fov_deg(),
+            refresh_hz,
+        })
+    }
+
+    pub fn with_emissivity(mut self, emissivity: f32) -> Self {
+        self.emissivity = emissivity;
+        self
+    }
+
+    pub fn with_fov(mut self, fov_deg: (f32, f32)) -> Self {
+        self.fov = fov_deg;
+        self
+    }
+
+    /// Set the subpage rate (0.5-64 Hz in powers of two)
+    pub fn set_refresh_hz(&mut self, hz: f32) -> Result<(), ThermalError> {
+        let code = refresh_code(hz).ok_or_else(|| ThermalError::Bus(format!("unsupported refresh rate {} Hz", hz)))?;
+        let mut control = [0u16];
+        read_words(&mut self.bus, self.address, MLX_CONTROL, &mut control)?;
+        write_word(&mut self.bus, self.address, MLX_CONTROL, (control[0] & 0xFC7F) | (code << 7))?;
+        self.refresh_hz = hz;
+        Ok(())
+    }
+
+    /// Wait for the next subpage and read it with the control and status words
+    fn read_subpage(&mut self, frame: &mut [u16; MLX_WORDS + 2]) -> Result<(), ThermalError> {
+        let timeout = Duration::from_secs_f32(2.0 / self.refresh_hz) + Duration::from_millis(500);
+        let deadline = Instant::now() + timeout;
+        let mut status = [0u16];
+        loop {
+            read_words(&mut self.bus, self.address, MLX_STATUS, &mut status)?;
+            if status[0] & 0x0008 != 0 {
+                break;
+            }
+            if Instant::now() > deadline {
+                return Err(ThermalError::Timeout(timeout));
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        // Read until no new subpage landed while reading, as the reference driver does
+        for _ in 0..5 {
+            write_word(&mut self.bus, self.address, MLX_STATUS, 0x0030)?;
+            read_words(&mut self.bus, self.address, MLX_RAM, &mut frame[..MLX_WORDS])?;
+            read_words(&mut self.bus, self.address, MLX_STATUS, &mut status)?;
+            if status[0] & 0x0008 == 0 {
+                let mut control = [0u16];
+                read_words(&mut self.bus, self.address, MLX_CONTROL, &mut control)?;
+                frame[MLX_WORDS] = control[0];
+                frame[MLX_WORDS + 1] = status[0] & 0x0001;
+                return Ok(());
+            }
+        }
+        Err(ThermalError::Bus("subpage overwritten while reading".to_string()))
+    }
+}
+
+impl<I2C: I2c + Send> ThermalCamera for Mlx90640<I2C> {
+    fn read_frame(&mut self) -> Result<ThermalFrame, ThermalError> {
+        let mut frame = [0u16; MLX_WORDS + 2];
+        let mut temperatures = vec![f32::NAN; MLX_PIXELS];
+        let mut subpages = [false; 2];
+        while !(subpages[0] && subpages[1]) {
+            self.read_subpage(&mut frame)?;
+            self.calibration.temperatures(&frame, self.emissivity, &mut temperatures);
+            subpages[frame[MLX_WORDS + 1] as usize] = true;
+        }
+        self.calibration.patch(&mut temperatures);
+        Ok(ThermalFrame {
+            width: MLX_WIDTH,
+            height: MLX_HEIGHT,
+            temperatures,
+            horizontal_fov_deg: self.fov.0,
+            vertical_fov_deg: self.fov.1,
+            timestamp: Utc::now(),
+        })
+    }
+}
+
+fn read_words<I2C: I2c>(bus: &mut I2C, address: u8, start: u16, words: &mut [u16]) -> Result<(), ThermalError> {
+    let mut bytes = [0u8; 256];
+    for (chunk, words) in words.chunks_mut(128).enumerate() {
+        let register = start + (chunk * 128) as u16;
+        let bytes = &mut bytes[..words.len() * 2];
+        bus.write_read(address, &register.to_be_bytes(), bytes).map_err(|e| ThermalError::Bus(format!("{:?}", e)))?;
+        for (word, pair) in words.iter_mut().zip(bytes.chunks_exact(2)) {
+            *word = u16::from_be_bytes([pair[0], pair[1]]);
+        }
+    }
+    Ok(())
+}
+
+fn write_word<I2C: I2c>(bus: &mut I2C, address: u8, register: u16, value: u16) -> Result<(), ThermalError> {
+    let [register_hi, register_lo] = register.to_be_bytes();
+    let [value_hi, value_lo] = value.to_be_bytes();
+    bus.write(address, &[register_hi, register_lo, value_hi, value_lo]).map_err(|e| ThermalError::Bus(format!("{:?}", e)))
+}
+
+// VoSPI packets: two ID bytes, two CRC bytes and a line of 80 pixels
+const VOSPI_PACKET: usize = 164;
+const VOSPI_LINE: usize = 80;
+const VOSPI_PACKETS: usize = 60;
+
+/// FLIR Lepton 2.5 or 3.5 on VoSPI, in TLinear mode
+pub struct Lepton<SPI> {
+    spi: SPI,
+    segments: usize,
+    fov: (f32, f32),
+}
+
+impl<SPI: SpiDevice> Lepton<SPI> {
+    /// 80x60, one segment per frame
+    pub fn lepton25(spi: SPI) -> Self {
+        Self { spi, segments: 1, fov: ThermalSensor::Lepton25.default_fov_deg() }
+    }
+
+    /// 160x120, four segments per frame
+    pub fn lepton35(spi: SPI) -> Self {
+        Self { spi, segments: 4, fov: ThermalSensor::Lepton35.default_fov_deg() }
+    }
+
+    pub fn with_fov(mut self, fov_deg: (f32, f32)) -> Self {
+        self.fov = fov_deg;
+        self
+    }
+
+    /// Read the 60 packets of a segment; returns its number (1-4 on a
+    /// Lepton 3.5, where 0 marks a segment to throw away)
+    fn read_segment(&mut self, lines: &mut [u16], deadline: Instant) -> Result<usize, ThermalError> {
+        let mut packet = [0u8; VOSPI_PACKET];
+        let mut expected = 0;
+        let mut segment = 1;
+        while expected < VOSPI_PACKETS {
+            if Instant::now() > deadline {
+                return Err(ThermalError::Timeout(Duration::from_secs(2)));
+            }
+            self.spi.read(&mut packet).map_err(|e| ThermalError::Bus(format!("{:?}", e)))?;
+            let id = u16::from_be_bytes([packet[0], packet[1]]);
+            if id & 0x0F00 == 0x0F00 {
+                continue;
+            }
+            let number = (id & 0x0FFF) as usize;
+            if number != expected {
+                // Lost sync mid-segment: holding chip select idle past a frame
+                // period restarts the stream; either way wait for a packet 0
+                if expected != 0 {
+                    std::thread::sleep(Duration::from_millis(200));
+                    expected = 0;
+                }
+                continue;
+            }
+            if self.segments > 1 && number == 20 {
+                segment = ((id >> 12) & 0x7) as usize;
+            }
+            for (pixel, pair) in lines[number * VOSPI_LINE..][..VOSPI_LINE].iter_mut().zip(packet[4..].chunks_exact(2)) {
+                *pixel = u16::from_be_bytes([pair[0], pair[1]]);
+            }
+            expected += 1;
+        }
+        Ok(segment)
+    }
+}
+
+impl<SPI: SpiDevice + Send> ThermalCamera for Lepton<SPI> {
+    fn read_frame(&mut self) -> Result<ThermalFrame, ThermalError> {
+        let deadline = Instant::now() + Duration::from_secs(2);
+        let mut lines = vec![0u16; VOSPI_PACKETS * VOSPI_LINE];
+        let mut image = vec![0u16; VOSPI_PACKETS * VOSPI_LINE * self.segments];
+        let mut next = 1;
+        while next <= self.segments {
+            let segment = self.read_segment(&mut lines, deadline)?;
+            if segment == 0 {
+                continue;
+            }
+            if segment != next {
+                next = 1;
+                if segment != 1 {
+                    continue;
+                }
+            }
+            // A segment holds 60 half-lines on a Lepton 3.5, whole lines on a 2.5
+            let start = (segment - 1) * lines.len();
+            image[start..start + lines.len()].copy_from_slice(&lines);
+            next += 1;
+        }
+        let (width, height) = if self.segments > 1 { (2 * VOSPI_LINE, 2 * VOSPI_PACKETS) } else { (VOSPI_LINE, VOSPI_PACKETS) };
+        Ok(ThermalFrame {
+            width,
+            height,
+            // TLinear reports centikelvin
+            temperatures: image.iter().map(|&value| value as f32 / 100.0 - KELVIN).collect(),
+            horizontal_fov_deg: self.fov.0,
+            vertical_fov_deg: self.fov.1,
+            timestamp: Utc::now(),
+        })
+    }
+}
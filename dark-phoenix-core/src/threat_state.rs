@@ -0,0 +1,346 @@
+use crate::{DecisionTrace, RiskTrend, ThreatLevel};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::broadcast;
+
+/// Rules every threat level change must satisfy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransitionRules {
+    /// Assessments must stay below the current level this long before each
+    /// single-step de-escalation
+    pub quiet_period_secs: u64,
+    /// Omega (lethal force) only with a live operator authorization
+    pub omega_requires_authorization: bool,
+    /// How long an Omega authorization stays valid
+    pub authorization_ttl_secs: u64,
+}
+
+impl Default for TransitionRules {
+    fn default() -> Self {
+        Self {
+            quiet_period_secs: 30,
+            omega_requires_authorization: true,
+            authorization_ttl_secs: 300,
+        }
+    }
+}
+
+/// A threat level change, as delivered to subscribed modules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreatTransition {
+    pub from: ThreatLevel,
+    pub to: ThreatLevel,
+    pub reason: String,
+    pub timestamp: DateTime<Utc>,
+    /// What led to it, ending with the transition rule applied
+    #[serde(default)]
+    pub trace: DecisionTrace,
+}
+
+impl ThreatTransition {
+    pub fn is_escalation(&self) -> bool {
+        self.to > self.from
+    }
+}
+
+/// Operator sign-off allowing Omega
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OmegaAuthorization {
+    pub authorized_by: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum TransitionError {
+    #[error("Omega requires an unexpired operator authorization")]
+    OmegaNotAuthorized,
+    #[error("de-escalation needs {remaining_secs}s more of quiet")]
+    QuietPeriodNotElapsed { remaining_secs: i64 },
+    #[error("de-escalation is one level at a time ({from:?} can only drop to {allowed:?})")]
+    StepTooLarge { from: ThreatLevel, allowed: ThreatLevel },
+    #[error("already at {0:?}")]
+    Unchanged(ThreatLevel),
+}
+
+/// Buffered transitions per subscriber
+const TRANSITION_BUFFER: usize = 32;
+
+fn transition_channel() -> broadcast::Sender<ThreatTransition> {
+    broadcast::channel(TRANSITION_BUFFER).0
+}
+
+/// The drone's threat level and the only way to change it
+///
+/// Escalation is immediate (Omega only while authorized); de-escalation is
+/// one level per `quiet_period_secs` of assessments below the current level.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreatStateMachine {
+    level: ThreatLevel,
+    entered_at: DateTime<Utc>,
+    /// When assessments first dropped below the current level
+    quiet_since: Option<DateTime<Utc>>,
+    authorization: Option<OmegaAuthorization>,
+    pub rules: TransitionRules,
+    #[serde(skip, default = "transition_channel")]
+    transitions: broadcast::Sender<ThreatTransition>,
+}
+
+impl Default for ThreatStateMachine {
+    fn default() -> Self {
+        Self::new(TransitionRules::default())
+    }
+}
+
+impl ThreatStateMachine {
+    pub fn new(rules: TransitionRules) -> Self {
+        Self {
+            level: ThreatLevel::Green,
+            entered_at: Utc::now(),
+            quiet_since: None,
+            authorization: None,
+            rules,
+            transitions: transition_channel(),
+        }
+    }
+
+    pub fn level(&self) -> ThreatLevel {
+        self.level
+    }
+
+    pub fn entered_at(&self) -> DateTime<Utc> {
+        self.entered_at
+    }
+
+    /// Every transition from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ThreatTransition> {
+        self.transitions.subscribe()
+    }
+
+    pub fn omega_authorized(&self, now: DateTime<Utc>) -> bool {
+        !self.rules.omega_requires_authorization
+            || self.authorization.as_ref().is_some_and(|authorization| authorization.expires_at > now)
+    }
+
+    pub fn authorize_omega(&mut self, authorized_by: &str, now: DateTime<Utc>) -> &OmegaAuthorization {
+        self.authorization.insert(OmegaAuthorization {
+            authorized_by: authorized_by.to_string(),
+            expires_at: now + Duration::seconds(self.rules.authorization_ttl_secs as i64),
+        })
+    }
+
+    /// Withdraw authorization, dropping out of Omega at once
+    pub fn revoke_omega(&mut self, now: DateTime<Utc>) -> Option<ThreatTransition> {
+        self.authorization = None;
+        (self.level == ThreatLevel::Omega).then(|| {
+            let mut trace = DecisionTrace::new();
+            trace.rule("omega authorization", ThreatLevel::Red, "revoked; Omega ends at once");
+            self.transition(ThreatLevel::Red, "Omega authorization revoked", trace, now)
+        })
+    }
+
+    /// Follow a fresh assessment: escalate at once (holding at Red without
+    /// Omega authorization), and step down one level once assessments have
+    /// stayed lower for the quiet period while risk is not rising. `cause` is
+    /// the assessment's own trace, which a transition carries ahead of its rule.
+    pub fn observe(
+        &mut self,
+        assessed: ThreatLevel,
+        trend: RiskTrend,
+        reason: &str,
+        cause: &DecisionTrace,
+        now: DateTime<Utc>,
+    ) -> Option<ThreatTransition> {
+        if self.level == ThreatLevel::Omega && !self.omega_authorized(now) {
+            let mut trace = DecisionTrace::new();
+            trace.rule("omega authorization", ThreatLevel::Red, "expired; Omega ends at once");
+            return Some(self.transition(ThreatLevel::Red, "Omega authorization expired", trace, now));
+        }
+        let mut trace = DecisionTrace::new();
+        let allowed = if assessed == ThreatLevel::Omega && !self.omega_authorized(now) {
+            trace.rule("omega authorization", ThreatLevel::Red, "Omega needs an operator authorization; held at Red");
+            ThreatLevel::Red
+        } else {
+            assessed
+        };
+
+        if allowed > self.level {
+            trace.rule("escalation", allowed, format!("up from {} at once", self.level.as_str()));
+            return Some(self.transition(allowed, reason, trace.after(cause), now));
+        }
+        if allowed == self.level {
+            self.quiet_since = None;
+            return None;
+        }
+
+        let quiet_since = *self.quiet_since.get_or_insert(now);
+        let quiet_for = now - quiet_since;
+        if trend == RiskTrend::Rising || quiet_for < self.quiet_period() {
+            return None;
+        }
+        let target = allowed.max(self.level.step_down());
+        trace.threshold(
+            "quiet period",
+            target,
+            quiet_for.num_seconds() as f32,
+            self.rules.quiet_period_secs as f32,
+            format!(
+                "below {} for {}s of a {}s quiet period with risk not rising; one level down",
+                self.level.as_str(),
+                quiet_for.num_seconds(),
+                self.rules.quiet_period_secs
+            ),
+        );
+        let transition = self.transition(target, reason, trace.after(cause), now);
+        // Each further step needs its own quiet period
+        if target > allowed {
+            self.quiet_since = Some(now);
+        }
+        Some(transition)
+    }
+
+    /// Move to `level` on request (operator command, module alarm), under the
+    /// same rules as `observe`
+    pub fn request(&mut self, level: ThreatLevel, reason: &str, now: DateTime<Utc>) -> Result<ThreatTransition, TransitionError> {
+        if level == self.level {
+            return Err(TransitionError::Unchanged(level));
+        }
+        if level == ThreatLevel::Omega && !self.omega_authorized(now) {
+            return Err(TransitionError::OmegaNotAuthorized);
+        }
+        if level < self.level {
+            let allowed = self.level.step_down();
+            if level < allowed {
+                return Err(TransitionError::StepTooLarge { from: self.level, allowed });
+            }
+            let quiet_for = self.quiet_since.map_or(Duration::zero(), |quiet_since| now - quiet_since);
+            if quiet_for < self.quiet_period() {
+                return Err(TransitionError::QuietPeriodNotElapsed {
+                    remaining_secs: (self.quiet_period() - quiet_for).num_seconds().max(1),
+                });
+            }
+        }
+        let mut trace = DecisionTrace::new();
+        trace.rule("request", level, reason);
+        Ok(self.transition(level, reason, trace, now))
+    }
+
+    /// Undo a false alarm (a cancelled panic) by dropping straight back to
+    /// `level`, waiving the quiet period and the one-step rule; never
+    /// escalates. The trace records the override, and a threat still present
+    /// escalates again on the next assessment.
+    pub fn cancel_false_alarm(&mut self, level: ThreatLevel, reason: &str, now: DateTime<Utc>) -> Result<ThreatTransition, TransitionError> {
+        let level = level.min(self.level);
+        if level == self.level {
+            return Err(TransitionError::Unchanged(level));
+        }
+        let mut trace = DecisionTrace::new();
+        trace.rule("false alarm override", level, format!("{}; quiet period and one-step rule waived", reason));
+        Ok(self.transition(level, reason, trace, now))
+    }
+
+    fn quiet_period(&self) -> Duration {
+        Duration::seconds(self.rules.quiet_period_secs as i64)
+    }
+
+    fn transition(&mut self, to: ThreatLevel, reason: &str, trace: DecisionTrace, now: DateTime<Utc>) -> ThreatTransition {
+        let transition = ThreatTransition {
+            from: self.level,
+            to,
+            reason: reason.to_string(),
+            timestamp: now,
+            trace,
+        };
+        self.level = to;
+        self.entered_at = now;
+        self.quiet_since = None;
+        // No subscribers is fine - the caller still gets the transition
+        let _ = self.transitions.send(transition.clone());
+        transition
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap()
+    }
+
+    fn observe(machine: &mut ThreatStateMachine, assessed: ThreatLevel, now: DateTime<Utc>) -> Option<ThreatTransition> {
+        machine.observe(assessed, RiskTrend::Stable, "test", &DecisionTrace::new(), now)
+    }
+
+    fn refused(machine: &mut ThreatStateMachine, level: ThreatLevel, now: DateTime<Utc>) -> TransitionError {
+        machine.request(level, "test", now).unwrap_err()
+    }
+
+    #[test]
+    fn escalation_is_immediate() {
+        let mut machine = ThreatStateMachine::default();
+        let transition = observe(&mut machine, ThreatLevel::Red, at(0)).unwrap();
+        assert_eq!((transition.from, transition.to), (ThreatLevel::Green, ThreatLevel::Red));
+        assert!(transition.is_escalation());
+        assert_eq!(machine.level(), ThreatLevel::Red);
+        assert_eq!(refused(&mut machine, ThreatLevel::Red, at(0)), TransitionError::Unchanged(ThreatLevel::Red));
+    }
+
+    #[test]
+    fn omega_needs_a_live_authorization() {
+        let mut machine = ThreatStateMachine::default();
+        assert_eq!(refused(&mut machine, ThreatLevel::Omega, at(0)), TransitionError::OmegaNotAuthorized);
+        assert_eq!(observe(&mut machine, ThreatLevel::Omega, at(0)).unwrap().to, ThreatLevel::Red);
+
+        machine.authorize_omega("operator", at(0));
+        assert_eq!(machine.request(ThreatLevel::Omega, "test", at(10)).unwrap().to, ThreatLevel::Omega);
+
+        // Expiry drops out of Omega on the next assessment, and bars a return
+        let expired = at(machine.rules.authorization_ttl_secs as i64 + 1);
+        assert_eq!(observe(&mut machine, ThreatLevel::Omega, expired).unwrap().to, ThreatLevel::Red);
+        assert_eq!(refused(&mut machine, ThreatLevel::Omega, expired), TransitionError::OmegaNotAuthorized);
+    }
+
+    #[test]
+    fn de_escalation_waits_out_the_quiet_period() {
+        let mut machine = ThreatStateMachine::default();
+        machine.request(ThreatLevel::Orange, "test", at(0)).unwrap();
+        assert!(observe(&mut machine, ThreatLevel::Yellow, at(0)).is_none());
+
+        assert_eq!(
+            refused(&mut machine, ThreatLevel::Yellow, at(10)),
+            TransitionError::QuietPeriodNotElapsed { remaining_secs: 20 }
+        );
+        let transition = machine.request(ThreatLevel::Yellow, "test", at(30)).unwrap();
+        assert_eq!((transition.from, transition.to), (ThreatLevel::Orange, ThreatLevel::Yellow));
+    }
+
+    #[test]
+    fn de_escalation_is_one_level_at_a_time() {
+        let mut machine = ThreatStateMachine::default();
+        machine.request(ThreatLevel::Red, "test", at(0)).unwrap();
+        observe(&mut machine, ThreatLevel::Green, at(0));
+        assert_eq!(
+            refused(&mut machine, ThreatLevel::Yellow, at(60)),
+            TransitionError::StepTooLarge { from: ThreatLevel::Red, allowed: ThreatLevel::Orange }
+        );
+
+        // Assessments step down too, one quiet period per level
+        assert_eq!(observe(&mut machine, ThreatLevel::Green, at(60)).unwrap().to, ThreatLevel::Orange);
+        assert!(observe(&mut machine, ThreatLevel::Green, at(70)).is_none());
+        assert_eq!(observe(&mut machine, ThreatLevel::Green, at(90)).unwrap().to, ThreatLevel::Yellow);
+    }
+
+    #[test]
+    fn false_alarm_drops_straight_back() {
+        let mut machine = ThreatStateMachine::default();
+        machine.request(ThreatLevel::Red, "test", at(0)).unwrap();
+        let transition = machine.cancel_false_alarm(ThreatLevel::Green, "false alarm", at(1)).unwrap();
+        assert_eq!((transition.from, transition.to), (ThreatLevel::Red, ThreatLevel::Green));
+        assert_eq!(
+            machine.cancel_false_alarm(ThreatLevel::Orange, "false alarm", at(2)).unwrap_err(),
+            TransitionError::Unchanged(ThreatLevel::Green)
+        );
+    }
+}
@@ -0,0 +1,469 @@
+//! Terminal dashboard (`phoenix-tui` feature)
+
+use crate::{
+    Action, AuthContext, DeterrenceTelemetry, DroneState, FireSuppressionTelemetry, MissionEvent, ModuleControl, RingBuffer, ShieldTelemetry,
+    TelemetryMessage, ThreatLevel,
+};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Sparkline};
+use ratatui::{DefaultTerminal, Frame};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, RwLock};
+
+/// Risk samples kept for the sparkline
+const RISK_HISTORY: usize = 240;
+
+/// Mission events kept for the feed
+const EVENT_FEED: usize = 100;
+
+const REDRAW_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Extinguisher pressure shown as a full gauge
+const FULL_PRESSURE_PSI: f32 = 150.0;
+
+/// What the dashboard shows, updated from telemetry
+struct Dashboard {
+    name: String,
+    threat_level: ThreatLevel,
+    battery_level: u8,
+    flight_time_remaining: u32, // seconds
+    /// Risk score in percent, for the sparkline
+    risk: RingBuffer<u64>,
+    fire: Option<FireSuppressionTelemetry>,
+    deterrence: Option<DeterrenceTelemetry>,
+    shield: Option<ShieldTelemetry>,
+    events: RingBuffer<MissionEvent>,
+    /// Outcome of the last key command
+    notice: String,
+}
+
+impl Dashboard {
+    fn new(state: &DroneState) -> Self {
+        let mut events = RingBuffer::new(EVENT_FEED);
+        for event in state.mission_log.iter().rev().take(EVENT_FEED).rev() {
+            events.push(event.clone());
+        }
+        let mut dashboard = Self {
+            name: state.name.clone(),
+            threat_level: state.threat_level(),
+            battery_level: 0,
+            flight_time_remaining: 0,
+            risk: RingBuffer::new(RISK_HISTORY),
+            fire: None,
+            deterrence: None,
+            shield: None,
+            events,
+            notice: "a arm | d disarm | f test fire suppression | t test deterrence | s/r shield | q quit".to_string(),
+        };
+        dashboard.refresh(state);
+        dashboard
+    }
+
+    fn refresh(&mut self, state: &DroneState) {
+        self.threat_level = state.threat_level();
+        self.battery_level = state.system_health.battery_level;
+        self.flight_time_remaining = state.system_health.flight_time_remaining;
+    }
+
+    fn apply(&mut self, message: TelemetryMessage) {
+        match message {
+            TelemetryMessage::Status { threat_level, battery_level, .. } => {
+                self.threat_level = threat_level;
+                self.battery_level = battery_level;
+            },
+            TelemetryMessage::Health(health) => {
+                self.battery_level = health.battery_level;
+                self.flight_time_remaining = health.flight_time_remaining;
+            },
+            TelemetryMessage::Event(event) => {
+                self.events.push(event);
+            },
+            TelemetryMessage::ThreatTransition(transition) => self.threat_level = transition.to,
+            TelemetryMessage::ModuleHealth { .. } => {},
+            TelemetryMessage::Risk { score, .. } => {
+                self.risk.push((score * 100.0).round() as u64);
+            },
+            TelemetryMessage::FireSuppression(status) => self.fire = Some(status),
+            TelemetryMessage::Deterrence(status) => self.deterrence = Some(status),
+            TelemetryMessage::Shield(status) => self.shield = Some(status),
+            TelemetryMessage::Flight(status) => {
+                if let Some(battery_level) = status.battery_level {
+                    self.battery_level = battery_level;
+                }
+            },
+            TelemetryMessage::Power(status) => {
+                self.battery_level = status.battery_level;
+                self.flight_time_remaining = status.flight_time_remaining;
+            },
+            // Failovers reach the event feed as mission events
+            TelemetryMessage::Links(_) => {},
+        }
+    }
+}
+
+/// Take over the terminal and show the dashboard until `q` is pressed or
+/// `shutdown` completes; commands need `control`, as modules own the hardware
+pub async fn run(
+    drone: Arc<RwLock<DroneState>>,
+    control: Option<Arc<dyn ModuleControl>>,
+    operator: AuthContext,
+    shutdown: impl Future<Output = ()>,
+) -> std::io::Result<()> {
+    let (mut dashboard, telemetry) = {
+        let state = drone.read().await;
+        (Dashboard::new(&state), state.subscribe_telemetry())
+    };
+    let mut terminal = ratatui::try_init()?;
+    let result = run_dashboard(&mut terminal, &mut dashboard, &drone, control, &operator, telemetry, shutdown).await;
+    ratatui::try_restore()?;
+    result
+}
+
+async fn run_dashboard(
+    terminal: &mut DefaultTerminal,
+    dashboard: &mut Dashboard,
+    drone: &RwLock<DroneState>,
+    control: Option<Arc<dyn ModuleControl>>,
+    operator: &AuthContext,
+    mut telemetry: broadcast::Receiver<TelemetryMessage>,
+    shutdown: impl Future<Output = ()>,
+) -> std::io::Result<()> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut keys = read_keys(Arc::clone(&stop));
+    let (notices, mut notice) = mpsc::unbounded_channel();
+    let mut redraw = crate::runtime::interval(REDRAW_INTERVAL);
+    tokio::pin!(shutdown);
+
+    let result = loop {
+        tokio::select! {
+            _ = &mut shutdown => break Ok(()),
+            _ = redraw.tick() => {
+                dashboard.refresh(&*drone.read().await);
+                if let Err(e) = terminal.draw(|frame| draw(frame, dashboard)) {
+                    break Err(e);
+                }
+            },
+            received = telemetry.recv() => match received {
+                Ok(message) => dashboard.apply(message),
+                // Redrawing catches up with the status; a few events are lost from the feed
+                Err(broadcast::error::RecvError::Lagged(_)) => {},
+                Err(broadcast::error::RecvError::Closed) => break Ok(()),
+            },
+            Some(text) = notice.recv() => dashboard.notice = text,
+            key = keys.recv() => match key {
+                // Raw mode turns Ctrl-C into a key press rather than a signal
+                Some(key) if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) => break Ok(()),
+                Some(KeyEvent { code: KeyCode::Char('q') | KeyCode::Esc, .. }) => break Ok(()),
+                Some(KeyEvent { code: KeyCode::Char(key), .. }) => {
+                    if let Some(command) = Command::from_key(key) {
+                        match operator.authorize(command.action(), &mut *drone.write().await) {
+                            Ok(()) => {
+                                dashboard.notice = format!("⏳ {}...", command.label());
+                                command.spawn(control.clone(), notices.clone());
+                            },
+                            Err(e) => dashboard.notice = format!("🔐 {}: {}", command.label(), e),
+                        }
+                    }
+                },
+                Some(_) => {},
+                None => break Err(std::io::Error::other("keyboard input closed")),
+            },
+        }
+    };
+    stop.store(true, Ordering::Relaxed);
+    result
+}
+
+/// Key presses from a blocking reader thread, which exits once `stop` is set
+fn read_keys(stop: Arc<AtomicBool>) -> mpsc::UnboundedReceiver<KeyEvent> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        while !stop.load(Ordering::Relaxed) {
+            match event::poll(Duration::from_millis(100)) {
+                Ok(false) => continue,
+                Ok(true) => {},
+                Err(_) => return,
+            }
+            match event::read() {
+                Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                    if sender.send(key).is_err() {
+                        return;
+                    }
+                },
+                Ok(_) => {},
+                Err(_) => return,
+            }
+        }
+    });
+    receiver
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Command {
+    Arm,
+    Disarm,
+    TestFireSuppression,
+    TestDeterrence,
+    DeployShield,
+    RetractShield,
+}
+
+impl Command {
+    fn from_key(key: char) -> Option<Self> {
+        match key {
+            'a' => Some(Command::Arm),
+            'd' => Some(Command::Disarm),
+            'f' => Some(Command::TestFireSuppression),
+            't' => Some(Command::TestDeterrence),
+            's' => Some(Command::DeployShield),
+            'r' => Some(Command::RetractShield),
+            _ => None,
+        }
+    }
+
+    fn action(&self) -> Action {
+        match self {
+            Command::Arm | Command::Disarm => Action::ArmDisarm,
+            Command::TestFireSuppression | Command::TestDeterrence => Action::TestModule,
+            Command::DeployShield | Command::RetractShield => Action::DeployShield,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Command::Arm => "Arming",
+            Command::Disarm => "Disarming",
+            Command::TestFireSuppression => "Fire suppression self-test",
+            Command::TestDeterrence => "Deterrence self-test",
+            Command::DeployShield => "Deploying shield",
+            Command::RetractShield => "Retracting shield",
+        }
+    }
+
+    /// Run in the background so a long self-test does not freeze the screen
+    fn spawn(self, control: Option<Arc<dyn ModuleControl>>, notices: mpsc::UnboundedSender<String>) {
+        crate::runtime::spawn(async move {
+            let notice = match control {
+                None => format!("❌ {}: no modules attached", self.label()),
+                Some(control) => {
+                    let result = match self {
+                        Command::Arm => control.set_armed(true).await,
+                        Command::Disarm => control.set_armed(false).await,
+                        Command::TestFireSuppression => control.test_fire_suppression().await,
+                        Command::TestDeterrence => control.test_deterrence().await,
+                        Command::DeployShield => control.deploy_shield(true).await,
+                        Command::RetractShield => control.deploy_shield(false).await,
+                    };
+                    match result {
+                        Ok(()) => format!("✅ {} done", self.label()),
+                        Err(e) => format!("❌ {} failed: {}", self.label(), e),
+                    }
+                },
+            };
+            // The dashboard has closed
+            let _ = notices.send(notice);
+        });
+    }
+}
+
+fn threat_color(level: ThreatLevel) -> Color {
+    match level {
+        ThreatLevel::Green => Color::Green,
+        ThreatLevel::Yellow => Color::Yellow,
+        ThreatLevel::Orange => Color::LightRed,
+        ThreatLevel::Red => Color::Red,
+        ThreatLevel::Omega => Color::Magenta,
+    }
+}
+
+fn draw(frame: &mut Frame, dashboard: &Dashboard) {
+    let [header, risk_row, modules_row, events, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(6),
+        Constraint::Length(7),
+        Constraint::Min(4),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [risk, battery] = Layout::horizontal([Constraint::Percentage(70), Constraint::Percentage(30)]).areas(risk_row);
+    let [fire, deterrence, shield] =
+        Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(40), Constraint::Percentage(25)]).areas(modules_row);
+
+    draw_header(frame, header, dashboard);
+    draw_risk(frame, risk, dashboard);
+    draw_battery(frame, battery, dashboard);
+    draw_fire_suppression(frame, fire, dashboard);
+    draw_deterrence(frame, deterrence, dashboard);
+    draw_shield(frame, shield, dashboard);
+    draw_events(frame, events, dashboard);
+    frame.render_widget(Paragraph::new(dashboard.notice.as_str()), footer);
+}
+
+fn draw_header(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+    let color = threat_color(dashboard.threat_level);
+    let line = Line::from(vec![
+        Span::styled(
+            format!(" {} ", dashboard.threat_level.as_str()),
+            Style::default().fg(Color::Black).bg(color).add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(format!("  {}", dashboard.threat_level.description())),
+    ]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color))
+        .title(format!(" 🔥 {} ", dashboard.name));
+    frame.render_widget(Paragraph::new(line).block(block), area);
+}
+
+fn draw_risk(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+    let title = match dashboard.risk.last() {
+        Some(score) => format!(" Risk {}% ", score),
+        None => " Risk (no assessments yet) ".to_string(),
+    };
+    // Newest on the right, as many as fit
+    let width = usize::from(area.width.saturating_sub(2));
+    let sparkline = Sparkline::default()
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .data(dashboard.risk.recent(width))
+        .max(100)
+        .style(Style::default().fg(threat_color(dashboard.threat_level)));
+    frame.render_widget(sparkline, area);
+}
+
+fn draw_battery(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+    let color = match dashboard.battery_level {
+        0..=19 => Color::Red,
+        20..=49 => Color::Yellow,
+        _ => Color::Green,
+    };
+    let gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title(" Battery "))
+        .gauge_style(Style::default().fg(color))
+        .percent(u16::from(dashboard.battery_level.min(100)))
+        .label(format!("{}% | {} min", dashboard.battery_level, dashboard.flight_time_remaining / 60));
+    frame.render_widget(gauge, area);
+}
+
+fn draw_fire_suppression(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+    let block = Block::default().borders(Borders::ALL).title(" Fire suppression ");
+    let Some(fire) = &dashboard.fire else {
+        frame.render_widget(Paragraph::new("No report yet").block(block), area);
+        return;
+    };
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let [state, pressure, capacity] =
+        Layout::vertical([Constraint::Length(1), Constraint::Length(2), Constraint::Length(2)]).areas(inner);
+
+    let state_line = if fire.discharging {
+        Span::styled("💨 DISCHARGING", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+    } else if fire.armed {
+        Span::styled("ARMED", Style::default().fg(Color::Green))
+    } else {
+        Span::styled("DISARMED", Style::default().fg(Color::Yellow))
+    };
+    frame.render_widget(Paragraph::new(Line::from(state_line)), state);
+    let pressure_ratio = f64::from((fire.pressure.0 / FULL_PRESSURE_PSI).clamp(0.0, 1.0));
+    frame.render_widget(
+        Gauge::default()
+            .gauge_style(Style::default().fg(Color::Cyan))
+            .ratio(pressure_ratio)
+            .label(format!("Pressure {:.0} PSI", fire.pressure.0)),
+        pressure,
+    );
+    frame.render_widget(
+        Gauge::default()
+            .gauge_style(Style::default().fg(Color::Blue))
+            .ratio(f64::from((fire.capacity / 100.0).clamp(0.0, 1.0)))
+            .label(format!("Capacity {:.0}%", fire.capacity)),
+        capacity,
+    );
+}
+
+fn draw_deterrence(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+    let block = Block::default().borders(Borders::ALL).title(" Deterrence ");
+    let Some(deterrence) = &dashboard.deterrence else {
+        frame.render_widget(Paragraph::new("No report yet").block(block), area);
+        return;
+    };
+    let output = |name: &str, active: bool, detail: String| {
+        let (marker, style) = if active {
+            ("●", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+        } else {
+            ("○", Style::default().fg(Color::DarkGray))
+        };
+        Line::from(vec![Span::styled(format!("{} {}", marker, name), style), Span::raw(detail)])
+    };
+    let mut lines = vec![
+        output("Siren", deterrence.siren_active, format!("  {}%", deterrence.siren_volume)),
+        output("Strobe", deterrence.strobe_active, String::new()),
+        output(
+            "Voice",
+            deterrence.voice_active,
+            deterrence.current_message.as_ref().map(|message| format!("  \"{}\"", message)).unwrap_or_default(),
+        ),
+    ];
+    if deterrence.rehearsal {
+        lines.push(Line::styled("REHEARSAL - outputs only logged", Style::default().fg(Color::Yellow)));
+    }
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn draw_shield(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+    let block = Block::default().borders(Borders::ALL).title(" Shield ");
+    let Some(shield) = &dashboard.shield else {
+        frame.render_widget(Paragraph::new("No report yet").block(block), area);
+        return;
+    };
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let [state, integrity] = Layout::vertical([Constraint::Length(2), Constraint::Length(2)]).areas(inner);
+
+    let state_line = if shield.deployed {
+        Span::styled(format!("DEPLOYED {:+.0}°", shield.angle_deg), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+    } else {
+        Span::styled("STOWED", Style::default().fg(Color::DarkGray))
+    };
+    let impacts = Span::raw(format!("  {} hits", shield.impacts));
+    frame.render_widget(Paragraph::new(Line::from(vec![state_line, impacts])), state);
+    let color = match shield.integrity {
+        0..=49 => Color::Red,
+        50..=79 => Color::Yellow,
+        _ => Color::Green,
+    };
+    frame.render_widget(
+        Gauge::default()
+            .gauge_style(Style::default().fg(color))
+            .percent(u16::from(shield.integrity.min(100)))
+            .label(format!("Integrity {}%", shield.integrity)),
+        integrity,
+    );
+}
+
+fn draw_events(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+    let visible = usize::from(area.height.saturating_sub(2));
+    // Newest first
+    let items: Vec<ListItem> = dashboard
+        .events
+        .iter()
+        .rev()
+        .take(visible)
+        .map(|event| {
+            ListItem::new(Line::from(vec![
+                Span::styled(event.timestamp.format("%H:%M:%S ").to_string(), Style::default().fg(Color::DarkGray)),
+                Span::styled(
+                    format!("{:<7} ", event.threat_level.as_str()),
+                    Style::default().fg(threat_color(event.threat_level)),
+                ),
+                Span::raw(event.description.clone()),
+            ]))
+        })
+        .collect();
+    frame.render_widget(List::new(items).block(Block::default().borders(Borders::ALL).title(" Mission events ")), area);
+}
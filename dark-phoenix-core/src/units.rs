@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Temperature in degrees Celsius - the internal unit for all thermal logic
+///
+/// Deserializes from a bare number (Celsius) or a tagged value such as
+/// `{ "fahrenheit": 140.0 }`, so configs can be written in either unit.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd, Default)]
+#[serde(from = "TemperatureInput")]
+pub struct Celsius(pub f32);
+
+/// Temperature in degrees Fahrenheit
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd, Default)]
+pub struct Fahrenheit(pub f32);
+
+/// Pressure in pounds per square inch - the internal unit for extinguisher logic
+///
+/// Deserializes from a bare number (PSI) or a tagged value such as `{ "bar": 6.9 }`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd, Default)]
+#[serde(from = "PressureInput")]
+pub struct Psi(pub f32);
+
+/// Pressure in bar
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd, Default)]
+pub struct Bar(pub f32);
+
+const PSI_PER_BAR: f32 = 14.503_774;
+
+impl From<Fahrenheit> for Celsius {
+    fn from(f: Fahrenheit) -> Self {
+        Celsius((f.0 - 32.0) * 5.0 / 9.0)
+    }
+}
+
+impl From<Celsius> for Fahrenheit {
+    fn from(c: Celsius) -> Self {
+        Fahrenheit(c.0 * 9.0 / 5.0 + 32.0)
+    }
+}
+
+impl From<Bar> for Psi {
+    fn from(b: Bar) -> Self {
+        Psi(b.0 * PSI_PER_BAR)
+    }
+}
+
+impl From<Psi> for Bar {
+    fn from(p: Psi) -> Self {
+        Bar(p.0 / PSI_PER_BAR)
+    }
+}
+
+impl fmt::Display for Celsius {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1}°C", self.0)
+    }
+}
+
+impl fmt::Display for Fahrenheit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1}°F", self.0)
+    }
+}
+
+impl fmt::Display for Psi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.0} PSI", self.0)
+    }
+}
+
+impl fmt::Display for Bar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.2} bar", self.0)
+    }
+}
+
+/// Accepted config representations for a temperature
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TemperatureInput {
+    Bare(f32),
+    Tagged(TaggedTemperature),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum TaggedTemperature {
+    Celsius(f32),
+    Fahrenheit(f32),
+}
+
+impl From<TemperatureInput> for Celsius {
+    fn from(input: TemperatureInput) -> Self {
+        match input {
+            TemperatureInput::Bare(c) | TemperatureInput::Tagged(TaggedTemperature::Celsius(c)) => Celsius(c),
+            TemperatureInput::Tagged(TaggedTemperature::Fahrenheit(f)) => Fahrenheit(f).into(),
+        }
+    }
+}
+
+/// Accepted config representations for a pressure
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PressureInput {
+    Bare(f32),
+    Tagged(TaggedPressure),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum TaggedPressure {
+    Psi(f32),
+    Bar(f32),
+}
+
+impl From<PressureInput> for Psi {
+    fn from(input: PressureInput) -> Self {
+        match input {
+            PressureInput::Bare(p) | PressureInput::Tagged(TaggedPressure::Psi(p)) => Psi(p),
+            PressureInput::Tagged(TaggedPressure::Bar(b)) => Bar(b).into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.01
+    }
+
+    #[test]
+    fn bare_numbers_are_celsius_and_psi() {
+        let celsius: Celsius = serde_json::from_str("60.5").unwrap();
+        let psi: Psi = serde_json::from_str("100").unwrap();
+        assert_eq!(celsius, Celsius(60.5));
+        assert_eq!(psi, Psi(100.0));
+    }
+
+    #[test]
+    fn tagged_units_are_converted() {
+        let celsius: Celsius = serde_json::from_str(r#"{ "fahrenheit": 140.0 }"#).unwrap();
+        assert!(close(celsius.0, 60.0));
+        let celsius: Celsius = serde_json::from_str(r#"{ "celsius": -40.0 }"#).unwrap();
+        assert_eq!(celsius, Celsius(-40.0));
+
+        let psi: Psi = serde_json::from_str(r#"{ "bar": 6.9 }"#).unwrap();
+        assert!(close(psi.0, 100.075));
+        let psi: Psi = serde_json::from_str(r#"{ "psi": 150 }"#).unwrap();
+        assert_eq!(psi, Psi(150.0));
+    }
+
+    #[test]
+    fn unknown_units_and_junk_are_rejected() {
+        assert!(serde_json::from_str::<Celsius>(r#"{ "kelvin": 300.0 }"#).is_err());
+        assert!(serde_json::from_str::<Psi>(r#"{ "bar": "high" }"#).is_err());
+        assert!(serde_json::from_str::<Psi>(r#""100""#).is_err());
+    }
+
+    #[test]
+    fn serializes_as_a_bare_number_that_reads_back() {
+        let json = serde_json::to_string(&Celsius(21.5)).unwrap();
+        assert_eq!(json, "21.5");
+        assert_eq!(serde_json::from_str::<Celsius>(&json).unwrap(), Celsius(21.5));
+    }
+
+    #[test]
+    fn conversions_round_trip() {
+        assert!(close(Fahrenheit::from(Celsius(100.0)).0, 212.0));
+        assert!(close(Celsius::from(Fahrenheit::from(Celsius(37.0))).0, 37.0));
+        assert!(close(Bar::from(Psi::from(Bar(2.5))).0, 2.5));
+    }
+}
@@ -0,0 +1,315 @@
+//! Encryption at rest with a device keyring
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+// `::` since the crate has a `ring` module of its own
+use ::ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const MAGIC: &[u8; 4] = b"DPv1";
+const HEADER_LEN: usize = MAGIC.len() + 4 + NONCE_LEN;
+
+/// Marks a sealed line in a JSONL stream
+pub const SEALED_LINE_PREFIX: &str = "dpenc:";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    /// Device keyring, created on first use; keep it off the SD card if the
+    /// hardware allows (a secure element, a separate partition)
+    pub keyring_path: PathBuf,
+}
+
+impl Default for EncryptionConfig {
+    fn default() -> Self {
+        Self {
+            keyring_path: PathBuf::from("phoenix.keyring"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum VaultError {
+    #[error("keyring {0}: {1}")]
+    Keyring(PathBuf, String),
+    #[error("sealed with key {0}, which is not in the keyring")]
+    UnknownKey(u32),
+    #[error("sealed data found but no keyring to open it")]
+    Locked,
+    #[error("sealed data is damaged or was altered")]
+    Corrupt,
+    #[error("data is not sealed but encryption is enabled; seal older stores with `phoenix vault migrate`")]
+    Unsealed,
+}
+
+/// The device's encryption keys, by id
+pub struct Keyring {
+    current: u32,
+    keys: BTreeMap<u32, [u8; 32]>,
+}
+
+/// On-disk form of the keyring
+#[derive(Serialize, Deserialize)]
+struct KeyringFile {
+    current: u32,
+    /// Hex AES-256 keys by id
+    keys: BTreeMap<u32, String>,
+}
+
+impl fmt::Debug for Keyring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never the keys themselves
+        f.debug_struct("Keyring").field("current", &self.current).field("keys", &self.keys.keys()).finish()
+    }
+}
+
+impl Keyring {
+    /// A keyring with one fresh key
+    pub fn generate() -> Self {
+        Self {
+            current: 1,
+            keys: BTreeMap::from([(1, rand::random())]),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, VaultError> {
+        let invalid = |reason: String| VaultError::Keyring(path.to_path_buf(), reason);
+        let text = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let file: KeyringFile = serde_json::from_str(&text).map_err(|e| invalid(e.to_string()))?;
+        let mut keys = BTreeMap::new();
+        for (id, key) in file.keys {
+            let key = hex::decode(key)
+                .ok()
+                .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                .ok_or_else(|| invalid(format!("key {} is not 64 hex digits", id)))?;
+            keys.insert(id, key);
+        }
+        if !keys.contains_key(&file.current) {
+            return Err(invalid(format!("current key {} is missing", file.current)));
+        }
+        Ok(Self { current: file.current, keys })
+    }
+
+    /// Load the keyring at `path`, creating it with one key if it is missing
+    pub fn load_or_create(path: &Path) -> Result<Self, VaultError> {
+        if path.exists() {
+            return Self::load(path);
+        }
+        let keyring = Self::generate();
+        keyring.save(path)?;
+        tracing::info!("🔐 Created device keyring {}", path.display());
+        Ok(keyring)
+    }
+
+    /// Write the keyring readable by its owner only, replacing it whole
+    pub fn save(&self, path: &Path) -> Result<(), VaultError> {
+        let file = KeyringFile {
+            current: self.current,
+            keys: self.keys.iter().map(|(id, key)| (*id, hex::encode(key))).collect(),
+        };
+        let temp = path.with_extension("tmp");
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options
+            .open(&temp)
+            .and_then(|mut out| std::io::Write::write_all(&mut out, serde_json::to_string_pretty(&file).unwrap_or_default().as_bytes()))
+            .and_then(|_| std::fs::rename(&temp, path))
+            .map_err(|e| VaultError::Keyring(path.to_path_buf(), e.to_string()))
+    }
+
+    /// Add a key and seal everything from now on with it; returns its id
+    pub fn rotate(&mut self) -> u32 {
+        let id = self.keys.keys().max().map_or(1, |max| max + 1);
+        self.keys.insert(id, rand::random());
+        self.current = id;
+        id
+    }
+
+    /// Id of the key new data is sealed with
+    pub fn current(&self) -> u32 {
+        self.current
+    }
+
+    pub fn key_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.keys.keys().copied()
+    }
+
+    fn key(&self, id: u32) -> Result<LessSafeKey, VaultError> {
+        let bytes = self.keys.get(&id).ok_or(VaultError::UnknownKey(id))?;
+        let key = UnboundKey::new(&AES_256_GCM, bytes).map_err(|_| VaultError::UnknownKey(id))?;
+        Ok(LessSafeKey::new(key))
+    }
+
+    /// Encrypt under the current key
+    pub fn seal(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce: [u8; NONCE_LEN] = rand::random();
+        let mut sealed = Vec::with_capacity(HEADER_LEN + plaintext.len() + AES_256_GCM.tag_len());
+        sealed.extend_from_slice(MAGIC);
+        sealed.extend_from_slice(&self.current.to_be_bytes());
+        sealed.extend_from_slice(&nonce);
+        let mut body = plaintext.to_vec();
+        // The header is authenticated, so a blob cannot be passed off as sealed by another key
+        let key = self.key(self.current).expect("the current key is always in the keyring");
+        key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(&sealed[..MAGIC.len() + 4]), &mut body)
+            .expect("AES-GCM sealing only fails on absurd lengths");
+        sealed.append(&mut body);
+        sealed
+    }
+
+    /// Decrypt a sealed blob
+    pub fn open(&self, data: &[u8]) -> Result<Vec<u8>, VaultError> {
+        if !is_sealed(data) {
+            return Err(VaultError::Unsealed);
+        }
+        let header = data.get(..HEADER_LEN).ok_or(VaultError::Corrupt)?;
+        let id = u32::from_be_bytes(header[MAGIC.len()..MAGIC.len() + 4].try_into().map_err(|_| VaultError::Corrupt)?);
+        let nonce = Nonce::try_assume_unique_for_key(&header[MAGIC.len() + 4..]).map_err(|_| VaultError::Corrupt)?;
+        let mut body = data[HEADER_LEN..].to_vec();
+        let plaintext = self
+            .key(id)?
+            .open_in_place(nonce, Aad::from(&header[..MAGIC.len() + 4]), &mut body)
+            .map_err(|_| VaultError::Corrupt)?;
+        Ok(plaintext.to_vec())
+    }
+
+    /// Seal one JSONL line (without its newline)
+    pub fn seal_line(&self, line: &[u8]) -> String {
+        format!("{}{}", SEALED_LINE_PREFIX, BASE64.encode(self.seal(line)))
+    }
+
+    /// Open a line written by `seal_line`
+    pub fn open_line(&self, line: &str) -> Result<String, VaultError> {
+        let encoded = line.strip_prefix(SEALED_LINE_PREFIX).ok_or(VaultError::Unsealed)?;
+        let sealed = BASE64.decode(encoded).map_err(|_| VaultError::Corrupt)?;
+        String::from_utf8(self.open(&sealed)?).map_err(|_| VaultError::Corrupt)
+    }
+
+    /// Decrypt a whole file as stored: a sealed blob, or a JSONL stream
+    /// whose every line is sealed
+    pub fn open_file(&self, data: &[u8]) -> Result<Vec<u8>, VaultError> {
+        if is_sealed(data) {
+            return self.open(data);
+        }
+        let text = std::str::from_utf8(data).map_err(|_| VaultError::Unsealed)?;
+        let mut opened = String::with_capacity(text.len());
+        for line in text.lines() {
+            opened.push_str(&self.open_line(line)?);
+            opened.push('\n');
+        }
+        Ok(opened.into_bytes())
+    }
+
+    /// Seal the plaintext lines of a JSONL stream written before encryption
+    /// was enabled, leaving sealed ones alone; returns the stream and how
+    /// many lines were sealed
+    pub fn migrate_lines(&self, data: &[u8]) -> Result<(Vec<u8>, usize), VaultError> {
+        let text = std::str::from_utf8(data).map_err(|_| VaultError::Corrupt)?;
+        let mut migrated = String::with_capacity(text.len());
+        let mut sealed = 0;
+        for line in text.lines() {
+            if line.starts_with(SEALED_LINE_PREFIX) {
+                migrated.push_str(line);
+            } else {
+                migrated.push_str(&self.seal_line(line.as_bytes()));
+                sealed += 1;
+            }
+            migrated.push('\n');
+        }
+        Ok((migrated.into_bytes(), sealed))
+    }
+}
+
+/// Whether `data` is a sealed blob
+pub fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Whether a file as stored holds anything sealed: a blob, or sealed lines
+pub fn holds_sealed(data: &[u8]) -> bool {
+    is_sealed(data) || std::str::from_utf8(data).is_ok_and(|text| text.lines().any(|line| line.starts_with(SEALED_LINE_PREFIX)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_blob_round_trips() {
+        let keyring = Keyring::generate();
+        let sealed = keyring.seal(b"evidence bytes");
+        assert!(is_sealed(&sealed));
+        assert_ne!(&sealed[HEADER_LEN..], b"evidence bytes");
+        assert_eq!(keyring.open(&sealed).unwrap(), b"evidence bytes");
+        assert_eq!(keyring.open_file(&sealed).unwrap(), b"evidence bytes");
+    }
+
+    #[test]
+    fn sealed_lines_round_trip() {
+        let keyring = Keyring::generate();
+        let line = keyring.seal_line(br#"{"sequence":0}"#);
+        assert!(line.starts_with(SEALED_LINE_PREFIX));
+        assert_eq!(keyring.open_line(&line).unwrap(), r#"{"sequence":0}"#);
+
+        let stream = format!("{}\n{}\n", line, keyring.seal_line(b"{}"));
+        assert_eq!(keyring.open_file(stream.as_bytes()).unwrap(), b"{\"sequence\":0}\n{}\n");
+    }
+
+    #[test]
+    fn old_keys_still_open_after_rotation() {
+        let mut keyring = Keyring::generate();
+        let sealed = keyring.seal(b"before");
+        assert_eq!(keyring.rotate(), 2);
+        assert_eq!(keyring.open(&sealed).unwrap(), b"before");
+        assert!(matches!(Keyring::generate().open(&sealed), Err(VaultError::Corrupt)));
+        assert!(matches!(Keyring::generate().open(&keyring.seal(b"after")), Err(VaultError::UnknownKey(2))));
+    }
+
+    #[test]
+    fn altered_data_is_rejected() {
+        let keyring = Keyring::generate();
+        let mut sealed = keyring.seal(b"evidence bytes");
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        assert!(matches!(keyring.open(&sealed), Err(VaultError::Corrupt)));
+        assert!(matches!(keyring.open_line("dpenc:not base64!"), Err(VaultError::Corrupt)));
+    }
+
+    #[test]
+    fn unsealed_data_is_rejected() {
+        let keyring = Keyring::generate();
+        assert!(matches!(keyring.open(b"plain"), Err(VaultError::Unsealed)));
+        assert!(matches!(keyring.open_line("{}"), Err(VaultError::Unsealed)));
+        assert!(matches!(keyring.open_file(b"{}\n"), Err(VaultError::Unsealed)));
+        let mixed = format!("{}\n{{}}\n", keyring.seal_line(b"{}"));
+        assert!(matches!(keyring.open_file(mixed.as_bytes()), Err(VaultError::Unsealed)));
+    }
+
+    #[test]
+    fn migration_seals_only_plaintext_lines() {
+        let keyring = Keyring::generate();
+        let sealed = keyring.seal_line(b"{\"a\":1}");
+        let stream = format!("{}\n{{\"b\":2}}\n", sealed);
+        let (migrated, count) = keyring.migrate_lines(stream.as_bytes()).unwrap();
+        assert_eq!(count, 1);
+        assert!(String::from_utf8_lossy(&migrated).starts_with(&sealed));
+        assert_eq!(keyring.open_file(&migrated).unwrap(), b"{\"a\":1}\n{\"b\":2}\n");
+    }
+
+    #[test]
+    fn keyring_survives_save_and_load() {
+        let path = std::env::temp_dir().join(format!("dark-phoenix-keyring-{}", uuid::Uuid::new_v4()));
+        let mut keyring = Keyring::generate();
+        keyring.rotate();
+        keyring.save(&path).unwrap();
+        let loaded = Keyring::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.current(), 2);
+        assert_eq!(loaded.open(&keyring.seal(b"x")).unwrap(), b"x");
+    }
+}
@@ -0,0 +1,275 @@
+//! Protectee vitals from a BLE wearable (`ble` feature)
+
+use crate::{DroneState, ModuleResult, ThreatLevel, VitalSigns};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
+
+/// Bluetooth SIG 16-bit UUIDs
+pub mod uuid {
+    pub const HEART_RATE_SERVICE: u16 = 0x180D;
+    pub const HEART_RATE_MEASUREMENT: u16 = 0x2A37;
+    pub const PULSE_OXIMETER_SERVICE: u16 = 0x1822;
+    pub const PLX_CONTINUOUS_MEASUREMENT: u16 = 0x2A5F;
+}
+
+/// A connected wearable, e.g. a `BleWearable`
+#[async_trait]
+pub trait GattClient: Send + Sync {
+    /// Enable notifications on a characteristic; each value arrives raw
+    async fn subscribe(
+        &self,
+        service: u16,
+        characteristic: u16,
+    ) -> Result<mpsc::Receiver<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// A decoded Heart Rate Measurement (0x2A37)
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeartRateMeasurement {
+    pub bpm: u16,
+    /// `None` when the strap cannot tell
+    pub skin_contact: Option<bool>,
+    /// Beat-to-beat intervals since the last notification
+    pub rr_intervals_ms: Vec<f64>,
+}
+
+impl HeartRateMeasurement {
+    pub fn parse(value: &[u8]) -> Option<Self> {
+        let flags = *value.first()?;
+        let wide = flags & 0x01 != 0;
+        let (bpm, mut at) = if wide {
+            (u16::from_le_bytes([*value.get(1)?, *value.get(2)?]), 3)
+        } else {
+            (u16::from(*value.get(1)?), 2)
+        };
+        let skin_contact = (flags & 0x04 != 0).then_some(flags & 0x02 != 0);
+        if flags & 0x08 != 0 {
+            at += 2; // Energy expended
+        }
+        let mut rr_intervals_ms = Vec::new();
+        if flags & 0x10 != 0 {
+            while let (Some(&low), Some(&high)) = (value.get(at), value.get(at + 1)) {
+                // 1/1024 s resolution
+                rr_intervals_ms.push(f64::from(u16::from_le_bytes([low, high])) * 1000.0 / 1024.0);
+                at += 2;
+            }
+        }
+        Some(Self {
+            bpm,
+            skin_contact,
+            rr_intervals_ms,
+        })
+    }
+}
+
+/// SpO2 (%) from a PLX Continuous Measurement (0x2A5F)
+pub fn parse_spo2(value: &[u8]) -> Option<u8> {
+    let spo2 = sfloat(u16::from_le_bytes([*value.get(1)?, *value.get(2)?]))?;
+    (0.0..=100.0).contains(&spo2).then(|| spo2.round() as u8)
+}
+
+/// IEEE 11073 16-bit SFLOAT: 4-bit exponent, 12-bit mantissa; `None` for
+/// the reserved NaN/NRes/infinity values
+fn sfloat(raw: u16) -> Option<f64> {
+    let mantissa = (raw & 0x0FFF) as i16;
+    if (0x07FE..=0x0802).contains(&mantissa) {
+        return None;
+    }
+    let mantissa = if mantissa >= 0x0800 { mantissa - 0x1000 } else { mantissa };
+    let exponent = (raw >> 12) as i8;
+    let exponent = if exponent >= 8 { exponent - 16 } else { exponent };
+    Some(f64::from(mantissa) * 10f64.powi(i32::from(exponent)))
+}
+
+/// Vitals that count as distress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistressThresholds {
+    pub heart_rate_above: u16,
+    pub heart_rate_below: u16,
+    pub spo2_below: u8,
+    pub stress_above: u8,
+}
+
+impl Default for DistressThresholds {
+    fn default() -> Self {
+        Self {
+            heart_rate_above: 150,
+            heart_rate_below: 40,
+            spo2_below: 90,
+            stress_above: 85,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VitalsConfig {
+    /// Beat-to-beat intervals kept for heart rate variability
+    pub rr_window: usize,
+    /// RMSSD (ms) at or above which stress reads 0
+    pub relaxed_rmssd_ms: f64,
+    /// RMSSD (ms) at or below which stress reads 100
+    pub stressed_rmssd_ms: f64,
+    pub distress: DistressThresholds,
+    /// The protectee's wearable, followed by `phoenix run` (absent = none)
+    pub wearable: Option<crate::WearableConfig>,
+}
+
+impl Default for VitalsConfig {
+    fn default() -> Self {
+        Self {
+            rr_window: 30,
+            relaxed_rmssd_ms: 60.0,
+            stressed_rmssd_ms: 10.0,
+            distress: DistressThresholds::default(),
+            wearable: None,
+        }
+    }
+}
+
+/// Turns wearable notifications into `VitalSigns` and spots distress
+#[derive(Debug, Clone)]
+pub struct VitalsMonitor {
+    config: VitalsConfig,
+    rr_intervals: VecDeque<f64>,
+    in_distress: bool,
+}
+
+impl VitalsMonitor {
+    pub fn new(config: VitalsConfig) -> Self {
+        Self {
+            config,
+            rr_intervals: VecDeque::new(),
+            in_distress: false,
+        }
+    }
+
+    /// Stress (0-100) from the RMSSD of recent beat-to-beat intervals; low
+    /// variability reads as high stress. `None` until enough beats are in
+    pub fn stress_level(&self) -> Option<u8> {
+        if self.rr_intervals.len() < 5 {
+            return None;
+        }
+        let diffs: Vec<f64> = self.rr_intervals.iter().zip(self.rr_intervals.iter().skip(1)).map(|(a, b)| b - a).collect();
+        let rmssd = (diffs.iter().map(|d| d * d).sum::<f64>() / diffs.len() as f64).sqrt();
+        let (relaxed, stressed) = (self.config.relaxed_rmssd_ms, self.config.stressed_rmssd_ms);
+        let stress = ((relaxed - rmssd) / (relaxed - stressed)).clamp(0.0, 1.0);
+        Some((stress * 100.0).round() as u8)
+    }
+
+    /// Fold a heart rate notification into the drone's view of the protectee
+    pub fn record_heart_rate(&mut self, measurement: &HeartRateMeasurement, drone: &mut DroneState) {
+        if measurement.skin_contact == Some(false) {
+            // Strap off: the reading is noise
+            return;
+        }
+        for rr in &measurement.rr_intervals_ms {
+            if self.rr_intervals.len() == self.config.rr_window.max(2) {
+                self.rr_intervals.pop_front();
+            }
+            self.rr_intervals.push_back(*rr);
+        }
+        let stress_level = self.stress_level();
+        let vitals = vitals_of(drone);
+        vitals.heart_rate = Some(measurement.bpm);
+        vitals.stress_level = stress_level.or(vitals.stress_level);
+        self.check_distress(drone);
+    }
+
+    pub fn record_spo2(&mut self, spo2: u8, drone: &mut DroneState) {
+        vitals_of(drone).blood_oxygen = Some(spo2);
+        self.check_distress(drone);
+    }
+
+    /// Raise the threat one level when vitals turn to distress while a
+    /// threat is active; a calm protectee at Green is left alone
+    fn check_distress(&mut self, drone: &mut DroneState) {
+        let Some(vitals) = &drone.target_vitals else {
+            return;
+        };
+        let limits = &self.config.distress;
+        let mut signs = Vec::new();
+        if let Some(heart_rate) = vitals.heart_rate {
+            if heart_rate > limits.heart_rate_above || heart_rate < limits.heart_rate_below {
+                signs.push(format!("heart rate {} bpm", heart_rate));
+            }
+        }
+        if let Some(spo2) = vitals.blood_oxygen.filter(|spo2| *spo2 < limits.spo2_below) {
+            signs.push(format!("SpO2 {}%", spo2));
+        }
+        if let Some(stress) = vitals.stress_level.filter(|stress| *stress > limits.stress_above) {
+            signs.push(format!("stress {}", stress));
+        }
+
+        let distress = !signs.is_empty();
+        if distress == self.in_distress {
+            return;
+        }
+        self.in_distress = distress;
+        if !distress {
+            tracing::info!("💓 Protectee vitals back within limits");
+            return;
+        }
+        let level = drone.threat_level();
+        tracing::warn!("💓 Protectee distress: {}", signs.join(", "));
+        if level > ThreatLevel::Green {
+            let next = match level {
+                ThreatLevel::Green | ThreatLevel::Yellow => ThreatLevel::Orange,
+                ThreatLevel::Orange => ThreatLevel::Red,
+                ThreatLevel::Red | ThreatLevel::Omega => ThreatLevel::Omega,
+            };
+            drone.escalate_threat(next, format!("Protectee in distress: {}", signs.join(", ")));
+        }
+    }
+}
+
+fn vitals_of(drone: &mut DroneState) -> &mut VitalSigns {
+    let vitals = drone.target_vitals.get_or_insert(VitalSigns {
+        heart_rate: None,
+        blood_oxygen: None,
+        temperature: None,
+        stress_level: None,
+        timestamp: Utc::now(),
+    });
+    vitals.timestamp = Utc::now();
+    vitals
+}
+
+/// Stream the wearable's vitals into the drone until it disconnects; a
+/// wearable without a pulse oximeter is fine
+pub async fn follow(mut monitor: VitalsMonitor, wearable: Arc<dyn GattClient>, drone: Arc<RwLock<DroneState>>) -> ModuleResult {
+    let mut heart_rate = wearable.subscribe(uuid::HEART_RATE_SERVICE, uuid::HEART_RATE_MEASUREMENT).await?;
+    let mut spo2 = match wearable.subscribe(uuid::PULSE_OXIMETER_SERVICE, uuid::PLX_CONTINUOUS_MEASUREMENT).await {
+        Ok(spo2) => Some(spo2),
+        Err(e) => {
+            tracing::info!("💓 Wearable has no pulse oximeter: {}", e);
+            None
+        },
+    };
+    tracing::info!("💓 Streaming protectee vitals");
+    loop {
+        tokio::select! {
+            value = heart_rate.recv() => {
+                let Some(value) = value else {
+                    return Err("wearable disconnected".into());
+                };
+                match HeartRateMeasurement::parse(&value) {
+                    Some(measurement) => monitor.record_heart_rate(&measurement, &mut *drone.write().await),
+                    None => tracing::debug!("Malformed heart rate measurement {:02x?}", value),
+                }
+            },
+            value = async { spo2.as_mut()?.recv().await }, if spo2.is_some() => match value {
+                Some(value) => {
+                    if let Some(reading) = parse_spo2(&value) {
+                        monitor.record_spo2(reading, &mut *drone.write().await);
+                    }
+                },
+                None => spo2 = None,
+            },
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// What to do when a module stops petting the watchdog
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchdogAction {
+    Log,
+    /// Abort the hung loop and let the supervisor start it again
+    RestartModule,
+    /// The module is safety-critical: land
+    EmergencyLanding,
+}
+
+/// Petted by a module once per cycle to prove its loop is still turning
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    epoch: Instant,
+    last_pet_ms: Arc<AtomicU64>,
+}
+
+impl Heartbeat {
+    pub fn pet(&self) {
+        self.last_pet_ms.store(self.epoch.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug)]
+struct Watched {
+    last_pet_ms: Arc<AtomicU64>,
+    timeout: Duration,
+    action: WatchdogAction,
+    missed: bool,
+    misses: u32,
+}
+
+/// A module that went silent, or came back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatEvent {
+    pub module: String,
+    pub silent_for: Duration,
+    pub action: WatchdogAction,
+    /// False when the module has started petting again
+    pub missed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatStatus {
+    pub module: String,
+    pub silent_for: Duration,
+    pub timeout: Duration,
+    pub missed: bool,
+    /// Times the module has gone silent since registration
+    pub misses: u32,
+}
+
+/// Tracks a heartbeat per module and reports the ones that go quiet
+///
+/// A hung loop never crashes, so the supervisor alone cannot see it.
+#[derive(Debug, Clone)]
+pub struct Watchdog {
+    epoch: Instant,
+    modules: Arc<Mutex<HashMap<String, Watched>>>,
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Watchdog {
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            modules: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Watch a module, which must pet the returned heartbeat at least every `timeout`
+    pub fn register(&self, module: &str, timeout: Duration, action: WatchdogAction) -> Heartbeat {
+        let heartbeat = Heartbeat {
+            epoch: self.epoch,
+            last_pet_ms: Arc::new(AtomicU64::new(0)),
+        };
+        heartbeat.pet();
+        self.modules().insert(
+            module.to_string(),
+            Watched {
+                last_pet_ms: Arc::clone(&heartbeat.last_pet_ms),
+                timeout,
+                action,
+                missed: false,
+                misses: 0,
+            },
+        );
+        heartbeat
+    }
+
+    pub fn unregister(&self, module: &str) {
+        self.modules().remove(module);
+    }
+
+    /// Modules that went silent or recovered since the last check
+    pub fn check(&self) -> Vec<HeartbeatEvent> {
+        let now_ms = self.epoch.elapsed().as_millis() as u64;
+        let mut events = Vec::new();
+        for (module, watched) in self.modules().iter_mut() {
+            let silent_for = Duration::from_millis(now_ms.saturating_sub(watched.last_pet_ms.load(Ordering::Relaxed)));
+            let missed = silent_for > watched.timeout;
+            if missed == watched.missed {
+                continue;
+            }
+            watched.missed = missed;
+            if missed {
+                watched.misses += 1;
+            }
+            events.push(HeartbeatEvent {
+                module: module.clone(),
+                silent_for,
+                action: watched.action,
+                missed,
+            });
+        }
+        events
+    }
+
+    pub fn status(&self) -> Vec<HeartbeatStatus> {
+        let now_ms = self.epoch.elapsed().as_millis() as u64;
+        let mut status: Vec<HeartbeatStatus> = self
+            .modules()
+            .iter()
+            .map(|(module, watched)| HeartbeatStatus {
+                module: module.clone(),
+                silent_for: Duration::from_millis(now_ms.saturating_sub(watched.last_pet_ms.load(Ordering::Relaxed))),
+                timeout: watched.timeout,
+                missed: watched.missed,
+                misses: watched.misses,
+            })
+            .collect();
+        status.sort_by(|a, b| a.module.cmp(&b.module));
+        status
+    }
+
+    fn modules(&self) -> std::sync::MutexGuard<'_, HashMap<String, Watched>> {
+        self.modules.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
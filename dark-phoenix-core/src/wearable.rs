@@ -0,0 +1,177 @@
+//! The protectee's wearable over Bluetooth LE, through btleplug (`ble` feature)
+
+use crate::runtime;
+use crate::vitals::{uuid, GattClient};
+use async_trait::async_trait;
+use btleplug::api::bleuuid::uuid_from_u16;
+use btleplug::api::{Central, Manager as _, Peripheral as _, ScanFilter};
+use btleplug::platform::{Manager, Peripheral};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::{mpsc, Mutex};
+use tracing::info;
+
+/// How often the scan results are looked through for the wearable
+const SCAN_POLL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WearableConfig {
+    /// Bluetooth address (`AA:BB:CC:DD:EE:FF`) or advertised name
+    pub device: String,
+    /// How long to scan for the wearable before giving up
+    pub scan_timeout_secs: u64,
+}
+
+impl Default for WearableConfig {
+    fn default() -> Self {
+        Self {
+            device: String::new(),
+            scan_timeout_secs: 30,
+        }
+    }
+}
+
+impl WearableConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.device.trim().is_empty() {
+            problems.push("vitals.wearable.device must not be empty".to_string());
+        }
+        if self.scan_timeout_secs == 0 {
+            problems.push("vitals.wearable.scan_timeout_secs must be positive".to_string());
+        }
+        problems
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum WearableError {
+    #[error("Bluetooth failed: {0}")]
+    Bluetooth(#[from] btleplug::Error),
+    #[error("no Bluetooth adapter")]
+    NoAdapter,
+    #[error("wearable {0} not found")]
+    NotFound(String),
+    #[error("wearable has no characteristic {characteristic:#06x} in service {service:#06x}")]
+    NoCharacteristic { service: u16, characteristic: u16 },
+}
+
+/// A wearable found on the first Bluetooth adapter, connected on first use
+/// and again whenever it has dropped out
+pub struct BleWearable {
+    config: WearableConfig,
+    peripheral: Mutex<Option<Peripheral>>,
+}
+
+impl BleWearable {
+    pub fn new(config: WearableConfig) -> Self {
+        Self {
+            config,
+            peripheral: Mutex::new(None),
+        }
+    }
+
+    /// The wearable, connected and with its services discovered
+    async fn connected(&self) -> Result<Peripheral, WearableError> {
+        let mut peripheral = self.peripheral.lock().await;
+        if let Some(connected) = peripheral.as_ref() {
+            if connected.is_connected().await? {
+                return Ok(connected.clone());
+            }
+        }
+        let wearable = match peripheral.take() {
+            Some(known) => known,
+            None => self.scan().await?,
+        };
+        wearable.connect().await?;
+        wearable.discover_services().await?;
+        info!("💓 Connected to wearable {}", self.config.device);
+        *peripheral = Some(wearable.clone());
+        Ok(wearable)
+    }
+
+    /// Scan for heart rate sensors until the configured one shows up
+    async fn scan(&self) -> Result<Peripheral, WearableError> {
+        let manager = Manager::new().await?;
+        let adapter = manager.adapters().await?.into_iter().next().ok_or(WearableError::NoAdapter)?;
+        adapter
+            .start_scan(ScanFilter {
+                services: vec![uuid_from_u16(uuid::HEART_RATE_SERVICE)],
+            })
+            .await?;
+        let deadline = Instant::now() + Duration::from_secs(self.config.scan_timeout_secs);
+        let found = 'scan: loop {
+            for peripheral in adapter.peripherals().await? {
+                if self.is_wearable(&peripheral).await? {
+                    break 'scan Some(peripheral);
+                }
+            }
+            if Instant::now() >= deadline {
+                break None;
+            }
+            runtime::sleep(SCAN_POLL).await;
+        };
+        adapter.stop_scan().await?;
+        found.ok_or_else(|| WearableError::NotFound(self.config.device.clone()))
+    }
+
+    async fn is_wearable(&self, peripheral: &Peripheral) -> Result<bool, WearableError> {
+        if peripheral.address().to_string().eq_ignore_ascii_case(&self.config.device) {
+            return Ok(true);
+        }
+        let name = peripheral.properties().await?.and_then(|properties| properties.local_name);
+        Ok(name.is_some_and(|name| name == self.config.device))
+    }
+}
+
+#[async_trait]
+impl GattClient for BleWearable {
+    async fn subscribe(
+        &self,
+        service: u16,
+        characteristic: u16,
+    ) -> Result<mpsc::Receiver<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>> {
+        let wearable = self.connected().await?;
+        let (service_uuid, characteristic_uuid) = (uuid_from_u16(service), uuid_from_u16(characteristic));
+        let target = wearable
+            .characteristics()
+            .into_iter()
+            .find(|found| found.service_uuid == service_uuid && found.uuid == characteristic_uuid)
+            .ok_or(WearableError::NoCharacteristic { service, characteristic })?;
+        let mut notifications = wearable.notifications().await?;
+        wearable.subscribe(&target).await?;
+
+        // The stream ends when the wearable disconnects, which closes the
+        // channel and has the caller reconnect
+        let (values, received) = mpsc::channel(32);
+        runtime::spawn(async move {
+            while let Some(notification) = notifications.next().await {
+                if notification.uuid == characteristic_uuid && values.send(notification.value).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(received)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VitalsConfig;
+
+    #[test]
+    fn wearable_is_optional_and_needs_a_device() {
+        let vitals: VitalsConfig = serde_json::from_str("{}").unwrap();
+        assert!(vitals.wearable.is_none());
+        let vitals: VitalsConfig = serde_json::from_str(r#"{"wearable": {"device": "Polar H10 1A2B3C4D"}}"#).unwrap();
+        let wearable = vitals.wearable.unwrap();
+        assert_eq!(wearable.scan_timeout_secs, 30);
+        assert!(wearable.problems().is_empty());
+        assert_eq!(WearableConfig::default().problems(), vec!["vitals.wearable.device must not be empty"]);
+    }
+}
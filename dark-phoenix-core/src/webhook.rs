@@ -0,0 +1,572 @@
+//! Webhook notifications of mission events
+
+use crate::{DroneState, EventType, MissionEvent, ModuleResult, OutboundMessage, Outbox, OutboxConfig, OutboxPriority, ThreatLevel};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+/// Header with the HMAC of the timestamp and body, when the webhook has a secret
+pub const SIGNATURE_HEADER: &str = "x-phoenix-signature";
+/// Header with the Unix time the body was signed at
+pub const TIMESTAMP_HEADER: &str = "x-phoenix-timestamp";
+
+/// Placeholders a template may use
+pub const PLACEHOLDERS: &[&str] = &[
+    "drone",
+    "drone_id",
+    "class",
+    "event_type",
+    "description",
+    "threat_level",
+    "timestamp",
+    "latitude",
+    "longitude",
+    "actions",
+    "sequence",
+    "suppressed",
+    "event",
+];
+
+/// What kind of event a webhook is sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventClass {
+    /// The threat level went up, or Omega was authorized
+    ThreatEscalation,
+    /// Fire suppression started discharging
+    FireActivation,
+    /// Loads shed to save power, a worn pack, or a battery failsafe
+    LowBattery,
+    /// The panic button, or the protectee in distress or out of reach
+    Panic,
+    /// Police or medical aid called
+    Emergency,
+    /// Intrusion attempts and refused commands
+    Security,
+    /// Failsafes, geofence breaches and a lost command link
+    Failsafe,
+    /// Malfunctions and degraded sensors
+    System,
+    /// Everything else: patrols, pairings, fleet changes, ...
+    Other,
+}
+
+impl EventClass {
+    pub fn of(event: &MissionEvent) -> Self {
+        match event.event_type {
+            EventType::ThreatDetected | EventType::OmegaAuthorized => EventClass::ThreatEscalation,
+            EventType::FireSuppressed => EventClass::FireActivation,
+            EventType::LoadShed | EventType::BatteryDegraded => EventClass::LowBattery,
+            // The trigger is only in the description, as `FailsafeTrigger` displays it
+            EventType::FailsafeEngaged if event.description.contains("battery") => EventClass::LowBattery,
+            EventType::PanicActivated | EventType::ProtecteeDistress | EventType::ProtecteeLost => EventClass::Panic,
+            EventType::PoliceContacted | EventType::MedicalAidDeployed => EventClass::Emergency,
+            EventType::HackingAttempt | EventType::AccessDenied | EventType::CommandRejected => EventClass::Security,
+            EventType::FailsafeEngaged | EventType::GeofenceBreach | EventType::LinkLost => EventClass::Failsafe,
+            EventType::SystemMalfunction | EventType::SensorDegraded => EventClass::System,
+            _ => EventClass::Other,
+        }
+    }
+
+    /// Never held back by the rate limit
+    pub fn is_urgent(self) -> bool {
+        matches!(self, EventClass::ThreatEscalation | EventClass::FireActivation | EventClass::Panic | EventClass::Emergency)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventClass::ThreatEscalation => "threat_escalation",
+            EventClass::FireActivation => "fire_activation",
+            EventClass::LowBattery => "low_battery",
+            EventClass::Panic => "panic",
+            EventClass::Emergency => "emergency",
+            EventClass::Security => "security",
+            EventClass::Failsafe => "failsafe",
+            EventClass::System => "system",
+            EventClass::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for EventClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// Names the webhook in logs and the outbox
+    pub name: String,
+    pub url: String,
+    /// Classes of event it is sent (empty = every class but `other`)
+    pub classes: Vec<EventClass>,
+    /// Body with `{{placeholder}}`s (absent = the event as JSON)
+    pub template: Option<String>,
+    pub content_type: String,
+    /// Extra request headers, e.g. for ntfy's `Title` or `Priority`
+    pub headers: BTreeMap<String, String>,
+    /// Signs each body (absent = unsigned)
+    pub secret: Option<String>,
+    pub timeout_ms: u64,
+    /// Tries per delivery before it goes to the outbox
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubling after each
+    pub backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// Deliveries a minute, on average
+    pub per_minute: u32,
+    /// Deliveries that may go out back to back
+    pub burst: u32,
+    /// Hold back repeats of the same kind of event at the same threat level
+    pub repeat_cooldown_secs: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            url: String::new(),
+            classes: Vec::new(),
+            template: None,
+            content_type: "application/json".to_string(),
+            headers: BTreeMap::new(),
+            secret: None,
+            timeout_ms: 5000,
+            max_attempts: 5,
+            backoff_ms: 1000,
+            max_backoff_ms: 60_000,
+            per_minute: 10,
+            burst: 3,
+            repeat_cooldown_secs: 300,
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let name = &self.name;
+        if name.is_empty() {
+            problems.push("webhook name must not be empty".to_string());
+        }
+        if !(self.url.starts_with("https://") || self.url.starts_with("http://")) {
+            problems.push(format!("webhook '{}' url must be http:// or https://", name));
+        }
+        if self.max_attempts == 0 {
+            problems.push(format!("webhook '{}' max_attempts must be at least 1", name));
+        }
+        if self.timeout_ms == 0 {
+            problems.push(format!("webhook '{}' timeout_ms must be positive", name));
+        }
+        if self.per_minute == 0 || self.burst == 0 {
+            problems.push(format!("webhook '{}' per_minute and burst must be positive", name));
+        }
+        if self.secret.as_deref() == Some("") {
+            problems.push(format!("webhook '{}' secret must not be empty", name));
+        }
+        if let Some(template) = &self.template {
+            let sample = sample_values();
+            match render(template, &sample, self.is_json()) {
+                Err(placeholder) => problems.push(format!(
+                    "webhook '{}' template has unknown placeholder {{{{{}}}}} (expected one of {})",
+                    name,
+                    placeholder,
+                    PLACEHOLDERS.join(", ")
+                )),
+                Ok(body) if self.is_json() && serde_json::from_str::<serde_json::Value>(&body).is_err() => {
+                    problems.push(format!("webhook '{}' template does not render to JSON", name))
+                },
+                Ok(_) => {},
+            }
+        }
+        problems
+    }
+
+    fn wants(&self, class: EventClass) -> bool {
+        if self.classes.is_empty() {
+            class != EventClass::Other
+        } else {
+            self.classes.contains(&class)
+        }
+    }
+
+    fn is_json(&self) -> bool {
+        self.content_type.contains("json")
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhooksConfig {
+    pub hooks: Vec<WebhookConfig>,
+    /// Keeps deliveries that ran out of retries (absent = they are dropped)
+    pub outbox: Option<OutboxConfig>,
+}
+
+impl WebhooksConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems: Vec<String> = self.hooks.iter().flat_map(WebhookConfig::problems).collect();
+        for (i, hook) in self.hooks.iter().enumerate() {
+            if self.hooks[..i].iter().any(|earlier| earlier.name == hook.name) {
+                problems.push(format!("webhook '{}' is defined twice", hook.name));
+            }
+        }
+        problems.extend(self.outbox.iter().flat_map(|outbox| outbox.problems()).map(|problem| format!("webhooks.{}", problem)));
+        problems
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// Worth retrying: a server error, a timeout or too many requests
+    #[error("answered {status}")]
+    Unavailable { status: u16, retry_after: Option<Duration> },
+    /// Retrying would get the same answer
+    #[error("refused with {0}")]
+    Rejected(u16),
+    #[error("no webhook named '{0}'")]
+    Unknown(String),
+}
+
+/// A rendered body on its way to a webhook
+#[derive(Debug, Clone)]
+pub struct WebhookDelivery {
+    pub hook: String,
+    pub priority: OutboxPriority,
+    pub body: Vec<u8>,
+}
+
+impl WebhookDelivery {
+    fn into_outbound(self) -> OutboundMessage {
+        OutboundMessage::new(self.hook, self.priority, self.body)
+    }
+}
+
+#[derive(Debug)]
+struct HookState {
+    config: Arc<WebhookConfig>,
+    tokens: f64,
+    refilled: DateTime<Utc>,
+    last_sent: HashMap<(EventType, ThreatLevel), DateTime<Utc>>,
+    suppressed: HashMap<EventType, u32>,
+}
+
+impl HookState {
+    /// Whether the event may go out now, taking a token if so
+    fn admit(&mut self, event: &MissionEvent, class: EventClass, now: DateTime<Utc>) -> bool {
+        let key = (event.event_type.clone(), event.threat_level);
+        let cooldown = chrono::Duration::seconds(self.config.repeat_cooldown_secs as i64);
+        if self.last_sent.get(&key).is_some_and(|sent| now - *sent < cooldown) {
+            return false;
+        }
+        let per_second = f64::from(self.config.per_minute) / 60.0;
+        let elapsed = (now - self.refilled).num_milliseconds().max(0) as f64 / 1000.0;
+        self.tokens = (self.tokens + elapsed * per_second).min(f64::from(self.config.burst));
+        self.refilled = now;
+        if !class.is_urgent() && event.threat_level < ThreatLevel::Red {
+            if self.tokens < 1.0 {
+                return false;
+            }
+            self.tokens -= 1.0;
+        }
+        self.last_sent.insert(key, now);
+        true
+    }
+}
+
+/// Picks out the mission events each webhook is sent and renders them
+#[derive(Debug)]
+pub struct Webhooks {
+    hooks: Vec<HookState>,
+    /// Sequence of the next event to look at
+    next_sequence: u64,
+}
+
+impl Webhooks {
+    pub fn new(config: &WebhooksConfig) -> Self {
+        let now = Utc::now();
+        Self {
+            hooks: config
+                .hooks
+                .iter()
+                .map(|hook| HookState {
+                    tokens: f64::from(hook.burst),
+                    config: Arc::new(hook.clone()),
+                    refilled: now,
+                    last_sent: HashMap::new(),
+                    suppressed: HashMap::new(),
+                })
+                .collect(),
+            next_sequence: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Every webhook's configuration, for sending
+    pub fn configs(&self) -> Vec<Arc<WebhookConfig>> {
+        self.hooks.iter().map(|hook| Arc::clone(&hook.config)).collect()
+    }
+
+    /// Deliveries for the events logged since the last call
+    pub fn sync(&mut self, drone: &DroneState, now: DateTime<Utc>) -> Vec<WebhookDelivery> {
+        let start = drone.mission_log.partition_point(|event| event.sequence < self.next_sequence);
+        let mut deliveries = Vec::new();
+        for event in &drone.mission_log[start..] {
+            self.next_sequence = event.sequence + 1;
+            let class = EventClass::of(event);
+            for hook in self.hooks.iter_mut().filter(|hook| hook.config.wants(class)) {
+                if !hook.admit(event, class, now) {
+                    *hook.suppressed.entry(event.event_type.clone()).or_default() += 1;
+                    tracing::debug!("📣 Held back {:?} from webhook '{}'", event.event_type, hook.config.name);
+                    continue;
+                }
+                let suppressed = hook.suppressed.remove(&event.event_type).unwrap_or(0);
+                let values = event_values(drone, event, class, suppressed);
+                let body = match &hook.config.template {
+                    Some(template) => render(template, &values, hook.config.is_json()).unwrap_or_else(|placeholder| {
+                        tracing::warn!("📣 Webhook '{}' template has unknown placeholder {}", hook.config.name, placeholder);
+                        String::new()
+                    }),
+                    None => default_body(&values),
+                };
+                deliveries.push(WebhookDelivery {
+                    hook: hook.config.name.clone(),
+                    priority: OutboxPriority::of_level(event.threat_level),
+                    body: body.into_bytes(),
+                });
+            }
+        }
+        deliveries
+    }
+}
+
+/// POST `body` to the webhook once
+pub async fn deliver(http: &reqwest::Client, hook: &WebhookConfig, body: &[u8]) -> Result<(), WebhookError> {
+    let mut request = http
+        .post(&hook.url)
+        .timeout(Duration::from_millis(hook.timeout_ms))
+        .header(reqwest::header::CONTENT_TYPE, &hook.content_type);
+    for (name, value) in &hook.headers {
+        request = request.header(name, value);
+    }
+    if let Some(secret) = &hook.secret {
+        let timestamp = Utc::now().timestamp().to_string();
+        request = request
+            .header(SIGNATURE_HEADER, signature(secret, &timestamp, body))
+            .header(TIMESTAMP_HEADER, timestamp);
+    }
+    let response = request.body(body.to_vec()).send().await?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    if status.is_server_error() || status.as_u16() == 408 || status.as_u16() == 429 {
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+            .map(Duration::from_secs);
+        return Err(WebhookError::Unavailable {
+            status: status.as_u16(),
+            retry_after,
+        });
+    }
+    Err(WebhookError::Rejected(status.as_u16()))
+}
+
+/// Deliver with retries, backing off between them
+pub async fn deliver_with_retries(http: &reqwest::Client, hook: &WebhookConfig, body: &[u8]) -> Result<(), WebhookError> {
+    let mut backoff = Duration::from_millis(hook.backoff_ms);
+    let max_backoff = Duration::from_millis(hook.max_backoff_ms);
+    let mut attempt = 1;
+    loop {
+        let error = match deliver(http, hook, body).await {
+            Ok(()) => return Ok(()),
+            Err(e @ WebhookError::Rejected(_)) => return Err(e),
+            Err(e) if attempt >= hook.max_attempts => return Err(e),
+            Err(e) => e,
+        };
+        let wait = match &error {
+            WebhookError::Unavailable { retry_after: Some(after), .. } => (*after).min(max_backoff),
+            _ => backoff,
+        };
+        tracing::warn!("📣 Webhook '{}' failed ({}), retrying in {:?}", hook.name, error, wait);
+        crate::runtime::sleep(wait).await;
+        backoff = (backoff * 2).min(max_backoff);
+        attempt += 1;
+    }
+}
+
+/// `sha256=` and the hex HMAC-SHA256 of `{timestamp}.{body}`
+pub fn signature(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes any key length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Send new events to the webhooks until the drone is gone, parking
+/// deliveries that run out of retries in `outbox`
+pub async fn run(webhooks: Arc<Mutex<Webhooks>>, outbox: Option<Outbox>, drone: Arc<RwLock<DroneState>>) -> ModuleResult {
+    let http = reqwest::Client::new();
+    let hooks: HashMap<String, Arc<WebhookConfig>> = lock(&webhooks).configs().into_iter().map(|hook| (hook.name.clone(), hook)).collect();
+    let hooks = Arc::new(hooks);
+    let draining = Arc::new(tokio::sync::Mutex::new(()));
+    let mut poll = crate::runtime::interval(Duration::from_secs(1));
+    let mut retry = crate::runtime::interval(Duration::from_secs(60));
+    let mut transitions = drone.read().await.subscribe_threat_transitions();
+    loop {
+        tokio::select! {
+            received = transitions.recv() => match received {
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => return Ok(()),
+                _ => continue,
+            },
+            _ = retry.tick() => {
+                if let Some(outbox) = outbox.clone().filter(|outbox| !outbox.is_empty()) {
+                    crate::runtime::spawn(drain(outbox, http.clone(), Arc::clone(&hooks), Arc::clone(&draining)));
+                }
+            },
+            _ = poll.tick() => {
+                let deliveries = {
+                    let drone = drone.read().await;
+                    lock(&webhooks).sync(&drone, Utc::now())
+                };
+                for delivery in deliveries {
+                    let Some(hook) = hooks.get(&delivery.hook).cloned() else { continue };
+                    let (http, outbox, hooks, draining) = (http.clone(), outbox.clone(), Arc::clone(&hooks), Arc::clone(&draining));
+                    crate::runtime::spawn(async move {
+                        match deliver_with_retries(&http, &hook, &delivery.body).await {
+                            Ok(()) => {
+                                tracing::debug!("📣 Delivered to webhook '{}'", hook.name);
+                                if let Some(outbox) = outbox.filter(|outbox| !outbox.is_empty()) {
+                                    drain(outbox, http, hooks, draining).await;
+                                }
+                            },
+                            Err(e) => match outbox {
+                                Some(outbox) if !matches!(e, WebhookError::Rejected(_)) => {
+                                    tracing::warn!("📣 Webhook '{}' unreachable ({}); queued for later", hook.name, e);
+                                    if let Err(e) = outbox.push(delivery.into_outbound()) {
+                                        tracing::error!("📣 Could not queue the webhook delivery: {}", e);
+                                    }
+                                },
+                                _ => tracing::error!("📣 Webhook '{}' delivery dropped: {}", hook.name, e),
+                            },
+                        }
+                    });
+                }
+            },
+        }
+    }
+}
+
+/// Send what the outbox holds, one drain at a time
+async fn drain(outbox: Outbox, http: reqwest::Client, hooks: Arc<HashMap<String, Arc<WebhookConfig>>>, draining: Arc<tokio::sync::Mutex<()>>) {
+    let Ok(_draining) = draining.try_lock() else { return };
+    outbox
+        .drain(|message| {
+            let (http, hooks) = (http.clone(), Arc::clone(&hooks));
+            async move {
+                match hooks.get(&message.destination) {
+                    Some(hook) => match deliver(&http, hook, &message.payload).await {
+                        // Nothing would change on a retry, so it is not kept
+                        Err(WebhookError::Rejected(status)) => {
+                            tracing::error!("📣 Webhook '{}' refused a queued delivery with {}; dropped", hook.name, status);
+                            Ok(())
+                        },
+                        result => result,
+                    },
+                    None => {
+                        tracing::warn!("📣 Dropping a queued delivery for removed webhook '{}'", message.destination);
+                        Ok(())
+                    },
+                }
+            }
+        })
+        .await;
+}
+
+fn lock(webhooks: &Mutex<Webhooks>) -> std::sync::MutexGuard<'_, Webhooks> {
+    webhooks.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn event_values(drone: &DroneState, event: &MissionEvent, class: EventClass, suppressed: u32) -> BTreeMap<&'static str, serde_json::Value> {
+    use serde_json::Value;
+    BTreeMap::from([
+        ("drone", Value::from(drone.name.clone())),
+        ("drone_id", Value::from(drone.id.to_string())),
+        ("class", Value::from(class.as_str())),
+        ("event_type", Value::from(format!("{:?}", event.event_type))),
+        ("description", Value::from(event.description.clone())),
+        ("threat_level", Value::from(event.threat_level.as_str())),
+        ("timestamp", Value::from(event.timestamp.to_rfc3339())),
+        ("latitude", Value::from(event.position.latitude)),
+        ("longitude", Value::from(event.position.longitude)),
+        ("actions", Value::from(event.response_actions.join("; "))),
+        ("sequence", Value::from(event.sequence)),
+        ("suppressed", Value::from(suppressed)),
+        ("event", serde_json::to_value(event).unwrap_or_default()),
+    ])
+}
+
+fn default_body(values: &BTreeMap<&'static str, serde_json::Value>) -> String {
+    let field = |name| values.get(name).cloned().unwrap_or_default();
+    serde_json::json!({
+        "drone": field("drone"),
+        "drone_id": field("drone_id"),
+        "class": field("class"),
+        "suppressed": field("suppressed"),
+        "event": field("event"),
+    })
+    .to_string()
+}
+
+/// Fill in `{{placeholder}}`s; strings are escaped for use inside a JSON
+/// string when `json`, anything else is written as JSON. Fails with the
+/// first unknown placeholder.
+fn render(template: &str, values: &BTreeMap<&'static str, serde_json::Value>, json: bool) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else { break };
+        out.push_str(&rest[..start]);
+        let name = rest[start + 2..start + end].trim();
+        match values.get(name) {
+            Some(serde_json::Value::String(text)) if json => {
+                let quoted = serde_json::Value::from(text.as_str()).to_string();
+                out.push_str(&quoted[1..quoted.len() - 1]);
+            },
+            Some(serde_json::Value::String(text)) => out.push_str(text),
+            Some(value) => out.push_str(&value.to_string()),
+            None => return Err(name.to_string()),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Values to check a template against
+fn sample_values() -> BTreeMap<&'static str, serde_json::Value> {
+    let mut drone = DroneState::new("Sample \"drone\"".to_string());
+    drone.log_event(EventType::ThreatDetected, "Threat level escalated to RED: sample".to_string(), vec!["Sample action".to_string()]);
+    let event = drone.mission_log.last().cloned().expect("just logged");
+    event_values(&drone, &event, EventClass::ThreatEscalation, 0)
+}
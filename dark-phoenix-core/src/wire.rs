@@ -0,0 +1,341 @@
+//! Compact binary serialization (`binary-wire` feature)
+
+use crate::{DroneState, MissionEvent, Position, ThreatLevel};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+const MAGIC: u8 = 0xD9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WireFormat {
+    Cbor,
+    Postcard,
+}
+
+impl WireFormat {
+    fn code(self) -> u8 {
+        match self {
+            WireFormat::Cbor => 0,
+            WireFormat::Postcard => 1,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(WireFormat::Cbor),
+            1 => Some(WireFormat::Postcard),
+            _ => None,
+        }
+    }
+}
+
+/// What a message holds; codes are fixed once assigned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WireKind {
+    DroneState,
+    MissionEvent,
+    ThreatAssessment,
+    StatusFrame,
+    EventFrame,
+    ThreatFrame,
+    SignedCommand,
+    CommandAck,
+}
+
+impl WireKind {
+    fn code(self) -> u8 {
+        match self {
+            WireKind::DroneState => 1,
+            WireKind::MissionEvent => 2,
+            WireKind::ThreatAssessment => 3,
+            WireKind::StatusFrame => 4,
+            WireKind::EventFrame => 5,
+            WireKind::ThreatFrame => 6,
+            WireKind::SignedCommand => 7,
+            WireKind::CommandAck => 8,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            1 => Some(WireKind::DroneState),
+            2 => Some(WireKind::MissionEvent),
+            3 => Some(WireKind::ThreatAssessment),
+            4 => Some(WireKind::StatusFrame),
+            5 => Some(WireKind::EventFrame),
+            6 => Some(WireKind::ThreatFrame),
+            7 => Some(WireKind::SignedCommand),
+            8 => Some(WireKind::CommandAck),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum WireError {
+    #[error("not a wire message")]
+    NotAMessage,
+    #[error("unknown wire format {0}")]
+    UnknownFormat(u8),
+    #[error("unknown message kind {0}")]
+    UnknownKind(u8),
+    #[error("expected {expected:?}, got {got:?}")]
+    WrongKind { expected: WireKind, got: WireKind },
+    #[error("{0:?} cannot be sent as {1:?}")]
+    UnsupportedFormat(WireKind, WireFormat),
+    #[error("{kind:?} schema version {version} is not readable by this build (knows up to {known})")]
+    UnsupportedVersion { kind: WireKind, version: u8, known: u8 },
+    #[error("CBOR encoding failed: {0}")]
+    CborEncode(String),
+    #[error("CBOR decoding failed: {0}")]
+    CborDecode(String),
+    #[error("postcard failed: {0}")]
+    Postcard(#[from] postcard::Error),
+}
+
+/// The header of a wire message, for dispatching on its kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WireHeader {
+    pub format: WireFormat,
+    pub kind: WireKind,
+    pub version: u8,
+}
+
+/// A type with a versioned wire schema
+pub trait WireSchema: Serialize + DeserializeOwned {
+    const KIND: WireKind;
+    /// Bumped whenever a postcard layout changes; informational for CBOR
+    const VERSION: u8;
+    /// Formats the type may be sent in
+    const FORMATS: &'static [WireFormat];
+
+    /// Read a body written under an older schema `version`. CBOR bodies
+    /// decode as they are; a postcard frame overrides this to read the
+    /// layouts it has had.
+    fn upgrade(format: WireFormat, version: u8, body: &[u8]) -> Result<Self, WireError> {
+        match format {
+            WireFormat::Cbor => from_cbor(body),
+            WireFormat::Postcard => Err(WireError::UnsupportedVersion {
+                kind: Self::KIND,
+                version,
+                known: Self::VERSION,
+            }),
+        }
+    }
+}
+
+/// Encode `value` with its header
+pub fn encode<T: WireSchema>(value: &T, format: WireFormat) -> Result<Vec<u8>, WireError> {
+    if !T::FORMATS.contains(&format) {
+        return Err(WireError::UnsupportedFormat(T::KIND, format));
+    }
+    let mut out = vec![MAGIC, (format.code() << 6) | T::KIND.code(), T::VERSION];
+    match format {
+        WireFormat::Cbor => ciborium::into_writer(value, &mut out).map_err(|e| WireError::CborEncode(e.to_string()))?,
+        WireFormat::Postcard => out = postcard::to_extend(value, out)?,
+    }
+    Ok(out)
+}
+
+/// The header of `bytes`, without decoding the body
+pub fn peek(bytes: &[u8]) -> Result<WireHeader, WireError> {
+    let [magic, packed, version, ..] = *bytes else { return Err(WireError::NotAMessage) };
+    if magic != MAGIC {
+        return Err(WireError::NotAMessage);
+    }
+    Ok(WireHeader {
+        format: WireFormat::from_code(packed >> 6).ok_or(WireError::UnknownFormat(packed >> 6))?,
+        kind: WireKind::from_code(packed & 0x3f).ok_or(WireError::UnknownKind(packed & 0x3f))?,
+        version,
+    })
+}
+
+/// Decode a message of type `T`, in whichever format and schema version it
+/// was written
+pub fn decode<T: WireSchema>(bytes: &[u8]) -> Result<T, WireError> {
+    let header = peek(bytes)?;
+    if header.kind != T::KIND {
+        return Err(WireError::WrongKind { expected: T::KIND, got: header.kind });
+    }
+    let body = &bytes[3..];
+    match (header.format, header.version) {
+        (WireFormat::Postcard, version) if version == T::VERSION => Ok(postcard::from_bytes(body)?),
+        (WireFormat::Postcard, version) if version > T::VERSION => Err(WireError::UnsupportedVersion {
+            kind: T::KIND,
+            version,
+            known: T::VERSION,
+        }),
+        (WireFormat::Cbor, version) if version >= T::VERSION => from_cbor(body),
+        (format, version) => T::upgrade(format, version, body),
+    }
+}
+
+fn from_cbor<T: DeserializeOwned>(body: &[u8]) -> Result<T, WireError> {
+    ciborium::from_reader(body).map_err(|e| WireError::CborDecode(e.to_string()))
+}
+
+impl WireSchema for DroneState {
+    const KIND: WireKind = WireKind::DroneState;
+    const VERSION: u8 = 1;
+    const FORMATS: &'static [WireFormat] = &[WireFormat::Cbor];
+}
+
+impl WireSchema for MissionEvent {
+    const KIND: WireKind = WireKind::MissionEvent;
+    const VERSION: u8 = 1;
+    const FORMATS: &'static [WireFormat] = &[WireFormat::Cbor];
+}
+
+/// Degrees as integer units of 1e-7, as MAVLink carries them (about 1 cm)
+pub fn to_e7(degrees: f64) -> i32 {
+    (degrees * 1e7).round() as i32
+}
+
+pub fn from_e7(e7: i32) -> f64 {
+    f64::from(e7) / 1e7
+}
+
+fn to_time(secs: i64) -> DateTime<Utc> {
+    Utc.timestamp_opt(secs, 0).single().unwrap_or_default()
+}
+
+/// The drone's status in a few dozen bytes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusFrame {
+    pub drone: Uuid,
+    /// Unix seconds
+    pub sent: i64,
+    pub threat_level: ThreatLevel,
+    pub latitude_e7: i32,
+    pub longitude_e7: i32,
+    /// Decimetres
+    pub altitude_dm: i32,
+    /// Percent
+    pub battery: u8,
+    pub flight_time_remaining: u32,
+    /// `StatusFrame::GPS_LOCK` and the rest
+    pub flags: u8,
+    /// 0 inside, 1 approaching, 2 breached
+    pub geofence: u8,
+    pub degraded_sensors: u8,
+}
+
+impl StatusFrame {
+    pub const GPS_LOCK: u8 = 1;
+    pub const COMMUNICATION: u8 = 1 << 1;
+    pub const SHIELD_DEPLOYED: u8 = 1 << 2;
+    pub const FIRE_SUPPRESSION_READY: u8 = 1 << 3;
+    pub const PROTECTEE_TRACKED: u8 = 1 << 4;
+
+    pub fn of(drone: &DroneState, now: DateTime<Utc>) -> Self {
+        let health = &drone.system_health;
+        let flags = [
+            (health.gps_lock, Self::GPS_LOCK),
+            (health.communication_status, Self::COMMUNICATION),
+            (health.shield_deployed, Self::SHIELD_DEPLOYED),
+            (health.fire_suppression_ready, Self::FIRE_SUPPRESSION_READY),
+            (drone.protectee.is_some(), Self::PROTECTEE_TRACKED),
+        ]
+        .into_iter()
+        .filter(|(set, _)| *set)
+        .fold(0, |flags, (_, flag)| flags | flag);
+        Self {
+            drone: drone.id,
+            sent: now.timestamp(),
+            threat_level: drone.threat_level(),
+            latitude_e7: to_e7(drone.position.latitude),
+            longitude_e7: to_e7(drone.position.longitude),
+            altitude_dm: (drone.position.altitude * 10.0).round() as i32,
+            battery: health.battery_level,
+            flight_time_remaining: health.flight_time_remaining,
+            flags,
+            geofence: drone.geofence_status().severity(),
+            degraded_sensors: health.degraded_sensors.len().min(u8::MAX as usize) as u8,
+        }
+    }
+
+    pub fn has(&self, flag: u8) -> bool {
+        self.flags & flag != 0
+    }
+
+    pub fn position(&self) -> Position {
+        Position {
+            latitude: from_e7(self.latitude_e7),
+            longitude: from_e7(self.longitude_e7),
+            altitude: f64::from(self.altitude_dm) / 10.0,
+            timestamp: to_time(self.sent),
+        }
+    }
+
+    pub fn sent(&self) -> DateTime<Utc> {
+        to_time(self.sent)
+    }
+}
+
+impl WireSchema for StatusFrame {
+    const KIND: WireKind = WireKind::StatusFrame;
+    const VERSION: u8 = 1;
+    const FORMATS: &'static [WireFormat] = &[WireFormat::Postcard, WireFormat::Cbor];
+}
+
+/// A mission event cut down to what fits a small packet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventFrame {
+    pub drone: Uuid,
+    pub sequence: u64,
+    /// Unix seconds
+    pub timestamp: i64,
+    /// The `EventType` by name, as variants are not only ever appended
+    pub event_type: String,
+    pub threat_level: ThreatLevel,
+    pub latitude_e7: i32,
+    pub longitude_e7: i32,
+    /// Cut to `EventFrame::MAX_DESCRIPTION` bytes
+    pub description: String,
+}
+
+impl EventFrame {
+    pub const MAX_DESCRIPTION: usize = 64;
+
+    pub fn of(drone: Uuid, event: &MissionEvent) -> Self {
+        let mut description = event.description.clone();
+        if description.len() > Self::MAX_DESCRIPTION {
+            let mut end = Self::MAX_DESCRIPTION;
+            while !description.is_char_boundary(end) {
+                end -= 1;
+            }
+            description.truncate(end);
+        }
+        Self {
+            drone,
+            sequence: event.sequence,
+            timestamp: event.timestamp.timestamp(),
+            event_type: format!("{:?}", event.event_type),
+            threat_level: event.threat_level,
+            latitude_e7: to_e7(event.position.latitude),
+            longitude_e7: to_e7(event.position.longitude),
+            description,
+        }
+    }
+
+    /// The event type, if this build knows it
+    pub fn event_type(&self) -> Option<crate::EventType> {
+        serde_json::from_value(serde_json::Value::String(self.event_type.clone())).ok()
+    }
+
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        to_time(self.timestamp)
+    }
+}
+
+impl WireSchema for EventFrame {
+    const KIND: WireKind = WireKind::EventFrame;
+    const VERSION: u8 = 1;
+    const FORMATS: &'static [WireFormat] = &[WireFormat::Postcard, WireFormat::Cbor];
+}
+
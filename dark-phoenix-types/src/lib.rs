@@ -0,0 +1,159 @@
+//! Dark Phoenix data types shared with firmware
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Core threat level classification system
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ThreatLevel {
+    /// No threats detected - all systems nominal
+    Green = 0,
+    /// Minor anomaly - increased awareness mode
+    Yellow = 1,
+    /// Moderate threat - defensive measures activated
+    Orange = 2,
+    /// High threat - all deterrence systems online
+    Red = 3,
+    /// Critical threat - lethal force authorized, maximum protection
+    Omega = 4,
+}
+
+impl ThreatLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThreatLevel::Green => "GREEN",
+            ThreatLevel::Yellow => "YELLOW",
+            ThreatLevel::Orange => "ORANGE", 
+            ThreatLevel::Red => "RED",
+            ThreatLevel::Omega => "OMEGA",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ThreatLevel::Green => "All systems nominal. Guardian mode active.",
+            ThreatLevel::Yellow => "Anomaly detected. Heightened awareness engaged.",
+            ThreatLevel::Orange => "Moderate threat identified. Defensive protocols online.",
+            ThreatLevel::Red => "High threat confirmed. All deterrence systems activated.",
+            ThreatLevel::Omega => "Critical threat. Dark Phoenix rising. Maximum protection authorized.",
+        }
+    }
+
+    /// The next level down (Green stays Green)
+    pub fn step_down(&self) -> ThreatLevel {
+        match self {
+            ThreatLevel::Green | ThreatLevel::Yellow => ThreatLevel::Green,
+            ThreatLevel::Orange => ThreatLevel::Yellow,
+            ThreatLevel::Red => ThreatLevel::Orange,
+            ThreatLevel::Omega => ThreatLevel::Red,
+        }
+    }
+}
+
+/// Position and movement data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[cfg(feature = "std")]
+impl Position {
+    /// Mean Earth radius used for short-range local approximations (metres)
+    pub const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+    /// The point `distance_m` away along `bearing_deg` (clockwise from north),
+    /// at the same altitude and time
+    pub fn destination(&self, bearing_deg: f64, distance_m: f64) -> Position {
+        let bearing = bearing_deg.to_radians();
+        let north = distance_m * bearing.cos();
+        let east = distance_m * bearing.sin();
+        Position {
+            latitude: self.latitude + (north / Self::EARTH_RADIUS_M).to_degrees(),
+            longitude: self.longitude + (east / (Self::EARTH_RADIUS_M * self.latitude.to_radians().cos())).to_degrees(),
+            altitude: self.altitude,
+            timestamp: self.timestamp,
+        }
+    }
+
+    /// Ground distance to `other` in metres, ignoring altitude; accurate over
+    /// the few kilometres a patrol covers
+    pub fn distance_m(&self, other: &Position) -> f64 {
+        let north = (other.latitude - self.latitude).to_radians() * Self::EARTH_RADIUS_M;
+        let mean_latitude = ((self.latitude + other.latitude) / 2.0).to_radians();
+        let east = (other.longitude - self.longitude).to_radians() * Self::EARTH_RADIUS_M * mean_latitude.cos();
+        north.hypot(east)
+    }
+
+    /// Bearing to `other`, clockwise from north (0 when they coincide)
+    pub fn bearing_deg(&self, other: &Position) -> f64 {
+        let north = (other.latitude - self.latitude).to_radians();
+        let east = (other.longitude - self.longitude).to_radians() * self.latitude.to_radians().cos();
+        east.atan2(north).to_degrees().rem_euclid(360.0)
+    }
+}
+
+/// Vitals and health monitoring
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VitalSigns {
+    pub heart_rate: Option<u16>,
+    pub blood_oxygen: Option<u8>,
+    pub temperature: Option<f32>,
+    pub stress_level: Option<u8>, // 0-100
+    pub timestamp: DateTime<Utc>,
+}
+
+/// What a mission event records
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EventType {
+    ThreatDetected,
+    ThreatDeEscalated,
+    OmegaAuthorized,
+    TerrenceActivated,
+    PoliceContacted,
+    ShieldDeployed,
+    FireSuppressed,
+    MedicalAidDeployed,
+    HackingAttempt,
+    SystemMalfunction,
+    SensorDegraded,
+    SensorRestored,
+    ThreatAcknowledged,
+    MissionComplete,
+    GeofenceBreach,
+    FailsafeEngaged,
+    DeterrenceHandoff,
+    PatrolStarted,
+    WaypointReached,
+    PatrolPaused,
+    PatrolResumed,
+    ProtecteePaired,
+    ProtecteeLost,
+    ProtecteeDistress,
+    PanicActivated,
+    PanicCancelled,
+    LoadShed,
+    BatteryDegraded,
+    PreflightPassed,
+    PreflightFailed,
+    ForceArmed,
+    Armed,
+    Disarmed,
+    AccessDenied,
+    CommandRejected,
+    LinkLost,
+    LinkRestored,
+    LinkFailover,
+    ControllerPaired,
+    ControllerRevoked,
+    FleetLeaderChanged,
+    FleetTaskAssigned,
+    ObstacleDetected,
+    ObstacleCleared,
+    PluginAction,
+    PhoenixRising, // Special ceremonial event
+}
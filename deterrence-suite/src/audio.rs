@@ -0,0 +1,104 @@
+use async_trait::async_trait;
+use dark_phoenix_core::ThreatLevel;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Curated recordings keyed by message, optionally specialised per threat level
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AudioLibrary {
+    /// Base directory that relative clip paths are resolved against
+    pub root: PathBuf,
+    pub clips: Vec<AudioClip>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioClip {
+    /// Message key - a situation name such as `weapon`, or a custom key used by policy steps
+    pub key: String,
+    /// Threat level this recording is for (absent = any level)
+    #[serde(default)]
+    pub threat_level: Option<ThreatLevel>,
+    pub file: PathBuf,
+    #[serde(default)]
+    pub schedule: PlaybackSchedule,
+}
+
+/// When and how often a clip plays once triggered
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PlaybackSchedule {
+    /// Pause before the first playback (milliseconds)
+    pub delay_ms: u64,
+    /// Total number of playbacks
+    pub repeat: u32,
+    /// Pause between playbacks (milliseconds)
+    pub interval_ms: u64,
+}
+
+impl Default for PlaybackSchedule {
+    fn default() -> Self {
+        Self {
+            delay_ms: 0,
+            repeat: 1,
+            interval_ms: 0,
+        }
+    }
+}
+
+/// How voice clips share the air with an active siren
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SirenMixing {
+    /// Leave the siren untouched
+    Overlay,
+    /// Lower the siren to the given volume while the clip plays
+    Duck { volume: u8 },
+    /// Silence the siren while the clip plays
+    Pause,
+}
+
+impl AudioLibrary {
+    /// Load a library definition from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Find the clip for a key, preferring one recorded for the exact threat level
+    pub fn lookup(&self, key: &str, threat_level: ThreatLevel) -> Option<&AudioClip> {
+        let mut candidates = self.clips.iter().filter(|clip| clip.key == key);
+        let exact = candidates.clone().find(|clip| clip.threat_level == Some(threat_level));
+        exact.or_else(|| candidates.find(|clip| clip.threat_level.is_none()))
+    }
+
+    /// Absolute path of a clip's audio file
+    pub fn resolve(&self, clip: &AudioClip) -> PathBuf {
+        self.root.join(&clip.file)
+    }
+}
+
+/// Audio output device that recorded clips are played through
+#[async_trait]
+pub trait SpeakerOutput: Send + Sync {
+    /// Play a clip to completion at the given volume (0-100)
+    async fn play(&self, clip: &Path, volume: u8) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Stop any clip currently playing
+    async fn stop(&self) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// Speaker placeholder used until audio hardware is attached
+pub struct LoggingSpeaker;
+
+#[async_trait]
+impl SpeakerOutput for LoggingSpeaker {
+    async fn play(&self, clip: &Path, volume: u8) -> Result<(), Box<dyn std::error::Error>> {
+        info!("📼 Playing clip {} at {}% volume", clip.display(), volume);
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<(), Box<dyn std::error::Error>> {
+        info!("📼 Clip playback stopped");
+        Ok(())
+    }
+}
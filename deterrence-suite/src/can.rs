@@ -0,0 +1,38 @@
+//! Siren controller on the CAN bus (`socketcan` feature)
+
+use crate::{DeterrenceSuite, SirenOutput};
+use dark_phoenix_core::{ActuatorCommand, CanBus, CanConfig};
+use std::sync::Arc;
+use tracing::info;
+
+/// A siren amplifier behind a CAN node
+pub struct CanSiren {
+    bus: CanBus,
+    node: u8,
+}
+
+impl CanSiren {
+    pub fn new(bus: CanBus, node: u8) -> Self {
+        Self { bus, node }
+    }
+}
+
+impl SirenOutput for CanSiren {
+    fn set_tone(&self, frequency_hz: f32, level: f32) -> Result<(), Box<dyn std::error::Error>> {
+        self.bus.send(self.node, ActuatorCommand::SirenTone { frequency_hz, level })?;
+        Ok(())
+    }
+}
+
+impl DeterrenceSuite {
+    /// Sound the siren through the controller `config` puts on `bus`, if any
+    pub fn with_can_siren(self, bus: &CanBus, config: &CanConfig) -> Self {
+        match config.siren_node {
+            Some(node) => {
+                info!("🔊 Siren on CAN node {:#x}", node);
+                self.with_siren_output(Arc::new(CanSiren::new(bus.clone(), node)))
+            },
+            None => self,
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Fault-injecting audio hardware (`fault-injection` feature)
+
+use crate::{DeterrenceSuite, Microphone, SpeakerOutput};
+use async_trait::async_trait;
+use dark_phoenix_core::{FaultInjector, Faulty};
+use std::path::Path;
+use std::sync::Arc;
+
+#[async_trait]
+impl Microphone for Faulty<Arc<dyn Microphone>> {
+    async fn ambient_level_db(&self) -> Result<f32, Box<dyn std::error::Error>> {
+        self.read("ambient_level_db", self.inner().ambient_level_db(), || f32::NAN).await
+    }
+}
+
+#[async_trait]
+impl SpeakerOutput for Faulty<Arc<dyn SpeakerOutput>> {
+    async fn play(&self, clip: &Path, volume: u8) -> Result<(), Box<dyn std::error::Error>> {
+        self.command("play", self.inner().play(clip, volume)).await
+    }
+
+    async fn stop(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.command("stop", self.inner().stop()).await
+    }
+}
+
+impl DeterrenceSuite {
+    /// Route the attached speaker and microphone through `injector`, as
+    /// devices `speaker` and `microphone`; attach real or scenario hardware first
+    pub fn with_fault_injector(mut self, injector: &FaultInjector) -> Self {
+        self.speaker = Arc::new(injector.wrap("speaker", Arc::clone(&self.speaker)));
+        if let Some(microphone) = self.microphone.take() {
+            self.microphone = Some(Arc::new(injector.wrap("microphone", microphone)));
+        }
+        self
+    }
+}
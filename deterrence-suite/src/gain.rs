@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Ambient sound level input
+#[async_trait]
+pub trait Microphone: Send + Sync {
+    /// Current ambient sound pressure level in dB SPL
+    async fn ambient_level_db(&self) -> Result<f32, Box<dyn std::error::Error>>;
+}
+
+/// Sound pressure range an output covers from 0% to 100% volume
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct OutputRange {
+    pub min_db: f32,
+    pub max_db: f32,
+}
+
+impl OutputRange {
+    /// Volume percentage producing the given level, clamped to the output's range
+    pub fn volume_for(&self, db: f32) -> u8 {
+        let span = (self.max_db - self.min_db).max(f32::EPSILON);
+        (((db - self.min_db) / span) * 100.0).clamp(0.0, 100.0).round() as u8
+    }
+
+    pub fn level_for(&self, volume: u8) -> f32 {
+        self.min_db + (self.max_db - self.min_db) * volume.min(100) as f32 / 100.0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoGainConfig {
+    pub enabled: bool,
+    /// How far above ambient full-intensity output should sit (dB)
+    pub margin_db: f32,
+    /// Legal maximum at the listener regardless of ambient (dB)
+    pub legal_limit_db: f32,
+    pub siren_range: OutputRange,
+    pub voice_range: OutputRange,
+    /// Weight of each new ambient sample in the running average (0.0-1.0)
+    pub smoothing: f32,
+}
+
+impl Default for AutoGainConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            margin_db: 15.0,
+            legal_limit_db: 110.0,
+            siren_range: OutputRange { min_db: 80.0, max_db: 120.0 },
+            voice_range: OutputRange { min_db: 60.0, max_db: 100.0 },
+            smoothing: 0.3,
+        }
+    }
+}
+
+/// Tracks ambient noise and derives output volumes that stay audible but legal
+///
+/// With auto-gain active the level reached by full-intensity policy steps
+/// follows ambient noise; scaled steps stay proportional to it.
+#[derive(Debug, Clone)]
+pub struct AutoGainController {
+    config: AutoGainConfig,
+    ambient_db: Option<f32>,
+}
+
+impl AutoGainController {
+    pub fn new(config: AutoGainConfig) -> Self {
+        Self {
+            config,
+            ambient_db: None,
+        }
+    }
+
+    /// Fold a new ambient measurement into the running average
+    pub fn observe(&mut self, ambient_db: f32) {
+        let weight = self.config.smoothing.clamp(0.0, 1.0);
+        self.ambient_db = Some(match self.ambient_db {
+            Some(current) => current + (ambient_db - current) * weight,
+            None => ambient_db,
+        });
+    }
+
+    pub fn ambient_db(&self) -> Option<f32> {
+        self.ambient_db
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.config.enabled && self.ambient_db.is_some()
+    }
+
+    /// Base siren volume to scale policy steps against, or the configured one without data
+    pub fn siren_base(&self, configured: u8) -> u8 {
+        self.base(self.config.siren_range, configured)
+    }
+
+    /// Highest siren volume the legal limit allows
+    pub fn siren_cap(&self) -> u8 {
+        self.config.siren_range.volume_for(self.config.legal_limit_db)
+    }
+
+    pub fn voice_base(&self, configured: u8) -> u8 {
+        self.base(self.config.voice_range, configured)
+    }
+
+    pub fn voice_cap(&self) -> u8 {
+        self.config.voice_range.volume_for(self.config.legal_limit_db)
+    }
+
+    fn base(&self, range: OutputRange, configured: u8) -> u8 {
+        match self.ambient_db {
+            Some(ambient) if self.config.enabled => {
+                let target = (ambient + self.config.margin_db).min(self.config.legal_limit_db);
+                range.volume_for(target)
+            },
+            _ => configured,
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use dark_phoenix_core::{Situation, ThreatLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::Duration;
+
+/// How an activation came to an end
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivationOutcome {
+    /// Outputs are still running
+    Ongoing,
+    /// Replaced by a newer activation before winding down
+    Superseded,
+    /// Decayed to silence after the quiet period
+    DeEscalated,
+    /// Stood down explicitly
+    Deactivated,
+    /// A policy step failed part way through
+    Failed,
+}
+
+/// One activation, from trigger to stand-down
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivationRecord {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub threat_level: ThreatLevel,
+    pub situation: Situation,
+    pub zone: Option<String>,
+    pub rule: String,
+    pub peak_siren_volume: u8,
+    /// Spoken messages and `[clip]` keys in the order they were played
+    pub messages: Vec<String>,
+    pub outcome: ActivationOutcome,
+    pub rehearsal: bool,
+}
+
+impl ActivationRecord {
+    /// Time from trigger to stand-down, or so far while ongoing
+    pub fn duration(&self) -> Duration {
+        let end = self.ended_at.unwrap_or_else(Utc::now);
+        (end - self.started_at).to_std().unwrap_or(Duration::ZERO)
+    }
+}
+
+/// Filter for history queries - unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct HistoryQuery {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Minimum threat level
+    pub threat_level: Option<ThreatLevel>,
+    pub situation: Option<Situation>,
+    pub zone: Option<String>,
+    pub include_rehearsals: bool,
+}
+
+impl HistoryQuery {
+    pub fn matches(&self, record: &ActivationRecord) -> bool {
+        self.since.is_none_or(|since| record.started_at >= since)
+            && self.until.is_none_or(|until| record.started_at < until)
+            && self.threat_level.is_none_or(|level| record.threat_level >= level)
+            && self.situation.is_none_or(|situation| record.situation == situation)
+            && self.zone.as_ref().is_none_or(|zone| record.zone.as_ref() == Some(zone))
+            && (self.include_rehearsals || !record.rehearsal)
+    }
+}
+
+/// Aggregate figures for post-incident review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivationStats {
+    pub total: usize,
+    pub per_day: BTreeMap<NaiveDate, usize>,
+    pub per_threat_level: HashMap<ThreatLevel, usize>,
+    /// Mean duration of activations that have ended
+    pub average_duration: Option<Duration>,
+    pub most_common_situation: Option<Situation>,
+    pub peak_siren_volume: u8,
+}
+
+/// Bounded log of recent activations, oldest dropped first
+#[derive(Debug, Clone)]
+pub struct ActivationHistory {
+    records: VecDeque<ActivationRecord>,
+    capacity: usize,
+}
+
+impl ActivationHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Start a new record, closing any ongoing one as superseded
+    pub fn begin(&mut self, record: ActivationRecord) {
+        self.finish(ActivationOutcome::Superseded);
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    /// The ongoing record, if outputs are still running
+    pub fn current_mut(&mut self) -> Option<&mut ActivationRecord> {
+        self.records
+            .back_mut()
+            .filter(|record| record.outcome == ActivationOutcome::Ongoing)
+    }
+
+    /// Close the ongoing record, if any
+    pub fn finish(&mut self, outcome: ActivationOutcome) {
+        if let Some(record) = self.current_mut() {
+            record.ended_at = Some(Utc::now());
+            record.outcome = outcome;
+        }
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &ActivationRecord> {
+        self.records.iter()
+    }
+
+    pub fn query(&self, query: &HistoryQuery) -> Vec<ActivationRecord> {
+        self.records.iter().filter(|record| query.matches(record)).cloned().collect()
+    }
+
+    pub fn stats(&self, query: &HistoryQuery) -> ActivationStats {
+        let records: Vec<&ActivationRecord> = self.records.iter().filter(|record| query.matches(record)).collect();
+
+        let mut per_day = BTreeMap::new();
+        let mut per_threat_level = HashMap::new();
+        let mut situations: HashMap<Situation, usize> = HashMap::new();
+        for record in &records {
+            *per_day.entry(record.started_at.date_naive()).or_insert(0) += 1;
+            *per_threat_level.entry(record.threat_level).or_insert(0) += 1;
+            *situations.entry(record.situation).or_insert(0) += 1;
+        }
+
+        let ended: Vec<Duration> = records
+            .iter()
+            .filter(|record| record.ended_at.is_some())
+            .map(|record| record.duration())
+            .collect();
+        let average_duration = (!ended.is_empty()).then(|| ended.iter().sum::<Duration>() / ended.len() as u32);
+
+        ActivationStats {
+            total: records.len(),
+            per_day,
+            per_threat_level,
+            average_duration,
+            most_common_situation: situations.into_iter().max_by_key(|(_, count)| *count).map(|(situation, _)| situation),
+            peak_siren_volume: records.iter().map(|record| record.peak_siren_volume).max().unwrap_or(0),
+        }
+    }
+}
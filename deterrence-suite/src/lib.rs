@@ -1,18 +1,90 @@
-use dark_phoenix_core::ThreatLevel;
+use dark_phoenix_core::runtime::{self, sleep, Task};
+use dark_phoenix_core::{DeterrenceTelemetry, Metrics, Situation, ThreatLevel};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::Duration;
-use tokio::time::{sleep, interval};
 use tracing::{info, warn, error};
 
+pub mod audio;
+#[cfg(feature = "socketcan")]
+pub mod can;
+#[cfg(feature = "fault-injection")]
+pub mod fault;
+pub mod gain;
+pub mod history;
+pub mod messages;
+pub mod noise;
+pub mod pattern;
+pub mod policy;
+pub mod preflight;
+pub mod routing;
+#[cfg(feature = "rpi-hw")]
+pub mod rpi;
+pub mod safety;
+pub mod siren;
+#[cfg(feature = "simulation")]
+pub mod simulation;
+#[cfg(feature = "sitl")]
+pub mod sitl;
+pub mod template;
+#[cfg(feature = "tts")]
+pub mod tts;
+
+pub use audio::{AudioClip, AudioLibrary, LoggingSpeaker, PlaybackSchedule, SirenMixing, SpeakerOutput};
+#[cfg(feature = "socketcan")]
+pub use can::CanSiren;
+pub use gain::{AutoGainConfig, AutoGainController, Microphone, OutputRange};
+pub use history::{ActivationHistory, ActivationOutcome, ActivationRecord, ActivationStats, HistoryQuery};
+pub use messages::{Locale, MessageCatalog};
+pub use noise::NoisePolicy;
+pub use pattern::{Color, CustomPattern, LightOutput, PatternError, PatternLibrary, StrobeSegment};
+pub use policy::{
+    ActivationContext, DeterrenceAction, DeterrenceStep, EscalationPolicy, EscalationRule, TimeWindow,
+    VoiceMessage, Volume,
+};
+pub use preflight::SelfTestCheck;
+pub use routing::{OutputZone, Pose, ZoneRoutes, ZoneRoutingConfig, ZoneSelection};
+#[cfg(feature = "rpi-hw")]
+pub use rpi::{PinStrobe, PwmSiren};
+pub use safety::{SafetyError, StrobeOutput, StrobeOverride, StrobeSafetyGuard, StrobeSafetyPolicy};
+pub use siren::{SirenOutput, SirenTone, SirenToneConfig, ToneGenerator};
+#[cfg(feature = "sitl")]
+pub use sitl::{SitlSiren, SitlStrobe};
+pub use template::TemplateError;
+#[cfg(feature = "tts")]
+pub use tts::{TtsConfig, TtsEngine};
+
 /// Configuration for deterrence systems
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DeterrenceConfig {
     pub max_siren_volume: u8,        // 0-100, maps to actual dB
     pub strobe_frequency_hz: f32,    // Strobe rate
     pub voice_volume: u8,            // Voice broadcast volume
     pub escalation_delay_ms: u64,    // Delay between escalation steps
     pub auto_de_escalate: bool,      // Auto reduce intensity over time
+    pub de_escalation_interval_ms: u64, // Time between de-escalation steps
+    pub de_escalation_step: u8,      // Siren volume reduction per step
+    pub quiet_period_ms: u64,        // Full deactivation after this long without activation
+    pub escalation_policy: EscalationPolicy,
+    pub audio_library: AudioLibrary,  // Recorded clips preferred over synthesized speech
+    pub siren_mixing: SirenMixing,    // Siren behavior while clips play
+    pub message_catalog: MessageCatalog,
+    pub language_fallbacks: Vec<String>, // Tried in order when a language lacks a message
+    pub broadcast_languages: Vec<String>, // Warnings are repeated in each language in turn
+    pub template_variables: HashMap<String, String>, // Values for {placeholders} in messages
+    pub auto_gain: AutoGainConfig,   // Ambient-adaptive volume with legal cap
+    pub strobe_safety: StrobeSafetyPolicy, // Photosensitive-epilepsy limits
+    pub custom_patterns: HashMap<String, String>, // Strobe pattern sources by name
+    pub siren_tones: SirenToneConfig, // Tone per threat level and ramp time
+    pub noise_policy: NoisePolicy,   // Quiet hours and per-zone siren caps
+    pub history_capacity: usize,     // Activation records kept for review
+    pub zone_routing: ZoneRoutingConfig, // Directional output selection per output type
+    #[cfg(feature = "tts")]
+    pub tts: TtsConfig,
 }
 
 impl Default for DeterrenceConfig {
@@ -23,40 +95,107 @@ impl Default for DeterrenceConfig {
             voice_volume: 75,
             escalation_delay_ms: 2000,
             auto_de_escalate: true,
+            de_escalation_interval_ms: 15_000,
+            de_escalation_step: 10,
+            quiet_period_ms: 120_000,
+            escalation_policy: EscalationPolicy::builtin(2000),
+            audio_library: AudioLibrary::default(),
+            siren_mixing: SirenMixing::Duck { volume: 20 },
+            message_catalog: MessageCatalog::default(),
+            language_fallbacks: Vec::new(),
+            broadcast_languages: vec!["en".to_string()],
+            template_variables: HashMap::from([
+                ("site".to_string(), "this property".to_string()),
+                ("protectee".to_string(), "the protected person".to_string()),
+                ("police_eta".to_string(), "minutes".to_string()),
+                ("callback_number".to_string(), "911".to_string()),
+            ]),
+            auto_gain: AutoGainConfig::default(),
+            strobe_safety: StrobeSafetyPolicy::default(),
+            custom_patterns: HashMap::new(),
+            siren_tones: SirenToneConfig::default(),
+            noise_policy: NoisePolicy::default(),
+            history_capacity: 1000,
+            zone_routing: ZoneRoutingConfig::default(),
+            #[cfg(feature = "tts")]
+            tts: TtsConfig::default(),
         }
     }
 }
 
+impl DeterrenceConfig {
+    /// Check that every voice message template can be rendered
+    pub fn validate(&self) -> Result<(), TemplateError> {
+        self.message_catalog.validate(&self.template_variables)
+    }
+}
+
+/// Whether activations drive hardware or only log what would be emitted
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DeterrenceMode {
+    #[default]
+    Live,
+    /// Full policy evaluation and state transitions with outputs only logged
+    Rehearsal,
+}
+
 /// Current state of deterrence systems
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeterrenceState {
+    pub mode: DeterrenceMode,
     pub siren_active: bool,
     pub siren_volume: u8,
+    pub siren_tone: Option<SirenTone>,
     pub strobe_active: bool,
     pub strobe_pattern: StrobePattern,
+    pub strobe_since: Option<DateTime<Utc>>, // Start of the current continuous strobe run
     pub voice_active: bool,
     pub current_message: Option<String>,
     pub last_activation: Option<DateTime<Utc>>,
     pub activation_count: u32,
+    pub ambient_level_db: Option<f32>,
+    pub output_routes: ZoneRoutes,
 }
 
 impl Default for DeterrenceState {
     fn default() -> Self {
         Self {
+            mode: DeterrenceMode::Live,
             siren_active: false,
             siren_volume: 0,
+            siren_tone: None,
             strobe_active: false,
             strobe_pattern: StrobePattern::Off,
+            strobe_since: None,
             voice_active: false,
             current_message: None,
             last_activation: None,
             activation_count: 0,
+            ambient_level_db: None,
+            output_routes: ZoneRoutes::default(),
+        }
+    }
+}
+
+impl DeterrenceState {
+    /// Dashboard summary, for `DroneState::report_deterrence`
+    pub fn telemetry(&self) -> DeterrenceTelemetry {
+        DeterrenceTelemetry {
+            rehearsal: self.mode == DeterrenceMode::Rehearsal,
+            siren_active: self.siren_active,
+            siren_volume: self.siren_volume,
+            strobe_active: self.strobe_active,
+            voice_active: self.voice_active,
+            current_message: self.current_message.clone(),
+            timestamp: Utc::now(),
         }
     }
 }
 
 /// Strobe light patterns for different threat levels
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
 pub enum StrobePattern {
     Off,
     Pulse,          // Gentle pulsing for awareness
@@ -64,17 +203,43 @@ pub enum StrobePattern {
     Warning,        // Rapid strobing for deterrence
     Emergency,      // Maximum intensity disorientation
     Phoenix,        // Mythic pattern - rising flame effect
+    Custom(String), // User-defined sequence from `custom_patterns` by name
 }
 
 impl StrobePattern {
+    /// Nominal flash rate - custom patterns report 0.0 until resolved via `sequence`
     pub fn frequency_hz(&self) -> f32 {
         match self {
-            StrobePattern::Off => 0.0,
+            StrobePattern::Off | StrobePattern::Custom(_) => 0.0,
             StrobePattern::Pulse => 1.0,
             StrobePattern::Alert => 4.0,
             StrobePattern::Warning => 8.0,
             StrobePattern::Emergency => 15.0,
-            StrobePattern::Phoenix => 3.0, // Slower, more dramatic
+            StrobePattern::Phoenix => phoenix_pattern().flash_frequency_hz(),
+        }
+    }
+
+    /// Light sequence for this pattern, or `None` for `Off` and unknown custom names
+    pub fn sequence(&self, library: &PatternLibrary) -> Option<CustomPattern> {
+        let square = |duty_cycle| Some(CustomPattern::square(self.description(), self.frequency_hz(), duty_cycle));
+        match self {
+            StrobePattern::Off => None,
+            StrobePattern::Pulse => square(0.5),
+            StrobePattern::Alert => square(0.5),
+            StrobePattern::Warning => square(0.3),
+            StrobePattern::Emergency => square(0.2),
+            StrobePattern::Phoenix => Some(phoenix_pattern().clone()),
+            StrobePattern::Custom(name) => library.get(name).cloned(),
+        }
+    }
+
+    /// Next calmer pattern used when de-escalating
+    pub fn step_down(&self) -> StrobePattern {
+        match self {
+            StrobePattern::Off | StrobePattern::Pulse => StrobePattern::Off,
+            StrobePattern::Alert => StrobePattern::Pulse,
+            StrobePattern::Warning | StrobePattern::Phoenix | StrobePattern::Custom(_) => StrobePattern::Alert,
+            StrobePattern::Emergency => StrobePattern::Warning,
         }
     }
 
@@ -86,74 +251,56 @@ impl StrobePattern {
             StrobePattern::Warning => "Warning deterrence flash",
             StrobePattern::Emergency => "Emergency disorientation strobe",
             StrobePattern::Phoenix => "Phoenix rising ceremonial pattern",
+            StrobePattern::Custom(_) => "Custom strobe sequence",
         }
     }
 }
 
+fn phoenix_pattern() -> &'static CustomPattern {
+    static PHOENIX: std::sync::OnceLock<CustomPattern> = std::sync::OnceLock::new();
+    PHOENIX.get_or_init(|| {
+        CustomPattern::compile("phoenix", pattern::PHOENIX_SOURCE).expect("built-in Phoenix pattern compiles")
+    })
+}
+
 /// Mythic voice messages for different situations
 pub struct MythicVoice;
 
 impl MythicVoice {
-    /// Get appropriate voice message based on threat level
-    pub fn get_message(threat_level: ThreatLevel, situation: &str) -> String {
-        match threat_level {
-            ThreatLevel::Green => Self::green_messages(),
-            ThreatLevel::Yellow => Self::yellow_messages(situation),
-            ThreatLevel::Orange => Self::orange_messages(situation),
-            ThreatLevel::Red => Self::red_messages(situation),
-            ThreatLevel::Omega => Self::omega_messages(),
-        }
-    }
-
-    fn green_messages() -> String {
-        "Guardian protocols active. Area under protection.".to_string()
-    }
-
-    fn yellow_messages(situation: &str) -> String {
-        match situation {
-            "anomaly" => "Anomaly detected. Please maintain calm behavior.".to_string(),
-            "proximity" => "You are entering a protected zone. Please identify yourself.".to_string(),
-            _ => "Dark Phoenix monitoring. Please proceed with caution.".to_string(),
-        }
-    }
-
-    fn orange_messages(situation: &str) -> String {
-        match situation {
-            "aggression" => "Aggressive behavior detected. Cease immediately or authorities will be contacted.".to_string(),
-            "weapon" => "Weapon detected. Drop the weapon and step back immediately.".to_string(),
-            "group_threat" => "Multiple aggressors detected. Disperse immediately or law enforcement will be summoned.".to_string(),
-            _ => "Warning: Threat level elevated. You are being recorded. Authorities have been notified.".to_string(),
-        }
-    }
-
-    fn red_messages(situation: &str) -> String {
-        match situation {
-            "imminent_danger" => "IMMINENT DANGER DETECTED. EMERGENCY SERVICES CONTACTED. RETREAT IMMEDIATELY.".to_string(),
-            "weapon_drawn" => "WEAPON DRAWN. DROP WEAPON NOW. POLICE EN ROUTE. YOU ARE BEING RECORDED.".to_string(),
-            "physical_attack" => "PHYSICAL ATTACK IN PROGRESS. MEDICAL AND POLICE ASSISTANCE REQUESTED.".to_string(),
-            _ => "HIGH THREAT CONFIRMED. ALL DETERRENCE SYSTEMS ACTIVE. SURRENDER IMMEDIATELY.".to_string(),
-        }
-    }
-
-    fn omega_messages() -> String {
-        "⚠️ OMEGA PROTOCOL ACTIVATED ⚠️ DARK PHOENIX RISING ⚠️ MAXIMUM PROTECTION AUTHORIZED ⚠️ SURRENDER OR FACE CONSEQUENCES ⚠️".to_string()
+    /// Get appropriate voice message based on threat level, in the requested language
+    pub fn get_message(threat_level: ThreatLevel, situation: Situation, locale: &Locale) -> String {
+        MessageCatalog::builtin()
+            .threat_message(threat_level, situation, locale)
+            .unwrap_or_default()
+            .to_string()
     }
 
     /// Get ceremonial announcement for special occasions
-    pub fn ceremonial_announcement(event: &str) -> String {
-        match event {
-            "activation" => "From the ashes of danger, the Dark Phoenix rises to protect the innocent.".to_string(),
-            "victory" => "The Phoenix has prevailed. Peace is restored. Guardian watch continues.".to_string(),
-            "retreat" => "Threat neutralized. The Phoenix returns to the shadows, ever watchful.".to_string(),
-            _ => "Dark Phoenix stands eternal vigil. None shall harm the protected.".to_string(),
-        }
+    pub fn ceremonial_announcement(event: &str, locale: &Locale) -> String {
+        MessageCatalog::builtin()
+            .ceremonial(event, locale)
+            .unwrap_or_default()
+            .to_string()
     }
 }
 
 /// Main deterrence system controller
 pub struct DeterrenceSuite {
     config: DeterrenceConfig,
-    state: DeterrenceState,
+    state: Arc<Mutex<DeterrenceState>>,
+    history: Arc<Mutex<ActivationHistory>>,
+    /// The current activation's escalation sequence, then its decay
+    response_task: Option<Task<()>>,
+    step_tasks: StepTasks,
+    speaker: Arc<dyn SpeakerOutput>,
+    microphone: Option<Arc<dyn Microphone>>,
+    auto_gain: AutoGainController,
+    output_mode: OutputMode,
+    routes: RouteHandle,
+    pose: Option<Pose>,
+    /// Whether an operator can reach the drone to call deterrence off
+    supervised: Arc<AtomicBool>,
+    metrics: Option<Metrics>,
     // Hardware interfaces (placeholders for now)
     siren_controller: SirenController,
     strobe_controller: StrobeController,
@@ -162,153 +309,333 @@ pub struct DeterrenceSuite {
 
 impl DeterrenceSuite {
     pub fn new(config: DeterrenceConfig) -> Self {
+        if let Err(e) = config.validate() {
+            warn!("Voice message catalog failed validation: {}", e);
+        }
+
+        let output_mode = OutputMode::default();
+        let routes = RouteHandle::default();
+        #[cfg(feature = "tts")]
+        let voice_controller = VoiceController::new(config.tts.clone(), output_mode.clone(), routes.clone());
+        #[cfg(not(feature = "tts"))]
+        let voice_controller = VoiceController::new(output_mode.clone(), routes.clone());
+        let auto_gain = AutoGainController::new(config.auto_gain.clone());
+        let patterns = PatternLibrary::compile(&config.custom_patterns).unwrap_or_else(|e| {
+            warn!("Custom strobe patterns failed to compile, none loaded: {}", e);
+            PatternLibrary::default()
+        });
+        let strobe_controller = StrobeController::new(config.strobe_safety.clone(), patterns, output_mode.clone(), routes.clone());
+        let siren_controller = SirenController::new(config.siren_tones.ramp_ms, output_mode.clone(), routes.clone());
+        let history = ActivationHistory::new(config.history_capacity);
+
         Self {
             config,
-            state: DeterrenceState::default(),
-            siren_controller: SirenController::new(),
-            strobe_controller: StrobeController::new(),
-            voice_controller: VoiceController::new(),
+            state: Arc::new(Mutex::new(DeterrenceState::default())),
+            history: Arc::new(Mutex::new(history)),
+            response_task: None,
+            step_tasks: StepTasks::default(),
+            speaker: Arc::new(LoggingSpeaker),
+            microphone: None,
+            auto_gain,
+            output_mode,
+            routes,
+            pose: None,
+            supervised: Arc::new(AtomicBool::new(true)),
+            metrics: None,
+            siren_controller,
+            strobe_controller,
+            voice_controller,
         }
     }
 
-    /// Activate deterrence systems based on threat level
-    pub async fn activate(&mut self, threat_level: ThreatLevel, situation: &str) -> Result<(), Box<dyn std::error::Error>> {
-        info!("🚨 Activating deterrence systems for threat level: {}", threat_level.as_str());
-        
-        self.state.last_activation = Some(Utc::now());
-        self.state.activation_count += 1;
+    /// Set a template value at runtime, e.g. `police_eta` once dispatch confirms
+    pub fn set_template_variable(&mut self, name: &str, value: &str) {
+        self.config.template_variables.insert(name.to_string(), value.to_string());
+    }
 
-        match threat_level {
-            ThreatLevel::Green => {
-                self.deactivate_all().await?;
-            },
-            ThreatLevel::Yellow => {
-                self.activate_low_deterrence(situation).await?;
-            },
-            ThreatLevel::Orange => {
-                self.activate_medium_deterrence(situation).await?;
-            },
-            ThreatLevel::Red => {
-                self.activate_high_deterrence(situation).await?;
-            },
-            ThreatLevel::Omega => {
-                self.activate_omega_protocol().await?;
-            },
-        }
+    /// Play recorded clips through the given speaker instead of the logging placeholder
+    pub fn with_speaker(mut self, speaker: Arc<dyn SpeakerOutput>) -> Self {
+        self.speaker = speaker;
+        self
+    }
 
-        Ok(())
+    /// Measure ambient noise with the given microphone for auto-gain
+    pub fn with_microphone(mut self, microphone: Arc<dyn Microphone>) -> Self {
+        self.microphone = Some(microphone);
+        self
     }
 
-    /// Low-level deterrence for Yellow threats
-    async fn activate_low_deterrence(&mut self, situation: &str) -> Result<(), Box<dyn std::error::Error>> {
-        // Gentle strobe to get attention
-        self.strobe_controller.set_pattern(StrobePattern::Pulse).await?;
-        self.state.strobe_active = true;
-        self.state.strobe_pattern = StrobePattern::Pulse;
+    /// Drive a siren by pitch and level, e.g. a piezo on a PWM pin
+    pub fn with_siren_output(mut self, output: Arc<dyn SirenOutput>) -> Self {
+        self.siren_controller.output = Some(output);
+        self
+    }
 
-        // Calm voice message
-        let message = MythicVoice::get_message(ThreatLevel::Yellow, situation);
-        self.voice_controller.speak(&message, self.config.voice_volume / 2).await?;
-        self.state.voice_active = true;
-        self.state.current_message = Some(message);
+    /// Play strobe patterns on light hardware
+    pub fn with_light_output(mut self, light: Arc<dyn LightOutput>) -> Self {
+        self.strobe_controller.light = Some(light);
+        self
+    }
 
-        info!("🟡 Low deterrence activated: {}", StrobePattern::Pulse.description());
+    /// Count activations by threat level in `metrics`
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Update the drone's position and heading used to aim directional outputs
+    pub fn set_pose(&mut self, pose: Pose) {
+        self.pose = Some(pose);
+    }
+
+    /// Tell deterrence whether an operator can reach the drone, e.g. from
+    /// `LinkMonitor::restrains_deterrence`; while nobody can, a sounding
+    /// siren is silenced and activations use strobe and voice instead
+    pub async fn set_supervised(&mut self, supervised: bool) -> Result<(), Box<dyn std::error::Error>> {
+        if self.supervised.swap(supervised, Ordering::SeqCst) == supervised {
+            return Ok(());
+        }
+        if supervised {
+            info!("📡 Operator link restored - siren available again");
+            return Ok(());
+        }
+        warn!("📡 {}No operator link - siren silenced, strobe and voice only", self.output_mode.tag());
+        if self.state().siren_active {
+            self.siren_controller.deactivate().await?;
+            let mut state = self.state();
+            state.siren_active = false;
+            state.siren_volume = 0;
+            state.siren_tone = None;
+        }
         Ok(())
     }
 
-    /// Medium deterrence for Orange threats
-    async fn activate_medium_deterrence(&mut self, situation: &str) -> Result<(), Box<dyn std::error::Error>> {
-        // Warning strobe
-        self.strobe_controller.set_pattern(StrobePattern::Warning).await?;
-        self.state.strobe_active = true;
-        self.state.strobe_pattern = StrobePattern::Warning;
+    /// Switch between driving hardware and rehearsing policies
+    ///
+    /// Outputs are stood down on every switch so rehearsal never leaves real
+    /// hardware running and live mode never inherits rehearsed state.
+    pub async fn set_mode(&mut self, mode: DeterrenceMode) -> Result<(), Box<dyn std::error::Error>> {
+        if mode == self.mode() {
+            return Ok(());
+        }
+        self.deactivate_all().await?;
+        self.output_mode.set(mode);
+        self.state().mode = mode;
+        match mode {
+            DeterrenceMode::Live => warn!("🔴 Deterrence suite LIVE - outputs drive hardware"),
+            DeterrenceMode::Rehearsal => info!("🎭 Deterrence suite in rehearsal mode - outputs are logged only"),
+        }
+        Ok(())
+    }
+
+    pub fn mode(&self) -> DeterrenceMode {
+        self.output_mode.get()
+    }
+
+    /// Activate deterrence systems based on threat level
+    pub async fn activate(&mut self, threat_level: ThreatLevel, situation: Situation) -> Result<(), Box<dyn std::error::Error>> {
+        self.activate_with_context(ActivationContext::now(threat_level, situation)).await
+    }
+
+    /// Activate deterrence systems using the escalation policy rule matching the context
+    ///
+    /// The rule's steps run in a background task, superseded by the next
+    /// activation and stopped by `deactivate_all`.
+    #[tracing::instrument(
+        name = "deterrence_activation",
+        skip_all,
+        fields(threat_level = ctx.threat_level.as_str(), situation = %ctx.situation)
+    )]
+    pub async fn activate_with_context(&mut self, ctx: ActivationContext) -> Result<(), Box<dyn std::error::Error>> {
+        info!("🚨 {}Activating deterrence systems for threat level: {}", self.output_mode.tag(), ctx.threat_level.as_str());
+        
+        // A fresh activation supersedes the last one's pending steps, decay and queued clips
+        self.cancel_response();
+        self.step_tasks.cancel_clips();
 
-        // Low-volume siren
-        let siren_volume = self.config.max_siren_volume / 3;
-        self.siren_controller.activate(siren_volume).await?;
-        self.state.siren_active = true;
-        self.state.siren_volume = siren_volume;
+        self.sample_ambient_noise().await;
 
-        // Authoritative voice message
-        let message = MythicVoice::get_message(ThreatLevel::Orange, situation);
-        self.voice_controller.speak(&message, self.config.voice_volume).await?;
-        self.state.current_message = Some(message);
+        {
+            let mut state = self.state();
+            state.last_activation = Some(Utc::now());
+            state.activation_count += 1;
+            state.ambient_level_db = self.auto_gain.ambient_db();
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .counter(
+                    "phoenix_deterrence_activations_total",
+                    "Deterrence activations",
+                    &[
+                        ("threat_level", ctx.threat_level.as_str()),
+                        ("rehearsal", if self.output_mode.is_rehearsal() { "true" } else { "false" }),
+                    ],
+                )
+                .inc();
+        }
+
+        let rule = match self.config.escalation_policy.select(&ctx) {
+            Some(rule) => rule.clone(),
+            None => {
+                warn!("No escalation rule matches {} / {}, using built-in policy", ctx.threat_level.as_str(), ctx.situation);
+                EscalationPolicy::builtin(self.config.escalation_delay_ms)
+                    .select(&ctx)
+                    .cloned()
+                    .ok_or("Built-in escalation policy has no rule for threat level")?
+            },
+        };
 
-        warn!("🟠 Medium deterrence activated: Siren {}%, Strobe {}", 
-              siren_volume, StrobePattern::Warning.description());
+        if ctx.threat_level == ThreatLevel::Omega {
+            error!("💀 OMEGA PROTOCOL ACTIVATED - DARK PHOENIX RISING 💀");
+        }
+
+        let steps = if !self.supervised.load(Ordering::SeqCst) {
+            info!("📡 No operator link - substituting strobe and voice for the siren");
+            quiet_hours_steps(&rule.steps)
+        } else if self.config.noise_policy.siren_permitted(&ctx) {
+            rule.steps.clone()
+        } else {
+            info!("🌙 Quiet hours in effect - substituting strobe and voice for the siren");
+            quiet_hours_steps(&rule.steps)
+        };
+
+        self.history().begin(ActivationRecord {
+            started_at: Utc::now(),
+            ended_at: None,
+            threat_level: ctx.threat_level,
+            situation: ctx.situation,
+            zone: ctx.zone.clone(),
+            rule: rule.name.clone(),
+            peak_siren_volume: 0,
+            messages: Vec::new(),
+            outcome: ActivationOutcome::Ongoing,
+            rehearsal: self.output_mode.is_rehearsal(),
+        });
+
+        let toward = self.config.zone_routing.zone_toward(self.pose.as_ref(), ctx.threat_position.as_ref());
+        let routes = self.config.zone_routing.routes(toward);
+        if let Some(zone) = toward {
+            info!("🧭 Threat bearing selects {} output zone", zone);
+        }
+        self.routes.set(routes.clone());
+        self.state().output_routes = routes;
+
+        let de_escalation = (self.config.auto_de_escalate && ctx.threat_level != ThreatLevel::Green).then(|| self.de_escalation());
+        let escalation = Escalation {
+            outputs: self.outputs(),
+            rule: rule.name.clone(),
+            steps,
+            ctx,
+            de_escalation,
+        };
+        self.response_task = Some(runtime::spawn(escalation.run()));
         Ok(())
     }
 
-    /// High deterrence for Red threats
-    async fn activate_high_deterrence(&mut self, situation: &str) -> Result<(), Box<dyn std::error::Error>> {
-        // Emergency strobe
-        self.strobe_controller.set_pattern(StrobePattern::Emergency).await?;
-        self.state.strobe_active = true;
-        self.state.strobe_pattern = StrobePattern::Emergency;
+    /// Decay that winds outputs down while no new activation arrives
+    fn de_escalation(&self) -> DeEscalation {
+        DeEscalation {
+            state: Arc::clone(&self.state),
+            history: Arc::clone(&self.history),
+            interval: Duration::from_millis(self.config.de_escalation_interval_ms.max(1)),
+            quiet_period: Duration::from_millis(self.config.quiet_period_ms),
+            siren_step: self.config.de_escalation_step,
+            siren_controller: self.siren_controller.clone(),
+            strobe_controller: self.strobe_controller.clone(),
+            voice_controller: self.voice_controller.clone(),
+        }
+    }
 
-        // High-volume siren
-        let siren_volume = (self.config.max_siren_volume * 2) / 3;
-        self.siren_controller.activate(siren_volume).await?;
-        self.state.siren_active = true;
-        self.state.siren_volume = siren_volume;
+    /// Stop the current activation's remaining steps and its decay
+    fn cancel_response(&mut self) {
+        if let Some(task) = self.response_task.take() {
+            task.abort();
+        }
+    }
 
-        // Commanding voice message
-        let message = MythicVoice::get_message(ThreatLevel::Red, situation);
-        self.voice_controller.speak(&message, self.config.voice_volume).await?;
-        self.state.current_message = Some(message);
+    /// Whether the current activation still has steps to run or is decaying
+    pub fn is_responding(&self) -> bool {
+        self.response_task.as_ref().is_some_and(|task| !task.is_finished())
+    }
 
-        error!("🔴 High deterrence activated: Siren {}%, Emergency strobe", siren_volume);
-        Ok(())
+    /// Handles the steps act on, for the task running them
+    fn outputs(&self) -> Outputs {
+        Outputs {
+            config: Arc::new(self.config.clone()),
+            auto_gain: self.auto_gain.clone(),
+            supervised: Arc::clone(&self.supervised),
+            state: Arc::clone(&self.state),
+            history: Arc::clone(&self.history),
+            speaker: Arc::clone(&self.speaker),
+            output_mode: self.output_mode.clone(),
+            tasks: self.step_tasks.clone(),
+            siren_controller: self.siren_controller.clone(),
+            strobe_controller: self.strobe_controller.clone(),
+            voice_controller: self.voice_controller.clone(),
+        }
     }
 
-    /// Maximum deterrence for Omega threats
-    async fn activate_omega_protocol(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        error!("💀 OMEGA PROTOCOL ACTIVATED - DARK PHOENIX RISING 💀");
+    async fn sample_ambient_noise(&mut self) {
+        if !self.config.auto_gain.enabled {
+            return;
+        }
+        let Some(microphone) = &self.microphone else { return };
+        match microphone.ambient_level_db().await {
+            Ok(level) => {
+                self.auto_gain.observe(level);
+                info!("🎙️ Ambient noise {:.0} dB (smoothed {:.0} dB)", level, self.auto_gain.ambient_db().unwrap_or(level));
+            },
+            Err(e) => warn!("Ambient noise measurement failed, keeping previous gain: {}", e),
+        }
+    }
 
-        // Phoenix ceremonial strobe pattern
-        self.strobe_controller.set_pattern(StrobePattern::Phoenix).await?;
-        self.state.strobe_active = true;
-        self.state.strobe_pattern = StrobePattern::Phoenix;
+    /// Authenticate an operator and lift strobe safety limits for a limited time
+    pub fn authorize_strobe_override(
+        &mut self,
+        operator: &str,
+        secret: &str,
+        duration: Duration,
+        reason: &str,
+    ) -> Result<StrobeOverride, SafetyError> {
+        self.strobe_controller.safety().authorize_override(operator, secret, duration, reason)
+    }
 
-        // Maximum siren volume
-        self.siren_controller.activate(self.config.max_siren_volume).await?;
-        self.state.siren_active = true;
-        self.state.siren_volume = self.config.max_siren_volume;
+    pub fn revoke_strobe_override(&mut self) {
+        self.strobe_controller.safety().revoke_override();
+    }
 
-        // Omega protocol voice message
-        let message = MythicVoice::get_message(ThreatLevel::Omega, "omega");
-        self.voice_controller.speak(&message, 100).await?; // Maximum volume
-        self.state.current_message = Some(message);
+    /// Every strobe safety override granted since startup
+    pub fn strobe_override_log(&self) -> Vec<StrobeOverride> {
+        self.strobe_controller.safety().override_log().to_vec()
+    }
 
-        // Wait, then ceremonial announcement
-        sleep(Duration::from_millis(self.config.escalation_delay_ms)).await;
-        let ceremonial = MythicVoice::ceremonial_announcement("activation");
-        self.voice_controller.speak(&ceremonial, 100).await?;
+    fn state(&self) -> MutexGuard<'_, DeterrenceState> {
+        lock_state(&self.state)
+    }
 
-        error!("🔥 OMEGA PROTOCOL FULLY DEPLOYED 🔥");
-        Ok(())
+    fn history(&self) -> MutexGuard<'_, ActivationHistory> {
+        lock_history(&self.history)
     }
 
-    /// Deactivate all deterrence systems
-    pub async fn deactivate_all(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        self.siren_controller.deactivate().await?;
-        self.strobe_controller.set_pattern(StrobePattern::Off).await?;
-        self.voice_controller.stop().await?;
+    /// Past activations matching the query, oldest first
+    pub fn activation_history(&self, query: &HistoryQuery) -> Vec<ActivationRecord> {
+        self.history().query(query)
+    }
 
-        self.state.siren_active = false;
-        self.state.siren_volume = 0;
-        self.state.strobe_active = false;
-        self.state.strobe_pattern = StrobePattern::Off;
-        self.state.voice_active = false;
-        self.state.current_message = None;
+    /// Aggregate figures over the activations matching the query
+    pub fn activation_stats(&self, query: &HistoryQuery) -> ActivationStats {
+        self.history().stats(query)
+    }
 
-        info!("🕊️ All deterrence systems deactivated - peaceful mode");
-        Ok(())
+    /// Deactivate all deterrence systems
+    pub async fn deactivate_all(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.cancel_response();
+        self.outputs().deactivate().await
     }
 
     /// Get current deterrence status
-    pub fn get_status(&self) -> &DeterrenceState {
-        &self.state
+    pub fn get_status(&self) -> DeterrenceState {
+        self.state().clone()
     }
 
     /// Emergency test of all systems
@@ -316,78 +643,943 @@ impl DeterrenceSuite {
         info!("🧪 Starting deterrence system test...");
 
         // Test each component briefly
-        self.voice_controller.speak("System test initiated", 50).await?;
+        self.voice_controller.speak("System test initiated", 50, ThreatLevel::Green).await?;
         sleep(Duration::from_millis(1000)).await;
 
-        self.strobe_controller.set_pattern(StrobePattern::Alert).await?;
+        self.strobe_controller.set_pattern(&StrobePattern::Alert).await?;
         sleep(Duration::from_millis(2000)).await;
 
-        self.siren_controller.activate(20).await?; // Low volume test
+        self.siren_controller.activate(20, SirenTone::Wail).await?; // Low volume test
         sleep(Duration::from_millis(1000)).await;
 
         self.deactivate_all().await?;
-        self.voice_controller.speak("System test complete. All systems operational.", 50).await?;
+        self.voice_controller.speak("System test complete. All systems operational.", 50, ThreatLevel::Green).await?;
 
         info!("✅ Deterrence system test completed successfully");
         Ok(())
     }
 }
 
-/// Siren controller (placeholder for hardware interface)
-struct SirenController;
+impl Drop for DeterrenceSuite {
+    fn drop(&mut self) {
+        self.cancel_response();
+        self.step_tasks.cancel_clips();
+        self.step_tasks.cancel_strobe_limit();
+        self.siren_controller.silence_now();
+    }
+}
+
+fn lock_state(state: &Mutex<DeterrenceState>) -> MutexGuard<'_, DeterrenceState> {
+    // A panic mid-update leaves plain flags behind, which are still safe to read
+    state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn lock_history(history: &Mutex<ActivationHistory>) -> MutexGuard<'_, ActivationHistory> {
+    history.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn clear_outputs(state: &mut DeterrenceState) {
+    state.siren_active = false;
+    state.siren_volume = 0;
+    state.siren_tone = None;
+    state.strobe_active = false;
+    state.strobe_pattern = StrobePattern::Off;
+    state.strobe_since = None;
+    state.voice_active = false;
+    state.current_message = None;
+}
+
+/// Quiet-hours variant of a rule: the siren is silenced and a spoken warning
+/// is guaranteed so the response still carries a message
+fn quiet_hours_steps(steps: &[DeterrenceStep]) -> Vec<DeterrenceStep> {
+    let mut quiet: Vec<DeterrenceStep> = steps
+        .iter()
+        .map(|step| match &step.action {
+            DeterrenceAction::Siren { tone, .. } => DeterrenceStep {
+                delay_ms: step.delay_ms,
+                action: DeterrenceAction::Siren { volume: Volume::Scaled(0.0), tone: *tone },
+            },
+            _ => step.clone(),
+        })
+        .collect();
+
+    let has_siren = steps.iter().any(|step| matches!(step.action, DeterrenceAction::Siren { .. }));
+    let has_voice = steps.iter().any(|step| matches!(step.action, DeterrenceAction::Voice { .. }));
+    if has_siren && !has_voice {
+        quiet.push(DeterrenceStep {
+            delay_ms: 0,
+            action: DeterrenceAction::Voice { message: VoiceMessage::Threat, volume: Volume::Scaled(1.0) },
+        });
+    }
+    quiet
+}
+
+/// What an activation's steps act on, cloned out of the suite so the
+/// sequence can run as a task of its own
+#[derive(Clone)]
+struct Outputs {
+    config: Arc<DeterrenceConfig>,
+    auto_gain: AutoGainController,
+    supervised: Arc<AtomicBool>,
+    state: Arc<Mutex<DeterrenceState>>,
+    history: Arc<Mutex<ActivationHistory>>,
+    speaker: Arc<dyn SpeakerOutput>,
+    output_mode: OutputMode,
+    tasks: StepTasks,
+    siren_controller: SirenController,
+    strobe_controller: StrobeController,
+    voice_controller: VoiceController,
+}
+
+impl Outputs {
+    fn state(&self) -> MutexGuard<'_, DeterrenceState> {
+        lock_state(&self.state)
+    }
+
+    fn history(&self) -> MutexGuard<'_, ActivationHistory> {
+        lock_history(&self.history)
+    }
+
+    /// Siren volume for a policy step, adapted to ambient noise when auto-gain is on
+    fn siren_volume(&self, volume: &Volume) -> u8 {
+        let configured = self.config.max_siren_volume;
+        if !self.config.auto_gain.enabled {
+            return volume.resolve(configured, configured);
+        }
+        let cap = self.auto_gain.siren_cap().min(configured);
+        volume.resolve(self.auto_gain.siren_base(configured), cap).min(cap)
+    }
+
+    /// Voice volume for a policy step, adapted to ambient noise when auto-gain is on
+    fn voice_volume(&self, volume: &Volume) -> u8 {
+        if !self.config.auto_gain.enabled {
+            return volume.resolve(self.config.voice_volume, 100);
+        }
+        let cap = self.auto_gain.voice_cap();
+        volume.resolve(self.auto_gain.voice_base(self.config.voice_volume), cap).min(cap)
+    }
+
+    /// Force strobes off once a continuous run exceeds the safety limit
+    fn start_strobe_limit(&self, since: DateTime<Utc>) {
+        let strobe_controller = self.strobe_controller.clone();
+        let state = Arc::clone(&self.state);
+        self.tasks.set_strobe_limit(runtime::spawn(async move {
+            loop {
+                let remaining = strobe_controller.safety().remaining_run_time(since);
+                if remaining.is_zero() {
+                    break;
+                }
+                sleep(remaining).await;
+            }
+
+            if lock_state(&state).strobe_since != Some(since) {
+                return;
+            }
+            warn!("🛡️ Continuous strobe limit reached - forcing strobes off");
+            if let Err(e) = strobe_controller.set_pattern(&StrobePattern::Off).await {
+                error!("Failed to stop strobes at safety limit: {}", e);
+            }
+            let mut state = lock_state(&state);
+            state.strobe_active = false;
+            state.strobe_pattern = StrobePattern::Off;
+            state.strobe_since = None;
+        }));
+    }
+
+    /// Schedule a recorded clip, mixing it against the siren per configuration
+    fn schedule_clip(&self, clip: &AudioClip, volume: u8) {
+        let playback = ClipPlayback {
+            path: self.config.audio_library.resolve(clip),
+            volume,
+            schedule: clip.schedule,
+            mixing: self.config.siren_mixing,
+            speaker: Arc::clone(&self.speaker),
+            siren_controller: self.siren_controller.clone(),
+            state: Arc::clone(&self.state),
+            output_mode: self.output_mode.clone(),
+        };
+        self.tasks.add_clip(runtime::spawn(playback.run()));
+    }
+
+    /// One locale per broadcast language, each sharing the configured fallback chain
+    fn broadcast_locales(&self) -> Vec<Locale> {
+        let languages = if self.config.broadcast_languages.is_empty() {
+            vec!["en".to_string()]
+        } else {
+            self.config.broadcast_languages.clone()
+        };
+        languages
+            .into_iter()
+            .map(|language| Locale {
+                language,
+                fallbacks: self.config.language_fallbacks.clone(),
+            })
+            .collect()
+    }
+
+    /// Perform a single policy step against the hardware and record it in state
+    async fn execute_step(&self, action: &DeterrenceAction, ctx: &ActivationContext) -> Result<(), Box<dyn std::error::Error>> {
+        match action {
+            DeterrenceAction::Strobe { pattern } => {
+                self.strobe_controller.set_pattern(pattern).await?;
+                let started = {
+                    let mut state = self.state();
+                    state.strobe_active = *pattern != StrobePattern::Off;
+                    state.strobe_pattern = pattern.clone();
+                    match (state.strobe_active, state.strobe_since) {
+                        (true, None) => {
+                            state.strobe_since = Some(Utc::now());
+                            state.strobe_since
+                        },
+                        (false, _) => {
+                            state.strobe_since = None;
+                            None
+                        },
+                        (true, Some(_)) => None,
+                    }
+                };
+                match started {
+                    Some(since) => self.start_strobe_limit(since),
+                    None if *pattern == StrobePattern::Off => self.tasks.cancel_strobe_limit(),
+                    None => {},
+                }
+            },
+            DeterrenceAction::Siren { volume, tone } => {
+                let mut volume = self.siren_volume(volume);
+                // The operator link may have dropped since the sequence started
+                if volume > 0 && !self.supervised.load(Ordering::SeqCst) {
+                    info!("📡 No operator link - skipping siren step");
+                    volume = 0;
+                }
+                if let Some(limit_db) = self.config.noise_policy.siren_limit_db(ctx) {
+                    let cap = self.config.auto_gain.siren_range.volume_for(limit_db);
+                    if volume > cap {
+                        info!("📏 Siren capped at {}% ({:.0} dB noise limit)", cap, limit_db);
+                        volume = cap;
+                    }
+                }
+                let tone = tone.unwrap_or_else(|| self.config.siren_tones.tone_for(ctx.threat_level));
+                if volume == 0 {
+                    self.siren_controller.deactivate().await?;
+                } else {
+                    self.siren_controller.activate(volume, tone).await?;
+                }
+                if let Some(record) = self.history().current_mut() {
+                    record.peak_siren_volume = record.peak_siren_volume.max(volume);
+                }
+                let mut state = self.state();
+                state.siren_active = volume > 0;
+                state.siren_volume = volume;
+                state.siren_tone = (volume > 0).then_some(tone);
+            },
+            DeterrenceAction::Voice { message, volume } => {
+                let volume = self.voice_volume(volume);
+
+                // Curated recordings take precedence over synthesized speech
+                let clip_key = match message {
+                    VoiceMessage::Threat => Some(ctx.situation.as_str()),
+                    VoiceMessage::Clip(key) => Some(key.as_str()),
+                    _ => None,
+                };
+                let clip = clip_key.and_then(|key| self.config.audio_library.lookup(key, ctx.threat_level).cloned());
+                if let Some(clip) = clip {
+                    self.schedule_clip(&clip, volume);
+                    if let Some(record) = self.history().current_mut() {
+                        record.messages.push(format!("[clip] {}", clip.key));
+                    }
+                    let mut state = self.state();
+                    state.voice_active = true;
+                    state.current_message = Some(format!("[clip] {}", clip.key));
+                    return Ok(());
+                }
+
+                if let VoiceMessage::Clip(key) = message {
+                    warn!("No recorded clip for '{}', falling back to spoken warning", key);
+                }
+
+                let mut broadcast = Vec::new();
+                for locale in self.broadcast_locales() {
+                    let text = match message {
+                        VoiceMessage::Threat | VoiceMessage::Clip(_) => self.config.message_catalog
+                            .threat_message(ctx.threat_level, ctx.situation, &locale),
+                        VoiceMessage::Ceremonial(event) => self.config.message_catalog.ceremonial(event, &locale),
+                        VoiceMessage::Text(text) => Some(text.as_str()),
+                    };
+                    let Some(text) = text else { continue };
+                    let text = match template::render(text, &self.config.template_variables) {
+                        Ok(text) => text,
+                        Err(e) => {
+                            // Built-in wording has no placeholders, so it always renders
+                            warn!("Voice template failed ({}), using built-in message", e);
+                            let builtin = MessageCatalog::builtin();
+                            let fallback = match message {
+                                VoiceMessage::Ceremonial(event) => builtin.ceremonial(event, &locale),
+                                _ => builtin.threat_message(ctx.threat_level, ctx.situation, &locale),
+                            };
+                            fallback.unwrap_or_default().to_string()
+                        },
+                    };
+                    // Fallback chains can land several languages on the same text
+                    if !text.is_empty() && !broadcast.contains(&text) {
+                        broadcast.push(text);
+                    }
+                }
+
+                for text in &broadcast {
+                    self.voice_controller.speak(text, volume, ctx.threat_level).await?;
+                }
+                if let Some(record) = self.history().current_mut() {
+                    record.messages.extend(broadcast.iter().cloned());
+                }
+                let mut state = self.state();
+                state.voice_active = !broadcast.is_empty();
+                state.current_message = (!broadcast.is_empty()).then(|| broadcast.join(" | "));
+            },
+            DeterrenceAction::DeactivateAll => {
+                self.deactivate().await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Stop every output and end the activation record
+    async fn deactivate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.tasks.cancel_clips() && !self.output_mode.is_rehearsal() {
+            self.speaker.stop().await?;
+        }
+
+        self.siren_controller.deactivate().await?;
+        self.strobe_controller.set_pattern(&StrobePattern::Off).await?;
+        self.voice_controller.stop().await?;
+        self.tasks.cancel_strobe_limit();
+
+        clear_outputs(&mut self.state());
+        self.history().finish(ActivationOutcome::Deactivated);
+
+        info!("🕊️ {}All deterrence systems deactivated - peaceful mode", self.output_mode.tag());
+        Ok(())
+    }
+}
+
+/// Clip playback and the strobe run limit started by steps, shared by the
+/// suite and the task running its sequence
+#[derive(Clone, Default)]
+struct StepTasks {
+    clips: Arc<Mutex<Vec<Task<()>>>>,
+    strobe_limit: Arc<Mutex<Option<Task<()>>>>,
+}
+
+impl StepTasks {
+    fn add_clip(&self, task: Task<()>) {
+        let mut clips = self.clips.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        clips.retain(|task| !task.is_finished());
+        clips.push(task);
+    }
+
+    /// Abort queued clips; true if there were any
+    fn cancel_clips(&self) -> bool {
+        let clips: Vec<Task<()>> = self.clips.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).drain(..).collect();
+        for task in &clips {
+            task.abort();
+        }
+        !clips.is_empty()
+    }
+
+    fn set_strobe_limit(&self, task: Task<()>) {
+        let previous = self.strobe_limit.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).replace(task);
+        if let Some(previous) = previous {
+            previous.abort();
+        }
+    }
+
+    fn cancel_strobe_limit(&self) {
+        if let Some(task) = self.strobe_limit.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take() {
+            task.abort();
+        }
+    }
+}
+
+/// One activation's policy steps, run in order with their delays, then
+/// its decay
+struct Escalation {
+    outputs: Outputs,
+    ctx: ActivationContext,
+    rule: String,
+    steps: Vec<DeterrenceStep>,
+    de_escalation: Option<DeEscalation>,
+}
+
+impl Escalation {
+    async fn run(self) {
+        for step in &self.steps {
+            if step.delay_ms > 0 {
+                sleep(Duration::from_millis(step.delay_ms)).await;
+            }
+            if let Err(e) = self.outputs.execute_step(&step.action, &self.ctx).await {
+                error!("Deterrence step of {} failed: {}", self.rule, e);
+                self.outputs.history().finish(ActivationOutcome::Failed);
+                return;
+            }
+        }
+
+        let (siren_volume, strobe_pattern) = {
+            let state = self.outputs.state();
+            (state.siren_volume, state.strobe_pattern.clone())
+        };
+        match self.ctx.threat_level {
+            ThreatLevel::Green => {},
+            ThreatLevel::Yellow => info!("🟡 Low deterrence activated ({})", self.rule),
+            ThreatLevel::Orange => warn!("🟠 Medium deterrence activated ({}): Siren {}%, Strobe {}",
+                                         self.rule, siren_volume, strobe_pattern.description()),
+            ThreatLevel::Red => error!("🔴 High deterrence activated ({}): Siren {}%, Strobe {}",
+                                       self.rule, siren_volume, strobe_pattern.description()),
+            ThreatLevel::Omega => error!("🔥 OMEGA PROTOCOL FULLY DEPLOYED 🔥"),
+        }
+
+        if let Some(de_escalation) = self.de_escalation {
+            de_escalation.run().await;
+        }
+    }
+}
+
+/// Background decay of deterrence outputs after the last activation
+struct DeEscalation {
+    state: Arc<Mutex<DeterrenceState>>,
+    history: Arc<Mutex<ActivationHistory>>,
+    interval: Duration,
+    quiet_period: Duration,
+    siren_step: u8,
+    siren_controller: SirenController,
+    strobe_controller: StrobeController,
+    voice_controller: VoiceController,
+}
+
+impl DeEscalation {
+    async fn run(self) {
+        let mut elapsed = Duration::ZERO;
+
+        loop {
+            sleep(self.interval).await;
+            elapsed += self.interval;
+
+            if elapsed >= self.quiet_period {
+                info!("🕊️ Quiet period of {}s elapsed - standing down deterrence", self.quiet_period.as_secs());
+                if let Err(e) = self.siren_controller.deactivate().await {
+                    error!("De-escalation failed to stop siren: {}", e);
+                }
+                if let Err(e) = self.strobe_controller.set_pattern(&StrobePattern::Off).await {
+                    error!("De-escalation failed to stop strobes: {}", e);
+                }
+                if let Err(e) = self.voice_controller.stop().await {
+                    error!("De-escalation failed to stop voice: {}", e);
+                }
+                clear_outputs(&mut lock_state(&self.state));
+                lock_history(&self.history).finish(ActivationOutcome::DeEscalated);
+                return;
+            }
+
+            let (siren_volume, siren_tone, strobe_pattern) = {
+                let state = lock_state(&self.state);
+                (state.siren_volume, state.siren_tone.unwrap_or(SirenTone::Wail), state.strobe_pattern.clone())
+            };
+            let next_volume = siren_volume.saturating_sub(self.siren_step);
+            let next_pattern = strobe_pattern.step_down();
+
+            if next_volume == siren_volume && next_pattern == strobe_pattern {
+                continue;
+            }
+
+            if next_volume != siren_volume {
+                let result = if next_volume == 0 {
+                    self.siren_controller.deactivate().await
+                } else {
+                    self.siren_controller.activate(next_volume, siren_tone).await
+                };
+                if let Err(e) = result {
+                    error!("De-escalation failed to adjust siren: {}", e);
+                }
+            }
+            if next_pattern != strobe_pattern {
+                if let Err(e) = self.strobe_controller.set_pattern(&next_pattern).await {
+                    error!("De-escalation failed to adjust strobes: {}", e);
+                }
+            }
+
+            {
+                let mut state = lock_state(&self.state);
+                state.siren_volume = next_volume;
+                state.siren_active = next_volume > 0;
+                if !state.siren_active {
+                    state.siren_tone = None;
+                }
+                state.strobe_active = next_pattern != StrobePattern::Off;
+                state.strobe_pattern = next_pattern.clone();
+                if !state.strobe_active {
+                    state.strobe_since = None;
+                }
+            }
+
+            info!("↘️ De-escalating: Siren {}%, Strobe {}", next_volume, next_pattern.description());
+        }
+    }
+}
+
+/// Scheduled playback of one recorded clip
+struct ClipPlayback {
+    path: std::path::PathBuf,
+    volume: u8,
+    schedule: PlaybackSchedule,
+    mixing: SirenMixing,
+    speaker: Arc<dyn SpeakerOutput>,
+    siren_controller: SirenController,
+    state: Arc<Mutex<DeterrenceState>>,
+    output_mode: OutputMode,
+}
+
+impl ClipPlayback {
+    async fn run(self) {
+        sleep(Duration::from_millis(self.schedule.delay_ms)).await;
+
+        for playback in 0..self.schedule.repeat {
+            if playback > 0 {
+                sleep(Duration::from_millis(self.schedule.interval_ms)).await;
+            }
+
+            let siren = {
+                let state = lock_state(&self.state);
+                state.siren_active.then_some((state.siren_volume, state.siren_tone.unwrap_or(SirenTone::Wail)))
+            };
+
+            if let Some((siren_volume, tone)) = siren {
+                let result = match self.mixing {
+                    SirenMixing::Overlay => Ok(()),
+                    SirenMixing::Duck { volume } => self.siren_controller.activate(volume.min(siren_volume), tone).await,
+                    SirenMixing::Pause => self.siren_controller.deactivate().await,
+                };
+                if let Err(e) = result {
+                    error!("Failed to mix siren for clip playback: {}", e);
+                }
+            }
+
+            if self.output_mode.is_rehearsal() {
+                info!("🎭 [REHEARSAL] Would play clip {} at {}% volume", self.path.display(), self.volume);
+            } else if let Err(e) = self.speaker.play(&self.path, self.volume).await {
+                error!("Failed to play clip {}: {}", self.path.display(), e);
+            }
+
+            // Restore whatever the siren should be doing now - it may have decayed meanwhile
+            if siren.is_some() && self.mixing != SirenMixing::Overlay {
+                let restore = {
+                    let state = lock_state(&self.state);
+                    state.siren_active.then_some((state.siren_volume, state.siren_tone.unwrap_or(SirenTone::Wail)))
+                };
+                if let Some((volume, tone)) = restore {
+                    if let Err(e) = self.siren_controller.activate(volume, tone).await {
+                        error!("Failed to restore siren after clip playback: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Live/rehearsal switch shared by every hardware controller and background task
+#[derive(Clone, Default)]
+struct OutputMode(Arc<AtomicBool>);
+
+impl OutputMode {
+    fn get(&self) -> DeterrenceMode {
+        if self.is_rehearsal() { DeterrenceMode::Rehearsal } else { DeterrenceMode::Live }
+    }
+
+    fn set(&self, mode: DeterrenceMode) {
+        self.0.store(mode == DeterrenceMode::Rehearsal, Ordering::SeqCst);
+    }
+
+    fn is_rehearsal(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Log prefix marking rehearsal events
+    fn tag(&self) -> &'static str {
+        if self.is_rehearsal() { "[REHEARSAL] " } else { "" }
+    }
+}
+
+/// Zones each output is currently routed to, shared with the controllers
+#[derive(Clone, Default)]
+struct RouteHandle(Arc<Mutex<ZoneRoutes>>);
+
+impl RouteHandle {
+    fn get(&self) -> ZoneRoutes {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    fn set(&self, routes: ZoneRoutes) {
+        *self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = routes;
+    }
+}
+
+/// Siren controller
+///
+/// Owns the tone generator an audio backend would pull samples from; level
+/// changes ramp rather than step so the amplifier never pops. With a
+/// `SirenOutput` attached, a task steps the generator and hands its pitch
+/// and level to the output until it ramps down to silence.
+#[derive(Clone)]
+struct SirenController {
+    generator: Arc<Mutex<ToneGenerator>>,
+    ramp_ms: u32,
+    mode: OutputMode,
+    routes: RouteHandle,
+    output: Option<Arc<dyn SirenOutput>>,
+    driver: Arc<Mutex<Option<Task<()>>>>,
+}
 
 impl SirenController {
-    fn new() -> Self {
-        Self
+    const SAMPLE_RATE: u32 = 48_000;
+    /// How often an attached output is given a new pitch
+    const DRIVE_INTERVAL: Duration = Duration::from_millis(10);
+
+    fn new(ramp_ms: u32, mode: OutputMode, routes: RouteHandle) -> Self {
+        Self {
+            generator: Arc::new(Mutex::new(ToneGenerator::new(SirenTone::Wail, Self::SAMPLE_RATE, ramp_ms))),
+            ramp_ms,
+            mode,
+            routes,
+            output: None,
+            driver: Arc::new(Mutex::new(None)),
+        }
     }
 
-    async fn activate(&self, volume: u8) -> Result<(), Box<dyn std::error::Error>> {
-        // Placeholder - would interface with actual siren hardware
-        info!("🔊 Siren activated at {}% volume (~{} dB)", volume, 80 + (volume * 40 / 100));
+    /// Keep the output following the generator until it falls silent
+    fn drive(&self) {
+        let Some(output) = self.output.clone() else { return };
+        let mut driver = self.driver.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if driver.as_ref().is_some_and(|task| !task.is_finished()) {
+            return;
+        }
+        let generator = Arc::clone(&self.generator);
+        *driver = Some(runtime::spawn(async move {
+            let mut failing = false;
+            loop {
+                let ((frequency, level), silent) = {
+                    let mut generator = generator.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    (generator.advance(Self::DRIVE_INTERVAL.as_secs_f32()), generator.is_silent())
+                };
+                match output.set_tone(frequency, level) {
+                    Ok(()) => failing = false,
+                    Err(e) if !failing => {
+                        error!("Siren output failed: {}", e);
+                        failing = true;
+                    },
+                    Err(_) => {},
+                }
+                if silent {
+                    break;
+                }
+                sleep(Self::DRIVE_INTERVAL).await;
+            }
+        }));
+    }
+
+    /// Cut the output at once, without a ramp
+    fn silence_now(&self) {
+        if let Some(task) = self.driver.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take() {
+            task.abort();
+        }
+        if let Some(output) = &self.output {
+            if let Err(e) = output.set_tone(0.0, 0.0) {
+                error!("Siren output failed to silence: {}", e);
+            }
+        }
+    }
+
+    async fn activate(&self, volume: u8, tone: SirenTone) -> Result<(), Box<dyn std::error::Error>> {
+        let zones = routing::describe(&self.routes.get().siren);
+        if self.mode.is_rehearsal() {
+            info!("🎭 [REHEARSAL] Would sound siren {} at {}% volume (~{} dB) on {}",
+                  tone.description(), volume, 80 + (volume as u16 * 40 / 100), zones);
+            return Ok(());
+        }
+        {
+            let mut generator = self.generator.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            generator.set_tone(tone);
+            generator.set_volume(volume);
+        }
+        self.drive();
+        info!("🔊 Siren {} at {}% volume (~{} dB) on {}, {}ms ramp",
+              tone.description(), volume, 80 + (volume as u16 * 40 / 100), zones, self.ramp_ms);
         Ok(())
     }
 
     async fn deactivate(&self) -> Result<(), Box<dyn std::error::Error>> {
-        info!("🔇 Siren deactivated");
+        if self.mode.is_rehearsal() {
+            info!("🎭 [REHEARSAL] Would silence siren");
+            return Ok(());
+        }
+        self.generator.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).set_volume(0);
+        self.drive();
+        info!("🔇 Siren deactivated ({}ms ramp down)", self.ramp_ms);
         Ok(())
     }
 }
 
-/// Strobe light controller (placeholder for hardware interface)
-struct StrobeController;
+/// Strobe light controller
+///
+/// Every pattern passes through the shared safety guard, so decay and test
+/// paths get the same photosensitivity limits as policy activations.
+#[derive(Clone)]
+struct StrobeController {
+    safety: Arc<Mutex<StrobeSafetyGuard>>,
+    patterns: Arc<PatternLibrary>,
+    playback: Arc<Mutex<Option<Task<()>>>>,
+    mode: OutputMode,
+    routes: RouteHandle,
+    light: Option<Arc<dyn LightOutput>>,
+}
 
 impl StrobeController {
-    fn new() -> Self {
-        Self
+    fn new(policy: StrobeSafetyPolicy, patterns: PatternLibrary, mode: OutputMode, routes: RouteHandle) -> Self {
+        Self {
+            safety: Arc::new(Mutex::new(StrobeSafetyGuard::new(policy))),
+            patterns: Arc::new(patterns),
+            playback: Arc::new(Mutex::new(None)),
+            mode,
+            routes,
+            light: None,
+        }
+    }
+
+    fn safety(&self) -> MutexGuard<'_, StrobeSafetyGuard> {
+        self.safety.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
     }
 
-    async fn set_pattern(&self, pattern: StrobePattern) -> Result<(), Box<dyn std::error::Error>> {
-        // Placeholder - would control LED arrays/strobe hardware
+    async fn set_pattern(&self, pattern: &StrobePattern) -> Result<(), Box<dyn std::error::Error>> {
+        let sequence = match pattern.sequence(&self.patterns) {
+            Some(sequence) => Some(sequence),
+            None if *pattern == StrobePattern::Off => None,
+            None => return Err(format!("Unknown strobe pattern {:?}", pattern).into()),
+        };
+
+        let mut playback = self.playback.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(task) = playback.take() {
+            task.abort();
+        }
+
+        let Some(sequence) = sequence else {
+            if let (Some(light), false) = (&self.light, self.mode.is_rehearsal()) {
+                light.set_light(Color::WHITE, 0.0)?;
+            }
+            info!("💡 {}Strobes OFF", self.mode.tag());
+            return Ok(());
+        };
+
+        let (sequence, output) = self.safety().limit(&sequence);
+        if output.limited {
+            warn!("🛡️ Strobe limited for photosensitive safety: now {:.1}Hz at {:.0}% duty",
+                  output.frequency_hz, output.duty_cycle * 100.0);
+        }
+
+        let zones = routing::describe(&self.routes.get().strobe);
+        if self.mode.is_rehearsal() {
+            info!("🎭 [REHEARSAL] Would strobe '{}' at {:.1}Hz, {:.0}% duty, {} segments on {}",
+                  sequence.name, output.frequency_hz, output.duty_cycle * 100.0, sequence.segments.len(), zones);
+            return Ok(());
+        }
+
         match pattern {
-            StrobePattern::Off => info!("💡 Strobes OFF"),
-            StrobePattern::Phoenix => info!("🔥 Phoenix strobe pattern: Rising flames effect"),
-            _ => info!("⚡ Strobe pattern: {} at {:.1}Hz", pattern.description(), pattern.frequency_hz()),
+            StrobePattern::Phoenix => info!("🔥 Phoenix strobe pattern: Rising flames effect on {}", zones),
+            StrobePattern::Custom(name) => info!("⚡ Custom strobe pattern '{}' at {:.1}Hz on {}", name, output.frequency_hz, zones),
+            _ => info!("⚡ Strobe pattern: {} at {:.1}Hz on {}", pattern.description(), output.frequency_hz, zones),
         }
+        *playback = Some(runtime::spawn(play_sequence(sequence, self.light.clone())));
         Ok(())
     }
 }
 
-/// Voice synthesis controller (placeholder for TTS system)
-struct VoiceController;
+/// Steps a ramp is played in on light hardware
+const RAMP_STEP_MS: u32 = 20;
+
+/// Loop a light sequence until replaced, on `light` when one is attached
+async fn play_sequence(sequence: CustomPattern, light: Option<Arc<dyn LightOutput>>) {
+    if sequence.cycle_ms() == 0 {
+        return;
+    }
+    let mut failing = false;
+    let mut show = |color: Color, intensity: f32| {
+        let Some(light) = &light else { return };
+        match light.set_light(color, intensity) {
+            Ok(()) => failing = false,
+            Err(e) if !failing => {
+                error!("Strobe output failed: {}", e);
+                failing = true;
+            },
+            Err(_) => {},
+        }
+    };
+    loop {
+        for segment in &sequence.segments {
+            match *segment {
+                StrobeSegment::On { color, intensity, duration_ms } => {
+                    tracing::trace!("strobe {} on {} at {:.0}%", sequence.name, color, intensity * 100.0);
+                    show(color, intensity);
+                    sleep(Duration::from_millis(duration_ms as u64)).await;
+                },
+                StrobeSegment::Off { duration_ms } => {
+                    tracing::trace!("strobe {} off", sequence.name);
+                    show(Color::WHITE, 0.0);
+                    sleep(Duration::from_millis(duration_ms as u64)).await;
+                },
+                StrobeSegment::Ramp { color, from, to, duration_ms } => {
+                    tracing::trace!("strobe {} ramp {} {:.0}%..{:.0}%", sequence.name, color, from * 100.0, to * 100.0);
+                    let steps = if light.is_some() { (duration_ms / RAMP_STEP_MS).max(1) } else { 1 };
+                    for step in 0..steps {
+                        show(color, from + (to - from) * step as f32 / steps as f32);
+                        sleep(Duration::from_millis((duration_ms / steps) as u64)).await;
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Voice synthesis controller - logs only unless the `tts` feature is enabled
+#[derive(Clone)]
+struct VoiceController {
+    mode: OutputMode,
+    routes: RouteHandle,
+    #[cfg(feature = "tts")]
+    config: TtsConfig,
+    #[cfg(feature = "tts")]
+    queue: Arc<std::sync::OnceLock<tts::SpeechQueue>>,
+}
 
 impl VoiceController {
-    fn new() -> Self {
-        Self
+    #[cfg(not(feature = "tts"))]
+    fn new(mode: OutputMode, routes: RouteHandle) -> Self {
+        Self { mode, routes }
     }
 
-    async fn speak(&self, message: &str, volume: u8) -> Result<(), Box<dyn std::error::Error>> {
-        // Placeholder - would use TTS engine and speaker hardware
-        info!("🗣️  Speaking at {}% volume: \"{}\"", volume, message);
+    #[cfg(feature = "tts")]
+    fn new(config: TtsConfig, mode: OutputMode, routes: RouteHandle) -> Self {
+        Self {
+            mode,
+            routes,
+            config,
+            queue: Arc::new(std::sync::OnceLock::new()),
+        }
+    }
+
+    #[cfg(feature = "tts")]
+    fn queue(&self) -> &tts::SpeechQueue {
+        // Started lazily so the suite can be constructed outside a runtime
+        self.queue.get_or_init(|| tts::SpeechQueue::spawn(self.config.clone()))
+    }
+
+    #[cfg(not(feature = "tts"))]
+    async fn speak(&self, message: &str, volume: u8, _priority: ThreatLevel) -> Result<(), Box<dyn std::error::Error>> {
+        let zones = routing::describe(&self.routes.get().voice);
+        if self.mode.is_rehearsal() {
+            info!("🎭 [REHEARSAL] Would speak at {}% volume on {}: \"{}\"", volume, zones, message);
+            return Ok(());
+        }
+        // Placeholder - enable the `tts` feature to drive a real speech engine
+        info!("🗣️  Speaking at {}% volume on {}: \"{}\"", volume, zones, message);
         Ok(())
     }
 
+    #[cfg(feature = "tts")]
+    async fn speak(&self, message: &str, volume: u8, priority: ThreatLevel) -> Result<(), Box<dyn std::error::Error>> {
+        let zones = routing::describe(&self.routes.get().voice);
+        if self.mode.is_rehearsal() {
+            info!("🎭 [REHEARSAL] Would speak at {}% volume ({} priority) on {}: \"{}\"", volume, priority.as_str(), zones, message);
+            return Ok(());
+        }
+        info!("🗣️  Voice routed to {}", zones);
+        self.queue().speak(message, volume, priority)
+    }
+
     async fn stop(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.mode.is_rehearsal() {
+            info!("🎭 [REHEARSAL] Would stop voice");
+            return Ok(());
+        }
+        #[cfg(feature = "tts")]
+        self.queue().stop()?;
         info!("🤐 Voice system stopped");
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A suite whose Orange rule lights the strobe at once and sounds the siren after `delay_ms`
+    fn suite(delay_ms: u64) -> DeterrenceSuite {
+        let rule = EscalationRule {
+            name: "test".to_string(),
+            threat_level: ThreatLevel::Orange,
+            situations: Vec::new(),
+            time_window: None,
+            zones: Vec::new(),
+            steps: vec![
+                DeterrenceStep { delay_ms: 0, action: DeterrenceAction::Strobe { pattern: StrobePattern::Alert } },
+                DeterrenceStep { delay_ms, action: DeterrenceAction::Siren { volume: Volume::Max, tone: None } },
+            ],
+        };
+        DeterrenceSuite::new(DeterrenceConfig {
+            escalation_policy: EscalationPolicy { rules: vec![rule] },
+            auto_de_escalate: false,
+            ..DeterrenceConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn activation_returns_while_the_sequence_runs() {
+        let mut suite = suite(100);
+        suite.activate(ThreatLevel::Orange, Situation::Unspecified).await.unwrap();
+        assert!(!suite.get_status().siren_active);
+
+        sleep(Duration::from_millis(300)).await;
+        let status = suite.get_status();
+        assert!(status.strobe_active);
+        assert!(status.siren_active);
+        assert!(!suite.is_responding());
+    }
+
+    #[tokio::test]
+    async fn deactivation_cancels_pending_steps() {
+        let mut suite = suite(100);
+        suite.activate(ThreatLevel::Orange, Situation::Unspecified).await.unwrap();
+        sleep(Duration::from_millis(20)).await;
+        assert!(suite.get_status().strobe_active);
+        suite.deactivate_all().await.unwrap();
+
+        sleep(Duration::from_millis(300)).await;
+        let status = suite.get_status();
+        assert!(!status.strobe_active);
+        assert!(!status.siren_active);
+    }
+
+    #[tokio::test]
+    async fn new_activation_supersedes_the_last() {
+        let mut suite = suite(100);
+        suite.activate(ThreatLevel::Orange, Situation::Unspecified).await.unwrap();
+        suite.activate(ThreatLevel::Green, Situation::Unspecified).await.unwrap();
+
+        sleep(Duration::from_millis(300)).await;
+        assert!(!suite.get_status().siren_active);
+    }
+
+    #[tokio::test]
+    async fn lost_link_mid_sequence_skips_the_siren() {
+        let mut suite = suite(100);
+        suite.activate(ThreatLevel::Orange, Situation::Unspecified).await.unwrap();
+        suite.set_supervised(false).await.unwrap();
+
+        sleep(Duration::from_millis(300)).await;
+        assert!(!suite.get_status().siren_active);
+    }
+}
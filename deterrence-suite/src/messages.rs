@@ -0,0 +1,229 @@
+use crate::template::{self, TemplateError};
+use dark_phoenix_core::{Situation, ThreatLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Language selection with an ordered fallback chain
+///
+/// English is always tried last so a message can always be produced.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Locale {
+    pub language: String,
+    #[serde(default)]
+    pub fallbacks: Vec<String>,
+}
+
+impl Locale {
+    pub fn new(language: &str) -> Self {
+        Self {
+            language: language.to_string(),
+            fallbacks: Vec::new(),
+        }
+    }
+
+    pub fn english() -> Self {
+        Self::new("en")
+    }
+
+    pub fn with_fallback(mut self, language: &str) -> Self {
+        self.fallbacks.push(language.to_string());
+        self
+    }
+
+    /// Languages to try, in order
+    pub fn chain(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.language.as_str())
+            .chain(self.fallbacks.iter().map(String::as_str))
+            .chain(std::iter::once("en"))
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+/// Voice message catalogs keyed by language, then by `<category>.<situation>`
+///
+/// Categories are the lowercase threat level names plus `ceremonial`. Each
+/// category may define a `default` entry used when the situation is unknown.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageCatalog {
+    pub languages: HashMap<String, HashMap<String, String>>,
+}
+
+impl Default for MessageCatalog {
+    fn default() -> Self {
+        Self::builtin().clone()
+    }
+}
+
+impl MessageCatalog {
+    /// Warning for a threat level and situation in the best available language
+    pub fn threat_message(&self, threat_level: ThreatLevel, situation: Situation, locale: &Locale) -> Option<&str> {
+        self.lookup(&threat_level.as_str().to_lowercase(), situation.as_str(), locale)
+    }
+
+    /// Ceremonial announcement for an event in the best available language
+    pub fn ceremonial(&self, event: &str, locale: &Locale) -> Option<&str> {
+        self.lookup("ceremonial", event, locale)
+    }
+
+    /// Stay in the listener's language where possible - a generic warning they
+    /// understand beats a specific one they don't
+    fn lookup(&self, category: &str, situation: &str, locale: &Locale) -> Option<&str> {
+        let specific = format!("{}.{}", category, situation);
+        let general = format!("{}.default", category);
+
+        locale.chain().find_map(|language| {
+            let messages = self.languages.get(language)?;
+            messages.get(&specific).or_else(|| messages.get(&general)).map(String::as_str)
+        })
+    }
+
+    /// Check every message parses and only uses placeholders with known values
+    pub fn validate(&self, variables: &HashMap<String, String>) -> Result<(), TemplateError> {
+        for (language, messages) in &self.languages {
+            for (key, text) in messages {
+                let invalid = |source| TemplateError::InvalidMessage {
+                    language: language.clone(),
+                    key: key.clone(),
+                    source: Box::new(source),
+                };
+                for name in template::placeholders(text).map_err(invalid)? {
+                    if !variables.contains_key(name) {
+                        return Err(invalid(TemplateError::Unresolved(name.to_string())));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add or replace entries, e.g. site-specific wording over the built-in catalog
+    pub fn merge(&mut self, other: MessageCatalog) {
+        for (language, messages) in other.languages {
+            self.languages.entry(language).or_default().extend(messages);
+        }
+    }
+
+    /// Catalog shipped with the crate (English, Spanish, French)
+    pub fn builtin() -> &'static MessageCatalog {
+        static BUILTIN: OnceLock<MessageCatalog> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            let mut languages = HashMap::new();
+            languages.insert("en".to_string(), entries(english, ENGLISH_CEREMONIAL));
+            languages.insert("es".to_string(), entries(spanish, SPANISH_CEREMONIAL));
+            languages.insert("fr".to_string(), entries(french, FRENCH_CEREMONIAL));
+            MessageCatalog { languages }
+        })
+    }
+}
+
+/// Flatten a language's situation table and ceremonial events into catalog keys
+fn entries(
+    messages: fn(Situation) -> &'static [(ThreatLevel, &'static str)],
+    ceremonial: &[(&str, &str)],
+) -> HashMap<String, String> {
+    let mut entries = HashMap::new();
+    for situation in Situation::ALL {
+        let key = match situation {
+            Situation::Unspecified => "default",
+            other => other.as_str(),
+        };
+        for (threat_level, text) in messages(situation) {
+            entries.insert(format!("{}.{}", threat_level.as_str().to_lowercase(), key), text.to_string());
+        }
+    }
+    for (event, text) in ceremonial {
+        entries.insert(format!("ceremonial.{}", event), text.to_string());
+    }
+    entries
+}
+
+// Built-in wording per situation - each table matches exhaustively, so adding
+// a `Situation` fails to compile until every language covers it. `Unspecified`
+// carries the per-level defaults used when a situation has no specific entry.
+
+fn english(situation: Situation) -> &'static [(ThreatLevel, &'static str)] {
+    match situation {
+        Situation::Unspecified => &[
+            (ThreatLevel::Green, "Guardian protocols active. Area under protection."),
+            (ThreatLevel::Yellow, "Dark Phoenix monitoring. Please proceed with caution."),
+            (ThreatLevel::Orange, "Warning: Threat level elevated. You are being recorded. Authorities have been notified."),
+            (ThreatLevel::Red, "HIGH THREAT CONFIRMED. ALL DETERRENCE SYSTEMS ACTIVE. SURRENDER IMMEDIATELY."),
+            (ThreatLevel::Omega, "⚠️ OMEGA PROTOCOL ACTIVATED ⚠️ DARK PHOENIX RISING ⚠️ MAXIMUM PROTECTION AUTHORIZED ⚠️ SURRENDER OR FACE CONSEQUENCES ⚠️"),
+        ],
+        Situation::Anomaly => &[(ThreatLevel::Yellow, "Anomaly detected. Please maintain calm behavior.")],
+        Situation::Proximity => &[(ThreatLevel::Yellow, "You are entering a protected zone. Please identify yourself.")],
+        Situation::Aggression => &[(ThreatLevel::Orange, "Aggressive behavior detected. Cease immediately or authorities will be contacted.")],
+        Situation::Weapon => &[(ThreatLevel::Orange, "Weapon detected. Drop the weapon and step back immediately.")],
+        Situation::WeaponDrawn => &[(ThreatLevel::Red, "WEAPON DRAWN. DROP WEAPON NOW. POLICE EN ROUTE. YOU ARE BEING RECORDED.")],
+        Situation::GroupThreat => &[(ThreatLevel::Orange, "Multiple aggressors detected. Disperse immediately or law enforcement will be summoned.")],
+        Situation::ImminentDanger => &[(ThreatLevel::Red, "IMMINENT DANGER DETECTED. EMERGENCY SERVICES CONTACTED. RETREAT IMMEDIATELY.")],
+        Situation::PhysicalAttack => &[(ThreatLevel::Red, "PHYSICAL ATTACK IN PROGRESS. MEDICAL AND POLICE ASSISTANCE REQUESTED.")],
+    }
+}
+
+const ENGLISH_CEREMONIAL: &[(&str, &str)] = &[
+    ("activation", "From the ashes of danger, the Dark Phoenix rises to protect the innocent."),
+    ("victory", "The Phoenix has prevailed. Peace is restored. Guardian watch continues."),
+    ("retreat", "Threat neutralized. The Phoenix returns to the shadows, ever watchful."),
+    ("default", "Dark Phoenix stands eternal vigil. None shall harm the protected."),
+];
+
+fn spanish(situation: Situation) -> &'static [(ThreatLevel, &'static str)] {
+    match situation {
+        Situation::Unspecified => &[
+            (ThreatLevel::Green, "Protocolos de guardián activos. Área bajo protección."),
+            (ThreatLevel::Yellow, "Dark Phoenix vigilando. Por favor, proceda con precaución."),
+            (ThreatLevel::Orange, "Advertencia: nivel de amenaza elevado. Está siendo grabado. Las autoridades han sido notificadas."),
+            (ThreatLevel::Red, "AMENAZA ALTA CONFIRMADA. TODOS LOS SISTEMAS DE DISUASIÓN ACTIVOS. RÍNDASE DE INMEDIATO."),
+            (ThreatLevel::Omega, "⚠️ PROTOCOLO OMEGA ACTIVADO ⚠️ DARK PHOENIX SE ALZA ⚠️ PROTECCIÓN MÁXIMA AUTORIZADA ⚠️ RÍNDASE O AFRONTE LAS CONSECUENCIAS ⚠️"),
+        ],
+        Situation::Anomaly => &[(ThreatLevel::Yellow, "Anomalía detectada. Por favor, mantenga la calma.")],
+        Situation::Proximity => &[(ThreatLevel::Yellow, "Está entrando en una zona protegida. Por favor, identifíquese.")],
+        Situation::Aggression => &[(ThreatLevel::Orange, "Comportamiento agresivo detectado. Deténgase de inmediato o se contactará a las autoridades.")],
+        Situation::Weapon => &[(ThreatLevel::Orange, "Arma detectada. Suelte el arma y retroceda de inmediato.")],
+        Situation::WeaponDrawn => &[(ThreatLevel::Red, "ARMA DESENFUNDADA. SUELTE EL ARMA AHORA. LA POLICÍA ESTÁ EN CAMINO. ESTÁ SIENDO GRABADO.")],
+        Situation::GroupThreat => &[(ThreatLevel::Orange, "Múltiples agresores detectados. Dispérsense de inmediato o se llamará a la policía.")],
+        Situation::ImminentDanger => &[(ThreatLevel::Red, "PELIGRO INMINENTE DETECTADO. SERVICIOS DE EMERGENCIA CONTACTADOS. RETÍRESE DE INMEDIATO.")],
+        Situation::PhysicalAttack => &[(ThreatLevel::Red, "ATAQUE FÍSICO EN CURSO. SE HA SOLICITADO ASISTENCIA MÉDICA Y POLICIAL.")],
+    }
+}
+
+const SPANISH_CEREMONIAL: &[(&str, &str)] = &[
+    ("activation", "De las cenizas del peligro, el Dark Phoenix se alza para proteger a los inocentes."),
+    ("victory", "El Fénix ha prevalecido. La paz ha sido restaurada. La guardia continúa."),
+    ("retreat", "Amenaza neutralizada. El Fénix regresa a las sombras, siempre vigilante."),
+    ("default", "Dark Phoenix mantiene su vigilia eterna. Nadie dañará a los protegidos."),
+];
+
+fn french(situation: Situation) -> &'static [(ThreatLevel, &'static str)] {
+    match situation {
+        Situation::Unspecified => &[
+            (ThreatLevel::Green, "Protocoles de gardien actifs. Zone sous protection."),
+            (ThreatLevel::Yellow, "Dark Phoenix en surveillance. Veuillez avancer avec prudence."),
+            (ThreatLevel::Orange, "Attention : niveau de menace élevé. Vous êtes enregistré. Les autorités ont été prévenues."),
+            (ThreatLevel::Red, "MENACE ÉLEVÉE CONFIRMÉE. TOUS LES SYSTÈMES DE DISSUASION ACTIFS. RENDEZ-VOUS IMMÉDIATEMENT."),
+            (ThreatLevel::Omega, "⚠️ PROTOCOLE OMEGA ACTIVÉ ⚠️ LE DARK PHOENIX S'ÉLÈVE ⚠️ PROTECTION MAXIMALE AUTORISÉE ⚠️ RENDEZ-VOUS OU ASSUMEZ LES CONSÉQUENCES ⚠️"),
+        ],
+        Situation::Anomaly => &[(ThreatLevel::Yellow, "Anomalie détectée. Veuillez rester calme.")],
+        Situation::Proximity => &[(ThreatLevel::Yellow, "Vous entrez dans une zone protégée. Veuillez vous identifier.")],
+        Situation::Aggression => &[(ThreatLevel::Orange, "Comportement agressif détecté. Cessez immédiatement ou les autorités seront contactées.")],
+        Situation::Weapon => &[(ThreatLevel::Orange, "Arme détectée. Lâchez l'arme et reculez immédiatement.")],
+        Situation::WeaponDrawn => &[(ThreatLevel::Red, "ARME DÉGAINÉE. LÂCHEZ L'ARME MAINTENANT. LA POLICE ARRIVE. VOUS ÊTES ENREGISTRÉ.")],
+        Situation::GroupThreat => &[(ThreatLevel::Orange, "Plusieurs agresseurs détectés. Dispersez-vous immédiatement ou la police sera appelée.")],
+        Situation::ImminentDanger => &[(ThreatLevel::Red, "DANGER IMMINENT DÉTECTÉ. SERVICES D'URGENCE CONTACTÉS. RECULEZ IMMÉDIATEMENT.")],
+        Situation::PhysicalAttack => &[(ThreatLevel::Red, "AGRESSION PHYSIQUE EN COURS. ASSISTANCE MÉDICALE ET POLICIÈRE DEMANDÉE.")],
+    }
+}
+
+const FRENCH_CEREMONIAL: &[(&str, &str)] = &[
+    ("activation", "Des cendres du danger, le Dark Phoenix s'élève pour protéger les innocents."),
+    ("victory", "Le Phénix a triomphé. La paix est rétablie. La garde continue."),
+    ("retreat", "Menace neutralisée. Le Phénix retourne dans l'ombre, toujours vigilant."),
+    ("default", "Dark Phoenix veille éternellement. Nul ne fera de mal aux protégés."),
+];
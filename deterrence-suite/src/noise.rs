@@ -0,0 +1,52 @@
+use crate::policy::{ActivationContext, TimeWindow};
+use dark_phoenix_core::ThreatLevel;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Local noise ordinance limits on siren output
+///
+/// During quiet hours the siren is withheld below `quiet_hours_override`, and
+/// the response falls back to strobe and voice only. Zone caps apply at all times.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoisePolicy {
+    /// Local time windows in which sirens are not sounded
+    pub quiet_hours: Vec<TimeWindow>,
+    /// Lowest threat level that may still sound the siren during quiet hours
+    pub quiet_hours_override: ThreatLevel,
+    /// Maximum siren level per zone (dB)
+    pub zone_limits_db: HashMap<String, f32>,
+    /// Maximum siren level outside any listed zone (dB, absent = uncapped)
+    pub default_limit_db: Option<f32>,
+}
+
+impl Default for NoisePolicy {
+    fn default() -> Self {
+        Self {
+            quiet_hours: Vec::new(),
+            quiet_hours_override: ThreatLevel::Red,
+            zone_limits_db: HashMap::new(),
+            default_limit_db: None,
+        }
+    }
+}
+
+impl NoisePolicy {
+    /// Whether quiet hours are in effect at the context's local time
+    pub fn in_quiet_hours(&self, ctx: &ActivationContext) -> bool {
+        self.quiet_hours.iter().any(|window| window.contains(ctx.local_time))
+    }
+
+    /// Whether the siren may sound at all for this activation
+    pub fn siren_permitted(&self, ctx: &ActivationContext) -> bool {
+        ctx.threat_level >= self.quiet_hours_override || !self.in_quiet_hours(ctx)
+    }
+
+    /// Siren level cap for the context's zone (dB)
+    pub fn siren_limit_db(&self, ctx: &ActivationContext) -> Option<f32> {
+        ctx.zone
+            .as_ref()
+            .and_then(|zone| self.zone_limits_db.get(zone))
+            .copied()
+            .or(self.default_limit_db)
+    }
+}
@@ -0,0 +1,317 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use thiserror::Error;
+
+/// Phoenix rising effect: flames swell from ember to full blaze and fade
+pub const PHOENIX_SOURCE: &str = "\
+    ramp 5%..100% 500ms #FF4500;
+    on 150ms #FFA500;
+    ramp 100%..20% 300ms #FF8C00;
+    off 50ms";
+
+/// RGB light color
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const WHITE: Color = Color { r: 255, g: 255, b: 255 };
+
+    fn parse(token: &str) -> Option<Color> {
+        let named = match token.to_ascii_lowercase().as_str() {
+            "white" => Some(Color::WHITE),
+            "red" => Some(Color { r: 255, g: 0, b: 0 }),
+            "blue" => Some(Color { r: 0, g: 0, b: 255 }),
+            "green" => Some(Color { r: 0, g: 255, b: 0 }),
+            "amber" => Some(Color { r: 255, g: 191, b: 0 }),
+            "orange" => Some(Color { r: 255, g: 165, b: 0 }),
+            "yellow" => Some(Color { r: 255, g: 255, b: 0 }),
+            _ => None,
+        };
+        if named.is_some() {
+            return named;
+        }
+
+        let hex = token.strip_prefix('#')?;
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Color { r: channel(0)?, g: channel(2)?, b: channel(4)? })
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+    }
+}
+
+/// Light hardware a strobe sequence is played on
+pub trait LightOutput: Send + Sync {
+    /// Show `color` at `intensity` (0.0-1.0); an intensity of 0 is dark
+    fn set_light(&self, color: Color, intensity: f32) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// One timed element of a strobe sequence
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StrobeSegment {
+    On { duration_ms: u32, color: Color, intensity: f32 },
+    Off { duration_ms: u32 },
+    /// Linear brightness change from `from` to `to` (0.0-1.0)
+    Ramp { duration_ms: u32, color: Color, from: f32, to: f32 },
+}
+
+impl StrobeSegment {
+    pub fn duration_ms(&self) -> u32 {
+        match self {
+            StrobeSegment::On { duration_ms, .. }
+            | StrobeSegment::Off { duration_ms }
+            | StrobeSegment::Ramp { duration_ms, .. } => *duration_ms,
+        }
+    }
+
+    fn scale(&mut self, factor: f32) {
+        let scaled = |ms: &mut u32| *ms = (*ms as f32 * factor).round() as u32;
+        match self {
+            StrobeSegment::On { duration_ms, .. }
+            | StrobeSegment::Off { duration_ms }
+            | StrobeSegment::Ramp { duration_ms, .. } => scaled(duration_ms),
+        }
+    }
+}
+
+/// A compiled, looping light sequence
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CustomPattern {
+    pub name: String,
+    pub segments: Vec<StrobeSegment>,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PatternError {
+    #[error("pattern '{pattern}' line {line}: {message}")]
+    Syntax { pattern: String, line: usize, message: String },
+    #[error("pattern '{0}' has no lit segments")]
+    Empty(String),
+}
+
+/// Brightness above which a segment counts as lit for flash analysis
+const LIT_THRESHOLD: f32 = 0.5;
+
+impl CustomPattern {
+    /// Compile pattern source - segments separated by `;` or newlines:
+    ///
+    /// ```text
+    /// on 80ms #FF0000 100%
+    /// off 40ms
+    /// ramp 0%..100% 1.5s amber
+    /// ```
+    pub fn compile(name: &str, source: &str) -> Result<Self, PatternError> {
+        let mut segments = Vec::new();
+
+        for (index, statement) in source.split([';', '\n']).enumerate() {
+            let tokens: Vec<&str> = statement.split_whitespace().collect();
+            let Some((keyword, args)) = tokens.split_first() else { continue };
+            let error = |message: String| PatternError::Syntax {
+                pattern: name.to_string(),
+                line: index + 1,
+                message,
+            };
+
+            let segment = match keyword.to_ascii_lowercase().as_str() {
+                "on" => {
+                    let duration_ms = parse_duration(args.first().copied()).map_err(error)?;
+                    let mut color = Color::WHITE;
+                    let mut intensity = 1.0;
+                    for arg in &args[1..] {
+                        if let Some(level) = parse_percent(arg) {
+                            intensity = level;
+                        } else {
+                            color = Color::parse(arg).ok_or_else(|| error(format!("unknown color '{}'", arg)))?;
+                        }
+                    }
+                    StrobeSegment::On { duration_ms, color, intensity }
+                },
+                "off" => StrobeSegment::Off {
+                    duration_ms: parse_duration(args.first().copied()).map_err(error)?,
+                },
+                "ramp" => {
+                    let range = args.first().ok_or_else(|| error("ramp needs a range like 0%..100%".to_string()))?;
+                    let (from, to) = range
+                        .split_once("..")
+                        .and_then(|(from, to)| Some((parse_percent(from)?, parse_percent(to)?)))
+                        .ok_or_else(|| error(format!("invalid ramp range '{}'", range)))?;
+                    let duration_ms = parse_duration(args.get(1).copied()).map_err(error)?;
+                    let color = match args.get(2) {
+                        Some(arg) => Color::parse(arg).ok_or_else(|| error(format!("unknown color '{}'", arg)))?,
+                        None => Color::WHITE,
+                    };
+                    StrobeSegment::Ramp { duration_ms, color, from, to }
+                },
+                other => return Err(error(format!("unknown segment '{}'", other))),
+            };
+            segments.push(segment);
+        }
+
+        let pattern = CustomPattern { name: name.to_string(), segments };
+        if pattern.lit_ms() == 0.0 {
+            return Err(PatternError::Empty(name.to_string()));
+        }
+        Ok(pattern)
+    }
+
+    /// Regular square-wave flashing at a frequency and duty cycle
+    pub fn square(name: &str, frequency_hz: f32, duty_cycle: f32) -> Self {
+        let period_ms = 1000.0 / frequency_hz.max(f32::EPSILON);
+        let on_ms = (period_ms * duty_cycle.clamp(0.0, 1.0)).round() as u32;
+        CustomPattern {
+            name: name.to_string(),
+            segments: vec![
+                StrobeSegment::On { duration_ms: on_ms, color: Color::WHITE, intensity: 1.0 },
+                StrobeSegment::Off { duration_ms: (period_ms.round() as u32).saturating_sub(on_ms) },
+            ],
+        }
+    }
+
+    pub fn cycle_ms(&self) -> u32 {
+        self.segments.iter().map(StrobeSegment::duration_ms).sum()
+    }
+
+    /// Dark-to-lit transitions per second while looping
+    pub fn flash_frequency_hz(&self) -> f32 {
+        let cycle_ms = self.cycle_ms();
+        if cycle_ms == 0 {
+            return 0.0;
+        }
+
+        // The sequence loops, so it starts in whatever state it ends in
+        let mut lit = self.segments.last().map(ends_lit).unwrap_or(false);
+        let mut flashes = 0;
+        for segment in &self.segments {
+            if !lit && starts_lit(segment) {
+                flashes += 1;
+            }
+            if !lit && ends_lit(segment) && !starts_lit(segment) {
+                flashes += 1;
+            }
+            lit = ends_lit(segment);
+        }
+        flashes as f32 * 1000.0 / cycle_ms as f32
+    }
+
+    /// Fraction of the cycle spent lit
+    pub fn duty_cycle(&self) -> f32 {
+        let cycle_ms = self.cycle_ms();
+        if cycle_ms == 0 {
+            return 0.0;
+        }
+        self.lit_ms() / cycle_ms as f32
+    }
+
+    fn lit_ms(&self) -> f32 {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                StrobeSegment::On { duration_ms, intensity, .. } if *intensity >= LIT_THRESHOLD => *duration_ms as f32,
+                StrobeSegment::Ramp { duration_ms, from, to, .. } => {
+                    // Portion of a linear ramp spent above the lit threshold
+                    let (low, high) = if from < to { (*from, *to) } else { (*to, *from) };
+                    if high < LIT_THRESHOLD {
+                        0.0
+                    } else if low >= LIT_THRESHOLD || high == low {
+                        *duration_ms as f32
+                    } else {
+                        *duration_ms as f32 * (high - LIT_THRESHOLD) / (high - low)
+                    }
+                },
+                _ => 0.0,
+            })
+            .sum()
+    }
+
+    /// Copy slowed down and padded with darkness to respect flash and duty limits
+    pub fn limited(&self, max_frequency_hz: f32, max_duty_cycle: f32) -> CustomPattern {
+        let mut limited = self.clone();
+
+        let frequency = limited.flash_frequency_hz();
+        if frequency > max_frequency_hz && max_frequency_hz > 0.0 {
+            let factor = frequency / max_frequency_hz;
+            for segment in &mut limited.segments {
+                segment.scale(factor);
+            }
+        }
+
+        let duty = limited.duty_cycle();
+        if duty > max_duty_cycle && max_duty_cycle > 0.0 {
+            let required_cycle = limited.lit_ms() / max_duty_cycle;
+            let padding = (required_cycle - limited.cycle_ms() as f32).ceil().max(0.0) as u32;
+            limited.segments.push(StrobeSegment::Off { duration_ms: padding });
+        }
+
+        limited
+    }
+}
+
+fn starts_lit(segment: &StrobeSegment) -> bool {
+    match segment {
+        StrobeSegment::On { intensity, .. } => *intensity >= LIT_THRESHOLD,
+        StrobeSegment::Off { .. } => false,
+        StrobeSegment::Ramp { from, .. } => *from >= LIT_THRESHOLD,
+    }
+}
+
+fn ends_lit(segment: &StrobeSegment) -> bool {
+    match segment {
+        StrobeSegment::On { intensity, .. } => *intensity >= LIT_THRESHOLD,
+        StrobeSegment::Off { .. } => false,
+        StrobeSegment::Ramp { to, .. } => *to >= LIT_THRESHOLD,
+    }
+}
+
+fn parse_duration(token: Option<&str>) -> Result<u32, String> {
+    let token = token.ok_or_else(|| "missing duration".to_string())?;
+    let (value, scale) = if let Some(ms) = token.strip_suffix("ms") {
+        (ms, 1.0)
+    } else if let Some(secs) = token.strip_suffix('s') {
+        (secs, 1000.0)
+    } else {
+        return Err(format!("duration '{}' needs a unit (ms or s)", token));
+    };
+    let value: f32 = value.parse().map_err(|_| format!("invalid duration '{}'", token))?;
+    if value < 0.0 {
+        return Err(format!("negative duration '{}'", token));
+    }
+    Ok((value * scale).round() as u32)
+}
+
+fn parse_percent(token: &str) -> Option<f32> {
+    let value: f32 = token.strip_suffix('%')?.parse().ok()?;
+    Some((value / 100.0).clamp(0.0, 1.0))
+}
+
+/// Compiled custom patterns by name
+#[derive(Debug, Clone, Default)]
+pub struct PatternLibrary {
+    patterns: HashMap<String, CustomPattern>,
+}
+
+impl PatternLibrary {
+    /// Compile every definition, reporting the first error
+    pub fn compile(definitions: &HashMap<String, String>) -> Result<Self, PatternError> {
+        let patterns = definitions
+            .iter()
+            .map(|(name, source)| Ok((name.clone(), CustomPattern::compile(name, source)?)))
+            .collect::<Result<_, PatternError>>()?;
+        Ok(Self { patterns })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CustomPattern> {
+        self.patterns.get(name)
+    }
+}
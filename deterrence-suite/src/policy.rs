@@ -0,0 +1,205 @@
+use crate::{SirenTone, StrobePattern};
+use chrono::{Local, NaiveTime};
+use dark_phoenix_core::{Position, Situation, ThreatLevel};
+pub use dark_phoenix_core::TimeWindow;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Declarative mapping from activation context to deterrence steps
+///
+/// Rules are evaluated in order and the first match wins, so specific rules
+/// (a zone, a night-time window) should be listed before catch-all rules.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationPolicy {
+    pub rules: Vec<EscalationRule>,
+}
+
+/// A single policy rule - all populated criteria must match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationRule {
+    pub name: String,
+    pub threat_level: ThreatLevel,
+    /// Situations this rule applies to (empty = any situation)
+    #[serde(default)]
+    pub situations: Vec<Situation>,
+    /// Local time window this rule applies to (absent = any time)
+    #[serde(default)]
+    pub time_window: Option<TimeWindow>,
+    /// Zones this rule applies to (empty = any zone)
+    #[serde(default)]
+    pub zones: Vec<String>,
+    pub steps: Vec<DeterrenceStep>,
+}
+
+/// One deterrence action, optionally preceded by a pause
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeterrenceStep {
+    /// Pause before performing this step (milliseconds)
+    #[serde(default)]
+    pub delay_ms: u64,
+    pub action: DeterrenceAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeterrenceAction {
+    Strobe { pattern: StrobePattern },
+    Siren {
+        volume: Volume,
+        /// Tone to sound (absent = the configured tone for the threat level)
+        #[serde(default)]
+        tone: Option<SirenTone>,
+    },
+    Voice { message: VoiceMessage, volume: Volume },
+    DeactivateAll,
+}
+
+/// Output volume relative to the configured limits
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Volume {
+    /// Fraction of the configured volume (0.0-1.0)
+    Scaled(f32),
+    /// Hardware maximum for the output
+    Max,
+}
+
+impl Volume {
+    pub fn resolve(&self, configured: u8, max: u8) -> u8 {
+        match self {
+            Volume::Scaled(fraction) => (configured as f32 * fraction.clamp(0.0, 1.0)).round() as u8,
+            Volume::Max => max,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoiceMessage {
+    /// Standard mythic warning for the threat level and situation
+    Threat,
+    /// Ceremonial announcement by event name
+    Ceremonial(String),
+    /// Literal text
+    Text(String),
+    /// Recorded clip from the audio library by key
+    Clip(String),
+}
+
+/// Everything a rule can match against
+#[derive(Debug, Clone)]
+pub struct ActivationContext {
+    pub threat_level: ThreatLevel,
+    pub situation: Situation,
+    pub zone: Option<String>,
+    pub local_time: NaiveTime,
+    /// Where the threat is, used to aim directional outputs
+    pub threat_position: Option<Position>,
+}
+
+impl ActivationContext {
+    /// Context for an activation happening now, outside any named zone
+    pub fn now(threat_level: ThreatLevel, situation: Situation) -> Self {
+        Self {
+            threat_level,
+            situation,
+            zone: None,
+            local_time: Local::now().time(),
+            threat_position: None,
+        }
+    }
+
+    pub fn in_zone(mut self, zone: &str) -> Self {
+        self.zone = Some(zone.to_string());
+        self
+    }
+
+    /// Aim outputs at a threat, e.g. from `ThreatAssessment.position`
+    pub fn with_threat_position(mut self, position: Option<Position>) -> Self {
+        self.threat_position = position;
+        self
+    }
+}
+
+impl EscalationRule {
+    pub fn matches(&self, ctx: &ActivationContext) -> bool {
+        if self.threat_level != ctx.threat_level {
+            return false;
+        }
+        if !self.situations.is_empty() && !self.situations.contains(&ctx.situation) {
+            return false;
+        }
+        if let Some(window) = &self.time_window {
+            if !window.contains(ctx.local_time) {
+                return false;
+            }
+        }
+        if !self.zones.is_empty() {
+            match &ctx.zone {
+                Some(zone) if self.zones.contains(zone) => {},
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+impl EscalationPolicy {
+    /// Find the first rule matching the context
+    pub fn select(&self, ctx: &ActivationContext) -> Option<&EscalationRule> {
+        self.rules.iter().find(|rule| rule.matches(ctx))
+    }
+
+    /// Load a policy from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Built-in response ladder - one catch-all rule per threat level
+    pub fn builtin(escalation_delay_ms: u64) -> Self {
+        use DeterrenceAction::*;
+
+        let step = |action| DeterrenceStep { delay_ms: 0, action };
+        let rule = |name: &str, threat_level, steps| EscalationRule {
+            name: name.to_string(),
+            threat_level,
+            situations: Vec::new(),
+            time_window: None,
+            zones: Vec::new(),
+            steps,
+        };
+
+        Self {
+            rules: vec![
+                rule("green-standdown", ThreatLevel::Green, vec![step(DeactivateAll)]),
+                rule("yellow-awareness", ThreatLevel::Yellow, vec![
+                    step(Strobe { pattern: StrobePattern::Pulse }),
+                    step(Voice { message: VoiceMessage::Threat, volume: Volume::Scaled(0.5) }),
+                ]),
+                rule("orange-warning", ThreatLevel::Orange, vec![
+                    step(Strobe { pattern: StrobePattern::Warning }),
+                    step(Siren { volume: Volume::Scaled(1.0 / 3.0), tone: None }),
+                    step(Voice { message: VoiceMessage::Threat, volume: Volume::Scaled(1.0) }),
+                ]),
+                rule("red-deterrence", ThreatLevel::Red, vec![
+                    step(Strobe { pattern: StrobePattern::Emergency }),
+                    step(Siren { volume: Volume::Scaled(2.0 / 3.0), tone: None }),
+                    step(Voice { message: VoiceMessage::Threat, volume: Volume::Scaled(1.0) }),
+                ]),
+                rule("omega-protocol", ThreatLevel::Omega, vec![
+                    step(Strobe { pattern: StrobePattern::Phoenix }),
+                    step(Siren { volume: Volume::Scaled(1.0), tone: None }),
+                    step(Voice { message: VoiceMessage::Threat, volume: Volume::Max }),
+                    DeterrenceStep {
+                        delay_ms: escalation_delay_ms,
+                        action: Voice {
+                            message: VoiceMessage::Ceremonial("activation".to_string()),
+                            volume: Volume::Max,
+                        },
+                    },
+                ]),
+            ],
+        }
+    }
+}
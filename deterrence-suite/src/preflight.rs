@@ -0,0 +1,36 @@
+//! Deterrence self-test on the preflight checklist
+
+use crate::DeterrenceSuite;
+use async_trait::async_trait;
+use dark_phoenix_core::{CheckOutcome, DroneState, PreflightCheck};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Runs the siren, strobe and voice self-test (`DeterrenceSuite::system_test`)
+pub struct SelfTestCheck {
+    suite: Arc<Mutex<DeterrenceSuite>>,
+}
+
+impl SelfTestCheck {
+    pub fn new(suite: Arc<Mutex<DeterrenceSuite>>) -> Self {
+        Self { suite }
+    }
+}
+
+#[async_trait]
+impl PreflightCheck for SelfTestCheck {
+    fn name(&self) -> &str {
+        "deterrence"
+    }
+
+    async fn run(&self, _drone: &DroneState) -> CheckOutcome {
+        let result = self.suite.lock().await.system_test().await.map_err(|e| e.to_string());
+        match result {
+            Ok(()) => CheckOutcome::pass("siren, strobe and voice self-test passed"),
+            Err(e) => CheckOutcome::fail(
+                format!("self-test failed: {}", e),
+                "check the siren, strobe and speaker wiring, then run `phoenix test deterrence`",
+            ),
+        }
+    }
+}
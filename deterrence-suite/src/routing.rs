@@ -0,0 +1,147 @@
+use dark_phoenix_core::Position;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Directional output group on the airframe
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputZone {
+    Front,
+    Rear,
+    Left,
+    Right,
+    /// Downward-facing outputs over the protectee/payload
+    PayloadFacing,
+}
+
+impl OutputZone {
+    pub const ALL: [OutputZone; 5] = [
+        OutputZone::Front,
+        OutputZone::Rear,
+        OutputZone::Left,
+        OutputZone::Right,
+        OutputZone::PayloadFacing,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputZone::Front => "front",
+            OutputZone::Rear => "rear",
+            OutputZone::Left => "left",
+            OutputZone::Right => "right",
+            OutputZone::PayloadFacing => "payload-facing",
+        }
+    }
+}
+
+impl fmt::Display for OutputZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Which zones an output type is sent to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoneSelection {
+    /// Only the zone facing the threat, or every zone when its position is unknown
+    TowardThreat,
+    /// Omnidirectional broadcast
+    All,
+    Fixed(Vec<OutputZone>),
+}
+
+/// Per-output zone selection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZoneRoutingConfig {
+    pub siren: ZoneSelection,
+    pub voice: ZoneSelection,
+    pub strobe: ZoneSelection,
+    /// Threats closer than this horizontally count as beneath the drone (metres)
+    pub payload_radius_m: f64,
+}
+
+impl Default for ZoneRoutingConfig {
+    fn default() -> Self {
+        Self {
+            siren: ZoneSelection::TowardThreat,
+            voice: ZoneSelection::TowardThreat,
+            strobe: ZoneSelection::TowardThreat,
+            payload_radius_m: 3.0,
+        }
+    }
+}
+
+/// Where the drone is and which way it faces
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pose {
+    pub position: Position,
+    /// Compass heading of the airframe's front (degrees, 0 = north)
+    pub heading_deg: f64,
+}
+
+/// Zones currently driven by each output
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ZoneRoutes {
+    pub siren: Vec<OutputZone>,
+    pub voice: Vec<OutputZone>,
+    pub strobe: Vec<OutputZone>,
+}
+
+impl Default for ZoneRoutes {
+    fn default() -> Self {
+        Self {
+            siren: OutputZone::ALL.to_vec(),
+            voice: OutputZone::ALL.to_vec(),
+            strobe: OutputZone::ALL.to_vec(),
+        }
+    }
+}
+
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+impl ZoneRoutingConfig {
+    /// Zone facing a threat, or `None` when either position is unknown
+    pub fn zone_toward(&self, pose: Option<&Pose>, threat: Option<&Position>) -> Option<OutputZone> {
+        let (pose, threat) = (pose?, threat?);
+
+        // Equirectangular approximation - plenty at deterrence ranges
+        let lat = pose.position.latitude.to_radians();
+        let north = (threat.latitude - pose.position.latitude).to_radians() * EARTH_RADIUS_M;
+        let east = (threat.longitude - pose.position.longitude).to_radians() * EARTH_RADIUS_M * lat.cos();
+        if north.hypot(east) < self.payload_radius_m {
+            return Some(OutputZone::PayloadFacing);
+        }
+
+        let bearing = east.atan2(north).to_degrees();
+        let relative = (bearing - pose.heading_deg).rem_euclid(360.0);
+        Some(match relative {
+            r if !(45.0..315.0).contains(&r) => OutputZone::Front,
+            r if r < 135.0 => OutputZone::Right,
+            r if r < 225.0 => OutputZone::Rear,
+            _ => OutputZone::Left,
+        })
+    }
+
+    /// Zones for each output given the threat-facing zone, if known
+    pub fn routes(&self, toward: Option<OutputZone>) -> ZoneRoutes {
+        let resolve = |selection: &ZoneSelection| match (selection, toward) {
+            (ZoneSelection::TowardThreat, Some(zone)) => vec![zone],
+            (ZoneSelection::TowardThreat, None) | (ZoneSelection::All, _) => OutputZone::ALL.to_vec(),
+            (ZoneSelection::Fixed(zones), _) => zones.clone(),
+        };
+        ZoneRoutes {
+            siren: resolve(&self.siren),
+            voice: resolve(&self.voice),
+            strobe: resolve(&self.strobe),
+        }
+    }
+}
+
+/// Human-readable zone list for logs
+pub fn describe(zones: &[OutputZone]) -> String {
+    if zones.len() == OutputZone::ALL.len() {
+        return "all zones".to_string();
+    }
+    zones.iter().map(OutputZone::as_str).collect::<Vec<_>>().join("+")
+}
@@ -0,0 +1,57 @@
+//! Siren and strobe on Raspberry Pi pins (`rpi-hw` feature)
+
+use crate::{Color, DeterrenceSuite, LightOutput, SirenOutput};
+use dark_phoenix_core::{HardwareError, HardwarePins, PwmOutput, StrobeDriver};
+use std::sync::{Arc, Mutex};
+use tracing::info;
+
+/// A piezo or amplifier input on a PWM pin
+pub struct PwmSiren(Mutex<PwmOutput>);
+
+impl PwmSiren {
+    pub fn new(pwm: PwmOutput) -> Self {
+        Self(Mutex::new(pwm))
+    }
+}
+
+impl SirenOutput for PwmSiren {
+    fn set_tone(&self, frequency_hz: f32, level: f32) -> Result<(), Box<dyn std::error::Error>> {
+        let mut pwm = self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let frequency = if frequency_hz > 0.0 { frequency_hz as f64 } else { pwm.frequency_hz() };
+        pwm.set(frequency, 0.5 * level.clamp(0.0, 1.0) as f64)?;
+        Ok(())
+    }
+}
+
+/// A GPIO strobe or WS2812 strip
+pub struct PinStrobe(Mutex<StrobeDriver>);
+
+impl PinStrobe {
+    pub fn new(driver: StrobeDriver) -> Self {
+        Self(Mutex::new(driver))
+    }
+}
+
+impl LightOutput for PinStrobe {
+    fn set_light(&self, color: Color, intensity: f32) -> Result<(), Box<dyn std::error::Error>> {
+        let mut driver = self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        driver.set((color.r, color.g, color.b), intensity)?;
+        Ok(())
+    }
+}
+
+impl DeterrenceSuite {
+    /// Drive the siren and strobe from the pins mapped in `pins`; unmapped
+    /// outputs stay placeholders
+    pub fn with_rpi_outputs(mut self, pins: &HardwarePins) -> Result<Self, HardwareError> {
+        if let Some(pwm) = pins.open_siren()? {
+            info!("🔊 Siren on {:?}", pins.siren);
+            self = self.with_siren_output(Arc::new(PwmSiren::new(pwm)));
+        }
+        if let Some(driver) = pins.open_strobe()? {
+            info!("💡 Strobe on {:?}", pins.strobe);
+            self = self.with_light_output(Arc::new(PinStrobe::new(driver)));
+        }
+        Ok(self)
+    }
+}
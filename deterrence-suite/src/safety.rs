@@ -0,0 +1,173 @@
+use crate::CustomPattern;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::warn;
+
+/// Photosensitive-epilepsy limits applied to every strobe output
+///
+/// Defaults follow broadcast flash guidance of no more than three flashes per
+/// second. Exceeding them requires an authenticated, time-limited override.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrobeSafetyPolicy {
+    pub max_frequency_hz: f32,
+    /// Maximum fraction of each flash cycle the light may be on (0.0-1.0)
+    pub max_duty_cycle: f32,
+    /// Longest continuous strobe run before lights are forced off (seconds)
+    pub max_continuous_secs: u64,
+    /// Longest override that can be granted at once (seconds)
+    pub max_override_secs: u64,
+    /// Operators allowed to override, mapped to the hex SHA-256 of their secret
+    pub override_credentials: HashMap<String, String>,
+}
+
+impl Default for StrobeSafetyPolicy {
+    fn default() -> Self {
+        Self {
+            max_frequency_hz: 3.0,
+            max_duty_cycle: 0.5,
+            max_continuous_secs: 60,
+            max_override_secs: 300,
+            override_credentials: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SafetyError {
+    #[error("operator '{0}' is not authorized to override strobe safety limits")]
+    UnknownOperator(String),
+    #[error("invalid override credentials for operator '{0}'")]
+    InvalidCredentials(String),
+}
+
+/// A granted exemption from the strobe safety limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrobeOverride {
+    pub operator: String,
+    pub reason: String,
+    pub granted_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Strobe output after safety limits have been applied
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrobeOutput {
+    pub frequency_hz: f32,
+    pub duty_cycle: f32,
+    /// Whether the requested output was reduced to satisfy the policy
+    pub limited: bool,
+}
+
+/// Enforces a `StrobeSafetyPolicy` and records every override granted
+#[derive(Debug, Clone)]
+pub struct StrobeSafetyGuard {
+    policy: StrobeSafetyPolicy,
+    active_override: Option<StrobeOverride>,
+    override_log: Vec<StrobeOverride>,
+}
+
+impl StrobeSafetyGuard {
+    pub fn new(policy: StrobeSafetyPolicy) -> Self {
+        Self {
+            policy,
+            active_override: None,
+            override_log: Vec::new(),
+        }
+    }
+
+    /// Authenticate an operator and lift the limits for up to `max_override_secs`
+    pub fn authorize_override(
+        &mut self,
+        operator: &str,
+        secret: &str,
+        duration: Duration,
+        reason: &str,
+    ) -> Result<StrobeOverride, SafetyError> {
+        let expected = self
+            .policy
+            .override_credentials
+            .get(operator)
+            .ok_or_else(|| SafetyError::UnknownOperator(operator.to_string()))?;
+
+        if !constant_time_eq(hash_secret(secret).as_bytes(), expected.to_lowercase().as_bytes()) {
+            warn!("⛔ Rejected strobe safety override for '{}': bad credentials", operator);
+            return Err(SafetyError::InvalidCredentials(operator.to_string()));
+        }
+
+        let duration = duration.min(Duration::from_secs(self.policy.max_override_secs));
+        let granted_at = Utc::now();
+        let grant = StrobeOverride {
+            operator: operator.to_string(),
+            reason: reason.to_string(),
+            granted_at,
+            expires_at: granted_at + chrono::Duration::from_std(duration).unwrap_or_default(),
+        };
+
+        warn!(
+            "⚠️ Strobe safety override granted to '{}' until {}: {}",
+            grant.operator, grant.expires_at, grant.reason
+        );
+        self.active_override = Some(grant.clone());
+        self.override_log.push(grant.clone());
+        Ok(grant)
+    }
+
+    pub fn revoke_override(&mut self) {
+        if let Some(grant) = self.active_override.take() {
+            warn!("Strobe safety override for '{}' revoked", grant.operator);
+        }
+    }
+
+    pub fn override_active(&self) -> bool {
+        self.active_override
+            .as_ref()
+            .is_some_and(|grant| grant.expires_at > Utc::now())
+    }
+
+    /// Every override granted since startup
+    pub fn override_log(&self) -> &[StrobeOverride] {
+        &self.override_log
+    }
+
+    /// Sequence adjusted to the frequency and duty-cycle caps, with its resulting output
+    pub fn limit(&self, sequence: &CustomPattern) -> (CustomPattern, StrobeOutput) {
+        let limited = if self.override_active() {
+            sequence.clone()
+        } else {
+            sequence.limited(self.policy.max_frequency_hz, self.policy.max_duty_cycle)
+        };
+        let output = StrobeOutput {
+            frequency_hz: limited.flash_frequency_hz(),
+            duty_cycle: limited.duty_cycle(),
+            limited: limited != *sequence,
+        };
+        (limited, output)
+    }
+
+    /// How long the strobe may keep running from `since`, honouring any override
+    pub fn remaining_run_time(&self, since: DateTime<Utc>) -> Duration {
+        let now = Utc::now();
+        let limit = since + chrono::Duration::seconds(self.policy.max_continuous_secs as i64);
+        let deadline = match &self.active_override {
+            Some(grant) if grant.expires_at > limit => grant.expires_at,
+            _ => limit,
+        };
+        (deadline - now).to_std().unwrap_or(Duration::ZERO)
+    }
+}
+
+/// Hex SHA-256 of an override secret, as stored in `override_credentials`
+pub fn hash_secret(secret: &str) -> String {
+    Sha256::digest(secret.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
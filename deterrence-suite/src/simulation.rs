@@ -0,0 +1,38 @@
+//! Scenario-backed audio hardware (`simulation` feature)
+
+use crate::{DeterrenceSuite, Microphone, SpeakerOutput};
+use async_trait::async_trait;
+use dark_phoenix_core::ScenarioPlayer;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::info;
+
+/// Reported when the scenario has no ambient track: a quiet room
+const QUIET_ROOM_DB: f32 = 40.0;
+
+#[async_trait]
+impl Microphone for ScenarioPlayer {
+    async fn ambient_level_db(&self) -> Result<f32, Box<dyn std::error::Error>> {
+        Ok(self.ambient_db().unwrap_or(QUIET_ROOM_DB))
+    }
+}
+
+#[async_trait]
+impl SpeakerOutput for ScenarioPlayer {
+    async fn play(&self, clip: &Path, volume: u8) -> Result<(), Box<dyn std::error::Error>> {
+        info!("🎬 [{:.1}s] Playing clip {} at {}% volume", self.elapsed_secs(), clip.display(), volume);
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<(), Box<dyn std::error::Error>> {
+        info!("🎬 [{:.1}s] Clip playback stopped", self.elapsed_secs());
+        Ok(())
+    }
+}
+
+impl DeterrenceSuite {
+    /// Hear the ambient level from `player` and play clips into its log
+    pub fn with_scenario(self, player: &ScenarioPlayer) -> Self {
+        self.with_speaker(Arc::new(player.clone())).with_microphone(Arc::new(player.clone()))
+    }
+}
@@ -0,0 +1,194 @@
+use dark_phoenix_core::ThreatLevel;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+
+/// Acoustically distinct siren tones
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SirenTone {
+    /// Slow rise and fall sweep
+    Wail,
+    /// Rapid rise and fall sweep
+    Yelp,
+    /// Alternating two-tone
+    HiLo,
+    /// Steady tone keyed on and off
+    Pulse,
+}
+
+impl SirenTone {
+    /// Instantaneous pitch at `t` seconds into the tone
+    pub fn frequency_at(&self, t: f32) -> f32 {
+        match self {
+            SirenTone::Wail => sweep(t, 4.0, 600.0, 1400.0),
+            SirenTone::Yelp => sweep(t, 0.3, 700.0, 1600.0),
+            SirenTone::HiLo => {
+                if t.rem_euclid(1.0) < 0.5 { 960.0 } else { 770.0 }
+            },
+            SirenTone::Pulse => 1000.0,
+        }
+    }
+
+    /// Keying envelope (0.0-1.0) at `t` seconds - only `Pulse` is keyed, with
+    /// short linear edges so each pulse doesn't click
+    pub fn keying_at(&self, t: f32) -> f32 {
+        const EDGE: f32 = 0.01;
+        match self {
+            SirenTone::Pulse => {
+                let position = t.rem_euclid(0.5);
+                if position < EDGE {
+                    position / EDGE
+                } else if position < 0.25 - EDGE {
+                    1.0
+                } else if position < 0.25 {
+                    (0.25 - position) / EDGE
+                } else {
+                    0.0
+                }
+            },
+            _ => 1.0,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            SirenTone::Wail => "wail",
+            SirenTone::Yelp => "yelp",
+            SirenTone::HiLo => "hi-lo",
+            SirenTone::Pulse => "pulse",
+        }
+    }
+}
+
+/// Every tone pattern repeats exactly within this many seconds
+const TONE_CYCLE_SECS: f32 = 12.0;
+
+/// Triangle sweep between `low` and `high` Hz over `period` seconds
+fn sweep(t: f32, period: f32, low: f32, high: f32) -> f32 {
+    let position = (t / period).fract();
+    let triangle = if position < 0.5 { position * 2.0 } else { 2.0 - position * 2.0 };
+    low + (high - low) * triangle
+}
+
+/// Which tone each threat level sounds, plus amplifier ramp time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SirenToneConfig {
+    pub tones: HashMap<ThreatLevel, SirenTone>,
+    /// Volume ramp duration on start, stop, and level changes (milliseconds)
+    pub ramp_ms: u32,
+}
+
+impl Default for SirenToneConfig {
+    fn default() -> Self {
+        Self {
+            tones: HashMap::from([
+                (ThreatLevel::Green, SirenTone::Pulse),
+                (ThreatLevel::Yellow, SirenTone::Pulse),
+                (ThreatLevel::Orange, SirenTone::Wail),
+                (ThreatLevel::Red, SirenTone::Yelp),
+                (ThreatLevel::Omega, SirenTone::HiLo),
+            ]),
+            ramp_ms: 250,
+        }
+    }
+}
+
+impl SirenToneConfig {
+    pub fn tone_for(&self, threat_level: ThreatLevel) -> SirenTone {
+        self.tones.get(&threat_level).copied().unwrap_or(SirenTone::Wail)
+    }
+}
+
+/// Sample generator for siren tones with click-free gain ramps
+///
+/// Phase is accumulated across pitch changes so sweeps stay continuous, and
+/// gain moves linearly towards its target so the amplifier never sees a step.
+#[derive(Debug, Clone)]
+pub struct ToneGenerator {
+    tone: SirenTone,
+    sample_rate: u32,
+    ramp_samples: u32,
+    elapsed: f32,
+    phase: f32,
+    gain: f32,
+    target_gain: f32,
+    gain_step: f32,
+}
+
+impl ToneGenerator {
+    pub fn new(tone: SirenTone, sample_rate: u32, ramp_ms: u32) -> Self {
+        Self {
+            tone,
+            sample_rate: sample_rate.max(1),
+            ramp_samples: (sample_rate as u64 * ramp_ms as u64 / 1000).max(1) as u32,
+            elapsed: 0.0,
+            phase: 0.0,
+            gain: 0.0,
+            target_gain: 0.0,
+            gain_step: 0.0,
+        }
+    }
+
+    pub fn tone(&self) -> SirenTone {
+        self.tone
+    }
+
+    /// Switch tone without resetting phase or gain
+    pub fn set_tone(&mut self, tone: SirenTone) {
+        if tone != self.tone {
+            self.tone = tone;
+            self.elapsed = 0.0;
+        }
+    }
+
+    /// Ramp towards a volume (0-100) over the configured ramp time
+    pub fn set_volume(&mut self, volume: u8) {
+        self.target_gain = volume.min(100) as f32 / 100.0;
+        self.gain_step = (self.target_gain - self.gain).abs() / self.ramp_samples as f32;
+    }
+
+    /// Whether the output has fully ramped down to silence
+    pub fn is_silent(&self) -> bool {
+        self.gain == 0.0 && self.target_gain == 0.0
+    }
+
+    /// Move on by `seconds` without rendering samples, returning the pitch
+    /// (Hz) and level (0.0-1.0) reached, for outputs driven by frequency
+    pub fn advance(&mut self, seconds: f32) -> (f32, f32) {
+        let samples = (seconds * self.sample_rate as f32).max(0.0);
+        let step = self.gain_step * samples;
+        self.gain = if self.gain < self.target_gain {
+            (self.gain + step).min(self.target_gain)
+        } else {
+            (self.gain - step).max(self.target_gain)
+        };
+        self.elapsed = (self.elapsed + seconds.max(0.0)) % TONE_CYCLE_SECS;
+        (self.tone.frequency_at(self.elapsed), self.gain * self.tone.keying_at(self.elapsed))
+    }
+
+    /// Fill `buffer` with the next mono samples in -1.0..=1.0
+    pub fn fill(&mut self, buffer: &mut [f32]) {
+        let dt = 1.0 / self.sample_rate as f32;
+        for sample in buffer.iter_mut() {
+            if self.gain < self.target_gain {
+                self.gain = (self.gain + self.gain_step).min(self.target_gain);
+            } else if self.gain > self.target_gain {
+                self.gain = (self.gain - self.gain_step).max(self.target_gain);
+            }
+
+            let frequency = self.tone.frequency_at(self.elapsed);
+            self.phase = (self.phase + TAU * frequency * dt) % TAU;
+            *sample = self.phase.sin() * self.gain * self.tone.keying_at(self.elapsed);
+            // Wrap at a common multiple of every tone period to keep f32 precision
+            self.elapsed = (self.elapsed + dt) % TONE_CYCLE_SECS;
+        }
+    }
+}
+
+/// A siren driven by pitch and level rather than samples, e.g. a piezo on a
+/// PWM pin
+pub trait SirenOutput: Send + Sync {
+    /// Sound `frequency_hz` at `level` (0.0-1.0); a level of 0 is silence
+    fn set_tone(&self, frequency_hz: f32, level: f32) -> Result<(), Box<dyn std::error::Error>>;
+}
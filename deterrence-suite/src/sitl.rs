@@ -0,0 +1,50 @@
+//! Siren and strobe in PX4 SITL + Gazebo (`sitl` feature)
+
+use crate::{Color, DeterrenceSuite, LightOutput, SirenOutput};
+use dark_phoenix_core::SitlActuators;
+use std::sync::Arc;
+use tracing::info;
+
+/// The siren, as an intent
+pub struct SitlSiren(SitlActuators);
+
+impl SitlSiren {
+    pub fn new(actuators: SitlActuators) -> Self {
+        Self(actuators)
+    }
+}
+
+impl SirenOutput for SitlSiren {
+    fn set_tone(&self, frequency_hz: f32, level: f32) -> Result<(), Box<dyn std::error::Error>> {
+        self.0.update(|intents| {
+            intents.siren_frequency_hz = frequency_hz;
+            intents.siren_level = level.clamp(0.0, 1.0);
+        });
+        Ok(())
+    }
+}
+
+/// The strobe, as an intent; the simulator only sees its brightness
+pub struct SitlStrobe(SitlActuators);
+
+impl SitlStrobe {
+    pub fn new(actuators: SitlActuators) -> Self {
+        Self(actuators)
+    }
+}
+
+impl LightOutput for SitlStrobe {
+    fn set_light(&self, _color: Color, intensity: f32) -> Result<(), Box<dyn std::error::Error>> {
+        self.0.update(|intents| intents.strobe_intensity = intensity.clamp(0.0, 1.0));
+        Ok(())
+    }
+}
+
+impl DeterrenceSuite {
+    /// Sound the siren and flash the strobe in the simulator
+    pub fn with_sitl_outputs(self, actuators: &SitlActuators) -> Self {
+        info!("🎮 Siren and strobe published to SITL");
+        self.with_siren_output(Arc::new(SitlSiren::new(actuators.clone())))
+            .with_light_output(Arc::new(SitlStrobe::new(actuators.clone())))
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Problems found while parsing or rendering a voice message template
+#[derive(Debug, Error, PartialEq)]
+pub enum TemplateError {
+    #[error("placeholder {{{0}}} has no value")]
+    Unresolved(String),
+    #[error("unterminated placeholder starting at byte {0}")]
+    Unterminated(usize),
+    #[error("unmatched '}}' at byte {0}")]
+    UnmatchedClose(usize),
+    #[error("message {language}/{key}: {source}")]
+    InvalidMessage {
+        language: String,
+        key: String,
+        #[source]
+        source: Box<TemplateError>,
+    },
+}
+
+enum Segment<'a> {
+    Literal(&'a str),
+    Placeholder(&'a str),
+}
+
+/// Split a template into literal text and `{name}` placeholders
+///
+/// `{{` and `}}` produce literal braces.
+fn parse(template: &str) -> Result<Vec<Segment<'_>>, TemplateError> {
+    let mut segments = Vec::new();
+    let mut literal_start = 0;
+    let mut chars = template.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek().map(|(_, next)| *next) == Some(c) => {
+                segments.push(Segment::Literal(&template[literal_start..index + 1]));
+                chars.next();
+                literal_start = index + 2;
+            },
+            '{' => {
+                segments.push(Segment::Literal(&template[literal_start..index]));
+                let close = template[index..].find('}').ok_or(TemplateError::Unterminated(index))?;
+                segments.push(Segment::Placeholder(template[index + 1..index + close].trim()));
+                while chars.peek().is_some_and(|(i, _)| *i <= index + close) {
+                    chars.next();
+                }
+                literal_start = index + close + 1;
+            },
+            '}' => return Err(TemplateError::UnmatchedClose(index)),
+            _ => {},
+        }
+    }
+
+    segments.push(Segment::Literal(&template[literal_start..]));
+    Ok(segments)
+}
+
+/// Names of all placeholders used by a template
+pub fn placeholders(template: &str) -> Result<Vec<&str>, TemplateError> {
+    Ok(parse(template)?
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Placeholder(name) => Some(name),
+            Segment::Literal(_) => None,
+        })
+        .collect())
+}
+
+/// Substitute every placeholder, failing if any has no value
+pub fn render(template: &str, variables: &HashMap<String, String>) -> Result<String, TemplateError> {
+    let mut rendered = String::with_capacity(template.len());
+    for segment in parse(template)? {
+        match segment {
+            Segment::Literal(text) => rendered.push_str(text),
+            Segment::Placeholder(name) => {
+                let value = variables
+                    .get(name)
+                    .ok_or_else(|| TemplateError::Unresolved(name.to_string()))?;
+                rendered.push_str(value);
+            },
+        }
+    }
+    Ok(rendered)
+}
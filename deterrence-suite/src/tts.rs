@@ -0,0 +1,238 @@
+use dark_phoenix_core::{runtime, ThreatLevel};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// Text-to-speech backend configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TtsConfig {
+    pub engine: TtsEngine,
+    /// Directory of pre-rendered `<threat level>.wav` clips used when the engine is unavailable
+    pub fallback_dir: PathBuf,
+    /// Command used to play WAV files (program followed by arguments)
+    pub player: Vec<String>,
+}
+
+impl Default for TtsConfig {
+    fn default() -> Self {
+        Self {
+            engine: TtsEngine::EspeakNg { voice: "en-us".to_string() },
+            fallback_dir: PathBuf::from("/usr/share/dark-phoenix/voice"),
+            player: vec!["aplay".to_string(), "-q".to_string()],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TtsEngine {
+    /// espeak-ng speaking directly to the audio device
+    EspeakNg { voice: String },
+    /// Piper neural TTS rendering to a WAV file, then played back
+    Piper { model: PathBuf },
+}
+
+/// A queued message - higher threat levels are spoken first and preempt lower ones
+#[derive(Debug)]
+struct Utterance {
+    text: String,
+    volume: u8,
+    priority: ThreatLevel,
+    sequence: u64,
+}
+
+impl PartialEq for Utterance {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Utterance {}
+
+impl PartialOrd for Utterance {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Utterance {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Max-heap: highest priority first, then oldest message first
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+enum SpeechCommand {
+    Speak(Utterance),
+    Stop,
+}
+
+/// Handle to the background playback queue
+#[derive(Clone)]
+pub(crate) struct SpeechQueue {
+    sender: mpsc::UnboundedSender<SpeechCommand>,
+    sequence: std::sync::Arc<std::sync::atomic::AtomicU64>,
+}
+
+impl SpeechQueue {
+    /// Spawn the playback worker; the speech processes it starts need
+    /// tokio's reactor
+    pub(crate) fn spawn(config: TtsConfig) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        runtime::spawn(PlaybackWorker { config, receiver, pending: BinaryHeap::new() }.run());
+        Self {
+            sender,
+            sequence: Default::default(),
+        }
+    }
+
+    pub(crate) fn speak(&self, text: &str, volume: u8, priority: ThreatLevel) -> Result<(), Box<dyn std::error::Error>> {
+        let sequence = self.sequence.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.sender
+            .send(SpeechCommand::Speak(Utterance { text: text.to_string(), volume, priority, sequence }))
+            .map_err(|_| "Speech playback worker has stopped")?;
+        Ok(())
+    }
+
+    pub(crate) fn stop(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.sender
+            .send(SpeechCommand::Stop)
+            .map_err(|_| "Speech playback worker has stopped")?;
+        Ok(())
+    }
+}
+
+struct PlaybackWorker {
+    config: TtsConfig,
+    receiver: mpsc::UnboundedReceiver<SpeechCommand>,
+    pending: BinaryHeap<Utterance>,
+}
+
+impl PlaybackWorker {
+    async fn run(mut self) {
+        loop {
+            let utterance = match self.pending.pop() {
+                Some(utterance) => utterance,
+                None => match self.receiver.recv().await {
+                    Some(SpeechCommand::Speak(utterance)) => utterance,
+                    Some(SpeechCommand::Stop) => continue,
+                    None => return,
+                },
+            };
+
+            let mut child = match self.start_playback(&utterance).await {
+                Some(child) => child,
+                None => continue,
+            };
+
+            loop {
+                tokio::select! {
+                    _ = child.wait() => break,
+                    command = self.receiver.recv() => match command {
+                        Some(SpeechCommand::Speak(next)) => {
+                            let preempt = next.priority > utterance.priority;
+                            self.pending.push(next);
+                            if preempt {
+                                info!("🗣️  Higher priority message preempting {} broadcast", utterance.priority.as_str());
+                                let _ = child.kill().await;
+                                break;
+                            }
+                        },
+                        Some(SpeechCommand::Stop) => {
+                            self.pending.clear();
+                            let _ = child.kill().await;
+                            break;
+                        },
+                        None => {
+                            let _ = child.wait().await;
+                            return;
+                        },
+                    },
+                }
+            }
+        }
+    }
+
+    /// Start speaking through the TTS engine, falling back to a pre-rendered clip
+    async fn start_playback(&self, utterance: &Utterance) -> Option<Child> {
+        info!("🗣️  Speaking at {}% volume: \"{}\"", utterance.volume, utterance.text);
+
+        match self.synthesize(utterance).await {
+            Ok(child) => return Some(child),
+            Err(e) => warn!("TTS engine unavailable ({}), using pre-rendered clip", e),
+        }
+
+        let clip = self
+            .config
+            .fallback_dir
+            .join(format!("{}.wav", utterance.priority.as_str().to_lowercase()));
+        match self.play_wav(&clip) {
+            Ok(child) => Some(child),
+            Err(e) => {
+                warn!("Fallback clip {} could not be played: {}", clip.display(), e);
+                None
+            },
+        }
+    }
+
+    async fn synthesize(&self, utterance: &Utterance) -> Result<Child, Box<dyn std::error::Error>> {
+        match &self.config.engine {
+            TtsEngine::EspeakNg { voice } => {
+                // espeak-ng amplitude runs 0-200 with 100 as the default level
+                let amplitude = (utterance.volume as u16 * 2).to_string();
+                let child = Command::new("espeak-ng")
+                    .args(["-v", voice, "-a", &amplitude, &utterance.text])
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .kill_on_drop(true)
+                    .spawn()?;
+                Ok(child)
+            },
+            TtsEngine::Piper { model } => {
+                use tokio::io::AsyncWriteExt;
+
+                let output = std::env::temp_dir().join(format!("dark-phoenix-tts-{}.wav", utterance.sequence));
+                let mut piper = Command::new("piper")
+                    .arg("--model")
+                    .arg(model)
+                    .arg("--output_file")
+                    .arg(&output)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .kill_on_drop(true)
+                    .spawn()?;
+                if let Some(mut stdin) = piper.stdin.take() {
+                    stdin.write_all(utterance.text.as_bytes()).await?;
+                }
+                let status = piper.wait().await?;
+                if !status.success() {
+                    return Err(format!("piper exited with {}", status).into());
+                }
+                self.play_wav(&output)
+            },
+        }
+    }
+
+    fn play_wav(&self, path: &std::path::Path) -> Result<Child, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Err(format!("{} not found", path.display()).into());
+        }
+        let (program, args) = self.config.player.split_first().ok_or("No audio player configured")?;
+        let child = Command::new(program)
+            .args(args)
+            .arg(path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        Ok(child)
+    }
+}
@@ -0,0 +1,443 @@
+//! Alert rules: who hears about which mission events, and how insistently
+
+use crate::{deliver, Delivery, EmergencyContactConfig, EmergencyNotification, LinkedClient, NotificationChannel, PushChannel, SmsChannel, VoiceCallChannel};
+use chrono::{DateTime, Local, NaiveTime, Utc};
+use dark_phoenix_core::runtime;
+use dark_phoenix_core::{DroneState, EventType, MissionEvent, ModuleResult, ThreatLevel, TimeWindow};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Alerts remembered for `alerts()`
+const ALERT_HISTORY: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        })
+    }
+}
+
+/// One step of an escalation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertChannel {
+    Push,
+    Sms,
+    Call,
+}
+
+impl fmt::Display for AlertChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AlertChannel::Push => "push",
+            AlertChannel::Sms => "SMS",
+            AlertChannel::Call => "voice call",
+        })
+    }
+}
+
+/// Someone alerts can reach
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertContact {
+    pub name: String,
+    /// For SMS and voice calls, through the telephony account
+    #[serde(default)]
+    pub phone: Option<String>,
+    /// ntfy-compatible topic their phone subscribes to
+    #[serde(default)]
+    pub push_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertRule {
+    pub name: String,
+    /// Events it matches (empty = any)
+    pub event_types: Vec<EventType>,
+    /// Lowest threat level it matches
+    pub min_level: ThreatLevel,
+    /// Geofence zones the drone must be in (empty = anywhere)
+    pub zones: Vec<String>,
+    pub severity: Severity,
+    /// Names from the contact list
+    pub contacts: Vec<String>,
+    /// Channels tried in turn until the alert is acknowledged
+    pub escalation: Vec<AlertChannel>,
+    /// Wait for an acknowledgment before the next step
+    pub step_secs: u64,
+    /// Fold repeats into the open alert for this long after the last one
+    pub dedup_window_secs: u64,
+}
+
+impl Default for AlertRule {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            event_types: Vec::new(),
+            min_level: ThreatLevel::Green,
+            zones: Vec::new(),
+            severity: Severity::Medium,
+            contacts: Vec::new(),
+            escalation: vec![AlertChannel::Push, AlertChannel::Sms, AlertChannel::Call],
+            step_secs: 120,
+            dedup_window_secs: 600,
+        }
+    }
+}
+
+impl AlertRule {
+    fn matches(&self, event: &MissionEvent, zones: &[&str]) -> bool {
+        (self.event_types.is_empty() || self.event_types.contains(&event.event_type))
+            && event.threat_level >= self.min_level
+            && (self.zones.is_empty() || zones.iter().any(|zone| self.zones.iter().any(|wanted| wanted == zone)))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertingConfig {
+    pub contacts: Vec<AlertContact>,
+    pub rules: Vec<AlertRule>,
+    /// Local times low-severity alerts stay silent (absent = never)
+    pub quiet_hours: Option<TimeWindow>,
+    /// Lowest severity that still goes out during quiet hours
+    pub quiet_hours_min_severity: Severity,
+}
+
+impl Default for AlertingConfig {
+    fn default() -> Self {
+        Self {
+            contacts: Vec::new(),
+            rules: Vec::new(),
+            quiet_hours: None,
+            quiet_hours_min_severity: Severity::High,
+        }
+    }
+}
+
+impl AlertingConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (i, rule) in self.rules.iter().enumerate() {
+            if rule.name.is_empty() {
+                problems.push("alert rule name must not be empty".to_string());
+            }
+            if self.rules[..i].iter().any(|earlier| earlier.name == rule.name) {
+                problems.push(format!("alert rule '{}' is defined twice", rule.name));
+            }
+            if rule.escalation.is_empty() {
+                problems.push(format!("alert rule '{}' needs at least one escalation step", rule.name));
+            }
+            if rule.step_secs == 0 {
+                problems.push(format!("alert rule '{}' step_secs must be positive", rule.name));
+            }
+            if rule.contacts.is_empty() {
+                problems.push(format!("alert rule '{}' has no contacts", rule.name));
+            }
+            for contact in rule.contacts.iter().filter(|name| !self.contacts.iter().any(|contact| &contact.name == *name)) {
+                problems.push(format!("alert rule '{}' names unknown contact '{}'", rule.name, contact));
+            }
+        }
+        for contact in self.contacts.iter().filter(|contact| contact.phone.is_none() && contact.push_url.is_none()) {
+            problems.push(format!("alert contact '{}' has neither a phone nor a push_url", contact.name));
+        }
+        problems
+    }
+}
+
+/// Who acknowledged an alert, and when
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Acknowledgment {
+    pub by: String,
+    pub at: DateTime<Utc>,
+}
+
+/// An alert raised by a rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub id: Uuid,
+    pub rule: String,
+    pub severity: Severity,
+    /// The event that raised it
+    pub event: MissionEvent,
+    /// Highest threat level among the events folded into it
+    pub threat_level: ThreatLevel,
+    /// Zone the drone was in, when the rule names zones
+    pub zone: Option<String>,
+    pub opened_at: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// Matching events, the first included
+    pub occurrences: u32,
+    /// Escalation steps taken
+    pub steps_taken: usize,
+    pub next_step_at: Option<DateTime<Utc>>,
+    /// Held back by quiet hours
+    pub quiet: bool,
+    pub acknowledged: Option<Acknowledgment>,
+}
+
+impl Alert {
+    pub fn is_open(&self) -> bool {
+        self.acknowledged.is_none()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AlertError {
+    #[error("no alert {0}")]
+    Unknown(Uuid),
+    #[error("alert {0} was already acknowledged by {1}")]
+    AlreadyAcknowledged(Uuid, String),
+}
+
+/// One escalation step to send
+struct Step {
+    alert: Uuid,
+    channel: AlertChannel,
+    channels: Vec<Arc<dyn NotificationChannel>>,
+    notification: EmergencyNotification,
+}
+
+#[derive(Default)]
+struct AlertState {
+    alerts: Vec<Alert>,
+    /// Sequence of the next mission event to look at
+    next_sequence: u64,
+}
+
+/// Matches mission events against the rules and escalates the alerts they raise
+pub struct Alerting {
+    config: AlertingConfig,
+    /// Retries, confirmation and the status link
+    contact: EmergencyContactConfig,
+    channels: HashMap<(String, AlertChannel), Arc<dyn NotificationChannel>>,
+    state: Mutex<AlertState>,
+    deliveries: Arc<Mutex<Vec<Delivery>>>,
+}
+
+impl Alerting {
+    /// Channels for every contact, sending through `contact`'s telephony
+    /// account with its retry settings
+    pub fn new(config: AlertingConfig, contact: &EmergencyContactConfig, http: Arc<LinkedClient>) -> Self {
+        let mut channels: HashMap<(String, AlertChannel), Arc<dyn NotificationChannel>> = HashMap::new();
+        for person in &config.contacts {
+            if let Some(url) = &person.push_url {
+                channels.insert((person.name.clone(), AlertChannel::Push), Arc::new(PushChannel::new(url).with_client(Arc::clone(&http))));
+            }
+            match (&contact.telephony, &person.phone) {
+                (Some(telephony), Some(phone)) => {
+                    channels.insert((person.name.clone(), AlertChannel::Sms), Arc::new(SmsChannel::new(telephony.clone(), phone).with_client(Arc::clone(&http))));
+                    channels.insert(
+                        (person.name.clone(), AlertChannel::Call),
+                        Arc::new(VoiceCallChannel::new(telephony.clone(), phone, contact.call_repeat).with_client(Arc::clone(&http))),
+                    );
+                },
+                (None, Some(_)) => warn!("📟 Alert contact '{}' has a phone but there is no telephony account - SMS and calls skipped", person.name),
+                _ => {},
+            }
+        }
+        Self {
+            config,
+            contact: contact.clone(),
+            channels,
+            state: Mutex::new(AlertState::default()),
+            deliveries: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Reach `contact` over `channel` some other way, e.g. a pager gateway
+    pub fn with_channel(mut self, contact: &str, channel: AlertChannel, sender: Arc<dyn NotificationChannel>) -> Self {
+        self.channels.insert((contact.to_string(), channel), sender);
+        self
+    }
+
+    /// Recent alerts, oldest first
+    pub fn alerts(&self) -> Vec<Alert> {
+        self.lock().alerts.clone()
+    }
+
+    /// Deliveries of escalation steps, oldest first; each carries its
+    /// alert's id as `notification_id`
+    pub fn deliveries(&self) -> Vec<Delivery> {
+        self.deliveries.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// Stop escalating an alert
+    pub fn acknowledge(&self, id: Uuid, by: &str, now: DateTime<Utc>) -> Result<Alert, AlertError> {
+        let mut state = self.lock();
+        let alert = state.alerts.iter_mut().find(|alert| alert.id == id).ok_or(AlertError::Unknown(id))?;
+        if let Some(acknowledgment) = &alert.acknowledged {
+            return Err(AlertError::AlreadyAcknowledged(id, acknowledgment.by.clone()));
+        }
+        acknowledge(alert, by, now);
+        Ok(alert.clone())
+    }
+
+    /// Raise, fold or acknowledge alerts for the events logged since the
+    /// last call, and return the escalation steps now due
+    fn sync(&self, drone: &DroneState, now: DateTime<Utc>, local_time: NaiveTime) -> Vec<Step> {
+        let mut state = self.lock();
+        let start = drone.mission_log.partition_point(|event| event.sequence < state.next_sequence);
+        let quiet_hours = self.config.quiet_hours.is_some_and(|window| window.contains(local_time));
+        for event in &drone.mission_log[start..] {
+            state.next_sequence = event.sequence + 1;
+            if event.event_type == EventType::ThreatAcknowledged {
+                let by = acknowledged_by(&event.description);
+                for alert in state.alerts.iter_mut().filter(|alert| alert.is_open()) {
+                    acknowledge(alert, by, now);
+                }
+                continue;
+            }
+            let zones = drone.geofence().zones_at(&event.position);
+            for rule in self.config.rules.iter().filter(|rule| rule.matches(event, &zones)) {
+                let zone = rule.zones.iter().find(|wanted| zones.contains(&wanted.as_str())).cloned();
+                let window = chrono::Duration::seconds(rule.dedup_window_secs as i64);
+                let folded = state.alerts.iter_mut().rev().find(|alert| {
+                    alert.rule == rule.name && alert.zone == zone && now - alert.last_seen < window && event.threat_level <= alert.threat_level
+                });
+                if let Some(alert) = folded {
+                    alert.occurrences += 1;
+                    alert.last_seen = now;
+                    continue;
+                }
+                let quiet = quiet_hours && rule.severity < self.config.quiet_hours_min_severity;
+                if quiet {
+                    info!("📟 Alert '{}' held back for quiet hours: {}", rule.name, event.description);
+                } else {
+                    info!("📟 Alert '{}' ({}): {}", rule.name, rule.severity, event.description);
+                }
+                state.alerts.push(Alert {
+                    id: Uuid::new_v4(),
+                    rule: rule.name.clone(),
+                    severity: rule.severity,
+                    event: event.clone(),
+                    threat_level: event.threat_level,
+                    zone,
+                    opened_at: now,
+                    last_seen: now,
+                    occurrences: 1,
+                    steps_taken: 0,
+                    next_step_at: (!quiet).then_some(now),
+                    quiet,
+                    acknowledged: None,
+                });
+            }
+        }
+        let excess = state.alerts.len().saturating_sub(ALERT_HISTORY);
+        state.alerts.drain(..excess);
+
+        let mut steps = Vec::new();
+        for alert in state.alerts.iter_mut().filter(|alert| alert.is_open()) {
+            if alert.next_step_at.is_none_or(|at| at > now) {
+                continue;
+            }
+            let Some(rule) = self.config.rules.iter().find(|rule| rule.name == alert.rule) else {
+                alert.next_step_at = None;
+                continue;
+            };
+            // Steps nobody can be reached by are passed over
+            while let Some(&channel) = rule.escalation.get(alert.steps_taken) {
+                alert.steps_taken += 1;
+                let channels: Vec<_> = rule.contacts.iter().filter_map(|name| self.channels.get(&(name.clone(), channel)).cloned()).collect();
+                if channels.is_empty() {
+                    continue;
+                }
+                let mut notification = EmergencyNotification::from_drone(drone, self.contact.status_url.as_deref());
+                notification.id = alert.id;
+                notification.threat_level = alert.threat_level;
+                notification.summary = alert_summary(alert);
+                steps.push(Step {
+                    alert: alert.id,
+                    channel,
+                    channels,
+                    notification,
+                });
+                break;
+            }
+            alert.next_step_at = (alert.steps_taken < rule.escalation.len()).then(|| now + chrono::Duration::seconds(rule.step_secs as i64));
+        }
+        steps
+    }
+
+    /// Raise and escalate alerts from the drone's mission log until the
+    /// drone is gone
+    pub async fn follow(alerting: Arc<Alerting>, drone: Arc<RwLock<DroneState>>) -> ModuleResult {
+        let mut poll = runtime::interval(Duration::from_secs(1));
+        let mut transitions = drone.read().await.subscribe_threat_transitions();
+        loop {
+            tokio::select! {
+                received = transitions.recv() => match received {
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                    _ => continue,
+                },
+                _ = poll.tick() => {
+                    let steps = {
+                        let drone = drone.read().await;
+                        alerting.sync(&drone, Utc::now(), Local::now().time())
+                    };
+                    for step in steps {
+                        alerting.send(step);
+                    }
+                },
+            }
+        }
+    }
+
+    fn send(&self, step: Step) {
+        info!("📟 Escalating alert {} by {} to {} contacts", step.alert, step.channel, step.channels.len());
+        for channel in step.channels {
+            let (notification, contact, deliveries) = (step.notification.clone(), self.contact.clone(), Arc::clone(&self.deliveries));
+            runtime::spawn(deliver(channel, notification, contact, deliveries));
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, AlertState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn acknowledge(alert: &mut Alert, by: &str, now: DateTime<Utc>) {
+    info!("📟 Alert '{}' acknowledged by {} after {} steps", alert.rule, by, alert.steps_taken);
+    alert.acknowledged = Some(Acknowledgment { by: by.to_string(), at: now });
+    alert.next_step_at = None;
+}
+
+/// The operator in a `ThreatAcknowledged` event's description
+fn acknowledged_by(description: &str) -> &str {
+    description
+        .split_once(" acknowledged by ")
+        .map(|(_, rest)| rest.split(':').next().unwrap_or(rest))
+        .unwrap_or("the drone operator")
+}
+
+fn alert_summary(alert: &Alert) -> String {
+    let mut summary = format!("{} ({} alert)", alert.event.description, alert.severity);
+    if alert.occurrences > 1 {
+        summary.push_str(&format!(", {} times since {}", alert.occurrences, alert.opened_at.format("%H:%M UTC")));
+    }
+    if let Some(zone) = &alert.zone {
+        summary.push_str(&format!(" in {}", zone));
+    }
+    summary
+}
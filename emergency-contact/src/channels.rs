@@ -0,0 +1,397 @@
+//! Notification channels: Twilio-compatible voice and SMS, ntfy-compatible
+//! push, and monitoring-center webhooks
+
+use crate::EmergencyNotification;
+use async_trait::async_trait;
+use dark_phoenix_core::{LinkManager, ThreatLevel, TrafficClass};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Where a notification stands with one channel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", content = "reason", rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Not yet accepted by the provider
+    Pending,
+    /// Accepted, waiting for the provider to confirm delivery
+    Sent,
+    Delivered,
+    Failed(String),
+}
+
+impl DeliveryStatus {
+    pub fn is_final(&self) -> bool {
+        matches!(self, DeliveryStatus::Delivered | DeliveryStatus::Failed(_))
+    }
+}
+
+/// What a provider said when it accepted a notification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Receipt {
+    pub provider_id: Option<String>, // Message or call SID, for following up
+    pub status: DeliveryStatus,
+}
+
+#[derive(Debug, Error)]
+pub enum ChannelError {
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("provider rejected the request ({status}): {body}")]
+    Rejected { status: u16, body: String },
+}
+
+/// One way of reaching emergency services or a monitoring center
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    /// e.g. `sms:+15551234567`
+    fn name(&self) -> String;
+
+    async fn send(&self, notification: &EmergencyNotification) -> Result<Receipt, ChannelError>;
+
+    /// Ask the provider what became of a sent notification; channels that
+    /// confirm on acceptance never get asked
+    async fn confirm(&self, receipt: &Receipt) -> Result<DeliveryStatus, ChannelError> {
+        Ok(receipt.status.clone())
+    }
+}
+
+/// Account for a Twilio-compatible voice and messaging API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelephonyConfig {
+    pub api_base: String, // e.g. https://api.twilio.com/2010-04-01
+    pub account_sid: String,
+    pub auth_token: String,
+    pub from: String, // Caller ID / sender number
+}
+
+impl TelephonyConfig {
+    fn resource(&self, resource: &str) -> String {
+        format!("{}/Accounts/{}/{}", self.api_base.trim_end_matches('/'), self.account_sid, resource)
+    }
+}
+
+/// Provider response to creating or fetching a message or call
+#[derive(Debug, Deserialize)]
+struct ProviderResource {
+    sid: String,
+    status: String,
+    #[serde(default)]
+    error_message: Option<String>,
+}
+
+async fn provider_response(response: reqwest::Response) -> Result<ProviderResource, ChannelError> {
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ChannelError::Rejected {
+            status: status.as_u16(),
+            body: response.text().await.unwrap_or_default(),
+        });
+    }
+    Ok(response.json().await?)
+}
+
+/// HTTP client shared by the channels, sending from whichever network link
+/// the link manager routes emergency traffic over
+#[derive(Debug, Default)]
+pub struct LinkedClient {
+    links: Mutex<Option<Arc<Mutex<LinkManager>>>>,
+    /// One client per local address, as reqwest binds when it is built
+    clients: Mutex<HashMap<Option<IpAddr>, reqwest::Client>>,
+}
+
+impl LinkedClient {
+    /// Route requests as `links` says from now on
+    pub fn follow(&self, links: Arc<Mutex<LinkManager>>) {
+        *self.links.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(links);
+    }
+
+    /// Client for the current emergency route
+    pub fn get(&self) -> reqwest::Client {
+        let links = self.links.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+        let local_address = links.and_then(|links| {
+            let links = links.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            links.route(TrafficClass::Emergency)?.local_address
+        });
+        let mut clients = self.clients.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        clients
+            .entry(local_address)
+            .or_insert_with(|| {
+                reqwest::Client::builder()
+                    .timeout(Duration::from_secs(15))
+                    .local_address(local_address)
+                    .build()
+                    .unwrap_or_default()
+            })
+            .clone()
+    }
+}
+
+/// Text message to one number
+pub struct SmsChannel {
+    telephony: TelephonyConfig,
+    to: String,
+    http: Arc<LinkedClient>,
+}
+
+impl SmsChannel {
+    pub fn new(telephony: TelephonyConfig, to: &str) -> Self {
+        Self {
+            telephony,
+            to: to.to_string(),
+            http: Arc::default(),
+        }
+    }
+
+    /// Share `http`, e.g. one that follows the link manager
+    pub fn with_client(mut self, http: Arc<LinkedClient>) -> Self {
+        self.http = http;
+        self
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for SmsChannel {
+    fn name(&self) -> String {
+        format!("sms:{}", self.to)
+    }
+
+    async fn send(&self, notification: &EmergencyNotification) -> Result<Receipt, ChannelError> {
+        let body = notification.text();
+        let response = self
+            .http
+            .get()
+            .post(self.telephony.resource("Messages.json"))
+            .basic_auth(&self.telephony.account_sid, Some(&self.telephony.auth_token))
+            .form(&[("To", self.to.as_str()), ("From", self.telephony.from.as_str()), ("Body", body.as_str())])
+            .send()
+            .await?;
+        let message = provider_response(response).await?;
+        Ok(Receipt {
+            status: sms_status(&message),
+            provider_id: Some(message.sid),
+        })
+    }
+
+    async fn confirm(&self, receipt: &Receipt) -> Result<DeliveryStatus, ChannelError> {
+        let Some(sid) = &receipt.provider_id else { return Ok(receipt.status.clone()) };
+        let response = self
+            .http
+            .get()
+            .get(self.telephony.resource(&format!("Messages/{}.json", sid)))
+            .basic_auth(&self.telephony.account_sid, Some(&self.telephony.auth_token))
+            .send()
+            .await?;
+        Ok(sms_status(&provider_response(response).await?))
+    }
+}
+
+fn sms_status(message: &ProviderResource) -> DeliveryStatus {
+    match message.status.as_str() {
+        "delivered" | "read" => DeliveryStatus::Delivered,
+        "failed" | "undelivered" | "canceled" => {
+            DeliveryStatus::Failed(message.error_message.clone().unwrap_or_else(|| message.status.clone()))
+        },
+        _ => DeliveryStatus::Sent,
+    }
+}
+
+/// Voice call to a phone number or SIP URI that reads the alert aloud
+pub struct VoiceCallChannel {
+    telephony: TelephonyConfig,
+    to: String,
+    repeat: u32,
+    http: Arc<LinkedClient>,
+}
+
+impl VoiceCallChannel {
+    /// `to` is a number (`+15551234567`) or a SIP URI (`sip:dispatch@psap.example`)
+    pub fn new(telephony: TelephonyConfig, to: &str, repeat: u32) -> Self {
+        Self {
+            telephony,
+            to: to.to_string(),
+            repeat: repeat.max(1),
+            http: Arc::default(),
+        }
+    }
+
+    /// Share `http`, e.g. one that follows the link manager
+    pub fn with_client(mut self, http: Arc<LinkedClient>) -> Self {
+        self.http = http;
+        self
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for VoiceCallChannel {
+    fn name(&self) -> String {
+        match self.to.strip_prefix("sip:") {
+            Some(uri) => format!("sip:{}", uri),
+            None => format!("call:{}", self.to),
+        }
+    }
+
+    async fn send(&self, notification: &EmergencyNotification) -> Result<Receipt, ChannelError> {
+        // The provider's text-to-speech reads the alert once the call is answered
+        let twiml = format!(
+            "<Response><Say loop=\"{}\">{}</Say></Response>",
+            self.repeat,
+            xml_escape(&notification.spoken())
+        );
+        let response = self
+            .http
+            .get()
+            .post(self.telephony.resource("Calls.json"))
+            .basic_auth(&self.telephony.account_sid, Some(&self.telephony.auth_token))
+            .form(&[("To", self.to.as_str()), ("From", self.telephony.from.as_str()), ("Twiml", twiml.as_str())])
+            .send()
+            .await?;
+        let call = provider_response(response).await?;
+        Ok(Receipt {
+            status: call_status(&call),
+            provider_id: Some(call.sid),
+        })
+    }
+
+    async fn confirm(&self, receipt: &Receipt) -> Result<DeliveryStatus, ChannelError> {
+        let Some(sid) = &receipt.provider_id else { return Ok(receipt.status.clone()) };
+        let response = self
+            .http
+            .get()
+            .get(self.telephony.resource(&format!("Calls/{}.json", sid)))
+            .basic_auth(&self.telephony.account_sid, Some(&self.telephony.auth_token))
+            .send()
+            .await?;
+        Ok(call_status(&provider_response(response).await?))
+    }
+}
+
+fn call_status(call: &ProviderResource) -> DeliveryStatus {
+    match call.status.as_str() {
+        // Answered and played through
+        "completed" => DeliveryStatus::Delivered,
+        "busy" | "no-answer" | "failed" | "canceled" => DeliveryStatus::Failed(call.status.clone()),
+        _ => DeliveryStatus::Sent,
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Monitoring center endpoint that takes the notification as JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    pub bearer_token: Option<String>,
+}
+
+/// JSON POST of the whole notification; a 2xx reply is the confirmation
+pub struct WebhookChannel {
+    config: WebhookConfig,
+    http: Arc<LinkedClient>,
+}
+
+impl WebhookChannel {
+    pub fn new(config: WebhookConfig) -> Self {
+        Self {
+            config,
+            http: Arc::default(),
+        }
+    }
+
+    /// Share `http`, e.g. one that follows the link manager
+    pub fn with_client(mut self, http: Arc<LinkedClient>) -> Self {
+        self.http = http;
+        self
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for WebhookChannel {
+    fn name(&self) -> String {
+        format!("webhook:{}", self.config.url)
+    }
+
+    async fn send(&self, notification: &EmergencyNotification) -> Result<Receipt, ChannelError> {
+        let mut request = self.http.get().post(&self.config.url).json(notification);
+        if let Some(token) = &self.config.bearer_token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ChannelError::Rejected {
+                status: status.as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+        Ok(Receipt {
+            provider_id: None,
+            status: DeliveryStatus::Delivered,
+        })
+    }
+}
+
+/// Push notification to the phones subscribed to an ntfy-compatible topic,
+/// e.g. `https://ntfy.sh/<topic>`; accepted means delivered to the gateway
+pub struct PushChannel {
+    url: String,
+    http: Arc<LinkedClient>,
+}
+
+impl PushChannel {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            http: Arc::default(),
+        }
+    }
+
+    /// Share `http`, e.g. one that follows the link manager
+    pub fn with_client(mut self, http: Arc<LinkedClient>) -> Self {
+        self.http = http;
+        self
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for PushChannel {
+    fn name(&self) -> String {
+        format!("push:{}", self.url)
+    }
+
+    async fn send(&self, notification: &EmergencyNotification) -> Result<Receipt, ChannelError> {
+        let priority = if notification.threat_level >= ThreatLevel::Red { "urgent" } else { "high" };
+        let response = self
+            .http
+            .get()
+            .post(&self.url)
+            .header("Title", format!("Dark Phoenix {}: {}", notification.drone, notification.threat_level.as_str()))
+            .header("Priority", priority)
+            .header("Tags", "rotating_light")
+            .header("Click", &notification.map_url)
+            .body(notification.text())
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ChannelError::Rejected {
+                status: status.as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+        let message: serde_json::Value = response.json().await.unwrap_or_default();
+        Ok(Receipt {
+            provider_id: message["id"].as_str().map(str::to_string),
+            status: DeliveryStatus::Delivered,
+        })
+    }
+}
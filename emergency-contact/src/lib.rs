@@ -0,0 +1,448 @@
+//! Emergency services notification
+
+use chrono::{DateTime, Utc};
+use dark_phoenix_core::runtime::{self, TaskError};
+use dark_phoenix_core::{DroneState, EventType, LinkManager, ModuleResult, Position, ThreatLevel};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+pub mod alerting;
+pub mod channels;
+
+pub use alerting::{Acknowledgment, Alert, AlertChannel, AlertContact, AlertError, AlertRule, Alerting, AlertingConfig, Severity};
+pub use channels::{
+    ChannelError, DeliveryStatus, LinkedClient, NotificationChannel, PushChannel, Receipt, SmsChannel, TelephonyConfig,
+    VoiceCallChannel, WebhookChannel, WebhookConfig,
+};
+
+/// Who to notify, how, and how hard to try
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyContactConfig {
+    /// Threat level that calls for outside help
+    pub trigger_level: ThreatLevel,
+    /// Live status page included in every notification, e.g. the API's `/status`
+    pub status_url: Option<String>,
+    /// Twilio-compatible account used for calls and SMS
+    pub telephony: Option<TelephonyConfig>,
+    pub sms_recipients: Vec<String>,
+    /// Phone numbers or SIP URIs (`sip:dispatch@psap.example`)
+    pub call_recipients: Vec<String>,
+    /// Times the spoken alert repeats on each call
+    pub call_repeat: u32,
+    pub webhook: Option<WebhookConfig>,
+    /// Sends per channel before giving up
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubling after each failure (milliseconds)
+    pub retry_backoff_ms: u64,
+    /// How often unconfirmed deliveries are checked (milliseconds)
+    pub confirm_interval_ms: u64,
+    /// How long to wait for a provider to confirm delivery (seconds)
+    pub confirm_timeout_secs: u64,
+    /// Rules alerting the drone's own contacts (absent = none)
+    #[serde(default)]
+    pub alerting: Option<AlertingConfig>,
+}
+
+impl Default for EmergencyContactConfig {
+    fn default() -> Self {
+        Self {
+            trigger_level: ThreatLevel::Red,
+            status_url: None,
+            telephony: None,
+            sms_recipients: Vec::new(),
+            call_recipients: Vec::new(),
+            call_repeat: 2,
+            webhook: None,
+            max_attempts: 5,
+            retry_backoff_ms: 1000,
+            confirm_interval_ms: 5000,
+            confirm_timeout_secs: 300, // Calls can ring for a while before they are answered
+            alerting: None,
+        }
+    }
+}
+
+/// What emergency services are told
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyNotification {
+    pub id: Uuid,
+    pub drone: String,
+    pub threat_level: ThreatLevel,
+    pub summary: String,
+    pub position: Position,
+    pub map_url: String,
+    pub status_url: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl EmergencyNotification {
+    /// Describe the drone's current situation, summarised from the latest
+    /// escalation in its mission log
+    pub fn from_drone(state: &DroneState, status_url: Option<&str>) -> Self {
+        let summary = state
+            .mission_log
+            .iter()
+            .rev()
+            .find(|event| event.event_type == EventType::ThreatDetected)
+            .map(|event| event.description.clone())
+            .unwrap_or_else(|| state.threat_level().description().to_string());
+        Self {
+            id: Uuid::new_v4(),
+            drone: state.name.clone(),
+            threat_level: state.threat_level(),
+            summary,
+            position: state.position.clone(),
+            map_url: format!(
+                "https://www.google.com/maps/search/?api=1&query={:.6},{:.6}",
+                state.position.latitude, state.position.longitude
+            ),
+            status_url: status_url.map(str::to_string),
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// SMS body
+    pub fn text(&self) -> String {
+        let mut text = format!(
+            "DARK PHOENIX ALERT ({}): {} threat. {}. Location {:.5}, {:.5} {}",
+            self.drone,
+            self.threat_level.as_str(),
+            self.summary,
+            self.position.latitude,
+            self.position.longitude,
+            self.map_url
+        );
+        if let Some(url) = &self.status_url {
+            text.push_str(&format!(" Live status: {}", url));
+        }
+        text
+    }
+
+    /// Script read out on voice calls
+    pub fn spoken(&self) -> String {
+        format!(
+            "This is an automated emergency alert from the Dark Phoenix security drone {}. Threat level {}. {}. \
+             Location: latitude {:.5}, longitude {:.5}. A live status link has been sent by text message.",
+            self.drone,
+            self.threat_level.as_str(),
+            self.summary,
+            self.position.latitude,
+            self.position.longitude
+        )
+    }
+}
+
+/// Progress of one notification over one channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delivery {
+    pub notification_id: Uuid,
+    pub channel: String,
+    pub attempts: u32,
+    pub status: DeliveryStatus,
+    pub provider_id: Option<String>,
+    pub last_error: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Deliveries remembered for `deliveries()`
+const DELIVERY_HISTORY: usize = 200;
+
+/// Dispatches notifications over every channel and tracks their delivery
+pub struct EmergencyContact {
+    config: EmergencyContactConfig,
+    channels: Vec<Arc<dyn NotificationChannel>>,
+    /// Shared by the built-in channels
+    http: Arc<LinkedClient>,
+    deliveries: Arc<Mutex<Vec<Delivery>>>,
+    alerting: Option<Arc<Alerting>>,
+}
+
+impl EmergencyContact {
+    /// Channels for every configured recipient and webhook
+    pub fn new(config: EmergencyContactConfig) -> Self {
+        let http = Arc::new(LinkedClient::default());
+        let mut channels: Vec<Arc<dyn NotificationChannel>> = Vec::new();
+        if let Some(telephony) = &config.telephony {
+            for to in &config.call_recipients {
+                channels.push(Arc::new(VoiceCallChannel::new(telephony.clone(), to, config.call_repeat).with_client(Arc::clone(&http))));
+            }
+            for to in &config.sms_recipients {
+                channels.push(Arc::new(SmsChannel::new(telephony.clone(), to).with_client(Arc::clone(&http))));
+            }
+        } else if !config.call_recipients.is_empty() || !config.sms_recipients.is_empty() {
+            warn!("📞 Call and SMS recipients configured without a telephony account - skipped");
+        }
+        if let Some(webhook) = &config.webhook {
+            channels.push(Arc::new(WebhookChannel::new(webhook.clone()).with_client(Arc::clone(&http))));
+        }
+        let alerting = config.alerting.clone().map(|alerting| Arc::new(Alerting::new(alerting, &config, Arc::clone(&http))));
+        Self {
+            config,
+            channels,
+            http,
+            deliveries: Arc::new(Mutex::new(Vec::new())),
+            alerting,
+        }
+    }
+
+    /// Add another way of reaching help, e.g. a radio gateway
+    pub fn with_channel(mut self, channel: Arc<dyn NotificationChannel>) -> Self {
+        self.channels.push(channel);
+        self
+    }
+
+    /// Send from the link `links` routes emergency traffic over
+    pub fn with_links(self, links: Arc<std::sync::Mutex<LinkManager>>) -> Self {
+        self.http.follow(links);
+        self
+    }
+
+    /// Alert rules, when configured; run them with `Alerting::follow`
+    pub fn alerting(&self) -> Option<Arc<Alerting>> {
+        self.alerting.clone()
+    }
+
+    pub fn channels(&self) -> Vec<String> {
+        self.channels.iter().map(|channel| channel.name()).collect()
+    }
+
+    /// Recent deliveries, oldest first
+    pub fn deliveries(&self) -> Vec<Delivery> {
+        lock(&self.deliveries).clone()
+    }
+
+    /// Send over every channel at once and wait until each is confirmed,
+    /// failed or timed out
+    pub async fn notify(&self, notification: &EmergencyNotification) -> Vec<Delivery> {
+        let mut pending = self.dispatch(notification);
+        let mut deliveries = Vec::new();
+        while let Some(joined) = pending.recv().await {
+            match joined {
+                Ok(delivery) => deliveries.push(delivery),
+                Err(e) => error!("Notification task failed: {}", e),
+            }
+        }
+        deliveries
+    }
+
+    /// Notify on every escalation to `trigger_level` or beyond, logging the
+    /// outcome to the drone; returns once the drone is gone
+    pub async fn follow(contact: Arc<EmergencyContact>, drone: Arc<RwLock<DroneState>>) -> ModuleResult {
+        if contact.channels.is_empty() {
+            warn!("📞 No emergency channels configured - authorities will not be notified");
+        }
+        let mut transitions = drone.read().await.subscribe_threat_transitions();
+        // Highest level already reported in this incident
+        let mut notified: Option<ThreatLevel> = None;
+        loop {
+            let level = match transitions.recv().await {
+                Ok(transition) => transition.to,
+                Err(broadcast::error::RecvError::Lagged(_)) => drone.read().await.threat_level(),
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            };
+            if level < contact.config.trigger_level {
+                notified = None;
+                continue;
+            }
+            if notified.is_some_and(|notified| level <= notified) {
+                continue;
+            }
+            notified = Some(level);
+
+            let notification = EmergencyNotification::from_drone(&*drone.read().await, contact.config.status_url.as_deref());
+            info!("🚨 Notifying emergency services of {} threat over {} channels", level.as_str(), contact.channels.len());
+            contact.queue(&notification, Arc::clone(&drone));
+        }
+    }
+
+    /// Send in the background, logging each channel's outcome to the drone
+    pub fn queue(&self, notification: &EmergencyNotification, drone: Arc<RwLock<DroneState>>) {
+        let pending = self.dispatch(notification);
+        runtime::spawn(report_outcome(pending, drone));
+    }
+
+    pub fn status_url(&self) -> Option<&str> {
+        self.config.status_url.as_deref()
+    }
+
+    /// Deliver over every channel in parallel; outcomes arrive as they settle
+    fn dispatch(&self, notification: &EmergencyNotification) -> mpsc::UnboundedReceiver<Result<Delivery, TaskError>> {
+        let (settled, pending) = mpsc::unbounded_channel();
+        for channel in &self.channels {
+            let task = runtime::spawn(deliver(
+                Arc::clone(channel),
+                notification.clone(),
+                self.config.clone(),
+                Arc::clone(&self.deliveries),
+            ));
+            let settled = settled.clone();
+            runtime::spawn(async move {
+                let _ = settled.send(task.await);
+            });
+        }
+        pending
+    }
+}
+
+/// Log each channel's outcome as it settles, and a malfunction if none got through
+async fn report_outcome(mut pending: mpsc::UnboundedReceiver<Result<Delivery, TaskError>>, drone: Arc<RwLock<DroneState>>) {
+    let mut reached = 0;
+    let mut failures = Vec::new();
+    while let Some(joined) = pending.recv().await {
+        let Ok(delivery) = joined else { continue };
+        let description = match &delivery.status {
+            DeliveryStatus::Delivered => format!("Emergency services notified via {}", delivery.channel),
+            DeliveryStatus::Sent => format!("Emergency services notified via {} (delivery unconfirmed)", delivery.channel),
+            DeliveryStatus::Failed(reason) => {
+                failures.push(format!("{}: {}", delivery.channel, reason));
+                continue;
+            },
+            DeliveryStatus::Pending => {
+                failures.push(format!("{}: {}", delivery.channel, delivery.last_error.as_deref().unwrap_or("not sent")));
+                continue;
+            },
+        };
+        reached += 1;
+        let actions = delivery.provider_id.iter().map(|id| format!("Provider reference {}", id)).collect();
+        drone.write().await.log_event(EventType::PoliceContacted, description, actions);
+    }
+    if reached == 0 && !failures.is_empty() {
+        error!("🚨 Emergency services could not be notified: {}", failures.join("; "));
+        drone.write().await.log_event(
+            EventType::SystemMalfunction,
+            format!("Emergency notification failed on every channel: {}", failures.join("; ")),
+            Vec::new(),
+        );
+    }
+}
+
+/// Send with retries, then follow the provider until delivery is settled
+pub(crate) async fn deliver(
+    channel: Arc<dyn NotificationChannel>,
+    notification: EmergencyNotification,
+    config: EmergencyContactConfig,
+    deliveries: Arc<Mutex<Vec<Delivery>>>,
+) -> Delivery {
+    let mut delivery = Delivery {
+        notification_id: notification.id,
+        channel: channel.name(),
+        attempts: 0,
+        status: DeliveryStatus::Pending,
+        provider_id: None,
+        last_error: None,
+        updated_at: Utc::now(),
+    };
+    track(&deliveries, &delivery);
+
+    let mut backoff = Duration::from_millis(config.retry_backoff_ms);
+    let receipt = loop {
+        delivery.attempts += 1;
+        match channel.send(&notification).await {
+            Ok(receipt) => break Some(receipt),
+            Err(e) => {
+                warn!("📞 {} attempt {} failed: {}", delivery.channel, delivery.attempts, e);
+                delivery.last_error = Some(e.to_string());
+            },
+        }
+        if delivery.attempts >= config.max_attempts.max(1) {
+            break None;
+        }
+        track(&deliveries, &delivery);
+        runtime::sleep(backoff).await;
+        backoff *= 2;
+    };
+    let Some(receipt) = receipt else {
+        delivery.status = DeliveryStatus::Failed(format!("gave up after {} attempts", delivery.attempts));
+        track(&deliveries, &delivery);
+        return delivery;
+    };
+    info!("📞 {} accepted the alert", delivery.channel);
+    delivery.provider_id = receipt.provider_id.clone();
+    delivery.status = receipt.status.clone();
+    track(&deliveries, &delivery);
+
+    let started = Instant::now();
+    let timeout = Duration::from_secs(config.confirm_timeout_secs);
+    while !delivery.status.is_final() && started.elapsed() < timeout {
+        runtime::sleep(Duration::from_millis(config.confirm_interval_ms.max(100))).await;
+        match channel.confirm(&receipt).await {
+            Ok(status) if status != delivery.status => {
+                delivery.status = status;
+                track(&deliveries, &delivery);
+            },
+            Ok(_) => {},
+            Err(e) => delivery.last_error = Some(e.to_string()),
+        }
+    }
+    match &delivery.status {
+        DeliveryStatus::Delivered => info!("✅ {} confirmed delivery", delivery.channel),
+        DeliveryStatus::Failed(reason) => warn!("📞 {} delivery failed: {}", delivery.channel, reason),
+        _ => warn!("📞 {} never confirmed delivery", delivery.channel),
+    }
+    delivery
+}
+
+/// Replace the recorded state of a delivery, or start recording it
+pub(crate) fn track(deliveries: &Mutex<Vec<Delivery>>, delivery: &Delivery) {
+    let mut deliveries = lock(deliveries);
+    let existing = deliveries
+        .iter_mut()
+        .find(|tracked| tracked.notification_id == delivery.notification_id && tracked.channel == delivery.channel);
+    let mut delivery = delivery.clone();
+    delivery.updated_at = Utc::now();
+    match existing {
+        Some(tracked) => *tracked = delivery,
+        None => {
+            deliveries.push(delivery);
+            let excess = deliveries.len().saturating_sub(DELIVERY_HISTORY);
+            deliveries.drain(..excess);
+        },
+    }
+}
+
+fn lock(deliveries: &Mutex<Vec<Delivery>>) -> MutexGuard<'_, Vec<Delivery>> {
+    deliveries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    /// Accepts every notification and hands it to the test
+    struct Recorder(mpsc::UnboundedSender<EmergencyNotification>);
+
+    #[async_trait]
+    impl NotificationChannel for Recorder {
+        fn name(&self) -> String {
+            "recorder".to_string()
+        }
+
+        async fn send(&self, notification: &EmergencyNotification) -> Result<Receipt, ChannelError> {
+            let _ = self.0.send(notification.clone());
+            Ok(Receipt { provider_id: None, status: DeliveryStatus::Delivered })
+        }
+    }
+
+    #[tokio::test]
+    async fn red_transition_dispatches_a_notification() {
+        let (sent, mut received) = mpsc::unbounded_channel();
+        let contact = Arc::new(EmergencyContact::new(EmergencyContactConfig::default()).with_channel(Arc::new(Recorder(sent))));
+        let drone = Arc::new(RwLock::new(DroneState::new("test".to_string())));
+        runtime::spawn(EmergencyContact::follow(Arc::clone(&contact), Arc::clone(&drone)));
+        runtime::sleep(Duration::from_millis(50)).await;
+
+        drone.write().await.escalate_threat(ThreatLevel::Orange, "Intruder at the gate".to_string());
+        runtime::sleep(Duration::from_millis(50)).await;
+        assert!(received.try_recv().is_err());
+
+        drone.write().await.escalate_threat(ThreatLevel::Red, "Intruder is armed".to_string());
+        let notification = runtime::timeout(Duration::from_secs(1), received.recv()).await.unwrap().unwrap();
+        assert_eq!(notification.threat_level, ThreatLevel::Red);
+        assert!(notification.summary.ends_with("Intruder is armed"));
+    }
+}
@@ -0,0 +1,96 @@
+//! Valve and nozzle controllers on the CAN bus (`socketcan` feature)
+
+use crate::{ExtinguisherValve, FireSuppressionSystem, NozzleActuator};
+use async_trait::async_trait;
+use dark_phoenix_core::{ActuatorCommand, CanBus, CanConfig, Psi};
+use std::sync::Arc;
+use tracing::info;
+
+/// An extinguisher valve controller that also reads the tank pressure
+pub struct CanValve {
+    bus: CanBus,
+    node: u8,
+}
+
+impl CanValve {
+    pub fn new(bus: CanBus, node: u8) -> Self {
+        Self { bus, node }
+    }
+}
+
+#[async_trait]
+impl ExtinguisherValve for CanValve {
+    async fn open(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.bus.request(self.node, ActuatorCommand::ValveOpen).await?;
+        info!("💨 Extinguisher valve OPENED over CAN - discharge active");
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.bus.request(self.node, ActuatorCommand::ValveClose).await?;
+        info!("🛑 Extinguisher valve CLOSED over CAN - discharge stopped");
+        Ok(())
+    }
+
+    async fn read_pressure(&self) -> Result<Psi, Box<dyn std::error::Error>> {
+        let reply = self.bus.request(self.node, ActuatorCommand::ReadPressure).await?;
+        let psi = reply.pressure_psi().ok_or("valve controller sent no pressure")?;
+        Ok(Psi(psi))
+    }
+}
+
+/// A nozzle gimbal controller
+pub struct CanNozzle {
+    bus: CanBus,
+    node: u8,
+}
+
+impl CanNozzle {
+    pub fn new(bus: CanBus, node: u8) -> Self {
+        Self { bus, node }
+    }
+}
+
+#[async_trait]
+impl NozzleActuator for CanNozzle {
+    async fn deploy(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.bus.request(self.node, ActuatorCommand::NozzleDeploy).await?;
+        info!("🔧 Fire suppression nozzle deployed");
+        Ok(())
+    }
+
+    async fn retract(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.bus.request(self.node, ActuatorCommand::NozzleRetract).await?;
+        info!("🔧 Fire suppression nozzle retracted");
+        Ok(())
+    }
+
+    async fn target_fire(&self, aim: Option<(f32, f32)>) -> Result<(), Box<dyn std::error::Error>> {
+        let (pan_deg, tilt_deg) = aim.unwrap_or((0.0, 0.0));
+        self.bus.request(self.node, ActuatorCommand::NozzleAim { pan_deg, tilt_deg }).await?;
+        info!("🎯 Nozzle gimbal at {:.0}° pan, {:.0}° tilt", pan_deg, tilt_deg);
+        Ok(())
+    }
+
+    async fn emergency_deploy(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.bus.request(self.node, ActuatorCommand::NozzleEmergency).await?;
+        info!("🚨 Emergency nozzle deployment - maximum coverage");
+        Ok(())
+    }
+}
+
+impl FireSuppressionSystem {
+    /// Command the valve and nozzle controllers `config` puts on `bus`;
+    /// controllers without a node id stay placeholders
+    pub fn with_can_outputs(mut self, bus: &CanBus, config: &CanConfig) -> Self {
+        if let Some(node) = config.valve_node {
+            info!("💨 Extinguisher valve on CAN node {:#x}", node);
+            self = self.with_extinguisher_valve(Arc::new(CanValve::new(bus.clone(), node)));
+        }
+        if let Some(node) = config.nozzle_node {
+            info!("🎯 Nozzle gimbal on CAN node {:#x}", node);
+            self = self.with_nozzle_actuator(Box::new(CanNozzle::new(bus.clone(), node)));
+        }
+        self
+    }
+}
@@ -0,0 +1,61 @@
+//! Fault-injecting sensors and valve (`fault-injection` feature)
+
+use crate::{ExtinguisherValve, FireSuppressionSystem, FlameReading, FlameSensor, FlameSpectrum, SmokeDetector, TemperatureSensor};
+use async_trait::async_trait;
+use dark_phoenix_core::{Celsius, FaultInjector, Faulty, Psi};
+use std::sync::Arc;
+
+#[async_trait]
+impl TemperatureSensor for Faulty<Box<dyn TemperatureSensor>> {
+    async fn read_temperature(&self) -> Result<Celsius, Box<dyn std::error::Error>> {
+        self.read("read_temperature", self.inner().read_temperature(), || Celsius(f32::NAN)).await
+    }
+}
+
+#[async_trait]
+impl SmokeDetector for Faulty<Box<dyn SmokeDetector>> {
+    async fn read_smoke_level(&self) -> Result<f32, Box<dyn std::error::Error>> {
+        // Obscuration outside 0.0-1.0
+        self.read("read_smoke_level", self.inner().read_smoke_level(), || -1.0).await
+    }
+}
+
+#[async_trait]
+impl FlameSensor for Faulty<Box<dyn FlameSensor>> {
+    async fn read_flame(&self) -> Result<FlameReading, Box<dyn std::error::Error>> {
+        let garbage = || FlameReading {
+            detected: true,
+            confidence: f32::NAN,
+            spectrum: FlameSpectrum::UvIr,
+        };
+        self.read("read_flame", self.inner().read_flame(), garbage).await
+    }
+}
+
+#[async_trait]
+impl ExtinguisherValve for Faulty<Arc<dyn ExtinguisherValve>> {
+    async fn open(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.command("open", self.inner().open()).await
+    }
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.command("close", self.inner().close()).await
+    }
+
+    async fn read_pressure(&self) -> Result<Psi, Box<dyn std::error::Error>> {
+        self.read("read_pressure", self.inner().read_pressure(), || Psi(f32::NAN)).await
+    }
+}
+
+impl FireSuppressionSystem {
+    /// Route the attached sensors and valve through `injector`, as devices
+    /// `temperature_sensor`, `smoke_detector`, `flame_sensor` and
+    /// `extinguisher_valve`; attach real or scenario hardware first
+    pub fn with_fault_injector(mut self, injector: &FaultInjector) -> Self {
+        self.temperature_sensor = Box::new(injector.wrap("temperature_sensor", self.temperature_sensor));
+        self.smoke_detector = Box::new(injector.wrap("smoke_detector", self.smoke_detector));
+        self.flame_sensor = Box::new(injector.wrap("flame_sensor", self.flame_sensor));
+        self.extinguisher_valve = Arc::new(injector.wrap("extinguisher_valve", self.extinguisher_valve));
+        self
+    }
+}
@@ -0,0 +1,61 @@
+//! Fire hotspots located in a thermal camera's frames (`thermal` feature)
+
+use dark_phoenix_core::{Celsius, ThermalFrame};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotspotConfig {
+    /// Hottest pixel a hotspot must reach
+    pub min_temp: Celsius,
+    /// Smallest patch counted, against single-pixel glints
+    pub min_pixels: usize,
+    /// Frames older than this are ignored, as after the camera has failed
+    pub max_age_ms: u64,
+}
+
+impl Default for HotspotConfig {
+    fn default() -> Self {
+        Self {
+            min_temp: Celsius(150.0),
+            min_pixels: 2,
+            max_age_ms: 2000,
+        }
+    }
+}
+
+/// A fire as seen by the thermal camera
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hotspot {
+    pub peak: Celsius,
+    /// Centre, as fractions of the frame width and height
+    pub position: (f32, f32),
+    /// Degrees right of the camera axis
+    pub azimuth_deg: f32,
+    /// Degrees above the camera axis
+    pub elevation_deg: f32,
+    /// Share of the frame the hotspot covers (0.0-1.0)
+    pub extent: f32,
+}
+
+impl HotspotConfig {
+    /// The hottest qualifying patch in `frame`, if it is recent enough
+    pub fn locate(&self, frame: &ThermalFrame) -> Option<Hotspot> {
+        let age = chrono::Utc::now().signed_duration_since(frame.timestamp).num_milliseconds();
+        if age > self.max_age_ms as i64 {
+            return None;
+        }
+        let region = frame
+            .regions(self.min_temp, Celsius(f32::INFINITY))
+            .into_iter()
+            .find(|region| region.pixels >= self.min_pixels)?;
+        let (azimuth_deg, elevation_deg) = frame.direction(region.centroid);
+        Some(Hotspot {
+            peak: region.peak,
+            position: region.centroid,
+            azimuth_deg,
+            elevation_deg,
+            extent: region.pixels as f32 / (frame.width * frame.height) as f32,
+        })
+    }
+}
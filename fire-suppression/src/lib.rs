@@ -1,14 +1,45 @@
+use async_trait::async_trait;
+use dark_phoenix_core::{metrics, runtime, Celsius, FireSuppressionTelemetry, Metrics, ProximityFeed, Psi, RingBuffer};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::sync::Arc;
 use std::time::Duration;
 use tracing::{info, warn, error};
 use uuid::Uuid;
 
+#[cfg(feature = "socketcan")]
+pub mod can;
+#[cfg(feature = "fault-injection")]
+pub mod fault;
+#[cfg(feature = "thermal")]
+pub mod hotspot;
+pub mod preflight;
+#[cfg(feature = "rpi-hw")]
+pub mod rpi;
+#[cfg(feature = "siem")]
+pub mod siem;
+#[cfg(feature = "simulation")]
+pub mod simulation;
+#[cfg(feature = "sitl")]
+pub mod sitl;
+
+#[cfg(feature = "socketcan")]
+pub use can::{CanNozzle, CanValve};
+#[cfg(feature = "thermal")]
+pub use hotspot::{Hotspot, HotspotConfig};
+pub use preflight::PressureCheck;
+#[cfg(feature = "rpi-hw")]
+pub use rpi::{RelayValve, ServoNozzle};
+#[cfg(feature = "sitl")]
+pub use sitl::{SitlNozzle, SitlValve};
+
 /// Fire suppression system configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FireSuppressionConfig {
-    /// Temperature threshold for automatic activation (Celsius)
-    pub auto_activation_temp: f32,
+    /// Temperature threshold for automatic activation (Celsius or Fahrenheit in config)
+    pub auto_activation_temp: Celsius,
     /// Smoke detection sensitivity (0.0-1.0)
     pub smoke_sensitivity: f32,
     /// Maximum discharge duration in seconds
@@ -17,19 +48,81 @@ pub struct FireSuppressionConfig {
     pub cooldown_period: u32,
     /// Allow manual override even during cooldown
     pub allow_manual_override: bool,
-    /// Minimum extinguisher pressure for operation (PSI)
-    pub min_pressure: f32,
+    /// Minimum extinguisher pressure for operation (PSI or bar in config)
+    pub min_pressure: Psi,
+    /// Relative weighting of temperature and smoke in the risk score
+    pub risk_weights: RiskWeights,
+    /// Risk score boundaries for each severity level
+    pub risk_thresholds: RiskThresholds,
+    /// Consecutive readings required before escalating severity
+    pub escalation_readings: u32,
+    /// Consecutive readings required before de-escalating severity
+    pub de_escalation_readings: u32,
+    /// Temperature rise rate that independently triggers High severity (°C per minute)
+    pub rate_of_rise_threshold: f32,
+    /// Sliding window used to measure the temperature rise rate (seconds)
+    pub rate_of_rise_window: u32,
+    /// Minimum flame sensor confidence treated as a real flame (0.0-1.0)
+    pub flame_confidence_threshold: f32,
+    /// What counts as a fire in thermal camera frames
+    #[cfg(feature = "thermal")]
+    pub hotspot: HotspotConfig,
 }
 
 impl Default for FireSuppressionConfig {
     fn default() -> Self {
         Self {
-            auto_activation_temp: 60.0,  // 60°C / 140°F
+            auto_activation_temp: Celsius(60.0),  // 60°C / 140°F
             smoke_sensitivity: 0.7,
             max_discharge_duration: 10,   // 10 seconds max burst
             cooldown_period: 30,          // 30 second cooldown
             allow_manual_override: true,
-            min_pressure: 100.0,          // 100 PSI minimum
+            min_pressure: Psi(100.0),     // 100 PSI minimum
+            risk_weights: RiskWeights::default(),
+            risk_thresholds: RiskThresholds::default(),
+            escalation_readings: 2,       // Confirm quickly before suppressing
+            de_escalation_readings: 5,    // Stand down slowly to avoid valve chatter
+            rate_of_rise_threshold: 8.0,  // 8°C/min, typical rate-of-rise detector rating
+            rate_of_rise_window: 60,      // 1 minute window
+            flame_confidence_threshold: 0.8,
+            #[cfg(feature = "thermal")]
+            hotspot: HotspotConfig::default(),
+        }
+    }
+}
+
+/// Weighting of each sensor factor in the combined fire risk score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskWeights {
+    pub temperature: f32,
+    pub smoke: f32,
+}
+
+impl Default for RiskWeights {
+    fn default() -> Self {
+        Self {
+            temperature: 0.6,
+            smoke: 0.4,
+        }
+    }
+}
+
+/// Minimum risk score (0.0-1.0) for each severity above `Low`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskThresholds {
+    pub medium: f32,
+    pub high: f32,
+    pub critical: f32,
+}
+
+impl Default for RiskThresholds {
+    fn default() -> Self {
+        Self {
+            medium: 0.3,
+            high: 0.6,
+            critical: 0.8,
         }
     }
 }
@@ -38,32 +131,70 @@ impl Default for FireSuppressionConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FireSuppressionState {
     pub system_armed: bool,
-    pub extinguisher_pressure: f32,      // PSI
+    pub extinguisher_pressure: Psi,
     pub extinguisher_capacity: f32,      // Percentage remaining
     pub nozzle_position: NozzlePosition,
-    pub current_temperature: f32,        // Celsius
+    pub current_temperature: Celsius,
+    pub temperature_rise_rate: f32,     // °C per minute
     pub smoke_level: f32,               // 0.0-1.0
+    pub flame_detected: bool,
+    /// Fire located by the thermal camera
+    #[cfg(feature = "thermal")]
+    pub hotspot: Option<Hotspot>,
     pub last_activation: Option<DateTime<Utc>>,
     pub total_activations: u32,
     pub system_health: SystemHealth,
     pub discharge_active: bool,
     pub manual_override_active: bool,
+    /// Discharge is being held back because something is too close to the nozzle
+    #[serde(default)]
+    pub discharge_held: bool,
+    /// Hottest heat detector on the building's fire panel
+    #[cfg(feature = "modbus")]
+    #[serde(default)]
+    pub building_temperature: Option<Celsius>,
+    /// Building detectors the fire panel reports in alarm
+    #[cfg(feature = "modbus")]
+    #[serde(default)]
+    pub building_alarms: Vec<String>,
 }
 
 impl Default for FireSuppressionState {
     fn default() -> Self {
         Self {
             system_armed: true,
-            extinguisher_pressure: 150.0,  // Full pressure
+            extinguisher_pressure: Psi(150.0),  // Full pressure
             extinguisher_capacity: 100.0,  // Full capacity
             nozzle_position: NozzlePosition::Retracted,
-            current_temperature: 20.0,     // Room temperature
+            current_temperature: Celsius(20.0), // Room temperature
+            temperature_rise_rate: 0.0,
             smoke_level: 0.0,              // No smoke
+            flame_detected: false,
+            #[cfg(feature = "thermal")]
+            hotspot: None,
             last_activation: None,
             total_activations: 0,
             system_health: SystemHealth::Optimal,
             discharge_active: false,
             manual_override_active: false,
+            discharge_held: false,
+            #[cfg(feature = "modbus")]
+            building_temperature: None,
+            #[cfg(feature = "modbus")]
+            building_alarms: Vec::new(),
+        }
+    }
+}
+
+impl FireSuppressionState {
+    /// Dashboard summary, for `DroneState::report_fire_suppression`
+    pub fn telemetry(&self) -> FireSuppressionTelemetry {
+        FireSuppressionTelemetry {
+            armed: self.system_armed,
+            pressure: self.extinguisher_pressure,
+            capacity: self.extinguisher_capacity,
+            discharging: self.discharge_active,
+            timestamp: Utc::now(),
         }
     }
 }
@@ -103,7 +234,7 @@ pub struct FireEvent {
     pub id: Uuid,
     pub timestamp: DateTime<Utc>,
     pub event_type: FireEventType,
-    pub temperature: f32,
+    pub temperature: Celsius,
     pub smoke_level: f32,
     pub location_estimate: Option<(f32, f32)>, // Relative x, y coordinates
     pub severity: FireSeverity,
@@ -119,6 +250,8 @@ pub enum FireEventType {
     SystemActivated,
     ManualOverride,
     EmergencyShutdown,
+    DischargeHeld,
+    BuildingAlarm,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd)]
@@ -133,12 +266,32 @@ pub enum FireSeverity {
 pub struct FireSuppressionSystem {
     config: FireSuppressionConfig,
     state: FireSuppressionState,
-    event_history: Vec<FireEvent>,
+    event_history: RingBuffer<FireEvent>,
+    /// Severity acted upon after debouncing
+    confirmed_severity: FireSeverity,
+    /// Candidate severity and how many consecutive readings have agreed with it
+    pending_severity: Option<(FireSeverity, u32)>,
+    /// Recent temperature samples for rate-of-rise detection
+    temperature_samples: VecDeque<(DateTime<Utc>, Celsius)>,
     // Hardware controllers (placeholders)
-    temperature_sensor: TemperatureSensor,
-    smoke_detector: SmokeDetector,
-    extinguisher_valve: ExtinguisherValve,
-    nozzle_actuator: NozzleActuator,
+    temperature_sensor: Box<dyn TemperatureSensor>,
+    smoke_detector: Box<dyn SmokeDetector>,
+    flame_sensor: Box<dyn FlameSensor>,
+    extinguisher_valve: Arc<dyn ExtinguisherValve>,
+    nozzle_actuator: Box<dyn NozzleActuator>,
+    /// Activation and discharge metrics, when exported
+    metrics: Option<Metrics>,
+    /// SIEM collector fire events are forwarded to
+    #[cfg(feature = "siem")]
+    siem: Option<dark_phoenix_core::SiemForwarder>,
+    /// Thermal camera searched for hotspots
+    #[cfg(feature = "thermal")]
+    thermal: Option<dark_phoenix_core::ThermalFeed>,
+    /// Ranging checked for people near the nozzle before discharging
+    proximity: Option<ProximityFeed>,
+    /// Building fire panel read for detectors and alarmed on discharge
+    #[cfg(feature = "modbus")]
+    building: Option<dark_phoenix_core::ModbusBridge>,
 }
 
 impl FireSuppressionSystem {
@@ -146,21 +299,105 @@ impl FireSuppressionSystem {
         Self {
             config,
             state: FireSuppressionState::default(),
-            event_history: Vec::new(),
-            temperature_sensor: TemperatureSensor::new(),
-            smoke_detector: SmokeDetector::new(),
-            extinguisher_valve: ExtinguisherValve::new(),
-            nozzle_actuator: NozzleActuator::new(),
+            event_history: RingBuffer::new(100),
+            confirmed_severity: FireSeverity::Low,
+            pending_severity: None,
+            temperature_samples: VecDeque::new(),
+            temperature_sensor: Box::new(SimulatedTemperatureSensor),
+            smoke_detector: Box::new(SimulatedSmokeDetector),
+            flame_sensor: Box::new(SimulatedFlameSensor),
+            extinguisher_valve: Arc::new(SimulatedExtinguisherValve),
+            nozzle_actuator: Box::new(SimulatedNozzleActuator),
+            metrics: None,
+            #[cfg(feature = "siem")]
+            siem: None,
+            #[cfg(feature = "thermal")]
+            thermal: None,
+            proximity: None,
+            #[cfg(feature = "modbus")]
+            building: None,
         }
     }
 
+    /// Replace the default temperature sensor with a hardware implementation
+    pub fn with_temperature_sensor(mut self, sensor: Box<dyn TemperatureSensor>) -> Self {
+        self.temperature_sensor = sensor;
+        self
+    }
+
+    /// Replace the default smoke detector with a hardware implementation
+    pub fn with_smoke_detector(mut self, detector: Box<dyn SmokeDetector>) -> Self {
+        self.smoke_detector = detector;
+        self
+    }
+
+    /// Replace the default flame sensor with a hardware implementation
+    pub fn with_flame_sensor(mut self, sensor: Box<dyn FlameSensor>) -> Self {
+        self.flame_sensor = sensor;
+        self
+    }
+
+    /// Replace the default extinguisher valve with a hardware implementation
+    pub fn with_extinguisher_valve(mut self, valve: Arc<dyn ExtinguisherValve>) -> Self {
+        self.extinguisher_valve = valve;
+        self
+    }
+
+    /// Replace the default nozzle with a hardware implementation
+    pub fn with_nozzle_actuator(mut self, actuator: Box<dyn NozzleActuator>) -> Self {
+        self.nozzle_actuator = actuator;
+        self
+    }
+
+    /// Count activations and time discharges in `metrics`
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Forward every fire event to a SIEM collector
+    #[cfg(feature = "siem")]
+    pub fn with_siem(mut self, forwarder: dark_phoenix_core::SiemForwarder) -> Self {
+        self.siem = Some(forwarder);
+        self
+    }
+
+    /// Locate fires in the frames of a thermal camera
+    #[cfg(feature = "thermal")]
+    pub fn with_thermal(mut self, feed: dark_phoenix_core::ThermalFeed) -> Self {
+        self.thermal = Some(feed);
+        self
+    }
+
+    /// Hold discharges while anything is inside the minimum safe distance
+    pub fn with_proximity(mut self, feed: ProximityFeed) -> Self {
+        self.proximity = Some(feed);
+        self
+    }
+
+    /// Read the building fire panel's detectors alongside the drone's own,
+    /// and sound its alarm relays while discharging
+    #[cfg(feature = "modbus")]
+    pub fn with_building_panel(mut self, bridge: dark_phoenix_core::ModbusBridge) -> Self {
+        self.building = Some(bridge);
+        self
+    }
+
     /// Main monitoring and response loop
     pub async fn monitor_and_respond(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         // Update sensor readings
         self.update_sensors().await?;
         
-        // Assess fire risk
-        let fire_risk = self.assess_fire_risk();
+        // Assess fire risk, debounced to prevent valve chattering
+        let raw_risk = self.assess_fire_risk();
+        let fire_risk = if self.state.flame_detected {
+            // Direct flame sighting is unambiguous - act without waiting for confirmation
+            self.confirmed_severity = raw_risk;
+            self.pending_severity = None;
+            raw_risk
+        } else {
+            self.debounce_severity(raw_risk)
+        };
         
         // Respond based on risk level
         match fire_risk {
@@ -191,10 +428,39 @@ impl FireSuppressionSystem {
     async fn update_sensors(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         // Read temperature
         self.state.current_temperature = self.temperature_sensor.read_temperature().await?;
+        self.record_temperature_sample(Utc::now(), self.state.current_temperature);
         
         // Read smoke level
         self.state.smoke_level = self.smoke_detector.read_smoke_level().await?;
+
+        // Read flame sensor
+        let flame = self.flame_sensor.read_flame().await?;
+        let flame_detected = flame.detected && flame.confidence >= self.config.flame_confidence_threshold;
+        if flame_detected && !self.state.flame_detected {
+            error!("🔥 Flame detected ({:?}, confidence {:.2})", flame.spectrum, flame.confidence);
+            self.state.flame_detected = true;
+            self.log_fire_event(
+                FireEventType::FlameDetected,
+                format!("{:?} flame sensor reports open flame", flame.spectrum)
+            );
+        }
+        self.state.flame_detected = flame_detected;
+
+        #[cfg(feature = "thermal")]
+        if let Some(feed) = &self.thermal {
+            let hotspot = feed.latest().and_then(|frame| self.config.hotspot.locate(&frame));
+            let appeared = self.state.hotspot.is_none();
+            self.state.hotspot = hotspot;
+            if let (true, Some(hotspot)) = (appeared, &self.state.hotspot) {
+                warn!("🌡️ Thermal hotspot {} at {:.0}° azimuth, {:.0}° elevation", hotspot.peak, hotspot.azimuth_deg, hotspot.elevation_deg);
+                let description = format!("Thermal camera hotspot of {}", hotspot.peak);
+                self.log_fire_event(FireEventType::TemperatureSpike, description);
+            }
+        }
         
+        #[cfg(feature = "modbus")]
+        self.read_building_panel();
+
         // Update extinguisher status
         self.state.extinguisher_pressure = self.extinguisher_valve.read_pressure().await?;
         
@@ -204,10 +470,65 @@ impl FireSuppressionSystem {
         Ok(())
     }
 
+    /// Fold the fire panel's latest poll into smoke, heat and alarms
+    #[cfg(feature = "modbus")]
+    fn read_building_panel(&mut self) {
+        let Some(bridge) = &self.building else { return };
+        let panel = bridge.latest();
+        if let Some(smoke) = panel.as_ref().and_then(|panel| panel.smoke_level()) {
+            self.state.smoke_level = self.state.smoke_level.max(smoke);
+        }
+        self.state.building_temperature = panel.as_ref().and_then(|panel| panel.temperature());
+        let alarms: Vec<String> = panel.iter().flat_map(|panel| panel.alarms().map(|reading| reading.name.clone())).collect();
+        let raised: Vec<String> = alarms.iter().filter(|name| !self.state.building_alarms.contains(name)).cloned().collect();
+        self.state.building_alarms = alarms;
+        for name in raised {
+            warn!("🏢 Building detector {} in alarm", name);
+            self.log_fire_event(FireEventType::BuildingAlarm, format!("Building fire panel reports {} in alarm", name));
+        }
+    }
+
+    /// Switch the building's alarm relays, when a fire panel is connected
+    #[cfg_attr(not(feature = "modbus"), allow(unused_variables))]
+    fn sound_building_alarm(&self, on: bool) {
+        #[cfg(feature = "modbus")]
+        if let Some(bridge) = &self.building {
+            bridge.set_alarm(on);
+        }
+    }
+
+    /// Track temperature history and update the rise rate over the configured window
+    fn record_temperature_sample(&mut self, timestamp: DateTime<Utc>, temperature: Celsius) {
+        self.temperature_samples.push_back((timestamp, temperature));
+
+        let window = chrono::Duration::seconds(self.config.rate_of_rise_window as i64);
+        while let Some((oldest, _)) = self.temperature_samples.front() {
+            if timestamp.signed_duration_since(*oldest) > window {
+                self.temperature_samples.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        self.state.temperature_rise_rate = match self.temperature_samples.front() {
+            Some((oldest_time, oldest_temp)) => {
+                let elapsed_secs = timestamp.signed_duration_since(*oldest_time).num_milliseconds() as f32 / 1000.0;
+                // Require a few seconds of history so sensor noise isn't read as a spike
+                if elapsed_secs >= 5.0 {
+                    (temperature.0 - oldest_temp.0) / elapsed_secs * 60.0
+                } else {
+                    0.0
+                }
+            },
+            None => 0.0,
+        };
+    }
+
     /// Assess current fire risk level
     fn assess_fire_risk(&self) -> FireSeverity {
-        let temp_factor = if self.state.current_temperature > self.config.auto_activation_temp {
-            (self.state.current_temperature - 20.0) / 50.0 // Normalize to 0-1 range
+        let temperature = self.hottest_temperature();
+        let temp_factor = if temperature > self.config.auto_activation_temp {
+            (temperature.0 - 20.0) / 50.0 // Normalize to 0-1 range
         } else {
             0.0
         };
@@ -215,17 +536,110 @@ impl FireSuppressionSystem {
         let smoke_factor = self.state.smoke_level;
         
         // Combined risk score
-        let risk_score = (temp_factor * 0.6) + (smoke_factor * 0.4);
+        let weights = &self.config.risk_weights;
+        let risk_score = (temp_factor * weights.temperature) + (smoke_factor * weights.smoke);
 
-        if risk_score >= 0.8 {
+        let thresholds = &self.config.risk_thresholds;
+        let score_severity = if risk_score >= thresholds.critical {
             FireSeverity::Critical
-        } else if risk_score >= 0.6 {
+        } else if risk_score >= thresholds.high {
             FireSeverity::High
-        } else if risk_score >= 0.3 {
+        } else if risk_score >= thresholds.medium {
             FireSeverity::Medium
         } else {
             FireSeverity::Low
+        };
+
+        // Open flame is an active fire regardless of what heat and smoke report
+        if self.state.flame_detected {
+            return if score_severity >= FireSeverity::High {
+                FireSeverity::Critical
+            } else {
+                FireSeverity::High
+            };
+        }
+
+        // A building detector in alarm readies the nozzle even when the drone's own sensors are quiet
+        #[cfg(feature = "modbus")]
+        let score_severity = if !self.state.building_alarms.is_empty() && score_severity < FireSeverity::Medium {
+            FireSeverity::Medium
+        } else {
+            score_severity
+        };
+
+        // Rapid heating indicates a developing fire even below the absolute threshold
+        if self.state.temperature_rise_rate >= self.config.rate_of_rise_threshold && score_severity < FireSeverity::High {
+            FireSeverity::High
+        } else {
+            score_severity
+        }
+    }
+
+    /// Contact temperature, or a hotter fire seen by the thermal camera or
+    /// the building's heat detectors
+    fn hottest_temperature(&self) -> Celsius {
+        #[allow(unused_mut)]
+        let mut hottest = self.state.current_temperature;
+        #[cfg(feature = "thermal")]
+        if let Some(hotspot) = &self.state.hotspot {
+            if hotspot.peak > hottest {
+                hottest = hotspot.peak;
+            }
+        }
+        #[cfg(feature = "modbus")]
+        if let Some(building) = self.state.building_temperature {
+            if building > hottest {
+                hottest = building;
+            }
+        }
+        hottest
+    }
+
+    /// Where the fire is, as relative x, y in the thermal camera's view
+    fn location_estimate(&self) -> Option<(f32, f32)> {
+        #[cfg(feature = "thermal")]
+        if let Some(hotspot) = &self.state.hotspot {
+            return Some(hotspot.position);
+        }
+        None
+    }
+
+    /// Nozzle aim as azimuth and elevation in degrees, when the fire has been located
+    fn aim(&self) -> Option<(f32, f32)> {
+        #[cfg(feature = "thermal")]
+        if let Some(hotspot) = &self.state.hotspot {
+            return Some((hotspot.azimuth_deg, hotspot.elevation_deg));
+        }
+        None
+    }
+
+    /// Only change the acted-upon severity once enough consecutive readings agree
+    fn debounce_severity(&mut self, observed: FireSeverity) -> FireSeverity {
+        if observed == self.confirmed_severity {
+            self.pending_severity = None;
+            return self.confirmed_severity;
+        }
+
+        let count = match self.pending_severity {
+            Some((pending, count)) if pending == observed => count + 1,
+            _ => 1,
+        };
+
+        let required = if observed > self.confirmed_severity {
+            self.config.escalation_readings
+        } else {
+            self.config.de_escalation_readings
+        };
+
+        if count >= required.max(1) {
+            info!("Fire severity changed: {:?} -> {:?}", self.confirmed_severity, observed);
+            self.confirmed_severity = observed;
+            self.pending_severity = None;
+        } else {
+            self.pending_severity = Some((observed, count));
         }
+
+        self.confirmed_severity
     }
 
     /// Prepare suppression system for activation
@@ -248,6 +662,7 @@ impl FireSuppressionSystem {
     }
 
     /// Activate fire suppression
+    #[tracing::instrument(name = "fire_suppression_activation", skip(self))]
     pub async fn activate_suppression(&mut self, emergency: bool) -> Result<(), Box<dyn std::error::Error>> {
         // Check if we're in cooldown period (unless emergency or manual override)
         if !emergency && !self.state.manual_override_active {
@@ -266,6 +681,10 @@ impl FireSuppressionSystem {
             return Err("System not ready".into());
         }
 
+        if !self.clear_to_discharge(emergency) {
+            return Ok(());
+        }
+
         let activation_type = if emergency { "EMERGENCY" } else { "STANDARD" };
         error!("🔥🚨 {} FIRE SUPPRESSION ACTIVATED 🚨🔥", activation_type);
 
@@ -274,15 +693,25 @@ impl FireSuppressionSystem {
             self.nozzle_actuator.emergency_deploy().await?;
             self.state.nozzle_position = NozzlePosition::Emergency;
         } else {
-            self.nozzle_actuator.target_fire().await?;
+            self.nozzle_actuator.target_fire(self.aim()).await?;
             self.state.nozzle_position = NozzlePosition::Targeting;
         }
 
         // Open extinguisher valve
         self.extinguisher_valve.open().await?;
         self.state.discharge_active = true;
+        self.sound_building_alarm(true);
         self.state.last_activation = Some(Utc::now());
         self.state.total_activations += 1;
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .counter(
+                    "phoenix_fire_suppression_activations_total",
+                    "Extinguisher discharges started",
+                    &[("kind", if emergency { "emergency" } else { "standard" })],
+                )
+                .inc();
+        }
 
         // Log suppression event
         self.log_fire_event(
@@ -292,10 +721,10 @@ impl FireSuppressionSystem {
 
         // Schedule automatic stop after max duration
         let max_duration = Duration::from_secs(self.config.max_discharge_duration as u64);
-        tokio::spawn({
-            let valve = self.extinguisher_valve.clone();
+        runtime::spawn({
+            let valve = Arc::clone(&self.extinguisher_valve);
             async move {
-                tokio::time::sleep(max_duration).await;
+                runtime::sleep(max_duration).await;
                 if let Err(e) = valve.close().await {
                     error!("Failed to auto-stop extinguisher: {}", e);
                 }
@@ -306,6 +735,33 @@ impl FireSuppressionSystem {
         Ok(())
     }
 
+    /// Whether the spray is clear of anything inside the safe distance: the
+    /// arc around the aim point, or all round for an emergency deployment
+    fn clear_to_discharge(&mut self, emergency: bool) -> bool {
+        let Some(feed) = &self.proximity else { return true };
+        let arc = match (emergency, self.aim()) {
+            (false, Some((azimuth, _))) => Some((azimuth, NOZZLE_SPRAY_HALF_WIDTH_DEG)),
+            _ => None,
+        };
+        match feed.latest().check(arc, Utc::now()) {
+            Ok(()) => {
+                if self.state.discharge_held {
+                    info!("🧯 Nozzle clear - discharge no longer held");
+                    self.state.discharge_held = false;
+                }
+                true
+            },
+            Err(e) => {
+                if !self.state.discharge_held {
+                    warn!("🧯 Holding discharge: {}", e);
+                    self.state.discharge_held = true;
+                    self.log_fire_event(FireEventType::DischargeHeld, format!("Discharge held: {}", e));
+                }
+                false
+            },
+        }
+    }
+
     /// Manual activation override
     pub async fn manual_activate(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         warn!("🔥 Manual fire suppression override activated");
@@ -327,11 +783,13 @@ impl FireSuppressionSystem {
             info!("🛑 Stopping fire suppression discharge");
             
             self.extinguisher_valve.close().await?;
+            self.record_discharge_duration();
             self.state.discharge_active = false;
+            self.sound_building_alarm(false);
             self.state.manual_override_active = false;
             
             // Retract nozzle after suppression
-            tokio::time::sleep(Duration::from_secs(2)).await;
+            runtime::sleep(Duration::from_secs(2)).await;
             self.nozzle_actuator.retract().await?;
             self.state.nozzle_position = NozzlePosition::Retracted;
             
@@ -344,6 +802,48 @@ impl FireSuppressionSystem {
         Ok(())
     }
 
+    /// Close the valve and retract the nozzle whatever the current state,
+    /// e.g. before the drone shuts down
+    pub async fn enter_safe_state(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.extinguisher_valve.close().await?;
+        if self.state.discharge_active {
+            self.record_discharge_duration();
+        }
+        self.state.discharge_active = false;
+        self.sound_building_alarm(false);
+        self.state.manual_override_active = false;
+        self.nozzle_actuator.retract().await?;
+        self.state.nozzle_position = NozzlePosition::Retracted;
+        info!("🧯 Fire suppression safed: valve closed, nozzle retracted");
+        Ok(())
+    }
+
+    /// Arm or disarm automatic activation; disarming also safes the system
+    pub async fn set_armed(&mut self, armed: bool) -> Result<(), Box<dyn std::error::Error>> {
+        if !armed {
+            self.enter_safe_state().await?;
+        }
+        if self.state.system_armed != armed {
+            self.state.system_armed = armed;
+            info!("🧯 Fire suppression {}", if armed { "armed" } else { "disarmed" });
+        }
+        Ok(())
+    }
+
+    /// Time from the last activation to the valve closing
+    fn record_discharge_duration(&self) {
+        let (Some(metrics), Some(started)) = (&self.metrics, self.state.last_activation) else { return };
+        let elapsed = Utc::now().signed_duration_since(started);
+        metrics
+            .histogram(
+                "phoenix_fire_discharge_seconds",
+                "How long the extinguisher valve stayed open",
+                &[],
+                metrics::DISCHARGE_BUCKETS,
+            )
+            .observe(elapsed.num_milliseconds().max(0) as f64 / 1000.0);
+    }
+
     /// Check if system is ready for activation
     fn is_system_ready(&self) -> bool {
         self.state.system_armed &&
@@ -369,19 +869,20 @@ impl FireSuppressionSystem {
             id: Uuid::new_v4(),
             timestamp: Utc::now(),
             event_type,
-            temperature: self.state.current_temperature,
+            temperature: self.hottest_temperature(),
             smoke_level: self.state.smoke_level,
-            location_estimate: None, // Would be calculated from sensors
+            location_estimate: self.location_estimate(),
             severity: self.assess_fire_risk(),
             response_actions: vec![description],
         };
 
-        self.event_history.push(event);
-        
-        // Keep only recent events
-        if self.event_history.len() > 100 {
-            self.event_history.drain(0..10);
+        #[cfg(feature = "siem")]
+        if let Some(siem) = &self.siem {
+            siem.forward(&event);
         }
+
+        // Keep only recent events
+        self.event_history.push(event);
     }
 
     /// Get current system status
@@ -389,6 +890,16 @@ impl FireSuppressionSystem {
         &self.state
     }
 
+    /// The last 100 fire events, oldest first
+    pub fn event_history(&self) -> &RingBuffer<FireEvent> {
+        &self.event_history
+    }
+
+    /// Get the debounced severity the system is currently acting on
+    pub fn current_severity(&self) -> FireSeverity {
+        self.confirmed_severity
+    }
+
     /// Get system status summary
     pub fn status_summary(&self) -> String {
         let health_emoji = match self.state.system_health {
@@ -407,7 +918,7 @@ impl FireSuppressionSystem {
         };
 
         format!(
-            "{} Fire Suppression {} | Health: {} | Pressure: {:.0} PSI | Capacity: {:.0}% | Temp: {:.1}°C | Smoke: {:.1}%",
+            "{} Fire Suppression {} | Health: {} | Pressure: {} | Capacity: {:.0}% | Temp: {} | Smoke: {:.1}%",
             status_emoji,
             self.state.nozzle_position.description(),
             health_emoji,
@@ -418,22 +929,30 @@ impl FireSuppressionSystem {
         )
     }
 
+    /// Extinguisher pressure check for the drone's preflight checklist
+    pub fn preflight_check(&self) -> PressureCheck {
+        PressureCheck {
+            valve: Arc::clone(&self.extinguisher_valve),
+            min_pressure: self.config.min_pressure,
+        }
+    }
+
     /// Emergency system test
     pub async fn system_test(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         info!("🧪 Starting fire suppression system test...");
 
         // Test nozzle deployment
         self.nozzle_actuator.deploy().await?;
-        tokio::time::sleep(Duration::from_millis(1000)).await;
+        runtime::sleep(Duration::from_millis(1000)).await;
         
         // Test pressure check
         let pressure = self.extinguisher_valve.read_pressure().await?;
-        info!("Extinguisher pressure: {:.1} PSI", pressure);
+        info!("Extinguisher pressure: {}", pressure);
         
         // Test sensors
         let temp = self.temperature_sensor.read_temperature().await?;
         let smoke = self.smoke_detector.read_smoke_level().await?;
-        info!("Temperature: {:.1}°C, Smoke: {:.1}%", temp, smoke * 100.0);
+        info!("Temperature: {}, Smoke: {:.1}%", temp, smoke * 100.0);
 
         // Retract nozzle
         self.nozzle_actuator.retract().await?;
@@ -443,37 +962,99 @@ impl FireSuppressionSystem {
     }
 }
 
+/// Thermal sensor interface
+#[async_trait]
+pub trait TemperatureSensor: Send + Sync {
+    async fn read_temperature(&self) -> Result<Celsius, Box<dyn std::error::Error>>;
+}
+
+/// Smoke detector interface
+#[async_trait]
+pub trait SmokeDetector: Send + Sync {
+    /// Obscuration from 0.0 (clear) to 1.0 (dense smoke)
+    async fn read_smoke_level(&self) -> Result<f32, Box<dyn std::error::Error>>;
+}
+
 // Hardware interface placeholders
-#[derive(Clone)]
-struct TemperatureSensor;
+struct SimulatedTemperatureSensor;
 
-impl TemperatureSensor {
-    fn new() -> Self { Self }
-    
-    async fn read_temperature(&self) -> Result<f32, Box<dyn std::error::Error>> {
+#[async_trait]
+impl TemperatureSensor for SimulatedTemperatureSensor {
+    async fn read_temperature(&self) -> Result<Celsius, Box<dyn std::error::Error>> {
         // Placeholder - would read from actual thermal sensor
-        Ok(22.0 + (rand::random::<f32>() * 5.0)) // Simulated room temp + noise
+        Ok(Celsius(22.0 + (rand::random::<f32>() * 5.0))) // Simulated room temp + noise
     }
 }
 
-#[derive(Clone)]
-struct SmokeDetector;
+struct SimulatedSmokeDetector;
 
-impl SmokeDetector {
-    fn new() -> Self { Self }
-    
+#[async_trait]
+impl SmokeDetector for SimulatedSmokeDetector {
     async fn read_smoke_level(&self) -> Result<f32, Box<dyn std::error::Error>> {
         // Placeholder - would read from actual smoke sensor
         Ok(rand::random::<f32>() * 0.1) // Low random smoke levels
     }
 }
 
-#[derive(Clone)]
-struct ExtinguisherValve;
+/// Flame sensor reading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlameReading {
+    pub detected: bool,
+    pub confidence: f32, // 0.0-1.0
+    pub spectrum: FlameSpectrum,
+}
 
-impl ExtinguisherValve {
-    fn new() -> Self { Self }
-    
+/// Detection band of a flame sensor
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum FlameSpectrum {
+    Infrared,
+    Ultraviolet,
+    /// Combined UV/IR sensor - fewest false alarms
+    UvIr,
+}
+
+/// IR/UV flame sensor interface
+#[async_trait]
+pub trait FlameSensor: Send + Sync {
+    async fn read_flame(&self) -> Result<FlameReading, Box<dyn std::error::Error>>;
+}
+
+/// Flame sensor placeholder used until hardware is attached
+struct SimulatedFlameSensor;
+
+#[async_trait]
+impl FlameSensor for SimulatedFlameSensor {
+    async fn read_flame(&self) -> Result<FlameReading, Box<dyn std::error::Error>> {
+        // Placeholder - would read from actual UV/IR flame detector
+        Ok(FlameReading {
+            detected: false,
+            confidence: 0.0,
+            spectrum: FlameSpectrum::UvIr,
+        })
+    }
+}
+
+/// Extinguisher discharge valve and its pressure gauge
+#[async_trait]
+pub trait ExtinguisherValve: Send + Sync {
+    async fn open(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    async fn read_pressure(&self) -> Result<Psi, Box<dyn std::error::Error>>;
+}
+
+/// Extinguisher pressure transducer, for valves that only switch
+#[async_trait]
+pub trait PressureGauge: Send + Sync {
+    async fn read_pressure(&self) -> Result<Psi, Box<dyn std::error::Error>>;
+}
+
+/// Valve placeholder used until hardware is attached
+struct SimulatedExtinguisherValve;
+
+#[async_trait]
+impl ExtinguisherValve for SimulatedExtinguisherValve {
     async fn open(&self) -> Result<(), Box<dyn std::error::Error>> {
         info!("💨 Extinguisher valve OPENED - CO₂ discharge active");
         Ok(())
@@ -484,17 +1065,36 @@ impl ExtinguisherValve {
         Ok(())
     }
     
-    async fn read_pressure(&self) -> Result<f32, Box<dyn std::error::Error>> {
+    async fn read_pressure(&self) -> Result<Psi, Box<dyn std::error::Error>> {
         // Placeholder - would read from pressure sensor
-        Ok(145.0 + (rand::random::<f32>() * 10.0)) // Simulated pressure
+        Ok(Psi(145.0 + (rand::random::<f32>() * 10.0))) // Simulated pressure
     }
 }
 
-struct NozzleActuator;
+/// Spread either side of the aim point checked for obstacles before a
+/// targeted discharge (degrees)
+const NOZZLE_SPRAY_HALF_WIDTH_DEG: f32 = 30.0;
 
-impl NozzleActuator {
-    fn new() -> Self { Self }
-    
+/// Nozzle positioning: stowed, deployed, aimed at a fire, or opened for
+/// maximum coverage
+#[async_trait]
+pub trait NozzleActuator: Send + Sync {
+    async fn deploy(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    async fn retract(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Aim at `aim` (degrees right of and above the thermal camera's axis),
+    /// or straight ahead when the fire has not been located
+    async fn target_fire(&self, aim: Option<(f32, f32)>) -> Result<(), Box<dyn std::error::Error>>;
+
+    async fn emergency_deploy(&self) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// Nozzle placeholder used until hardware is attached
+struct SimulatedNozzleActuator;
+
+#[async_trait]
+impl NozzleActuator for SimulatedNozzleActuator {
     async fn deploy(&self) -> Result<(), Box<dyn std::error::Error>> {
         info!("🔧 Fire suppression nozzle deployed");
         Ok(())
@@ -505,8 +1105,11 @@ impl NozzleActuator {
         Ok(())
     }
     
-    async fn target_fire(&self) -> Result<(), Box<dyn std::error::Error>> {
-        info!("🎯 Nozzle targeting fire source");
+    async fn target_fire(&self, aim: Option<(f32, f32)>) -> Result<(), Box<dyn std::error::Error>> {
+        match aim {
+            Some((azimuth, elevation)) => info!("🎯 Nozzle targeting fire source at {:.0}° azimuth, {:.0}° elevation", azimuth, elevation),
+            None => info!("🎯 Nozzle targeting fire source"),
+        }
         Ok(())
     }
     
@@ -515,3 +1118,123 @@ impl NozzleActuator {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rise(system: &mut FireSuppressionSystem, samples: &[(i64, f32)]) {
+        let start = Utc::now();
+        for &(seconds, temperature) in samples {
+            system.record_temperature_sample(start + chrono::Duration::seconds(seconds), Celsius(temperature));
+        }
+    }
+
+    #[test]
+    fn rate_of_rise_is_measured_per_minute() {
+        let mut system = FireSuppressionSystem::new(FireSuppressionConfig::default());
+        rise(&mut system, &[(0, 20.0), (30, 25.0)]);
+        assert!((system.state.temperature_rise_rate - 10.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn rate_of_rise_ignores_a_few_seconds_of_noise() {
+        let mut system = FireSuppressionSystem::new(FireSuppressionConfig::default());
+        rise(&mut system, &[(0, 20.0), (2, 24.0)]);
+        assert_eq!(system.state.temperature_rise_rate, 0.0);
+    }
+
+    #[test]
+    fn rate_of_rise_forgets_samples_outside_the_window() {
+        let mut system = FireSuppressionSystem::new(FireSuppressionConfig::default());
+        // The slow climb from 0s falls out of the 60s window; only the last minute counts
+        rise(&mut system, &[(0, 0.0), (60, 20.0), (120, 20.0)]);
+        assert_eq!(system.temperature_samples.len(), 2);
+        assert_eq!(system.state.temperature_rise_rate, 0.0);
+    }
+
+    #[test]
+    fn fast_rise_below_the_activation_temperature_is_high() {
+        let mut system = FireSuppressionSystem::new(FireSuppressionConfig::default());
+        rise(&mut system, &[(0, 25.0), (60, 40.0)]);
+        system.state.current_temperature = Celsius(40.0);
+        system.state.smoke_level = 0.0;
+        assert_eq!(system.assess_fire_risk(), FireSeverity::High);
+
+        system.temperature_samples.clear();
+        rise(&mut system, &[(0, 25.0), (60, 30.0)]);
+        assert_eq!(system.assess_fire_risk(), FireSeverity::Low);
+    }
+
+    fn debounced(system: &mut FireSuppressionSystem, readings: &[FireSeverity]) -> Vec<FireSeverity> {
+        readings.iter().map(|&observed| system.debounce_severity(observed)).collect()
+    }
+
+    #[test]
+    fn escalation_waits_for_consecutive_readings() {
+        use FireSeverity::*;
+        let mut system = FireSuppressionSystem::new(FireSuppressionConfig::default());
+        assert_eq!(debounced(&mut system, &[High, Low, High, High]), vec![Low, Low, Low, High]);
+    }
+
+    #[test]
+    fn de_escalation_is_slower_than_escalation() {
+        use FireSeverity::*;
+        let mut system = FireSuppressionSystem::new(FireSuppressionConfig::default());
+        debounced(&mut system, &[Critical, Critical]);
+        assert_eq!(debounced(&mut system, &[Low; 4]), vec![Critical; 4]);
+        assert_eq!(system.debounce_severity(Low), Low);
+    }
+
+    #[test]
+    fn partial_config_keeps_the_defaults() {
+        let config: FireSuppressionConfig = serde_json::from_str(r#"{ "escalation_readings": 3, "risk_thresholds": { "high": 0.5 } }"#).unwrap();
+        assert_eq!(config.escalation_readings, 3);
+        assert_eq!(config.de_escalation_readings, 5);
+        assert_eq!(config.risk_thresholds.high, 0.5);
+        assert_eq!(config.risk_thresholds.critical, 0.8);
+        assert_eq!(config.rate_of_rise_window, 60);
+        assert_eq!(config.flame_confidence_threshold, 0.8);
+    }
+
+    struct FixedFlame(f32);
+
+    #[async_trait]
+    impl FlameSensor for FixedFlame {
+        async fn read_flame(&self) -> Result<FlameReading, Box<dyn std::error::Error>> {
+            Ok(FlameReading {
+                detected: true,
+                confidence: self.0,
+                spectrum: FlameSpectrum::UvIr,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn confident_flame_is_an_active_fire() {
+        let mut system = FireSuppressionSystem::new(FireSuppressionConfig::default()).with_flame_sensor(Box::new(FixedFlame(0.9)));
+        system.update_sensors().await.unwrap();
+        assert!(system.state.flame_detected);
+        assert!(system.assess_fire_risk() >= FireSeverity::High);
+    }
+
+    #[tokio::test]
+    async fn unconfident_flame_is_ignored() {
+        let mut system = FireSuppressionSystem::new(FireSuppressionConfig::default()).with_flame_sensor(Box::new(FixedFlame(0.5)));
+        system.update_sensors().await.unwrap();
+        assert!(!system.state.flame_detected);
+    }
+
+    #[tokio::test]
+    async fn disarmed_system_does_not_discharge() {
+        let mut system = FireSuppressionSystem::new(FireSuppressionConfig::default());
+        system.set_armed(false).await.unwrap();
+        assert!(!system.state.system_armed);
+        assert!(system.manual_activate().await.is_err());
+        assert!(!system.state.discharge_active);
+
+        system.set_armed(true).await.unwrap();
+        system.manual_activate().await.unwrap();
+        assert!(system.state.discharge_active);
+    }
+}
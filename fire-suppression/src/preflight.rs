@@ -0,0 +1,40 @@
+//! Extinguisher pressure on the preflight checklist
+
+use crate::ExtinguisherValve;
+use async_trait::async_trait;
+use dark_phoenix_core::{CheckOutcome, DroneState, PreflightCheck, Psi};
+use std::sync::Arc;
+
+/// Reads the extinguisher gauge and fails below the configured minimum;
+/// made by `FireSuppressionSystem::preflight_check`
+pub struct PressureCheck {
+    pub(crate) valve: Arc<dyn ExtinguisherValve>,
+    pub(crate) min_pressure: Psi,
+}
+
+#[async_trait]
+impl PreflightCheck for PressureCheck {
+    fn name(&self) -> &str {
+        "fire_suppression"
+    }
+
+    async fn run(&self, _drone: &DroneState) -> CheckOutcome {
+        let pressure = match self.valve.read_pressure().await {
+            Ok(pressure) => pressure,
+            Err(e) => {
+                return CheckOutcome::fail(
+                    format!("pressure gauge unreadable: {}", e),
+                    "check the gauge's connection to the valve controller",
+                )
+            },
+        };
+        if pressure < self.min_pressure {
+            CheckOutcome::fail(
+                format!("extinguisher at {}, below the {} minimum", pressure, self.min_pressure),
+                "recharge or replace the extinguisher cartridge",
+            )
+        } else {
+            CheckOutcome::pass(format!("extinguisher at {}", pressure))
+        }
+    }
+}
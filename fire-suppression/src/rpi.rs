@@ -0,0 +1,109 @@
+//! Valve relay and nozzle servos on Raspberry Pi pins (`rpi-hw` feature)
+
+use crate::{ExtinguisherValve, FireSuppressionSystem, NozzleActuator, PressureGauge};
+use async_trait::async_trait;
+use dark_phoenix_core::{GpioRelay, HardwareError, HardwarePins, NozzleServos, Psi};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tracing::info;
+
+/// An extinguisher valve opened by a relay
+pub struct RelayValve {
+    relay: Mutex<GpioRelay>,
+    gauge: Arc<dyn PressureGauge>,
+}
+
+impl RelayValve {
+    pub fn new(relay: GpioRelay, gauge: Arc<dyn PressureGauge>) -> Self {
+        Self { relay: Mutex::new(relay), gauge }
+    }
+
+    fn relay(&self) -> MutexGuard<'_, GpioRelay> {
+        self.relay.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[async_trait]
+impl ExtinguisherValve for RelayValve {
+    async fn open(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.relay().set(true)?;
+        info!("💨 Extinguisher valve relay OPENED - discharge active");
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.relay().set(false)?;
+        info!("🛑 Extinguisher valve relay CLOSED - discharge stopped");
+        Ok(())
+    }
+
+    async fn read_pressure(&self) -> Result<Psi, Box<dyn std::error::Error>> {
+        self.gauge.read_pressure().await
+    }
+}
+
+/// A nozzle on pan and tilt servos
+pub struct ServoNozzle {
+    servos: Mutex<NozzleServos>,
+}
+
+impl ServoNozzle {
+    pub fn new(servos: NozzleServos) -> Self {
+        Self { servos: Mutex::new(servos) }
+    }
+
+    fn point(&self, pan_deg: f32, tilt_deg: f32) -> Result<(), HardwareError> {
+        let mut servos = self.servos.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        servos.pan.set_angle(pan_deg)?;
+        servos.tilt.set_angle(tilt_deg)
+    }
+}
+
+#[async_trait]
+impl NozzleActuator for ServoNozzle {
+    async fn deploy(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.point(0.0, 0.0)?;
+        info!("🔧 Fire suppression nozzle deployed");
+        Ok(())
+    }
+
+    async fn retract(&self) -> Result<(), Box<dyn std::error::Error>> {
+        {
+            let mut servos = self.servos.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            servos.pan.rest()?;
+            servos.tilt.rest()?;
+        }
+        info!("🔧 Fire suppression nozzle retracted");
+        Ok(())
+    }
+
+    async fn target_fire(&self, aim: Option<(f32, f32)>) -> Result<(), Box<dyn std::error::Error>> {
+        let (azimuth, elevation) = aim.unwrap_or((0.0, 0.0));
+        self.point(azimuth, elevation)?;
+        info!("🎯 Nozzle servos at {:.0}° pan, {:.0}° tilt", azimuth, elevation);
+        Ok(())
+    }
+
+    async fn emergency_deploy(&self) -> Result<(), Box<dyn std::error::Error>> {
+        // Centred, where the spray cone covers most of the camera's view
+        self.point(0.0, 0.0)?;
+        info!("🚨 Emergency nozzle deployment - maximum coverage");
+        Ok(())
+    }
+}
+
+impl FireSuppressionSystem {
+    /// Switch the valve and aim the nozzle through the pins mapped in
+    /// `pins`, reading pressure from `gauge`; unmapped outputs stay
+    /// placeholders
+    pub fn with_rpi_outputs(mut self, pins: &HardwarePins, gauge: Arc<dyn PressureGauge>) -> Result<Self, HardwareError> {
+        if let Some(relay) = pins.open_valve()? {
+            info!("💨 Extinguisher valve relay on {:?}", pins.valve);
+            self = self.with_extinguisher_valve(Arc::new(RelayValve::new(relay, gauge)));
+        }
+        if let Some(servos) = pins.open_nozzle()? {
+            info!("🎯 Nozzle servos on {:?} and {:?}", pins.nozzle_pan.as_ref().map(|pin| pin.pwm), pins.nozzle_tilt.as_ref().map(|pin| pin.pwm));
+            self = self.with_nozzle_actuator(Box::new(ServoNozzle::new(servos)));
+        }
+        Ok(self)
+    }
+}
@@ -0,0 +1,45 @@
+//! Fire events as SIEM records (`siem` feature)
+
+use crate::{FireEvent, FireSeverity};
+use chrono::{DateTime, Utc};
+use dark_phoenix_core::siem::{SiemField, SiemRecord};
+
+impl SiemRecord for FireEvent {
+    const KIND: &'static str = "fire_event";
+
+    fn signature_id(&self) -> String {
+        format!("{:?}", self.event_type)
+    }
+
+    /// The response taken, which is what the event was logged for
+    fn name(&self) -> String {
+        if self.response_actions.is_empty() {
+            format!("{:?}", self.event_type)
+        } else {
+            self.response_actions.join("; ")
+        }
+    }
+
+    fn severity(&self) -> u8 {
+        match self.severity {
+            FireSeverity::Low => 3,
+            FireSeverity::Medium => 5,
+            FireSeverity::High => 8,
+            FireSeverity::Critical => 10,
+        }
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn default_fields() -> Vec<SiemField> {
+        vec![
+            SiemField::new("externalId", "id"),
+            SiemField::labelled("cs1", "severity", "fireSeverity"),
+            SiemField::labelled("cfp1", "temperature", "temperatureCelsius"),
+            SiemField::labelled("cfp2", "smoke_level", "smokeLevel"),
+            SiemField::labelled("cs2", "location_estimate", "locationEstimate"),
+        ]
+    }
+}
@@ -0,0 +1,43 @@
+//! Scenario-backed sensors (`simulation` feature)
+
+use crate::{FireSuppressionSystem, FlameReading, FlameSensor, FlameSpectrum, SmokeDetector, TemperatureSensor};
+use async_trait::async_trait;
+use dark_phoenix_core::{Celsius, ScenarioPlayer};
+
+/// Reported when the scenario has no temperature track
+const ROOM_TEMPERATURE: Celsius = Celsius(22.0);
+
+#[async_trait]
+impl TemperatureSensor for ScenarioPlayer {
+    async fn read_temperature(&self) -> Result<Celsius, Box<dyn std::error::Error>> {
+        Ok(self.temperature().unwrap_or(ROOM_TEMPERATURE))
+    }
+}
+
+#[async_trait]
+impl SmokeDetector for ScenarioPlayer {
+    async fn read_smoke_level(&self) -> Result<f32, Box<dyn std::error::Error>> {
+        Ok(self.smoke_level().unwrap_or(0.0))
+    }
+}
+
+#[async_trait]
+impl FlameSensor for ScenarioPlayer {
+    async fn read_flame(&self) -> Result<FlameReading, Box<dyn std::error::Error>> {
+        let flame = self.flame();
+        Ok(FlameReading {
+            detected: flame.as_ref().is_some_and(|flame| flame.detected),
+            confidence: flame.map_or(0.0, |flame| flame.confidence),
+            spectrum: FlameSpectrum::UvIr,
+        })
+    }
+}
+
+impl FireSuppressionSystem {
+    /// Read temperature, smoke and flame from `player` instead of hardware
+    pub fn with_scenario(self, player: &ScenarioPlayer) -> Self {
+        self.with_temperature_sensor(Box::new(player.clone()))
+            .with_smoke_detector(Box::new(player.clone()))
+            .with_flame_sensor(Box::new(player.clone()))
+    }
+}
@@ -0,0 +1,116 @@
+//! Valve and nozzle in PX4 SITL + Gazebo (`sitl` feature)
+
+use crate::{ExtinguisherValve, FireSuppressionSystem, NozzleActuator};
+use async_trait::async_trait;
+use dark_phoenix_core::{NozzleIntent, Psi, SitlActuators};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tracing::info;
+
+/// Pressure of a full tank, and how fast an open valve drains it
+const FULL_PSI: f32 = 150.0;
+const DRAIN_PSI_PER_SEC: f32 = 6.0;
+
+/// The extinguisher valve, as an intent, with a modelled tank
+pub struct SitlValve {
+    actuators: SitlActuators,
+    /// Pressure when the valve last moved, and since when it has been open
+    tank: Mutex<(f32, Option<Instant>)>,
+}
+
+impl SitlValve {
+    pub fn new(actuators: SitlActuators) -> Self {
+        Self {
+            actuators,
+            tank: Mutex::new((FULL_PSI, None)),
+        }
+    }
+
+    fn pressure(tank: &(f32, Option<Instant>)) -> f32 {
+        let drained = tank.1.map_or(0.0, |opened| opened.elapsed().as_secs_f32() * DRAIN_PSI_PER_SEC);
+        (tank.0 - drained).max(0.0)
+    }
+}
+
+#[async_trait]
+impl ExtinguisherValve for SitlValve {
+    async fn open(&self) -> Result<(), Box<dyn std::error::Error>> {
+        {
+            let mut tank = self.tank.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if tank.1.is_none() {
+                *tank = (Self::pressure(&tank), Some(Instant::now()));
+            }
+        }
+        self.actuators.update(|intents| intents.valve_open = true);
+        info!("💨 Extinguisher valve OPENED in SITL - discharge active");
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<(), Box<dyn std::error::Error>> {
+        {
+            let mut tank = self.tank.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            *tank = (Self::pressure(&tank), None);
+        }
+        self.actuators.update(|intents| intents.valve_open = false);
+        info!("🛑 Extinguisher valve CLOSED in SITL - discharge stopped");
+        Ok(())
+    }
+
+    async fn read_pressure(&self) -> Result<Psi, Box<dyn std::error::Error>> {
+        let tank = self.tank.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok(Psi(Self::pressure(&tank)))
+    }
+}
+
+/// The nozzle gimbal, as an intent
+pub struct SitlNozzle(SitlActuators);
+
+impl SitlNozzle {
+    pub fn new(actuators: SitlActuators) -> Self {
+        Self(actuators)
+    }
+}
+
+#[async_trait]
+impl NozzleActuator for SitlNozzle {
+    async fn deploy(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.0.update(|intents| intents.nozzle = NozzleIntent::Deployed);
+        info!("🔧 Fire suppression nozzle deployed in SITL");
+        Ok(())
+    }
+
+    async fn retract(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.0.update(|intents| {
+            intents.nozzle = NozzleIntent::Stowed;
+            intents.nozzle_pan_deg = 0.0;
+            intents.nozzle_tilt_deg = 0.0;
+        });
+        info!("🔧 Fire suppression nozzle retracted in SITL");
+        Ok(())
+    }
+
+    async fn target_fire(&self, aim: Option<(f32, f32)>) -> Result<(), Box<dyn std::error::Error>> {
+        let (pan_deg, tilt_deg) = aim.unwrap_or((0.0, 0.0));
+        self.0.update(|intents| {
+            intents.nozzle_pan_deg = pan_deg;
+            intents.nozzle_tilt_deg = tilt_deg;
+        });
+        info!("🎯 Nozzle gimbal at {:.0}° pan, {:.0}° tilt in SITL", pan_deg, tilt_deg);
+        Ok(())
+    }
+
+    async fn emergency_deploy(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.0.update(|intents| intents.nozzle = NozzleIntent::Emergency);
+        info!("🚨 Emergency nozzle deployment in SITL - maximum coverage");
+        Ok(())
+    }
+}
+
+impl FireSuppressionSystem {
+    /// Discharge and aim in the simulator
+    pub fn with_sitl_outputs(self, actuators: &SitlActuators) -> Self {
+        info!("🎮 Extinguisher valve and nozzle gimbal published to SITL");
+        self.with_extinguisher_valve(Arc::new(SitlValve::new(actuators.clone())))
+            .with_nozzle_actuator(Box::new(SitlNozzle::new(actuators.clone())))
+    }
+}
@@ -1,6 +0,0 @@
-// Flight Control Module - Autonomous Navigation and Control
-// TODO: Implement PX4/ArduPilot integration, GPS navigation, collision avoidance
-
-pub fn placeholder() {
-    println!("Flight Control Module - Ready for implementation");
-}
@@ -0,0 +1,442 @@
+//! Flight controller link over MAVLink (PX4 and ArduPilot)
+
+use chrono::{DateTime, Utc};
+use mavlink::{command, frame, result, Frame, Message, Parser};
+use phoenix_runtime::{self as runtime, Task};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::{broadcast, watch};
+use tracing::{error, info, warn};
+use transport::Transport;
+
+pub mod mavlink;
+mod transport;
+
+/// Flight controller link configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlightConfig {
+    /// `udpin:host:port`, `udpout:host:port` or `tcpout:host:port`
+    pub connection: String,
+    /// Our identity on the link; 191 is MAV_COMP_ID_ONBOARD_COMPUTER
+    pub system_id: u8,
+    pub component_id: u8,
+    /// The autopilot's identity
+    pub target_system: u8,
+    pub target_component: u8,
+    /// How often we announce ourselves (milliseconds)
+    pub heartbeat_interval_ms: u64,
+    /// Silence from the autopilot before the link counts as lost (milliseconds)
+    pub link_timeout_ms: u64,
+    /// Wait for each COMMAND_ACK (milliseconds)
+    pub command_timeout_ms: u64,
+    pub command_attempts: u32,
+}
+
+impl Default for FlightConfig {
+    fn default() -> Self {
+        Self {
+            connection: "udpin:0.0.0.0:14540".to_string(), // PX4's offboard API port
+            system_id: 1,
+            component_id: 191,
+            target_system: 1,
+            target_component: 1,
+            heartbeat_interval_ms: 1000,
+            link_timeout_ms: 3000,
+            command_timeout_ms: 1500,
+            command_attempts: 3,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FlightError {
+    #[error("invalid connection '{0}' (expected udpin:, udpout: or tcpout: followed by host:port)")]
+    InvalidConnection(String),
+    #[error("link error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("flight controller closed the link")]
+    Closed,
+    #[error("{command} rejected by the flight controller (MAV_RESULT {result})")]
+    Rejected { command: &'static str, result: u8 },
+    #[error("{command} not acknowledged after {attempts} attempts")]
+    NoAck { command: &'static str, attempts: u32 },
+}
+
+/// Autopilot firmware, which decides how flight modes are requested
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Autopilot {
+    Px4,
+    ArduPilot,
+    Other(u8), // MAV_AUTOPILOT value
+}
+
+impl Autopilot {
+    fn from_mav(autopilot: u8) -> Self {
+        match autopilot {
+            mavlink::MAV_AUTOPILOT_PX4 => Autopilot::Px4,
+            mavlink::MAV_AUTOPILOT_ARDUPILOTMEGA => Autopilot::ArduPilot,
+            other => Autopilot::Other(other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Attitude {
+    pub roll_deg: f32,
+    pub pitch_deg: f32,
+    pub yaw_deg: f32,
+}
+
+/// Accelerometer and gyro readings, body frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Imu {
+    pub acceleration_mps2: [f32; 3],
+    pub angular_rate_dps: [f32; 3],
+}
+
+/// What the autopilot last reported
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VehicleStatus {
+    /// Heartbeats arriving within the link timeout
+    pub connected: bool,
+    pub autopilot: Option<Autopilot>,
+    pub armed: bool,
+    pub custom_mode: u32, // Firmware-specific flight mode
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude_msl_m: f64,
+    pub relative_altitude_m: f64, // Above the home position
+    pub heading_deg: Option<f32>,
+    pub attitude: Attitude,
+    /// From HIGHRES_IMU, which PX4 streams to onboard computers
+    pub imu: Option<Imu>,
+    pub gps_fix_type: u8, // GPS_FIX_TYPE: 3 and above is a 3D fix
+    pub satellites: u8,
+    pub battery_voltage: Option<f32>,
+    pub battery_current_a: Option<f32>,
+    pub battery_remaining: Option<u8>, // 0-100%
+    pub last_heartbeat: Option<DateTime<Utc>>,
+}
+
+impl VehicleStatus {
+    pub fn gps_lock(&self) -> bool {
+        self.gps_fix_type >= 3
+    }
+
+    /// Fold one message from the autopilot in; true if anything changed
+    fn apply(&mut self, message: &Message, from_autopilot: bool) -> bool {
+        match *message {
+            Message::Heartbeat {
+                custom_mode,
+                autopilot,
+                base_mode,
+                ..
+            } if from_autopilot && autopilot != mavlink::MAV_AUTOPILOT_INVALID => {
+                self.connected = true;
+                self.autopilot = Some(Autopilot::from_mav(autopilot));
+                self.armed = base_mode & mavlink::MAV_MODE_FLAG_SAFETY_ARMED != 0;
+                self.custom_mode = custom_mode;
+                self.last_heartbeat = Some(Utc::now());
+            },
+            Message::SysStatus {
+                voltage_mv,
+                current_ca,
+                battery_remaining,
+            } => {
+                self.battery_voltage = (voltage_mv != u16::MAX).then(|| f32::from(voltage_mv) / 1000.0);
+                self.battery_current_a = (current_ca >= 0).then(|| f32::from(current_ca) / 100.0);
+                self.battery_remaining = u8::try_from(battery_remaining).ok().map(|remaining| remaining.min(100));
+            },
+            Message::GpsRawInt {
+                fix_type,
+                satellites_visible,
+            } => {
+                self.gps_fix_type = fix_type;
+                self.satellites = satellites_visible;
+            },
+            Message::Attitude { roll, pitch, yaw } => {
+                self.attitude = Attitude {
+                    roll_deg: roll.to_degrees(),
+                    pitch_deg: pitch.to_degrees(),
+                    yaw_deg: yaw.to_degrees(),
+                };
+            },
+            Message::GlobalPositionInt {
+                lat,
+                lon,
+                alt_mm,
+                relative_alt_mm,
+                hdg,
+            } => {
+                self.latitude = f64::from(lat) / 1e7;
+                self.longitude = f64::from(lon) / 1e7;
+                self.altitude_msl_m = f64::from(alt_mm) / 1000.0;
+                self.relative_altitude_m = f64::from(relative_alt_mm) / 1000.0;
+                self.heading_deg = (hdg != u16::MAX).then(|| f32::from(hdg) / 100.0);
+            },
+            Message::HighresImu { acceleration, angular_rate } => {
+                self.imu = Some(Imu {
+                    acceleration_mps2: acceleration,
+                    angular_rate_dps: angular_rate.map(f32::to_degrees),
+                });
+            },
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// PX4 custom modes: main mode AUTO with its sub-modes
+const PX4_MAIN_AUTO: f32 = 4.0;
+const PX4_AUTO_LOITER: f32 = 3.0;
+/// ArduCopter flight modes
+const COPTER_LOITER: f32 = 5.0;
+const COPTER_LAND: f32 = 9.0;
+/// MAV_DO_REPOSITION_FLAGS_CHANGE_MODE
+const REPOSITION_CHANGE_MODE: f32 = 1.0;
+
+/// A live link to the autopilot
+pub struct FlightController {
+    config: FlightConfig,
+    transport: Arc<Transport>,
+    status: watch::Receiver<VehicleStatus>,
+    acks: broadcast::Sender<(u16, u8)>,
+    sequence: Arc<AtomicU8>,
+    tasks: Vec<Task<()>>,
+}
+
+impl FlightController {
+    /// Open the link and start listening; the autopilot counts as connected
+    /// from its first heartbeat
+    pub async fn connect(config: FlightConfig) -> Result<Self, FlightError> {
+        let transport = Arc::new(Transport::connect(&config.connection).await?);
+        let (status_tx, status) = watch::channel(VehicleStatus::default());
+        let (acks, _) = broadcast::channel(16);
+        let sequence = Arc::new(AtomicU8::new(0));
+        let tasks = vec![
+            runtime::spawn(read_link(Arc::clone(&transport), config.clone(), status_tx, acks.clone())),
+            runtime::spawn(send_heartbeats(Arc::clone(&transport), config.clone(), Arc::clone(&sequence))),
+        ];
+        info!("🛩️ Flight controller link open on {}", config.connection);
+        Ok(Self {
+            config,
+            transport,
+            status,
+            acks,
+            sequence,
+            tasks,
+        })
+    }
+
+    pub fn status(&self) -> VehicleStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Status updates as the autopilot streams them
+    pub fn watch(&self) -> watch::Receiver<VehicleStatus> {
+        self.status.clone()
+    }
+
+    /// Hold the current position
+    pub async fn loiter(&self) -> Result<(), FlightError> {
+        let autopilot = self.status.borrow().autopilot;
+        match autopilot {
+            Some(Autopilot::Px4) => self.set_mode("loiter", PX4_MAIN_AUTO, PX4_AUTO_LOITER).await,
+            Some(Autopilot::ArduPilot) => self.set_mode("loiter", COPTER_LOITER, 0.0).await,
+            // Firmware we cannot set modes on: pause whatever it is doing
+            _ => self.command("loiter", command::DO_PAUSE_CONTINUE, [0.0; 7]).await,
+        }
+    }
+
+    /// Set the autopilot's generic actuator outputs 1-6 (-1.0 to 1.0; NaN
+    /// leaves an output as it is), e.g. payload servos in a simulator
+    pub async fn set_actuators(&self, values: [f32; 6]) -> Result<(), FlightError> {
+        let [a, b, c, d, e, f] = values;
+        self.command("set actuators", command::DO_SET_ACTUATOR, [a, b, c, d, e, f, 0.0]).await
+    }
+
+    pub async fn return_to_home(&self) -> Result<(), FlightError> {
+        self.command("return to home", command::NAV_RETURN_TO_LAUNCH, [0.0; 7]).await
+    }
+
+    /// Descend and land where the drone is
+    pub async fn emergency_land(&self) -> Result<(), FlightError> {
+        let ardupilot = self.status.borrow().autopilot == Some(Autopilot::ArduPilot);
+        if ardupilot {
+            // ArduCopter lands on a mode change rather than NAV_LAND
+            self.set_mode("land", COPTER_LAND, 0.0).await
+        } else {
+            // NaN latitude/longitude/yaw: land here, keeping the current heading
+            let nan = f32::NAN;
+            self.command("land", command::NAV_LAND, [0.0, 0.0, 0.0, nan, nan, nan, 0.0]).await
+        }
+    }
+
+    /// Fly to a point `altitude_m` above home and hold there, facing
+    /// `heading_deg` (clockwise from north) when given
+    pub async fn fly_to(&self, latitude: f64, longitude: f64, altitude_m: f64, heading_deg: Option<f32>) -> Result<(), FlightError> {
+        // Default speed; switch to a guided mode so the autopilot follows
+        let params = [-1.0, REPOSITION_CHANGE_MODE, 0.0, heading_deg.unwrap_or(f32::NAN)];
+        let message = Message::CommandInt {
+            target_system: self.config.target_system,
+            target_component: self.config.target_component,
+            frame: frame::GLOBAL_RELATIVE_ALT,
+            command: command::DO_REPOSITION,
+            params,
+            x: (latitude * 1e7).round() as i32,
+            y: (longitude * 1e7).round() as i32,
+            z: altitude_m as f32,
+        };
+        self.send_command("fly to", command::DO_REPOSITION, |_| message.clone()).await
+    }
+
+    async fn set_mode(&self, name: &'static str, main: f32, sub: f32) -> Result<(), FlightError> {
+        let params = [mavlink::MAV_MODE_FLAG_CUSTOM_MODE_ENABLED, main, sub, 0.0, 0.0, 0.0, 0.0];
+        self.command(name, command::DO_SET_MODE, params).await
+    }
+
+    /// Send COMMAND_LONG until the autopilot acknowledges it
+    async fn command(&self, name: &'static str, command: u16, params: [f32; 7]) -> Result<(), FlightError> {
+        self.send_command(name, command, |confirmation| Message::CommandLong {
+            target_system: self.config.target_system,
+            target_component: self.config.target_component,
+            command,
+            confirmation,
+            params,
+        })
+        .await
+    }
+
+    /// Send the message built for each attempt until the autopilot
+    /// acknowledges `command`
+    async fn send_command(
+        &self,
+        name: &'static str,
+        command: u16,
+        message: impl Fn(u8) -> Message,
+    ) -> Result<(), FlightError> {
+        let mut acks = self.acks.subscribe();
+        let attempts = self.config.command_attempts.max(1);
+        let timeout = Duration::from_millis(self.config.command_timeout_ms);
+        let mut rejected = None;
+        for confirmation in 0..attempts {
+            let frame = Frame {
+                sequence: self.sequence.fetch_add(1, Ordering::Relaxed),
+                system_id: self.config.system_id,
+                component_id: self.config.component_id,
+                message: message(confirmation.min(255) as u8),
+            };
+            self.transport.send(&frame.encode()).await?;
+
+            let deadline = Instant::now() + timeout;
+            rejected = loop {
+                match runtime::timeout_at(deadline, acks.recv()).await {
+                    Ok(Ok((acked, outcome))) if acked == command => match outcome {
+                        result::ACCEPTED | result::IN_PROGRESS => {
+                            info!("🛩️ Flight controller accepted {}", name);
+                            return Ok(());
+                        },
+                        result::TEMPORARILY_REJECTED => break Some(outcome),
+                        _ => return Err(FlightError::Rejected { command: name, result: outcome }),
+                    },
+                    Ok(Ok(_)) | Ok(Err(broadcast::error::RecvError::Lagged(_))) => continue,
+                    Ok(Err(broadcast::error::RecvError::Closed)) => return Err(FlightError::Closed),
+                    Err(_) => break None,
+                }
+            };
+            warn!("🛩️ {} not accepted (attempt {}/{})", name, confirmation + 1, attempts);
+        }
+        Err(match rejected {
+            Some(result) => FlightError::Rejected { command: name, result },
+            None => FlightError::NoAck { command: name, attempts },
+        })
+    }
+}
+
+impl Drop for FlightController {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Parse everything the autopilot sends into the status and acknowledgements
+async fn read_link(
+    transport: Arc<Transport>,
+    config: FlightConfig,
+    status: watch::Sender<VehicleStatus>,
+    acks: broadcast::Sender<(u16, u8)>,
+) {
+    let mut parser = Parser::new();
+    let mut buffer = [0u8; 2048];
+    let link_timeout = Duration::from_millis(config.link_timeout_ms.max(100));
+    loop {
+        match runtime::timeout(link_timeout, transport.recv(&mut buffer)).await {
+            Ok(Ok(len)) => {
+                for frame in parser.push(&buffer[..len]) {
+                    if frame.system_id != config.target_system {
+                        continue;
+                    }
+                    if let Message::CommandAck { command, result } = frame.message {
+                        // No subscribers is fine
+                        let _ = acks.send((command, result));
+                        continue;
+                    }
+                    let from_autopilot = frame.component_id == config.target_component;
+                    status.send_if_modified(|status| status.apply(&frame.message, from_autopilot));
+                }
+            },
+            Ok(Err(FlightError::Closed)) => {
+                error!("🛩️ Flight controller closed the link");
+                status.send_modify(|status| status.connected = false);
+                return;
+            },
+            Ok(Err(e)) => {
+                // e.g. ICMP port unreachable while the autopilot boots
+                warn!("🛩️ Flight controller link error: {}", e);
+                runtime::sleep(Duration::from_millis(100)).await;
+            },
+            Err(_) => {},
+        }
+
+        let stale = status.borrow().last_heartbeat.is_none_or(|at| {
+            (Utc::now() - at).to_std().unwrap_or_default() > link_timeout
+        });
+        status.send_if_modified(|status| {
+            let lost = stale && status.connected;
+            if lost {
+                warn!("🛩️ No heartbeat from the flight controller for {:?}", link_timeout);
+                status.connected = false;
+            }
+            lost
+        });
+    }
+}
+
+/// Announce the companion computer so the autopilot accepts its commands
+async fn send_heartbeats(transport: Arc<Transport>, config: FlightConfig, sequence: Arc<AtomicU8>) {
+    let mut interval = runtime::interval(Duration::from_millis(config.heartbeat_interval_ms.max(100)));
+    loop {
+        interval.tick().await;
+        let frame = Frame {
+            sequence: sequence.fetch_add(1, Ordering::Relaxed),
+            system_id: config.system_id,
+            component_id: config.component_id,
+            message: Message::Heartbeat {
+                custom_mode: 0,
+                mav_type: mavlink::MAV_TYPE_ONBOARD_CONTROLLER,
+                autopilot: mavlink::MAV_AUTOPILOT_INVALID,
+                base_mode: 0,
+                system_status: mavlink::MAV_STATE_ACTIVE,
+            },
+        };
+        if let Err(e) = transport.send(&frame.encode()).await {
+            warn!("🛩️ Heartbeat to the flight controller failed: {}", e);
+        }
+    }
+}
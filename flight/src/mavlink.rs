@@ -0,0 +1,466 @@
+//! Just enough MAVLink to fly: v1/v2 framing, the X.25 checksum, and the
+//! handful of common-dialect messages the drone reads and sends
+
+/// `MAV_CMD` values used by the controller
+pub mod command {
+    pub const NAV_RETURN_TO_LAUNCH: u16 = 20;
+    pub const NAV_LAND: u16 = 21;
+    pub const DO_SET_MODE: u16 = 176;
+    pub const DO_SET_ACTUATOR: u16 = 187;
+    pub const DO_REPOSITION: u16 = 192;
+    pub const DO_PAUSE_CONTINUE: u16 = 193;
+}
+
+/// `MAV_FRAME` values
+pub mod frame {
+    /// Latitude/longitude, altitude above home
+    pub const GLOBAL_RELATIVE_ALT: u8 = 3;
+}
+
+/// `MAV_RESULT` values
+pub mod result {
+    pub const ACCEPTED: u8 = 0;
+    pub const TEMPORARILY_REJECTED: u8 = 1;
+    pub const IN_PROGRESS: u8 = 5;
+}
+
+pub(crate) const MAV_AUTOPILOT_ARDUPILOTMEGA: u8 = 3;
+pub(crate) const MAV_AUTOPILOT_INVALID: u8 = 8;
+pub(crate) const MAV_AUTOPILOT_PX4: u8 = 12;
+pub(crate) const MAV_TYPE_ONBOARD_CONTROLLER: u8 = 18;
+pub(crate) const MAV_STATE_ACTIVE: u8 = 4;
+pub(crate) const MAV_MODE_FLAG_SAFETY_ARMED: u8 = 0x80;
+pub(crate) const MAV_MODE_FLAG_CUSTOM_MODE_ENABLED: f32 = 1.0;
+
+const STX_V1: u8 = 0xFE;
+const STX_V2: u8 = 0xFD;
+const V1_OVERHEAD: usize = 8; // STX, len, seq, sysid, compid, msgid, checksum
+const V2_OVERHEAD: usize = 12; // STX, len, flags x2, seq, sysid, compid, msgid x3, checksum
+const V2_SIGNATURE_LEN: usize = 13;
+const INCOMPAT_FLAG_SIGNED: u8 = 0x01;
+
+const HEARTBEAT: u32 = 0;
+const SYS_STATUS: u32 = 1;
+const GPS_RAW_INT: u32 = 24;
+const ATTITUDE: u32 = 30;
+const GLOBAL_POSITION_INT: u32 = 33;
+const COMMAND_INT: u32 = 75;
+const COMMAND_LONG: u32 = 76;
+const COMMAND_ACK: u32 = 77;
+const HIGHRES_IMU: u32 = 105;
+
+/// CRC_EXTRA and the base payload length of each message we speak
+fn message_info(message_id: u32) -> Option<(u8, usize)> {
+    match message_id {
+        HEARTBEAT => Some((50, 9)),
+        SYS_STATUS => Some((124, 31)),
+        GPS_RAW_INT => Some((24, 30)),
+        ATTITUDE => Some((39, 28)),
+        GLOBAL_POSITION_INT => Some((104, 28)),
+        COMMAND_INT => Some((158, 35)),
+        COMMAND_LONG => Some((152, 33)),
+        COMMAND_ACK => Some((143, 3)),
+        HIGHRES_IMU => Some((93, 62)),
+        _ => None,
+    }
+}
+
+/// The messages the controller understands
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Heartbeat {
+        custom_mode: u32,
+        mav_type: u8,
+        autopilot: u8,
+        base_mode: u8,
+        system_status: u8,
+    },
+    SysStatus {
+        voltage_mv: u16,     // u16::MAX when unknown
+        current_ca: i16,     // -1 when unknown
+        battery_remaining: i8, // -1 when unknown
+    },
+    GpsRawInt {
+        fix_type: u8,
+        satellites_visible: u8,
+    },
+    Attitude {
+        roll: f32, // radians
+        pitch: f32,
+        yaw: f32,
+    },
+    GlobalPositionInt {
+        lat: i32, // degrees * 1e7
+        lon: i32,
+        alt_mm: i32, // above mean sea level
+        relative_alt_mm: i32, // above home
+        hdg: u16, // centidegrees, u16::MAX when unknown
+    },
+    /// A command with a precise position, e.g. DO_REPOSITION
+    CommandInt {
+        target_system: u8,
+        target_component: u8,
+        frame: u8,
+        command: u16,
+        params: [f32; 4],
+        x: i32, // latitude, degrees * 1e7
+        y: i32, // longitude, degrees * 1e7
+        z: f32, // altitude, metres in `frame`
+    },
+    CommandLong {
+        target_system: u8,
+        target_component: u8,
+        command: u16,
+        confirmation: u8,
+        params: [f32; 7],
+    },
+    CommandAck {
+        command: u16,
+        result: u8,
+    },
+    HighresImu {
+        acceleration: [f32; 3], // m/s², body frame
+        angular_rate: [f32; 3], // rad/s
+    },
+}
+
+impl Message {
+    fn id(&self) -> u32 {
+        match self {
+            Message::Heartbeat { .. } => HEARTBEAT,
+            Message::SysStatus { .. } => SYS_STATUS,
+            Message::GpsRawInt { .. } => GPS_RAW_INT,
+            Message::Attitude { .. } => ATTITUDE,
+            Message::GlobalPositionInt { .. } => GLOBAL_POSITION_INT,
+            Message::CommandInt { .. } => COMMAND_INT,
+            Message::CommandLong { .. } => COMMAND_LONG,
+            Message::CommandAck { .. } => COMMAND_ACK,
+            Message::HighresImu { .. } => HIGHRES_IMU,
+        }
+    }
+
+    /// Wire payload, fields ordered largest type first as the spec requires
+    fn payload(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            Message::Heartbeat {
+                custom_mode,
+                mav_type,
+                autopilot,
+                base_mode,
+                system_status,
+            } => {
+                out.extend(custom_mode.to_le_bytes());
+                out.extend([*mav_type, *autopilot, *base_mode, *system_status, 3]);
+            },
+            Message::SysStatus {
+                voltage_mv,
+                current_ca,
+                battery_remaining,
+            } => {
+                out.extend([0u8; 12]); // Sensor bitmasks
+                out.extend(0u16.to_le_bytes()); // Load
+                out.extend(voltage_mv.to_le_bytes());
+                out.extend(current_ca.to_le_bytes());
+                out.extend([0u8; 12]); // Drop rate and error counters
+                out.push(*battery_remaining as u8);
+            },
+            Message::GpsRawInt {
+                fix_type,
+                satellites_visible,
+            } => {
+                out.extend([0u8; 28]);
+                out.extend([*fix_type, *satellites_visible]);
+            },
+            Message::Attitude { roll, pitch, yaw } => {
+                out.extend(0u32.to_le_bytes());
+                for value in [*roll, *pitch, *yaw, 0.0, 0.0, 0.0] {
+                    out.extend(value.to_le_bytes());
+                }
+            },
+            Message::GlobalPositionInt {
+                lat,
+                lon,
+                alt_mm,
+                relative_alt_mm,
+                hdg,
+            } => {
+                out.extend(0u32.to_le_bytes());
+                for value in [*lat, *lon, *alt_mm, *relative_alt_mm] {
+                    out.extend(value.to_le_bytes());
+                }
+                out.extend([0u8; 6]); // Velocities
+                out.extend(hdg.to_le_bytes());
+            },
+            Message::CommandInt {
+                target_system,
+                target_component,
+                frame,
+                command,
+                params,
+                x,
+                y,
+                z,
+            } => {
+                for param in params {
+                    out.extend(param.to_le_bytes());
+                }
+                out.extend(x.to_le_bytes());
+                out.extend(y.to_le_bytes());
+                out.extend(z.to_le_bytes());
+                out.extend(command.to_le_bytes());
+                out.extend([*target_system, *target_component, *frame, 0, 0]); // Not current, no autocontinue
+            },
+            Message::CommandLong {
+                target_system,
+                target_component,
+                command,
+                confirmation,
+                params,
+            } => {
+                for param in params {
+                    out.extend(param.to_le_bytes());
+                }
+                out.extend(command.to_le_bytes());
+                out.extend([*target_system, *target_component, *confirmation]);
+            },
+            Message::CommandAck { command, result } => {
+                out.extend(command.to_le_bytes());
+                out.push(*result);
+            },
+            Message::HighresImu { acceleration, angular_rate } => {
+                out.extend(0u64.to_le_bytes());
+                for value in acceleration.iter().chain(angular_rate) {
+                    out.extend(value.to_le_bytes());
+                }
+                out.extend([0u8; 28]); // Magnetometer, pressures and temperature
+                out.extend(0x3Fu16.to_le_bytes()); // Accelerometer and gyro updated
+            },
+        }
+        out
+    }
+
+    fn parse(message_id: u32, payload: &[u8]) -> Option<Message> {
+        let u16_at = |at: usize| u16::from_le_bytes([payload[at], payload[at + 1]]);
+        let i16_at = |at: usize| i16::from_le_bytes([payload[at], payload[at + 1]]);
+        let u32_at = |at: usize| u32::from_le_bytes([payload[at], payload[at + 1], payload[at + 2], payload[at + 3]]);
+        let i32_at = |at: usize| u32_at(at) as i32;
+        let f32_at = |at: usize| f32::from_bits(u32_at(at));
+        let message = match message_id {
+            HEARTBEAT => Message::Heartbeat {
+                custom_mode: u32_at(0),
+                mav_type: payload[4],
+                autopilot: payload[5],
+                base_mode: payload[6],
+                system_status: payload[7],
+            },
+            SYS_STATUS => Message::SysStatus {
+                voltage_mv: u16_at(14),
+                current_ca: i16_at(16),
+                battery_remaining: payload[30] as i8,
+            },
+            GPS_RAW_INT => Message::GpsRawInt {
+                fix_type: payload[28],
+                satellites_visible: payload[29],
+            },
+            ATTITUDE => Message::Attitude {
+                roll: f32_at(4),
+                pitch: f32_at(8),
+                yaw: f32_at(12),
+            },
+            GLOBAL_POSITION_INT => Message::GlobalPositionInt {
+                lat: i32_at(4),
+                lon: i32_at(8),
+                alt_mm: i32_at(12),
+                relative_alt_mm: i32_at(16),
+                hdg: u16_at(26),
+            },
+            COMMAND_INT => {
+                let mut params = [0.0; 4];
+                for (i, param) in params.iter_mut().enumerate() {
+                    *param = f32_at(i * 4);
+                }
+                Message::CommandInt {
+                    params,
+                    x: i32_at(16),
+                    y: i32_at(20),
+                    z: f32_at(24),
+                    command: u16_at(28),
+                    target_system: payload[30],
+                    target_component: payload[31],
+                    frame: payload[32],
+                }
+            },
+            COMMAND_LONG => {
+                let mut params = [0.0; 7];
+                for (i, param) in params.iter_mut().enumerate() {
+                    *param = f32_at(i * 4);
+                }
+                Message::CommandLong {
+                    params,
+                    command: u16_at(28),
+                    target_system: payload[30],
+                    target_component: payload[31],
+                    confirmation: payload[32],
+                }
+            },
+            COMMAND_ACK => Message::CommandAck {
+                command: u16_at(0),
+                result: payload[2],
+            },
+            HIGHRES_IMU => Message::HighresImu {
+                acceleration: [f32_at(8), f32_at(12), f32_at(16)],
+                angular_rate: [f32_at(20), f32_at(24), f32_at(28)],
+            },
+            _ => return None,
+        };
+        Some(message)
+    }
+}
+
+/// A decoded frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub sequence: u8,
+    pub system_id: u8,
+    pub component_id: u8,
+    pub message: Message,
+}
+
+impl Frame {
+    /// MAVLink 2 bytes, trailing zeros trimmed from the payload
+    pub fn encode(&self) -> Vec<u8> {
+        let message_id = self.message.id();
+        let (crc_extra, _) = message_info(message_id).expect("every Message variant has a CRC_EXTRA");
+        let mut payload = self.message.payload();
+        while payload.len() > 1 && payload.last() == Some(&0) {
+            payload.pop();
+        }
+
+        let mut frame = vec![
+            STX_V2,
+            payload.len() as u8,
+            0, // Incompatibility flags: unsigned
+            0,
+            self.sequence,
+            self.system_id,
+            self.component_id,
+        ];
+        frame.extend(&message_id.to_le_bytes()[..3]);
+        frame.extend(&payload);
+        let checksum = crc(&frame[1..], crc_extra);
+        frame.extend(checksum.to_le_bytes());
+        frame
+    }
+}
+
+/// Reassembles frames from a byte stream, resynchronising after garbage
+#[derive(Debug, Default)]
+pub struct Parser {
+    buffer: Vec<u8>,
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed received bytes; returns every complete frame we understand
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<Frame> {
+        self.buffer.extend_from_slice(bytes);
+        let mut frames = Vec::new();
+        loop {
+            // Drop anything before the next start marker
+            match self.buffer.iter().position(|byte| *byte == STX_V1 || *byte == STX_V2) {
+                Some(start) => {
+                    self.buffer.drain(..start);
+                },
+                None => {
+                    self.buffer.clear();
+                    return frames;
+                },
+            }
+            match self.next_frame() {
+                FrameStatus::Incomplete => return frames,
+                FrameStatus::Decoded(frame, len) => {
+                    frames.push(frame);
+                    self.buffer.drain(..len);
+                },
+                FrameStatus::Skipped(len) => {
+                    self.buffer.drain(..len);
+                },
+                // Not a real frame start: look for the next one
+                FrameStatus::Corrupt => {
+                    self.buffer.drain(..1);
+                },
+            }
+        }
+    }
+
+    fn next_frame(&self) -> FrameStatus {
+        let buffer = &self.buffer;
+        if buffer.len() < 2 {
+            return FrameStatus::Incomplete;
+        }
+        let payload_len = buffer[1] as usize;
+        let (header_len, total_len, system_id, component_id, sequence, message_id) = if buffer[0] == STX_V2 {
+            if buffer.len() < 10 {
+                return FrameStatus::Incomplete;
+            }
+            let signed = buffer[2] & INCOMPAT_FLAG_SIGNED != 0;
+            let total = V2_OVERHEAD + payload_len + if signed { V2_SIGNATURE_LEN } else { 0 };
+            let message_id = u32::from_le_bytes([buffer[7], buffer[8], buffer[9], 0]);
+            (10, total, buffer[5], buffer[6], buffer[4], message_id)
+        } else {
+            if buffer.len() < 6 {
+                return FrameStatus::Incomplete;
+            }
+            (6, V1_OVERHEAD + payload_len, buffer[3], buffer[4], buffer[2], buffer[5] as u32)
+        };
+        if buffer.len() < total_len {
+            return FrameStatus::Incomplete;
+        }
+        let Some((crc_extra, base_len)) = message_info(message_id) else {
+            return FrameStatus::Skipped(total_len);
+        };
+        let checksum_at = header_len + payload_len;
+        let expected = u16::from_le_bytes([buffer[checksum_at], buffer[checksum_at + 1]]);
+        if crc(&buffer[1..checksum_at], crc_extra) != expected {
+            return FrameStatus::Corrupt;
+        }
+        // MAVLink 2 trims trailing zeros; extension fields may follow the base
+        let mut payload = buffer[header_len..checksum_at].to_vec();
+        payload.resize(payload.len().max(base_len), 0);
+        match Message::parse(message_id, &payload) {
+            Some(message) => FrameStatus::Decoded(
+                Frame {
+                    sequence,
+                    system_id,
+                    component_id,
+                    message,
+                },
+                total_len,
+            ),
+            None => FrameStatus::Skipped(total_len),
+        }
+    }
+}
+
+enum FrameStatus {
+    Incomplete,
+    Decoded(Frame, usize),
+    Skipped(usize),
+    Corrupt,
+}
+
+/// CRC-16/MCRF4XX (MAVLink's "X.25") over `bytes`, then the message's CRC_EXTRA
+fn crc(bytes: &[u8], crc_extra: u8) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for byte in bytes.iter().chain(std::iter::once(&crc_extra)) {
+        let mut tmp = byte ^ (crc & 0xFF) as u8;
+        tmp ^= tmp << 4;
+        let tmp = tmp as u16;
+        crc = (crc >> 8) ^ (tmp << 8) ^ (tmp << 3) ^ (tmp >> 4);
+    }
+    crc
+}
@@ -0,0 +1,82 @@
+//! Links to the flight controller: UDP (SITL, mavlink-router, telemetry
+//! radios bridged to IP) and TCP
+
+use crate::FlightError;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpStream, UdpSocket};
+
+pub(crate) enum Transport {
+    Udp {
+        socket: UdpSocket,
+        /// Learned from the first packet when listening
+        peer: Mutex<Option<SocketAddr>>,
+    },
+    Tcp {
+        reader: tokio::sync::Mutex<OwnedReadHalf>,
+        writer: tokio::sync::Mutex<OwnedWriteHalf>,
+    },
+}
+
+impl Transport {
+    pub(crate) async fn connect(connection: &str) -> Result<Self, FlightError> {
+        let invalid = || FlightError::InvalidConnection(connection.to_string());
+        let (scheme, address) = connection.split_once(':').ok_or_else(invalid)?;
+        let address: SocketAddr = tokio::net::lookup_host(address)
+            .await
+            .map_err(|_| invalid())?
+            .next()
+            .ok_or_else(invalid)?;
+        match scheme {
+            "udpin" => Ok(Transport::Udp {
+                socket: UdpSocket::bind(address).await?,
+                peer: Mutex::new(None),
+            }),
+            "udpout" => {
+                let local: SocketAddr = if address.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }.parse().map_err(|_| invalid())?;
+                Ok(Transport::Udp {
+                    socket: UdpSocket::bind(local).await?,
+                    peer: Mutex::new(Some(address)),
+                })
+            },
+            "tcpout" => {
+                let (reader, writer) = TcpStream::connect(address).await?.into_split();
+                Ok(Transport::Tcp {
+                    reader: tokio::sync::Mutex::new(reader),
+                    writer: tokio::sync::Mutex::new(writer),
+                })
+            },
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Send one frame; dropped quietly while a listening link has heard nobody
+    pub(crate) async fn send(&self, frame: &[u8]) -> Result<(), FlightError> {
+        match self {
+            Transport::Udp { socket, peer } => {
+                let peer = *peer.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                if let Some(peer) = peer {
+                    socket.send_to(frame, peer).await?;
+                }
+            },
+            Transport::Tcp { writer, .. } => writer.lock().await.write_all(frame).await?,
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn recv(&self, buffer: &mut [u8]) -> Result<usize, FlightError> {
+        match self {
+            Transport::Udp { socket, peer } => {
+                let (len, from) = socket.recv_from(buffer).await?;
+                peer.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).get_or_insert(from);
+                Ok(len)
+            },
+            Transport::Tcp { reader, .. } => match reader.lock().await.read(buffer).await? {
+                0 => Err(FlightError::Closed),
+                len => Ok(len),
+            },
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Protectee distress detection
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// One accelerometer reading from the wearable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MotionSample {
+    /// x, y, z in g, gravity included
+    pub acceleration_g: [f64; 3],
+    pub timestamp: DateTime<Utc>,
+}
+
+impl MotionSample {
+    pub fn magnitude_g(&self) -> f64 {
+        self.acceleration_g.iter().map(|a| a * a).sum::<f64>().sqrt()
+    }
+}
+
+/// Evidence that the protectee is in trouble
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "sign", rename_all = "snake_case")]
+pub enum DistressSign {
+    /// An impact the protectee did not get up from
+    Fall { peak_g: f64 },
+    /// An impact followed by movement
+    Impact { peak_g: f64 },
+    HeartRateSurge { from_bpm: u16, to_bpm: u16 },
+    Keyword { word: String },
+}
+
+impl DistressSign {
+    /// A fall counts twice, enough on its own
+    fn weight(&self) -> u32 {
+        match self {
+            DistressSign::Fall { .. } => 2,
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for DistressSign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DistressSign::Fall { peak_g } => write!(f, "fall ({:.1} g impact, then still)", peak_g),
+            DistressSign::Impact { peak_g } => write!(f, "{:.1} g impact", peak_g),
+            DistressSign::HeartRateSurge { from_bpm, to_bpm } => write!(f, "heart rate {} -> {} bpm", from_bpm, to_bpm),
+            DistressSign::Keyword { word } => write!(f, "heard \"{}\"", word),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DistressConfig {
+    /// Acceleration that counts as an impact
+    pub impact_g: f64,
+    /// How far from 1 g the wearable may read while the protectee lies still
+    pub stillness_tolerance_g: f64,
+    /// How long stillness must last after an impact to count as a fall
+    pub stillness_secs: u64,
+    /// Time after an impact for the stillness to begin
+    pub fall_window_secs: u64,
+    /// Rise in heart rate that counts as a surge
+    pub heart_rate_surge_bpm: u16,
+    /// ... within this long
+    pub heart_rate_window_secs: u64,
+    /// Matched case-insensitively against the audio pipeline's keywords
+    pub keywords: Vec<String>,
+    /// How close together two signs must be to back each other up
+    pub fusion_window_secs: u64,
+}
+
+impl Default for DistressConfig {
+    fn default() -> Self {
+        Self {
+            impact_g: 2.5,
+            stillness_tolerance_g: 0.15,
+            stillness_secs: 5,
+            fall_window_secs: 10,
+            heart_rate_surge_bpm: 30,
+            heart_rate_window_secs: 30,
+            keywords: ["help", "ambulance", "can't breathe", "call 911", "i'm hurt"].map(String::from).to_vec(),
+            fusion_window_secs: 30,
+        }
+    }
+}
+
+/// An impact waiting to see whether the protectee gets up
+#[derive(Debug, Clone)]
+struct PendingImpact {
+    peak_g: f64,
+    at: DateTime<Utc>,
+    still_since: Option<DateTime<Utc>>,
+}
+
+/// Collects distress signs and decides when they add up
+#[derive(Debug, Clone)]
+pub struct DistressDetector {
+    config: DistressConfig,
+    impact: Option<PendingImpact>,
+    heart_rates: VecDeque<(DateTime<Utc>, u16)>,
+    signs: Vec<(DateTime<Utc>, DistressSign)>,
+}
+
+impl DistressDetector {
+    pub fn new(config: DistressConfig) -> Self {
+        Self {
+            config,
+            impact: None,
+            heart_rates: VecDeque::new(),
+            signs: Vec::new(),
+        }
+    }
+
+    pub fn record_motion(&mut self, sample: &MotionSample) {
+        let magnitude = sample.magnitude_g();
+        if magnitude >= self.config.impact_g {
+            // The peak of a fall spans several samples
+            let peak_g = self.impact.as_ref().map_or(magnitude, |impact| impact.peak_g.max(magnitude));
+            self.impact = Some(PendingImpact {
+                peak_g,
+                at: sample.timestamp,
+                still_since: None,
+            });
+            return;
+        }
+        self.settle_impact(sample.timestamp);
+        let Some(impact) = &mut self.impact else {
+            return;
+        };
+        if (magnitude - 1.0).abs() > self.config.stillness_tolerance_g {
+            impact.still_since = None;
+            return;
+        }
+        let still_since = *impact.still_since.get_or_insert(sample.timestamp);
+        if sample.timestamp - still_since >= Duration::seconds(self.config.stillness_secs as i64) {
+            let peak_g = impact.peak_g;
+            self.impact = None;
+            self.signs.push((sample.timestamp, DistressSign::Fall { peak_g }));
+        }
+    }
+
+    pub fn record_heart_rate(&mut self, bpm: u16, timestamp: DateTime<Utc>) {
+        let window = Duration::seconds(self.config.heart_rate_window_secs as i64);
+        while self.heart_rates.front().is_some_and(|(at, _)| timestamp - *at > window) {
+            self.heart_rates.pop_front();
+        }
+        if let Some(from_bpm) = self.heart_rates.iter().map(|(_, bpm)| *bpm).min() {
+            if bpm.saturating_sub(from_bpm) >= self.config.heart_rate_surge_bpm {
+                self.signs.push((timestamp, DistressSign::HeartRateSurge { from_bpm, to_bpm: bpm }));
+                // Start over, so one surge is not reported on every beat
+                self.heart_rates.clear();
+            }
+        }
+        self.heart_rates.push_back((timestamp, bpm));
+    }
+
+    /// Keywords the audio pipeline matched (`AudioFeatures::keyword_matches`)
+    pub fn record_keywords(&mut self, matches: &[String], timestamp: DateTime<Utc>) {
+        for heard in matches {
+            let heard = heard.to_lowercase();
+            if let Some(word) = self.config.keywords.iter().find(|word| heard.contains(&word.to_lowercase())) {
+                self.signs.push((timestamp, DistressSign::Keyword { word: word.clone() }));
+            }
+        }
+    }
+
+    /// The signs that add up to distress, if they do; they are consumed, so
+    /// the same evidence raises the alarm only once
+    pub fn assess(&mut self, now: DateTime<Utc>) -> Option<Vec<DistressSign>> {
+        self.settle_impact(now);
+        let window = Duration::seconds(self.config.fusion_window_secs as i64);
+        self.signs.retain(|(at, _)| now - *at <= window);
+        let weight: u32 = self.signs.iter().map(|(_, sign)| sign.weight()).sum();
+        if weight < 2 {
+            return None;
+        }
+        Some(self.signs.drain(..).map(|(_, sign)| sign).collect())
+    }
+
+    /// An impact nobody lay still after is only an impact
+    fn settle_impact(&mut self, now: DateTime<Utc>) {
+        let window = Duration::seconds(self.config.fall_window_secs as i64);
+        if let Some(impact) = self.impact.as_ref().filter(|impact| now - impact.at > window && impact.still_since.is_none()) {
+            self.signs.push((impact.at, DistressSign::Impact { peak_g: impact.peak_g }));
+            self.impact = None;
+        }
+    }
+}
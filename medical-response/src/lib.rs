@@ -1,6 +1,292 @@
-// Medical Response Module - Emergency Medical Aid
-// TODO: Implement auto-injector, vitals monitoring, emergency stabilization
+//! Emergency medical response for the protectee
 
-pub fn placeholder() {
-    println!("Medical Response Module - Ready for implementation");
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use dark_phoenix_core::{DroneState, EventType, ModuleResult, ThreatLevel};
+use emergency_contact::{EmergencyContact, EmergencyNotification};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex, RwLock};
+use tracing::{error, info, warn};
+
+pub mod distress;
+
+pub use distress::{DistressConfig, DistressDetector, DistressSign, MotionSample};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MedicalResponseConfig {
+    pub distress: DistressConfig,
+    /// Threat level the drone goes to on distress
+    pub escalate_to: ThreatLevel,
+    /// How long the distress may go unacknowledged before emergency contacts are notified
+    pub ack_timeout_secs: u64,
+    /// How often the detector is checked (milliseconds)
+    pub poll_ms: u64,
+}
+
+impl Default for MedicalResponseConfig {
+    fn default() -> Self {
+        Self {
+            distress: DistressConfig::default(),
+            escalate_to: ThreatLevel::Orange,
+            ack_timeout_secs: 60,
+            poll_ms: 500,
+        }
+    }
+}
+
+/// Where the current distress episode stands
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum DistressStatus {
+    Clear,
+    /// Waiting for someone to acknowledge
+    Alerted { since: DateTime<Utc>, signs: Vec<DistressSign> },
+    /// Nobody acknowledged in time; emergency contacts have been notified
+    Escalated { since: DateTime<Utc>, signs: Vec<DistressSign> },
+}
+
+/// Medical equipment carried to the protectee
+#[async_trait]
+pub trait AidKit: Send + Sync {
+    /// Ready the equipment and get it to the protectee, e.g. release a
+    /// first-aid pouch or an AED; returns what was done
+    async fn deploy(&self, signs: &[DistressSign]) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Runs the distress workflow across protection cycles
+pub struct MedicalResponse {
+    config: MedicalResponseConfig,
+    detector: DistressDetector,
+    kit: Box<dyn AidKit>,
+    contact: Option<Arc<EmergencyContact>>,
+    status: DistressStatus,
+    acknowledged_by: Option<String>,
+    last_heart_rate: Option<DateTime<Utc>>,
+}
+
+impl MedicalResponse {
+    pub fn new(config: MedicalResponseConfig) -> Self {
+        Self {
+            detector: DistressDetector::new(config.distress.clone()),
+            config,
+            kit: Box::new(SimulatedAidKit),
+            contact: None,
+            status: DistressStatus::Clear,
+            acknowledged_by: None,
+            last_heart_rate: None,
+        }
+    }
+
+    pub fn with_aid_kit(mut self, kit: Box<dyn AidKit>) -> Self {
+        self.kit = kit;
+        self
+    }
+
+    /// Who to notify when distress goes unacknowledged
+    pub fn with_emergency_contact(mut self, contact: Arc<EmergencyContact>) -> Self {
+        self.contact = Some(contact);
+        self
+    }
+
+    pub fn status(&self) -> &DistressStatus {
+        &self.status
+    }
+
+    pub fn record_motion(&mut self, sample: &MotionSample) {
+        self.detector.record_motion(sample);
+    }
+
+    pub fn record_keywords(&mut self, matches: &[String], timestamp: DateTime<Utc>) {
+        self.detector.record_keywords(matches, timestamp);
+    }
+
+    /// The protectee (or someone with them) says they are all right
+    pub fn acknowledge(&mut self, by: &str) {
+        if self.status != DistressStatus::Clear {
+            self.acknowledged_by = Some(by.to_string());
+        }
+    }
+
+    /// Run one detection cycle against the drone
+    pub async fn check(&mut self, drone: &Arc<RwLock<DroneState>>, now: DateTime<Utc>) {
+        // Heart rate arrives through the drone, from the vitals or beacon feed
+        let heart_rate = drone
+            .read()
+            .await
+            .target_vitals
+            .as_ref()
+            .and_then(|vitals| Some((vitals.heart_rate?, vitals.timestamp)));
+        if let Some((bpm, at)) = heart_rate.filter(|(_, at)| self.last_heart_rate.is_none_or(|last| *at > last)) {
+            self.last_heart_rate = Some(at);
+            self.detector.record_heart_rate(bpm, at);
+        }
+
+        match self.status.clone() {
+            DistressStatus::Clear => {
+                if let Some(signs) = self.detector.assess(now) {
+                    self.raise(drone, signs, now).await;
+                }
+            },
+            DistressStatus::Alerted { since, signs } | DistressStatus::Escalated { since, signs } => {
+                if let Some(acknowledgement) = self.acknowledgement(drone, since).await {
+                    info!("🩺 Protectee distress acknowledged: {}", acknowledgement);
+                    self.status = DistressStatus::Clear;
+                    return;
+                }
+                let escalated = matches!(self.status, DistressStatus::Escalated { .. });
+                if !escalated && now - since >= Duration::seconds(self.config.ack_timeout_secs as i64) {
+                    self.notify(drone, &signs).await;
+                    self.status = DistressStatus::Escalated { since, signs };
+                }
+            },
+        }
+    }
+
+    async fn raise(&mut self, drone: &Arc<RwLock<DroneState>>, signs: Vec<DistressSign>, now: DateTime<Utc>) {
+        let summary = describe(&signs);
+        warn!("🩺 Protectee distress: {}", summary);
+        self.acknowledged_by = None;
+        {
+            let mut drone = drone.write().await;
+            drone.log_event(
+                EventType::ProtecteeDistress,
+                format!("Protectee distress: {}", summary),
+                vec![
+                    "Starting medical-aid workflow".to_string(),
+                    format!("Emergency contacts notified if not acknowledged within {}s", self.config.ack_timeout_secs),
+                ],
+            );
+            drone.escalate_threat(self.config.escalate_to, format!("Protectee distress: {}", summary));
+        }
+        match self.kit.deploy(&signs).await {
+            Ok(actions) => drone.write().await.log_event(
+                EventType::MedicalAidDeployed,
+                format!("Medical aid deployed for protectee distress: {}", summary),
+                actions,
+            ),
+            Err(e) => {
+                error!("🩺 Medical aid failed to deploy: {}", e);
+                drone
+                    .write()
+                    .await
+                    .log_event(EventType::SystemMalfunction, format!("Medical aid failed to deploy: {}", e), Vec::new());
+            },
+        }
+        self.status = DistressStatus::Alerted { since: now, signs };
+    }
+
+    /// How the distress was acknowledged since it was raised, if it was
+    async fn acknowledgement(&mut self, drone: &Arc<RwLock<DroneState>>, since: DateTime<Utc>) -> Option<String> {
+        if let Some(by) = self.acknowledged_by.take() {
+            return Some(format!("by {}", by));
+        }
+        let drone = drone.read().await;
+        drone
+            .mission_log
+            .iter()
+            .rev()
+            .take_while(|event| event.timestamp >= since)
+            .find(|event| event.event_type == EventType::ThreatAcknowledged)
+            .map(|event| event.description.clone())
+    }
+
+    async fn notify(&self, drone: &Arc<RwLock<DroneState>>, signs: &[DistressSign]) {
+        let Some(contact) = &self.contact else {
+            warn!("🩺 Protectee distress unacknowledged, but no emergency contact is configured");
+            return;
+        };
+        let mut notification = EmergencyNotification::from_drone(&*drone.read().await, contact.status_url());
+        notification.summary = format!(
+            "Protectee in distress ({}), unacknowledged for {}s",
+            describe(signs),
+            self.config.ack_timeout_secs
+        );
+        warn!("🩺 {} - notifying emergency contacts", notification.summary);
+        contact.queue(&notification, Arc::clone(drone));
+    }
+
+    /// Check for distress until the drone is gone
+    pub async fn follow(medical: Arc<Mutex<MedicalResponse>>, drone: Arc<RwLock<DroneState>>) -> ModuleResult {
+        let poll_ms = medical.lock().await.config.poll_ms.max(10);
+        let mut poll = dark_phoenix_core::runtime::interval(std::time::Duration::from_millis(poll_ms));
+        let mut transitions = drone.read().await.subscribe_threat_transitions();
+        loop {
+            tokio::select! {
+                received = transitions.recv() => match received {
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                    _ => continue,
+                },
+                _ = poll.tick() => medical.lock().await.check(&drone, Utc::now()).await,
+            }
+        }
+    }
+}
+
+fn describe(signs: &[DistressSign]) -> String {
+    signs.iter().map(DistressSign::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// Aid kit placeholder used until hardware is attached
+struct SimulatedAidKit;
+
+#[async_trait]
+impl AidKit for SimulatedAidKit {
+    async fn deploy(&self, _signs: &[DistressSign]) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        // Placeholder - would release the first-aid pouch
+        info!("🩺 First-aid pouch released");
+        Ok(vec!["First-aid pouch released".to_string()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use emergency_contact::{ChannelError, DeliveryStatus, EmergencyContactConfig, NotificationChannel, Receipt};
+    use tokio::sync::mpsc;
+
+    /// Accepts every notification and hands it to the test
+    struct Recorder(mpsc::UnboundedSender<EmergencyNotification>);
+
+    #[async_trait]
+    impl NotificationChannel for Recorder {
+        fn name(&self) -> String {
+            "recorder".to_string()
+        }
+
+        async fn send(&self, notification: &EmergencyNotification) -> Result<Receipt, ChannelError> {
+            let _ = self.0.send(notification.clone());
+            Ok(Receipt { provider_id: None, status: DeliveryStatus::Delivered })
+        }
+    }
+
+    #[tokio::test]
+    async fn unacknowledged_distress_runs_the_workflow_and_notifies() {
+        let (sent, mut received) = mpsc::unbounded_channel();
+        let contact = EmergencyContact::new(EmergencyContactConfig::default()).with_channel(Arc::new(Recorder(sent)));
+        let mut medical = MedicalResponse::new(MedicalResponseConfig {
+            ack_timeout_secs: 0,
+            ..MedicalResponseConfig::default()
+        })
+        .with_emergency_contact(Arc::new(contact));
+        let drone = Arc::new(RwLock::new(DroneState::new("test".to_string())));
+
+        let now = Utc::now();
+        medical.record_keywords(&["help".to_string(), "call 911".to_string()], now);
+        medical.check(&drone, now).await;
+        assert!(matches!(medical.status(), DistressStatus::Alerted { .. }));
+        {
+            let drone = drone.read().await;
+            assert_eq!(drone.threat_level(), ThreatLevel::Orange);
+            let logged: Vec<_> = drone.mission_log.iter().map(|event| event.event_type.clone()).collect();
+            assert!(logged.contains(&EventType::ProtecteeDistress));
+            assert!(logged.contains(&EventType::MedicalAidDeployed));
+        }
+
+        medical.check(&drone, now).await;
+        assert!(matches!(medical.status(), DistressStatus::Escalated { .. }));
+        let notification = dark_phoenix_core::runtime::timeout(std::time::Duration::from_secs(1), received.recv()).await.unwrap().unwrap();
+        assert!(notification.summary.starts_with("Protectee in distress"));
+    }
 }
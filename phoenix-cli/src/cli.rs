@@ -0,0 +1,734 @@
+use clap::{Parser, Subcommand};
+use chrono::{DateTime, Utc};
+use dark_phoenix_core::client::read_signing_key;
+use dark_phoenix_core::{
+    ApiClient, AuditExport, CommandEnvelope, DeltaDecoder, EventStore, Incident, Keyring, MissionEvent, PairedController, PairingCode, PairingRequest, PairingResponse, ReportFormat, ReportScope,
+    ReportSources, Settings, SystemHealth, TelemetryMessage,
+};
+use ed25519_dalek::SigningKey;
+use std::error::Error;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Dark Phoenix protection drone
+#[derive(Debug, Parser)]
+#[command(name = "phoenix", version)]
+pub struct Cli {
+    /// Control API of the running instance
+    #[arg(long, global = true, default_value = "http://127.0.0.1:8080")]
+    pub api: String,
+    /// Access token for the control API; its role decides which commands are allowed
+    #[arg(long, global = true, env = "PHOENIX_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+    /// Sign commands with this key file instead of relying on the token
+    #[arg(long, global = true, env = "PHOENIX_SIGNING_KEY", requires = "key_id")]
+    pub signing_key: Option<PathBuf>,
+    /// Id the drone knows the signing key by
+    #[arg(long, global = true, env = "PHOENIX_KEY_ID")]
+    pub key_id: Option<String>,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Start the drone and run until a shutdown signal
+    Run {
+        /// TOML, YAML or JSON settings file
+        #[arg(long, short)]
+        config: Option<PathBuf>,
+        /// Show the terminal dashboard (`phoenix-tui` builds); logs go to phoenix.log
+        #[arg(long)]
+        tui: bool,
+        /// Arm even if preflight fails; the reason goes into the mission log
+        #[arg(long, value_name = "REASON")]
+        force_arm: Option<String>,
+        /// Open a pairing code at start-up and print its PIN and QR code
+        #[arg(long)]
+        pair: bool,
+    },
+    /// Threat level, battery and system health of the running drone
+    Status,
+    /// Run a module self-test on the running drone
+    Test {
+        #[command(subcommand)]
+        module: TestTarget,
+    },
+    /// Mission event log
+    Events {
+        #[command(subcommand)]
+        command: EventsCommand,
+    },
+    /// Incidents in the mission log, and reports on them for police or insurers
+    Incidents {
+        #[command(subcommand)]
+        command: IncidentsCommand,
+    },
+    /// Hash-chained audit log, for handing to auditors
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommand,
+    },
+    /// Device keyring and decryption of encrypted stores
+    Vault {
+        #[command(subcommand)]
+        command: VaultCommand,
+    },
+    /// Keys for signing commands
+    Keys {
+        #[command(subcommand)]
+        command: KeysCommand,
+    },
+    /// Settings files
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Delta-encoded telemetry, on the ground station side
+    Telemetry {
+        #[command(subcommand)]
+        command: TelemetryCommand,
+    },
+    /// Pair this ground station with a drone, or manage paired controllers
+    Pairing {
+        #[command(subcommand)]
+        command: PairingCommand,
+    },
+    /// List drones announcing their control API on the local network
+    #[cfg(feature = "mdns")]
+    Discover {
+        /// How long to listen (seconds)
+        #[arg(long, default_value_t = 3)]
+        timeout: u64,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TestTarget {
+    Fire,
+    Deterrence,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EventsCommand {
+    /// Write the newest events as JSON lines, oldest first
+    Export {
+        #[arg(long, default_value_t = 1000)]
+        limit: usize,
+        /// File to write (default: stdout)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Why the threat level changed: the inputs, weights and thresholds
+    /// behind each change among the newest events
+    Explain {
+        /// Only this event
+        event: Option<uuid::Uuid>,
+        /// How many of the newest events to look through
+        #[arg(long, default_value_t = 1000)]
+        limit: usize,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum IncidentsCommand {
+    /// Incidents the drone has logged, oldest first
+    List {
+        /// Read the stores named in this settings file instead of asking
+        /// the running drone, e.g. after it has landed
+        #[arg(long, short)]
+        config: Option<PathBuf>,
+    },
+    /// Write a report on one incident, or on every event in a time range
+    Report {
+        /// Incident id, from `phoenix incidents list`
+        #[arg(conflicts_with_all = ["since", "until"], required_unless_present_any = ["since", "until"])]
+        incident: Option<uuid::Uuid>,
+        /// Start of the range (RFC 3339)
+        #[arg(long)]
+        since: Option<DateTime<Utc>>,
+        /// End of the range (RFC 3339)
+        #[arg(long)]
+        until: Option<DateTime<Utc>>,
+        /// markdown, html or pdf (`pdf-report` builds)
+        #[arg(long, default_value = "markdown")]
+        format: ReportFormat,
+        /// File to write (default: stdout)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Read the stores named in this settings file instead of asking
+        /// the running drone
+        #[arg(long, short)]
+        config: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuditCommand {
+    /// Bundle an audit store's events and signed checkpoints into one JSON file
+    Export {
+        /// The `audit.store_dir` of the drone
+        #[arg(long)]
+        dir: PathBuf,
+        /// The drone's audit key file, to take the public key from
+        #[arg(long, required_unless_present = "public_key")]
+        key: Option<PathBuf>,
+        /// The drone's public key (hex), if the key file is not at hand
+        #[arg(long, conflicts_with = "key")]
+        public_key: Option<String>,
+        /// Device keyring, if the store is encrypted
+        #[arg(long)]
+        keyring: Option<PathBuf>,
+        /// File to write (default: stdout)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Check every hash link and checkpoint signature in an export
+    Verify {
+        path: PathBuf,
+        /// Public key (hex) the drone is known to sign with; without it the
+        /// export's own key is taken on trust
+        #[arg(long)]
+        public_key: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum VaultCommand {
+    /// Add a new key that seals everything from now on; older keys are kept
+    /// so existing data stays readable
+    Rotate {
+        #[arg(long)]
+        keyring: PathBuf,
+    },
+    /// Decrypt a store file, or a whole store or evidence directory, for an
+    /// authorized export
+    Decrypt {
+        path: PathBuf,
+        #[arg(long)]
+        keyring: PathBuf,
+        /// File or directory to write (default: stdout; required for directories)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Seal, in place, the event streams of a store written before
+    /// encryption was enabled, so the drone can read it with the keyring
+    Migrate {
+        dir: PathBuf,
+        #[arg(long)]
+        keyring: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum KeysCommand {
+    /// Create a signing key and print the public key to register on the drone
+    Generate {
+        /// Key file to create
+        output: PathBuf,
+    },
+    /// Sign a command with `--signing-key` and print its envelope, e.g. for
+    /// publishing on the MQTT command topic
+    Sign {
+        /// The command as JSON, e.g. '{"command": "arm"}'
+        command: String,
+    },
+    /// Sign a LoRa command with `--signing-key` and print the frame to
+    /// transmit, in hex
+    #[cfg(feature = "lora")]
+    SignLora {
+        /// ping, rth or disarm
+        command: dark_phoenix_core::LoraCommand,
+        /// The drone it is for (default: every drone in range)
+        #[arg(long)]
+        drone: Option<uuid::Uuid>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Load a settings file and report every problem found
+    Validate { path: PathBuf },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TelemetryCommand {
+    /// Decode recorded frames, each preceded by its length as a 4-byte
+    /// little-endian integer, into the rebuilt state as JSON lines
+    Decode {
+        /// File to read (default: stdin)
+        path: Option<PathBuf>,
+        /// Print only the fields each frame carried, not the whole state
+        #[arg(long)]
+        channels: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PairingCommand {
+    /// Open a pairing code on the running drone and print its PIN and QR code
+    Code,
+    /// Pair with the drone showing a code, creating the signing key if needed
+    Join {
+        /// The PIN, or the `phoenix-pair://` link in the QR code, which
+        /// finds the drone on the local network in `mdns` builds
+        code: String,
+        /// Name to pair as; it becomes the key id
+        #[arg(long)]
+        name: String,
+        /// Signing key file, created if it does not exist
+        #[arg(long)]
+        key: PathBuf,
+    },
+    /// Controllers paired with the running drone
+    List,
+    /// Revoke a paired controller's key
+    Revoke { name: String },
+}
+
+/// Run a command other than `run` against the instance at `api`, as the
+/// holder of `token`, signed with `signing_key` when given
+pub async fn execute(api: &str, token: Option<&str>, signing_key: Option<&Path>, key_id: Option<&str>, command: Command) -> Result<(), Box<dyn Error>> {
+    let signer = match (signing_key, key_id) {
+        (Some(path), Some(key_id)) => Some((key_id.to_string(), read_signing_key(path)?)),
+        _ => None,
+    };
+    let client = ApiClient::new(api, token, signer.clone());
+    match command {
+        Command::Run { .. } => unreachable!("`run` starts the drone itself"),
+        Command::Status => {
+            let status: TelemetryMessage = client.get("/status").await?;
+            let health: SystemHealth = client.get("/health").await?;
+            print_status(&status, &health);
+        },
+        Command::Test { module } => {
+            let path = match module {
+                TestTarget::Fire => "/fire-suppression/test",
+                TestTarget::Deterrence => "/deterrence/test",
+            };
+            client.post(path).await?;
+            println!("✅ {:?} self-test passed", module);
+        },
+        Command::Events {
+            command: EventsCommand::Export { limit, output },
+        } => {
+            let events: Vec<MissionEvent> = client.get(&format!("/events?limit={}", limit)).await?;
+            let mut out: Box<dyn Write> = match &output {
+                Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+                None => Box::new(std::io::stdout().lock()),
+            };
+            for event in &events {
+                writeln!(out, "{}", serde_json::to_string(event)?)?;
+            }
+            out.flush()?;
+            if let Some(path) = output {
+                eprintln!("📝 Exported {} events to {}", events.len(), path.display());
+            }
+        },
+        Command::Events {
+            command: EventsCommand::Explain { event, limit },
+        } => {
+            let events = client.events(limit).await?;
+            let decisions: Vec<&MissionEvent> = events
+                .iter()
+                .filter(|logged| event.map_or(logged.decision.is_some(), |id| logged.id == id))
+                .collect();
+            if decisions.is_empty() {
+                match event {
+                    Some(id) => println!("No event {} among the newest {}", id, limit),
+                    None => println!("No threat level changes among the newest {} events", limit),
+                }
+            }
+            for logged in decisions {
+                println!("{}  {}", logged.timestamp.format("%Y-%m-%d %H:%M:%S"), logged.description);
+                match &logged.decision {
+                    Some(trace) => print!("{}", trace),
+                    None => println!("  (not a threat level change)"),
+                }
+                println!();
+            }
+        },
+        Command::Incidents {
+            command: IncidentsCommand::List { config },
+        } => {
+            let incidents: Vec<Incident> = match config {
+                Some(config) => {
+                    let (_, sources) = report_sources(&config)?;
+                    dark_phoenix_core::incident::incidents(&sources.events(&[])?)
+                },
+                None => client.get("/incidents").await?,
+            };
+            if incidents.is_empty() {
+                println!("No incidents");
+            }
+            for incident in incidents {
+                let ended = incident.ended.map_or("ongoing".to_string(), |ended| ended.format("%H:%M:%S").to_string());
+                println!(
+                    "{}  {} to {:<8} {:<7} {:>4} events  {}",
+                    incident.id,
+                    incident.started.format("%Y-%m-%d %H:%M:%S"),
+                    ended,
+                    incident.peak.as_str(),
+                    incident.events,
+                    incident.description
+                );
+            }
+        },
+        Command::Incidents {
+            command: IncidentsCommand::Report { incident, since, until, format, output, config },
+        } => {
+            let scope = match incident {
+                Some(id) => ReportScope::Incident(id),
+                None => ReportScope::Range { since, until },
+            };
+            let report = match config {
+                Some(config) => {
+                    let (settings, sources) = report_sources(&config)?;
+                    sources.report(&settings.name, scope, &[], Utc::now())?.render(format)?
+                },
+                None => {
+                    let mut query = vec![format!("format={}", format)];
+                    match scope {
+                        ReportScope::Incident(id) => query.push(format!("incident={}", id)),
+                        ReportScope::Range { since, until } => {
+                            query.extend(since.map(|since| format!("since={}", since.format("%Y-%m-%dT%H:%M:%SZ"))));
+                            query.extend(until.map(|until| format!("until={}", until.format("%Y-%m-%dT%H:%M:%SZ"))));
+                        },
+                    }
+                    client.get_bytes(&format!("/incidents/report?{}", query.join("&"))).await?
+                },
+            };
+            match &output {
+                Some(path) => {
+                    std::fs::write(path, &report)?;
+                    eprintln!("📄 Wrote the {} report to {}", format, path.display());
+                },
+                None => std::io::stdout().lock().write_all(&report)?,
+            }
+        },
+        Command::Audit {
+            command: AuditCommand::Export { dir, key, public_key, keyring, output },
+        } => {
+            let public_key = match (key, public_key) {
+                (Some(key), _) => dark_phoenix_core::audit::public_key_of(&key)?,
+                (None, Some(public_key)) => public_key,
+                (None, None) => unreachable!("clap requires one of them"),
+            };
+            let keyring = keyring.map(|path| Keyring::load(&path)).transpose()?.map(std::sync::Arc::new);
+            let export = dark_phoenix_core::audit::export(&EventStore::open(&dir)?.with_keyring(keyring), &public_key)?;
+            let mut out: Box<dyn Write> = match &output {
+                Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+                None => Box::new(std::io::stdout().lock()),
+            };
+            serde_json::to_writer_pretty(&mut out, &export)?;
+            writeln!(out)?;
+            out.flush()?;
+            if let Some(path) = output {
+                eprintln!("🔏 Exported {} events and {} checkpoints to {}", export.events.len(), export.checkpoints.len(), path.display());
+            }
+        },
+        Command::Audit {
+            command: AuditCommand::Verify { path, public_key },
+        } => {
+            let export: AuditExport = serde_json::from_reader(std::io::BufReader::new(std::fs::File::open(&path)?))?;
+            let summary = export.verify(public_key.as_deref())?;
+            match (summary.first_sequence, summary.last_sequence) {
+                (Some(first), Some(last)) => println!("✅ Events {}-{} ({}) link up unbroken", first, last, summary.events),
+                _ => println!("✅ No events"),
+            }
+            match summary.sealed_through {
+                Some(sealed) if summary.last_sequence > Some(sealed) => println!(
+                    "⚠️ {} checkpoints verified, but events after {} are not sealed and could have been cut off",
+                    summary.checkpoints, sealed
+                ),
+                Some(sealed) => println!("🔏 {} checkpoints verified; sealed through event {}", summary.checkpoints, sealed),
+                None => println!("⚠️ No checkpoint covers these events; the tail could have been cut off"),
+            }
+            if public_key.is_none() {
+                println!("⚠️ Signatures checked against the export's own key {}; pass --public-key to pin it", export.public_key);
+            }
+        },
+        Command::Vault {
+            command: VaultCommand::Rotate { keyring: path },
+        } => {
+            let mut keyring = Keyring::load(&path)?;
+            let id = keyring.rotate();
+            keyring.save(&path)?;
+            println!("🔐 Key {} now seals new data; keys {:?} remain for reading", id, keyring.key_ids().collect::<Vec<_>>());
+        },
+        Command::Vault {
+            command: VaultCommand::Decrypt { path, keyring, output },
+        } => {
+            let keyring = Keyring::load(&keyring)?;
+            if path.is_dir() {
+                let output = output.ok_or("decrypting a directory needs --output")?;
+                let count = decrypt_dir(&keyring, &path, &output)?;
+                eprintln!("🔓 Decrypted {} files into {}", count, output.display());
+            } else {
+                let opened = keyring.open_file(&std::fs::read(&path)?)?;
+                match &output {
+                    Some(output) => std::fs::write(output, opened)?,
+                    None => std::io::stdout().lock().write_all(&opened)?,
+                }
+            }
+        },
+        Command::Vault {
+            command: VaultCommand::Migrate { dir, keyring },
+        } => {
+            let keyring = Keyring::load(&keyring)?;
+            let (files, lines) = migrate_dir(&keyring, &dir)?;
+            eprintln!("🔐 Sealed {} plaintext records in {} streams under {}", lines, files, dir.display());
+        },
+        Command::Keys {
+            command: KeysCommand::Generate { output },
+        } => {
+            let key = create_signing_key(&output)?;
+            eprintln!("🔐 Created signing key {}; register its public key on the drone:", output.display());
+            println!("{}", hex::encode(key.verifying_key().to_bytes()));
+        },
+        Command::Keys {
+            command: KeysCommand::Sign { command },
+        } => {
+            let (key_id, key) = signer.ok_or("signing needs --signing-key and --key-id")?;
+            serde_json::from_str::<serde_json::Value>(&command).map_err(|e| format!("command is not JSON: {}", e))?;
+            let envelope = CommandEnvelope::sign(&key_id, command, chrono::Utc::now(), &key);
+            println!("{}", serde_json::to_string(&envelope)?);
+        },
+        #[cfg(feature = "lora")]
+        Command::Keys {
+            command: KeysCommand::SignLora { command, drone },
+        } => {
+            let (key_id, key) = signer.ok_or("signing needs --signing-key and --key-id")?;
+            let frame = dark_phoenix_core::LoraCommandFrame::sign(&key_id, command, drone, chrono::Utc::now(), &key);
+            let bytes = dark_phoenix_core::wire::encode(&frame, dark_phoenix_core::WireFormat::Postcard)?;
+            eprintln!("📡 Signed '{}' in {} bytes; transmit it before the drone's max_age_secs runs out", command.as_str(), bytes.len());
+            println!("{}", hex::encode(bytes));
+        },
+        Command::Config {
+            command: ConfigCommand::Validate { path },
+        } => {
+            Settings::load(&path)?;
+            println!("✅ {} is valid", path.display());
+        },
+        Command::Telemetry {
+            command: TelemetryCommand::Decode { path, channels },
+        } => {
+            let mut input: Box<dyn Read> = match &path {
+                Some(path) => Box::new(std::io::BufReader::new(std::fs::File::open(path)?)),
+                None => Box::new(std::io::stdin().lock()),
+            };
+            let mut out = std::io::stdout().lock();
+            let mut decoder = DeltaDecoder::new();
+            let (mut decoded, mut skipped) = (0, 0);
+            let mut length = [0u8; 4];
+            while read_exact_or_eof(&mut input, &mut length)? {
+                let mut frame = vec![0; u32::from_le_bytes(length) as usize];
+                input.read_exact(&mut frame)?;
+                match decoder.decode(&frame) {
+                    Ok(info) => {
+                        decoded += 1;
+                        let state = if channels { decoder.channel(&info.channel).unwrap_or_default() } else { decoder.state() };
+                        let line = serde_json::json!({"channel": info.channel, "seq": info.seq, "keyframe": info.keyframe, "sent": info.sent, "state": state});
+                        writeln!(out, "{}", line)?;
+                    },
+                    Err(e) => {
+                        skipped += 1;
+                        eprintln!("⚠️ Skipped frame: {}", e);
+                    },
+                }
+            }
+            out.flush()?;
+            eprintln!("📡 Decoded {} frames, skipped {}", decoded, skipped);
+        },
+        Command::Pairing { command: PairingCommand::Code } => {
+            let code: PairingCode = client.post_json("/pairing/code", &serde_json::json!({})).await?;
+            print_pairing_code(&code);
+        },
+        Command::Pairing {
+            command: PairingCommand::Join { code, name, key },
+        } => {
+            let (drone, pin) = match PairingCode::parse_uri(&code) {
+                Some((drone, pin)) => (Some(drone), pin),
+                None => (None, code),
+            };
+            #[allow(unused_mut)] // Only a link found over mDNS moves it
+            let mut api = api.to_string();
+            #[cfg(feature = "mdns")]
+            if let Some(drone) = drone {
+                let drones = dark_phoenix_core::discovery::discover(std::time::Duration::from_secs(3)).await?;
+                match drones.into_iter().find(|found| found.id == Some(drone)).and_then(|found| found.api_url()) {
+                    Some(found) => api = found,
+                    None => eprintln!("⚠️ Drone {} not found on the local network; trying {}", drone, api),
+                }
+            }
+            let signing_key = if key.exists() { read_signing_key(&key)? } else { create_signing_key(&key)? };
+            let request = PairingRequest::new(&name, &signing_key, &pin);
+            let response: PairingResponse = ApiClient::new(&api, None, None).post_json("/pairing", &request).await?;
+            if !response.verify(&request, &pin) || drone.is_some_and(|drone| drone != response.drone) {
+                return Err(format!("{} answered without proving it showed this PIN; do not trust it", api).into());
+            }
+            println!("🔐 Paired with {} ({}) as '{}', role {}", response.drone_name, api, response.key_id, response.role);
+            println!("   Sign commands with --api {} --signing-key {} --key-id {}", api, key.display(), response.key_id);
+        },
+        Command::Pairing { command: PairingCommand::List } => {
+            let controllers: Vec<PairedController> = client.get("/pairing/controllers").await?;
+            if controllers.is_empty() {
+                println!("No paired controllers");
+            }
+            for controller in controllers {
+                println!("{:<24} {:<10} paired {}  {}", controller.name, controller.role.to_string(), controller.paired_at.format("%Y-%m-%d %H:%M UTC"), controller.public_key);
+            }
+        },
+        Command::Pairing {
+            command: PairingCommand::Revoke { name },
+        } => {
+            client.delete(&format!("/pairing/controllers/{}", name)).await?;
+            println!("🔐 Revoked '{}'; its signed commands are refused from now on", name);
+        },
+        #[cfg(feature = "mdns")]
+        Command::Discover { timeout } => {
+            let drones = dark_phoenix_core::discovery::discover(std::time::Duration::from_secs(timeout)).await?;
+            if drones.is_empty() {
+                println!("No drones found");
+            }
+            for drone in drones {
+                let id = drone.id.map(|id| id.to_string()).unwrap_or_default();
+                let mut notes = Vec::new();
+                if drone.signed {
+                    notes.push("signed commands");
+                }
+                if drone.pairing {
+                    notes.push("pairing");
+                }
+                println!("{:<24} {:<36} {:<28} {}", drone.name, id, drone.api_url().unwrap_or_default(), notes.join(", "));
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Write a fresh signing key to a new file only its owner can read
+fn create_signing_key(path: &Path) -> Result<SigningKey, Box<dyn Error>> {
+    let key = SigningKey::from_bytes(&rand::random());
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(hex::encode(key.to_bytes()).as_bytes())?;
+    Ok(key)
+}
+
+/// Print a pairing code's PIN, and its link as a QR code for a phone or
+/// ground station to scan
+pub fn print_pairing_code(code: &PairingCode) {
+    if let Ok(qr) = qrcode::QrCode::new(code.uri()) {
+        println!("{}", qr.render::<qrcode::render::unicode::Dense1x2>().quiet_zone(true).build());
+    }
+    println!("🔐 Pairing PIN {} (valid until {})", code.pin, code.expires.format("%H:%M:%S UTC"));
+    println!("   phoenix pairing join {} --name <ground station> --key <key file>", code.uri());
+}
+
+/// A settings file and the report stores it names, for reading them offline
+fn report_sources(path: &Path) -> Result<(Settings, ReportSources), Box<dyn Error>> {
+    let settings = Settings::load(path)?;
+    if settings.audit.is_none() {
+        return Err(format!("{} has no audit store, so no mission log to report on; ask the running drone instead", path.display()).into());
+    }
+    let keyring = match &settings.encryption {
+        Some(encryption) => Some(std::sync::Arc::new(Keyring::load(&encryption.keyring_path)?)),
+        None => None,
+    };
+    let sources = ReportSources::open(&settings.reports, settings.audit.as_ref(), keyring)?;
+    Ok((settings, sources))
+}
+
+/// Fill `buf`, or return false if the input ends before the first byte
+fn read_exact_or_eof(input: &mut impl Read, buf: &mut [u8]) -> std::io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match input.read(&mut buf[filled..])? {
+            0 if filled == 0 => return Ok(false),
+            0 => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            read => filled += read,
+        }
+    }
+    Ok(true)
+}
+
+/// Decrypt every file under `from` into the same place under `to`
+fn decrypt_dir(keyring: &Keyring, from: &std::path::Path, to: &std::path::Path) -> Result<usize, Box<dyn Error>> {
+    std::fs::create_dir_all(to)?;
+    let mut count = 0;
+    for entry in std::fs::read_dir(from)? {
+        let path = entry?.path();
+        let target = to.join(path.file_name().ok_or("unnamed file")?);
+        if path.is_dir() {
+            count += decrypt_dir(keyring, &path, &target)?;
+        } else {
+            let data = std::fs::read(&path)?;
+            // Manifests and the like are never sealed
+            if dark_phoenix_core::vault::holds_sealed(&data) {
+                std::fs::write(target, keyring.open_file(&data)?)?;
+            } else {
+                std::fs::write(target, data)?;
+            }
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Seal the plaintext lines of every event stream under `dir`; returns the
+/// streams changed and the lines sealed
+fn migrate_dir(keyring: &Keyring, dir: &std::path::Path) -> Result<(usize, usize), Box<dyn Error>> {
+    let (mut files, mut lines) = (0, 0);
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            let (more_files, more_lines) = migrate_dir(keyring, &path)?;
+            files += more_files;
+            lines += more_lines;
+        } else if path.extension().is_some_and(|extension| extension == "jsonl") {
+            let (migrated, sealed) = keyring.migrate_lines(&std::fs::read(&path)?)?;
+            if sealed > 0 {
+                // Replaced whole, so a crash leaves either the old stream or the new one
+                let temp = path.with_extension("jsonl.tmp");
+                std::fs::write(&temp, migrated)?;
+                std::fs::rename(&temp, &path)?;
+                files += 1;
+                lines += sealed;
+            }
+        }
+    }
+    Ok((files, lines))
+}
+
+fn print_status(status: &TelemetryMessage, health: &SystemHealth) {
+    if let TelemetryMessage::Status { name, threat_level, position, .. } = status {
+        println!("🔥 {} - threat level {}", name, threat_level.as_str());
+        println!("   {}", threat_level.description());
+        println!("📍 {:.6}, {:.6} at {:.1} m", position.latitude, position.longitude, position.altitude);
+    }
+    println!(
+        "🔋 Battery {}% | Flight time {} min | Shield {}% | Medical supplies {}%",
+        health.battery_level,
+        health.flight_time_remaining / 60,
+        health.shield_integrity,
+        health.medical_supplies
+    );
+    let flag = |ok: bool| if ok { "✅" } else { "❌" };
+    println!(
+        "   GPS lock {} | Comms {} | Fire suppression ready {}",
+        flag(health.gps_lock),
+        flag(health.communication_status),
+        flag(health.fire_suppression_ready)
+    );
+    if !health.degraded_sensors.is_empty() {
+        println!("📡 Degraded sensors: {}", health.degraded_sensors.join(", "));
+    }
+    let mut modules: Vec<_> = health.modules.iter().collect();
+    modules.sort_by(|a, b| a.0.cmp(b.0));
+    for (module, state) in modules {
+        println!("⚠️ Module '{}' {:?}", module, state);
+    }
+}
@@ -0,0 +1,1063 @@
+use clap::Parser;
+use dark_phoenix_core::{
+    metrics, AuditLog, AuthConfig, BatteryHealth, CommandVerifier, DroneState, EventType, Failsafe, FailsafeConfig, FlightCommand, Heartbeat, HeartbeatStatus, Keyring, LinkManager, LinkMonitor, Metrics, ModuleHealth, ModuleReport, ModuleRestarter, ModuleResult,
+    Pairing, PanicButton, PatrolPlanner, PowerManager, PreflightCheck, PreflightChecklist, Protectee, ReportSources, RestartPolicy, ShutdownCoordinator, ShutdownHandle, ShutdownPhase, ShutdownReport, StepOutcome, Supervisor,
+    Settings, ThreatLevel, Watchdog, WatchdogAction, Webhooks, WebhooksConfig,
+};
+use std::future::Future;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use std::path::PathBuf;
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn, error};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+mod cli;
+mod modules;
+
+/// Main orchestration engine for the Dark Phoenix drone
+///
+/// Every module loop, the core's own protection loop included, runs under
+/// the supervisor so a crash restarts that module instead of the drone.
+pub struct DarkPhoenixCore {
+    state: Arc<RwLock<DroneState>>,
+    supervisor: Supervisor,
+    shutdown: ShutdownCoordinator,
+    watchdog: Watchdog,
+    metrics: Metrics,
+    failsafe: FailsafeConfig,
+    patrol: Arc<std::sync::Mutex<PatrolPlanner>>,
+    link: Arc<std::sync::Mutex<LinkMonitor>>,
+    /// Picks the network link telemetry and emergency calls go over
+    links: Arc<std::sync::Mutex<LinkManager>>,
+    protectee: Arc<std::sync::Mutex<Protectee>>,
+    panic: Arc<std::sync::Mutex<PanicButton>>,
+    power: Arc<std::sync::Mutex<PowerManager>>,
+    battery: Arc<std::sync::Mutex<BatteryHealth>>,
+    preflight: PreflightChecklist,
+    auth: Arc<AuthConfig>,
+    /// Checks signed commands on every link, sharing one nonce cache
+    commands: Arc<CommandVerifier>,
+    /// Ground stations paired with their own keys, when pairing is enabled
+    pairing: Option<Arc<std::sync::Mutex<Pairing>>>,
+    /// Where mission events are posted as they happen
+    webhooks: WebhooksConfig,
+    /// Persists the hash-chained mission log, when configured
+    audit: Option<Arc<std::sync::Mutex<AuditLog>>>,
+    /// What incident reports are built from
+    reports: Arc<ReportSources>,
+    /// Device keys the stores are encrypted with, when configured
+    keyring: Option<Arc<Keyring>>,
+    /// Operator's reason to arm despite a failed preflight
+    force_arm: Option<String>,
+    /// Response modules started under supervision on ignition
+    modules: Option<modules::Modules>,
+    /// Forwards mission events, audit checkpoints and module records
+    #[cfg(feature = "siem")]
+    siem: Option<dark_phoenix_core::SiemForwarder>,
+    /// Actuator intents published to PX4 SITL, for modules attached after connecting
+    #[cfg(feature = "sitl")]
+    sitl: Option<dark_phoenix_core::SitlActuators>,
+    /// Lands the drone on emergency landing
+    #[cfg(feature = "mavlink")]
+    flight: Option<Arc<dyn dark_phoenix_core::FlightControl>>,
+    /// Operator-supplied response logic, run every protection cycle
+    #[cfg(feature = "wasm-plugins")]
+    plugins: Option<Arc<std::sync::Mutex<dark_phoenix_core::PluginHost>>>,
+}
+
+impl DarkPhoenixCore {
+    pub fn new(drone_name: String) -> Self {
+        Self::with_state(DroneState::new(drone_name))
+    }
+
+    /// `keyring` encrypts every store the core opens (absent = plaintext)
+    pub fn from_settings(settings: &Settings, keyring: Option<Arc<Keyring>>) -> Self {
+        let audit = settings.audit.clone().and_then(|config| {
+            AuditLog::open(config, keyring.clone())
+                .inspect_err(|e| error!("🔏 Audit log unavailable, mission events will not be persisted: {}", e))
+                .ok()
+        });
+        // New events continue the chain the last run left on disk
+        let audit_head = audit.as_ref().map(AuditLog::head).unwrap_or_default();
+        let mut core = Self::with_state(
+            DroneState::new(settings.name.clone())
+                .with_transition_rules(settings.threat_rules.clone())
+                .with_geofence(settings.geofence.clone())
+                .with_audit_head(audit_head),
+        );
+        core.audit = audit.map(|audit| Arc::new(std::sync::Mutex::new(audit)));
+        core.failsafe = settings.failsafe.clone();
+        core.patrol = Arc::new(std::sync::Mutex::new(
+            PatrolPlanner::new(settings.patrol.clone()).with_geofence(settings.geofence.clone()),
+        ));
+        core.link = Arc::new(std::sync::Mutex::new(LinkMonitor::new(settings.link.clone())));
+        core.links = Arc::new(std::sync::Mutex::new(LinkManager::new(settings.links.clone())));
+        core.protectee = Arc::new(std::sync::Mutex::new(Protectee::new(settings.protectee.clone())));
+        core.panic = Arc::new(std::sync::Mutex::new(PanicButton::new(settings.panic.clone())));
+        core.power = Arc::new(std::sync::Mutex::new(PowerManager::new(settings.power.clone())));
+        core.battery = Arc::new(std::sync::Mutex::new(BatteryHealth::from_config(settings.battery.clone(), keyring.clone()).unwrap_or_else(|e| {
+            error!("🔋 Battery history unavailable, starting without it: {}", e);
+            BatteryHealth::new(settings.battery.clone())
+        })));
+        core.auth = Arc::new(settings.auth.clone());
+        core.commands = Arc::new(CommandVerifier::new(settings.signed_commands.clone()));
+        let pairing = settings.pairing.clone().and_then(|config| {
+            Pairing::open(config, keyring.clone())
+                .inspect_err(|e| error!("🔐 Pairing unavailable, only configured command keys will be accepted: {}", e))
+                .ok()
+        });
+        // Paired controllers sign commands like any configured key
+        for key in pairing.iter().flat_map(Pairing::keys) {
+            if !core.commands.add_key(&key) {
+                warn!("🔐 Paired controller '{}' clashes with a configured command key and is ignored", key.id);
+            }
+        }
+        core.pairing = pairing.map(|pairing| Arc::new(std::sync::Mutex::new(pairing)));
+        core.webhooks = settings.webhooks.clone();
+        core.reports = Arc::new(ReportSources::open(&settings.reports, settings.audit.as_ref(), keyring.clone()).unwrap_or_else(|e| {
+            error!("📄 Report stores unavailable, incident reports will only cover the live mission log: {}", e);
+            ReportSources::default()
+        }));
+        core.preflight = PreflightChecklist::new(settings.preflight.clone()).with_standard_checks(core.battery());
+        core.keyring = keyring;
+        #[cfg(feature = "wasm-plugins")]
+        {
+            core.plugins = settings.plugins.clone().and_then(|config| {
+                dark_phoenix_core::PluginHost::open(config)
+                    .inspect_err(|e| error!("🧩 Plugins unavailable, responding without them: {}", e))
+                    .ok()
+                    .map(|plugins| Arc::new(std::sync::Mutex::new(plugins)))
+            });
+        }
+        core
+    }
+
+    fn with_state(state: DroneState) -> Self {
+        let state = Arc::new(RwLock::new(state));
+        let battery = Arc::new(std::sync::Mutex::new(BatteryHealth::new(Default::default())));
+
+        Self {
+            supervisor: Supervisor::new(Arc::clone(&state)),
+            shutdown: ShutdownCoordinator::new(),
+            watchdog: Watchdog::new(),
+            metrics: Metrics::new(),
+            failsafe: FailsafeConfig::default(),
+            patrol: Arc::new(std::sync::Mutex::new(PatrolPlanner::new(Default::default()))),
+            link: Arc::new(std::sync::Mutex::new(LinkMonitor::new(Default::default()))),
+            links: Arc::new(std::sync::Mutex::new(LinkManager::new(Default::default()))),
+            protectee: Arc::new(std::sync::Mutex::new(Protectee::new(Default::default()))),
+            panic: Arc::new(std::sync::Mutex::new(PanicButton::new(Default::default()))),
+            power: Arc::new(std::sync::Mutex::new(PowerManager::new(Default::default()))),
+            preflight: PreflightChecklist::new(Default::default()).with_standard_checks(Arc::clone(&battery)),
+            battery,
+            force_arm: None,
+            modules: None,
+            #[cfg(feature = "siem")]
+            siem: None,
+            #[cfg(feature = "sitl")]
+            sitl: None,
+            auth: Arc::new(AuthConfig::default()),
+            commands: Arc::new(CommandVerifier::default()),
+            pairing: None,
+            webhooks: WebhooksConfig::default(),
+            audit: None,
+            reports: Arc::new(ReportSources::default()),
+            keyring: None,
+            #[cfg(feature = "mavlink")]
+            flight: None,
+            #[cfg(feature = "wasm-plugins")]
+            plugins: None,
+            state,
+        }
+    }
+
+    /// Shared drone state, for module loops that report into it
+    pub fn state(&self) -> Arc<RwLock<DroneState>> {
+        Arc::clone(&self.state)
+    }
+
+    /// Patrol routes flown from the protection loop, for adding routes and
+    /// starting or stopping patrols
+    pub fn patrol(&self) -> Arc<std::sync::Mutex<PatrolPlanner>> {
+        Arc::clone(&self.patrol)
+    }
+
+    /// The command link monitor, for deterrence to check whether it must
+    /// keep the siren silent while nobody can call it off
+    pub fn link(&self) -> Arc<std::sync::Mutex<LinkMonitor>> {
+        Arc::clone(&self.link)
+    }
+
+    /// The link manager, for senders to ask which network link to use
+    pub fn links(&self) -> Arc<std::sync::Mutex<LinkManager>> {
+        Arc::clone(&self.links)
+    }
+
+    /// The protectee tracker, for the BLE scanner to feed beacon readings into
+    pub fn protectee(&self) -> Arc<std::sync::Mutex<Protectee>> {
+        Arc::clone(&self.protectee)
+    }
+
+    /// The panic button, for commands arriving other than through the API,
+    /// e.g. from the protectee's wearable
+    pub fn panic_button(&self) -> Arc<std::sync::Mutex<PanicButton>> {
+        Arc::clone(&self.panic)
+    }
+
+    /// Paired controllers, when pairing is enabled, for opening a pairing
+    /// code from the drone itself
+    pub fn pairing(&self) -> Option<Arc<std::sync::Mutex<Pairing>>> {
+        self.pairing.clone()
+    }
+
+    /// The power budget, for modules to report their measured draw into and
+    /// check whether their load has been shed
+    pub fn power(&self) -> Arc<std::sync::Mutex<PowerManager>> {
+        Arc::clone(&self.power)
+    }
+
+    /// Battery state of health, for the BMS to report temperatures into and
+    /// preflight checks to grade the pack
+    pub fn battery(&self) -> Arc<std::sync::Mutex<BatteryHealth>> {
+        Arc::clone(&self.battery)
+    }
+
+    /// The device keyring, for modules to encrypt their own stores and
+    /// evidence with; absent when storage is plaintext
+    pub fn keyring(&self) -> Option<Arc<Keyring>> {
+        self.keyring.clone()
+    }
+
+    /// The WASM plugin host, for threat detection to show its assessments
+    /// to the plugins; absent when none are configured
+    #[cfg(feature = "wasm-plugins")]
+    pub fn plugins(&self) -> Option<Arc<std::sync::Mutex<dark_phoenix_core::PluginHost>>> {
+        self.plugins.clone()
+    }
+
+    /// Add a module's check to the preflight checklist, after those already on it
+    pub fn add_preflight_check(&mut self, check: Box<dyn PreflightCheck>) {
+        self.preflight.add_check(check);
+    }
+
+    /// Arm on ignition even if preflight fails, recording `reason`
+    pub fn force_arm(&mut self, reason: &str) {
+        self.force_arm = Some(reason.to_string());
+    }
+
+    /// The registry every module reports into, e.g. via `with_metrics`
+    pub fn metrics(&self) -> Metrics {
+        self.metrics.clone()
+    }
+
+    /// Serve Prometheus metrics on their own port until shutdown starts
+    pub fn serve_metrics(&self, bind: std::net::SocketAddr) -> tokio::task::JoinHandle<std::io::Result<()>> {
+        let shutdown = self.shutdown_handle();
+        tokio::spawn(metrics::serve_exporter(bind, self.metrics(), async move { shutdown.wait().await }))
+    }
+
+    /// Run a module loop (fire suppression, deterrence, threat detection)
+    /// under supervision, restarting it per `policy` when it fails
+    pub fn supervise<F, Fut>(&mut self, name: &str, policy: RestartPolicy, start: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ModuleResult> + Send + 'static,
+    {
+        self.supervisor.supervise(name, policy, start);
+    }
+
+    /// Register a step that brings a module to a safe state on shutdown,
+    /// e.g. closing the extinguisher valve or silencing the siren
+    pub fn on_shutdown<F, Fut>(&mut self, name: &str, phase: ShutdownPhase, timeout: Duration, step: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ModuleResult> + Send + 'static,
+    {
+        self.shutdown.add_step(name, phase, timeout, step);
+    }
+
+    /// Trigger the shutdown sequence from a command handler
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.handle()
+    }
+
+    /// Watch a module loop, which must pet the returned heartbeat every cycle;
+    /// `action` is taken if it goes quiet for longer than `timeout`
+    pub fn watch(&self, name: &str, timeout: Duration, action: WatchdogAction) -> Heartbeat {
+        self.watchdog.register(name, timeout, action)
+    }
+
+    pub fn heartbeats(&self) -> Vec<HeartbeatStatus> {
+        self.watchdog.status()
+    }
+
+    /// Serve the HTTP + WebSocket control API, with `/metrics`, until shutdown starts
+    #[cfg(feature = "api-server")]
+    pub fn serve_api(
+        &self,
+        config: dark_phoenix_core::ApiConfig,
+        control: Option<Arc<dyn dark_phoenix_core::ModuleControl>>,
+    ) -> tokio::task::JoinHandle<std::io::Result<()>> {
+        let shutdown = self.shutdown_handle();
+        tokio::spawn(dark_phoenix_core::api::serve(config, self.state(), control, Arc::clone(&self.auth), Arc::clone(&self.commands), self.pairing(), Arc::clone(&self.reports), Some(self.panic_button()), Some(self.metrics()), async move {
+            shutdown.wait().await
+        }))
+    }
+
+    /// Announce the control API over mDNS, withdrawing it when shutdown starts
+    #[cfg(all(feature = "api-server", feature = "mdns"))]
+    pub async fn advertise_api(&mut self, config: &dark_phoenix_core::DiscoveryConfig, api: &dark_phoenix_core::ApiConfig, signed: bool) -> Result<(), dark_phoenix_core::DiscoveryError> {
+        let advertisement = dark_phoenix_core::discovery::advertise(config, api.bind, &*self.state.read().await, signed, self.pairing.is_some())?;
+        self.on_shutdown("mdns", ShutdownPhase::Final, Duration::from_secs(1), move || async move {
+            advertisement.stop();
+            Ok(())
+        });
+        Ok(())
+    }
+
+    /// Serve the fleet controller gRPC service until shutdown starts
+    #[cfg(feature = "grpc")]
+    pub fn serve_grpc(
+        &self,
+        config: phoenix_grpc::GrpcConfig,
+        control: Option<Arc<dyn dark_phoenix_core::ModuleControl>>,
+    ) -> tokio::task::JoinHandle<Result<(), tonic::transport::Error>> {
+        let shutdown = self.shutdown_handle();
+        let outbox = config.outbox.clone().and_then(|outbox| match dark_phoenix_core::Outbox::open(outbox, self.keyring()) {
+            Ok(outbox) => Some(outbox),
+            Err(e) => {
+                error!("🛰️ gRPC outbox unavailable, telemetry will not be queued: {}", e);
+                None
+            },
+        });
+        tokio::spawn(phoenix_grpc::serve(config, self.state(), control, Arc::clone(&self.auth), Arc::clone(&self.commands), outbox, async move {
+            shutdown.wait().await
+        }))
+    }
+
+    /// Start publishing to an MQTT broker; returns the publisher, for
+    /// forwarding threat assessments, and the commands received
+    #[cfg(feature = "mqtt")]
+    pub fn connect_mqtt(
+        &self,
+        config: dark_phoenix_core::MqttConfig,
+    ) -> Result<
+        (
+            dark_phoenix_core::MqttPublisher,
+            tokio::sync::mpsc::Receiver<(dark_phoenix_core::AuthContext, dark_phoenix_core::MqttCommand)>,
+        ),
+        dark_phoenix_core::MqttError,
+    > {
+        let (publisher, mut connection) = dark_phoenix_core::mqtt::connect(config, self.keyring())?;
+        let routes = {
+            let links = self.links.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            (!links.config().transports.is_empty()).then(|| links.subscribe())
+        };
+        if let Some(routes) = routes {
+            connection = connection.with_links(routes);
+        }
+        let (commands, received) = tokio::sync::mpsc::channel(16);
+        tokio::spawn(connection.run(self.state(), Arc::clone(&self.auth), Arc::clone(&self.commands), commands));
+        Ok((publisher, received))
+    }
+
+    /// Forward mission events and audit checkpoints to a SIEM collector
+    /// under supervision, and the records of modules attached after this;
+    /// returns the forwarder, for anything else worth forwarding
+    #[cfg(feature = "siem")]
+    pub async fn connect_siem(&mut self, config: dark_phoenix_core::SiemConfig) -> Result<dark_phoenix_core::SiemForwarder, dark_phoenix_core::SiemError> {
+        let name = self.state.read().await.name.clone();
+        let (forwarder, connection) = dark_phoenix_core::siem::connect(config, &name)?;
+        tokio::spawn(connection.run());
+        let (follower, state) = (forwarder.clone(), self.state());
+        self.supervise("siem", RestartPolicy::default(), move || dark_phoenix_core::siem::follow(follower.clone(), Arc::clone(&state)));
+        if let Some(audit) = &self.audit {
+            audit.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).set_siem(forwarder.clone());
+        }
+        self.siem = Some(forwarder.clone());
+        // Give the collector a moment to take what is still queued
+        let queue = forwarder.clone();
+        self.on_shutdown("siem", ShutdownPhase::Flush, Duration::from_secs(2), move || async move {
+            while queue.queued() > 0 {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            Ok(())
+        });
+        Ok(forwarder)
+    }
+
+    /// Beacon the drone's status and take commands over a LoRa modem under
+    /// supervision, reopening the serial port when it fails; returns the
+    /// commands received, to be answered, other than ping, which is
+    /// answered on the spot
+    #[cfg(feature = "lora")]
+    pub fn connect_lora(&mut self, config: dark_phoenix_core::LoraConfig) -> tokio::sync::mpsc::Receiver<dark_phoenix_core::LoraRequest> {
+        let (commands, received) = tokio::sync::mpsc::channel(16);
+        let (state, auth, verifier) = (self.state(), Arc::clone(&self.auth), Arc::clone(&self.commands));
+        self.supervise("lora", RestartPolicy::default(), move || {
+            let (config, state, auth, verifier, commands) = (config.clone(), Arc::clone(&state), Arc::clone(&auth), Arc::clone(&verifier), commands.clone());
+            async move {
+                let modem = dark_phoenix_core::lora::open(&config).await?;
+                dark_phoenix_core::lora::run(config, modem, state, auth, verifier, commands).await
+            }
+        });
+        received
+    }
+
+    /// Show the terminal dashboard until shutdown starts; closing it shuts
+    /// the drone down
+    #[cfg(feature = "phoenix-tui")]
+    pub fn show_dashboard(
+        &self,
+        control: Option<Arc<dyn dark_phoenix_core::ModuleControl>>,
+    ) -> tokio::task::JoinHandle<std::io::Result<()>> {
+        let shutdown = self.shutdown_handle();
+        let state = self.state();
+        let operator = self.auth.console();
+        tokio::spawn(async move {
+            let waiting = shutdown.clone();
+            let result = dark_phoenix_core::tui::run(state, control, operator, async move { waiting.wait().await }).await;
+            shutdown.request("dashboard closed");
+            result
+        })
+    }
+
+    /// Land through `flight` on emergency landing
+    #[cfg(feature = "mavlink")]
+    pub fn set_flight_control(&mut self, flight: Arc<dyn dark_phoenix_core::FlightControl>) {
+        self.flight = Some(flight);
+    }
+
+    /// Connect to the flight controller, report its status into the drone
+    /// under supervision, and land through it on emergency landing
+    #[cfg(feature = "mavlink")]
+    pub async fn connect_flight(
+        &mut self,
+        config: dark_phoenix_core::FlightConfig,
+    ) -> Result<Arc<dark_phoenix_core::FlightController>, dark_phoenix_core::FlightError> {
+        let controller = Arc::new(dark_phoenix_core::FlightController::connect(config).await?);
+        let state = self.state();
+        let follower = Arc::clone(&controller);
+        self.supervise("flight", RestartPolicy::default(), move || {
+            dark_phoenix_core::autopilot::follow(Arc::clone(&follower), Arc::clone(&state))
+        });
+        self.set_flight_control(controller.clone());
+        Ok(controller)
+    }
+
+    /// Fly PX4 SITL in Gazebo: connect to the simulated autopilot as to a
+    /// real one and publish the actuator intents recorded in the returned
+    /// handle back to it, including those of modules attached after this
+    #[cfg(feature = "sitl")]
+    pub async fn connect_sitl(
+        &mut self,
+        config: dark_phoenix_core::SitlConfig,
+    ) -> Result<dark_phoenix_core::SitlActuators, dark_phoenix_core::FlightError> {
+        let controller = self.connect_flight(config.flight_config()).await?;
+        let actuators = dark_phoenix_core::SitlActuators::new();
+        let publishing = actuators.clone();
+        self.supervise("sitl", RestartPolicy::default(), move || {
+            dark_phoenix_core::sitl::publish(Arc::clone(&controller), publishing.clone(), config.clone())
+        });
+        self.sitl = Some(actuators.clone());
+        Ok(actuators)
+    }
+
+    /// Stream the protectee's vitals from their wearable under supervision,
+    /// reconnecting when it drops out
+    #[cfg(feature = "ble")]
+    pub fn follow_vitals(&mut self, config: dark_phoenix_core::VitalsConfig, wearable: Arc<dyn dark_phoenix_core::GattClient>) {
+        let state = self.state();
+        self.supervise("vitals", RestartPolicy::default(), move || {
+            let monitor = dark_phoenix_core::VitalsMonitor::new(config.clone());
+            dark_phoenix_core::vitals::follow(monitor, Arc::clone(&wearable), Arc::clone(&state))
+        });
+    }
+
+    /// Crash counters and health of every supervised module
+    pub fn module_reports(&self) -> Vec<ModuleReport> {
+        self.supervisor.reports()
+    }
+
+    /// Start the main protection loop and run until a shutdown signal or
+    /// command, then land safely
+    pub async fn ignite(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        match self.preflight.arm(&self.state, self.force_arm.as_deref()).await {
+            Ok(report) => info!("🛫 Preflight:\n{}", report),
+            Err(e) => {
+                let dark_phoenix_core::ArmError::PreflightFailed(report) = &e;
+                error!("🛫 Preflight:\n{}", report);
+                return Err(e.into());
+            },
+        }
+        info!("🔥 Dark Phoenix igniting... 🔥");
+        
+        // Log the ceremonial awakening
+        {
+            let mut state = self.state.write().await;
+            state.log_event(
+                EventType::PhoenixRising,
+                "Dark Phoenix has awakened. Guardian protocols active.".to_string(),
+                vec!["All systems online".to_string(), "Protection mode engaged".to_string()],
+            );
+        }
+        self.supervise_modules();
+
+        // Battery health follows the flight controller's readings; the open
+        // discharge session is saved on the way down
+        let battery = self.battery();
+        let state = Arc::clone(&self.state);
+        self.supervise("battery", RestartPolicy::default(), move || {
+            dark_phoenix_core::battery::follow(Arc::clone(&battery), Arc::clone(&state))
+        });
+        let battery = self.battery();
+        self.on_shutdown("battery history", ShutdownPhase::Flush, Duration::from_secs(2), move || async move {
+            let mut battery = battery.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            battery.end_session(chrono::Utc::now());
+            battery.flush()?;
+            Ok(())
+        });
+
+        // Mission events go to disk as they are logged; the last of them are
+        // sealed with a checkpoint on the way down
+        if let Some(audit) = self.audit.clone() {
+            let state = Arc::clone(&self.state);
+            let follower = Arc::clone(&audit);
+            self.supervise("audit", RestartPolicy::default(), move || {
+                dark_phoenix_core::audit::follow(Arc::clone(&follower), Arc::clone(&state))
+            });
+            let state = Arc::clone(&self.state);
+            self.on_shutdown("audit log", ShutdownPhase::Flush, Duration::from_secs(2), move || async move {
+                let state = state.read().await;
+                let mut audit = audit.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                let now = chrono::Utc::now();
+                audit.sync(&state, now)?;
+                audit.checkpoint(now)?;
+                Ok(())
+            });
+        }
+
+        // Network links are probed and telemetry moved to the best of them
+        let links_configured = !self.links.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).config().transports.is_empty();
+        if links_configured {
+            let links = self.links();
+            let state = Arc::clone(&self.state);
+            self.supervise("links", RestartPolicy::default(), move || {
+                dark_phoenix_core::link_manager::run(Arc::clone(&links), Arc::clone(&state))
+            });
+        }
+
+        // Mission events are posted to webhooks; what cannot be delivered
+        // waits in the outbox for the next delivery that gets through
+        if !self.webhooks.hooks.is_empty() {
+            let webhooks = Arc::new(std::sync::Mutex::new(Webhooks::new(&self.webhooks)));
+            let outbox = self.webhooks.outbox.clone().and_then(|outbox| match dark_phoenix_core::Outbox::open(outbox, self.keyring()) {
+                Ok(outbox) => Some(outbox),
+                Err(e) => {
+                    error!("📣 Webhook outbox unavailable, undeliverable notifications will be dropped: {}", e);
+                    None
+                },
+            });
+            let state = Arc::clone(&self.state);
+            self.supervise("webhooks", RestartPolicy::default(), move || {
+                dark_phoenix_core::webhook::run(Arc::clone(&webhooks), outbox.clone(), Arc::clone(&state))
+            });
+        }
+
+        // Main protection loop
+        let state = Arc::clone(&self.state);
+        let heartbeat = self.watch("protection", Duration::from_secs(1), WatchdogAction::RestartModule);
+        let cycle_time = self.metrics.histogram(
+            "phoenix_protection_cycle_seconds",
+            "Time taken by one protection loop cycle",
+            &[],
+            metrics::LATENCY_BUCKETS,
+        );
+        let battery = self.metrics.gauge("phoenix_battery_level_percent", "Remaining battery charge", &[]);
+        let failsafe = self.failsafe.clone();
+        let patrol = self.patrol();
+        let link = self.link();
+        let protectee = self.protectee();
+        let power = self.power();
+        #[cfg(feature = "mavlink")]
+        let flight = self.flight.clone();
+        #[cfg(feature = "wasm-plugins")]
+        let plugins = self.plugins.clone();
+        self.supervisor.supervise("protection", RestartPolicy::default(), move || {
+            let state = Arc::clone(&state);
+            let heartbeat = heartbeat.clone();
+            let cycle_time = cycle_time.clone();
+            let battery = battery.clone();
+            let mut failsafe = Failsafe::new(failsafe.clone());
+            let patrol = Arc::clone(&patrol);
+            let link = Arc::clone(&link);
+            let protectee = Arc::clone(&protectee);
+            let power = Arc::clone(&power);
+            #[cfg(feature = "mavlink")]
+            let flight = flight.clone();
+            #[cfg(feature = "wasm-plugins")]
+            let plugins = plugins.clone();
+            async move {
+                loop {
+                    let started = std::time::Instant::now();
+                    {
+                        let mut state = state.write().await;
+                        let mut power = power.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                        // Modules switch their own loads off, checking `is_shed`
+                        power.update(&mut state, chrono::Utc::now());
+                    }
+                    Self::protection_cycle(
+                        &state,
+                        #[cfg(feature = "wasm-plugins")]
+                        plugins.as_deref(),
+                    )
+                    .await?;
+                    let command = {
+                        let mut state = state.write().await;
+                        let mut patrol = patrol.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                        let mut link = link.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                        let mut protectee = protectee.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                        Self::plan_flight(&mut state, &mut failsafe, &mut link, &mut protectee, &mut patrol)
+                    };
+                    if let Some(command) = command {
+                        #[cfg(feature = "mavlink")]
+                        Self::send_flight_command(flight.clone(), command);
+                        #[cfg(not(feature = "mavlink"))]
+                        warn!("🛩️ No flight controller to {}", command);
+                    }
+                    cycle_time.observe(started.elapsed().as_secs_f64());
+                    battery.set(f64::from(state.read().await.system_health.battery_level));
+                    heartbeat.pet();
+                    sleep(Duration::from_millis(100)).await; // 10Hz update rate
+                }
+            }
+        });
+
+        let watchdog = Self::run_watchdog(
+            self.watchdog.clone(),
+            Arc::clone(&self.state),
+            self.supervisor.restarter(),
+        );
+        let reason = tokio::select! {
+            reason = self.shutdown.wait_for_signal() => reason,
+            reason = watchdog => reason,
+            _ = self.supervisor.join() => "all modules stopped".to_string(),
+        };
+        let report = self.emergency_landing(&reason).await?;
+        if !report.is_clean() {
+            return Err("shutdown did not complete cleanly".into());
+        }
+        Ok(())
+    }
+
+    /// Check heartbeats at 10 Hz and act on modules that go quiet; returns
+    /// only when a safety-critical module hangs and the drone must land
+    async fn run_watchdog(watchdog: Watchdog, state: Arc<RwLock<DroneState>>, restarter: ModuleRestarter) -> String {
+        loop {
+            sleep(Duration::from_millis(100)).await;
+            for event in watchdog.check() {
+                if !event.missed {
+                    info!("🐕 Module '{}' heartbeat restored", event.module);
+                    state.write().await.update_module_health(&event.module, ModuleHealth::Running);
+                    continue;
+                }
+                warn!("🐕 Module '{}' silent for {:?}", event.module, event.silent_for);
+                state.write().await.update_module_health(&event.module, ModuleHealth::Unresponsive);
+                match event.action {
+                    WatchdogAction::Log => {},
+                    WatchdogAction::RestartModule => {
+                        if !restarter.restart(&event.module) {
+                            warn!("Module '{}' is not supervised and cannot be restarted", event.module);
+                        }
+                    },
+                    WatchdogAction::EmergencyLanding => {
+                        return format!("module '{}' stopped responding", event.module);
+                    },
+                }
+            }
+        }
+    }
+
+    /// Single cycle of the protection algorithm
+    #[tracing::instrument(skip_all)]
+    async fn protection_cycle(
+        state: &RwLock<DroneState>,
+        #[cfg(feature = "wasm-plugins")] plugins: Option<&std::sync::Mutex<dark_phoenix_core::PluginHost>>,
+    ) -> ModuleResult {
+        let mut state = state.write().await;
+        
+        // System health check
+        Self::update_system_health(&mut state).await;
+        
+        // Threat assessment (placeholder - will integrate with threat-detection module)
+        Self::assess_threats(&mut state).await;
+        
+        // Response coordination (placeholder - will integrate with all modules)
+        Self::coordinate_response(
+            &mut state,
+            #[cfg(feature = "wasm-plugins")]
+            plugins,
+        )
+        .await;
+        
+        Ok(())
+    }
+
+    /// Decide this cycle's flight command: geofence, failsafe and comms-loss
+    /// corrections come first, then escorting the protectee, and the patrol
+    /// only flies while none of those has taken over
+    fn plan_flight(
+        state: &mut DroneState,
+        failsafe: &mut Failsafe,
+        link: &mut LinkMonitor,
+        protectee: &mut Protectee,
+        patrol: &mut PatrolPlanner,
+    ) -> Option<FlightCommand> {
+        let now = chrono::Utc::now();
+        let fenced = state.check_geofence();
+        let failsafe_action = failsafe.check(state, now);
+        let link_action = link.check(state, now).filter(|action| failsafe.engaged().is_none_or(|engaged| *action > engaged));
+        // Never undercut a failsafe or comms-loss policy already under way
+        let engaged = failsafe.engaged().max(link.engaged());
+        let correction = fenced.filter(|action| engaged.is_none_or(|engaged| *action > engaged)).max(failsafe_action).max(link_action);
+        if correction.is_some() || engaged.is_some() || failsafe.handing_off() {
+            patrol.interrupt();
+            return correction.map(FlightCommand::Action);
+        }
+        if protectee.paired().is_some() {
+            patrol.interrupt();
+            return protectee.escort(state, now);
+        }
+        patrol.tick(state, now, chrono::Local::now().time())
+    }
+
+    /// Send a command to the flight controller without holding up the loop
+    /// while it is acknowledged; should it fail the drone is landed where it
+    /// is rather than left to fly on
+    #[cfg(feature = "mavlink")]
+    fn send_flight_command(flight: Option<Arc<dyn dark_phoenix_core::FlightControl>>, command: FlightCommand) {
+        use dark_phoenix_core::FlightAction;
+        let Some(flight) = flight else {
+            warn!("🛩️ No flight controller to {}", command);
+            return;
+        };
+        tokio::spawn(async move {
+            let result = match &command {
+                FlightCommand::FlyTo { target, heading_deg } => flight.fly_to(target, *heading_deg).await,
+                FlightCommand::Action(FlightAction::Loiter) => flight.loiter().await,
+                FlightCommand::Action(FlightAction::ReturnToHome) => flight.return_to_home().await,
+                FlightCommand::Action(FlightAction::Land) => flight.emergency_land().await,
+            };
+            if let Err(e) = result {
+                error!("🛩️ Flight controller refused to {}: {}", command, e);
+                if !matches!(command, FlightCommand::Action(FlightAction::Land)) {
+                    if let Err(e) = flight.emergency_land().await {
+                        error!("🛩️ Flight controller refused to land: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    async fn update_system_health(state: &mut DroneState) {
+        // Battery charge comes from the power manager, or the flight controller
+        if state.system_health.battery_level < 20 && state.threat_level() < ThreatLevel::Orange {
+            warn!("⚠️ Battery critical: {}%", state.system_health.battery_level);
+            state.escalate_threat(ThreatLevel::Orange, "Critical battery level detected".to_string());
+        }
+    }
+
+    async fn assess_threats(state: &mut DroneState) {
+        // Placeholder for Ultra Seeker integration
+        // This will eventually call into the threat-detection module
+        
+        // Simulated threat detection for demo
+        if state.mission_log.len().is_multiple_of(100) && state.threat_level() == ThreatLevel::Green {
+            info!("🔍 Scanning for threats...");
+            // In real implementation, this would analyze camera feeds, audio, movement patterns
+        }
+    }
+
+    async fn coordinate_response(
+        state: &mut DroneState,
+        #[cfg(feature = "wasm-plugins")] plugins: Option<&std::sync::Mutex<dark_phoenix_core::PluginHost>>,
+    ) {
+        // Placeholder for module coordination
+        // This will orchestrate all response modules based on threat level
+
+        // Plugins have their say first, so the level below reflects it
+        #[cfg(feature = "wasm-plugins")]
+        if let Some(plugins) = plugins {
+            plugins.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).coordinate(state);
+        }
+
+        match state.threat_level() {
+            ThreatLevel::Green => {
+                // Passive monitoring mode
+            },
+            ThreatLevel::Yellow => {
+                // Increased sensor sensitivity
+                info!("🟡 Heightened awareness mode active");
+            },
+            ThreatLevel::Orange => {
+                // Defensive posture, prepare deterrence
+                warn!("🟠 Defensive protocols engaged");
+            },
+            ThreatLevel::Red => {
+                // All deterrence systems active
+                error!("🔴 High threat - all systems active");
+            },
+            ThreatLevel::Omega => {
+                // Maximum protection, all systems deployed
+                error!("💀 OMEGA PROTOCOL - DARK PHOENIX RISING 💀");
+            },
+        }
+    }
+
+    /// Get current drone status for external monitoring
+    pub async fn get_status(&self) -> String {
+        let state = self.state.read().await;
+        state.mythic_status()
+    }
+
+    /// Emergency shutdown protocol: stop every module loop so nothing
+    /// re-arms behind us, then run the registered safe-state steps
+    /// (valves, nozzle, deterrence outputs, event stores) in order
+    pub async fn emergency_landing(&mut self, reason: &str) -> Result<ShutdownReport, Box<dyn std::error::Error>> {
+        error!("🚨 EMERGENCY LANDING PROTOCOL ACTIVATED 🚨");
+        self.supervisor.stop_all().await;
+
+        // Start the descent first; the safe-state steps run on the way down
+        #[cfg(feature = "mavlink")]
+        let landing = match &self.flight {
+            Some(flight) => match flight.emergency_land().await {
+                Ok(()) => Some("Flight controller landing".to_string()),
+                Err(e) => {
+                    error!("Flight controller did not accept landing: {}", e);
+                    Some(format!("Flight controller landing failed: {}", e))
+                },
+            },
+            None => None,
+        };
+
+        let report = std::mem::take(&mut self.shutdown).run(reason).await;
+        let unsafe_steps: Vec<&str> = report
+            .steps
+            .iter()
+            .filter(|step| step.outcome != StepOutcome::Completed)
+            .map(|step| step.name.as_str())
+            .collect();
+
+        #[cfg_attr(not(feature = "mavlink"), allow(unused_mut))]
+        let mut actions = if unsafe_steps.is_empty() {
+            vec!["All systems shut down safely".to_string()]
+        } else {
+            vec![format!("Shutdown steps not confirmed: {}", unsafe_steps.join(", "))]
+        };
+        #[cfg(feature = "mavlink")]
+        actions.extend(landing);
+
+        let mut state = self.state.write().await;
+        state.log_event(EventType::SystemMalfunction, format!("Emergency landing initiated: {}", reason), actions);
+        Ok(report)
+    }
+}
+
+#[tokio::main]
+async fn main() -> std::process::ExitCode {
+    let cli = cli::Cli::parse();
+    let result = match cli.command {
+        cli::Command::Run { config, tui, force_arm, pair } => run(config, tui, force_arm, pair).await,
+        command => cli::execute(&cli.api, cli.token.as_deref(), cli.signing_key.as_deref(), cli.key_id.as_deref(), command).await,
+    };
+    match result {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::ExitCode::FAILURE
+        },
+    }
+}
+
+/// Where `phoenix run --tui` logs, as the dashboard owns the terminal
+const TUI_LOG: &str = "phoenix.log";
+
+/// `phoenix run`: bring the drone up with its servers and protect until shutdown
+async fn run(config: Option<PathBuf>, tui: bool, force_arm: Option<String>, pair: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if tui && !cfg!(feature = "phoenix-tui") {
+        return Err("this build has no dashboard; rebuild with --features phoenix-tui".into());
+    }
+    let settings = match &config {
+        Some(path) => Settings::load(path)?,
+        None => Settings::default(),
+    };
+
+    // Initialize logging, exporting traces when a collector is configured
+    let log_writer = if tui {
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(TUI_LOG)?;
+        BoxMakeWriter::new(std::sync::Mutex::new(file))
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+    #[cfg(feature = "otel")]
+    let _otel = match dark_phoenix_core::OtelConfig::from_env() {
+        Some(config) => Some(dark_phoenix_core::otel::init_tracing_to(&config, log_writer).map_err(|e| e.to_string())?),
+        None => {
+            tracing_subscriber::fmt().with_writer(log_writer).with_ansi(!tui).init();
+            None
+        },
+    };
+    #[cfg(not(feature = "otel"))]
+    tracing_subscriber::fmt().with_writer(log_writer).with_ansi(!tui).init();
+
+    // Stores are never written in plaintext once encryption is configured,
+    // so a missing or damaged keyring stops the drone here
+    let keyring = match &settings.encryption {
+        Some(encryption) => Some(Arc::new(Keyring::load_or_create(&encryption.keyring_path)?)),
+        None => None,
+    };
+
+    let module_settings = match &config {
+        Some(path) => modules::ModuleSettings::load(path)?,
+        None => modules::ModuleSettings::default(),
+    };
+    #[cfg(feature = "grpc")]
+    module_settings.validate(&settings)?;
+
+    // Create the Dark Phoenix instance
+    let mut phoenix = DarkPhoenixCore::from_settings(&settings, keyring);
+    if let Some(reason) = &force_arm {
+        phoenix.force_arm(reason);
+    }
+    if let Some(bind) = settings.metrics_bind {
+        phoenix.serve_metrics(bind);
+    }
+    if pair {
+        let pairing = phoenix.pairing().ok_or("pairing is not enabled; add a pairing section to the settings")?;
+        let state = phoenix.state();
+        let drone = state.read().await;
+        let code = pairing.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).start(&drone, chrono::Utc::now());
+        cli::print_pairing_code(&code);
+    }
+    // Modules forward their own records and publish their outputs once the
+    // collector and the simulator are connected
+    #[cfg(feature = "siem")]
+    if let Some(siem) = settings.siem.clone() {
+        phoenix.connect_siem(siem).await?;
+    }
+    #[cfg(feature = "mavlink")]
+    if let Some(flight) = settings.flight.clone() {
+        phoenix.connect_flight(flight).await?;
+    }
+    #[cfg(feature = "sitl")]
+    if let Some(sitl) = settings.sitl.clone() {
+        phoenix.connect_sitl(sitl).await?;
+    }
+    #[cfg(feature = "grpc")]
+    let grpc = module_settings.grpc.clone();
+    let modules = phoenix.attach_modules(module_settings);
+    #[cfg_attr(not(any(feature = "api-server", feature = "grpc", feature = "lora", feature = "mqtt", feature = "phoenix-tui")), allow(unused_variables))]
+    let control: Arc<dyn dark_phoenix_core::ModuleControl> = Arc::new(modules::ModuleController::new(modules.clone(), phoenix.state()));
+    #[cfg(feature = "api-server")]
+    phoenix.serve_api(settings.api.clone(), Some(Arc::clone(&control)));
+    #[cfg(all(feature = "api-server", feature = "mdns"))]
+    if let Some(discovery) = &settings.discovery {
+        if let Err(e) = phoenix.advertise_api(discovery, &settings.api, settings.signed_commands.required).await {
+            warn!("📡 Control API not announced over mDNS: {}", e);
+        }
+    }
+    #[cfg(feature = "grpc")]
+    if let Some(grpc) = grpc {
+        phoenix.serve_grpc(grpc, Some(Arc::clone(&control)));
+    }
+    #[cfg(feature = "mqtt")]
+    if let Some(mqtt) = settings.mqtt.clone() {
+        let (publisher, mut commands) = phoenix.connect_mqtt(mqtt)?;
+        publisher.forward(modules.threat_detection.lock().await.subscribe());
+        let control = Arc::clone(&control);
+        tokio::spawn(async move {
+            while let Some((caller, command)) = commands.recv().await {
+                info!("📡 MQTT command {:?} from {}", command, caller.principal);
+                let result = match &command {
+                    dark_phoenix_core::MqttCommand::Arm => control.set_armed(true).await,
+                    dark_phoenix_core::MqttCommand::Disarm => control.set_armed(false).await,
+                    dark_phoenix_core::MqttCommand::Test { module } => match module.as_str() {
+                        "deterrence" => control.test_deterrence().await,
+                        "fire_suppression" | "fire-suppression" => control.test_fire_suppression().await,
+                        other => Err(format!("no module '{}' to test", other).into()),
+                    },
+                };
+                if let Err(e) = result {
+                    error!("📡 MQTT command {:?} from {} failed: {}", command, caller.principal, e);
+                }
+            }
+        });
+    }
+
+    #[cfg(feature = "ble")]
+    if let Some(wearable) = settings.vitals.wearable.clone() {
+        phoenix.follow_vitals(settings.vitals.clone(), Arc::new(dark_phoenix_core::BleWearable::new(wearable)));
+    }
+
+    #[cfg(feature = "lora")]
+    if let Some(lora) = settings.lora.clone() {
+        let mut commands = phoenix.connect_lora(lora);
+        #[cfg(feature = "mavlink")]
+        let flight = phoenix.flight.clone();
+        let control = Arc::clone(&control);
+        tokio::spawn(async move {
+            while let Some(request) = commands.recv().await {
+                let (command, principal) = (request.command, request.caller.principal.clone());
+                let result = match command {
+                    // Answered with a beacon by the link itself
+                    dark_phoenix_core::LoraCommand::Ping => Ok(()),
+                    dark_phoenix_core::LoraCommand::Disarm => control.set_armed(false).await,
+                    #[cfg(feature = "mavlink")]
+                    dark_phoenix_core::LoraCommand::ReturnToHome if flight.is_some() => {
+                        info!("📡 Returning home on LoRa command from {}", principal);
+                        DarkPhoenixCore::send_flight_command(flight.clone(), FlightCommand::Action(dark_phoenix_core::FlightAction::ReturnToHome));
+                        Ok(())
+                    },
+                    dark_phoenix_core::LoraCommand::ReturnToHome => Err("no flight controller is connected".into()),
+                };
+                if let Err(e) = &result {
+                    error!("📡 LoRa command {:?} from {} failed: {}", command, principal, e);
+                }
+                request.answer(result);
+            }
+        });
+    }
+
+    #[cfg(feature = "phoenix-tui")]
+    if tui {
+        let dashboard = phoenix.show_dashboard(Some(Arc::clone(&control)));
+        let result = phoenix.ignite().await;
+        // The terminal is only restored once the dashboard task has finished
+        dashboard.await??;
+        return result;
+    }
+
+    // Display startup banner
+    println!(r#"
+    🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥
+    
+    ██████╗  █████╗ ██████╗ ██╗  ██╗    ██████╗ ██╗  ██╗ ██████╗ ███████╗███╗   ██╗██╗██╗  ██╗
+    ██╔══██╗██╔══██╗██╔══██╗██║ ██╔╝    ██╔══██╗██║  ██║██╔═══██╗██╔════╝████╗  ██║██║╚██╗██╔╝
+    ██║  ██║███████║██████╔╝█████╔╝     ██████╔╝███████║██║   ██║█████╗  ██╔██╗ ██║██║ ╚███╔╝ 
+    ██║  ██║██╔══██║██╔══██╗██╔═██╗     ██╔═══╝ ██╔══██║██║   ██║██╔══╝  ██║╚██╗██║██║ ██╔██╗ 
+    ██████╔╝██║  ██║██║  ██║██║  ██╗    ██║     ██║  ██║╚██████╔╝███████╗██║ ╚████║██║██╔╝ ██╗
+    ╚═════╝ ╚═╝  ╚═╝╚═╝  ╚═╝╚═╝  ╚═╝    ╚═╝     ╚═╝  ╚═╝ ╚═════╝ ╚══════╝╚═╝  ╚═══╝╚═╝╚═╝  ╚═╝
+    
+    MYTHIC-GRADE AUTONOMOUS PROTECTION SYSTEM
+    "From the ashes of danger, the Phoenix rises to protect."
+    
+    🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥🔥
+    "#);
+
+    println!("\n{}", phoenix.get_status().await);
+    println!("\n🚀 Initiating protection protocols...\n");
+
+    // Start the protection system
+    phoenix.ignite().await
+}
@@ -0,0 +1,352 @@
+use crate::DarkPhoenixCore;
+use async_trait::async_trait;
+use cyber_defense::{CyberDefense, CyberDefenseConfig};
+use dark_phoenix_core::{runtime, DroneState, ModuleControl, ModuleResult, RestartPolicy, SettingsError, ShutdownPhase, Situation, ThreatLevel, WatchdogAction};
+use deterrence_suite::{ActivationContext, DeterrenceConfig, DeterrenceSuite, SelfTestCheck};
+use emergency_contact::{EmergencyContact, EmergencyContactConfig, EmergencyNotification};
+use fire_suppression::{FireSuppressionConfig, FireSuppressionSystem};
+use medical_response::{MedicalResponse, MedicalResponseConfig};
+use serde::Deserialize;
+use shield_system::{ShieldConfig, ShieldController};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use threat_detection::{ThreatAssessment, ThreatDetectionConfig, UltraSeekerEngine};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{watch, Mutex, RwLock};
+use tracing::info;
+
+/// How often fire suppression reads its sensors
+const FIRE_SUPPRESSION_CYCLE: Duration = Duration::from_millis(500);
+
+/// Sections of the config file for the crates built on the core, read
+/// alongside the core's `Settings`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ModuleSettings {
+    pub fire_suppression: FireSuppressionConfig,
+    pub deterrence: DeterrenceConfig,
+    pub threat_detection: ThreatDetectionConfig,
+    pub shield: ShieldConfig,
+    pub emergency_contact: EmergencyContactConfig,
+    pub cyber_defense: CyberDefenseConfig,
+    pub medical_response: MedicalResponseConfig,
+    /// Fleet controller gRPC service (absent = not served)
+    #[cfg(feature = "grpc")]
+    pub grpc: Option<phoenix_grpc::GrpcConfig>,
+}
+
+impl ModuleSettings {
+    /// Read the same file and `PHOENIX__` overrides as `Settings::load`
+    pub fn load(path: &Path) -> Result<Self, SettingsError> {
+        Ok(config::Config::builder()
+            .add_source(config::File::from(path))
+            .add_source(config::Environment::with_prefix("PHOENIX").prefix_separator("__").separator("__"))
+            .build()?
+            .try_deserialize()?)
+    }
+
+    /// Check the gRPC section, its port included against the core's servers
+    #[cfg(feature = "grpc")]
+    pub fn validate(&self, settings: &dark_phoenix_core::Settings) -> Result<(), SettingsError> {
+        let Some(grpc) = &self.grpc else { return Ok(()) };
+        let mut problems = grpc.problems();
+        #[cfg(feature = "api-server")]
+        let api = Some(("api.bind", settings.api.bind));
+        #[cfg(not(feature = "api-server"))]
+        let api = None;
+        let metrics = settings.metrics_bind.map(|bind| ("metrics_bind", bind));
+        for (name, bind) in api.into_iter().chain(metrics) {
+            if bind == grpc.bind {
+                problems.push(format!("{} and grpc.bind both use {}", name, bind));
+            }
+        }
+        match problems.is_empty() {
+            true => Ok(()),
+            false => Err(SettingsError::Invalid(problems)),
+        }
+    }
+}
+
+/// The response modules run by `phoenix run`, shared with the command
+/// handlers that reach into them
+#[derive(Clone)]
+pub struct Modules {
+    pub fire_suppression: Arc<Mutex<FireSuppressionSystem>>,
+    pub deterrence: Arc<Mutex<DeterrenceSuite>>,
+    pub threat_detection: Arc<Mutex<UltraSeekerEngine>>,
+    pub shield: Arc<Mutex<ShieldController>>,
+    pub emergency_contact: Arc<EmergencyContact>,
+    pub cyber_defense: Arc<Mutex<CyberDefense>>,
+    pub medical_response: Arc<Mutex<MedicalResponse>>,
+    /// Latest assessment, for deterrence to word and aim its response
+    assessments: watch::Sender<Option<ThreatAssessment>>,
+    analysis_period: Duration,
+}
+
+impl DarkPhoenixCore {
+    /// Build the response modules, add their preflight checks and safe-state
+    /// steps; their loops start under supervision on ignition. Connect the
+    /// SIEM and SITL first for the modules to forward and publish to them.
+    pub fn attach_modules(&mut self, settings: ModuleSettings) -> Modules {
+        let fire_suppression = FireSuppressionSystem::new(settings.fire_suppression).with_metrics(self.metrics());
+        let deterrence = DeterrenceSuite::new(settings.deterrence).with_metrics(self.metrics());
+        let threat_detection = UltraSeekerEngine::new(settings.threat_detection.clone()).with_metrics(self.metrics());
+        #[cfg(feature = "siem")]
+        let (fire_suppression, threat_detection) = match self.siem.clone() {
+            Some(siem) => (fire_suppression.with_siem(siem.clone()), threat_detection.with_siem(siem)),
+            None => (fire_suppression, threat_detection),
+        };
+        // Valve, nozzle, siren and strobe become intents for the simulator
+        #[cfg(feature = "sitl")]
+        let (fire_suppression, deterrence) = match &self.sitl {
+            Some(actuators) => (fire_suppression.with_sitl_outputs(actuators), deterrence.with_sitl_outputs(actuators)),
+            None => (fire_suppression, deterrence),
+        };
+        self.add_preflight_check(Box::new(fire_suppression.preflight_check()));
+        let deterrence = Arc::new(Mutex::new(deterrence));
+        self.add_preflight_check(Box::new(SelfTestCheck::new(Arc::clone(&deterrence))));
+        let emergency_contact = Arc::new(EmergencyContact::new(settings.emergency_contact).with_links(Arc::clone(&self.links)));
+        let medical_response = MedicalResponse::new(settings.medical_response).with_emergency_contact(Arc::clone(&emergency_contact));
+        let modules = Modules {
+            fire_suppression: Arc::new(Mutex::new(fire_suppression)),
+            deterrence,
+            threat_detection: Arc::new(Mutex::new(threat_detection)),
+            shield: Arc::new(Mutex::new(ShieldController::new(settings.shield))),
+            emergency_contact,
+            cyber_defense: Arc::new(Mutex::new(CyberDefense::new(settings.cyber_defense))),
+            medical_response: Arc::new(Mutex::new(medical_response)),
+            assessments: watch::channel(None).0,
+            analysis_period: Duration::from_secs_f64(1.0 / f64::from(settings.threat_detection.update_frequency_hz.max(1))),
+        };
+
+        let fire_suppression = Arc::clone(&modules.fire_suppression);
+        self.on_shutdown("fire suppression", ShutdownPhase::Actuators, Duration::from_secs(5), move || async move {
+            fire_suppression.lock().await.enter_safe_state().await.map_err(|e| e.to_string())?;
+            Ok(())
+        });
+        let shield = Arc::clone(&modules.shield);
+        self.on_shutdown("shield", ShutdownPhase::Actuators, Duration::from_secs(5), move || async move {
+            shield.lock().await.retract().await.map_err(|e| e.to_string())?;
+            Ok(())
+        });
+        let deterrence = Arc::clone(&modules.deterrence);
+        self.on_shutdown("deterrence", ShutdownPhase::Outputs, Duration::from_secs(2), move || async move {
+            deterrence.lock().await.deactivate_all().await.map_err(|e| e.to_string())?;
+            Ok(())
+        });
+        let threat_detection = Arc::clone(&modules.threat_detection);
+        self.on_shutdown("evidence", ShutdownPhase::Flush, Duration::from_secs(2), move || async move {
+            threat_detection.lock().await.close_evidence()?;
+            Ok(())
+        });
+
+        self.modules = Some(modules.clone());
+        modules
+    }
+
+    /// Start the attached modules' loops under the supervisor
+    pub(crate) fn supervise_modules(&mut self) {
+        let Some(modules) = self.modules.clone() else { return };
+
+        // Threat detection analyses on its own clock and moves the drone's
+        // threat level with each assessment; the keywords it hears count
+        // towards protectee distress
+        let (engine, latest, state, period) = (Arc::clone(&modules.threat_detection), modules.assessments.clone(), self.state(), modules.analysis_period);
+        let medical = Arc::clone(&modules.medical_response);
+        let heartbeat = self.watch("threat detection", Duration::from_secs(5), WatchdogAction::RestartModule);
+        self.supervise("threat detection", RestartPolicy::default(), move || {
+            let (engine, latest, state, heartbeat) = (Arc::clone(&engine), latest.clone(), Arc::clone(&state), heartbeat.clone());
+            let medical = Arc::clone(&medical);
+            async move {
+                let mut ticker = runtime::interval(period);
+                loop {
+                    ticker.tick().await;
+                    let (assessment, trend, risk, degraded) = {
+                        let mut engine = engine.lock().await;
+                        let assessment = engine.analyze_threats().await.map_err(|e| e.to_string())?;
+                        (assessment, engine.risk_trend(), engine.calculate_risk_score(), engine.sensor_health().degraded)
+                    };
+                    {
+                        let mut state = state.write().await;
+                        state.update_threat(assessment.threat_level, trend, assessment.description.clone(), &assessment.trace);
+                        state.update_sensor_health(degraded);
+                        state.report_risk(risk);
+                    }
+                    if let Some(audio) = &assessment.evidence.audio_data {
+                        medical.lock().await.record_keywords(&audio.keyword_matches, assessment.timestamp);
+                    }
+                    latest.send_replace(Some(assessment));
+                    heartbeat.pet();
+                }
+            }
+        });
+
+        // Deterrence answers every change of the drone's threat level, and
+        // stays silent while the modules are disarmed
+        let (suite, latest, state) = (Arc::clone(&modules.deterrence), modules.assessments.clone(), self.state());
+        self.supervise("deterrence", RestartPolicy::default(), move || {
+            let (suite, latest, state) = (Arc::clone(&suite), latest.subscribe(), Arc::clone(&state));
+            async move {
+                let mut transitions = state.read().await.subscribe_threat_transitions();
+                loop {
+                    // A lagged receiver only missed steps on the way to the current level
+                    if let Err(RecvError::Closed) = transitions.recv().await {
+                        return Ok(());
+                    }
+                    let (level, armed) = {
+                        let state = state.read().await;
+                        (state.threat_level(), state.is_armed())
+                    };
+                    let mut suite = suite.lock().await;
+                    let result = if armed && level > ThreatLevel::Green {
+                        let latest = latest.borrow().clone();
+                        let mut ctx = ActivationContext::now(level, latest.as_ref().map(ThreatAssessment::situation).unwrap_or_default());
+                        if let Some(assessment) = latest {
+                            ctx.zone = assessment.zone;
+                            ctx.threat_position = assessment.position;
+                        }
+                        suite.activate_with_context(ctx).await.map_err(|e| e.to_string())
+                    } else {
+                        suite.deactivate_all().await.map_err(|e| e.to_string())
+                    };
+                    state.read().await.report_deterrence(suite.get_status().telemetry());
+                    result?;
+                }
+            }
+        });
+
+        // Fire suppression reads its sensors and discharges on its own
+        let (system, state) = (Arc::clone(&modules.fire_suppression), self.state());
+        let heartbeat = self.watch("fire suppression", Duration::from_secs(5), WatchdogAction::RestartModule);
+        self.supervise("fire suppression", RestartPolicy::default(), move || {
+            let (system, state, heartbeat) = (Arc::clone(&system), Arc::clone(&state), heartbeat.clone());
+            async move {
+                loop {
+                    let status = {
+                        let mut system = system.lock().await;
+                        system.monitor_and_respond().await.map_err(|e| e.to_string())?;
+                        system.get_status().telemetry()
+                    };
+                    state.write().await.report_fire_suppression(status);
+                    heartbeat.pet();
+                    runtime::sleep(FIRE_SUPPRESSION_CYCLE).await;
+                }
+            }
+        });
+        // The shield follows the threat level and reports its integrity into
+        // the health report
+        let (shield, state) = (Arc::clone(&modules.shield), self.state());
+        self.supervise("shield", RestartPolicy::default(), move || ShieldController::follow(Arc::clone(&shield), Arc::clone(&state)));
+        // Emergency services hear of every escalation to the trigger level
+        let (contact, state) = (Arc::clone(&modules.emergency_contact), self.state());
+        self.supervise("emergency contact", RestartPolicy::default(), move || EmergencyContact::follow(Arc::clone(&contact), Arc::clone(&state)));
+        // Cyber-defense watches for attacks on the drone itself, hardening
+        // and escalating on its own
+        let (defense, state) = (Arc::clone(&modules.cyber_defense), self.state());
+        self.supervise("cyber defense", RestartPolicy::default(), move || CyberDefense::follow(Arc::clone(&defense), Arc::clone(&state)));
+        // Medical response watches the protectee for distress, and calls for
+        // help through emergency contact when nobody acknowledges it
+        let (medical, state) = (Arc::clone(&modules.medical_response), self.state());
+        self.supervise("medical response", RestartPolicy::default(), move || MedicalResponse::follow(Arc::clone(&medical), Arc::clone(&state)));
+        info!("🛡️ Fire suppression, deterrence, threat detection, the shield, emergency contact, cyber-defense and medical response attached");
+    }
+}
+
+/// Carries commands from the API, gRPC, MQTT and the dashboard out
+/// through the attached modules
+pub struct ModuleController {
+    modules: Modules,
+    state: Arc<RwLock<DroneState>>,
+}
+
+impl ModuleController {
+    pub fn new(modules: Modules, state: Arc<RwLock<DroneState>>) -> Self {
+        Self { modules, state }
+    }
+}
+
+#[async_trait]
+impl ModuleControl for ModuleController {
+    async fn activate_deterrence(&self, level: ThreatLevel) -> ModuleResult {
+        self.activate_deterrence_for(level, Situation::Unspecified).await
+    }
+
+    async fn activate_deterrence_for(&self, level: ThreatLevel, situation: Situation) -> ModuleResult {
+        if !self.state.read().await.is_armed() {
+            return Err("deterrence is disarmed".into());
+        }
+        let mut suite = self.modules.deterrence.lock().await;
+        let result = suite.activate(level, situation).await.map_err(|e| e.to_string());
+        self.state.read().await.report_deterrence(suite.get_status().telemetry());
+        Ok(result?)
+    }
+
+    async fn deactivate_deterrence(&self) -> ModuleResult {
+        let mut suite = self.modules.deterrence.lock().await;
+        let result = suite.deactivate_all().await.map_err(|e| e.to_string());
+        self.state.read().await.report_deterrence(suite.get_status().telemetry());
+        Ok(result?)
+    }
+
+    async fn notify_emergency_contacts(&self, summary: &str) -> ModuleResult {
+        let contact = &self.modules.emergency_contact;
+        if contact.channels().is_empty() {
+            return Err("no emergency channels are configured".into());
+        }
+        let mut notification = EmergencyNotification::from_drone(&*self.state.read().await, contact.status_url());
+        notification.summary = summary.to_string();
+        contact.queue(&notification, Arc::clone(&self.state));
+        Ok(())
+    }
+
+    async fn test_deterrence(&self) -> ModuleResult {
+        self.modules.deterrence.lock().await.system_test().await.map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    async fn test_fire_suppression(&self) -> ModuleResult {
+        self.modules.fire_suppression.lock().await.system_test().await.map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    async fn activate_fire_suppression(&self) -> ModuleResult {
+        let mut system = self.modules.fire_suppression.lock().await;
+        let result = system.manual_activate().await.map_err(|e| e.to_string());
+        self.state.write().await.report_fire_suppression(system.get_status().telemetry());
+        Ok(result?)
+    }
+
+    async fn deploy_shield(&self, deploy: bool) -> ModuleResult {
+        let mut shield = self.modules.shield.lock().await;
+        let result = match deploy {
+            true => shield.deploy().await,
+            false => shield.retract().await,
+        }
+        .map_err(|e| e.to_string());
+        self.state.write().await.report_shield(shield.state().telemetry());
+        Ok(result?)
+    }
+
+    async fn set_armed(&self, armed: bool) -> ModuleResult {
+        let level = {
+            let mut state = self.state.write().await;
+            state.set_armed(armed);
+            state.threat_level()
+        };
+        // Re-armed mid-incident, deterrence picks up at the current level
+        let deterrence = match armed {
+            true if level > ThreatLevel::Green => {
+                let situation = self.modules.assessments.borrow().as_ref().map(ThreatAssessment::situation).unwrap_or_default();
+                self.activate_deterrence_for(level, situation).await
+            },
+            true => Ok(()),
+            false => self.deactivate_deterrence().await,
+        };
+        let mut system = self.modules.fire_suppression.lock().await;
+        let fire_suppression = system.set_armed(armed).await.map_err(|e| e.to_string());
+        self.state.write().await.report_fire_suppression(system.get_status().telemetry());
+        deterrence?;
+        Ok(fire_suppression?)
+    }
+}
@@ -0,0 +1,127 @@
+/**
+ * 🔥 Dark Phoenix C API 🔥
+ *
+ * Talks to the control API of a running drone (`phoenix run` with the API
+ * enabled) through the same client the `phoenix` CLI uses. Link against
+ * libphoenix_ffi.
+ *
+ * Ownership:
+ *  - A PhoenixClient from phoenix_connect is freed with phoenix_disconnect.
+ *  - A PhoenixSubscription from phoenix_subscribe is freed with
+ *    phoenix_unsubscribe. Clients and subscriptions may be freed in any
+ *    order.
+ *  - Strings returned through a `char **` are owned by the caller and freed
+ *    with phoenix_string_free, never with free().
+ *  - Strings passed to callbacks, and the one phoenix_last_error returns,
+ *    are borrowed: copy them to keep them.
+ *  - Strings passed in are only read during the call.
+ *
+ * Threads:
+ *  - Every function except phoenix_unsubscribe, phoenix_last_error and
+ *    phoenix_string_free blocks until the drone answers, so call them off
+ *    the UI thread.
+ *  - A client may be used from several threads at once.
+ *  - Callbacks run on a thread of the client's own; hand events over to the
+ *    UI thread (e.g. a queued Qt signal). Blocking functions called from a
+ *    callback fail with PHOENIX_ERR_INVALID_ARGUMENT.
+ */
+
+#ifndef DARK_PHOENIX_H
+#define DARK_PHOENIX_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * Results; on anything but PHOENIX_OK, phoenix_last_error says why
+ */
+typedef enum {
+    PHOENIX_OK = 0,
+    PHOENIX_ERR_INVALID_ARGUMENT = 1,  // NULL or malformed argument
+    PHOENIX_ERR_UNREACHABLE = 2,       // No drone answering at the API URL
+    PHOENIX_ERR_REFUSED = 3,           // The drone refused, e.g. not authorized
+    PHOENIX_ERR_FAILED = 4             // Anything else, e.g. a bad response
+} PhoenixResult;
+
+/**
+ * Commands for phoenix_command
+ */
+typedef enum {
+    PHOENIX_SET_THREAT_LEVEL = 0,           // args {"level": "Orange", "reason": "..."}
+    PHOENIX_TEST_DETERRENCE = 1,
+    PHOENIX_TEST_FIRE_SUPPRESSION = 2,
+    PHOENIX_ACTIVATE_FIRE_SUPPRESSION = 3,
+    PHOENIX_DEPLOY_SHIELD = 4,
+    PHOENIX_RETRACT_SHIELD = 5
+} PhoenixCommand;
+
+typedef struct PhoenixClient PhoenixClient;
+typedef struct PhoenixSubscription PhoenixSubscription;
+
+/**
+ * One telemetry message as JSON, e.g. {"type": "status", ...}
+ */
+typedef void (*PhoenixEventCallback)(const char *message_json, void *user_data);
+
+/**
+ * The stream ended without phoenix_unsubscribe: the drone closed it
+ * (PHOENIX_OK, error NULL) or it failed. No events follow.
+ */
+typedef void (*PhoenixClosedCallback)(PhoenixResult result, const char *error, void *user_data);
+
+/**
+ * Connect to the control API at `api`, e.g. "http://192.168.1.20:8080",
+ * and check the drone answers. `token` may be NULL. To sign commands, pass
+ * both `key_id` and the path of a key file from `phoenix keys generate` or
+ * `phoenix pairing join`; otherwise pass NULL for both.
+ */
+PhoenixResult phoenix_connect(const char *api, const char *token, const char *key_id, const char *signing_key_path, PhoenixClient **client);
+
+/**
+ * Free a client; NULL is ignored
+ */
+void phoenix_disconnect(PhoenixClient *client);
+
+/**
+ * The drone's status and health as {"status": {...}, "health": {...}}
+ */
+PhoenixResult phoenix_status(PhoenixClient *client, char **status_json);
+
+/**
+ * Run a command; `args_json` is NULL for all but PHOENIX_SET_THREAT_LEVEL
+ */
+PhoenixResult phoenix_command(PhoenixClient *client, PhoenixCommand command, const char *args_json);
+
+/**
+ * Stream live telemetry and periodic status snapshots to `on_event` until
+ * phoenix_unsubscribe. `on_closed` may be NULL. `user_data` is passed to
+ * both and must stay valid until phoenix_unsubscribe returns.
+ */
+PhoenixResult phoenix_subscribe(PhoenixClient *client, PhoenixEventCallback on_event, PhoenixClosedCallback on_closed, void *user_data, PhoenixSubscription **subscription);
+
+/**
+ * Stop a stream and free it; NULL is ignored. Once it returns no callback
+ * is running or will run, so `user_data` may be freed. Called from one of
+ * the subscription's own callbacks, that callback is the last.
+ */
+void phoenix_unsubscribe(PhoenixSubscription *subscription);
+
+/**
+ * Why the last call on this thread failed, or NULL. Valid until the next
+ * call on this thread.
+ */
+const char *phoenix_last_error(void);
+
+/**
+ * Free a string this library returned; NULL is ignored
+ */
+void phoenix_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif  // DARK_PHOENIX_H
@@ -0,0 +1,295 @@
+//! C API for ground-control apps (`include/phoenix.h`)
+
+use dark_phoenix_core::{ApiClient, ClientError, ThreatLevel};
+use std::cell::{Cell, RefCell};
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::runtime::Runtime;
+
+pub const PHOENIX_OK: c_int = 0;
+pub const PHOENIX_ERR_INVALID_ARGUMENT: c_int = 1;
+pub const PHOENIX_ERR_UNREACHABLE: c_int = 2;
+pub const PHOENIX_ERR_REFUSED: c_int = 3;
+pub const PHOENIX_ERR_FAILED: c_int = 4;
+
+pub const PHOENIX_SET_THREAT_LEVEL: c_int = 0;
+pub const PHOENIX_TEST_DETERRENCE: c_int = 1;
+pub const PHOENIX_TEST_FIRE_SUPPRESSION: c_int = 2;
+pub const PHOENIX_ACTIVATE_FIRE_SUPPRESSION: c_int = 3;
+pub const PHOENIX_DEPLOY_SHIELD: c_int = 4;
+pub const PHOENIX_RETRACT_SHIELD: c_int = 5;
+
+pub type PhoenixEventCallback = extern "C" fn(message_json: *const c_char, user_data: *mut c_void);
+pub type PhoenixClosedCallback = extern "C" fn(result: c_int, error: *const c_char, user_data: *mut c_void);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+    /// The subscription whose callback this thread is in, if any
+    static IN_CALLBACK: Cell<*const Gate> = const { Cell::new(std::ptr::null()) };
+}
+
+/// A failure: its result code, with the reason kept for `phoenix_last_error`
+struct Failure(c_int, String);
+
+impl From<ClientError> for Failure {
+    fn from(e: ClientError) -> Self {
+        let code = match e {
+            ClientError::Unreachable { .. } => PHOENIX_ERR_UNREACHABLE,
+            ClientError::Api { .. } => PHOENIX_ERR_REFUSED,
+            ClientError::SigningKey(_) | ClientError::Io { .. } => PHOENIX_ERR_INVALID_ARGUMENT,
+            _ => PHOENIX_ERR_FAILED,
+        };
+        Failure(code, e.to_string())
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> Failure {
+    Failure(PHOENIX_ERR_INVALID_ARGUMENT, reason.into())
+}
+
+/// The result code for `result`, remembering why it failed
+fn finish(result: Result<(), Failure>) -> c_int {
+    let (code, error) = match result {
+        Ok(()) => (PHOENIX_OK, None),
+        Err(Failure(code, reason)) => (code, Some(CString::new(reason.replace('\0', " ")).unwrap_or_default())),
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = error);
+    code
+}
+
+/// A borrowed C string argument; NULL is None
+unsafe fn text<'a>(value: *const c_char, name: &str) -> Result<Option<&'a str>, Failure> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(value).to_str().map(Some).map_err(|_| invalid(format!("{} is not UTF-8", name)))
+}
+
+/// Hand `value` to the caller through `out`, to free with `phoenix_string_free`
+unsafe fn give(out: *mut *mut c_char, value: String) -> Result<(), Failure> {
+    let value = CString::new(value).map_err(|e| Failure(PHOENIX_ERR_FAILED, e.to_string()))?;
+    *out = value.into_raw();
+    Ok(())
+}
+
+/// Run `future` on the client's runtime, unless the caller is already on
+/// one, where blocking would stall it
+fn block_on<T>(runtime: &Runtime, future: impl Future<Output = Result<T, ClientError>>) -> Result<T, Failure> {
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return Err(invalid("blocking call from a callback or async context"));
+    }
+    Ok(runtime.block_on(future)?)
+}
+
+/// The last holder of a runtime shuts it down without waiting, which is
+/// also allowed on the runtime's own thread
+fn release(runtime: Arc<Runtime>) {
+    if let Ok(runtime) = Arc::try_unwrap(runtime) {
+        runtime.shutdown_background();
+    }
+}
+
+pub struct PhoenixClient {
+    runtime: Arc<Runtime>,
+    client: ApiClient,
+}
+
+/// Lets `phoenix_unsubscribe` wait out a callback in progress
+#[derive(Default)]
+struct Gate {
+    calling: Mutex<()>,
+    closed: AtomicBool,
+}
+
+impl Gate {
+    /// Run `callback` unless the subscription was stopped
+    fn call(&self, callback: impl FnOnce()) {
+        let _calling = self.calling.lock().unwrap_or_else(|e| e.into_inner());
+        if self.closed.load(Ordering::Acquire) {
+            return;
+        }
+        IN_CALLBACK.with(|current| current.set(self));
+        callback();
+        IN_CALLBACK.with(|current| current.set(std::ptr::null()));
+    }
+}
+
+/// The caller's pointer, which it promised stays valid until unsubscribing
+struct UserData(*mut c_void);
+
+// Safety: the header tells callers user_data is used from the client's thread
+unsafe impl Send for UserData {}
+
+pub struct PhoenixSubscription {
+    runtime: Arc<Runtime>,
+    task: tokio::task::JoinHandle<()>,
+    gate: Arc<Gate>,
+}
+
+/// # Safety
+/// String arguments are NULL or NUL-terminated; `client` is writable.
+#[no_mangle]
+pub unsafe extern "C" fn phoenix_connect(
+    api: *const c_char,
+    token: *const c_char,
+    key_id: *const c_char,
+    signing_key_path: *const c_char,
+    client: *mut *mut PhoenixClient,
+) -> c_int {
+    finish((|| {
+        if client.is_null() {
+            return Err(invalid("client is NULL"));
+        }
+        let api = text(api, "api")?.ok_or_else(|| invalid("api is NULL"))?;
+        let signer = match (text(key_id, "key_id")?, text(signing_key_path, "signing_key_path")?) {
+            (Some(key_id), Some(path)) => Some((key_id.to_string(), dark_phoenix_core::client::read_signing_key(Path::new(path))?)),
+            (None, None) => None,
+            _ => return Err(invalid("signing needs both key_id and signing_key_path")),
+        };
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("phoenix-ffi")
+            .enable_all()
+            .build()
+            .map_err(|e| Failure(PHOENIX_ERR_FAILED, e.to_string()))?;
+        let api_client = ApiClient::new(api, text(token, "token")?, signer);
+        block_on(&runtime, api_client.health())?;
+        *client = Box::into_raw(Box::new(PhoenixClient {
+            runtime: Arc::new(runtime),
+            client: api_client,
+        }));
+        Ok(())
+    })())
+}
+
+/// # Safety
+/// `client` is NULL or from `phoenix_connect`, and not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn phoenix_disconnect(client: *mut PhoenixClient) {
+    if !client.is_null() {
+        release(Box::from_raw(client).runtime);
+    }
+}
+
+/// # Safety
+/// `client` is from `phoenix_connect`; `status_json` is writable.
+#[no_mangle]
+pub unsafe extern "C" fn phoenix_status(client: *mut PhoenixClient, status_json: *mut *mut c_char) -> c_int {
+    finish((|| {
+        let (Some(client), false) = (client.as_ref(), status_json.is_null()) else {
+            return Err(invalid("client or status_json is NULL"));
+        };
+        let (status, health) = block_on(&client.runtime, async { Ok((client.client.status().await?, client.client.health().await?)) })?;
+        give(status_json, serde_json::json!({ "status": status, "health": health }).to_string())
+    })())
+}
+
+/// # Safety
+/// `client` is from `phoenix_connect`; `args_json` is NULL or NUL-terminated.
+#[no_mangle]
+pub unsafe extern "C" fn phoenix_command(client: *mut PhoenixClient, command: c_int, args_json: *const c_char) -> c_int {
+    finish((|| {
+        let client = client.as_ref().ok_or_else(|| invalid("client is NULL"))?;
+        let path = match command {
+            PHOENIX_SET_THREAT_LEVEL => {
+                let args = text(args_json, "args_json")?.ok_or_else(|| invalid("setting the threat level needs args_json"))?;
+                let args: serde_json::Value = serde_json::from_str(args).map_err(|e| invalid(format!("args_json: {}", e)))?;
+                let level: ThreatLevel = serde_json::from_value(args["level"].clone()).map_err(|e| invalid(format!("args_json level: {}", e)))?;
+                let reason = args["reason"].as_str().unwrap_or_default();
+                block_on(&client.runtime, client.client.set_threat_level(level, reason))?;
+                return Ok(());
+            },
+            PHOENIX_TEST_DETERRENCE => "/deterrence/test",
+            PHOENIX_TEST_FIRE_SUPPRESSION => "/fire-suppression/test",
+            PHOENIX_ACTIVATE_FIRE_SUPPRESSION => "/fire-suppression/activate",
+            PHOENIX_DEPLOY_SHIELD => "/shield/deploy",
+            PHOENIX_RETRACT_SHIELD => "/shield/retract",
+            other => return Err(invalid(format!("unknown command {}", other))),
+        };
+        block_on(&client.runtime, client.client.post(path))
+    })())
+}
+
+/// # Safety
+/// `client` is from `phoenix_connect`; `user_data` stays valid until
+/// `phoenix_unsubscribe` returns; `subscription` is writable.
+#[no_mangle]
+pub unsafe extern "C" fn phoenix_subscribe(
+    client: *mut PhoenixClient,
+    on_event: Option<PhoenixEventCallback>,
+    on_closed: Option<PhoenixClosedCallback>,
+    user_data: *mut c_void,
+    subscription: *mut *mut PhoenixSubscription,
+) -> c_int {
+    finish((|| {
+        let (Some(client), Some(on_event), false) = (client.as_ref(), on_event, subscription.is_null()) else {
+            return Err(invalid("client, on_event or subscription is NULL"));
+        };
+        let mut stream = block_on(&client.runtime, client.client.subscribe())?;
+        let gate = Arc::new(Gate::default());
+        let user_data = UserData(user_data);
+        let task = client.runtime.spawn({
+            let gate = gate.clone();
+            async move {
+                let user_data = user_data;
+                let ended = loop {
+                    match stream.next().await {
+                        Some(Ok(message)) => {
+                            let Ok(json) = CString::new(serde_json::to_string(&message).unwrap_or_default()) else { continue };
+                            gate.call(|| on_event(json.as_ptr(), user_data.0));
+                        },
+                        Some(Err(e)) => break Err(Failure::from(e)),
+                        None => break Ok(()),
+                    }
+                };
+                if let Some(on_closed) = on_closed {
+                    let (code, error) = match ended {
+                        Ok(()) => (PHOENIX_OK, None),
+                        Err(Failure(code, reason)) => (code, CString::new(reason).ok()),
+                    };
+                    gate.call(|| on_closed(code, error.as_ref().map_or(std::ptr::null(), |error| error.as_ptr()), user_data.0));
+                }
+            }
+        });
+        *subscription = Box::into_raw(Box::new(PhoenixSubscription {
+            runtime: client.runtime.clone(),
+            task,
+            gate,
+        }));
+        Ok(())
+    })())
+}
+
+/// # Safety
+/// `subscription` is NULL or from `phoenix_subscribe`, and not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn phoenix_unsubscribe(subscription: *mut PhoenixSubscription) {
+    if subscription.is_null() {
+        return;
+    }
+    let subscription = Box::from_raw(subscription);
+    subscription.gate.closed.store(true, Ordering::Release);
+    subscription.task.abort();
+    // Wait out a callback on another thread; in our own, it is the last one
+    if IN_CALLBACK.with(|current| current.get()) != Arc::as_ptr(&subscription.gate) {
+        drop(subscription.gate.calling.lock());
+    }
+    release(subscription.runtime);
+}
+
+#[no_mangle]
+pub extern "C" fn phoenix_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(std::ptr::null(), |error| error.as_ptr()))
+}
+
+/// # Safety
+/// `string` is NULL or was returned by this library, and not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn phoenix_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
@@ -0,0 +1,160 @@
+//! Fleet messages and how they travel
+
+use crate::{FleetError, FleetTask};
+use chrono::{DateTime, Duration, Utc};
+use dark_phoenix_core::{DroneState, Position, ThreatLevel};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use threat_detection::{ThreatAssessment, ThreatType, TrackHandoff};
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// What a drone tells the fleet about itself on every announcement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Announcement {
+    pub drone_id: Uuid,
+    pub name: String,
+    /// Higher wins leader election
+    pub priority: u8,
+    pub threat_level: ThreatLevel,
+    pub battery_level: u8,
+    pub position: Position,
+    /// Where the protectee is, while this drone tracks their beacon
+    pub protectee: Option<Position>,
+    /// Whether this drone's own command link is up
+    pub communication_status: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Announcement {
+    pub fn from_drone(drone: &DroneState, priority: u8, now: DateTime<Utc>) -> Self {
+        Self {
+            drone_id: drone.id,
+            name: drone.name.clone(),
+            priority,
+            threat_level: drone.threat_level(),
+            battery_level: drone.system_health.battery_level,
+            position: drone.position.clone(),
+            protectee: drone.protectee.as_ref().and_then(|fix| fix.position.clone()),
+            communication_status: drone.system_health.communication_status,
+            timestamp: now,
+        }
+    }
+}
+
+/// A threat one drone assessed, cut down to what the fleet merges on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThreatReport {
+    /// The reporting drone's assessment id
+    pub assessment_id: Uuid,
+    pub reported_by: Uuid,
+    pub threat_level: ThreatLevel,
+    pub confidence: f32,
+    pub threat_types: Vec<ThreatType>,
+    pub position: Position,
+    pub description: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ThreatReport {
+    /// `None` for an assessment without a position, as there is nothing to
+    /// place it on the map by
+    pub fn from_assessment(drone_id: Uuid, assessment: &ThreatAssessment) -> Option<Self> {
+        Some(Self {
+            assessment_id: assessment.id,
+            reported_by: drone_id,
+            threat_level: assessment.threat_level,
+            confidence: assessment.confidence,
+            threat_types: assessment.threat_types.clone(),
+            position: assessment.position.clone()?,
+            description: assessment.description.clone(),
+            timestamp: assessment.timestamp,
+        })
+    }
+}
+
+/// Every drone's task, as the leader decided it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Assignment {
+    pub leader: Uuid,
+    pub term: u64,
+    pub tasks: HashMap<Uuid, FleetTask>,
+}
+
+/// A tracked subject passed on as it leaves the sender's coverage
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Handoff {
+    /// The drone expected to pick the subject up (absent = whoever does)
+    pub to: Option<Uuid>,
+    pub handoff: TrackHandoff,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum FleetMessage {
+    Announce(Announcement),
+    Threat(ThreatReport),
+    Assign(Assignment),
+    Handoff(Handoff),
+}
+
+impl FleetMessage {
+    /// The drone that sent the message
+    pub fn sender(&self) -> Uuid {
+        match self {
+            FleetMessage::Announce(announcement) => announcement.drone_id,
+            FleetMessage::Threat(report) => report.reported_by,
+            FleetMessage::Assign(assignment) => assignment.leader,
+            FleetMessage::Handoff(handoff) => handoff.handoff.from_drone,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Datagram {
+    fleet: String,
+    sent: DateTime<Utc>,
+    /// The message's JSON, as authenticated
+    message: String,
+    /// Hex HMAC-SHA256 of fleet, send time and message
+    tag: String,
+}
+
+/// Encode and authenticate a message for the wire
+pub(crate) fn seal(fleet: &str, secret: &str, message: &FleetMessage, now: DateTime<Utc>) -> Result<Vec<u8>, FleetError> {
+    let message = serde_json::to_string(message).map_err(|e| FleetError::Malformed(e.to_string()))?;
+    let tag = hex::encode(mac(secret, fleet, now, &message).finalize().into_bytes());
+    let datagram = Datagram {
+        fleet: fleet.to_string(),
+        sent: now,
+        message,
+        tag,
+    };
+    serde_json::to_vec(&datagram).map_err(|e| FleetError::Malformed(e.to_string()))
+}
+
+/// Check and decode a datagram from the wire
+pub(crate) fn open(fleet: &str, secret: &str, bytes: &[u8], max_age: Duration, now: DateTime<Utc>) -> Result<FleetMessage, FleetError> {
+    let datagram: Datagram = serde_json::from_slice(bytes).map_err(|e| FleetError::Malformed(e.to_string()))?;
+    if datagram.fleet != fleet {
+        return Err(FleetError::OtherFleet(datagram.fleet));
+    }
+    let tag = hex::decode(&datagram.tag).map_err(|_| FleetError::Unauthenticated)?;
+    mac(secret, fleet, datagram.sent, &datagram.message)
+        .verify_slice(&tag)
+        .map_err(|_| FleetError::Unauthenticated)?;
+    let age = now - datagram.sent;
+    if age > max_age || -age > max_age {
+        return Err(FleetError::Stale(age.num_milliseconds()));
+    }
+    serde_json::from_str(&datagram.message).map_err(|e| FleetError::Malformed(e.to_string()))
+}
+
+fn mac(secret: &str, fleet: &str, sent: DateTime<Utc>, message: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(format!("{}\n{}\n{}", fleet, sent.timestamp_millis(), message).as_bytes());
+    mac
+}
@@ -0,0 +1,445 @@
+//! Multi-drone fleet coordination
+
+use chrono::{DateTime, Duration, Utc};
+use dark_phoenix_core::{DroneState, EventType, Position, ThreatLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use thiserror::Error;
+use threat_detection::{ThreatAssessment, TrackHandoff};
+use tokio::net::UdpSocket;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+pub mod discovery;
+pub mod tasks;
+pub mod threat_map;
+
+pub use discovery::{Announcement, Assignment, FleetMessage, Handoff, ThreatReport};
+pub use tasks::{assign, FleetTask};
+pub use threat_map::{MappedThreat, ThreatMap, ThreatMapConfig};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FleetConfig {
+    /// Shared by the drones of one property; messages of other fleets are
+    /// ignored
+    pub fleet_id: String,
+    /// Key every fleet message is authenticated with
+    pub secret: String,
+    pub bind: SocketAddr,
+    /// Where announcements are broadcast (absent = only to `peers`)
+    pub broadcast: Option<SocketAddr>,
+    /// Drones to reach directly, for networks that drop broadcasts
+    pub peers: Vec<SocketAddr>,
+    /// Higher wins leader election
+    pub priority: u8,
+    pub announce_interval_ms: u64,
+    /// Silence after which a drone has left the fleet
+    pub peer_timeout_ms: u64,
+    pub threat_map: ThreatMapConfig,
+    /// Lowest threat level a drone is sent to track
+    pub track_level: ThreatLevel,
+}
+
+impl Default for FleetConfig {
+    fn default() -> Self {
+        Self {
+            fleet_id: "dark-phoenix".to_string(),
+            secret: String::new(),
+            bind: SocketAddr::from(([0, 0, 0, 0], 47100)),
+            broadcast: Some(SocketAddr::from(([255, 255, 255, 255], 47100))),
+            peers: Vec::new(),
+            priority: 100,
+            announce_interval_ms: 1000,
+            peer_timeout_ms: 3500,
+            threat_map: ThreatMapConfig::default(),
+            track_level: ThreatLevel::Orange,
+        }
+    }
+}
+
+impl FleetConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.fleet_id.is_empty() {
+            problems.push("fleet.fleet_id must not be empty".to_string());
+        }
+        if self.secret.len() < 16 {
+            problems.push("fleet.secret must be at least 16 characters, or anyone on the network can steer the fleet".to_string());
+        }
+        if self.broadcast.is_none() && self.peers.is_empty() {
+            problems.push("fleet needs a broadcast address or peers, or no other drone is ever found".to_string());
+        }
+        if self.announce_interval_ms == 0 {
+            problems.push("fleet.announce_interval_ms must be positive".to_string());
+        }
+        if self.peer_timeout_ms < 2 * self.announce_interval_ms {
+            problems.push("fleet.peer_timeout_ms must cover at least two announcements, or one lost packet drops a drone".to_string());
+        }
+        problems
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FleetError {
+    #[error("fleet network error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("malformed fleet message: {0}")]
+    Malformed(String),
+    #[error("message for fleet '{0}'")]
+    OtherFleet(String),
+    #[error("fleet message failed authentication")]
+    Unauthenticated,
+    #[error("fleet message sent {0} ms from now")]
+    Stale(i64),
+}
+
+/// One drone as the fleet status shows it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemberStatus {
+    pub announcement: Announcement,
+    pub leader: bool,
+    pub task: Option<FleetTask>,
+    /// When this drone last heard from it
+    pub last_heard: DateTime<Utc>,
+}
+
+/// The fleet at a glance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetStatus {
+    pub fleet_id: String,
+    pub leader: Option<Uuid>,
+    pub term: u64,
+    /// This drone included
+    pub members: Vec<MemberStatus>,
+    /// Highest of every member's level and every mapped threat
+    pub threat_level: ThreatLevel,
+    pub threats: Vec<MappedThreat>,
+    pub lowest_battery: Option<u8>,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct Peer {
+    announcement: Announcement,
+    last_heard: DateTime<Utc>,
+}
+
+/// What one drone knows of the fleet, updated from its own announcements
+/// and the messages it receives
+#[derive(Debug, Clone)]
+pub struct FleetState {
+    config: FleetConfig,
+    drone_id: Uuid,
+    own: Option<Peer>,
+    peers: HashMap<Uuid, Peer>,
+    leader: Option<Uuid>,
+    term: u64,
+    map: ThreatMap,
+    assignment: Option<Assignment>,
+}
+
+impl FleetState {
+    pub fn new(config: FleetConfig, drone_id: Uuid) -> Self {
+        Self {
+            map: ThreatMap::new(config.threat_map.clone()),
+            config,
+            drone_id,
+            own: None,
+            peers: HashMap::new(),
+            leader: None,
+            term: 0,
+            assignment: None,
+        }
+    }
+
+    pub fn leader(&self) -> Option<Uuid> {
+        self.leader
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.leader == Some(self.drone_id)
+    }
+
+    pub fn threat_map(&self) -> &ThreatMap {
+        &self.map
+    }
+
+    /// This drone's task from the leader's latest assignment
+    pub fn task(&self) -> Option<&FleetTask> {
+        self.assignment.as_ref()?.tasks.get(&self.drone_id)
+    }
+
+    /// The live member nearest `position`, other than this drone
+    pub fn nearest_member(&self, position: &Position) -> Option<Uuid> {
+        self.peers
+            .values()
+            .map(|peer| (peer.announcement.drone_id, peer.announcement.position.distance_m(position)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(drone_id, _)| drone_id)
+    }
+
+    /// Record what this drone is about to announce
+    pub fn announce(&mut self, announcement: Announcement, now: DateTime<Utc>) {
+        self.own = Some(Peer { announcement, last_heard: now });
+    }
+
+    /// Place a threat on the map; what the drone assessed itself goes on
+    /// its own map at once, as well as out to the fleet
+    pub fn report(&mut self, report: ThreatReport) -> Uuid {
+        self.map.merge(report)
+    }
+
+    /// Take in a message from another drone
+    pub fn handle(&mut self, message: FleetMessage, now: DateTime<Utc>) {
+        if message.sender() == self.drone_id {
+            return;
+        }
+        match message {
+            FleetMessage::Announce(announcement) => {
+                let drone_id = announcement.drone_id;
+                let peer = Peer { announcement, last_heard: now };
+                if self.peers.insert(drone_id, peer).is_none() {
+                    info!("🛸 {} ({}) joined the fleet", self.peers[&drone_id].announcement.name, drone_id);
+                }
+            },
+            FleetMessage::Threat(report) => {
+                self.map.merge(report);
+            },
+            // Only the leader this drone recognises hands out tasks
+            FleetMessage::Assign(assignment) if Some(assignment.leader) == self.leader => {
+                self.term = self.term.max(assignment.term);
+                self.assignment = Some(assignment);
+            },
+            FleetMessage::Assign(assignment) => {
+                debug!("🛸 Ignoring assignment from {}, not the fleet leader", assignment.leader);
+            },
+            // Handoffs go straight to the threat engine, see `Fleet::subscribe_handoffs`
+            FleetMessage::Handoff(_) => {},
+        }
+    }
+
+    /// Drop silent drones and stale threats, re-elect if the membership
+    /// changed, and as leader decide every drone's task; returns the
+    /// assignment to send
+    pub fn tick(&mut self, now: DateTime<Utc>) -> Option<Assignment> {
+        let timeout = Duration::milliseconds(self.config.peer_timeout_ms as i64);
+        self.peers.retain(|drone_id, peer| {
+            let live = now - peer.last_heard < timeout;
+            if !live {
+                warn!("🛸 {} ({}) left the fleet: silent for {}ms", peer.announcement.name, drone_id, (now - peer.last_heard).num_milliseconds());
+            }
+            live
+        });
+        self.map.expire(now);
+
+        let leader = self.members().max_by_key(|member| (member.priority, std::cmp::Reverse(member.drone_id))).map(|member| member.drone_id);
+        if leader != self.leader {
+            self.term += 1;
+            self.leader = leader;
+            self.assignment = None;
+            match leader {
+                Some(leader) if leader == self.drone_id => info!("👑 This drone now leads the fleet (term {})", self.term),
+                Some(leader) => info!("👑 {} now leads the fleet (term {})", leader, self.term),
+                None => {},
+            }
+        }
+        if !self.is_leader() {
+            return None;
+        }
+        let members: Vec<&Announcement> = self.members().collect();
+        let assignment = Assignment {
+            leader: self.drone_id,
+            term: self.term,
+            tasks: assign(&members, self.map.most_severe(), self.config.track_level),
+        };
+        self.assignment = Some(assignment.clone());
+        Some(assignment)
+    }
+
+    fn members(&self) -> impl Iterator<Item = &Announcement> {
+        self.own.iter().chain(self.peers.values()).map(|peer| &peer.announcement)
+    }
+
+    pub fn status(&self, now: DateTime<Utc>) -> FleetStatus {
+        let tasks = self.assignment.as_ref().map(|assignment| &assignment.tasks);
+        let members: Vec<MemberStatus> = self
+            .own
+            .iter()
+            .chain(self.peers.values())
+            .map(|peer| MemberStatus {
+                announcement: peer.announcement.clone(),
+                leader: self.leader == Some(peer.announcement.drone_id),
+                task: tasks.and_then(|tasks| tasks.get(&peer.announcement.drone_id)).cloned(),
+                last_heard: peer.last_heard,
+            })
+            .collect();
+        let threat_level = members
+            .iter()
+            .map(|member| member.announcement.threat_level)
+            .chain(self.map.threats().iter().map(|threat| threat.threat_level))
+            .max()
+            .unwrap_or(ThreatLevel::Green);
+        FleetStatus {
+            fleet_id: self.config.fleet_id.clone(),
+            leader: self.leader,
+            term: self.term,
+            threat_level,
+            lowest_battery: members.iter().map(|member| member.announcement.battery_level).min(),
+            members,
+            threats: self.map.threats().to_vec(),
+            timestamp: now,
+        }
+    }
+}
+
+/// A drone's membership of the fleet over UDP
+pub struct Fleet {
+    config: FleetConfig,
+    drone_id: Uuid,
+    socket: UdpSocket,
+    state: Arc<Mutex<FleetState>>,
+    /// Handoffs addressed to this drone, or to whoever picks them up
+    handoffs: broadcast::Sender<TrackHandoff>,
+}
+
+/// Handoffs buffered per subscriber before slow receivers start lagging
+const HANDOFF_BUFFER: usize = 16;
+
+impl Fleet {
+    /// Open the fleet socket for the drone `drone_id`
+    pub async fn bind(config: FleetConfig, drone_id: Uuid) -> Result<Self, FleetError> {
+        let socket = UdpSocket::bind(config.bind).await?;
+        socket.set_broadcast(config.broadcast.is_some())?;
+        info!("🛸 Fleet '{}' listening on {}", config.fleet_id, socket.local_addr()?);
+        Ok(Self {
+            state: Arc::new(Mutex::new(FleetState::new(config.clone(), drone_id))),
+            config,
+            drone_id,
+            socket,
+            handoffs: broadcast::channel(HANDOFF_BUFFER).0,
+        })
+    }
+
+    /// What this drone knows of the fleet, for status reports and reading
+    /// its task
+    pub fn state(&self) -> Arc<Mutex<FleetState>> {
+        Arc::clone(&self.state)
+    }
+
+    pub fn status(&self) -> FleetStatus {
+        self.lock().status(Utc::now())
+    }
+
+    /// Share a threat this drone assessed; one without a position cannot
+    /// be mapped and is not sent
+    pub async fn report_threat(&self, assessment: &ThreatAssessment) -> Result<(), FleetError> {
+        let Some(report) = ThreatReport::from_assessment(self.drone_id, assessment) else {
+            return Ok(());
+        };
+        self.lock().report(report.clone());
+        self.send(&FleetMessage::Threat(report)).await
+    }
+
+    /// Pass a subject leaving this drone's view to the member nearest where
+    /// it was last seen, or to the whole fleet when that is not known
+    pub async fn hand_off(&self, handoff: TrackHandoff) -> Result<(), FleetError> {
+        let to = handoff.last_position.as_ref().and_then(|position| self.lock().nearest_member(position));
+        match to {
+            Some(to) => info!("🤝 Handing {} track {} to drone {}", handoff.object_type, handoff.track_id, to),
+            None => info!("🤝 Handing {} track {} to the fleet", handoff.object_type, handoff.track_id),
+        }
+        self.send(&FleetMessage::Handoff(Handoff { to, handoff })).await
+    }
+
+    /// Receive every handoff meant for this drone from now on, to pass to
+    /// `UltraSeekerEngine::accept_handoff`
+    pub fn subscribe_handoffs(&self) -> broadcast::Receiver<TrackHandoff> {
+        self.handoffs.subscribe()
+    }
+
+    /// Announce the drone and take in the fleet's messages until the
+    /// socket fails, logging leader and task changes into the mission log
+    pub async fn run(&self, drone: Arc<RwLock<DroneState>>) -> Result<(), FleetError> {
+        let mut interval = dark_phoenix_core::runtime::interval(std::time::Duration::from_millis(self.config.announce_interval_ms.max(1)));
+        let mut buffer = vec![0u8; 65_536];
+        let (mut leader, mut task) = (None, None);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let now = Utc::now();
+                    let announcement = Announcement::from_drone(&*drone.read().await, self.config.priority, now);
+                    let assignment = {
+                        let mut state = self.lock();
+                        state.announce(announcement.clone(), now);
+                        state.tick(now)
+                    };
+                    self.send_logged(&FleetMessage::Announce(announcement)).await;
+                    if let Some(assignment) = assignment {
+                        self.send_logged(&FleetMessage::Assign(assignment)).await;
+                    }
+                    let (now_leader, now_task) = {
+                        let state = self.lock();
+                        (state.leader(), state.task().cloned())
+                    };
+                    let mut drone = drone.write().await;
+                    if now_leader != leader {
+                        let description = match now_leader {
+                            Some(id) if id == self.drone_id => "This drone now leads the fleet".to_string(),
+                            Some(id) => format!("Drone {} now leads the fleet", id),
+                            None => "The fleet has no leader".to_string(),
+                        };
+                        drone.log_event(EventType::FleetLeaderChanged, description, Vec::new());
+                        leader = now_leader;
+                    }
+                    if now_task != task {
+                        if let Some(now_task) = &now_task {
+                            info!("🛸 Fleet task: {}", now_task);
+                            drone.log_event(EventType::FleetTaskAssigned, format!("Fleet task: {}", now_task), Vec::new());
+                        }
+                        task = now_task;
+                    }
+                },
+                received = self.socket.recv_from(&mut buffer) => {
+                    let (length, from) = received?;
+                    let now = Utc::now();
+                    let max_age = Duration::milliseconds(self.config.peer_timeout_ms as i64);
+                    match discovery::open(&self.config.fleet_id, &self.config.secret, &buffer[..length], max_age, now) {
+                        Ok(FleetMessage::Handoff(Handoff { to, handoff })) => {
+                            if handoff.from_drone != self.drone_id && to.is_none_or(|to| to == self.drone_id) {
+                                // Nobody listening is not an error; the handoff just expires
+                                let _ = self.handoffs.send(handoff);
+                            }
+                        },
+                        Ok(message) => self.lock().handle(message, now),
+                        Err(FleetError::OtherFleet(fleet)) => debug!("🛸 Ignoring message for fleet '{}' from {}", fleet, from),
+                        Err(e) => warn!("🛸 Dropped fleet message from {}: {}", from, e),
+                    }
+                },
+            }
+        }
+    }
+
+    async fn send(&self, message: &FleetMessage) -> Result<(), FleetError> {
+        let datagram = discovery::seal(&self.config.fleet_id, &self.config.secret, message, Utc::now())?;
+        for target in self.config.broadcast.iter().chain(&self.config.peers) {
+            self.socket.send_to(&datagram, target).await?;
+        }
+        Ok(())
+    }
+
+    /// Send, carrying on past a network hiccup; the next round resends
+    async fn send_logged(&self, message: &FleetMessage) {
+        if let Err(e) = self.send(message).await {
+            warn!("🛸 Could not send to the fleet: {}", e);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FleetState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,70 @@
+//! Who does what
+
+use crate::{Announcement, MappedThreat};
+use dark_phoenix_core::{Position, ThreatLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FleetTask {
+    /// Follow a threat and keep it in view; by position, as every drone
+    /// numbers its own threat map
+    Track { position: Position, threat_level: ThreatLevel },
+    /// Stay with the protectee
+    Escort,
+    /// Fly the scheduled patrol
+    Patrol,
+}
+
+impl fmt::Display for FleetTask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FleetTask::Track { position, threat_level } => {
+                write!(f, "track {} threat at {:.5}, {:.5}", threat_level.as_str(), position.latitude, position.longitude)
+            },
+            FleetTask::Escort => f.write_str("escort the protectee"),
+            FleetTask::Patrol => f.write_str("patrol"),
+        }
+    }
+}
+
+/// Every member's task, given the live members and the fleet's most severe
+/// threat
+pub fn assign(members: &[&Announcement], threat: Option<&MappedThreat>, track_level: ThreatLevel) -> HashMap<Uuid, FleetTask> {
+    let mut members: Vec<&Announcement> = members.to_vec();
+    // Every leader must reach the same answer from the same members
+    members.sort_by_key(|member| member.drone_id);
+
+    let escort = members
+        .iter()
+        .filter_map(|member| Some((member.drone_id, member.position.distance_m(member.protectee.as_ref()?))))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(drone_id, _)| drone_id);
+    let threat = threat.filter(|threat| threat.threat_level >= track_level);
+    let tracker = threat.and_then(|threat| {
+        let nearest = |member: &&&Announcement| member.position.distance_m(&threat.position);
+        members
+            .iter()
+            .filter(|member| Some(member.drone_id) != escort)
+            .min_by(|a, b| nearest(a).total_cmp(&nearest(b)))
+            .map(|member| member.drone_id)
+    });
+
+    members
+        .iter()
+        .map(|member| {
+            let task = match (threat, tracker) {
+                (Some(threat), Some(tracker)) if tracker == member.drone_id => FleetTask::Track {
+                    position: threat.position.clone(),
+                    threat_level: threat.threat_level,
+                },
+                _ if escort == Some(member.drone_id) => FleetTask::Escort,
+                _ => FleetTask::Patrol,
+            };
+            (member.drone_id, task)
+        })
+        .collect()
+}
@@ -0,0 +1,141 @@
+//! The threat map shared across the fleet
+
+use crate::ThreatReport;
+use chrono::{DateTime, Duration, Utc};
+use dark_phoenix_core::{Position, ThreatLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use threat_detection::ThreatType;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThreatMapConfig {
+    /// Reports closer than this to a mapped threat are merged into it
+    pub merge_radius_m: f64,
+    /// How long a threat stays on the map without a new report
+    pub expire_secs: u64,
+}
+
+impl Default for ThreatMapConfig {
+    fn default() -> Self {
+        Self {
+            merge_radius_m: 15.0,
+            expire_secs: 60,
+        }
+    }
+}
+
+/// One threat as the fleet sees it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappedThreat {
+    /// This drone's id for the threat; each drone numbers its own map
+    pub id: Uuid,
+    pub threat_level: ThreatLevel,
+    /// Combined over every drone reporting it
+    pub confidence: f32,
+    pub threat_types: Vec<ThreatType>,
+    /// Confidence-weighted mean of the reported positions
+    pub position: Position,
+    /// Latest report from each drone
+    pub reports: HashMap<Uuid, ThreatReport>,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+impl MappedThreat {
+    fn new(report: ThreatReport) -> Self {
+        let mut threat = Self {
+            id: Uuid::new_v4(),
+            threat_level: report.threat_level,
+            confidence: report.confidence,
+            threat_types: Vec::new(),
+            position: report.position.clone(),
+            reports: HashMap::new(),
+            first_seen: report.timestamp,
+            last_seen: report.timestamp,
+        };
+        threat.add(report);
+        threat
+    }
+
+    fn add(&mut self, report: ThreatReport) {
+        self.reports.insert(report.reported_by, report);
+        let reports: Vec<&ThreatReport> = self.reports.values().collect();
+        self.threat_level = reports.iter().map(|report| report.threat_level).max().unwrap_or(ThreatLevel::Green);
+        self.confidence = 1.0 - reports.iter().map(|report| 1.0 - report.confidence.clamp(0.0, 1.0)).product::<f32>();
+        self.threat_types.clear();
+        for threat_type in reports.iter().flat_map(|report| report.threat_types.iter().copied()) {
+            if !self.threat_types.contains(&threat_type) {
+                self.threat_types.push(threat_type);
+            }
+        }
+        self.last_seen = reports.iter().map(|report| report.timestamp).max().unwrap_or(self.last_seen);
+
+        // Every report counts a little, so zero-confidence ones still place it
+        let weights: Vec<f64> = reports.iter().map(|report| f64::from(report.confidence).max(0.01)).collect();
+        let total: f64 = weights.iter().sum();
+        let mean = |coordinate: fn(&Position) -> f64| reports.iter().zip(&weights).map(|(report, weight)| coordinate(&report.position) * weight).sum::<f64>() / total;
+        self.position = Position {
+            latitude: mean(|position| position.latitude),
+            longitude: mean(|position| position.longitude),
+            altitude: mean(|position| position.altitude),
+            timestamp: self.last_seen,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ThreatMap {
+    config: ThreatMapConfig,
+    threats: Vec<MappedThreat>,
+}
+
+impl ThreatMap {
+    pub fn new(config: ThreatMapConfig) -> Self {
+        Self {
+            config,
+            threats: Vec::new(),
+        }
+    }
+
+    /// Place a report on the map, merging it into the nearest threat within
+    /// range; returns the mapped threat's id
+    pub fn merge(&mut self, report: ThreatReport) -> Uuid {
+        let nearest = self
+            .threats
+            .iter_mut()
+            .map(|threat| (threat.position.distance_m(&report.position), threat))
+            .filter(|(distance, _)| *distance <= self.config.merge_radius_m)
+            .min_by(|(a, _), (b, _)| a.total_cmp(b));
+        if let Some((_, threat)) = nearest {
+            threat.add(report);
+            return threat.id;
+        }
+        let threat = MappedThreat::new(report);
+        let id = threat.id;
+        self.threats.push(threat);
+        id
+    }
+
+    /// Drop threats nobody has reported for `expire_secs`
+    pub fn expire(&mut self, now: DateTime<Utc>) {
+        let ttl = Duration::seconds(self.config.expire_secs as i64);
+        self.threats.retain(|threat| now - threat.last_seen < ttl);
+    }
+
+    pub fn threats(&self) -> &[MappedThreat] {
+        &self.threats
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<&MappedThreat> {
+        self.threats.iter().find(|threat| threat.id == id)
+    }
+
+    /// Highest level first, then the most confident
+    pub fn most_severe(&self) -> Option<&MappedThreat> {
+        self.threats
+            .iter()
+            .max_by(|a, b| a.threat_level.cmp(&b.threat_level).then(a.confidence.total_cmp(&b.confidence)))
+    }
+}
@@ -0,0 +1,6 @@
+fn main() {
+    // The bundled protoc keeps builds free of a system protobuf install
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("bundled protoc");
+    std::env::set_var("PROTOC", protoc);
+    tonic_build::compile_protos("proto/phoenix.proto").expect("failed to compile proto/phoenix.proto");
+}
@@ -0,0 +1,551 @@
+//! gRPC service for fleet controllers
+
+use chrono::{DateTime, Utc};
+use dark_phoenix_core::envelope::grpc_command;
+use dark_phoenix_core::{
+    runtime, Action, AuthConfig, AuthContext, AuthError, CommandEnvelope, CommandSource, CommandVerifier, DroneState, MissionEvent, ModuleControl, ModuleHealth, OutboundMessage, Outbox,
+    OutboxConfig, OutboxPriority, Position, StoreError, SystemHealth, TelemetryMessage, ThreatLevel, ThreatTransition,
+};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+/// Prefix of every method path of the service, as signed
+const SERVICE_PATH: &str = "/phoenix.v1.PhoenixControl/";
+
+/// Generated from `proto/phoenix.proto`; it follows prost's style, not ours
+#[allow(clippy::all)]
+pub mod proto;
+
+use proto::phoenix_control_server::{PhoenixControl, PhoenixControlServer};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcConfig {
+    /// Loopback by default; expose deliberately
+    pub bind: SocketAddr,
+    /// Default status snapshot interval for `StreamStatus` (ms)
+    pub status_interval_ms: u64,
+    /// Queue on disk the telemetry produced while no controller is
+    /// streaming (absent = drop it)
+    #[serde(default)]
+    pub outbox: Option<OutboxConfig>,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        Self {
+            bind: SocketAddr::from(([127, 0, 0, 1], 50051)),
+            status_interval_ms: 1000,
+            outbox: Some(OutboxConfig {
+                dir: PathBuf::from("outbox/grpc"),
+                ..OutboxConfig::default()
+            }),
+        }
+    }
+}
+
+impl GrpcConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        self.outbox.iter().flat_map(|outbox| outbox.problems()).map(|problem| format!("grpc.{}", problem)).collect()
+    }
+}
+
+/// The `PhoenixControl` service over a drone's shared state
+#[derive(Clone)]
+pub struct PhoenixGrpc {
+    drone: Arc<RwLock<DroneState>>,
+    control: Option<Arc<dyn ModuleControl>>,
+    auth: Arc<AuthConfig>,
+    commands: Arc<CommandVerifier>,
+    status_interval: Duration,
+    outbox: Option<Outbox>,
+    /// `StreamStatus` calls in progress
+    streams: Arc<AtomicUsize>,
+}
+
+/// Counts a `StreamStatus` call for as long as it lasts
+struct StreamGuard(Arc<AtomicUsize>);
+
+impl StreamGuard {
+    fn new(streams: &Arc<AtomicUsize>) -> Self {
+        streams.fetch_add(1, Ordering::SeqCst);
+        Self(Arc::clone(streams))
+    }
+}
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Destination recorded on queued telemetry
+const STREAM_DESTINATION: &str = "StreamStatus";
+
+impl PhoenixGrpc {
+    pub fn new(
+        config: &GrpcConfig,
+        drone: Arc<RwLock<DroneState>>,
+        control: Option<Arc<dyn ModuleControl>>,
+        auth: Arc<AuthConfig>,
+        commands: Arc<CommandVerifier>,
+    ) -> Self {
+        Self {
+            drone,
+            control,
+            auth,
+            commands,
+            status_interval: Duration::from_millis(config.status_interval_ms.max(100)),
+            outbox: None,
+            streams: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Queue telemetry in `outbox` while no controller is streaming; takes
+    /// effect once [`PhoenixGrpc::record_offline`] runs
+    pub fn with_outbox(mut self, outbox: Outbox) -> Self {
+        self.outbox = Some(outbox);
+        self
+    }
+
+    /// Queue telemetry, and a status snapshot on the default interval,
+    /// whenever no `StreamStatus` call is open; runs until the drone state
+    /// is dropped
+    pub async fn record_offline(&self) {
+        let Some(outbox) = &self.outbox else { return };
+        let mut telemetry = self.drone.read().await.subscribe_telemetry();
+        let mut ticker = runtime::interval(self.status_interval);
+        loop {
+            let message = tokio::select! {
+                _ = ticker.tick() => TelemetryMessage::status(&*self.drone.read().await),
+                received = telemetry.recv() => match received {
+                    Ok(message) => message,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            };
+            if self.streams.load(Ordering::SeqCst) > 0 {
+                continue;
+            }
+            let (priority, dedup_key) = OutboxPriority::of_telemetry(&message);
+            let queued = serde_json::to_vec(&message).map_err(StoreError::from).and_then(|payload| {
+                let mut message = OutboundMessage::new(STREAM_DESTINATION, priority, payload);
+                message.dedup_key = dedup_key;
+                outbox.push(message)
+            });
+            if let Err(e) = queued {
+                warn!("🛰️ Failed to queue telemetry: {}", e);
+            }
+        }
+    }
+
+    /// The tonic service, for adding to a larger server
+    pub fn into_service(self) -> PhoenixControlServer<Self> {
+        PhoenixControlServer::new(self)
+    }
+
+    #[allow(clippy::result_large_err)] // Every handler returns tonic's `Status` anyway
+    fn module_control(&self) -> Result<Arc<dyn ModuleControl>, Status> {
+        self.control.clone().ok_or_else(|| Status::unavailable("no modules attached"))
+    }
+
+    /// The caller behind the call's signature or bearer token, if they may
+    /// perform `action`; `method` is the call's name in the service
+    async fn authorize<T: prost::Message>(&self, request: &Request<T>, method: &str, action: Action) -> Result<AuthContext, Status> {
+        let metadata = request.metadata();
+        let token = metadata
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        let command = grpc_command(&format!("{}{}", SERVICE_PATH, method), &request.get_ref().encode_to_vec());
+        let envelope = CommandEnvelope::from_headers(command, |name| metadata.get(name).and_then(|value| value.to_str().ok()));
+        let mut drone = self.drone.write().await;
+        self.commands
+            .authorize(envelope.as_ref(), token, &self.auth, CommandSource::Grpc, action, &mut drone)
+            .map_err(|e| match e {
+                AuthError::Unauthenticated | AuthError::Rejected(_) => Status::unauthenticated(e.to_string()),
+                AuthError::Forbidden { .. } => Status::permission_denied(e.to_string()),
+            })
+    }
+
+    async fn reply(&self) -> Response<proto::CommandReply> {
+        Response::new(proto::CommandReply {
+            status: Some(status_of(&*self.drone.read().await)),
+        })
+    }
+}
+
+/// Serve `PhoenixControl` until `shutdown` completes, queueing telemetry
+/// in `outbox` while nobody streams it
+pub async fn serve(
+    config: GrpcConfig,
+    drone: Arc<RwLock<DroneState>>,
+    control: Option<Arc<dyn ModuleControl>>,
+    auth: Arc<AuthConfig>,
+    commands: Arc<CommandVerifier>,
+    outbox: Option<Outbox>,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> Result<(), tonic::transport::Error> {
+    info!("🛰️ gRPC service listening on {}", config.bind);
+    let mut service = PhoenixGrpc::new(&config, drone, control, auth, commands);
+    if let Some(outbox) = outbox {
+        service = service.with_outbox(outbox);
+    }
+    let recorder = runtime::spawn({
+        let service = service.clone();
+        async move { service.record_offline().await }
+    });
+    let served = tonic::transport::Server::builder()
+        .add_service(service.into_service())
+        .serve_with_shutdown(config.bind, shutdown)
+        .await;
+    recorder.abort();
+    served
+}
+
+type TelemetryStream = Pin<Box<dyn Stream<Item = Result<proto::Telemetry, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl PhoenixControl for PhoenixGrpc {
+    type StreamStatusStream = TelemetryStream;
+
+    async fn stream_status(
+        &self,
+        request: Request<proto::StreamStatusRequest>,
+    ) -> Result<Response<Self::StreamStatusStream>, Status> {
+        self.authorize(&request, "StreamStatus", Action::ViewStatus).await?;
+        let interval = match request.into_inner().status_interval_ms {
+            0 => self.status_interval,
+            ms => Duration::from_millis(u64::from(ms).max(100)),
+        };
+        let drone = Arc::clone(&self.drone);
+        let mut telemetry = drone.read().await.subscribe_telemetry();
+        let (sender, receiver) = mpsc::channel(16);
+        let guard = StreamGuard::new(&self.streams);
+        let outbox = self.outbox.clone();
+        runtime::spawn(async move {
+            let _guard = guard;
+            // What queued up while nobody was streaming goes first
+            if let Some(outbox) = outbox {
+                outbox
+                    .drain(|queued| {
+                        let sender = &sender;
+                        async move {
+                            let message: TelemetryMessage = serde_json::from_slice(&queued.payload).map_err(|e| e.to_string())?;
+                            sender.send(Ok(proto::Telemetry::from(message))).await.map_err(|_| "the client hung up".to_string())
+                        }
+                    })
+                    .await;
+            }
+            let mut ticker = runtime::interval(interval);
+            loop {
+                let message = tokio::select! {
+                    _ = ticker.tick() => TelemetryMessage::status(&*drone.read().await),
+                    received = telemetry.recv() => match received {
+                        Ok(message) => message,
+                        // A slow controller skips ahead rather than falling further behind
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                };
+                // The client hung up
+                if sender.send(Ok(proto::Telemetry::from(message))).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(receiver))))
+    }
+
+    async fn activate_deterrence(
+        &self,
+        request: Request<proto::ActivateDeterrenceRequest>,
+    ) -> Result<Response<proto::CommandReply>, Status> {
+        let caller = self.authorize(&request, "ActivateDeterrence", Action::ActivateDeterrence).await?;
+        let request = request.into_inner();
+        let level = proto::ThreatLevel::from_i32(request.level)
+            .map(ThreatLevel::from)
+            .ok_or_else(|| Status::invalid_argument(format!("unknown threat level {}", request.level)))?;
+        self.module_control()?
+            .activate_deterrence(level)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        info!("🛰️ Deterrence activated at {} by {}: {}", level.as_str(), caller.principal, request.reason);
+        Ok(self.reply().await)
+    }
+
+    async fn suppress(&self, request: Request<proto::SuppressRequest>) -> Result<Response<proto::CommandReply>, Status> {
+        let caller = self.authorize(&request, "Suppress", Action::ActivateFireSuppression).await?;
+        self.module_control()?
+            .activate_fire_suppression()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        info!("🛰️ Fire suppression activated by {}: {}", caller.principal, request.into_inner().reason);
+        Ok(self.reply().await)
+    }
+
+    async fn acknowledge_threat(
+        &self,
+        request: Request<proto::AcknowledgeThreatRequest>,
+    ) -> Result<Response<proto::CommandReply>, Status> {
+        self.authorize(&request, "AcknowledgeThreat", Action::AcknowledgeThreat).await?;
+        let request = request.into_inner();
+        if request.operator.is_empty() {
+            return Err(Status::invalid_argument("operator is required"));
+        }
+        self.drone.write().await.acknowledge_threat(&request.operator, &request.note);
+        Ok(self.reply().await)
+    }
+}
+
+fn timestamp_ms(timestamp: &DateTime<Utc>) -> i64 {
+    timestamp.timestamp_millis()
+}
+
+fn status_of(state: &DroneState) -> proto::Status {
+    proto::Status {
+        name: state.name.clone(),
+        threat_level: proto::ThreatLevel::from(state.threat_level()) as i32,
+        position: Some((&state.position).into()),
+        battery_level: state.system_health.battery_level.into(),
+        timestamp_ms: timestamp_ms(&Utc::now()),
+    }
+}
+
+impl From<ThreatLevel> for proto::ThreatLevel {
+    fn from(level: ThreatLevel) -> Self {
+        match level {
+            ThreatLevel::Green => proto::ThreatLevel::Green,
+            ThreatLevel::Yellow => proto::ThreatLevel::Yellow,
+            ThreatLevel::Orange => proto::ThreatLevel::Orange,
+            ThreatLevel::Red => proto::ThreatLevel::Red,
+            ThreatLevel::Omega => proto::ThreatLevel::Omega,
+        }
+    }
+}
+
+impl From<proto::ThreatLevel> for ThreatLevel {
+    fn from(level: proto::ThreatLevel) -> Self {
+        match level {
+            proto::ThreatLevel::Green => ThreatLevel::Green,
+            proto::ThreatLevel::Yellow => ThreatLevel::Yellow,
+            proto::ThreatLevel::Orange => ThreatLevel::Orange,
+            proto::ThreatLevel::Red => ThreatLevel::Red,
+            proto::ThreatLevel::Omega => ThreatLevel::Omega,
+        }
+    }
+}
+
+impl From<ModuleHealth> for proto::ModuleHealth {
+    fn from(health: ModuleHealth) -> Self {
+        match health {
+            ModuleHealth::Running => proto::ModuleHealth::Running,
+            ModuleHealth::Restarting => proto::ModuleHealth::Restarting,
+            ModuleHealth::Degraded => proto::ModuleHealth::Degraded,
+            ModuleHealth::Unresponsive => proto::ModuleHealth::Unresponsive,
+            ModuleHealth::Failed => proto::ModuleHealth::Failed,
+            ModuleHealth::Stopped => proto::ModuleHealth::Stopped,
+        }
+    }
+}
+
+impl From<&Position> for proto::Position {
+    fn from(position: &Position) -> Self {
+        proto::Position {
+            latitude: position.latitude,
+            longitude: position.longitude,
+            altitude: position.altitude,
+            timestamp_ms: timestamp_ms(&position.timestamp),
+        }
+    }
+}
+
+impl From<&SystemHealth> for proto::SystemHealth {
+    fn from(health: &SystemHealth) -> Self {
+        proto::SystemHealth {
+            battery_level: health.battery_level.into(),
+            flight_time_remaining: health.flight_time_remaining,
+            shield_integrity: health.shield_integrity.into(),
+            fire_suppression_ready: health.fire_suppression_ready,
+            medical_supplies: health.medical_supplies.into(),
+            communication_status: health.communication_status,
+            gps_lock: health.gps_lock,
+            degraded_sensors: health.degraded_sensors.clone(),
+            modules: health
+                .modules
+                .iter()
+                .map(|(module, health)| (module.clone(), proto::ModuleHealth::from(*health) as i32))
+                .collect(),
+            timestamp_ms: timestamp_ms(&health.timestamp),
+            shield_deployed: health.shield_deployed,
+            power_draw_w: health.power_draw_w,
+            shed_loads: health.shed_loads.clone(),
+            battery_state_of_health: health.battery_state_of_health.map_or(-1, i32::from),
+            active_link: health.active_link.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&MissionEvent> for proto::MissionEvent {
+    fn from(event: &MissionEvent) -> Self {
+        proto::MissionEvent {
+            id: event.id.to_string(),
+            timestamp_ms: timestamp_ms(&event.timestamp),
+            event_type: format!("{:?}", event.event_type),
+            description: event.description.clone(),
+            threat_level: proto::ThreatLevel::from(event.threat_level) as i32,
+            position: Some((&event.position).into()),
+            response_actions: event.response_actions.clone(),
+            trace_id: event.trace_id.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&ThreatTransition> for proto::ThreatTransition {
+    fn from(transition: &ThreatTransition) -> Self {
+        proto::ThreatTransition {
+            from: proto::ThreatLevel::from(transition.from) as i32,
+            to: proto::ThreatLevel::from(transition.to) as i32,
+            reason: transition.reason.clone(),
+            timestamp_ms: timestamp_ms(&transition.timestamp),
+        }
+    }
+}
+
+impl From<TelemetryMessage> for proto::Telemetry {
+    fn from(message: TelemetryMessage) -> Self {
+        use proto::telemetry::Message;
+
+        let message = match message {
+            TelemetryMessage::Status {
+                name,
+                threat_level,
+                position,
+                battery_level,
+                timestamp,
+            } => Message::Status(proto::Status {
+                name,
+                threat_level: proto::ThreatLevel::from(threat_level) as i32,
+                position: Some((&position).into()),
+                battery_level: battery_level.into(),
+                timestamp_ms: timestamp_ms(&timestamp),
+            }),
+            TelemetryMessage::Health(health) => Message::Health((&health).into()),
+            TelemetryMessage::Event(event) => Message::Event((&event).into()),
+            TelemetryMessage::ThreatTransition(transition) => Message::ThreatTransition((&transition).into()),
+            TelemetryMessage::ModuleHealth {
+                module,
+                health,
+                timestamp,
+            } => Message::ModuleHealth(proto::ModuleHealthUpdate {
+                module,
+                health: proto::ModuleHealth::from(health) as i32,
+                timestamp_ms: timestamp_ms(&timestamp),
+            }),
+            TelemetryMessage::Risk { score, timestamp } => Message::Risk(proto::RiskUpdate {
+                score,
+                timestamp_ms: timestamp_ms(&timestamp),
+            }),
+            TelemetryMessage::FireSuppression(status) => Message::FireSuppression(proto::FireSuppressionUpdate {
+                armed: status.armed,
+                pressure_psi: status.pressure.0,
+                capacity: status.capacity,
+                discharging: status.discharging,
+                timestamp_ms: timestamp_ms(&status.timestamp),
+            }),
+            TelemetryMessage::Deterrence(status) => Message::Deterrence(proto::DeterrenceUpdate {
+                rehearsal: status.rehearsal,
+                siren_active: status.siren_active,
+                siren_volume: status.siren_volume.into(),
+                strobe_active: status.strobe_active,
+                voice_active: status.voice_active,
+                current_message: status.current_message.unwrap_or_default(),
+                timestamp_ms: timestamp_ms(&status.timestamp),
+            }),
+            TelemetryMessage::Shield(status) => Message::Shield(proto::ShieldUpdate {
+                deployed: status.deployed,
+                angle_deg: status.angle_deg,
+                integrity: status.integrity.into(),
+                impacts: status.impacts,
+                timestamp_ms: timestamp_ms(&status.timestamp),
+            }),
+            TelemetryMessage::Flight(status) => Message::Flight(proto::FlightUpdate {
+                connected: status.connected,
+                armed: status.armed,
+                position: status.position.as_ref().map(Into::into),
+                roll_deg: status.roll_deg,
+                pitch_deg: status.pitch_deg,
+                yaw_deg: status.yaw_deg,
+                gps_lock: status.gps_lock,
+                satellites: status.satellites.into(),
+                battery_level: status.battery_level.map_or(-1, i32::from),
+                battery_voltage: status.battery_voltage.unwrap_or_default(),
+                timestamp_ms: timestamp_ms(&status.timestamp),
+                battery_current_a: status.battery_current_a.unwrap_or(-1.0),
+            }),
+            TelemetryMessage::Power(status) => Message::Power(proto::PowerUpdate {
+                battery_level: status.battery_level.into(),
+                remaining_wh: status.remaining_wh,
+                draw_w: status.draw_w,
+                flight_time_remaining: status.flight_time_remaining,
+                loads: status
+                    .loads
+                    .into_iter()
+                    .map(|load| proto::LoadDraw {
+                        name: load.name,
+                        draw_w: load.draw_w,
+                        shed: load.shed,
+                    })
+                    .collect(),
+                timestamp_ms: timestamp_ms(&status.timestamp),
+            }),
+            TelemetryMessage::Links(status) => Message::Links(proto::LinksUpdate {
+                active: status.active.unwrap_or_default(),
+                links: status
+                    .links
+                    .into_iter()
+                    .map(|link| proto::LinkQuality {
+                        name: link.name,
+                        kind: format!("{:?}", link.kind).to_lowercase(),
+                        state: link.state.to_string(),
+                        latency_ms: link.latency_ms.unwrap_or(-1.0),
+                        loss: link.loss,
+                    })
+                    .collect(),
+                timestamp_ms: timestamp_ms(&status.timestamp),
+            }),
+        };
+        proto::Telemetry { message: Some(message) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn threat_levels_survive_the_wire() {
+        for level in [ThreatLevel::Green, ThreatLevel::Yellow, ThreatLevel::Orange, ThreatLevel::Red, ThreatLevel::Omega] {
+            assert_eq!(ThreatLevel::from(proto::ThreatLevel::from(level)), level);
+        }
+    }
+
+    #[test]
+    fn outbox_problems_are_named_under_grpc() {
+        let mut config = GrpcConfig::default();
+        assert!(config.problems().is_empty());
+        config.outbox.as_mut().unwrap().max_messages = 0;
+        assert_eq!(config.problems(), vec!["grpc.outbox.max_messages must be positive"]);
+    }
+}
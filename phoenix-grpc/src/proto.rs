@@ -0,0 +1,3 @@
+//! Types and service traits generated from `proto/phoenix.proto`
+
+tonic::include_proto!("phoenix.v1");
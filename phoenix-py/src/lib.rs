@@ -0,0 +1,318 @@
+//! Python bindings for incident analysis (`import phoenix`)
+
+use chrono::{DateTime, Utc};
+use dark_phoenix_core::audit::AUDIT_STREAM;
+use dark_phoenix_core::{Keyring, TransitionRules};
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+use threat_detection::{Replay, ReplayLog, ThreatDetectionConfig, UltraSeekerEngine, THREAT_STREAM};
+
+create_exception!(phoenix, PhoenixError, PyException, "A store, config or replay failure");
+
+fn failed(e: impl std::fmt::Display) -> PyErr {
+    PhoenixError::new_err(e.to_string())
+}
+
+/// A drone's event store, read-only
+#[pyclass(module = "phoenix", frozen)]
+struct EventStore {
+    store: dark_phoenix_core::EventStore,
+}
+
+#[pymethods]
+impl EventStore {
+    /// `keyring` is the device keyring file, for stores written encrypted
+    #[new]
+    #[pyo3(signature = (path, keyring = None))]
+    fn new(path: PathBuf, keyring: Option<PathBuf>) -> PyResult<Self> {
+        let keyring = keyring.map(|path| Keyring::load(&path).map(Arc::new)).transpose().map_err(failed)?;
+        let store = dark_phoenix_core::EventStore::open(path).map_err(failed)?.with_keyring(keyring);
+        Ok(Self { store })
+    }
+
+    /// Every record in `stream`, oldest first, as dicts and lists
+    fn read<'py>(&self, py: Python<'py>, stream: &str) -> PyResult<Bound<'py, PyList>> {
+        let records: Vec<Value> = self.store.read(stream).map_err(failed)?;
+        to_list(py, &records)
+    }
+
+    /// The last `count` records in `stream`, oldest first
+    fn read_recent<'py>(&self, py: Python<'py>, stream: &str, count: usize) -> PyResult<Bound<'py, PyList>> {
+        let records: Vec<Value> = self.store.read_recent(stream, count).map_err(failed)?;
+        to_list(py, &records)
+    }
+
+    /// The mission log persisted by the audit log
+    fn mission_events<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        self.read(py, AUDIT_STREAM)
+    }
+
+    /// Recorded threat assessments, oldest first, optionally only those
+    /// between `since` and `until`
+    #[pyo3(signature = (since = None, until = None))]
+    fn assessments(&self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> PyResult<Vec<ThreatAssessment>> {
+        let mut assessments: Vec<threat_detection::ThreatAssessment> = self.store.read(THREAT_STREAM).map_err(failed)?;
+        assessments.retain(|assessment| {
+            since.is_none_or(|since| assessment.timestamp >= since) && until.is_none_or(|until| assessment.timestamp <= until)
+        });
+        assessments.sort_by_key(|assessment| assessment.timestamp);
+        Ok(assessments.into_iter().map(|inner| ThreatAssessment { inner }).collect())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("EventStore('{}')", self.store.root().display())
+    }
+}
+
+/// One Ultra Seeker threat assessment
+#[pyclass(module = "phoenix", frozen)]
+#[derive(Clone)]
+struct ThreatAssessment {
+    inner: threat_detection::ThreatAssessment,
+}
+
+#[pymethods]
+impl ThreatAssessment {
+    #[getter]
+    fn id(&self) -> String {
+        self.inner.id.to_string()
+    }
+
+    #[getter]
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.inner.timestamp
+    }
+
+    /// GREEN, YELLOW, ORANGE, RED or OMEGA
+    #[getter]
+    fn threat_level(&self) -> &'static str {
+        self.inner.threat_level.as_str()
+    }
+
+    /// The threat level as 0 (Green) to 4 (Omega), for plotting
+    #[getter]
+    fn level(&self) -> u32 {
+        self.inner.threat_level as u32
+    }
+
+    #[getter]
+    fn confidence(&self) -> f32 {
+        self.inner.confidence
+    }
+
+    #[getter]
+    fn threat_types(&self) -> Vec<String> {
+        self.inner.threat_types.iter().map(|threat| format!("{:?}", threat)).collect()
+    }
+
+    #[getter]
+    fn description(&self) -> &str {
+        &self.inner.description
+    }
+
+    #[getter]
+    fn recommended_actions(&self) -> Vec<String> {
+        self.inner.recommended_actions.clone()
+    }
+
+    #[getter]
+    fn zone(&self) -> Option<&str> {
+        self.inner.zone.as_deref()
+    }
+
+    /// Inputs, weights and thresholds behind the level, as text
+    fn explain(&self) -> String {
+        self.inner.trace.to_string()
+    }
+
+    /// Every field, evidence included, as plain dicts and lists
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &serde_json::to_value(&self.inner).map_err(failed)?)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ThreatAssessment({} {} {:.2} at {})", self.inner.id, self.inner.threat_level.as_str(), self.inner.confidence, self.inner.timestamp)
+    }
+}
+
+/// Step-by-step outcome of a replayed session
+#[pyclass(module = "phoenix", frozen)]
+struct ReplayReport {
+    report: threat_detection::ReplayReport,
+}
+
+#[pymethods]
+impl ReplayReport {
+    /// Whether every step reproduced the original decision
+    #[getter]
+    fn is_faithful(&self) -> bool {
+        self.report.is_faithful()
+    }
+
+    /// Inputs recorded after the last assessment, never analysed
+    #[getter]
+    fn trailing_inputs(&self) -> usize {
+        self.report.trailing_inputs
+    }
+
+    fn __len__(&self) -> usize {
+        self.report.steps.len()
+    }
+
+    /// Every step as a dict: the original and replayed decisions, the risk
+    /// trend, any escalation and the divergences between the two runs
+    fn steps<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let steps = serde_json::to_value(&self.report.steps).map_err(failed)?;
+        Ok(to_py(py, &steps)?.downcast_into::<PyList>()?)
+    }
+
+    /// Where the replay parted ways with the original run, as
+    /// (timestamp, description) pairs
+    fn divergences(&self) -> Vec<(DateTime<Utc>, String)> {
+        self.report
+            .divergent_steps()
+            .flat_map(|step| step.divergences.iter().map(|divergence| (step.timestamp, divergence.to_string())))
+            .collect()
+    }
+
+    /// Columns for plotting the original against the replayed risk:
+    /// timestamp, original/replayed level (0-4) and confidence, risk trend
+    /// and whether the step diverged
+    fn timeline<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let steps = &self.report.steps;
+        let columns = PyDict::new(py);
+        columns.set_item("timestamp", steps.iter().map(|step| step.timestamp).collect::<Vec<_>>())?;
+        columns.set_item("original_level", steps.iter().map(|step| step.original.threat_level as u32).collect::<Vec<_>>())?;
+        columns.set_item("replayed_level", steps.iter().map(|step| step.replayed.threat_level as u32).collect::<Vec<_>>())?;
+        columns.set_item("original_confidence", steps.iter().map(|step| step.original.confidence).collect::<Vec<_>>())?;
+        columns.set_item("replayed_confidence", steps.iter().map(|step| step.replayed.confidence).collect::<Vec<_>>())?;
+        let trends: Vec<String> = steps.iter().map(|step| format!("{:?}", step.risk_trend).to_lowercase()).collect();
+        columns.set_item("risk_trend", trends)?;
+        columns.set_item("diverged", steps.iter().map(|step| !step.is_faithful()).collect::<Vec<_>>())?;
+        Ok(columns)
+    }
+
+    fn __repr__(&self) -> String {
+        let divergent = self.report.divergent_steps().count();
+        format!("ReplayReport({} steps, {} divergent)", self.report.steps.len(), divergent)
+    }
+}
+
+/// Columns for plotting recorded assessments: timestamp, level (0-4),
+/// confidence and zone
+#[pyfunction]
+fn timeline<'py>(py: Python<'py>, assessments: Vec<ThreatAssessment>) -> PyResult<Bound<'py, PyDict>> {
+    let columns = PyDict::new(py);
+    columns.set_item("timestamp", assessments.iter().map(|assessment| assessment.inner.timestamp).collect::<Vec<_>>())?;
+    columns.set_item("level", assessments.iter().map(ThreatAssessment::level).collect::<Vec<_>>())?;
+    columns.set_item("confidence", assessments.iter().map(ThreatAssessment::confidence).collect::<Vec<_>>())?;
+    columns.set_item("zone", assessments.iter().map(|assessment| assessment.inner.zone.clone()).collect::<Vec<_>>())?;
+    Ok(columns)
+}
+
+/// Re-run the session recorded in `store` (with `record_sensor_inputs` on)
+/// through the threat engine. `config` and `transition_rules` are dicts of
+/// the fields to change from the defaults, as in the drone's config file;
+/// pass the original run's values to check it reproduces, or tweaked ones
+/// to see how scoring would have differed.
+#[pyfunction]
+#[pyo3(signature = (store, config = None, transition_rules = None, since = None, until = None, confidence_tolerance = None))]
+fn replay(
+    py: Python<'_>,
+    store: &EventStore,
+    config: Option<Bound<'_, PyDict>>,
+    transition_rules: Option<Bound<'_, PyDict>>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    confidence_tolerance: Option<f32>,
+) -> PyResult<ReplayReport> {
+    let config: ThreatDetectionConfig = with_overrides(py, ThreatDetectionConfig::default(), config)?;
+    let rules: TransitionRules = with_overrides(py, TransitionRules::default(), transition_rules)?;
+    let mut log = ReplayLog::load(&store.store).map_err(failed)?;
+    if since.is_some() || until.is_some() {
+        log = log.between(since.unwrap_or(DateTime::<Utc>::MIN_UTC), until.unwrap_or(DateTime::<Utc>::MAX_UTC));
+    }
+
+    let mut replay = Replay::new(UltraSeekerEngine::new(config)).with_transition_rules(rules);
+    if let Some(tolerance) = confidence_tolerance {
+        replay = replay.with_confidence_tolerance(tolerance);
+    }
+    // The engine is async; run it to completion off the interpreter lock
+    let report = py
+        .allow_threads(|| {
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+            runtime.block_on(replay.run(&log)).map_err(|e| std::io::Error::other(e.to_string()))
+        })
+        .map_err(failed)?;
+    Ok(ReplayReport { report })
+}
+
+/// `defaults` with the fields in `overrides` replaced, nested dicts merged
+/// field by field
+fn with_overrides<T>(py: Python<'_>, defaults: T, overrides: Option<Bound<'_, PyDict>>) -> PyResult<T>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    let Some(overrides) = overrides else { return Ok(defaults) };
+    let json: String = py.import("json")?.call_method1("dumps", (overrides,))?.extract()?;
+    let overrides: Value = serde_json::from_str(&json).map_err(failed)?;
+    let mut merged = serde_json::to_value(defaults).map_err(failed)?;
+    merge(&mut merged, overrides);
+    serde_json::from_value(merged).map_err(failed)
+}
+
+fn merge(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    },
+                }
+            }
+        },
+        (base, overrides) => *base = overrides,
+    }
+}
+
+fn to_list<'py>(py: Python<'py>, values: &[Value]) -> PyResult<Bound<'py, PyList>> {
+    PyList::new(py, values.iter().map(|value| to_py(py, value)).collect::<PyResult<Vec<_>>>()?)
+}
+
+fn to_py<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match value {
+        Value::Null => py.None().into_bound(py),
+        Value::Bool(flag) => flag.into_pyobject(py)?.to_owned().into_any(),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => integer.into_pyobject(py)?.into_any(),
+            None => number.as_f64().unwrap_or(f64::NAN).into_pyobject(py)?.into_any(),
+        },
+        Value::String(text) => text.into_pyobject(py)?.into_any(),
+        Value::Array(items) => to_list(py, items)?.into_any(),
+        Value::Object(fields) => {
+            let dict = PyDict::new(py);
+            for (key, field) in fields {
+                dict.set_item(key, to_py(py, field)?)?;
+            }
+            dict.into_any()
+        },
+    })
+}
+
+#[pymodule]
+fn phoenix(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add("PhoenixError", module.py().get_type::<PhoenixError>())?;
+    module.add_class::<EventStore>()?;
+    module.add_class::<ThreatAssessment>()?;
+    module.add_class::<ReplayReport>()?;
+    module.add_function(wrap_pyfunction!(replay, module)?)?;
+    module.add_function(wrap_pyfunction!(timeline, module)?)?;
+    Ok(())
+}
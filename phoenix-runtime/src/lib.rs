@@ -0,0 +1,266 @@
+//! Spawning and timers behind a small executor trait
+
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, Notify};
+
+/// A boxed future an executor can run
+pub type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// What the crates need from an async executor
+pub trait Executor: Send + Sync + 'static {
+    /// Run `task` in the background
+    fn spawn(&self, task: BoxFuture);
+
+    /// A future that completes after `duration`
+    fn sleep(&self, duration: Duration) -> BoxFuture;
+
+    /// Run blocking `work` where it cannot stall other tasks; a thread of
+    /// its own unless the executor has a pool for it
+    fn spawn_blocking(&self, work: Box<dyn FnOnce() + Send + 'static>) {
+        std::thread::spawn(work);
+    }
+}
+
+/// Tokio's multi-threaded runtime, entered by `#[tokio::main]`
+#[cfg(feature = "tokio-runtime")]
+pub struct TokioExecutor;
+
+#[cfg(feature = "tokio-runtime")]
+impl Executor for TokioExecutor {
+    fn spawn(&self, task: BoxFuture) {
+        tokio::spawn(task);
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture {
+        Box::pin(tokio::time::sleep(duration))
+    }
+
+    fn spawn_blocking(&self, work: Box<dyn FnOnce() + Send + 'static>) {
+        tokio::task::spawn_blocking(work);
+    }
+}
+
+static EXECUTOR: OnceLock<Box<dyn Executor>> = OnceLock::new();
+
+#[derive(Debug, thiserror::Error)]
+#[error("an executor is already installed")]
+pub struct AlreadyInstalled;
+
+/// Use `executor` for everything spawned from now on; only the first call
+/// takes effect
+pub fn install(executor: impl Executor) -> Result<(), AlreadyInstalled> {
+    EXECUTOR.set(Box::new(executor)).map_err(|_| AlreadyInstalled)
+}
+
+fn executor() -> &'static dyn Executor {
+    #[cfg(feature = "tokio-runtime")]
+    return EXECUTOR.get_or_init(|| Box::new(TokioExecutor)).as_ref();
+    #[cfg(not(feature = "tokio-runtime"))]
+    return EXECUTOR
+        .get()
+        .expect("no executor installed: call runtime::install or enable tokio-runtime")
+        .as_ref();
+}
+
+/// Why a task produced no output
+#[derive(Debug, thiserror::Error)]
+pub enum TaskError {
+    #[error("task was aborted")]
+    Aborted,
+    #[error("task panicked")]
+    Panicked(Box<dyn std::any::Any + Send>),
+}
+
+impl TaskError {
+    pub fn is_panic(&self) -> bool {
+        matches!(self, TaskError::Panicked(_))
+    }
+
+    /// The panic payload; panics itself if the task was aborted
+    pub fn into_panic(self) -> Box<dyn std::any::Any + Send> {
+        match self {
+            TaskError::Panicked(payload) => payload,
+            TaskError::Aborted => panic!("task was aborted, not panicked"),
+        }
+    }
+}
+
+struct TaskState {
+    finished: AtomicBool,
+    abort: Notify,
+}
+
+/// A spawned task; awaiting it gives its output. Dropping the handle leaves
+/// the task running.
+pub struct Task<T> {
+    state: Arc<TaskState>,
+    output: oneshot::Receiver<Result<T, TaskError>>,
+}
+
+impl<T> Task<T> {
+    /// Stop the task at its next await point
+    pub fn abort(&self) {
+        self.state.abort.notify_one();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state.finished.load(Ordering::Acquire)
+    }
+}
+
+impl<T> Future for Task<T> {
+    type Output = Result<T, TaskError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.output)
+            .poll(cx)
+            .map(|output| output.unwrap_or(Err(TaskError::Aborted)))
+    }
+}
+
+/// Run `future` on the installed executor
+pub fn spawn<F>(future: F) -> Task<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let state = Arc::new(TaskState { finished: AtomicBool::new(false), abort: Notify::new() });
+    let (tx, output) = oneshot::channel();
+    let task = Arc::clone(&state);
+    executor().spawn(Box::pin(async move {
+        let result = tokio::select! {
+            result = CatchUnwind(Box::pin(future)) => result.map_err(TaskError::Panicked),
+            _ = task.abort.notified() => Err(TaskError::Aborted),
+        };
+        task.finished.store(true, Ordering::Release);
+        let _ = tx.send(result);
+    }));
+    Task { state, output }
+}
+
+/// Run blocking `work` off the async threads
+pub fn spawn_blocking<F, T>(work: F) -> Task<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let state = Arc::new(TaskState { finished: AtomicBool::new(false), abort: Notify::new() });
+    let (tx, output) = oneshot::channel();
+    let task = Arc::clone(&state);
+    executor().spawn_blocking(Box::new(move || {
+        let result = std::panic::catch_unwind(AssertUnwindSafe(work)).map_err(TaskError::Panicked);
+        task.finished.store(true, Ordering::Release);
+        let _ = tx.send(result);
+    }));
+    Task { state, output }
+}
+
+pub fn sleep(duration: Duration) -> BoxFuture {
+    executor().sleep(duration)
+}
+
+pub async fn sleep_until(deadline: Instant) {
+    sleep(deadline.saturating_duration_since(Instant::now())).await
+}
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("deadline elapsed")]
+pub struct Elapsed;
+
+/// `future`'s output, unless `duration` passes first
+pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Elapsed> {
+    tokio::select! {
+        biased;
+        output = future => Ok(output),
+        _ = sleep(duration) => Err(Elapsed),
+    }
+}
+
+/// `future`'s output, unless `deadline` passes first
+pub async fn timeout_at<F: Future>(deadline: Instant, future: F) -> Result<F::Output, Elapsed> {
+    timeout(deadline.saturating_duration_since(Instant::now()), future).await
+}
+
+/// Ticks every `period`, the first one straight away. A tick missed while
+/// the loop was busy fires late once rather than in a burst.
+pub fn interval(period: Duration) -> Interval {
+    Interval { period, next: Instant::now() }
+}
+
+pub struct Interval {
+    period: Duration,
+    next: Instant,
+}
+
+impl Interval {
+    pub async fn tick(&mut self) -> Instant {
+        sleep_until(self.next).await;
+        let now = Instant::now();
+        self.next += self.period;
+        if self.next < now {
+            self.next = now + self.period;
+        }
+        now
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Restart the period from now
+    pub fn reset(&mut self) {
+        self.next = Instant::now() + self.period;
+    }
+}
+
+/// Turns a panic while polling into an error
+struct CatchUnwind<F>(Pin<Box<F>>);
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, Box<dyn std::any::Any + Send>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match std::panic::catch_unwind(AssertUnwindSafe(|| self.0.as_mut().poll(cx))) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tokio-runtime"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn task_output_panic_and_abort_are_told_apart() {
+        assert_eq!(spawn(async { 7 }).await.unwrap(), 7);
+        assert!(spawn(async { panic!("boom") }).await.unwrap_err().is_panic());
+        let task = spawn(std::future::pending::<()>());
+        task.abort();
+        assert!(matches!(task.await, Err(TaskError::Aborted)));
+    }
+
+    #[tokio::test]
+    async fn timeout_gives_up_at_the_deadline() {
+        assert!(timeout(Duration::from_millis(10), std::future::pending::<()>()).await.is_err());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        assert_eq!(timeout_at(deadline, async { 1 }).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn missed_ticks_are_skipped_not_bunched() {
+        let mut ticker = interval(Duration::from_millis(20));
+        ticker.tick().await;
+        std::thread::sleep(Duration::from_millis(70));
+        let late = ticker.tick().await;
+        let next = ticker.tick().await;
+        assert!(next - late >= Duration::from_millis(15));
+    }
+}
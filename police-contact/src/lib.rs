@@ -1,6 +0,0 @@
-// Police Contact Module - Emergency Services Integration
-// TODO: Implement 911 auto-dial, GPS transmission, live video feed
-
-pub fn placeholder() {
-    println!("Police Contact Module - Ready for implementation");
-}
@@ -1,6 +1,385 @@
-// Shield System Module - Ballistic Protection Deployment
-// TODO: Implement servo control, shield integrity monitoring, rapid deployment
+//! Ballistic shield deployment, aiming and integrity tracking
 
-pub fn placeholder() {
-    println!("Shield System Module - Ready for implementation");
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dark_phoenix_core::{DroneState, EventType, ModuleResult, ProximityFeed, ShieldTelemetry, ThreatLevel};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex, RwLock};
+use tracing::{error, info, warn};
+
+/// Shield configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShieldConfig {
+    /// Threat level at which the shield deploys on its own
+    pub auto_deploy_level: ThreatLevel,
+    /// Stow an automatically deployed shield once the threat drops below `auto_deploy_level`
+    pub auto_retract: bool,
+    /// Travel either side of the drone's heading (degrees)
+    pub max_angle_deg: f32,
+    /// Total impact energy the shield absorbs before it is spent (joules)
+    pub energy_capacity_joules: f32,
+    /// Single-hit energy that risks penetration and counts double (joules)
+    pub penetration_joules: f32,
+    /// Integrity below which the shield is reported compromised (0-100)
+    pub compromised_below: u8,
+    /// How often the impact sensors are read (milliseconds)
+    pub impact_poll_ms: u64,
+}
+
+impl Default for ShieldConfig {
+    fn default() -> Self {
+        Self {
+            auto_deploy_level: ThreatLevel::Red,
+            auto_retract: true,
+            max_angle_deg: 90.0,
+            energy_capacity_joules: 20_000.0,
+            penetration_joules: 1_500.0, // Around a .44 Magnum, the top of NIJ Level IIIA
+            compromised_below: 40,
+            impact_poll_ms: 100,
+        }
+    }
+}
+
+/// One hit registered by the impact sensors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Impact {
+    pub energy_joules: f32,
+    pub incidence_deg: f32, // 0 is head-on, 90 a graze along the face
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Impact {
+    /// Energy the shield takes: glancing hits deflect most of theirs, but
+    /// even a graze costs a fifth
+    pub fn absorbed_joules(&self) -> f32 {
+        let normal = self.incidence_deg.to_radians().cos().abs().max(0.2);
+        self.energy_joules.max(0.0) * normal
+    }
+}
+
+/// Current state of the shield
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShieldState {
+    pub deployed: bool,
+    pub angle_deg: f32, // Off the drone's heading, positive to the right
+    pub integrity: u8,  // 0-100%
+    pub absorbed_joules: f32,
+    pub impacts: u32,
+    pub last_impact: Option<DateTime<Utc>>,
+    /// Deployed by the threat rules rather than an operator, so it may stow itself
+    pub auto_deployed: bool,
+    /// An automatic deployment is waiting for an obstacle to clear
+    #[serde(default)]
+    pub deploy_held: bool,
+}
+
+impl Default for ShieldState {
+    fn default() -> Self {
+        Self {
+            deployed: false,
+            angle_deg: 0.0,
+            integrity: 100,
+            absorbed_joules: 0.0,
+            impacts: 0,
+            last_impact: None,
+            auto_deployed: false,
+            deploy_held: false,
+        }
+    }
+}
+
+impl ShieldState {
+    /// Dashboard summary, for `DroneState::report_shield`
+    pub fn telemetry(&self) -> ShieldTelemetry {
+        ShieldTelemetry {
+            deployed: self.deployed,
+            angle_deg: self.angle_deg,
+            integrity: self.integrity,
+            impacts: self.impacts,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+/// Main shield controller
+pub struct ShieldController {
+    config: ShieldConfig,
+    state: ShieldState,
+    actuator: Box<dyn ShieldActuator>,
+    impact_sensor: Box<dyn ImpactSensor>,
+    /// Ranging checked for people in the shield's swing before it deploys
+    proximity: Option<ProximityFeed>,
+}
+
+impl ShieldController {
+    pub fn new(config: ShieldConfig) -> Self {
+        Self {
+            config,
+            state: ShieldState::default(),
+            actuator: Box::new(SimulatedShieldActuator),
+            impact_sensor: Box::new(SimulatedImpactSensor),
+            proximity: None,
+        }
+    }
+
+    /// Replace the default servos with a hardware implementation
+    pub fn with_actuator(mut self, actuator: Box<dyn ShieldActuator>) -> Self {
+        self.actuator = actuator;
+        self
+    }
+
+    /// Replace the default impact sensor with a hardware implementation
+    pub fn with_impact_sensor(mut self, sensor: Box<dyn ImpactSensor>) -> Self {
+        self.impact_sensor = sensor;
+        self
+    }
+
+    /// Hold automatic deployments while anything is inside the minimum safe distance
+    pub fn with_proximity(mut self, feed: ProximityFeed) -> Self {
+        self.proximity = Some(feed);
+        self
+    }
+
+    pub fn state(&self) -> &ShieldState {
+        &self.state
+    }
+
+    pub fn is_compromised(&self) -> bool {
+        self.state.integrity < self.config.compromised_below
+    }
+
+    /// Raise the shield; an operator deployment stays up until retracted
+    pub async fn deploy(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.state.auto_deployed = false;
+        self.raise().await
+    }
+
+    pub async fn retract(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.state.deployed {
+            return Ok(());
+        }
+        self.actuator.retract().await?;
+        self.state.deployed = false;
+        self.state.auto_deployed = false;
+        info!("🛡️ Shield stowed");
+        Ok(())
+    }
+
+    /// Turn the shield towards `angle_deg` off the drone's heading, within its travel
+    pub async fn set_angle(&mut self, angle_deg: f32) -> Result<(), Box<dyn std::error::Error>> {
+        let angle = angle_deg.clamp(-self.config.max_angle_deg, self.config.max_angle_deg);
+        if angle != angle_deg {
+            warn!("Shield angle {:.0}° beyond travel, holding at {:.0}°", angle_deg, angle);
+        }
+        self.actuator.set_angle(angle).await?;
+        self.state.angle_deg = angle;
+        Ok(())
+    }
+
+    /// Apply the automatic deployment rules to a new threat level
+    pub async fn respond_to_threat(&mut self, level: ThreatLevel) -> Result<(), Box<dyn std::error::Error>> {
+        if level >= self.config.auto_deploy_level {
+            if !self.state.deployed && self.clear_to_deploy() {
+                info!("🛡️ {} threat - deploying shield", level.as_str());
+                self.raise().await?;
+                self.state.auto_deployed = true;
+            }
+        } else {
+            self.state.deploy_held = false;
+            if self.state.deployed && self.state.auto_deployed && self.config.auto_retract {
+                info!("🛡️ Threat down to {} - stowing shield", level.as_str());
+                self.retract().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the shield's swing is clear of anything inside the safe distance
+    fn clear_to_deploy(&mut self) -> bool {
+        let Some(feed) = &self.proximity else { return true };
+        match feed.latest().check(None, chrono::Utc::now()) {
+            Ok(()) => {
+                self.state.deploy_held = false;
+                true
+            },
+            Err(e) => {
+                if !self.state.deploy_held {
+                    warn!("🛡️ Holding shield deployment: {}", e);
+                    self.state.deploy_held = true;
+                }
+                false
+            },
+        }
+    }
+
+    /// Account for a hit, returning the integrity left
+    pub fn record_impact(&mut self, impact: Impact) -> u8 {
+        let mut absorbed = impact.absorbed_joules();
+        if impact.energy_joules >= self.config.penetration_joules {
+            warn!("🛡️ {:.0} J impact - possible penetration", impact.energy_joules);
+            absorbed *= 2.0;
+        }
+        self.state.absorbed_joules += absorbed;
+        self.state.impacts += 1;
+        self.state.last_impact = Some(impact.timestamp);
+
+        let remaining = 1.0 - self.state.absorbed_joules / self.config.energy_capacity_joules.max(f32::EPSILON);
+        self.state.integrity = (remaining * 100.0).clamp(0.0, 100.0).round() as u8;
+        info!("🛡️ Impact absorbed ({:.0} J) - integrity {}%", absorbed, self.state.integrity);
+        self.state.integrity
+    }
+
+    /// Read the impact sensors, returning how many hits they registered
+    pub async fn check_impacts(&mut self) -> Result<usize, Box<dyn std::error::Error>> {
+        let impacts = self.impact_sensor.read_impacts().await?;
+        let count = impacts.len();
+        for impact in impacts {
+            self.record_impact(impact);
+        }
+        Ok(count)
+    }
+
+    /// Follow the drone's threat level and poll the impact sensors, reporting
+    /// every change back to the drone; returns once the drone is gone
+    pub async fn follow(shield: Arc<Mutex<ShieldController>>, drone: Arc<RwLock<DroneState>>) -> ModuleResult {
+        let (mut transitions, level) = {
+            let drone = drone.read().await;
+            (drone.subscribe_threat_transitions(), drone.threat_level())
+        };
+        let poll_interval = Duration::from_millis(shield.lock().await.config.impact_poll_ms.max(10));
+        let mut poll = dark_phoenix_core::runtime::interval(poll_interval);
+        let mut level = Some(level);
+        let mut compromised = false;
+
+        loop {
+            if let Some(level) = level.take() {
+                if let Err(e) = shield.lock().await.respond_to_threat(level).await {
+                    error!("Shield failed to follow {} threat: {}", level.as_str(), e);
+                }
+            }
+            let (status, now_compromised) = {
+                let shield = shield.lock().await;
+                (shield.state.telemetry(), shield.is_compromised())
+            };
+            {
+                let mut drone = drone.write().await;
+                if now_compromised && !compromised {
+                    drone.log_event(
+                        EventType::SystemMalfunction,
+                        format!("Shield compromised: {}% integrity after {} hits", status.integrity, status.impacts),
+                        vec!["Reposition behind cover".to_string()],
+                    );
+                }
+                drone.report_shield(status);
+            }
+            compromised = now_compromised;
+
+            // Wait for the next thing worth reporting
+            loop {
+                tokio::select! {
+                    received = transitions.recv() => match received {
+                        Ok(transition) => level = Some(transition.to),
+                        Err(broadcast::error::RecvError::Lagged(_)) => level = Some(drone.read().await.threat_level()),
+                        Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                    },
+                    _ = poll.tick() => {
+                        let mut shield = shield.lock().await;
+                        if shield.state.deploy_held {
+                            level = Some(drone.read().await.threat_level());
+                            break;
+                        }
+                        match shield.check_impacts().await {
+                            Ok(0) => continue,
+                            Ok(_) => {},
+                            Err(e) => {
+                                warn!("Impact sensor read failed: {}", e);
+                                continue;
+                            },
+                        }
+                    },
+                }
+                break;
+            }
+        }
+    }
+
+    /// Deploy, aim straight ahead and stow again
+    pub async fn system_test(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        info!("🧪 Running shield system test...");
+        let was_deployed = self.state.deployed;
+        self.raise().await?;
+        self.set_angle(-self.config.max_angle_deg).await?;
+        self.set_angle(self.config.max_angle_deg).await?;
+        self.set_angle(0.0).await?;
+        if !was_deployed {
+            self.retract().await?;
+        }
+        info!("✅ Shield system test completed ({}% integrity)", self.state.integrity);
+        Ok(())
+    }
+
+    async fn raise(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.state.deployed {
+            return Ok(());
+        }
+        if self.is_compromised() {
+            warn!("🛡️ Deploying a compromised shield ({}% integrity)", self.state.integrity);
+        }
+        self.actuator.deploy().await?;
+        self.state.deployed = true;
+        info!("🛡️ Shield deployed at {:.0}°", self.state.angle_deg);
+        Ok(())
+    }
+}
+
+/// Servos that raise, lower and turn the shield
+#[async_trait]
+pub trait ShieldActuator: Send + Sync {
+    async fn deploy(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    async fn retract(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Turn to `angle_deg` off the drone's heading, positive to the right
+    async fn set_angle(&self, angle_deg: f32) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// Strain or accelerometer sensors on the shield face
+#[async_trait]
+pub trait ImpactSensor: Send + Sync {
+    /// Hits registered since the previous read
+    async fn read_impacts(&self) -> Result<Vec<Impact>, Box<dyn std::error::Error>>;
+}
+
+/// Servo placeholder used until hardware is attached
+struct SimulatedShieldActuator;
+
+#[async_trait]
+impl ShieldActuator for SimulatedShieldActuator {
+    async fn deploy(&self) -> Result<(), Box<dyn std::error::Error>> {
+        // Placeholder - would drive the deployment servos
+        info!("🛡️ Shield servos extending");
+        Ok(())
+    }
+
+    async fn retract(&self) -> Result<(), Box<dyn std::error::Error>> {
+        info!("🛡️ Shield servos retracting");
+        Ok(())
+    }
+
+    async fn set_angle(&self, angle_deg: f32) -> Result<(), Box<dyn std::error::Error>> {
+        info!("🛡️ Shield turning to {:.0}°", angle_deg);
+        Ok(())
+    }
+}
+
+/// Impact sensor placeholder that never registers a hit
+struct SimulatedImpactSensor;
+
+#[async_trait]
+impl ImpactSensor for SimulatedImpactSensor {
+    async fn read_impacts(&self) -> Result<Vec<Impact>, Box<dyn std::error::Error>> {
+        Ok(Vec::new())
+    }
 }
@@ -0,0 +1,221 @@
+use crate::ThreatType;
+use serde::{Deserialize, Serialize};
+
+/// Impulsive or sustained sound that signals violence on its own
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AcousticEvent {
+    Gunshot,
+    GlassBreak,
+    Scream,
+}
+
+impl AcousticEvent {
+    pub fn threat_type(&self) -> ThreatType {
+        match self {
+            AcousticEvent::Gunshot => ThreatType::WeaponDetected,
+            AcousticEvent::GlassBreak => ThreatType::HostileIntent,
+            AcousticEvent::Scream => ThreatType::PhysicalAggression,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            AcousticEvent::Gunshot => "Gunshot detected",
+            AcousticEvent::GlassBreak => "Breaking glass detected",
+            AcousticEvent::Scream => "Scream detected",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcousticDetection {
+    pub event: AcousticEvent,
+    pub confidence: f32,
+    /// Where in the chunk the event starts (ms)
+    pub offset_ms: u32,
+}
+
+/// Frame-based impulse and spectral classifier for mono PCM
+#[derive(Debug, Clone)]
+pub struct AcousticClassifier {
+    pub sample_rate: u32,
+    /// Samples per analysis frame
+    pub frame_len: usize,
+    /// Rise over the preceding frames that marks an impulse onset (dB)
+    pub onset_db: f32,
+    /// Drop within `decay_ms` of an onset that marks a gunshot rather than a crash (dB)
+    pub gunshot_decay_db: f32,
+    pub decay_ms: u32,
+    /// Spectral centroid above which an impulse rings like glass (Hz)
+    pub glass_centroid_hz: f32,
+    /// Level a scream must hold (dBFS)
+    pub scream_level_dbfs: f32,
+    /// Shortest sustained tonal cry counted as a scream (ms)
+    pub scream_min_ms: u32,
+}
+
+impl Default for AcousticClassifier {
+    fn default() -> Self {
+        Self {
+            sample_rate: 16_000,
+            frame_len: 256,
+            onset_db: 18.0,
+            gunshot_decay_db: 12.0,
+            decay_ms: 120,
+            glass_centroid_hz: 3000.0,
+            scream_level_dbfs: -20.0,
+            scream_min_ms: 300,
+        }
+    }
+}
+
+/// Per-frame features
+struct Frame {
+    energy_db: f32,
+    centroid_hz: f32,
+    /// Spectral flatness, 1.0 for white noise and near 0.0 for a pure tone
+    flatness: f32,
+}
+
+impl AcousticClassifier {
+    /// Samples normalised to -1.0..1.0
+    pub fn classify(&self, samples: &[f32]) -> Vec<AcousticDetection> {
+        let frame_len = self.frame_len.max(16);
+        let frames: Vec<Frame> = samples.chunks_exact(frame_len).map(|chunk| self.features(chunk)).collect();
+        let frame_ms = frame_len as f32 * 1000.0 / self.sample_rate.max(1) as f32;
+
+        let mut detections = Vec::new();
+        detections.extend(self.impulses(&frames, frame_ms));
+        detections.extend(self.screams(&frames, frame_ms));
+        detections
+    }
+
+    fn features(&self, chunk: &[f32]) -> Frame {
+        let energy = chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32;
+        let power = power_spectrum(chunk);
+        let bin_hz = self.sample_rate as f32 / chunk.len() as f32;
+        let total: f32 = power.iter().sum();
+
+        let centroid_hz = if total > 0.0 {
+            power.iter().enumerate().map(|(bin, p)| bin as f32 * bin_hz * p).sum::<f32>() / total
+        } else {
+            0.0
+        };
+        let flatness = if total > 0.0 {
+            let log_mean = power.iter().map(|p| p.max(1e-12).ln()).sum::<f32>() / power.len() as f32;
+            log_mean.exp() / (total / power.len() as f32)
+        } else {
+            0.0
+        };
+
+        Frame {
+            energy_db: 10.0 * energy.max(1e-12).log10(),
+            centroid_hz,
+            flatness,
+        }
+    }
+
+    /// Gunshots and breaking glass both start as a sharp onset; a gunshot is
+    /// broadband and dies away fast, glass keeps ringing with high tones
+    fn impulses(&self, frames: &[Frame], frame_ms: f32) -> Vec<AcousticDetection> {
+        const BACKGROUND_FRAMES: usize = 5;
+        let decay_frames = ((self.decay_ms as f32 / frame_ms).ceil() as usize).max(1);
+        let mut detections = Vec::new();
+        let mut index = 1;
+
+        while index < frames.len() {
+            let background = frames[index.saturating_sub(BACKGROUND_FRAMES)..index]
+                .iter()
+                .map(|frame| frame.energy_db)
+                .fold(f32::INFINITY, f32::min);
+            let onset = &frames[index];
+            let rise = onset.energy_db - background;
+            if rise < self.onset_db {
+                index += 1;
+                continue;
+            }
+
+            let tail = &frames[index + 1..(index + 1 + decay_frames).min(frames.len())];
+            let decay = tail.iter().map(|frame| onset.energy_db - frame.energy_db).fold(0.0, f32::max);
+            let ringing = tail
+                .iter()
+                .filter(|frame| frame.centroid_hz >= self.glass_centroid_hz && frame.flatness < 0.3)
+                .count();
+            let offset_ms = (index as f32 * frame_ms) as u32;
+            let strength = ((rise - self.onset_db) / 20.0).clamp(0.0, 1.0);
+
+            if !tail.is_empty() && ringing * 2 >= tail.len() {
+                detections.push(AcousticDetection {
+                    event: AcousticEvent::GlassBreak,
+                    confidence: 0.6 + 0.4 * strength,
+                    offset_ms,
+                });
+            } else if onset.flatness >= 0.3 && decay >= self.gunshot_decay_db {
+                detections.push(AcousticDetection {
+                    event: AcousticEvent::Gunshot,
+                    confidence: (0.6 + 0.2 * strength + 0.2 * onset.flatness).min(1.0),
+                    offset_ms,
+                });
+            }
+            // One detection per impulse
+            index += decay_frames + 1;
+        }
+        detections
+    }
+
+    /// Loud, tonal and sustained with energy in the 1-4 kHz band
+    fn screams(&self, frames: &[Frame], frame_ms: f32) -> Vec<AcousticDetection> {
+        let min_frames = ((self.scream_min_ms as f32 / frame_ms).ceil() as usize).max(1);
+        let screaming = |frame: &Frame| {
+            frame.energy_db >= self.scream_level_dbfs
+                && frame.flatness < 0.2
+                && (800.0..4000.0).contains(&frame.centroid_hz)
+        };
+
+        let mut detections = Vec::new();
+        let mut start = None;
+        for (index, frame) in frames.iter().enumerate() {
+            match (screaming(frame), start) {
+                (true, None) => start = Some(index),
+                (false, Some(first)) => {
+                    detections.extend(self.scream(first, index - first, min_frames, frame_ms));
+                    start = None;
+                },
+                _ => {},
+            }
+        }
+        if let Some(first) = start {
+            detections.extend(self.scream(first, frames.len() - first, min_frames, frame_ms));
+        }
+        detections
+    }
+
+    fn scream(&self, first: usize, length: usize, min_frames: usize, frame_ms: f32) -> Option<AcousticDetection> {
+        (length >= min_frames).then(|| AcousticDetection {
+            event: AcousticEvent::Scream,
+            // Longer cries are less likely to be a whistle or a squeal of brakes
+            confidence: (0.6 + 0.4 * (length - min_frames) as f32 / min_frames as f32).min(1.0),
+            offset_ms: (first as f32 * frame_ms) as u32,
+        })
+    }
+}
+
+/// Power of each positive-frequency bin of a Hann-windowed frame
+fn power_spectrum(chunk: &[f32]) -> Vec<f32> {
+    let n = chunk.len();
+    let windowed: Vec<f32> = chunk
+        .iter()
+        .enumerate()
+        .map(|(i, s)| s * (0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / n as f32).cos()))
+        .collect();
+    (1..n / 2)
+        .map(|bin| {
+            let (re, im) = windowed.iter().enumerate().fold((0.0f32, 0.0f32), |(re, im), (i, s)| {
+                let angle = std::f32::consts::TAU * bin as f32 * i as f32 / n as f32;
+                (re + s * angle.cos(), im - s * angle.sin())
+            });
+            re * re + im * im
+        })
+        .collect()
+}
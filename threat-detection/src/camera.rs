@@ -0,0 +1,360 @@
+//! IP camera ingest over RTSP (`rtsp` feature)
+
+use crate::extractors::jpeg_frame;
+use crate::{UltraSeekerEngine, ZoneMap};
+use base64::Engine as _;
+use dark_phoenix_core::runtime;
+use futures::StreamExt;
+use openh264::decoder::Decoder;
+use openh264::formats::YUVSource;
+use percent_encoding::percent_decode_str;
+use rand::RngCore;
+use retina::client::{Credentials, PlayOptions, Session, SessionOptions, SetupOptions, Transport};
+use retina::codec::{CodecItem, FrameFormat};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+use url::Url;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraConfig {
+    /// Name the camera's zones and log lines go by
+    pub name: String,
+    /// `rtsp://` address of the stream (absent = asked of the ONVIF service)
+    pub url: Option<String>,
+    pub onvif: Option<OnvifConfig>,
+    /// Login for RTSP and ONVIF; credentials in the URL work too
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Receive RTP over UDP rather than the RTSP connection; LAN only, as
+    /// reordered packets are dropped
+    pub udp: bool,
+    /// Frames handed to the engine per second (H.264 is still decoded in full)
+    pub max_fps: f32,
+    /// Zones in this camera's view (absent = the engine's zones)
+    pub zones: Option<ZoneMap>,
+    /// Reconnect when no frame has arrived for this long
+    pub stall_timeout_ms: u64,
+    /// First wait before reconnecting, doubled on each failure
+    pub reconnect_ms: u64,
+    pub max_reconnect_ms: u64,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        Self {
+            name: "camera".to_string(),
+            url: None,
+            onvif: None,
+            username: None,
+            password: None,
+            udp: false,
+            max_fps: 5.0,
+            zones: None,
+            stall_timeout_ms: 10_000,
+            reconnect_ms: 1000,
+            max_reconnect_ms: 30_000,
+        }
+    }
+}
+
+/// ONVIF media service the stream address is asked of
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnvifConfig {
+    /// Media service address, e.g. `http://192.168.1.64/onvif/media_service`
+    pub url: String,
+    /// Media profile token (absent = the first, usually the main stream)
+    #[serde(default)]
+    pub profile: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum CameraError {
+    #[error("invalid camera address '{0}'")]
+    Address(String),
+    #[error("ONVIF request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("ONVIF: {0}")]
+    Onvif(String),
+    #[error("RTSP: {0}")]
+    Rtsp(#[from] retina::Error),
+    #[error("no H.264 or JPEG video stream offered")]
+    NoVideo,
+    #[error("failed to decode frame: {0}")]
+    Decode(String),
+    #[error("no frame for {0:?}")]
+    Stalled(Duration),
+    #[error("stream ended")]
+    Ended,
+}
+
+impl CameraConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.name.trim().is_empty() {
+            problems.push("camera name must not be empty".to_string());
+        }
+        match (&self.url, &self.onvif) {
+            (Some(url), _) => {
+                if !Url::parse(url).is_ok_and(|url| url.scheme() == "rtsp") {
+                    problems.push(format!("camera '{}' url '{}' is not an rtsp:// address", self.name, url));
+                }
+            },
+            (None, Some(onvif)) => {
+                if !Url::parse(&onvif.url).is_ok_and(|url| matches!(url.scheme(), "http" | "https")) {
+                    problems.push(format!("camera '{}' onvif.url '{}' is not an http(s) address", self.name, onvif.url));
+                }
+            },
+            (None, None) => problems.push(format!("camera '{}' needs a url or an onvif service", self.name)),
+        }
+        if !self.max_fps.is_finite() || self.max_fps <= 0.0 {
+            problems.push(format!("camera '{}' max_fps must be positive", self.name));
+        }
+        if self.stall_timeout_ms == 0 || self.reconnect_ms == 0 {
+            problems.push(format!("camera '{}' stall_timeout_ms and reconnect_ms must be positive", self.name));
+        }
+        problems
+    }
+}
+
+/// One camera streaming into an engine
+pub struct CameraIngest {
+    config: CameraConfig,
+}
+
+impl CameraIngest {
+    pub fn new(config: CameraConfig) -> Self {
+        Self { config }
+    }
+
+    /// Stream frames into `engine` for as long as the task lives,
+    /// reconnecting whenever the camera goes away
+    pub async fn run(self, engine: Arc<Mutex<UltraSeekerEngine>>) {
+        let name = self.config.name.clone();
+        if let Some(zones) = &self.config.zones {
+            engine.lock().await.set_camera_zones(&name, zones.clone());
+        }
+        let max_backoff = Duration::from_millis(self.config.max_reconnect_ms.max(self.config.reconnect_ms));
+        let mut backoff = Duration::from_millis(self.config.reconnect_ms);
+        loop {
+            let mut streaming = false;
+            let Err(e) = self.stream(&engine, &mut streaming).await;
+            warn!("📹 Camera '{}': {}; reconnecting in {:?}", name, e, backoff);
+            runtime::sleep(backoff).await;
+            backoff = if streaming { Duration::from_millis(self.config.reconnect_ms) } else { (backoff * 2).min(max_backoff) };
+        }
+    }
+
+    /// One session, from DESCRIBE until the stream fails; `streaming` is set
+    /// once a frame has reached the engine
+    async fn stream(&self, engine: &Mutex<UltraSeekerEngine>, streaming: &mut bool) -> Result<Infallible, CameraError> {
+        let (url, creds) = self.address().await?;
+        let options = SessionOptions::default().creds(creds).user_agent(format!("dark-phoenix/{}", env!("CARGO_PKG_VERSION")));
+        let mut session = Session::describe(url.clone(), options).await?;
+        let (index, h264) = session
+            .streams()
+            .iter()
+            .enumerate()
+            .find_map(|(i, stream)| match (stream.media(), stream.encoding_name()) {
+                ("video", "h264") => Some((i, true)),
+                ("video", "jpeg") => Some((i, false)),
+                _ => None,
+            })
+            .ok_or(CameraError::NoVideo)?;
+        let transport = if self.config.udp { Transport::Udp(Default::default()) } else { Transport::Tcp(Default::default()) };
+        session.setup(index, SetupOptions::default().transport(transport).frame_format(FrameFormat::SIMPLE)).await?;
+        let mut frames = session.play(PlayOptions::default()).await?.demuxed()?;
+        info!("📹 Camera '{}' streaming {} from {}", self.config.name, if h264 { "H.264" } else { "JPEG" }, url);
+
+        let mut decoder = match h264 {
+            true => Some(Decoder::new().map_err(|e| CameraError::Decode(e.to_string()))?),
+            false => None,
+        };
+        let stall = Duration::from_millis(self.config.stall_timeout_ms);
+        let interval = Duration::from_secs_f32(1.0 / self.config.max_fps);
+        let mut last_sent: Option<Instant> = None;
+        loop {
+            let frame = match runtime::timeout(stall, frames.next()).await {
+                Err(_) => return Err(CameraError::Stalled(stall)),
+                Ok(None) => return Err(CameraError::Ended),
+                Ok(Some(item)) => match item? {
+                    CodecItem::VideoFrame(frame) => frame,
+                    _ => continue,
+                },
+            };
+            let due = last_sent.is_none_or(|sent| sent.elapsed() >= interval);
+            let jpeg = match decoder.take() {
+                // Every frame goes through the decoder, as later ones refer back to it
+                Some(mut h264) => {
+                    let (returned, picture) = runtime::spawn_blocking(move || {
+                        let picture = decode(&mut h264, frame.data(), due);
+                        (h264, picture)
+                    })
+                    .await
+                    .map_err(|e| CameraError::Decode(e.to_string()))?;
+                    decoder = Some(returned);
+                    match picture? {
+                        Some(jpeg) => jpeg,
+                        None => continue,
+                    }
+                },
+                None if due => frame.into_data(),
+                None => continue,
+            };
+            engine.lock().await.update_camera_frame(&self.config.name, jpeg);
+            last_sent = Some(Instant::now());
+            *streaming = true;
+        }
+    }
+
+    /// The stream address, with credentials moved out of it
+    async fn address(&self) -> Result<(Url, Option<Credentials>), CameraError> {
+        let address = match (&self.config.url, &self.config.onvif) {
+            (Some(url), _) => url.clone(),
+            (None, Some(onvif)) => stream_uri(onvif, self.config.username.as_deref(), self.config.password.as_deref()).await?,
+            (None, None) => return Err(CameraError::Address(String::new())),
+        };
+        let mut url = Url::parse(&address).map_err(|_| CameraError::Address(address.clone()))?;
+        let mut creds = self.config.username.clone().map(|username| Credentials {
+            username,
+            password: self.config.password.clone().unwrap_or_default(),
+        });
+        if !url.username().is_empty() {
+            let decode = |part: &str| percent_decode_str(part).decode_utf8_lossy().into_owned();
+            creds = creds.or_else(|| {
+                Some(Credentials {
+                    username: decode(url.username()),
+                    password: decode(url.password().unwrap_or_default()),
+                })
+            });
+            let _ = url.set_username("");
+            let _ = url.set_password(None);
+        }
+        Ok((url, creds))
+    }
+}
+
+/// Decode one H.264 access unit, encoding the picture as JPEG when `wanted`
+fn decode(decoder: &mut Decoder, data: &[u8], wanted: bool) -> Result<Option<Vec<u8>>, CameraError> {
+    let picture = decoder.decode(data).map_err(|e| CameraError::Decode(e.to_string()))?;
+    let Some(picture) = picture.filter(|_| wanted) else { return Ok(None) };
+    let (width, height) = picture.dimensions();
+    let mut rgb = vec![0; width * height * 3];
+    picture.write_rgb8(&mut rgb);
+    let jpeg = jpeg_frame(&rgb, width as u32, height as u32).map_err(|e| CameraError::Decode(e.to_string()))?;
+    Ok(Some(jpeg))
+}
+
+/// Ask an ONVIF media service for the RTSP address of a profile
+pub async fn stream_uri(onvif: &OnvifConfig, username: Option<&str>, password: Option<&str>) -> Result<String, CameraError> {
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(10)).build()?;
+    let call = |body: String| {
+        let envelope = envelope(username, password, &body);
+        let request = client
+            .post(&onvif.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/soap+xml; charset=utf-8")
+            .body(envelope);
+        async move {
+            let response = request.send().await?;
+            let status = response.status();
+            let text = response.text().await?;
+            if !status.is_success() {
+                let reason = element_text(&text, "Text").unwrap_or_else(|| status.to_string());
+                return Err(CameraError::Onvif(reason));
+            }
+            Ok(text)
+        }
+    };
+    let profile = match &onvif.profile {
+        Some(profile) => profile.clone(),
+        None => {
+            let profiles = call("<trt:GetProfiles/>".to_string()).await?;
+            element_attribute(&profiles, "Profiles", "token").ok_or_else(|| CameraError::Onvif("no media profiles".to_string()))?
+        },
+    };
+    let response = call(format!(
+        "<trt:GetStreamUri><trt:StreamSetup><tt:Stream>RTP-Unicast</tt:Stream><tt:Transport><tt:Protocol>RTSP</tt:Protocol></tt:Transport></trt:StreamSetup><trt:ProfileToken>{}</trt:ProfileToken></trt:GetStreamUri>",
+        escape(&profile)
+    ))
+    .await?;
+    element_text(&response, "Uri").ok_or_else(|| CameraError::Onvif(format!("no stream address for profile '{}'", profile)))
+}
+
+/// SOAP 1.2 envelope, signed with a WS-Security password digest when there
+/// are credentials
+fn envelope(username: Option<&str>, password: Option<&str>, body: &str) -> String {
+    let header = match username {
+        Some(username) => {
+            let mut nonce = [0u8; 16];
+            rand::thread_rng().fill_bytes(&mut nonce);
+            let created = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+            let mut digest = Sha1::new();
+            digest.update(nonce);
+            digest.update(created.as_bytes());
+            digest.update(password.unwrap_or_default().as_bytes());
+            let base64 = base64::engine::general_purpose::STANDARD;
+            format!(
+                concat!(
+                    r#"<s:Header><Security s:mustUnderstand="1" xmlns="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-wssecurity-secext-1.0.xsd">"#,
+                    r#"<UsernameToken><Username>{}</Username>"#,
+                    r#"<Password Type="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-username-token-profile-1.0#PasswordDigest">{}</Password>"#,
+                    r#"<Nonce EncodingType="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-soap-message-security-1.0#Base64Binary">{}</Nonce>"#,
+                    r#"<Created xmlns="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-wssecurity-utility-1.0.xsd">{}</Created>"#,
+                    r#"</UsernameToken></Security></s:Header>"#
+                ),
+                escape(username),
+                base64.encode(digest.finalize()),
+                base64.encode(nonce),
+                created
+            )
+        },
+        None => String::new(),
+    };
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            r#"<s:Envelope xmlns:s="http://www.w3.org/2003/05/soap-envelope" xmlns:trt="http://www.onvif.org/ver10/media/wsdl" xmlns:tt="http://www.onvif.org/ver10/schema">"#,
+            r#"{}<s:Body>{}</s:Body></s:Envelope>"#
+        ),
+        header, body
+    )
+}
+
+/// Elements with local name `name`, as their attributes and the text after
+/// the start tag
+fn elements<'a>(xml: &'a str, name: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+    xml.split('<').skip(1).filter_map(move |tag| {
+        let end = tag.find(|c: char| c.is_whitespace() || c == '>' || c == '/')?;
+        let close = tag.find('>')?;
+        (tag[..end].rsplit(':').next() == Some(name)).then(|| (&tag[end..close], &tag[close + 1..]))
+    })
+}
+
+/// Text of the first element with local name `name`
+fn element_text(xml: &str, name: &str) -> Option<String> {
+    elements(xml, name).next().map(|(_, text)| unescape(text.trim()))
+}
+
+/// Attribute of the first element with local name `name` that has it
+fn element_attribute(xml: &str, name: &str, attribute: &str) -> Option<String> {
+    let key = format!(" {}=\"", attribute);
+    elements(xml, name).find_map(|(attributes, _)| {
+        let value = &attributes[attributes.find(&key)? + key.len()..];
+        Some(unescape(&value[..value.find('"')?]))
+    })
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
@@ -0,0 +1,304 @@
+use crate::acoustic::{AcousticClassifier, AcousticDetection};
+use crate::extractors::{pcm_samples, MicrophoneExtractor};
+use crate::fusion::{Extracted, ExtractionError, FeatureExtractor};
+use crate::health::audio_quality;
+use crate::SensorInput;
+use serde::{Deserialize, Serialize};
+
+/// Microphone array geometry for direction-of-arrival estimation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MicArrayConfig {
+    /// Sensor type the interleaved multi-channel chunks arrive under
+    pub sensor_type: String,
+    /// Microphone positions in metres, x forward and y to the right of the
+    /// platform, in channel order
+    pub mic_positions: Vec<(f32, f32)>,
+    pub sample_rate: u32,
+    pub speed_of_sound: f32, // m/s
+    /// Azimuth search resolution (degrees)
+    pub resolution_deg: f32,
+}
+
+impl Default for MicArrayConfig {
+    fn default() -> Self {
+        // Four microphones on the arm tips of a small quadcopter
+        Self {
+            sensor_type: "microphone_array".to_string(),
+            mic_positions: vec![(0.12, 0.12), (0.12, -0.12), (-0.12, -0.12), (-0.12, 0.12)],
+            sample_rate: 16_000,
+            speed_of_sound: 343.0,
+            resolution_deg: 1.0,
+        }
+    }
+}
+
+/// Interleaved 16-bit PCM from a microphone array
+///
+/// Channel 0 provides levels and event classification; when an event is
+/// heard, GCC-PHAT across every microphone pair locates it.
+pub struct MicArrayExtractor {
+    array: MicArrayConfig,
+    levels: MicrophoneExtractor,
+}
+
+impl MicArrayExtractor {
+    pub fn new(array: MicArrayConfig) -> Self {
+        let levels = MicrophoneExtractor {
+            sample_rate: array.sample_rate,
+            classifier: AcousticClassifier {
+                sample_rate: array.sample_rate,
+                ..AcousticClassifier::default()
+            },
+            ..MicrophoneExtractor::default()
+        };
+        Self { array, levels }
+    }
+
+    /// Bearing in degrees clockwise from the platform's forward axis, with the
+    /// share of the GCC peak it explains (0.0-1.0)
+    pub fn estimate_bearing(&self, channels: &[Vec<f32>]) -> Option<(f32, f32)> {
+        let positions = &self.array.mic_positions;
+        let len = channels.iter().map(Vec::len).min()?;
+        if channels.len() < 2 || len < 2 {
+            return None;
+        }
+
+        let mut pairs = Vec::new();
+        for i in 0..channels.len() {
+            for j in i + 1..channels.len() {
+                pairs.push((i, j, gcc_phat(&channels[i][..len], &channels[j][..len])));
+            }
+        }
+
+        let samples_per_metre = self.array.sample_rate as f32 / self.array.speed_of_sound;
+        let steps = (360.0 / self.array.resolution_deg.max(0.1)).round() as usize;
+        let mut scores = (0..steps).map(|step| {
+            let azimuth = step as f32 * 360.0 / steps as f32;
+            let (dx, dy) = (azimuth.to_radians().cos(), azimuth.to_radians().sin());
+            // Channel i lags channel j by the extra path to microphone i
+            let score: f32 = pairs
+                .iter()
+                .map(|(i, j, correlation)| {
+                    let baseline = (positions[*j].0 - positions[*i].0) * dx + (positions[*j].1 - positions[*i].1) * dy;
+                    interpolate(correlation, baseline * samples_per_metre)
+                })
+                .sum();
+            (azimuth, score)
+        });
+
+        let first = scores.next()?;
+        let (mut best, mut total, mut count) = (first, first.1.max(0.0), 1);
+        for candidate in scores {
+            total += candidate.1.max(0.0);
+            count += 1;
+            if candidate.1 > best.1 {
+                best = candidate;
+            }
+        }
+        let mean = total / count as f32;
+        let confidence = if best.1 > 0.0 { (1.0 - mean / best.1).clamp(0.0, 1.0) } else { 0.0 };
+        Some((best.0, confidence))
+    }
+
+    /// Samples around an event: a little lead-in, then the event itself
+    fn event_window(&self, detection: &AcousticDetection, len: usize) -> std::ops::Range<usize> {
+        let per_ms = self.array.sample_rate as usize / 1000;
+        let start = (detection.offset_ms as usize * per_ms).saturating_sub(20 * per_ms).min(len);
+        start..(start + 300 * per_ms).min(len)
+    }
+}
+
+impl FeatureExtractor for MicArrayExtractor {
+    fn sensor_type(&self) -> &str {
+        &self.array.sensor_type
+    }
+
+    fn extract(&self, input: &SensorInput) -> Result<Extracted, ExtractionError> {
+        let channel_count = self.array.mic_positions.len().max(1);
+        let samples = pcm_samples(self.sensor_type(), &input.data)?;
+        if !samples.len().is_multiple_of(channel_count) {
+            return Err(ExtractionError::invalid(
+                self.sensor_type(),
+                format!("expected {} interleaved channels", channel_count),
+            ));
+        }
+        let channels: Vec<Vec<f32>> = (0..channel_count)
+            .map(|channel| samples.iter().skip(channel).step_by(channel_count).copied().collect())
+            .collect();
+
+        let mut audio = self.levels.analyze(&channels[0]);
+        let loudest = audio
+            .acoustic_events
+            .iter()
+            .max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+            .cloned();
+        if let Some(detection) = loudest {
+            let window = self.event_window(&detection, channels[0].len());
+            let segments: Vec<Vec<f32>> = channels.iter().map(|channel| channel[window.clone()].to_vec()).collect();
+            if let Some((bearing, confidence)) = self.estimate_bearing(&segments) {
+                // A flat response means reverberation or several sources
+                if confidence >= 0.3 {
+                    audio.bearing_deg = Some(bearing);
+                }
+            }
+        }
+        Ok(Extracted::Audio(audio))
+    }
+
+    /// Judged across every channel, so one dead microphone drags the score down
+    fn quality(&self, input: &SensorInput) -> f32 {
+        let channel_count = self.array.mic_positions.len().max(1);
+        let Ok(samples) = pcm_samples(self.sensor_type(), &input.data) else { return 0.0 };
+        (0..channel_count)
+            .map(|channel| audio_quality(&samples.iter().skip(channel).step_by(channel_count).copied().collect::<Vec<_>>()))
+            .fold(1.0, f32::min)
+    }
+}
+
+/// Generalised cross-correlation with phase transform, indexed by lag with
+/// negative lags wrapped to the end
+fn gcc_phat(a: &[f32], b: &[f32]) -> Vec<f32> {
+    let n = (a.len() * 2).next_power_of_two();
+    let spectrum = |signal: &[f32]| {
+        let mut buffer: Vec<(f32, f32)> = signal.iter().map(|s| (*s, 0.0)).collect();
+        buffer.resize(n, (0.0, 0.0));
+        fft(&mut buffer, false);
+        buffer
+    };
+    let (spectrum_a, spectrum_b) = (spectrum(a), spectrum(b));
+
+    let mut cross: Vec<(f32, f32)> = spectrum_a
+        .iter()
+        .zip(&spectrum_b)
+        .map(|(&(ar, ai), &(br, bi))| {
+            // A times the conjugate of B, whitened to unit magnitude
+            let (re, im) = (ar * br + ai * bi, ai * br - ar * bi);
+            let magnitude = re.hypot(im).max(1e-12);
+            (re / magnitude, im / magnitude)
+        })
+        .collect();
+    fft(&mut cross, true);
+    cross.into_iter().map(|(re, _)| re / n as f32).collect()
+}
+
+/// Correlation at a fractional lag
+fn interpolate(correlation: &[f32], lag: f32) -> f32 {
+    let n = correlation.len() as isize;
+    let at = |index: isize| correlation[index.rem_euclid(n) as usize];
+    let floor = lag.floor();
+    let fraction = lag - floor;
+    at(floor as isize) * (1.0 - fraction) + at(floor as isize + 1) * fraction
+}
+
+/// In-place iterative radix-2 FFT; `buffer.len()` must be a power of two
+fn fft(buffer: &mut [(f32, f32)], inverse: bool) {
+    let n = buffer.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            buffer.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * std::f32::consts::TAU / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (wr, wi) = ((angle * k as f32).cos(), (angle * k as f32).sin());
+                let (ur, ui) = buffer[start + k];
+                let (xr, xi) = buffer[start + k + len / 2];
+                let (vr, vi) = (xr * wr - xi * wi, xr * wi + xi * wr);
+                buffer[start + k] = (ur + vr, ui + vi);
+                buffer[start + k + len / 2] = (ur - vr, ui - vi);
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Broadband test signal at time `t` seconds: a few dozen tones with
+    /// fixed pseudo-random frequencies and phases
+    fn broadband(t: f32) -> f32 {
+        let mut seed = 0x2545_f491_u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 8) as f32 / (1u32 << 24) as f32
+        };
+        (0..48)
+            .map(|_| {
+                let (frequency, phase) = (200.0 + next() * 5800.0, next() * std::f32::consts::TAU);
+                (std::f32::consts::TAU * frequency * t + phase).sin()
+            })
+            .sum()
+    }
+
+    /// What each microphone hears of a far source at `azimuth` degrees
+    fn plane_wave(array: &MicArrayConfig, azimuth: f32, len: usize) -> Vec<Vec<f32>> {
+        let (dx, dy) = (azimuth.to_radians().cos(), azimuth.to_radians().sin());
+        array
+            .mic_positions
+            .iter()
+            .map(|(x, y)| {
+                // Microphones nearer the source hear it earlier
+                let lead = (x * dx + y * dy) / array.speed_of_sound;
+                (0..len).map(|n| broadband(n as f32 / array.sample_rate as f32 + lead)).collect()
+            })
+            .collect()
+    }
+
+    fn angle_between(a: f32, b: f32) -> f32 {
+        let difference = (a - b).rem_euclid(360.0);
+        difference.min(360.0 - difference)
+    }
+
+    #[test]
+    fn gcc_phat_peaks_at_the_delay() {
+        let b: Vec<f32> = (0..512).map(|n| broadband(n as f32 / 16_000.0)).collect();
+        let a: Vec<f32> = (0..512).map(|n| broadband((n as f32 - 7.0) / 16_000.0)).collect();
+        let correlation = gcc_phat(&a, &b);
+        let peak = (0..correlation.len()).max_by(|x, y| correlation[*x].total_cmp(&correlation[*y])).unwrap();
+        assert_eq!(peak, 7);
+
+        // Negative lags wrap to the end
+        let correlation = gcc_phat(&b, &a);
+        let peak = (0..correlation.len()).max_by(|x, y| correlation[*x].total_cmp(&correlation[*y])).unwrap();
+        assert_eq!(peak, correlation.len() - 7);
+    }
+
+    #[test]
+    fn interpolate_blends_neighbouring_lags_and_wraps() {
+        let correlation = [1.0, 3.0, 0.0, 5.0];
+        assert_eq!(interpolate(&correlation, 0.5), 2.0);
+        assert_eq!(interpolate(&correlation, -1.0), 5.0);
+    }
+
+    #[test]
+    fn bearing_of_a_plane_wave_is_recovered() {
+        let extractor = MicArrayExtractor::new(MicArrayConfig::default());
+        for azimuth in [0.0, 45.0, 130.0, 270.0] {
+            let channels = plane_wave(&extractor.array, azimuth, 2048);
+            let (bearing, confidence) = extractor.estimate_bearing(&channels).unwrap();
+            assert!(angle_between(bearing, azimuth) <= 5.0, "{} estimated as {}", azimuth, bearing);
+            assert!(confidence >= 0.3, "confidence {} at {}", confidence, azimuth);
+        }
+    }
+
+    #[test]
+    fn bearing_needs_two_channels() {
+        let extractor = MicArrayExtractor::new(MicArrayConfig::default());
+        assert!(extractor.estimate_bearing(&[vec![0.0; 64]]).is_none());
+        assert!(extractor.estimate_bearing(&[]).is_none());
+    }
+}
@@ -0,0 +1,534 @@
+//! Evidence recording with chain of custody
+
+use crate::{SensorInput, ThreatAssessment};
+use chrono::{DateTime, Utc};
+use dark_phoenix_core::{Keyring, ThreatLevel, VaultError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Name of the custody manifest in each capture directory
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvidenceConfig {
+    /// Where capture directories are kept
+    pub dir: PathBuf,
+    /// Assessments at or above this level start or extend a capture
+    pub trigger_level: ThreatLevel,
+    /// Recording kept from before the trigger (seconds)
+    pub pre_roll_secs: u64,
+    /// Recording kept after the last triggering assessment (seconds)
+    pub post_roll_secs: u64,
+    /// Length of each video or audio file (seconds)
+    pub segment_secs: u64,
+    /// Sensor types recorded as video
+    pub video_sensors: Vec<String>,
+    /// Sensor types recorded as audio
+    pub audio_sensors: Vec<String>,
+    /// Who the custody log names as having made the recording
+    pub recorder: String,
+    /// Areas of the camera view blacked out before anything is stored,
+    /// e.g. a neighbour's window
+    #[cfg(feature = "redaction")]
+    pub no_record: Vec<crate::DetectionZone>,
+}
+
+impl EvidenceConfig {
+    fn kind_of(&self, sensor_type: &str) -> Option<EvidenceKind> {
+        if self.video_sensors.iter().any(|sensor| sensor == sensor_type) {
+            Some(EvidenceKind::Video)
+        } else if self.audio_sensors.iter().any(|sensor| sensor == sensor_type) {
+            Some(EvidenceKind::Audio)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for EvidenceConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("evidence"),
+            trigger_level: ThreatLevel::Orange,
+            pre_roll_secs: 10,
+            post_roll_secs: 30,
+            segment_secs: 10,
+            video_sensors: vec!["camera".to_string()],
+            audio_sensors: vec!["microphone".to_string()],
+            recorder: "drone".to_string(),
+            #[cfg(feature = "redaction")]
+            no_record: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvidenceKind {
+    Video,
+    Audio,
+    /// Every sensor's latest input at the time of an assessment
+    SensorSnapshot,
+}
+
+/// One file in a capture
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvidenceItem {
+    /// Relative to the capture directory
+    pub file: String,
+    pub kind: EvidenceKind,
+    /// Absent for snapshots, which cover every sensor
+    pub sensor: Option<String>,
+    /// Lower-case hex SHA-256 of the file
+    pub sha256: String,
+    pub bytes: u64,
+    pub started: DateTime<Utc>,
+    pub ended: DateTime<Utc>,
+    /// The assessment that caused this file to be kept
+    pub assessment_id: Uuid,
+    /// Bystanders' faces and number plates have been blurred since capture
+    #[serde(default)]
+    pub redacted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CustodyAction {
+    Opened,
+    Closed,
+    Exported,
+    Verified,
+    /// Faces and number plates blurred; the detail gives the old and new hash
+    Redacted,
+    /// Files deleted by the retention policy
+    Purged,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustodyEntry {
+    pub timestamp: DateTime<Utc>,
+    pub actor: String,
+    pub action: CustodyAction,
+    pub detail: String,
+}
+
+/// A capture's contents and every hand it has passed through
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustodyManifest {
+    pub capture_id: Uuid,
+    /// Highest threat level assessed during the capture
+    pub threat_level: ThreatLevel,
+    pub assessment_ids: Vec<Uuid>,
+    pub opened: DateTime<Utc>,
+    /// Absent while still recording
+    pub closed: Option<DateTime<Utc>>,
+    pub items: Vec<EvidenceItem>,
+    pub custody: Vec<CustodyEntry>,
+    /// When the retention policy deleted the files; the manifest is kept
+    #[serde(default)]
+    pub purged: Option<DateTime<Utc>>,
+}
+
+impl CustodyManifest {
+    pub(crate) fn log(&mut self, actor: &str, action: CustodyAction, detail: String) {
+        self.custody.push(CustodyEntry {
+            timestamp: Utc::now(),
+            actor: actor.to_string(),
+            action,
+            detail,
+        });
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum EvidenceError {
+    #[error("evidence I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("evidence manifest is malformed: {0}")]
+    Manifest(#[from] serde_json::Error),
+    #[error("no capture {0}")]
+    UnknownCapture(Uuid),
+    #[error("capture {0} is still recording")]
+    StillRecording(Uuid),
+    #[error("{file} does not match its manifest hash (expected {expected}, found {found})")]
+    Tampered { file: String, expected: String, found: String },
+    #[error("{0} is listed in the manifest but missing")]
+    Missing(String),
+    #[error("{0} is not a valid segment file")]
+    Corrupt(String),
+    #[error("capture {0} has been purged by the retention policy")]
+    Purged(Uuid),
+    #[error("{0}: {1}")]
+    Sealed(String, VaultError),
+}
+
+/// Frames of one sensor waiting to be written as a segment
+struct Segment {
+    kind: EvidenceKind,
+    assessment_id: Uuid,
+    frames: Vec<SensorInput>,
+}
+
+struct ActiveCapture {
+    manifest: CustodyManifest,
+    dir: PathBuf,
+    /// Recording stops once an assessment after this is below the trigger
+    until: DateTime<Utc>,
+    segments: HashMap<String, Segment>,
+}
+
+/// Turns sensor inputs and assessments into captures on disk
+pub struct EvidenceRecorder {
+    config: EvidenceConfig,
+    /// Recent video and audio, kept in case a capture opens
+    pre_roll: HashMap<String, VecDeque<SensorInput>>,
+    /// Every sensor's newest input, for snapshots
+    latest: HashMap<String, SensorInput>,
+    active: Option<ActiveCapture>,
+    /// Seals files before they are written
+    keyring: Option<Arc<Keyring>>,
+}
+
+impl EvidenceRecorder {
+    pub fn new(config: EvidenceConfig) -> Result<Self, EvidenceError> {
+        std::fs::create_dir_all(&config.dir)?;
+        Ok(Self {
+            config,
+            pre_roll: HashMap::new(),
+            latest: HashMap::new(),
+            active: None,
+            keyring: None,
+        })
+    }
+
+    /// Encrypt every file recorded from now on
+    pub fn with_keyring(mut self, keyring: Arc<Keyring>) -> Self {
+        self.keyring = Some(keyring);
+        self
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Buffer or record a sensor input
+    pub fn record_input(&mut self, input: &SensorInput) -> Result<(), EvidenceError> {
+        #[cfg(feature = "redaction")]
+        let masked;
+        #[cfg(feature = "redaction")]
+        let input = if self.config.no_record.is_empty() || self.config.kind_of(&input.sensor_type) != Some(EvidenceKind::Video) {
+            input
+        } else {
+            masked = crate::redaction::mask_input(input, &self.config.no_record);
+            &masked
+        };
+        self.latest.insert(input.sensor_type.clone(), input.clone());
+        let Some(kind) = self.config.kind_of(&input.sensor_type) else {
+            return Ok(());
+        };
+        let Some(active) = &mut self.active else {
+            let buffer = self.pre_roll.entry(input.sensor_type.clone()).or_default();
+            buffer.push_back(input.clone());
+            let horizon = input.timestamp - chrono::Duration::seconds(self.config.pre_roll_secs as i64);
+            while buffer.front().is_some_and(|oldest| oldest.timestamp < horizon) {
+                buffer.pop_front();
+            }
+            return Ok(());
+        };
+        let segment_length = chrono::Duration::seconds(self.config.segment_secs as i64);
+        let full = active
+            .segments
+            .get(&input.sensor_type)
+            .and_then(|segment| segment.frames.first())
+            .is_some_and(|first| input.timestamp - first.timestamp >= segment_length);
+        if full {
+            Self::write_segment(active, &input.sensor_type, self.keyring.as_deref())?;
+        }
+        let assessment_id = *active.manifest.assessment_ids.last().expect("a capture opens with an assessment");
+        active
+            .segments
+            .entry(input.sensor_type.clone())
+            .or_insert_with(|| Segment {
+                kind,
+                assessment_id,
+                frames: Vec::new(),
+            })
+            .frames
+            .push(input.clone());
+        Ok(())
+    }
+
+    /// Open or extend a capture for an assessment at the trigger level, or
+    /// close the open one once its post-roll has run out; returns the
+    /// manifest of a capture that closed
+    pub fn observe(&mut self, assessment: &ThreatAssessment, now: DateTime<Utc>) -> Result<Option<CustodyManifest>, EvidenceError> {
+        if assessment.threat_level < self.config.trigger_level {
+            return match &self.active {
+                Some(active) if now >= active.until => self.close(now),
+                _ => Ok(None),
+            };
+        }
+        let post_roll = chrono::Duration::seconds(self.config.post_roll_secs as i64);
+        let active = match &mut self.active {
+            Some(active) => active,
+            None => {
+                let capture_id = Uuid::new_v4();
+                let dir = self.config.dir.join(capture_id.to_string());
+                std::fs::create_dir_all(&dir)?;
+                let mut manifest = CustodyManifest {
+                    capture_id,
+                    threat_level: assessment.threat_level,
+                    assessment_ids: Vec::new(),
+                    opened: now,
+                    closed: None,
+                    items: Vec::new(),
+                    custody: Vec::new(),
+                    purged: None,
+                };
+                manifest.log(
+                    &self.config.recorder,
+                    CustodyAction::Opened,
+                    format!("{} assessment {}: {}", assessment.threat_level.as_str(), assessment.id, assessment.description),
+                );
+                tracing::warn!("🎥 Recording evidence to {}", dir.display());
+                let mut segments = HashMap::new();
+                for (sensor, buffer) in self.pre_roll.drain() {
+                    if let Some(kind) = self.config.kind_of(&sensor) {
+                        segments.insert(
+                            sensor,
+                            Segment {
+                                kind,
+                                assessment_id: assessment.id,
+                                frames: buffer.into(),
+                            },
+                        );
+                    }
+                }
+                self.active.insert(ActiveCapture {
+                    manifest,
+                    dir,
+                    until: now,
+                    segments,
+                })
+            },
+        };
+        active.until = now + post_roll;
+        active.manifest.threat_level = active.manifest.threat_level.max(assessment.threat_level);
+        active.manifest.assessment_ids.push(assessment.id);
+
+        let mut inputs: Vec<&SensorInput> = self.latest.values().collect();
+        inputs.sort_by(|a, b| a.sensor_type.cmp(&b.sensor_type));
+        let snapshot = serde_json::to_vec_pretty(&serde_json::json!({ "assessment": assessment, "inputs": inputs }))?;
+        let file = format!("snapshot-{}.json", assessment.id);
+        let (sha256, bytes) = write_file(&active.dir, &file, &snapshot, self.keyring.as_deref())?;
+        active.manifest.items.push(EvidenceItem {
+            file,
+            kind: EvidenceKind::SensorSnapshot,
+            sensor: None,
+            sha256,
+            bytes,
+            started: assessment.timestamp,
+            ended: assessment.timestamp,
+            assessment_id: assessment.id,
+            redacted: false,
+        });
+        write_manifest(&active.dir, &active.manifest)?;
+        Ok(None)
+    }
+
+    /// Write out the open capture, e.g. on shutdown
+    pub fn close(&mut self, now: DateTime<Utc>) -> Result<Option<CustodyManifest>, EvidenceError> {
+        let Some(mut active) = self.active.take() else {
+            return Ok(None);
+        };
+        let sensors: Vec<String> = active.segments.keys().cloned().collect();
+        for sensor in sensors {
+            Self::write_segment(&mut active, &sensor, self.keyring.as_deref())?;
+        }
+        active.manifest.closed = Some(now);
+        let detail = format!("{} files", active.manifest.items.len());
+        active.manifest.log(&self.config.recorder, CustodyAction::Closed, detail);
+        write_manifest(&active.dir, &active.manifest)?;
+        tracing::info!("🎥 Evidence capture {} closed with {} files", active.manifest.capture_id, active.manifest.items.len());
+        Ok(Some(active.manifest))
+    }
+
+    /// Manifest of the capture being recorded, if any
+    pub fn current(&self) -> Option<&CustodyManifest> {
+        self.active.as_ref().map(|active| &active.manifest)
+    }
+
+    /// Manifests of every capture on disk, oldest first
+    pub fn captures(&self) -> Result<Vec<CustodyManifest>, EvidenceError> {
+        list_captures(&self.config.dir)
+    }
+
+    /// Copy a closed capture and its manifest into `dest`, recording the
+    /// export in the custody log of both copies
+    pub fn export(&self, capture_id: Uuid, dest: &Path, actor: &str) -> Result<CustodyManifest, EvidenceError> {
+        if self.active.as_ref().is_some_and(|active| active.manifest.capture_id == capture_id) {
+            return Err(EvidenceError::StillRecording(capture_id));
+        }
+        export_bundle(&self.config.dir, capture_id, dest, actor)
+    }
+
+    fn write_segment(active: &mut ActiveCapture, sensor: &str, keyring: Option<&Keyring>) -> Result<(), EvidenceError> {
+        let Some(segment) = active.segments.remove(sensor) else {
+            return Ok(());
+        };
+        let (Some(first), Some(last)) = (segment.frames.first(), segment.frames.last()) else {
+            return Ok(());
+        };
+        let data = encode_segment(segment.frames.iter().map(|frame| (frame.timestamp, frame.data.as_slice())));
+        let file = format!("{}-{}.seg", sensor, first.timestamp.format("%Y%m%dT%H%M%S%.3fZ"));
+        let (sha256, bytes) = write_file(&active.dir, &file, &data, keyring)?;
+        active.manifest.items.push(EvidenceItem {
+            file,
+            kind: segment.kind,
+            sensor: Some(sensor.to_string()),
+            sha256,
+            bytes,
+            started: first.timestamp,
+            ended: last.timestamp,
+            assessment_id: segment.assessment_id,
+            redacted: false,
+        });
+        write_manifest(&active.dir, &active.manifest)
+    }
+}
+
+/// Manifests of every capture under `dir`, oldest first
+pub fn list_captures(dir: &Path) -> Result<Vec<CustodyManifest>, EvidenceError> {
+    let mut captures = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path().join(MANIFEST_FILE);
+        if path.is_file() {
+            captures.push(read_manifest(&path)?);
+        }
+    }
+    captures.sort_by_key(|manifest| manifest.opened);
+    Ok(captures)
+}
+
+/// Copy capture `capture_id` from the evidence `dir` into `dest`, checking
+/// every hash first and logging the export as done by `actor`
+pub fn export_bundle(dir: &Path, capture_id: Uuid, dest: &Path, actor: &str) -> Result<CustodyManifest, EvidenceError> {
+    let source = dir.join(capture_id.to_string());
+    if !source.join(MANIFEST_FILE).is_file() {
+        return Err(EvidenceError::UnknownCapture(capture_id));
+    }
+    let mut manifest = verify_bundle(&source)?;
+    if manifest.purged.is_some() {
+        return Err(EvidenceError::Purged(capture_id));
+    }
+    if manifest.closed.is_none() {
+        return Err(EvidenceError::StillRecording(capture_id));
+    }
+    let bundle = dest.join(capture_id.to_string());
+    std::fs::create_dir_all(&bundle)?;
+    for item in &manifest.items {
+        std::fs::copy(source.join(&item.file), bundle.join(&item.file))?;
+    }
+    manifest.log(actor, CustodyAction::Exported, format!("{} files to {}", manifest.items.len(), bundle.display()));
+    write_manifest(&source, &manifest)?;
+    write_manifest(&bundle, &manifest)?;
+    tracing::info!("🎥 Exported evidence capture {} to {}", capture_id, bundle.display());
+    Ok(manifest)
+}
+
+/// Check every file in the capture or bundle directory `dir` against its
+/// manifest hash; a purged capture has only its manifest left to check
+pub fn verify_bundle(dir: &Path) -> Result<CustodyManifest, EvidenceError> {
+    let manifest = read_manifest(&dir.join(MANIFEST_FILE))?;
+    for item in manifest.items.iter().filter(|_| manifest.purged.is_none()) {
+        let data = std::fs::read(dir.join(&item.file)).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => EvidenceError::Missing(item.file.clone()),
+            _ => EvidenceError::Io(e),
+        })?;
+        let found = hex::encode(Sha256::digest(&data));
+        if found != item.sha256 {
+            return Err(EvidenceError::Tampered {
+                file: item.file.clone(),
+                expected: item.sha256.clone(),
+                found,
+            });
+        }
+    }
+    Ok(manifest)
+}
+
+/// Verify a bundle and record in its custody log that `actor` did
+pub fn attest_bundle(dir: &Path, actor: &str) -> Result<CustodyManifest, EvidenceError> {
+    let mut manifest = verify_bundle(dir)?;
+    manifest.log(actor, CustodyAction::Verified, format!("{} files match their hashes", manifest.items.len()));
+    write_manifest(dir, &manifest)?;
+    Ok(manifest)
+}
+
+/// Frames in the `.seg` layout described at the top of this module
+pub fn encode_segment<'a>(frames: impl IntoIterator<Item = (DateTime<Utc>, &'a [u8])>) -> Vec<u8> {
+    let mut data = Vec::new();
+    for (timestamp, frame) in frames {
+        data.extend_from_slice(&timestamp.timestamp_millis().to_be_bytes());
+        data.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+        data.extend_from_slice(frame);
+    }
+    data
+}
+
+/// Split a `.seg` file back into its frames; `None` if it is cut short
+pub fn decode_segment(mut data: &[u8]) -> Option<Vec<(DateTime<Utc>, Vec<u8>)>> {
+    let mut frames = Vec::new();
+    while !data.is_empty() {
+        let millis = i64::from_be_bytes(data.get(..8)?.try_into().ok()?);
+        let length = u32::from_be_bytes(data.get(8..12)?.try_into().ok()?) as usize;
+        let frame = data.get(12..12 + length)?;
+        frames.push((DateTime::from_timestamp_millis(millis)?, frame.to_vec()));
+        data = &data[12 + length..];
+    }
+    Some(frames)
+}
+
+/// Write an evidence file, sealed if there is a keyring, returning the
+/// hash and size of what was stored
+pub(crate) fn write_file(dir: &Path, file: &str, data: &[u8], keyring: Option<&Keyring>) -> Result<(String, u64), EvidenceError> {
+    let sealed;
+    let data = match keyring {
+        Some(keyring) => {
+            sealed = keyring.seal(data);
+            &sealed
+        },
+        None => data,
+    };
+    std::fs::write(dir.join(file), data)?;
+    Ok((hex::encode(Sha256::digest(data)), data.len() as u64))
+}
+
+/// Read an evidence file, opening it if it was sealed
+#[cfg(feature = "redaction")]
+pub(crate) fn read_file(path: &Path, keyring: Option<&Keyring>) -> Result<Vec<u8>, EvidenceError> {
+    let data = std::fs::read(path)?;
+    match keyring {
+        Some(keyring) => keyring.open(&data).map_err(|e| EvidenceError::Sealed(path.display().to_string(), e)),
+        None if dark_phoenix_core::vault::is_sealed(&data) => Err(EvidenceError::Sealed(path.display().to_string(), VaultError::Locked)),
+        None => Ok(data),
+    }
+}
+
+pub(crate) fn read_manifest(path: &Path) -> Result<CustodyManifest, EvidenceError> {
+    Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+}
+
+/// Replace the manifest whole, so a crash never leaves half of one
+pub(crate) fn write_manifest(dir: &Path, manifest: &CustodyManifest) -> Result<(), EvidenceError> {
+    let temp = dir.join(format!("{}.tmp", MANIFEST_FILE));
+    std::fs::write(&temp, serde_json::to_vec_pretty(manifest)?)?;
+    std::fs::rename(temp, dir.join(MANIFEST_FILE))?;
+    Ok(())
+}
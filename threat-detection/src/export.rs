@@ -0,0 +1,155 @@
+use crate::ThreatAssessment;
+use chrono::{DateTime, Utc};
+use dark_phoenix_core::ThreatLevel;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/// Which assessments to export and how much personal data to keep
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportOptions {
+    /// Drop locations, speech keywords, biometrics and whitelisted identities
+    pub strip_pii: bool,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub min_level: ThreatLevel,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            strip_pii: true,
+            since: None,
+            until: None,
+            min_level: ThreatLevel::Green,
+        }
+    }
+}
+
+impl ExportOptions {
+    fn admits(&self, assessment: &ThreatAssessment) -> bool {
+        assessment.threat_level >= self.min_level
+            && self.since.is_none_or(|since| assessment.timestamp >= since)
+            && self.until.is_none_or(|until| assessment.timestamp < until)
+    }
+
+    /// The assessments to export, stripped as configured
+    fn select<'a>(&'a self, assessments: impl IntoIterator<Item = &'a ThreatAssessment> + 'a) -> impl Iterator<Item = ThreatAssessment> + 'a {
+        assessments.into_iter().filter(|assessment| self.admits(assessment)).map(|assessment| {
+            let mut assessment = assessment.clone();
+            if self.strip_pii {
+                strip_pii(&mut assessment);
+            }
+            assessment
+        })
+    }
+}
+
+/// Remove everything that could identify or locate a person
+pub fn strip_pii(assessment: &mut ThreatAssessment) {
+    assessment.position = None;
+    assessment.evidence.biometric_data = None;
+    if let Some(audio) = &mut assessment.evidence.audio_data {
+        audio.keyword_matches.clear();
+    }
+    if let Some(visual) = &mut assessment.evidence.visual_data {
+        for detection in &mut visual.object_detections {
+            detection.known_person = None;
+        }
+    }
+}
+
+/// One assessment per line; returns how many were written
+pub fn export_jsonl<'a>(
+    assessments: impl IntoIterator<Item = &'a ThreatAssessment> + 'a,
+    mut writer: impl Write,
+    options: &'a ExportOptions,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut count = 0;
+    for assessment in options.select(assessments) {
+        serde_json::to_writer(&mut writer, &assessment)?;
+        writer.write_all(b"\n")?;
+        count += 1;
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+/// Columnar export for training pipelines (`parquet` feature)
+///
+/// Scalar fields get their own columns; the full evidence is kept as a JSON
+/// string column so no feature is lost to flattening.
+#[cfg(feature = "parquet")]
+pub fn export_parquet<'a>(
+    assessments: impl IntoIterator<Item = &'a ThreatAssessment> + 'a,
+    writer: impl Write + Send,
+    options: &'a ExportOptions,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, FloatType, Int32Type, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::sync::Arc;
+
+    const SCHEMA: &str = "message threat_assessment {
+        REQUIRED BYTE_ARRAY id (UTF8);
+        REQUIRED INT64 timestamp (TIMESTAMP_MILLIS);
+        REQUIRED INT32 threat_level;
+        REQUIRED FLOAT confidence;
+        REQUIRED BYTE_ARRAY threat_types (UTF8);
+        OPTIONAL DOUBLE latitude;
+        OPTIONAL DOUBLE longitude;
+        OPTIONAL BYTE_ARRAY zone (UTF8);
+        REQUIRED BYTE_ARRAY description (UTF8);
+        REQUIRED BYTE_ARRAY evidence (UTF8);
+    }";
+
+    let rows: Vec<ThreatAssessment> = options.select(assessments).collect();
+    let text = |value: &str| ByteArray::from(value.as_bytes().to_vec());
+    // Values present plus a definition level per row for optional columns
+    let optional = |values: Vec<Option<f64>>| {
+        let levels = values.iter().map(|value| value.is_some() as i16).collect::<Vec<_>>();
+        (values.into_iter().flatten().collect::<Vec<_>>(), levels)
+    };
+
+    let ids: Vec<ByteArray> = rows.iter().map(|row| text(&row.id.to_string())).collect();
+    let timestamps: Vec<i64> = rows.iter().map(|row| row.timestamp.timestamp_millis()).collect();
+    let levels: Vec<i32> = rows.iter().map(|row| row.threat_level as i32).collect();
+    let confidences: Vec<f32> = rows.iter().map(|row| row.confidence).collect();
+    let types: Vec<ByteArray> = rows
+        .iter()
+        .map(|row| text(&row.threat_types.iter().map(|t| format!("{:?}", t)).collect::<Vec<_>>().join(",")))
+        .collect();
+    let (latitudes, latitude_levels) = optional(rows.iter().map(|row| row.position.as_ref().map(|p| p.latitude)).collect());
+    let (longitudes, longitude_levels) = optional(rows.iter().map(|row| row.position.as_ref().map(|p| p.longitude)).collect());
+    let zones: Vec<ByteArray> = rows.iter().filter_map(|row| row.zone.as_deref().map(text)).collect();
+    let zone_levels: Vec<i16> = rows.iter().map(|row| row.zone.is_some() as i16).collect();
+    let descriptions: Vec<ByteArray> = rows.iter().map(|row| text(&row.description)).collect();
+    let evidence = rows
+        .iter()
+        .map(|row| serde_json::to_string(&row.evidence).map(|json| text(&json)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let schema = Arc::new(parse_message_type(SCHEMA)?);
+    let mut file = SerializedFileWriter::new(writer, schema, Arc::new(WriterProperties::builder().build()))?;
+    let mut row_group = file.next_row_group()?;
+    let mut index = 0;
+    while let Some(mut column) = row_group.next_column()? {
+        match index {
+            0 => column.typed::<ByteArrayType>().write_batch(&ids, None, None)?,
+            1 => column.typed::<Int64Type>().write_batch(&timestamps, None, None)?,
+            2 => column.typed::<Int32Type>().write_batch(&levels, None, None)?,
+            3 => column.typed::<FloatType>().write_batch(&confidences, None, None)?,
+            4 => column.typed::<ByteArrayType>().write_batch(&types, None, None)?,
+            5 => column.typed::<DoubleType>().write_batch(&latitudes, Some(&latitude_levels), None)?,
+            6 => column.typed::<DoubleType>().write_batch(&longitudes, Some(&longitude_levels), None)?,
+            7 => column.typed::<ByteArrayType>().write_batch(&zones, Some(&zone_levels), None)?,
+            8 => column.typed::<ByteArrayType>().write_batch(&descriptions, None, None)?,
+            _ => column.typed::<ByteArrayType>().write_batch(&evidence, None, None)?,
+        };
+        column.close()?;
+        index += 1;
+    }
+    row_group.close()?;
+    file.close()?;
+    Ok(rows.len())
+}
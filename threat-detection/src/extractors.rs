@@ -0,0 +1,275 @@
+use crate::acoustic::AcousticClassifier;
+use crate::fusion::{Extracted, ExtractionError, FeatureExtractor};
+use crate::health::{audio_quality, image_quality};
+use crate::{AudioEvidence, MovementEvidence, SensorInput, ThreatType, VisualEvidence};
+use serde::{Deserialize, Serialize};
+
+/// Threat type a detected object or sound label points to
+pub fn threat_type_for(label: &str) -> Option<ThreatType> {
+    match label.to_lowercase().as_str() {
+        "knife" | "gun" | "pistol" | "handgun" | "rifle" | "firearm" | "gunshot, gunfire" | "machine gun" => {
+            Some(ThreatType::WeaponDetected)
+        },
+        "baseball bat" | "bat" | "crowbar" => Some(ThreatType::WeaponDetected),
+        "shout" | "yell" | "screaming" | "fighting" => Some(ThreatType::PhysicalAggression),
+        "car" | "truck" | "motorcycle" => Some(ThreatType::VehicleThreat),
+        "fire" | "smoke" | "smoke detector, smoke alarm" => Some(ThreatType::EnvironmentalHazard),
+        _ => None,
+    }
+}
+
+/// Encoded camera frame (PNG, JPEG, ...) - derives scene lighting
+///
+/// Object detection needs a model, so detections stay empty until an
+/// inference backend is registered for the `camera` sensor type instead.
+pub struct CameraExtractor;
+
+impl FeatureExtractor for CameraExtractor {
+    fn sensor_type(&self) -> &str {
+        "camera"
+    }
+
+    fn extract(&self, input: &SensorInput) -> Result<Extracted, ExtractionError> {
+        let frame = image::load_from_memory(&input.data)
+            .map_err(|e| ExtractionError::invalid(self.sensor_type(), format!("undecodable frame: {}", e)))?
+            .to_luma8();
+
+        let pixels = frame.as_raw();
+        let brightness = if pixels.is_empty() {
+            0.0
+        } else {
+            pixels.iter().map(|&p| p as f32).sum::<f32>() / (pixels.len() as f32 * 255.0)
+        };
+        let lighting = match brightness {
+            b if b < 0.15 => "Dark",
+            b if b < 0.35 => "Dim",
+            b if b > 0.85 => "Overexposed",
+            _ => "Good",
+        };
+
+        Ok(Extracted::Visual(VisualEvidence {
+            object_detections: Vec::new(),
+            body_language_score: 0.0,
+            weapon_confidence: 0.0,
+            crowd_density: 0,
+            lighting_conditions: lighting.to_string(),
+        }))
+    }
+
+    fn quality(&self, input: &SensorInput) -> f32 {
+        image::load_from_memory(&input.data).map_or(0.0, |frame| image_quality(frame.to_luma8().as_raw()))
+    }
+}
+
+/// Mono 16-bit little-endian PCM audio chunk
+pub struct MicrophoneExtractor {
+    pub sample_rate: u32,
+    /// Sound level a full-scale sine reaches at the microphone (dB SPL)
+    pub full_scale_db: f32,
+    /// Level above which speech counts as aggressive (dB SPL)
+    pub aggression_threshold_db: f32,
+    pub classifier: AcousticClassifier,
+}
+
+impl Default for MicrophoneExtractor {
+    fn default() -> Self {
+        Self {
+            sample_rate: 16_000,
+            full_scale_db: 120.0,
+            aggression_threshold_db: 70.0,
+            classifier: AcousticClassifier::default(),
+        }
+    }
+}
+
+impl FeatureExtractor for MicrophoneExtractor {
+    fn sensor_type(&self) -> &str {
+        "microphone"
+    }
+
+    fn extract(&self, input: &SensorInput) -> Result<Extracted, ExtractionError> {
+        let samples = pcm_samples(self.sensor_type(), &input.data)?;
+        Ok(Extracted::Audio(self.analyze(&samples)))
+    }
+
+    fn quality(&self, input: &SensorInput) -> f32 {
+        pcm_samples(self.sensor_type(), &input.data).map_or(0.0, |samples| audio_quality(&samples))
+    }
+}
+
+impl MicrophoneExtractor {
+    /// Levels and classified sound events for samples normalised to -1.0..1.0
+    pub fn analyze(&self, samples: &[f32]) -> AudioEvidence {
+        let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+        let volume_level = self.full_scale_db + 20.0 * rms.max(1e-6).log10();
+
+        // Dominant pitch estimate from zero crossings
+        let crossings = samples.windows(2).filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0)).count();
+        let duration_secs = samples.len() as f32 / self.sample_rate.max(1) as f32;
+        let pitch_hz = crossings as f32 / 2.0 / duration_secs.max(f32::EPSILON);
+
+        let loudness = ((volume_level - self.aggression_threshold_db) / 30.0).clamp(0.0, 1.0);
+        // Raised pitch is a common marker of vocal stress
+        let voice_stress_level = ((pitch_hz - 150.0) / 450.0).clamp(0.0, 1.0);
+
+        let mut audio = AudioEvidence {
+            volume_level,
+            aggression_score: loudness,
+            keyword_matches: Vec::new(),
+            voice_stress_level,
+            gunshot_detected: false,
+            scream_detected: false,
+            acoustic_events: Vec::new(),
+            bearing_deg: None,
+        };
+        for detection in self.classifier.classify(samples) {
+            audio.record_event(detection);
+        }
+        audio
+    }
+}
+
+/// Decode 16-bit little-endian PCM to samples normalised to -1.0..1.0
+pub(crate) fn pcm_samples(sensor_type: &str, data: &[u8]) -> Result<Vec<f32>, ExtractionError> {
+    if data.len() < 2 || !data.len().is_multiple_of(2) {
+        return Err(ExtractionError::invalid(sensor_type, "expected 16-bit PCM samples"));
+    }
+    Ok(data
+        .chunks_exact(2)
+        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]) as f32 / i16::MAX as f32)
+        .collect())
+}
+
+/// Quality camera frames captured raw are encoded at for the `camera` input
+#[cfg(any(feature = "rtsp", feature = "v4l2"))]
+const JPEG_QUALITY: u8 = 90;
+
+/// Encode a captured RGB8 picture as the JPEG the `camera` input expects
+#[cfg(any(feature = "rtsp", feature = "v4l2"))]
+pub(crate) fn jpeg_frame(rgb: &[u8], width: u32, height: u32) -> image::ImageResult<Vec<u8>> {
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, JPEG_QUALITY).encode(rgb, width, height, image::ColorType::Rgb8)?;
+    Ok(jpeg)
+}
+
+/// One tracked position relative to the protectee (metres)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TrackPoint {
+    /// Seconds since the start of the track
+    pub t: f32,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// JSON array of `TrackPoint`s for a single tracked subject
+#[derive(Debug, Clone)]
+pub struct MotionExtractor {
+    /// Speed regarded as normal walking (m/s)
+    pub normal_speed: f32,
+    /// Distance from the protectee treated as a personal-space violation (m)
+    pub personal_space_m: f32,
+    /// Heading change counted as a direction change (degrees)
+    pub turn_threshold_deg: f32,
+}
+
+impl Default for MotionExtractor {
+    fn default() -> Self {
+        Self {
+            normal_speed: 1.5,
+            personal_space_m: 2.0,
+            turn_threshold_deg: 45.0,
+        }
+    }
+}
+
+impl FeatureExtractor for MotionExtractor {
+    fn sensor_type(&self) -> &str {
+        "motion"
+    }
+
+    fn extract(&self, input: &SensorInput) -> Result<Extracted, ExtractionError> {
+        let track: Vec<TrackPoint> = serde_json::from_slice(&input.data)
+            .map_err(|e| ExtractionError::invalid(self.sensor_type(), format!("invalid track: {}", e)))?;
+        Ok(Extracted::Movement(self.analyze(&track)))
+    }
+}
+
+impl MotionExtractor {
+    /// Movement evidence for one subject's trajectory
+    pub fn analyze(&self, track: &[TrackPoint]) -> MovementEvidence {
+        let steps: Vec<(f32, f32, f32)> = track
+            .windows(2)
+            .filter(|pair| pair[1].t > pair[0].t)
+            .map(|pair| {
+                let (dx, dy) = (pair[1].x - pair[0].x, pair[1].y - pair[0].y);
+                let speed = dx.hypot(dy) / (pair[1].t - pair[0].t);
+                let range_change = pair[1].x.hypot(pair[1].y) - pair[0].x.hypot(pair[0].y);
+                (speed, dy.atan2(dx).to_degrees(), range_change)
+            })
+            .collect();
+
+        let max_speed = steps.iter().map(|(speed, _, _)| *speed).fold(0.0, f32::max);
+        let velocity_anomaly = ((max_speed - self.normal_speed) / (self.normal_speed * 3.0)).clamp(0.0, 1.0);
+
+        let direction_changes = steps
+            .windows(2)
+            .filter(|pair| {
+                let turn = (pair[1].1 - pair[0].1).rem_euclid(360.0);
+                turn.min(360.0 - turn) > self.turn_threshold_deg
+            })
+            .count() as u32;
+
+        let proximity_violations = track
+            .iter()
+            .filter(|point| point.x.hypot(point.y) < self.personal_space_m)
+            .count() as u32;
+
+        // Sustained fast approach or retreat relative to the protectee
+        let sustained = |approaching: bool| {
+            let matching = steps
+                .iter()
+                .filter(|(speed, _, range_change)| *speed > self.normal_speed && (*range_change < 0.0) == approaching)
+                .count();
+            !steps.is_empty() && matching as f32 / steps.len() as f32 >= 0.8
+        };
+
+        MovementEvidence {
+            velocity_anomaly,
+            direction_changes,
+            proximity_violations,
+            pursuit_behavior: sustained(true),
+            escape_attempts: sustained(false),
+            targets: Vec::new(),
+        }
+    }
+}
+
+/// JSON-encoded `BiometricEvidence` from wearable sensors
+pub struct BiometricExtractor;
+
+impl FeatureExtractor for BiometricExtractor {
+    fn sensor_type(&self) -> &str {
+        "biometric"
+    }
+
+    fn extract(&self, input: &SensorInput) -> Result<Extracted, ExtractionError> {
+        serde_json::from_slice(&input.data)
+            .map(Extracted::Biometric)
+            .map_err(|e| ExtractionError::invalid(self.sensor_type(), e.to_string()))
+    }
+}
+
+/// JSON-encoded `EnvironmentalEvidence` from hazard sensors
+pub struct EnvironmentalExtractor;
+
+impl FeatureExtractor for EnvironmentalExtractor {
+    fn sensor_type(&self) -> &str {
+        "environmental"
+    }
+
+    fn extract(&self, input: &SensorInput) -> Result<Extracted, ExtractionError> {
+        serde_json::from_slice(&input.data)
+            .map(Extracted::Environmental)
+            .map_err(|e| ExtractionError::invalid(self.sensor_type(), e.to_string()))
+    }
+}
@@ -0,0 +1,167 @@
+//! Known-person whitelist matched by face embedding (`face-id` feature)
+
+use chrono::{DateTime, Utc};
+use image::RgbImage;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PersonRole {
+    Protectee,
+    Family,
+    Staff,
+    Guest,
+}
+
+/// Someone who should never be treated as a threat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnownPerson {
+    pub id: Uuid,
+    pub name: String,
+    pub role: PersonRole,
+    /// Unit-length embeddings, one per enrolled photo
+    pub embeddings: Vec<Vec<f32>>,
+    pub enrolled_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaceMatch {
+    pub person_id: Uuid,
+    pub name: String,
+    pub role: PersonRole,
+    /// Cosine similarity to the closest enrolled embedding
+    pub similarity: f32,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum EnrollmentError {
+    #[error("face embedding is empty or all zeros")]
+    EmptyEmbedding,
+    #[error("face embedding has {got} dimensions, enrolled faces have {expected}")]
+    DimensionMismatch { expected: usize, got: usize },
+    #[error("no enrolled person with id {0}")]
+    UnknownPerson(Uuid),
+    #[error("no face embedder is configured")]
+    NoEmbedder,
+    #[error("no face found in the enrollment image")]
+    NoFace,
+    #[error("undecodable enrollment image: {0}")]
+    InvalidImage(String),
+}
+
+/// Turns a cropped face into an embedding vector (e.g. an ArcFace model)
+pub trait FaceEmbedder: Send + Sync {
+    fn embed(&self, face: &RgbImage) -> Option<Vec<f32>>;
+}
+
+/// Enrolled people and the similarity needed to recognise them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaceRegistry {
+    people: Vec<KnownPerson>,
+    /// Minimum cosine similarity for a match
+    pub match_threshold: f32,
+}
+
+impl Default for FaceRegistry {
+    fn default() -> Self {
+        Self {
+            people: Vec::new(),
+            match_threshold: 0.6,
+        }
+    }
+}
+
+impl FaceRegistry {
+    pub fn enroll(&mut self, name: &str, role: PersonRole, embedding: &[f32]) -> Result<Uuid, EnrollmentError> {
+        let embedding = self.normalized(embedding)?;
+        let id = Uuid::new_v4();
+        self.people.push(KnownPerson {
+            id,
+            name: name.to_string(),
+            role,
+            embeddings: vec![embedding],
+            enrolled_at: Utc::now(),
+        });
+        Ok(id)
+    }
+
+    /// Another photo of an enrolled person, e.g. with glasses or a hat
+    pub fn add_embedding(&mut self, id: Uuid, embedding: &[f32]) -> Result<(), EnrollmentError> {
+        let embedding = self.normalized(embedding)?;
+        let person = self
+            .people
+            .iter_mut()
+            .find(|person| person.id == id)
+            .ok_or(EnrollmentError::UnknownPerson(id))?;
+        person.embeddings.push(embedding);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, id: Uuid) -> Option<KnownPerson> {
+        let index = self.people.iter().position(|person| person.id == id)?;
+        Some(self.people.remove(index))
+    }
+
+    pub fn people(&self) -> &[KnownPerson] {
+        &self.people
+    }
+
+    pub fn person(&self, id: Uuid) -> Option<&KnownPerson> {
+        self.people.iter().find(|person| person.id == id)
+    }
+
+    /// Closest enrolled person above `match_threshold`
+    pub fn identify(&self, embedding: &[f32]) -> Option<FaceMatch> {
+        let embedding = self.normalized(embedding).ok()?;
+        let embedding = &embedding;
+        self.people
+            .iter()
+            .flat_map(|person| person.embeddings.iter().map(move |enrolled| (person, cosine(enrolled, embedding))))
+            .filter(|(_, similarity)| *similarity >= self.match_threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(person, similarity)| FaceMatch {
+                person_id: person.id,
+                name: person.name.clone(),
+                role: person.role,
+                similarity,
+            })
+    }
+
+    fn normalized(&self, embedding: &[f32]) -> Result<Vec<f32>, EnrollmentError> {
+        if let Some(expected) = self.people.first().and_then(|person| person.embeddings.first()).map(Vec::len) {
+            if embedding.len() != expected {
+                return Err(EnrollmentError::DimensionMismatch { expected, got: embedding.len() });
+            }
+        }
+        let norm = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm <= f32::EPSILON {
+            return Err(EnrollmentError::EmptyEmbedding);
+        }
+        Ok(embedding.iter().map(|v| v / norm).collect())
+    }
+}
+
+/// Both inputs are unit length
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Face region of a detection, as pixels of the frame
+pub(crate) fn face_crop(frame: &RgbImage, object_type: &str, bounding_box: (f32, f32, f32, f32)) -> Option<RgbImage> {
+    let (x, y, w, mut h) = bounding_box;
+    // For a whole-body box, the head sits in roughly the top fifth
+    if object_type == "person" {
+        h *= 0.2;
+    }
+    let (frame_w, frame_h) = (frame.width() as f32, frame.height() as f32);
+    let left = (x.max(0.0) * frame_w) as u32;
+    let top = (y.max(0.0) * frame_h) as u32;
+    let right = ((x + w).min(1.0) * frame_w) as u32;
+    let bottom = ((y + h).min(1.0) * frame_h) as u32;
+    if right <= left + 8 || bottom <= top + 8 {
+        return None;
+    }
+    Some(image::imageops::crop_imm(frame, left, top, right - left, bottom - top).to_image())
+}
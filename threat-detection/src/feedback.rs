@@ -0,0 +1,219 @@
+use crate::{EvidenceSource, ThreatAssessment, ThreatType};
+use dark_phoenix_core::ThreatLevel;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Operator judgement on an assessment
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// The assessment was a real threat
+    Confirmed,
+    /// The assessment raised a threat that was not there
+    FalsePositive,
+    /// A real threat the assessment failed to escalate
+    Missed,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum FeedbackError {
+    #[error("assessment {0} is not in the engine's history")]
+    UnknownAssessment(Uuid),
+}
+
+/// An assessment together with the operator's verdict
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabeledAssessment {
+    pub id: Uuid,
+    pub threat_level: ThreatLevel,
+    pub confidence: f32,
+    pub threat_types: Vec<ThreatType>,
+    pub verdict: Verdict,
+    /// What each evidence source made of it
+    #[serde(default)]
+    pub source_risk: Vec<(EvidenceSource, f32)>,
+}
+
+impl LabeledAssessment {
+    /// Whether this assessment would have alerted at a confidence threshold
+    fn alerted(&self, threshold: f32) -> bool {
+        self.threat_level > ThreatLevel::Green && self.confidence >= threshold
+    }
+}
+
+/// Precision and recall over a set of labeled assessments
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct ClassificationStats {
+    pub true_positives: u32,
+    pub false_positives: u32,
+    pub false_negatives: u32,
+}
+
+impl ClassificationStats {
+    fn record(&mut self, labeled: &LabeledAssessment, threshold: f32) {
+        match (labeled.verdict, labeled.alerted(threshold)) {
+            (Verdict::Confirmed, true) => self.true_positives += 1,
+            (Verdict::FalsePositive, true) => self.false_positives += 1,
+            (Verdict::Confirmed, false) | (Verdict::Missed, _) => self.false_negatives += 1,
+            (Verdict::FalsePositive, false) => {},
+        }
+    }
+
+    pub fn precision(&self) -> Option<f32> {
+        let alerts = self.true_positives + self.false_positives;
+        (alerts > 0).then(|| self.true_positives as f32 / alerts as f32)
+    }
+
+    pub fn recall(&self) -> Option<f32> {
+        let threats = self.true_positives + self.false_negatives;
+        (threats > 0).then(|| self.true_positives as f32 / threats as f32)
+    }
+}
+
+/// Operating point at one candidate `confidence_threshold`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ThresholdPoint {
+    pub threshold: f32,
+    pub stats: ClassificationStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedbackReport {
+    /// Stats at the engine's current `confidence_threshold`
+    pub overall: ClassificationStats,
+    pub per_type: HashMap<ThreatType, ClassificationStats>,
+    /// Stats across thresholds from 0.0 to 1.0 in steps of 0.05
+    pub threshold_sweep: Vec<ThresholdPoint>,
+    pub calibration: HashMap<ThreatType, f32>,
+    /// Fusion weight multiplier each source has earned
+    pub reliability: HashMap<EvidenceSource, f32>,
+}
+
+/// Verdicts retained for calibration, oldest dropped first
+const MAX_LABELS: usize = 10_000;
+
+/// Risk at which a source counts as having pointed at a threat
+const SOURCE_ALERT_RISK: f32 = 0.5;
+
+/// Operator verdicts, and the per-type confidence calibration and
+/// per-source reliability derived from them
+#[derive(Debug, Clone, Default)]
+pub struct FeedbackStore {
+    labels: VecDeque<LabeledAssessment>,
+}
+
+impl FeedbackStore {
+    /// Record (or revise) the verdict for an assessment
+    pub fn mark(&mut self, assessment: &ThreatAssessment, verdict: Verdict) {
+        self.labels.retain(|labeled| labeled.id != assessment.id);
+        if self.labels.len() == MAX_LABELS {
+            self.labels.pop_front();
+        }
+        self.labels.push_back(LabeledAssessment {
+            id: assessment.id,
+            threat_level: assessment.threat_level,
+            confidence: assessment.confidence,
+            threat_types: assessment.threat_types.clone(),
+            verdict,
+            source_risk: EvidenceSource::ALL
+                .into_iter()
+                .filter_map(|source| Some((source, source.risk(&assessment.evidence)?)))
+                .collect(),
+        });
+    }
+
+    pub fn verdict(&self, id: Uuid) -> Option<Verdict> {
+        self.labels.iter().find(|labeled| labeled.id == id).map(|labeled| labeled.verdict)
+    }
+
+    /// Smoothed share of a type's alerts operators confirmed (0.5 without data)
+    fn precision_estimate(&self, threat_type: ThreatType) -> f32 {
+        let (confirmed, false_positives) = self
+            .labels
+            .iter()
+            .filter(|labeled| labeled.threat_types.contains(&threat_type))
+            .fold((0u32, 0u32), |(confirmed, false_positives), labeled| match labeled.verdict {
+                Verdict::Confirmed => (confirmed + 1, false_positives),
+                Verdict::FalsePositive => (confirmed, false_positives + 1),
+                Verdict::Missed => (confirmed, false_positives),
+            });
+        (confirmed as f32 + 1.0) / (confirmed as f32 + false_positives as f32 + 2.0)
+    }
+
+    /// Confidence multiplier for a type: 1.0 until operators reject more of
+    /// its alerts than they confirm, falling towards 0.0 as they do
+    pub fn calibration(&self, threat_type: ThreatType) -> f32 {
+        (self.precision_estimate(threat_type) * 2.0).min(1.0)
+    }
+
+    /// Calibration for an assessment - its most trustworthy type vouches for it
+    pub fn calibration_for(&self, threat_types: &[ThreatType]) -> f32 {
+        if threat_types.is_empty() {
+            return 1.0;
+        }
+        threat_types
+            .iter()
+            .map(|threat_type| self.calibration(*threat_type))
+            .fold(0.0, f32::max)
+    }
+
+    /// Smoothed share of verdicts a source got right (0.5 without data): it
+    /// pointed at a threat operators confirmed or said was missed, or stayed
+    /// quiet through a false positive
+    fn source_accuracy(&self, source: EvidenceSource) -> f32 {
+        let (right, wrong) = self
+            .labels
+            .iter()
+            .filter_map(|labeled| {
+                let (_, risk) = labeled.source_risk.iter().find(|(labeled_source, _)| *labeled_source == source)?;
+                Some((labeled.verdict, *risk >= SOURCE_ALERT_RISK))
+            })
+            .fold((0u32, 0u32), |(right, wrong), outcome| match outcome {
+                (Verdict::Confirmed | Verdict::Missed, true) | (Verdict::FalsePositive, false) => (right + 1, wrong),
+                _ => (right, wrong + 1),
+            });
+        (right as f32 + 1.0) / (right as f32 + wrong as f32 + 2.0)
+    }
+
+    /// Fusion weight multiplier for a source: 1.0 until verdicts show it
+    /// wrong more often than right, falling towards 0.0 as they do
+    pub fn reliability(&self, source: EvidenceSource) -> f32 {
+        (self.source_accuracy(source) * 2.0).min(1.0)
+    }
+
+    pub fn report(&self, confidence_threshold: f32) -> FeedbackReport {
+        let mut overall = ClassificationStats::default();
+        let mut per_type: HashMap<ThreatType, ClassificationStats> = HashMap::new();
+        for labeled in &self.labels {
+            overall.record(labeled, confidence_threshold);
+            for threat_type in &labeled.threat_types {
+                per_type.entry(*threat_type).or_default().record(labeled, confidence_threshold);
+            }
+        }
+
+        let threshold_sweep = (0..=20)
+            .map(|step| {
+                let threshold = step as f32 * 0.05;
+                let mut stats = ClassificationStats::default();
+                for labeled in &self.labels {
+                    stats.record(labeled, threshold);
+                }
+                ThresholdPoint { threshold, stats }
+            })
+            .collect();
+
+        let calibration = per_type.keys().map(|threat_type| (*threat_type, self.calibration(*threat_type))).collect();
+
+        let reliability = EvidenceSource::ALL.into_iter().map(|source| (source, self.reliability(source))).collect();
+
+        FeedbackReport {
+            overall,
+            per_type,
+            threshold_sweep,
+            calibration,
+            reliability,
+        }
+    }
+}
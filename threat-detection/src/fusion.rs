@@ -0,0 +1,454 @@
+use crate::acoustic::AcousticEvent;
+use crate::{
+    AudioEvidence, BiometricEvidence, EnvironmentalEvidence, MovementEvidence, SensorInput, ThreatEvidence,
+    ThreatType, VisualEvidence,
+};
+use dark_phoenix_core::{DecisionTrace, ThreatLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+use tracing::warn;
+
+#[derive(Debug, Error)]
+pub enum ExtractionError {
+    #[error("sensor '{sensor}': {message}")]
+    Invalid { sensor: String, message: String },
+}
+
+impl ExtractionError {
+    pub fn invalid(sensor: &str, message: impl Into<String>) -> Self {
+        ExtractionError::Invalid { sensor: sensor.to_string(), message: message.into() }
+    }
+}
+
+/// Evidence derived from one sensor input
+#[derive(Debug, Clone)]
+pub enum Extracted {
+    Visual(VisualEvidence),
+    Audio(AudioEvidence),
+    Movement(MovementEvidence),
+    Biometric(BiometricEvidence),
+    Environmental(EnvironmentalEvidence),
+}
+
+/// Converts raw `SensorInput.data` for one sensor type into evidence
+pub trait FeatureExtractor: Send + Sync {
+    /// The `sensor_type` this extractor handles, e.g. `"camera"`
+    fn sensor_type(&self) -> &str;
+
+    fn extract(&self, input: &SensorInput) -> Result<Extracted, ExtractionError>;
+
+    /// Signal quality of a raw input (0.0-1.0), judged from the data alone
+    fn quality(&self, _input: &SensorInput) -> f32 {
+        1.0
+    }
+}
+
+/// A kind of evidence, fused with its own weight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvidenceSource {
+    Visual,
+    Audio,
+    Movement,
+    Biometric,
+    Environmental,
+}
+
+impl EvidenceSource {
+    pub const ALL: [EvidenceSource; 5] = [
+        EvidenceSource::Visual,
+        EvidenceSource::Audio,
+        EvidenceSource::Movement,
+        EvidenceSource::Biometric,
+        EvidenceSource::Environmental,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EvidenceSource::Visual => "visual",
+            EvidenceSource::Audio => "audio",
+            EvidenceSource::Movement => "movement",
+            EvidenceSource::Biometric => "biometric",
+            EvidenceSource::Environmental => "environmental",
+        }
+    }
+
+    fn of(extracted: &Extracted) -> Self {
+        match extracted {
+            Extracted::Visual(_) => EvidenceSource::Visual,
+            Extracted::Audio(_) => EvidenceSource::Audio,
+            Extracted::Movement(_) => EvidenceSource::Movement,
+            Extracted::Biometric(_) => EvidenceSource::Biometric,
+            Extracted::Environmental(_) => EvidenceSource::Environmental,
+        }
+    }
+
+    /// This source's risk in `evidence`, absent if it contributed none
+    pub fn risk(&self, evidence: &ThreatEvidence) -> Option<f32> {
+        match self {
+            EvidenceSource::Visual => evidence.visual_data.as_ref().map(visual_risk),
+            EvidenceSource::Audio => evidence.audio_data.as_ref().map(audio_risk),
+            EvidenceSource::Movement => evidence.movement_data.as_ref().map(movement_risk),
+            EvidenceSource::Biometric => evidence.biometric_data.as_ref().map(biometric_risk),
+            EvidenceSource::Environmental => evidence.environmental_data.as_ref().map(environmental_risk),
+        }
+    }
+}
+
+/// Relative weight of each modality in the fused risk score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FusionWeights {
+    pub visual: f32,
+    pub audio: f32,
+    pub movement: f32,
+    pub biometric: f32,
+    pub environmental: f32,
+}
+
+impl Default for FusionWeights {
+    fn default() -> Self {
+        Self {
+            visual: 0.35,
+            audio: 0.3,
+            movement: 0.25,
+            biometric: 0.05,
+            environmental: 0.05,
+        }
+    }
+}
+
+impl FusionWeights {
+    pub fn weight(&self, source: EvidenceSource) -> f32 {
+        match source {
+            EvidenceSource::Visual => self.visual,
+            EvidenceSource::Audio => self.audio,
+            EvidenceSource::Movement => self.movement,
+            EvidenceSource::Biometric => self.biometric,
+            EvidenceSource::Environmental => self.environmental,
+        }
+    }
+}
+
+/// Fused risk score cut-offs for each threat level (0.0-1.0)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FusionThresholds {
+    pub yellow: f32,
+    pub orange: f32,
+    pub red: f32,
+    pub omega: f32,
+}
+
+impl Default for FusionThresholds {
+    fn default() -> Self {
+        Self {
+            yellow: 0.25,
+            orange: 0.5,
+            red: 0.75,
+            omega: 0.95,
+        }
+    }
+}
+
+impl FusionThresholds {
+    pub fn level_for(&self, risk: f32) -> ThreatLevel {
+        match risk {
+            r if r >= self.omega => ThreatLevel::Omega,
+            r if r >= self.red => ThreatLevel::Red,
+            r if r >= self.orange => ThreatLevel::Orange,
+            r if r >= self.yellow => ThreatLevel::Yellow,
+            _ => ThreatLevel::Green,
+        }
+    }
+
+    /// The risk `level` starts at; Green has none
+    pub fn threshold_for(&self, level: ThreatLevel) -> Option<f32> {
+        match level {
+            ThreatLevel::Green => None,
+            ThreatLevel::Yellow => Some(self.yellow),
+            ThreatLevel::Orange => Some(self.orange),
+            ThreatLevel::Red => Some(self.red),
+            ThreatLevel::Omega => Some(self.omega),
+        }
+    }
+}
+
+/// Result of fusing every current sensor input
+#[derive(Debug, Clone)]
+pub struct FusionResult {
+    pub evidence: ThreatEvidence,
+    /// Weighted risk across the modalities present (0.0-1.0)
+    pub risk_score: f32,
+    /// Mean quality of the inputs that produced evidence
+    pub confidence: f32,
+    pub threat_types: Vec<ThreatType>,
+    /// Each modality's risk and reliability-adjusted weight, and any decisive evidence
+    pub trace: DecisionTrace,
+}
+
+/// Registry of feature extractors plus the weighted fusion stage
+pub struct FusionPipeline {
+    extractors: HashMap<String, Box<dyn FeatureExtractor>>,
+    pub weights: FusionWeights,
+}
+
+impl Default for FusionPipeline {
+    fn default() -> Self {
+        Self::new(FusionWeights::default())
+    }
+}
+
+impl FusionPipeline {
+    pub fn new(weights: FusionWeights) -> Self {
+        Self {
+            extractors: HashMap::new(),
+            weights,
+        }
+    }
+
+    /// Pipeline with the built-in camera, microphone, motion, biometric and environmental extractors
+    pub fn with_builtin_extractors(weights: FusionWeights) -> Self {
+        let mut pipeline = Self::new(weights);
+        pipeline.register(crate::extractors::CameraExtractor);
+        pipeline.register(crate::extractors::MicrophoneExtractor::default());
+        pipeline.register(crate::extractors::MotionExtractor::default());
+        pipeline.register(crate::extractors::BiometricExtractor);
+        pipeline.register(crate::extractors::EnvironmentalExtractor);
+        pipeline
+    }
+
+    /// Add or replace the extractor for its sensor type
+    pub fn register(&mut self, extractor: impl FeatureExtractor + 'static) {
+        self.extractors.insert(extractor.sensor_type().to_string(), Box::new(extractor));
+    }
+
+    pub fn sensor_types(&self) -> impl Iterator<Item = &str> {
+        self.extractors.keys().map(String::as_str)
+    }
+
+    /// Quality of an input as judged by its extractor; unknown sensors are trusted
+    pub fn quality(&self, input: &SensorInput) -> f32 {
+        self.extractors
+            .get(&input.sensor_type)
+            .map_or(1.0, |extractor| extractor.quality(input).clamp(0.0, 1.0))
+    }
+
+    /// Extract evidence from each input and fuse it into a single score,
+    /// each source weighted by the quality of its inputs
+    pub fn fuse<'a>(&self, inputs: impl IntoIterator<Item = &'a SensorInput>) -> FusionResult {
+        let (evidence, confidence, reliability) = self.extract(inputs);
+        self.score(evidence, confidence, &reliability)
+    }
+
+    /// Merged evidence from every input, the mean quality of those that
+    /// produced it, and the best input quality behind each source
+    pub fn extract<'a>(
+        &self,
+        inputs: impl IntoIterator<Item = &'a SensorInput>,
+    ) -> (ThreatEvidence, f32, HashMap<EvidenceSource, f32>) {
+        let mut evidence = ThreatEvidence {
+            visual_data: None,
+            audio_data: None,
+            movement_data: None,
+            biometric_data: None,
+            environmental_data: None,
+        };
+        let mut qualities = Vec::new();
+        let mut source_quality: HashMap<EvidenceSource, f32> = HashMap::new();
+
+        for input in inputs {
+            let Some(extractor) = self.extractors.get(&input.sensor_type) else { continue };
+            match extractor.extract(input) {
+                Ok(extracted) => {
+                    let quality = input.quality.clamp(0.0, 1.0);
+                    let best = source_quality.entry(EvidenceSource::of(&extracted)).or_insert(0.0);
+                    *best = best.max(quality);
+                    merge(&mut evidence, extracted);
+                    qualities.push(quality);
+                },
+                Err(e) => warn!("Feature extraction failed, input ignored: {}", e),
+            }
+        }
+
+        let confidence = if qualities.is_empty() {
+            0.0
+        } else {
+            qualities.iter().sum::<f32>() / qualities.len() as f32
+        };
+        (evidence, confidence, source_quality)
+    }
+
+    /// Weighted risk score and candidate threat types for already merged
+    /// evidence; each source's weight is scaled by its `reliability`
+    /// (0.0-1.0, absent = 1.0)
+    pub fn score(&self, evidence: ThreatEvidence, confidence: f32, reliability: &HashMap<EvidenceSource, f32>) -> FusionResult {
+        let mut trace = DecisionTrace::new();
+        let mut scores = Vec::new();
+        for source in EvidenceSource::ALL {
+            let Some(risk) = source.risk(&evidence) else { continue };
+            let source_reliability = reliability.get(&source).copied().unwrap_or(1.0).clamp(0.0, 1.0);
+            let weight = self.weights.weight(source) * source_reliability;
+            if source_reliability < 1.0 {
+                trace.input(&format!("{} reliability", source.as_str()), source_reliability, None);
+            }
+            trace.input(&format!("{} risk", source.as_str()), risk, Some(weight));
+            scores.push((weight, risk));
+        }
+        // Renormalise over the modalities actually present
+        let (weighted, total_weight) = scores
+            .iter()
+            .fold((0.0, 0.0), |(sum, total), (weight, risk)| (sum + weight * risk, total + weight));
+        let mut risk_score = if total_weight > 0.0 { weighted / total_weight } else { 0.0 };
+
+        // A gunshot or clearly visible weapon is decisive on its own
+        let decisive = evidence.audio_data.as_ref().is_some_and(|audio| audio.gunshot_detected)
+            || evidence.visual_data.as_ref().is_some_and(|visual| visual.weapon_confidence >= 0.8);
+        if decisive && risk_score < 0.9 {
+            trace.input("decisive evidence floor (gunshot or weapon)", 0.9, None);
+            risk_score = 0.9;
+        }
+
+        FusionResult {
+            threat_types: threat_types(&evidence),
+            evidence,
+            risk_score: risk_score.clamp(0.0, 1.0),
+            confidence,
+            trace,
+        }
+    }
+}
+
+/// Fold new evidence into what is already known, keeping the stronger signal
+pub(crate) fn merge(evidence: &mut ThreatEvidence, extracted: Extracted) {
+    match extracted {
+        Extracted::Visual(new) => match &mut evidence.visual_data {
+            Some(visual) => {
+                visual.object_detections.extend(new.object_detections);
+                visual.body_language_score = visual.body_language_score.max(new.body_language_score);
+                visual.weapon_confidence = visual.weapon_confidence.max(new.weapon_confidence);
+                visual.crowd_density = visual.crowd_density.max(new.crowd_density);
+            },
+            None => evidence.visual_data = Some(new),
+        },
+        Extracted::Audio(new) => match &mut evidence.audio_data {
+            Some(audio) => {
+                audio.volume_level = audio.volume_level.max(new.volume_level);
+                audio.aggression_score = audio.aggression_score.max(new.aggression_score);
+                audio.keyword_matches.extend(new.keyword_matches);
+                audio.voice_stress_level = audio.voice_stress_level.max(new.voice_stress_level);
+                audio.gunshot_detected |= new.gunshot_detected;
+                audio.scream_detected |= new.scream_detected;
+                audio.acoustic_events.extend(new.acoustic_events);
+                audio.bearing_deg = audio.bearing_deg.or(new.bearing_deg);
+            },
+            None => evidence.audio_data = Some(new),
+        },
+        Extracted::Movement(new) => match &mut evidence.movement_data {
+            Some(movement) => {
+                movement.velocity_anomaly = movement.velocity_anomaly.max(new.velocity_anomaly);
+                movement.direction_changes = movement.direction_changes.max(new.direction_changes);
+                movement.proximity_violations += new.proximity_violations;
+                movement.pursuit_behavior |= new.pursuit_behavior;
+                movement.escape_attempts |= new.escape_attempts;
+                movement.targets.extend(new.targets);
+            },
+            None => evidence.movement_data = Some(new),
+        },
+        Extracted::Biometric(new) => evidence.biometric_data = Some(new),
+        Extracted::Environmental(new) => evidence.environmental_data = Some(new),
+    }
+}
+
+fn visual_risk(visual: &VisualEvidence) -> f32 {
+    let detections = visual
+        .object_detections
+        .iter()
+        .map(|detection| detection.confidence * detection.threat_relevance)
+        .fold(0.0, f32::max);
+    visual.weapon_confidence.max(visual.body_language_score).max(detections)
+}
+
+fn audio_risk(audio: &AudioEvidence) -> f32 {
+    let mut risk = audio.aggression_score.max(audio.voice_stress_level * 0.5);
+    if audio.scream_detected || audio.detected(AcousticEvent::GlassBreak).is_some() {
+        risk = risk.max(0.8);
+    }
+    if audio.gunshot_detected {
+        risk = 1.0;
+    }
+    risk
+}
+
+fn movement_risk(movement: &MovementEvidence) -> f32 {
+    let mut risk = movement
+        .velocity_anomaly
+        .max((movement.direction_changes as f32 / 10.0).min(1.0));
+    if movement.proximity_violations > 0 {
+        risk = risk.max(0.5);
+    }
+    if movement.pursuit_behavior {
+        risk = risk.max(0.8);
+    }
+    risk
+}
+
+fn biometric_risk(biometric: &BiometricEvidence) -> f32 {
+    let stress = biometric.stress_hormones.unwrap_or(0.0);
+    if biometric.elevated_heart_rate { stress.max(0.5) } else { stress }
+}
+
+fn environmental_risk(environmental: &EnvironmentalEvidence) -> f32 {
+    if environmental.smoke_detected || environmental.structural_damage {
+        0.8
+    } else if !environmental.chemical_traces.is_empty() || environmental.temperature_anomaly.is_some() {
+        0.5
+    } else {
+        0.0
+    }
+}
+
+/// Candidate threat types supported by the evidence
+fn threat_types(evidence: &ThreatEvidence) -> Vec<ThreatType> {
+    let mut types = Vec::new();
+    let mut add = |threat_type| {
+        if !types.contains(&threat_type) {
+            types.push(threat_type);
+        }
+    };
+
+    if let Some(visual) = &evidence.visual_data {
+        if visual.weapon_confidence >= 0.5 {
+            add(ThreatType::WeaponDetected);
+        }
+        if visual.body_language_score >= 0.6 {
+            add(ThreatType::HostileIntent);
+            if visual.crowd_density >= 3 {
+                add(ThreatType::GroupThreat);
+            }
+        }
+    }
+    if let Some(audio) = &evidence.audio_data {
+        if audio.gunshot_detected {
+            add(ThreatType::WeaponDetected);
+        }
+        if audio.aggression_score >= 0.6 || audio.scream_detected {
+            add(ThreatType::PhysicalAggression);
+        }
+        // Breaking glass usually means forced entry
+        if audio.detected(AcousticEvent::GlassBreak).is_some() {
+            add(ThreatType::HostileIntent);
+        }
+    }
+    if let Some(movement) = &evidence.movement_data {
+        if movement.velocity_anomaly >= 0.5 || movement.direction_changes >= 5 {
+            add(ThreatType::ErraticBehavior);
+        }
+        if movement.pursuit_behavior {
+            add(ThreatType::HostileIntent);
+        }
+    }
+    if evidence.environmental_data.as_ref().is_some_and(|environmental| environmental_risk(environmental) > 0.0) {
+        add(ThreatType::EnvironmentalHazard);
+    }
+    types
+}
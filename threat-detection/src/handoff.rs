@@ -0,0 +1,102 @@
+//! Passing a tracked subject from one drone to the next
+
+use crate::extractors::TrackPoint;
+use crate::tracking::Track;
+use crate::ThreatType;
+use chrono::{DateTime, Utc};
+use dark_phoenix_core::{Position, ThreatLevel};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HandoffConfig {
+    /// How long a received handoff waits for its subject to appear
+    pub accept_secs: u64,
+    /// Furthest a new track may be from the handed-over position and still
+    /// be taken for the same subject, when neither has a face to compare
+    pub match_radius_m: f64,
+    /// Share of the frame at each edge where a departing track is handed off
+    pub edge_margin: f32,
+    /// Span of recent assessments whose highest level is handed over
+    pub severity_window_secs: u64,
+}
+
+impl Default for HandoffConfig {
+    fn default() -> Self {
+        Self {
+            accept_secs: 60,
+            match_radius_m: 30.0,
+            edge_margin: 0.1,
+            severity_window_secs: 10,
+        }
+    }
+}
+
+/// A file of the sending drone's evidence, to be fetched or cited later
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvidenceReference {
+    pub capture_id: Uuid,
+    /// Relative to the capture directory
+    pub file: String,
+    /// Lower-case hex SHA-256 of the file
+    pub sha256: String,
+}
+
+/// Everything the next drone needs to carry on tracking a subject
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackHandoff {
+    pub handoff_id: Uuid,
+    pub from_drone: Uuid,
+    /// The sending drone's track id, for its own records
+    pub track_id: u64,
+    pub object_type: String,
+    /// Metres relative to the sending drone's protectee anchor
+    pub trajectory: Vec<TrackPoint>,
+    /// Where the subject was last seen, when the sending drone knew its pose
+    pub last_position: Option<Position>,
+    /// Unnormalised face embeddings of the subject
+    pub embeddings: Vec<Vec<f32>>,
+    pub threat_level: ThreatLevel,
+    pub confidence: f32,
+    pub threat_types: Vec<ThreatType>,
+    pub zone: Option<String>,
+    pub evidence: Vec<EvidenceReference>,
+    pub handed_off_at: DateTime<Utc>,
+}
+
+impl TrackHandoff {
+    /// Whether a new track could be the handed-over subject, judged by kind
+    /// and distance; `position` is where the track is, if known
+    pub(crate) fn fits(&self, track: &Track, position: Option<&Position>, config: &HandoffConfig) -> bool {
+        if track.object_type != self.object_type {
+            return false;
+        }
+        match (position, &self.last_position) {
+            (Some(position), Some(last)) => position.distance_m(last) <= config.match_radius_m,
+            _ => true,
+        }
+    }
+
+    /// Best cosine similarity between `embedding` and the handed-over faces
+    #[cfg(feature = "face-id")]
+    pub(crate) fn similarity(&self, embedding: &[f32]) -> Option<f32> {
+        let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+        let length = norm(embedding);
+        self.embeddings
+            .iter()
+            .filter(|handed| handed.len() == embedding.len())
+            .map(|handed| handed.iter().zip(embedding).map(|(a, b)| a * b).sum::<f32>() / (norm(handed) * length).max(f32::EPSILON))
+            .max_by(f32::total_cmp)
+    }
+}
+
+/// Whether a confirmed track that has stopped being seen was last heading
+/// out of the frame across the nearest edge
+pub(crate) fn is_departing(track: &Track, margin: f32) -> bool {
+    if track.misses == 0 {
+        return false;
+    }
+    let ((x, y), (vx, vy)) = (track.center(), track.velocity());
+    (x <= margin && vx < 0.0) || (x >= 1.0 - margin && vx > 0.0) || (y <= margin && vy < 0.0) || (y >= 1.0 - margin && vy > 0.0)
+}
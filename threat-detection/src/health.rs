@@ -0,0 +1,175 @@
+use crate::SensorInput;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Freshness and quality limits for sensor inputs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensorHealthConfig {
+    /// Maximum input age per sensor type (ms); others use `max_input_age_ms`
+    pub max_age_ms: HashMap<String, u64>,
+    /// Sensors whose loss puts detection into degraded mode
+    pub key_sensors: Vec<String>,
+    /// Inputs scoring below this are reported as poor
+    pub min_quality: f32,
+    /// Highest confidence an assessment may claim while degraded
+    pub degraded_confidence_cap: f32,
+}
+
+impl Default for SensorHealthConfig {
+    fn default() -> Self {
+        Self {
+            // Frames and audio chunks arrive many times a second; biometric
+            // and environmental readings are slower
+            max_age_ms: HashMap::from([
+                ("camera".to_string(), 500),
+                ("microphone".to_string(), 1000),
+                ("biometric".to_string(), 5000),
+                ("environmental".to_string(), 10_000),
+            ]),
+            key_sensors: vec!["camera".to_string(), "microphone".to_string()],
+            min_quality: 0.3,
+            degraded_confidence_cap: 0.7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SensorState {
+    Healthy,
+    /// Fresh, but the signal is clipped, blank or otherwise unusable
+    Poor,
+    /// Older than the sensor's maximum age
+    Stale,
+    /// Never reported
+    Missing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensorStatus {
+    pub sensor_type: String,
+    pub state: SensorState,
+    pub quality: f32,
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// State of every known sensor at one moment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensorHealthReport {
+    pub timestamp: DateTime<Utc>,
+    pub sensors: Vec<SensorStatus>,
+    /// Key sensors that are poor, stale or missing
+    pub degraded: Vec<String>,
+}
+
+impl SensorHealthReport {
+    pub fn is_degraded(&self) -> bool {
+        !self.degraded.is_empty()
+    }
+
+    pub fn status(&self, sensor_type: &str) -> Option<&SensorStatus> {
+        self.sensors.iter().find(|status| status.sensor_type == sensor_type)
+    }
+
+    /// "camera stale, microphone missing"
+    pub fn summary(&self) -> String {
+        self.degraded
+            .iter()
+            .filter_map(|sensor_type| self.status(sensor_type))
+            .map(|status| format!("{} {:?}", status.sensor_type, status.state).to_lowercase())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl SensorHealthConfig {
+    pub fn max_age(&self, sensor_type: &str, fallback_ms: u64) -> chrono::Duration {
+        chrono::Duration::milliseconds(self.max_age_ms.get(sensor_type).copied().unwrap_or(fallback_ms) as i64)
+    }
+
+    pub fn is_fresh(&self, input: &SensorInput, fallback_ms: u64, now: DateTime<Utc>) -> bool {
+        now - input.timestamp <= self.max_age(&input.sensor_type, fallback_ms)
+    }
+
+    /// Status of every reporting sensor plus any key sensor that never reported
+    pub fn evaluate(
+        &self,
+        inputs: &HashMap<String, SensorInput>,
+        fallback_ms: u64,
+        now: DateTime<Utc>,
+    ) -> SensorHealthReport {
+        let mut sensors: Vec<SensorStatus> = inputs
+            .values()
+            .map(|input| {
+                let state = if !self.is_fresh(input, fallback_ms, now) {
+                    SensorState::Stale
+                } else if input.quality < self.min_quality {
+                    SensorState::Poor
+                } else {
+                    SensorState::Healthy
+                };
+                SensorStatus {
+                    sensor_type: input.sensor_type.clone(),
+                    state,
+                    quality: input.quality,
+                    last_seen: Some(input.timestamp),
+                }
+            })
+            .collect();
+        for sensor_type in &self.key_sensors {
+            if !inputs.contains_key(sensor_type) {
+                sensors.push(SensorStatus {
+                    sensor_type: sensor_type.clone(),
+                    state: SensorState::Missing,
+                    quality: 0.0,
+                    last_seen: None,
+                });
+            }
+        }
+        sensors.sort_by(|a, b| a.sensor_type.cmp(&b.sensor_type));
+
+        let degraded = sensors
+            .iter()
+            .filter(|status| status.state != SensorState::Healthy && self.key_sensors.contains(&status.sensor_type))
+            .map(|status| status.sensor_type.clone())
+            .collect();
+        SensorHealthReport {
+            timestamp: now,
+            sensors,
+            degraded,
+        }
+    }
+}
+
+/// Quality of 16-bit PCM audio: clipped, silent-dead or DC-biased
+/// microphones score low
+pub(crate) fn audio_quality(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let count = samples.len() as f32;
+    let clipped = samples.iter().filter(|sample| sample.abs() >= 0.99).count() as f32 / count;
+    let mean = samples.iter().sum::<f32>() / count;
+    let variance = samples.iter().map(|sample| (sample - mean).powi(2)).sum::<f32>() / count;
+    // Even a quiet room has some noise; a perfectly flat signal is a dead capsule
+    if variance <= 1e-10 {
+        return 0.0;
+    }
+    (1.0 - clipped * 10.0 - (mean.abs() - 0.05).max(0.0) * 4.0).clamp(0.0, 1.0)
+}
+
+/// Quality of a greyscale frame: black, blown-out or featureless (covered
+/// lens, fog) frames score low
+pub(crate) fn image_quality(pixels: &[u8]) -> f32 {
+    if pixels.is_empty() {
+        return 0.0;
+    }
+    let count = pixels.len() as f32;
+    let mean = pixels.iter().map(|p| *p as f32).sum::<f32>() / count;
+    let contrast = (pixels.iter().map(|p| (*p as f32 - mean).powi(2)).sum::<f32>() / count).sqrt();
+    // Best at mid exposure, falling to zero at pure black or white
+    let exposure = 1.0 - ((mean - 127.5) / 127.5).abs();
+    let detail = (contrast / 32.0).min(1.0);
+    (exposure.sqrt() * detail).clamp(0.0, 1.0)
+}
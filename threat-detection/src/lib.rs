@@ -1,8 +1,83 @@
-use dark_phoenix_core::{ThreatLevel, Position};
+use dark_phoenix_core::{DecisionTrace, EventStore, Metrics, Position, RingBuffer, RiskTrend, Situation, StoreError, ThreatLevel};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use tokio::sync::{broadcast, watch};
+
+pub mod acoustic;
+#[cfg(feature = "rtsp")]
+pub mod camera;
+pub mod doa;
+pub mod evidence;
+pub mod export;
+pub mod extractors;
+#[cfg(feature = "face-id")]
+pub mod face;
+pub mod feedback;
+pub mod fusion;
+pub mod handoff;
+pub mod health;
+#[cfg(feature = "onnx")]
+pub mod onnx;
+#[cfg(feature = "radar")]
+pub mod radar;
+#[cfg(feature = "redaction")]
+pub mod redaction;
+pub mod replay;
+pub mod retention;
+#[cfg(feature = "siem")]
+pub mod siem;
+#[cfg(feature = "simulation")]
+pub mod simulation;
+pub mod stream;
+pub mod suppression;
+#[cfg(feature = "thermal")]
+pub mod thermal;
+pub mod tracking;
+#[cfg(feature = "v4l2")]
+pub mod v4l2;
+#[cfg(feature = "binary-wire")]
+pub mod wire;
+pub mod zones;
+
+pub use acoustic::{AcousticClassifier, AcousticDetection, AcousticEvent};
+#[cfg(feature = "rtsp")]
+pub use camera::{CameraConfig, CameraError, CameraIngest, OnvifConfig};
+pub use doa::{MicArrayConfig, MicArrayExtractor};
+pub use evidence::{CustodyAction, CustodyEntry, CustodyManifest, EvidenceConfig, EvidenceError, EvidenceItem, EvidenceKind, EvidenceRecorder};
+#[cfg(feature = "parquet")]
+pub use export::export_parquet;
+pub use export::{export_jsonl, strip_pii, ExportOptions};
+pub use extractors::{
+    BiometricExtractor, CameraExtractor, EnvironmentalExtractor, MicrophoneExtractor, MotionExtractor, TrackPoint,
+};
+#[cfg(feature = "face-id")]
+pub use face::{EnrollmentError, FaceEmbedder, FaceMatch, FaceRegistry, KnownPerson, PersonRole};
+pub use feedback::{ClassificationStats, FeedbackError, FeedbackReport, FeedbackStore, LabeledAssessment, ThresholdPoint, Verdict};
+pub use fusion::{
+    EvidenceSource, ExtractionError, Extracted, FeatureExtractor, FusionPipeline, FusionResult, FusionThresholds, FusionWeights,
+};
+pub use handoff::{EvidenceReference, HandoffConfig, TrackHandoff};
+pub use health::{SensorHealthConfig, SensorHealthReport, SensorState, SensorStatus};
+#[cfg(feature = "onnx")]
+pub use onnx::{OnnxAudioClassifier, OnnxConfig, OnnxObjectDetector};
+#[cfg(feature = "radar")]
+pub use radar::{Ld2450Decoder, RadarConfig, RadarError, RadarExtractor, RadarIngest};
+#[cfg(feature = "redaction")]
+pub use redaction::{RedactionConfig, Redactor, RegionDetector};
+pub use replay::{Decision, Divergence, Replay, ReplayLog, ReplayReport, ReplayStep};
+pub use retention::{RetentionEngine, RetentionPolicy, RetentionReport};
+pub use stream::SeekerHandle;
+pub use suppression::{SuppressionList, ThreatSuppression};
+#[cfg(feature = "thermal")]
+pub use thermal::{ThermalBodyConfig, ThermalExtractor};
+pub use tracking::{MultiObjectTracker, Track, TrackerConfig};
+#[cfg(feature = "v4l2")]
+pub use v4l2::{NightExposure, V4l2CameraConfig, V4l2Capture};
+#[cfg(feature = "binary-wire")]
+pub use wire::ThreatFrame;
+pub use zones::{DetectionZone, DwellTracker, LoiterRule, Loiterer, ZoneEntryRule, ZoneEvaluation, ZoneMap};
 
 /// Ultra Seeker threat analysis result
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,10 +91,15 @@ pub struct ThreatAssessment {
     pub description: String,
     pub recommended_actions: Vec<String>,
     pub evidence: ThreatEvidence,
+    #[serde(default)]
+    pub zone: Option<String>, // Detection zone the threat is in, if zones are configured
+    /// Inputs, weights and thresholds behind `threat_level`
+    #[serde(default)]
+    pub trace: DecisionTrace,
 }
 
 /// Types of threats the system can detect
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum ThreatType {
     /// Physical aggression detected
     PhysicalAggression,
@@ -39,6 +119,8 @@ pub enum ThreatType {
     CyberThreat,
     /// Unknown anomaly requiring investigation
     UnknownAnomaly,
+    /// Subject lingering in a monitored zone
+    Loitering,
 }
 
 impl ThreatType {
@@ -53,6 +135,7 @@ impl ThreatType {
             ThreatType::VehicleThreat => 1.7,
             ThreatType::CyberThreat => 1.4,
             ThreatType::UnknownAnomaly => 1.1,
+            ThreatType::Loitering => 1.1,
         }
     }
 
@@ -67,12 +150,34 @@ impl ThreatType {
             ThreatType::VehicleThreat => "Vehicle-based threat identified",
             ThreatType::CyberThreat => "Cyber attack or hacking attempt",
             ThreatType::UnknownAnomaly => "Unknown anomaly requiring investigation",
+            ThreatType::Loitering => "Subject loitering in a monitored zone",
+        }
+    }
+}
+
+impl ThreatAssessment {
+    /// The situation deterrence words its response for, from the most
+    /// serious threat type seen
+    pub fn situation(&self) -> Situation {
+        let seen = |threat_type| self.threat_types.contains(&threat_type);
+        if seen(ThreatType::WeaponDetected) {
+            Situation::Weapon
+        } else if seen(ThreatType::GroupThreat) {
+            Situation::GroupThreat
+        } else if seen(ThreatType::PhysicalAggression) || seen(ThreatType::HostileIntent) {
+            Situation::Aggression
+        } else if seen(ThreatType::Loitering) {
+            Situation::Proximity
+        } else if seen(ThreatType::ErraticBehavior) || seen(ThreatType::UnknownAnomaly) {
+            Situation::Anomaly
+        } else {
+            Situation::Unspecified
         }
     }
 }
 
 /// Evidence collected during threat assessment
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ThreatEvidence {
     pub visual_data: Option<VisualEvidence>,
     pub audio_data: Option<AudioEvidence>,
@@ -96,6 +201,12 @@ pub struct ObjectDetection {
     pub confidence: f32,
     pub bounding_box: (f32, f32, f32, f32), // x, y, width, height
     pub threat_relevance: f32,
+    #[serde(default)]
+    pub track_id: Option<u64>, // Stable across frames once tracked
+    #[serde(default)]
+    pub known_person: Option<Uuid>, // Whitelisted identity matched by face
+    #[serde(default)]
+    pub zone: Option<String>, // Detection zone the object stands in
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -106,6 +217,30 @@ pub struct AudioEvidence {
     pub voice_stress_level: f32,
     pub gunshot_detected: bool,
     pub scream_detected: bool,
+    #[serde(default)]
+    pub acoustic_events: Vec<AcousticDetection>,
+    #[serde(default)]
+    pub bearing_deg: Option<f32>, // Clockwise from the platform's heading, when a mic array located the sound
+}
+
+impl AudioEvidence {
+    /// Add a classified sound event, raising the matching flag
+    pub fn record_event(&mut self, detection: AcousticDetection) {
+        match detection.event {
+            AcousticEvent::Gunshot => self.gunshot_detected = true,
+            AcousticEvent::Scream => self.scream_detected = true,
+            AcousticEvent::GlassBreak => {},
+        }
+        self.acoustic_events.push(detection);
+    }
+
+    /// Most confident detection of an event, if any
+    pub fn detected(&self, event: AcousticEvent) -> Option<&AcousticDetection> {
+        self.acoustic_events
+            .iter()
+            .filter(|detection| detection.event == event)
+            .max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -115,8 +250,25 @@ pub struct MovementEvidence {
     pub proximity_violations: u32,
     pub pursuit_behavior: bool,
     pub escape_attempts: bool,
+    /// Radar returns, with range and closing speed
+    #[serde(default)]
+    pub targets: Vec<RadarTarget>,
+}
+
+/// One radar return relative to the platform
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RadarTarget {
+    pub range_m: f32,
+    /// Clockwise from the platform's heading
+    pub bearing_deg: f32,
+    /// Negative while the target closes in
+    pub radial_speed_mps: f32,
+    /// Visual track the return was matched to, by bearing
+    #[serde(default)]
+    pub track_id: Option<u64>,
 }
 
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BiometricEvidence {
     pub elevated_heart_rate: bool,
@@ -139,18 +291,108 @@ pub struct UltraSeekerEngine {
     /// Model state and configuration
     config: ThreatDetectionConfig,
     /// Historical threat patterns for learning
-    threat_history: Vec<ThreatAssessment>,
+    threat_history: RingBuffer<ThreatAssessment>,
     /// Current sensor inputs
     sensor_inputs: HashMap<String, SensorInput>,
+    /// Feature extraction and fusion of sensor inputs
+    pipeline: FusionPipeline,
+    /// Every assessment produced, for streaming consumers
+    assessments: broadcast::Sender<ThreatAssessment>,
+    /// Threat types temporarily ignored by operators
+    suppressions: SuppressionList,
+    /// Operator verdicts used to calibrate confidence
+    feedback: FeedbackStore,
+    /// Identity continuity for detected people and vehicles
+    tracker: MultiObjectTracker,
+    /// Platform position and heading (degrees from north) for locating threats
+    pose: Option<(Position, f32)>,
+    /// Time each track has spent in each zone
+    dwell: DwellTracker,
+    /// Zones of named cameras, used in place of the configured zones while
+    /// the latest frame is theirs
+    camera_zones: HashMap<String, ZoneMap>,
+    /// Camera the latest frame came from, when it was named
+    camera: Option<String>,
+    /// Tracks already recognised as whitelisted people
+    known_tracks: HashMap<u64, Uuid>,
+    /// Subjects handed over by other drones, waiting to reappear here
+    handoffs: Vec<TrackHandoff>,
+    /// Tracks resumed from a handoff, holding the level they arrived at
+    resumed: HashMap<u64, TrackHandoff>,
+    /// Tracks already handed off to the fleet
+    handed_off: HashSet<u64>,
+    /// Durable assessment log that outlives restarts
+    store: Option<EventStore>,
+    /// Latest sensor freshness and quality, for whoever supervises the drone
+    health: watch::Sender<SensorHealthReport>,
+    /// Risk, assessment and sensor staleness metrics, when exported
+    metrics: Option<Metrics>,
+    /// Video, audio and sensor snapshots kept around threats, when enabled
+    evidence: Option<EvidenceRecorder>,
+    /// Recorded time the engine runs at while replaying a session
+    clock: Option<DateTime<Utc>>,
+    /// SIEM collector non-routine assessments are forwarded to
+    #[cfg(feature = "siem")]
+    siem: Option<dark_phoenix_core::SiemForwarder>,
+    #[cfg(feature = "face-id")]
+    faces: FaceRegistry,
+    #[cfg(feature = "face-id")]
+    face_embedder: Option<Box<dyn FaceEmbedder>>,
+    /// Latest face embedding of each tracked person not on the whitelist
+    #[cfg(feature = "face-id")]
+    track_faces: HashMap<u64, Vec<f32>>,
+    /// Scripted sensor inputs in place of hardware
+    #[cfg(feature = "simulation")]
+    scenario: Option<dark_phoenix_core::ScenarioPlayer>,
 }
 
+/// Assessments buffered per subscriber before slow receivers start lagging
+const ASSESSMENT_BUFFER: usize = 64;
+
+/// Event store stream holding every persisted assessment
+pub const THREAT_STREAM: &str = "threat_assessments";
+
+/// Event store stream holding every sensor input, when recording for replay
+pub const SENSOR_STREAM: &str = "sensor_inputs";
+
+/// Assessments kept in memory
+const HISTORY_LIMIT: usize = 1000;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ThreatDetectionConfig {
     pub sensitivity_level: f32, // 0.0 - 1.0
     pub false_positive_tolerance: f32,
     pub update_frequency_hz: u32,
     pub enabled_threat_types: Vec<ThreatType>,
     pub confidence_threshold: f32,
+    pub fusion_weights: FusionWeights,
+    pub fusion_thresholds: FusionThresholds,
+    pub max_input_age_ms: u64, // Inputs older than this are ignored, unless overridden per sensor
+    pub sensor_health: SensorHealthConfig,
+    pub threat_sensitivity: HashMap<ThreatType, f32>, // Per-type risk multiplier (absent = 1.0)
+    pub tracker: TrackerConfig,
+    pub handoff: HandoffConfig,
+    pub acoustic_alert_confidence: f32, // Gunshot, glass break or scream confidence that forces Red
+    pub immediate_analysis_sensors: Vec<String>, // New input from these is analysed without waiting for the next tick
+    pub mic_array: Option<MicArrayConfig>, // Enables acoustic direction finding
+    pub acoustic_range_m: f32, // Assumed distance to a sound located by bearing alone
+    pub zones: ZoneMap,
+    pub risk_half_life_secs: f32, // Age at which an assessment counts half as much towards the risk score
+    pub risk_trend_window_secs: f32, // Span of recent assessments the trend is fitted over
+    pub risk_trend_threshold: f32, // Risk score change per minute that counts as rising or falling
+    pub persist_green: bool, // Also persist routine Green assessments, not just threats and level changes
+    pub record_sensor_inputs: bool, // Persist every sensor input and assessment so the session can be replayed
+    #[cfg(feature = "rtsp")]
+    pub cameras: Vec<CameraConfig>, // IP cameras streamed in once the engine starts, each with its own zones
+    #[cfg(feature = "v4l2")]
+    pub v4l2_cameras: Vec<V4l2CameraConfig>, // Onboard cameras captured once the engine starts
+    #[cfg(feature = "thermal")]
+    pub thermal: ThermalBodyConfig, // Warm bodies picked out of thermal camera frames
+    #[cfg(feature = "radar")]
+    pub radar: Option<RadarConfig>, // mmWave radar read once the engine starts
+    #[cfg(feature = "face-id")]
+    pub known_person_discount: f32, // Share of threat weight kept for whitelisted people (0.0 ignores them)
 }
 
 impl Default for ThreatDetectionConfig {
@@ -166,13 +408,41 @@ impl Default for ThreatDetectionConfig {
                 ThreatType::HostileIntent,
                 ThreatType::GroupThreat,
                 ThreatType::EnvironmentalHazard,
+                ThreatType::Loitering,
             ],
             confidence_threshold: 0.6,
+            fusion_weights: FusionWeights::default(),
+            fusion_thresholds: FusionThresholds::default(),
+            max_input_age_ms: 2000,
+            sensor_health: SensorHealthConfig::default(),
+            threat_sensitivity: HashMap::new(),
+            tracker: TrackerConfig::default(),
+            handoff: HandoffConfig::default(),
+            acoustic_alert_confidence: 0.7,
+            immediate_analysis_sensors: vec!["microphone".to_string(), "microphone_array".to_string()],
+            mic_array: None,
+            acoustic_range_m: 25.0,
+            zones: ZoneMap::default(),
+            risk_half_life_secs: 30.0,
+            risk_trend_window_secs: 20.0,
+            risk_trend_threshold: 1.0,
+            persist_green: false,
+            record_sensor_inputs: false,
+            #[cfg(feature = "rtsp")]
+            cameras: Vec::new(),
+            #[cfg(feature = "v4l2")]
+            v4l2_cameras: Vec::new(),
+            #[cfg(feature = "thermal")]
+            thermal: ThermalBodyConfig::default(),
+            #[cfg(feature = "radar")]
+            radar: None,
+            #[cfg(feature = "face-id")]
+            known_person_discount: 0.1,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SensorInput {
     pub sensor_type: String,
     pub data: Vec<u8>,
@@ -182,31 +452,180 @@ pub struct SensorInput {
 
 impl UltraSeekerEngine {
     pub fn new(config: ThreatDetectionConfig) -> Self {
+        let mut pipeline = FusionPipeline::with_builtin_extractors(config.fusion_weights.clone());
+        if let Some(array) = &config.mic_array {
+            pipeline.register(MicArrayExtractor::new(array.clone()));
+        }
+        #[cfg(feature = "thermal")]
+        pipeline.register(ThermalExtractor::new(config.thermal.clone()));
+        #[cfg(feature = "radar")]
+        if let Some(radar) = &config.radar {
+            pipeline.register(radar::RadarExtractor::new(radar.clone()));
+        }
         Self {
+            pipeline,
+            tracker: MultiObjectTracker::new(config.tracker.clone()),
             config,
-            threat_history: Vec::new(),
+            threat_history: RingBuffer::new(HISTORY_LIMIT),
             sensor_inputs: HashMap::new(),
+            assessments: broadcast::channel(ASSESSMENT_BUFFER).0,
+            suppressions: SuppressionList::default(),
+            feedback: FeedbackStore::default(),
+            pose: None,
+            dwell: DwellTracker::default(),
+            camera_zones: HashMap::new(),
+            camera: None,
+            known_tracks: HashMap::new(),
+            handoffs: Vec::new(),
+            resumed: HashMap::new(),
+            handed_off: HashSet::new(),
+            store: None,
+            health: watch::channel(SensorHealthReport {
+                timestamp: Utc::now(),
+                sensors: Vec::new(),
+                degraded: Vec::new(),
+            })
+            .0,
+            metrics: None,
+            evidence: None,
+            clock: None,
+            #[cfg(feature = "siem")]
+            siem: None,
+            #[cfg(feature = "face-id")]
+            faces: FaceRegistry::default(),
+            #[cfg(feature = "face-id")]
+            face_embedder: None,
+            #[cfg(feature = "face-id")]
+            track_faces: HashMap::new(),
+            #[cfg(feature = "simulation")]
+            scenario: None,
         }
     }
 
+    /// Persist assessments to `store` and reload the most recent ones into history
+    pub fn with_event_store(mut self, store: EventStore) -> Result<Self, StoreError> {
+        self.threat_history.clear();
+        self.threat_history.extend(store.read_recent(THREAT_STREAM, HISTORY_LIMIT)?);
+        tracing::info!("💾 Restored {} threat assessments from {}", self.threat_history.len(), store.root().display());
+        self.store = Some(store);
+        Ok(self)
+    }
+
+    /// Export risk score, assessment counts and sensor staleness to `metrics`
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Forward assessments to a SIEM collector, all but those of an
+    /// all-green stretch
+    #[cfg(feature = "siem")]
+    pub fn with_siem(mut self, forwarder: dark_phoenix_core::SiemForwarder) -> Self {
+        self.siem = Some(forwarder);
+        self
+    }
+
+    /// Record video, audio and sensor snapshots around threats at or above
+    /// the recorder's trigger level
+    pub fn with_evidence(mut self, recorder: EvidenceRecorder) -> Self {
+        self.evidence = Some(recorder);
+        self
+    }
+
+    /// The evidence recorder, for listing and exporting captures
+    pub fn evidence(&self) -> Option<&EvidenceRecorder> {
+        self.evidence.as_ref()
+    }
+
+    /// Write out any open evidence capture, e.g. on shutdown
+    pub fn close_evidence(&mut self) -> Result<Option<CustodyManifest>, EvidenceError> {
+        match &mut self.evidence {
+            Some(evidence) => evidence.close(self.clock.unwrap_or_else(Utc::now)),
+            None => Ok(None),
+        }
+    }
+
+    /// Replace the detection zones, e.g. after the camera is re-aimed
+    pub fn set_zone_map(&mut self, zones: ZoneMap) {
+        tracing::info!("🗺️ {} detection zones configured", zones.zones.len());
+        self.config.zones = zones;
+    }
+
+    /// Zones of a named camera's view, for frames from `update_camera_frame`
+    pub fn set_camera_zones(&mut self, camera: &str, zones: ZoneMap) {
+        tracing::info!("🗺️ {} detection zones configured for camera '{}'", zones.zones.len(), camera);
+        self.camera_zones.insert(camera.to_string(), zones);
+    }
+
+    /// Where the platform is and which way it faces, so bearings become positions
+    pub fn set_pose(&mut self, position: Position, heading_deg: f32) {
+        self.pose = Some((position, heading_deg));
+    }
+
+    /// Freshness and quality of each sensor as of the last analysis
+    pub fn sensor_health(&self) -> SensorHealthReport {
+        self.health.borrow().clone()
+    }
+
+    /// Follow sensor health as it changes, e.g. to report degraded detection
+    /// to the core with `DroneState::update_sensor_health`
+    pub fn watch_sensor_health(&self) -> watch::Receiver<SensorHealthReport> {
+        self.health.subscribe()
+    }
+
+    /// Receive every assessment this engine produces from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ThreatAssessment> {
+        self.assessments.subscribe()
+    }
+
+    /// Move the engine onto its own analysis loop at `update_frequency_hz`
+    pub fn start(self) -> SeekerHandle {
+        SeekerHandle::start(self)
+    }
+
     /// Process sensor data and return threat assessment
     pub async fn analyze_threats(&mut self) -> Result<ThreatAssessment, Box<dyn std::error::Error>> {
-        // Placeholder for actual AI/ML processing
-        // In real implementation, this would:
-        // 1. Process camera feeds for object detection
-        // 2. Analyze audio for aggressive speech patterns
-        // 3. Track movement patterns for erratic behavior
-        // 4. Monitor biometrics for stress indicators
-        // 5. Check environmental sensors for hazards
-        
+        for expired in self.suppressions.expire(self.now()) {
+            tracing::info!("Suppression of {:?} expired ({})", expired.threat_type, expired.reason);
+        }
+        #[cfg(feature = "simulation")]
+        self.feed_scenario();
+
         let assessment = self.generate_assessment().await?;
+
+        // No subscribers is fine - polling callers still get the result
+        let _ = self.assessments.send(assessment.clone());
+
+        let previous = self.threat_history.last().map(|previous| previous.threat_level);
+        let routine = assessment.threat_level == ThreatLevel::Green && previous.is_none_or(|level| level == ThreatLevel::Green);
+        #[cfg(feature = "siem")]
+        if let Some(siem) = self.siem.as_ref().filter(|_| !routine) {
+            siem.forward(&assessment);
+        }
+        if let Some(store) = &self.store {
+            if self.config.persist_green || self.config.record_sensor_inputs || !routine {
+                if let Err(e) = store.append(THREAT_STREAM, &assessment) {
+                    tracing::warn!("⚠️ Failed to persist assessment {}: {}", assessment.id, e);
+                }
+            }
+        }
         
-        // Store in history for learning
+        if let Some(evidence) = &mut self.evidence {
+            if let Err(e) = evidence.observe(&assessment, self.clock.unwrap_or_else(Utc::now)) {
+                tracing::warn!("⚠️ Failed to record evidence for assessment {}: {}", assessment.id, e);
+            }
+        }
+
+        // Store in history for learning; the oldest falls off once full
         self.threat_history.push(assessment.clone());
-        
-        // Keep only recent history to prevent memory bloat
-        if self.threat_history.len() > 1000 {
-            self.threat_history.drain(0..100);
+
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .counter("phoenix_threat_assessments_total", "Threat assessments produced", &[("threat_level", assessment.threat_level.as_str())])
+                .inc();
+            metrics
+                .gauge("phoenix_threat_risk_score", "Time-weighted risk score over recent assessments", &[])
+                .set(f64::from(self.calculate_risk_score()));
         }
         
         Ok(assessment)
@@ -214,126 +633,785 @@ impl UltraSeekerEngine {
 
     /// Update sensor inputs from hardware
     pub fn update_sensor_input(&mut self, sensor_type: String, data: Vec<u8>) {
-        let input = SensorInput {
+        let mut input = SensorInput {
             sensor_type: sensor_type.clone(),
             data,
-            timestamp: Utc::now(),
-            quality: 1.0, // Placeholder - would be calculated based on sensor health
+            timestamp: self.now(),
+            quality: 1.0,
         };
+        input.quality = self.pipeline.quality(&input);
+
+        if let Some(store) = self.store.as_ref().filter(|_| self.config.record_sensor_inputs) {
+            if let Err(e) = store.append(SENSOR_STREAM, &input) {
+                tracing::warn!("⚠️ Failed to record {} input: {}", input.sensor_type, e);
+            }
+        }
         
+        if let Some(evidence) = &mut self.evidence {
+            if let Err(e) = evidence.record_input(&input) {
+                tracing::warn!("⚠️ Failed to record {} evidence: {}", input.sensor_type, e);
+            }
+        }
+        
+        if sensor_type == self.tracker.config().sensor_type {
+            self.camera = None;
+        }
         self.sensor_inputs.insert(sensor_type, input);
     }
 
-    /// Generate threat assessment based on current inputs
-    async fn generate_assessment(&self) -> Result<ThreatAssessment, Box<dyn std::error::Error>> {
-        // Placeholder implementation - real version would use ML models
-        
-        let base_threat_level = ThreatLevel::Green;
-        let mut confidence = 0.95;
-        let mut threat_types = Vec::new();
-        let mut recommended_actions = Vec::new();
-        
-        // Simulate threat detection logic
-        let evidence = ThreatEvidence {
-            visual_data: Some(VisualEvidence {
-                object_detections: vec![],
-                body_language_score: 0.1,
-                weapon_confidence: 0.0,
-                crowd_density: 1,
-                lighting_conditions: "Good".to_string(),
-            }),
-            audio_data: Some(AudioEvidence {
-                volume_level: 45.0,
-                aggression_score: 0.1,
-                keyword_matches: vec![],
-                voice_stress_level: 0.2,
-                gunshot_detected: false,
-                scream_detected: false,
-            }),
-            movement_data: Some(MovementEvidence {
-                velocity_anomaly: 0.0,
-                direction_changes: 0,
-                proximity_violations: 0,
-                pursuit_behavior: false,
-                escape_attempts: false,
-            }),
-            biometric_data: Some(BiometricEvidence {
-                elevated_heart_rate: false,
-                stress_hormones: Some(0.3),
-                body_temperature: Some(98.6),
-                breathing_pattern: Some("Normal".to_string()),
-            }),
-            environmental_data: Some(EnvironmentalEvidence {
-                temperature_anomaly: None,
-                smoke_detected: false,
-                chemical_traces: vec![],
-                structural_damage: false,
-                weather_conditions: "Clear".to_string(),
-            }),
+    /// A frame from one of several cameras, judged against that camera's zones
+    pub fn update_camera_frame(&mut self, camera: &str, frame: Vec<u8>) {
+        self.update_sensor_input(self.tracker.config().sensor_type.clone(), frame);
+        self.camera = Some(camera.to_string());
+    }
+
+    /// Register a feature extractor for a new (or replacement) sensor type
+    pub fn register_extractor(&mut self, extractor: impl FeatureExtractor + 'static) {
+        self.pipeline.register(extractor);
+    }
+
+    /// Run the configured ONNX models on camera frames and audio chunks
+    /// pushed through `update_sensor_input`, replacing the heuristic extractors
+    #[cfg(feature = "onnx")]
+    pub fn enable_onnx(&mut self, config: &OnnxConfig) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(path) = &config.object_model {
+            self.pipeline.register(OnnxObjectDetector::load(path, config)?);
+            tracing::info!("Object detection model loaded from {}", path.display());
+        }
+        if let Some(path) = &config.audio_model {
+            self.pipeline.register(OnnxAudioClassifier::load(path, config)?);
+            tracing::info!("Audio classification model loaded from {}", path.display());
+        }
+        Ok(())
+    }
+
+    /// Generate threat assessment by fusing the current sensor inputs
+    async fn generate_assessment(&mut self) -> Result<ThreatAssessment, Box<dyn std::error::Error>> {
+        let now = self.now();
+        let health = self.config.sensor_health.evaluate(&self.sensor_inputs, self.config.max_input_age_ms, now);
+        let fresh = self
+            .sensor_inputs
+            .values()
+            .filter(|input| self.config.sensor_health.is_fresh(input, self.config.max_input_age_ms, now));
+        let (mut evidence, confidence, source_quality) = self.pipeline.extract(fresh);
+        self.track_objects(&mut evidence);
+        self.resume_handoffs(now);
+        let local_time = now.with_timezone(&chrono::Local).time();
+        let frame_time = self.sensor_inputs.get(&self.tracker.config().sensor_type).map(|frame| frame.timestamp);
+        let (zones, loiterers) = match (&mut evidence.visual_data, frame_time) {
+            (Some(visual), Some(frame_time)) => {
+                let zone_map = self.camera.as_ref().and_then(|camera| self.camera_zones.get(camera)).unwrap_or(&self.config.zones);
+                let zones = zone_map.evaluate(&mut visual.object_detections, local_time);
+                let loiterers = self.dwell.update(zone_map, &visual.object_detections, frame_time, local_time);
+                (zones, loiterers)
+            },
+            _ => (ZoneEvaluation::default(), Vec::new()),
+        };
+        // Sources earn their weight from operator verdicts and lose it with poor
+        // signal; evidence the tracker derived has no input quality of its own
+        let reliability = EvidenceSource::ALL
+            .into_iter()
+            .filter(|source| source.risk(&evidence).is_some())
+            .map(|source| {
+                let quality = source_quality.get(&source).copied().unwrap_or(1.0);
+                (source, self.feedback.reliability(source) * quality)
+            })
+            .collect();
+        let mut fused = self.pipeline.score(evidence, confidence, &reliability);
+        if !loiterers.is_empty() && !fused.threat_types.contains(&ThreatType::Loitering) {
+            fused.threat_types.push(ThreatType::Loitering);
+        }
+
+        let mut threat_types: Vec<ThreatType> = fused
+            .threat_types
+            .iter()
+            .copied()
+            .filter(|threat_type| self.config.enabled_threat_types.contains(threat_type))
+            .filter(|threat_type| !self.suppressions.is_suppressed(*threat_type, now))
+            .collect();
+
+        // Risk follows the most sensitive remaining type; evidence that only
+        // supports disabled or suppressed types carries no risk at all
+        let type_multiplier = if fused.threat_types.is_empty() {
+            1.0
+        } else {
+            threat_types
+                .iter()
+                .map(|threat_type| self.threat_sensitivity(*threat_type))
+                .fold(0.0, f32::max)
         };
 
-        // For demo purposes, occasionally simulate threats
-        let simulation_factor = chrono::Utc::now().timestamp() % 300;
-        let (threat_level, description) = if simulation_factor < 5 {
-            threat_types.push(ThreatType::ErraticBehavior);
-            recommended_actions.push("Increase monitoring sensitivity".to_string());
-            confidence = 0.7;
-            (ThreatLevel::Yellow, "Unusual movement pattern detected - monitoring".to_string())
+        let mut trace = std::mem::take(&mut fused.trace);
+        trace.input("fused risk", fused.risk_score, None);
+        trace.input("threat type sensitivity", type_multiplier, None);
+        trace.input("sensitivity level", self.config.sensitivity_level, None);
+        trace.input("input quality", fused.confidence, None);
+
+        // Types operators keep rejecting lose confidence, and with it escalation
+        let calibration = self.feedback.calibration_for(&threat_types);
+        let mut confidence = fused.confidence * calibration;
+        if calibration != 1.0 {
+            trace.input("operator feedback calibration", calibration, None);
+        }
+        // Without a key sensor the picture is incomplete, whatever the rest says
+        if health.is_degraded() {
+            confidence = confidence.min(self.config.sensor_health.degraded_confidence_cap);
+            trace.input("confidence cap (degraded sensors)", self.config.sensor_health.degraded_confidence_cap, None);
+        }
+
+        // Sensitivity bends the risk curve: 0.5 is neutral, higher amplifies weak signals
+        let risk = (fused.risk_score * type_multiplier)
+            .clamp(0.0, 1.0)
+            .powf(1.5 - self.config.sensitivity_level);
+        let thresholds = &self.config.fusion_thresholds;
+        let mut threat_level = thresholds.level_for(risk);
+        match thresholds.threshold_for(threat_level) {
+            Some(threshold) => trace.threshold(
+                "fusion thresholds",
+                threat_level,
+                risk,
+                threshold,
+                format!("risk {:.2} >= {} threshold {:.2}", risk, threat_level.as_str(), threshold),
+            ),
+            None => trace.threshold(
+                "fusion thresholds",
+                threat_level,
+                risk,
+                thresholds.yellow,
+                format!("risk {:.2} < YELLOW threshold {:.2}", risk, thresholds.yellow),
+            ),
+        }
+        // Poor-quality inputs may raise awareness but never drive escalation on their own
+        if confidence < self.config.confidence_threshold && threat_level > ThreatLevel::Yellow {
+            threat_level = ThreatLevel::Yellow;
+            trace.threshold(
+                "confidence threshold",
+                threat_level,
+                confidence,
+                self.config.confidence_threshold,
+                format!("confidence {:.2} < {:.2}; held at YELLOW", confidence, self.config.confidence_threshold),
+            );
+        }
+
+        // A clearly classified gunshot, breaking glass or scream is decisive on its own
+        let acoustic_alert = fused.evidence.audio_data.as_ref().and_then(|audio| {
+            audio
+                .acoustic_events
+                .iter()
+                .filter(|detection| detection.confidence >= self.config.acoustic_alert_confidence)
+                .filter(|detection| threat_types.contains(&detection.event.threat_type()))
+                .max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+                .cloned()
+        });
+        if let Some(detection) = &acoustic_alert {
+            threat_level = threat_level.max(ThreatLevel::Red);
+            confidence = confidence.max(detection.confidence);
+            trace.threshold(
+                "acoustic alert",
+                threat_level,
+                detection.confidence,
+                self.config.acoustic_alert_confidence,
+                format!(
+                    "{} at {:.2} >= {:.2}",
+                    detection.event.description(),
+                    detection.confidence,
+                    self.config.acoustic_alert_confidence
+                ),
+            );
+            tracing::warn!("🔫 {} ({:.0}% confidence)", detection.event.description(), detection.confidence * 100.0);
+        }
+
+        // Zones bound the level: a capped zone (a public sidewalk) cannot escalate
+        // past its cap unless a weapon is involved, and entry rules (the porch
+        // after midnight) raise a floor
+        if let Some(ceiling) = zones.ceiling {
+            if acoustic_alert.is_none() && !threat_types.contains(&ThreatType::WeaponDetected) && ceiling < threat_level {
+                threat_level = ceiling;
+                trace.rule("zone ceiling", threat_level, "every detection is in a zone capped here, and no weapon is involved");
+            }
+        }
+        let mut zone_entry = None;
+        if let Some((floor, zone)) = &zones.floor {
+            if *floor > threat_level {
+                threat_level = *floor;
+                zone_entry = Some(format!("Entry into {} zone", zone));
+                trace.rule("zone entry", threat_level, format!("entry into the {} zone raises the floor", zone));
+            }
+        }
+        let loiterer = loiterers.first().filter(|_| threat_types.contains(&ThreatType::Loitering));
+        if let Some(loiterer) = loiterer {
+            if loiterer.level > threat_level {
+                threat_level = loiterer.level;
+                trace.rule(
+                    "loitering",
+                    threat_level,
+                    format!("track {} in the {} zone for {}s", loiterer.track_id, loiterer.zone, loiterer.dwell.as_secs()),
+                );
+                zone_entry = Some(format!(
+                    "Track {} loitering in {} zone for {}s",
+                    loiterer.track_id,
+                    loiterer.zone,
+                    loiterer.dwell.as_secs()
+                ));
+            }
+        }
+        // A subject handed over by another drone arrives at the level it left at
+        let handed_over = self.resumed.iter().max_by_key(|(_, handoff)| handoff.threat_level);
+        if let Some((track_id, handoff)) = handed_over {
+            if handoff.threat_level > threat_level {
+                threat_level = handoff.threat_level;
+                confidence = confidence.max(handoff.confidence);
+                trace.rule("handoff", threat_level, format!("track {} arrived from drone {} at this level", track_id, handoff.from_drone));
+                zone_entry = Some(format!(
+                    "Track {} handed over by drone {} at {}",
+                    track_id,
+                    handoff.from_drone,
+                    handoff.threat_level.as_str()
+                ));
+                for threat_type in &handoff.threat_types {
+                    let enabled = self.config.enabled_threat_types.contains(threat_type) && !self.suppressions.is_suppressed(*threat_type, now);
+                    if enabled && !threat_types.contains(threat_type) {
+                        threat_types.push(*threat_type);
+                    }
+                }
+            }
+        }
+
+        // A located sound gives the direction to aim cameras, strobes and nozzles
+        let position = fused
+            .evidence
+            .audio_data
+            .as_ref()
+            .and_then(|audio| audio.bearing_deg)
+            .zip(self.pose.as_ref())
+            .map(|(bearing, (platform, heading))| {
+                platform.destination((heading + bearing).rem_euclid(360.0) as f64, self.config.acoustic_range_m as f64)
+            });
+
+        let mut description = if acoustic_alert.is_some() || zone_entry.is_some() {
+            let mut parts: Vec<String> = acoustic_alert.iter().map(|detection| detection.event.description().to_string()).collect();
+            parts.extend(zone_entry);
+            parts.extend(threat_types.iter().map(|threat_type| threat_type.description().to_string()));
+            parts.join("; ")
+        } else if threat_types.is_empty() {
+            match threat_level {
+                ThreatLevel::Green => "All systems nominal - no threats detected".to_string(),
+                _ => format!("Elevated sensor risk {:.2} - monitoring", risk),
+            }
         } else {
-            recommended_actions.push("Continue passive monitoring".to_string());
-            (ThreatLevel::Green, "All systems nominal - no threats detected".to_string())
+            threat_types.iter().map(ThreatType::description).collect::<Vec<_>>().join("; ")
         };
-        
+        if health.is_degraded() {
+            description = format!("{} (degraded detection: {})", description, health.summary());
+        }
+        self.publish_health(health);
+
+        let recommended_actions = match threat_level {
+            ThreatLevel::Green => vec!["Continue passive monitoring".to_string()],
+            ThreatLevel::Yellow => vec!["Increase monitoring sensitivity".to_string()],
+            ThreatLevel::Orange => vec!["Activate warning deterrence".to_string(), "Track subject".to_string()],
+            ThreatLevel::Red | ThreatLevel::Omega => vec![
+                "Activate full deterrence".to_string(),
+                "Contact emergency services".to_string(),
+                "Deploy shield".to_string(),
+            ],
+        };
+
         Ok(ThreatAssessment {
             id: Uuid::new_v4(),
-            timestamp: Utc::now(),
+            timestamp: now,
             threat_level,
             confidence,
             threat_types,
-            position: None, // Would be calculated from drone GPS
+            position,
             description,
             recommended_actions,
-            evidence,
+            evidence: fused.evidence,
+            zone: zones
+                .floor
+                .map(|(_, zone)| zone)
+                .or_else(|| loiterer.map(|loiterer| loiterer.zone.clone()))
+                .or_else(|| zones.occupied.into_iter().next()),
+            trace,
         })
     }
 
+    /// Replace the latest sensor health, logging key sensors dropping out or recovering
+    fn publish_health(&self, report: SensorHealthReport) {
+        if let Some(metrics) = &self.metrics {
+            for sensor in &report.sensors {
+                let Some(last_seen) = sensor.last_seen else { continue };
+                let age = (report.timestamp - last_seen).num_milliseconds().max(0) as f64 / 1000.0;
+                metrics
+                    .gauge("phoenix_sensor_staleness_seconds", "Age of the newest input from each sensor", &[("sensor", &sensor.sensor_type)])
+                    .set(age);
+            }
+        }
+        let previous = self.health.send_replace(report);
+        let current = self.health.borrow();
+        if current.degraded == previous.degraded {
+            return;
+        }
+        if current.is_degraded() {
+            tracing::warn!("📡 Degraded detection: {}", current.summary());
+        } else {
+            tracing::info!("📡 All key sensors healthy again");
+        }
+    }
+
+    /// Carry track IDs across camera frames and derive movement from the trajectories
+    fn track_objects(&mut self, evidence: &mut ThreatEvidence) {
+        let Some(visual) = &mut evidence.visual_data else { return };
+        let Some(frame) = self.sensor_inputs.get(&self.tracker.config().sensor_type) else { return };
+        self.tracker.update(&mut visual.object_detections, frame.timestamp);
+        self.known_tracks.retain(|track_id, _| self.tracker.contains(*track_id));
+        self.handed_off.retain(|track_id| self.tracker.contains(*track_id));
+        #[cfg(feature = "face-id")]
+        self.track_faces.retain(|track_id, _| self.tracker.contains(*track_id));
+        #[cfg(feature = "face-id")]
+        self.identify_known_people(visual);
+
+        #[cfg(feature = "radar")]
+        if let (Some(radar), Some(movement)) = (&self.config.radar, &mut evidence.movement_data) {
+            let bearings: Vec<(u64, f32)> = self
+                .tracker
+                .tracks()
+                .filter_map(|track| {
+                    let point = track.ground_track(self.tracker.config()).last().copied()?;
+                    Some((track.id, point.x.atan2(point.y).to_degrees().rem_euclid(360.0)))
+                })
+                .collect();
+            radar::associate(&mut movement.targets, &bearings, radar.match_bearing_deg);
+        }
+
+        // Whitelisted people hurrying towards the protectee are not pursuers
+        if let Some(movement) = self.tracker.movement_evidence(|track| self.known_tracks.contains_key(&track.id)) {
+            fusion::merge(evidence, Extracted::Movement(movement));
+        }
+    }
+
+    /// Tag whitelisted people in the frame and discount the threat they carry
+    #[cfg(feature = "face-id")]
+    fn identify_known_people(&mut self, visual: &mut VisualEvidence) {
+        let is_person = |detection: &ObjectDetection| matches!(detection.object_type.as_str(), "person" | "face");
+        let mut frame = None;
+        for detection in visual.object_detections.iter_mut().filter(|detection| is_person(detection)) {
+            let remembered = detection.track_id.and_then(|track_id| self.known_tracks.get(&track_id).copied());
+            let known = remembered.or_else(|| {
+                let embedder = self.face_embedder.as_ref()?;
+                let frame = frame
+                    .get_or_insert_with(|| {
+                        let input = self.sensor_inputs.get(&self.tracker.config().sensor_type)?;
+                        image::load_from_memory(&input.data).ok().map(|image| image.to_rgb8())
+                    })
+                    .as_ref()?;
+                let face = face::face_crop(frame, &detection.object_type, detection.bounding_box)?;
+                let embedding = embedder.embed(&face)?;
+                if let Some(track_id) = detection.track_id {
+                    self.track_faces.insert(track_id, embedding.clone());
+                }
+                let found = self.faces.identify(&embedding)?;
+                // Tracked people are matched once; untracked faces on every frame
+                match detection.track_id {
+                    Some(track_id) => {
+                        tracing::info!("👤 {} ({:?}) recognised, similarity {:.2}", found.name, found.role, found.similarity);
+                        self.known_tracks.insert(track_id, found.person_id);
+                    },
+                    None => tracing::debug!("👤 {} in view", found.name),
+                }
+                Some(found.person_id)
+            });
+            if let Some(person_id) = known {
+                detection.known_person = Some(person_id);
+                detection.threat_relevance *= self.config.known_person_discount;
+            }
+        }
+
+        // Body language is scored for the whole frame, so only discount it
+        // when everyone in view is whitelisted
+        let mut people = visual.object_detections.iter().filter(|detection| is_person(detection)).peekable();
+        if people.peek().is_some() && people.all(|detection| detection.known_person.is_some()) {
+            visual.body_language_score *= self.config.known_person_discount;
+        }
+    }
+
+    /// Confirmed tracks last seen leaving the frame and not yet handed off
+    pub fn departing_tracks(&self) -> Vec<u64> {
+        self.tracker
+            .tracks()
+            .filter(|track| !self.handed_off.contains(&track.id))
+            .filter(|track| handoff::is_departing(track, self.config.handoff.edge_margin))
+            .map(|track| track.id)
+            .collect()
+    }
+
+    /// Pack up a track for the drone whose coverage it is heading into,
+    /// with the highest level assessed over `severity_window_secs`
+    pub fn prepare_handoff(&mut self, track_id: u64, from_drone: Uuid) -> Option<TrackHandoff> {
+        let track = self.tracker.tracks().find(|track| track.id == track_id)?;
+        let now = self.now();
+        let window = chrono::Duration::seconds(self.config.handoff.severity_window_secs as i64);
+        let assessed = self
+            .threat_history
+            .recent_while(|assessment| now - assessment.timestamp <= window)
+            .max_by_key(|assessment| assessment.threat_level);
+        let resumed = self.resumed.get(&track_id);
+
+        let mut threat_types: Vec<ThreatType> = assessed.map(|assessment| assessment.threat_types.clone()).unwrap_or_default();
+        for threat_type in resumed.iter().flat_map(|handoff| &handoff.threat_types) {
+            if !threat_types.contains(threat_type) {
+                threat_types.push(*threat_type);
+            }
+        }
+        #[allow(unused_mut)]
+        let mut embeddings: Vec<Vec<f32>> = resumed.map(|handoff| handoff.embeddings.clone()).unwrap_or_default();
+        #[cfg(feature = "face-id")]
+        embeddings.extend(self.track_faces.get(&track_id).cloned());
+        let evidence = self
+            .evidence
+            .as_ref()
+            .and_then(EvidenceRecorder::current)
+            .map(|capture| {
+                capture
+                    .items
+                    .iter()
+                    .map(|item| EvidenceReference {
+                        capture_id: capture.capture_id,
+                        file: item.file.clone(),
+                        sha256: item.sha256.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let handoff = TrackHandoff {
+            handoff_id: Uuid::new_v4(),
+            from_drone,
+            track_id,
+            object_type: track.object_type.clone(),
+            trajectory: track.ground_track(self.tracker.config()),
+            last_position: self.track_position(track),
+            embeddings,
+            threat_level: assessed
+                .map(|assessment| assessment.threat_level)
+                .into_iter()
+                .chain(resumed.map(|handoff| handoff.threat_level))
+                .max()
+                .unwrap_or(ThreatLevel::Green),
+            confidence: assessed.map_or(0.0, |assessment| assessment.confidence),
+            threat_types,
+            zone: assessed.and_then(|assessment| assessment.zone.clone()).or_else(|| resumed.and_then(|handoff| handoff.zone.clone())),
+            evidence,
+            handed_off_at: now,
+        };
+        tracing::info!("🤝 Handing off track {} ({}) at {}", track_id, handoff.object_type, handoff.threat_level.as_str());
+        self.handed_off.insert(track_id);
+        Some(handoff)
+    }
+
+    /// Take in a subject another drone handed off; the next matching track
+    /// resumes at its level
+    pub fn accept_handoff(&mut self, handoff: TrackHandoff) {
+        if self.handoffs.iter().chain(self.resumed.values()).any(|held| held.handoff_id == handoff.handoff_id) {
+            return;
+        }
+        tracing::info!(
+            "🤝 Drone {} handed over a {} at {}, watching for it",
+            handoff.from_drone,
+            handoff.object_type,
+            handoff.threat_level.as_str()
+        );
+        self.handoffs.push(handoff);
+    }
+
+    /// Handoffs received and still waiting for their subject
+    pub fn pending_handoffs(&self) -> &[TrackHandoff] {
+        &self.handoffs
+    }
+
+    /// Match waiting handoffs to tracks in view, dropping the ones that have
+    /// waited too long and resumed tracks that are gone
+    fn resume_handoffs(&mut self, now: DateTime<Utc>) {
+        self.resumed.retain(|track_id, _| self.tracker.contains(*track_id));
+        let ttl = chrono::Duration::seconds(self.config.handoff.accept_secs as i64);
+        self.handoffs.retain(|handoff| {
+            let waiting = now - handoff.handed_off_at < ttl;
+            if !waiting {
+                tracing::info!("🤝 Handoff of a {} from drone {} expired unclaimed", handoff.object_type, handoff.from_drone);
+            }
+            waiting
+        });
+        if self.handoffs.is_empty() {
+            return;
+        }
+
+        let candidates: Vec<(u64, usize)> = self
+            .tracker
+            .tracks()
+            .filter(|track| track.misses == 0 && !self.resumed.contains_key(&track.id) && !self.known_tracks.contains_key(&track.id))
+            .filter_map(|track| {
+                let position = self.track_position(track);
+                let index = self.handoffs.iter().position(|handoff| self.claims(handoff, track, position.as_ref()))?;
+                Some((track.id, index))
+            })
+            .collect();
+        let mut claimed: Vec<(u64, usize)> = Vec::new();
+        for (track_id, index) in candidates {
+            if !claimed.iter().any(|(_, taken)| *taken == index) {
+                claimed.push((track_id, index));
+            }
+        }
+        // Remove from the back so the indices stay valid
+        claimed.sort_by_key(|(_, index)| std::cmp::Reverse(*index));
+        for (track_id, index) in claimed {
+            let handoff = self.handoffs.remove(index);
+            tracing::warn!(
+                "🤝 Track {} resumes drone {}'s {} at {}",
+                track_id,
+                handoff.from_drone,
+                handoff.object_type,
+                handoff.threat_level.as_str()
+            );
+            self.resumed.insert(track_id, handoff);
+        }
+    }
+
+    /// Whether a track in view is the subject of a handoff: by face when
+    /// both sides have one, otherwise by kind and distance
+    fn claims(&self, handoff: &TrackHandoff, track: &Track, position: Option<&Position>) -> bool {
+        #[cfg(feature = "face-id")]
+        if let Some(similarity) = self.track_faces.get(&track.id).and_then(|embedding| handoff.similarity(embedding)) {
+            return track.object_type == handoff.object_type && similarity >= self.faces.match_threshold;
+        }
+        handoff.fits(track, position, &self.config.handoff)
+    }
+
+    /// Where a track stands on the ground, taking the protectee anchor of
+    /// the frame as beneath the platform
+    fn track_position(&self, track: &Track) -> Option<Position> {
+        let (platform, heading) = self.pose.as_ref()?;
+        let point = track.ground_track(self.tracker.config()).last().copied()?;
+        let (x, y) = (f64::from(point.x), f64::from(point.y));
+        let bearing = (f64::from(*heading) + x.atan2(y).to_degrees()).rem_euclid(360.0);
+        Some(platform.destination(bearing, x.hypot(y)))
+    }
+
+    /// How long a track has spent in a zone so far
+    pub fn dwell_time(&self, track_id: u64, zone: &str) -> Option<std::time::Duration> {
+        self.dwell.dwell(track_id, zone)
+    }
+
+    /// Confirmed tracks from the most recent camera frame
+    pub fn tracks(&self) -> impl Iterator<Item = &Track> {
+        self.tracker.tracks()
+    }
+
     /// Adjust sensitivity based on environmental factors
     pub fn adjust_sensitivity(&mut self, new_sensitivity: f32) {
         self.config.sensitivity_level = new_sensitivity.clamp(0.0, 1.0);
         tracing::info!("Threat detection sensitivity adjusted to {}", self.config.sensitivity_level);
     }
 
+    pub fn threat_sensitivity(&self, threat_type: ThreatType) -> f32 {
+        self.config.threat_sensitivity.get(&threat_type).copied().unwrap_or(1.0)
+    }
+
+    /// Scale the risk attributed to one threat type (1.0 = neutral)
+    pub fn set_threat_sensitivity(&mut self, threat_type: ThreatType, multiplier: f32) {
+        let multiplier = multiplier.max(0.0);
+        self.config.threat_sensitivity.insert(threat_type, multiplier);
+        tracing::info!("{:?} sensitivity multiplier set to {}", threat_type, multiplier);
+    }
+
+    pub fn enable_threat_type(&mut self, threat_type: ThreatType) {
+        if !self.config.enabled_threat_types.contains(&threat_type) {
+            self.config.enabled_threat_types.push(threat_type);
+            tracing::info!("{:?} detection enabled", threat_type);
+        }
+    }
+
+    pub fn disable_threat_type(&mut self, threat_type: ThreatType) {
+        self.config.enabled_threat_types.retain(|enabled| *enabled != threat_type);
+        tracing::info!("{:?} detection disabled", threat_type);
+    }
+
+    /// Ignore a threat type for a limited time, e.g. vehicles during a parade
+    pub fn suppress_threat_type(&mut self, threat_type: ThreatType, duration: std::time::Duration, reason: &str) -> ThreatSuppression {
+        let suppression = self.suppressions.suppress(threat_type, duration, reason);
+        tracing::warn!("{:?} suppressed until {}: {}", threat_type, suppression.until, reason);
+        suppression
+    }
+
+    /// End a suppression early
+    pub fn lift_suppression(&mut self, threat_type: ThreatType) -> bool {
+        self.suppressions.lift(threat_type)
+    }
+
+    pub fn active_suppressions(&self) -> Vec<ThreatSuppression> {
+        self.suppressions.active(self.now())
+    }
+
+    /// Record an operator verdict on a past assessment
+    pub fn mark_assessment(&mut self, id: Uuid, verdict: Verdict) -> Result<(), FeedbackError> {
+        let assessment = self
+            .threat_history
+            .iter()
+            .find(|assessment| assessment.id == id)
+            .ok_or(FeedbackError::UnknownAssessment(id))?;
+        self.feedback.mark(assessment, verdict);
+        tracing::info!("Assessment {} marked {:?}", id, verdict);
+        Ok(())
+    }
+
+    pub fn assessment_verdict(&self, id: Uuid) -> Option<Verdict> {
+        self.feedback.verdict(id)
+    }
+
+    /// Precision/recall at the current threshold, per type and across thresholds
+    pub fn feedback_report(&self) -> FeedbackReport {
+        self.feedback.report(self.config.confidence_threshold)
+    }
+
     /// Get historical threat patterns for analysis
-    pub fn get_threat_history(&self) -> &[ThreatAssessment] {
+    pub fn get_threat_history(&self) -> &RingBuffer<ThreatAssessment> {
         &self.threat_history
     }
 
-    /// Calculate overall risk score based on recent assessments
+    /// Write assessments as JSON lines: the whole persisted log when an event
+    /// store is attached, otherwise the in-memory history
+    pub fn export_jsonl(&self, writer: impl std::io::Write, options: &ExportOptions) -> Result<usize, Box<dyn std::error::Error>> {
+        let assessments = self.exportable()?;
+        export::export_jsonl(&assessments, writer, options)
+    }
+
+    /// Write assessments as a Parquet file for offline analysis or training
+    #[cfg(feature = "parquet")]
+    pub fn export_parquet(&self, writer: impl std::io::Write + Send, options: &ExportOptions) -> Result<usize, Box<dyn std::error::Error>> {
+        let assessments = self.exportable()?;
+        export::export_parquet(&assessments, writer, options)
+    }
+
+    fn exportable(&self) -> Result<Vec<ThreatAssessment>, StoreError> {
+        match &self.store {
+            Some(store) => store.read(THREAT_STREAM),
+            None => Ok(self.threat_history.iter().cloned().collect()),
+        }
+    }
+
+    /// Overall risk from recent assessments, weighted by exponential time
+    /// decay so a threat seconds ago outweighs one from minutes ago, and
+    /// fading as the newest assessment ages
     pub fn calculate_risk_score(&self) -> f32 {
-        if self.threat_history.is_empty() {
+        let now = self.now();
+        let half_life = self.config.risk_half_life_secs.max(0.001);
+        let weight = |assessment: &ThreatAssessment| {
+            let age_secs = (now - assessment.timestamp).num_milliseconds().max(0) as f32 / 1000.0;
+            0.5f32.powf(age_secs / half_life)
+        };
+
+        // Older than ten half-lives contributes under 0.1%
+        let horizon = chrono::Duration::milliseconds((half_life * 10_000.0) as i64);
+        let recent = self.threat_history.recent_while(|assessment| now - assessment.timestamp <= horizon);
+        let (weighted, total_weight) = recent.fold((0.0, 0.0), |(sum, total), assessment| {
+            let w = weight(assessment);
+            (sum + w * assessment_score(assessment), total + w)
+        });
+        if total_weight <= 0.0 {
             return 0.0;
         }
+        let freshness = self.threat_history.last().map(weight).unwrap_or(0.0);
+        weighted / total_weight * freshness
+    }
 
-        let recent_assessments = self.threat_history
-            .iter()
-            .rev()
-            .take(10)
-            .collect::<Vec<_>>();
+    /// Whether risk is rising, steady or falling, from the least-squares
+    /// slope of assessment scores over `risk_trend_window_secs`
+    pub fn risk_trend(&self) -> RiskTrend {
+        risk_trend_at(&self.threat_history, &self.config, self.now())
+    }
 
-        let total_score: f32 = recent_assessments
-            .iter()
-            .map(|assessment| {
-                let base_score = assessment.threat_level as u8 as f32;
-                let confidence_modifier = assessment.confidence;
-                let type_modifier: f32 = assessment.threat_types
-                    .iter()
-                    .map(|t| t.severity_multiplier())
-                    .sum();
-                
-                base_score * confidence_modifier * (1.0 + type_modifier / 10.0)
-            })
-            .sum();
+    /// Wall-clock time, or the recorded time while replaying
+    fn now(&self) -> DateTime<Utc> {
+        self.clock.unwrap_or_else(Utc::now)
+    }
+}
+
+/// Risk trend over `history` as of `now`, shared by the engine and replays
+/// of the original run
+fn risk_trend_at(history: &RingBuffer<ThreatAssessment>, config: &ThreatDetectionConfig, now: DateTime<Utc>) -> RiskTrend {
+    let window = chrono::Duration::milliseconds((config.risk_trend_window_secs * 1000.0) as i64);
+    let points: Vec<(f32, f32)> = history
+        .recent_while(|assessment| now - assessment.timestamp <= window)
+        .map(|assessment| {
+            let minutes_ago = (now - assessment.timestamp).num_milliseconds() as f32 / 60_000.0;
+            (-minutes_ago, assessment_score(assessment))
+        })
+        .collect();
+    if points.len() < 3 {
+        return RiskTrend::Stable;
+    }
+
+    let count = points.len() as f32;
+    let mean_t = points.iter().map(|(t, _)| t).sum::<f32>() / count;
+    let mean_score = points.iter().map(|(_, score)| score).sum::<f32>() / count;
+    let covariance: f32 = points.iter().map(|(t, score)| (t - mean_t) * (score - mean_score)).sum();
+    let variance: f32 = points.iter().map(|(t, _)| (t - mean_t).powi(2)).sum();
+    if variance <= f32::EPSILON {
+        return RiskTrend::Stable;
+    }
 
-        total_score / recent_assessments.len() as f32
+    let per_minute = covariance / variance;
+    if per_minute > config.risk_trend_threshold {
+        RiskTrend::Rising
+    } else if per_minute < -config.risk_trend_threshold {
+        RiskTrend::Falling
+    } else {
+        RiskTrend::Stable
     }
 }
+
+/// Known-person whitelist management
+#[cfg(feature = "face-id")]
+impl UltraSeekerEngine {
+    /// Whitelist someone from a face embedding
+    pub fn enroll_person(&mut self, name: &str, role: PersonRole, embedding: &[f32]) -> Result<Uuid, EnrollmentError> {
+        let id = self.faces.enroll(name, role, embedding)?;
+        tracing::info!("👤 {} enrolled as {:?}", name, role);
+        Ok(id)
+    }
+
+    /// Whitelist someone from a photo of their face, using the configured embedder
+    pub fn enroll_face_image(&mut self, name: &str, role: PersonRole, image: &[u8]) -> Result<Uuid, EnrollmentError> {
+        let embedder = self.face_embedder.as_ref().ok_or(EnrollmentError::NoEmbedder)?;
+        let face = image::load_from_memory(image)
+            .map_err(|e| EnrollmentError::InvalidImage(e.to_string()))?
+            .to_rgb8();
+        let embedding = embedder.embed(&face).ok_or(EnrollmentError::NoFace)?;
+        self.enroll_person(name, role, &embedding)
+    }
+
+    pub fn add_face_embedding(&mut self, id: Uuid, embedding: &[f32]) -> Result<(), EnrollmentError> {
+        self.faces.add_embedding(id, embedding)
+    }
+
+    pub fn remove_person(&mut self, id: Uuid) -> Option<KnownPerson> {
+        self.known_tracks.retain(|_, person_id| *person_id != id);
+        let removed = self.faces.remove(id)?;
+        tracing::info!("👤 {} removed from the whitelist", removed.name);
+        Some(removed)
+    }
+
+    pub fn known_people(&self) -> &[KnownPerson] {
+        self.faces.people()
+    }
+
+    pub fn set_face_embedder(&mut self, embedder: impl FaceEmbedder + 'static) {
+        self.face_embedder = Some(Box::new(embedder));
+    }
+}
+
+/// Risk contributed by one assessment: level scaled by confidence and threat types
+fn assessment_score(assessment: &ThreatAssessment) -> f32 {
+    let base_score = assessment.threat_level as u8 as f32;
+    let type_modifier: f32 = assessment.threat_types.iter().map(|t| t.severity_multiplier()).sum();
+    base_score * assessment.confidence * (1.0 + type_modifier / 10.0)
+}
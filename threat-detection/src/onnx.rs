@@ -0,0 +1,267 @@
+//! ONNX model inference for camera frames and audio chunks (`onnx` feature)
+
+use crate::acoustic::{AcousticDetection, AcousticEvent};
+pub use crate::extractors::threat_type_for;
+use crate::extractors::{pcm_samples, CameraExtractor, MicrophoneExtractor};
+use crate::fusion::{Extracted, ExtractionError, FeatureExtractor};
+use crate::tracking::iou;
+use crate::{ObjectDetection, SensorInput, ThreatType};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tract_onnx::prelude::*;
+
+type Plan = TypedSimplePlan<TypedModel>;
+
+/// Model locations and label sets for the inference backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnnxConfig {
+    /// YOLOv5-style detector: `[1, 3, H, W]` in, `[1, N, 5 + classes]` out
+    pub object_model: Option<PathBuf>,
+    /// Waveform classifier: `[1, samples]` in, `[1, classes]` or `[frames, classes]` out
+    pub audio_model: Option<PathBuf>,
+    /// Detector input width and height in pixels
+    pub input_size: (u32, u32),
+    pub object_labels: Vec<String>,
+    pub audio_labels: Vec<String>,
+    /// Minimum class score for a detection or audio event to count
+    pub score_threshold: f32,
+    /// Samples per classifier window (e.g. 15600 for YAMNet at 16 kHz)
+    pub audio_window: usize,
+}
+
+impl Default for OnnxConfig {
+    fn default() -> Self {
+        Self {
+            object_model: None,
+            audio_model: None,
+            input_size: (640, 640),
+            object_labels: Vec::new(),
+            audio_labels: Vec::new(),
+            score_threshold: 0.5,
+            audio_window: 15_600,
+        }
+    }
+}
+
+/// How much a detected object class matters to threat assessment (0.0-1.0)
+fn threat_relevance(label: &str) -> f32 {
+    match label.to_lowercase().as_str() {
+        "gun" | "pistol" | "handgun" | "rifle" | "firearm" => 1.0,
+        "knife" => 0.9,
+        "baseball bat" | "bat" | "crowbar" => 0.6,
+        "person" => 0.2,
+        _ => match threat_type_for(label) {
+            Some(_) => 0.5,
+            None => 0.0,
+        },
+    }
+}
+
+fn load(path: &Path, input: InferenceFact) -> TractResult<Plan> {
+    tract_onnx::onnx()
+        .model_for_path(path)?
+        .with_input_fact(0, input)?
+        .into_optimized()?
+        .into_runnable()
+}
+
+fn inference_error(sensor: &str, error: impl std::fmt::Display) -> ExtractionError {
+    ExtractionError::invalid(sensor, format!("inference failed: {}", error))
+}
+
+/// Camera extractor that runs an object-detection model on each frame
+pub struct OnnxObjectDetector {
+    model: Plan,
+    labels: Vec<String>,
+    input_size: (u32, u32),
+    score_threshold: f32,
+}
+
+impl OnnxObjectDetector {
+    pub fn load(path: &Path, config: &OnnxConfig) -> TractResult<Self> {
+        let (width, height) = config.input_size;
+        let fact = f32::fact([1, 3, height as usize, width as usize]).into();
+        Ok(Self {
+            model: load(path, fact)?,
+            labels: config.object_labels.clone(),
+            input_size: config.input_size,
+            score_threshold: config.score_threshold,
+        })
+    }
+
+    fn detect(&self, frame: &image::RgbImage) -> TractResult<Vec<ObjectDetection>> {
+        let (width, height) = self.input_size;
+        let resized = image::imageops::resize(frame, width, height, image::imageops::FilterType::Triangle);
+        let input: Tensor = tract_ndarray::Array4::from_shape_fn((1, 3, height as usize, width as usize), |(_, c, y, x)| {
+            resized.get_pixel(x as u32, y as u32)[c] as f32 / 255.0
+        })
+        .into();
+
+        let outputs = self.model.run(tvec!(input.into()))?;
+        let predictions = outputs[0].to_array_view::<f32>()?;
+        let rows = predictions.shape().get(1).copied().unwrap_or(0);
+        let columns = predictions.shape().get(2).copied().unwrap_or(0);
+        if columns < 6 {
+            return Ok(Vec::new());
+        }
+
+        let mut candidates = Vec::new();
+        for row in 0..rows {
+            let value = |column: usize| predictions[[0, row, column]];
+            let objectness = value(4);
+            let (class, class_score) = (5..columns)
+                .map(|column| (column - 5, value(column)))
+                .fold((0, 0.0f32), |best, candidate| if candidate.1 > best.1 { candidate } else { best });
+            let confidence = objectness * class_score;
+            if confidence < self.score_threshold {
+                continue;
+            }
+
+            // Centre-based pixel box to top-left box as fractions of the frame
+            let (w, h) = (value(2) / width as f32, value(3) / height as f32);
+            let (x, y) = (value(0) / width as f32 - w / 2.0, value(1) / height as f32 - h / 2.0);
+            let label = self.labels.get(class).cloned().unwrap_or_else(|| format!("class_{}", class));
+            candidates.push(ObjectDetection {
+                threat_relevance: threat_relevance(&label),
+                object_type: label,
+                confidence,
+                bounding_box: (x, y, w, h),
+                track_id: None,
+                known_person: None,
+                zone: None,
+            });
+        }
+        Ok(non_max_suppression(candidates, 0.45))
+    }
+}
+
+impl FeatureExtractor for OnnxObjectDetector {
+    fn sensor_type(&self) -> &str {
+        "camera"
+    }
+
+    fn extract(&self, input: &SensorInput) -> Result<Extracted, ExtractionError> {
+        let Extracted::Visual(mut visual) = CameraExtractor.extract(input)? else {
+            unreachable!("camera extractor yields visual evidence");
+        };
+        let frame = image::load_from_memory(&input.data)
+            .map_err(|e| ExtractionError::invalid(self.sensor_type(), format!("undecodable frame: {}", e)))?
+            .to_rgb8();
+        let detections = self.detect(&frame).map_err(|e| inference_error(self.sensor_type(), e))?;
+
+        visual.weapon_confidence = detections
+            .iter()
+            .filter(|detection| threat_type_for(&detection.object_type) == Some(ThreatType::WeaponDetected))
+            .map(|detection| detection.confidence)
+            .fold(0.0, f32::max);
+        visual.crowd_density = detections.iter().filter(|detection| detection.object_type == "person").count() as u32;
+        visual.object_detections = detections;
+        Ok(Extracted::Visual(visual))
+    }
+
+    fn quality(&self, input: &SensorInput) -> f32 {
+        CameraExtractor.quality(input)
+    }
+}
+
+/// Keep the most confident box among heavily overlapping boxes of the same class
+fn non_max_suppression(mut candidates: Vec<ObjectDetection>, iou_threshold: f32) -> Vec<ObjectDetection> {
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    let mut kept: Vec<ObjectDetection> = Vec::new();
+    for candidate in candidates {
+        let overlaps = kept.iter().any(|existing| {
+            existing.object_type == candidate.object_type && iou(existing.bounding_box, candidate.bounding_box) > iou_threshold
+        });
+        if !overlaps {
+            kept.push(candidate);
+        }
+    }
+    kept
+}
+
+/// Microphone extractor that adds audio-event classification to level analysis
+pub struct OnnxAudioClassifier {
+    model: Plan,
+    labels: Vec<String>,
+    window: usize,
+    score_threshold: f32,
+    levels: MicrophoneExtractor,
+}
+
+impl OnnxAudioClassifier {
+    pub fn load(path: &Path, config: &OnnxConfig) -> TractResult<Self> {
+        let window = config.audio_window.max(1);
+        Ok(Self {
+            model: load(path, f32::fact([1, window]).into())?,
+            labels: config.audio_labels.clone(),
+            window,
+            score_threshold: config.score_threshold,
+            levels: MicrophoneExtractor::default(),
+        })
+    }
+
+    /// Labels scoring above the threshold in any window of the chunk
+    fn classify(&self, samples: &[f32]) -> TractResult<Vec<(String, f32)>> {
+        let mut events: Vec<(String, f32)> = Vec::new();
+        for chunk in samples.chunks(self.window) {
+            let mut window = chunk.to_vec();
+            window.resize(self.window, 0.0);
+            let input: Tensor = tract_ndarray::Array2::from_shape_vec((1, self.window), window)?.into();
+            let outputs = self.model.run(tvec!(input.into()))?;
+            let scores = outputs[0].to_array_view::<f32>()?;
+            let classes = scores.shape().last().copied().unwrap_or(0);
+
+            for (index, score) in scores.iter().enumerate() {
+                let class = index % classes.max(1);
+                if *score < self.score_threshold {
+                    continue;
+                }
+                let label = self.labels.get(class).cloned().unwrap_or_else(|| format!("class_{}", class));
+                match events.iter_mut().find(|(existing, _)| *existing == label) {
+                    Some(event) => event.1 = event.1.max(*score),
+                    None => events.push((label, *score)),
+                }
+            }
+        }
+        Ok(events)
+    }
+}
+
+impl FeatureExtractor for OnnxAudioClassifier {
+    fn sensor_type(&self) -> &str {
+        "microphone"
+    }
+
+    fn extract(&self, input: &SensorInput) -> Result<Extracted, ExtractionError> {
+        let Extracted::Audio(mut audio) = self.levels.extract(input)? else {
+            unreachable!("microphone extractor yields audio evidence");
+        };
+        let samples = pcm_samples(self.sensor_type(), &input.data)?;
+        let events = self.classify(&samples).map_err(|e| inference_error(self.sensor_type(), e))?;
+
+        for (label, score) in events {
+            let lower = label.to_lowercase();
+            let event = if lower.contains("gunshot") || lower.contains("gunfire") {
+                Some(AcousticEvent::Gunshot)
+            } else if lower.contains("glass") {
+                Some(AcousticEvent::GlassBreak)
+            } else if lower.contains("scream") {
+                Some(AcousticEvent::Scream)
+            } else {
+                None
+            };
+            if let Some(event) = event {
+                audio.record_event(AcousticDetection { event, confidence: score, offset_ms: 0 });
+            }
+            if threat_type_for(&label) == Some(ThreatType::PhysicalAggression) {
+                audio.aggression_score = audio.aggression_score.max(score);
+            }
+            audio.keyword_matches.push(label);
+        }
+        Ok(Extracted::Audio(audio))
+    }
+
+    fn quality(&self, input: &SensorInput) -> f32 {
+        self.levels.quality(input)
+    }
+}
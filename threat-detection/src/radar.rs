@@ -0,0 +1,274 @@
+use crate::fusion::{Extracted, ExtractionError, FeatureExtractor};
+use crate::{MovementEvidence, RadarTarget, SensorInput, UltraSeekerEngine};
+use dark_phoenix_core::runtime;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// mmWave presence radar on a serial port, e.g. an HLK-LD2450
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RadarConfig {
+    /// Sensor type the JSON `RadarTarget` lists arrive under
+    pub sensor_type: String,
+    pub port: String,
+    pub baud_rate: u32,
+    /// Radar boresight, clockwise from the platform's heading
+    pub mount_bearing_deg: f32,
+    /// Returns beyond this are ignored (metres)
+    pub max_range_m: f32,
+    /// Speed regarded as normal walking (m/s)
+    pub normal_speed: f32,
+    /// Distance treated as a personal-space violation (m)
+    pub personal_space_m: f32,
+    /// Widest bearing difference at which a return and a visual track are one subject
+    pub match_bearing_deg: f32,
+    /// Silence after which the port is reopened
+    pub stall_timeout_ms: u64,
+    pub reopen_ms: u64,
+    pub max_reopen_ms: u64,
+}
+
+impl Default for RadarConfig {
+    fn default() -> Self {
+        Self {
+            sensor_type: "radar".to_string(),
+            port: "/dev/ttyUSB0".to_string(),
+            baud_rate: 256_000,
+            mount_bearing_deg: 0.0,
+            max_range_m: 6.0,
+            normal_speed: 1.5,
+            personal_space_m: 2.0,
+            match_bearing_deg: 10.0,
+            stall_timeout_ms: 2000,
+            reopen_ms: 1000,
+            max_reopen_ms: 30_000,
+        }
+    }
+}
+
+impl RadarConfig {
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.port.is_empty() {
+            problems.push("radar.port is empty".to_string());
+        }
+        if !self.max_range_m.is_finite() || self.max_range_m <= 0.0 {
+            problems.push(format!("radar.max_range_m {} must be positive", self.max_range_m));
+        }
+        if !self.normal_speed.is_finite() || self.normal_speed <= 0.0 {
+            problems.push(format!("radar.normal_speed {} must be positive", self.normal_speed));
+        }
+        if !(0.0..=90.0).contains(&self.match_bearing_deg) {
+            problems.push(format!("radar.match_bearing_deg {} must be between 0 and 90", self.match_bearing_deg));
+        }
+        problems
+    }
+}
+
+/// JSON array of `RadarTarget`s - range, closing speed and personal space
+///
+/// Works in fog and darkness where the camera sees nothing; the engine then
+/// pairs each return with the visual track on the same bearing.
+pub struct RadarExtractor {
+    config: RadarConfig,
+}
+
+impl RadarExtractor {
+    pub fn new(config: RadarConfig) -> Self {
+        Self { config }
+    }
+
+    /// Movement evidence from one scan
+    pub fn analyze(&self, targets: Vec<RadarTarget>) -> MovementEvidence {
+        let targets: Vec<RadarTarget> = targets.into_iter().filter(|target| target.range_m <= self.config.max_range_m).collect();
+        let normal = self.config.normal_speed;
+        let fastest = targets.iter().map(|target| target.radial_speed_mps.abs()).fold(0.0, f32::max);
+        MovementEvidence {
+            velocity_anomaly: ((fastest - normal) / (normal * 3.0)).clamp(0.0, 1.0),
+            direction_changes: 0,
+            proximity_violations: targets.iter().filter(|target| target.range_m < self.config.personal_space_m).count() as u32,
+            pursuit_behavior: targets.iter().any(|target| target.radial_speed_mps < -normal),
+            escape_attempts: targets.iter().any(|target| target.radial_speed_mps > normal),
+            targets,
+        }
+    }
+}
+
+impl FeatureExtractor for RadarExtractor {
+    fn sensor_type(&self) -> &str {
+        &self.config.sensor_type
+    }
+
+    fn extract(&self, input: &SensorInput) -> Result<Extracted, ExtractionError> {
+        let targets: Vec<RadarTarget> = serde_json::from_slice(&input.data)
+            .map_err(|e| ExtractionError::invalid(self.sensor_type(), format!("invalid targets: {}", e)))?;
+        Ok(Extracted::Movement(self.analyze(targets)))
+    }
+}
+
+/// Give each unmatched return the closest track (id, bearing) within
+/// `max_difference_deg`, each track at most once
+pub(crate) fn associate(targets: &mut [RadarTarget], tracks: &[(u64, f32)], max_difference_deg: f32) {
+    let difference = |a: f32, b: f32| {
+        let turn = (a - b).rem_euclid(360.0);
+        turn.min(360.0 - turn)
+    };
+    let mut pairs = Vec::new();
+    for (target_index, target) in targets.iter().enumerate().filter(|(_, target)| target.track_id.is_none()) {
+        for &(track_id, bearing) in tracks {
+            let apart = difference(target.bearing_deg, bearing);
+            if apart <= max_difference_deg && !targets.iter().any(|target| target.track_id == Some(track_id)) {
+                pairs.push((apart, target_index, track_id));
+            }
+        }
+    }
+    pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut taken = Vec::new();
+    for (_, target_index, track_id) in pairs {
+        if targets[target_index].track_id.is_none() && !taken.contains(&track_id) {
+            targets[target_index].track_id = Some(track_id);
+            taken.push(track_id);
+        }
+    }
+}
+
+// LD2450 report frame: header, three 8-byte targets, tail
+const LD2450_HEADER: [u8; 4] = [0xAA, 0xFF, 0x03, 0x00];
+const LD2450_TAIL: [u8; 2] = [0x55, 0xCC];
+const LD2450_FRAME: usize = 30;
+
+/// Splits the LD2450's UART output into scans of up to three targets
+#[derive(Debug, Default)]
+pub struct Ld2450Decoder {
+    buffer: Vec<u8>,
+    mount_bearing_deg: f32,
+}
+
+impl Ld2450Decoder {
+    pub fn new(mount_bearing_deg: f32) -> Self {
+        Self { buffer: Vec::new(), mount_bearing_deg }
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// The next complete scan, skipping anything that is not a report frame
+    pub fn next_scan(&mut self) -> Option<Vec<RadarTarget>> {
+        loop {
+            let Some(start) = self.buffer.windows(LD2450_HEADER.len()).position(|window| window == LD2450_HEADER) else {
+                // Keep a possible partial header
+                let keep = self.buffer.len().min(LD2450_HEADER.len() - 1);
+                self.buffer.drain(..self.buffer.len() - keep);
+                return None;
+            };
+            self.buffer.drain(..start);
+            if self.buffer.len() < LD2450_FRAME {
+                return None;
+            }
+            if self.buffer[LD2450_FRAME - 2..LD2450_FRAME] != LD2450_TAIL {
+                self.buffer.drain(..1);
+                continue;
+            }
+            let targets = self.buffer[4..28].chunks_exact(8).filter_map(|target| self.target(target)).collect();
+            self.buffer.drain(..LD2450_FRAME);
+            return Some(targets);
+        }
+    }
+
+    fn target(&self, bytes: &[u8]) -> Option<RadarTarget> {
+        if bytes.iter().all(|&b| b == 0) {
+            return None;
+        }
+        // Sign in the top bit, set for positive
+        let value = |at: usize| {
+            let raw = u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+            let magnitude = (raw & 0x7FFF) as f32;
+            if raw & 0x8000 != 0 { magnitude } else { -magnitude }
+        };
+        // Millimetres right of and ahead of the radar; speed in cm/s, positive moving away
+        let (x, y, speed) = (value(0) / 1000.0, value(2) / 1000.0, value(4) / 100.0);
+        Some(RadarTarget {
+            range_m: x.hypot(y),
+            bearing_deg: (x.atan2(y).to_degrees() + self.mount_bearing_deg).rem_euclid(360.0),
+            radial_speed_mps: speed,
+            track_id: None,
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RadarError {
+    #[error("serial port failed: {0}")]
+    Serial(String),
+    #[error("no report for {0:?}")]
+    Stalled(Duration),
+    #[error("port closed")]
+    Ended,
+}
+
+/// Reads an LD2450 and hands every scan to the engine
+pub struct RadarIngest {
+    config: RadarConfig,
+}
+
+impl RadarIngest {
+    pub fn new(config: RadarConfig) -> Self {
+        Self { config }
+    }
+
+    /// Read until the task is dropped, reopening the port with backoff
+    pub async fn run(self, engine: Arc<Mutex<UltraSeekerEngine>>) {
+        let max_backoff = Duration::from_millis(self.config.max_reopen_ms.max(self.config.reopen_ms));
+        let mut backoff = Duration::from_millis(self.config.reopen_ms);
+        loop {
+            let mut reporting = false;
+            let Err(e) = self.open(&engine, &mut reporting).await;
+            warn!("📶 Radar on {}: {}; reopening in {:?}", self.config.port, e, backoff);
+            runtime::sleep(backoff).await;
+            backoff = if reporting { Duration::from_millis(self.config.reopen_ms) } else { (backoff * 2).min(max_backoff) };
+        }
+    }
+
+    async fn open(&self, engine: &Mutex<UltraSeekerEngine>, reporting: &mut bool) -> Result<Infallible, RadarError> {
+        use tokio_serial::SerialPortBuilderExt;
+        let port = tokio_serial::new(&self.config.port, self.config.baud_rate)
+            .open_native_async()
+            .map_err(|e| RadarError::Serial(e.to_string()))?;
+        info!("📶 Radar reading {} at {} baud", self.config.port, self.config.baud_rate);
+        self.forward(port, engine, reporting).await
+    }
+
+    /// Decode scans from `stream` into the engine; `reporting` is set once
+    /// one has arrived
+    pub async fn forward<S: AsyncRead + Unpin>(
+        &self,
+        mut stream: S,
+        engine: &Mutex<UltraSeekerEngine>,
+        reporting: &mut bool,
+    ) -> Result<Infallible, RadarError> {
+        let stall = Duration::from_millis(self.config.stall_timeout_ms);
+        let mut decoder = Ld2450Decoder::new(self.config.mount_bearing_deg);
+        let mut bytes = [0u8; 512];
+        loop {
+            let read = runtime::timeout(stall, stream.read(&mut bytes))
+                .await
+                .map_err(|_| RadarError::Stalled(stall))?
+                .map_err(|e| RadarError::Serial(e.to_string()))?;
+            if read == 0 {
+                return Err(RadarError::Ended);
+            }
+            decoder.push(&bytes[..read]);
+            while let Some(targets) = decoder.next_scan() {
+                let data = serde_json::to_vec(&targets).unwrap_or_default();
+                engine.lock().await.update_sensor_input(self.config.sensor_type.clone(), data);
+                *reporting = true;
+            }
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! Privacy redaction of stored footage (`redaction` feature)
+
+use crate::evidence::{decode_segment, encode_segment, read_file, verify_bundle, write_file, write_manifest, CustodyAction, EvidenceError, EvidenceItem, EvidenceKind};
+use crate::{DetectionZone, ObjectDetection, SensorInput, ThreatAssessment};
+use chrono::{DateTime, Utc};
+use dark_phoenix_core::Keyring;
+use image::{DynamicImage, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    /// Age at which video is redacted (seconds after it was recorded)
+    pub after_secs: u64,
+    /// Object classes blurred; for a `person` only the head is
+    pub object_types: Vec<String>,
+    pub blur_sigma: f32,
+    /// How far from a frame a snapshot's detections may be and still apply
+    pub max_detection_age_ms: u64,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            after_secs: 24 * 60 * 60,
+            object_types: vec!["face".to_string(), "person".to_string(), "license_plate".to_string()],
+            blur_sigma: 12.0,
+            max_detection_age_ms: 1000,
+        }
+    }
+}
+
+impl RedactionConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.blur_sigma <= 0.0 {
+            problems.push("redaction.blur_sigma must be positive".to_string());
+        }
+        if self.object_types.is_empty() {
+            problems.push("redaction.object_types must name at least one object class".to_string());
+        }
+        problems
+    }
+}
+
+/// Finds faces and number plates in a stored frame
+pub trait RegionDetector: Send + Sync {
+    fn detect(&self, frame: &RgbImage) -> Vec<ObjectDetection>;
+}
+
+pub struct Redactor {
+    config: RedactionConfig,
+    detector: Option<Box<dyn RegionDetector>>,
+    /// Opens sealed footage and seals the redacted copy
+    keyring: Option<Arc<Keyring>>,
+}
+
+impl Redactor {
+    pub fn new(config: RedactionConfig) -> Self {
+        Self {
+            config,
+            detector: None,
+            keyring: None,
+        }
+    }
+
+    /// Read and write evidence the recorder sealed with `keyring`
+    pub fn with_keyring(mut self, keyring: Arc<Keyring>) -> Self {
+        self.keyring = Some(keyring);
+        self
+    }
+
+    /// Detect regions in every frame rather than relying on snapshots
+    pub fn with_detector(mut self, detector: Box<dyn RegionDetector>) -> Self {
+        self.detector = Some(detector);
+        self
+    }
+
+    /// Whether a file is old enough to redact and not redacted yet
+    pub fn due(&self, item: &EvidenceItem, now: DateTime<Utc>) -> bool {
+        item.kind == EvidenceKind::Video && !item.redacted && now - item.ended >= chrono::Duration::seconds(self.config.after_secs as i64)
+    }
+
+    /// Blur every due video file of the capture in `dir`, checking the
+    /// hashes first; returns how many files were redacted
+    pub fn redact_capture(&self, dir: &Path, actor: &str, now: DateTime<Utc>) -> Result<usize, EvidenceError> {
+        let mut manifest = verify_bundle(dir)?;
+        if !manifest.items.iter().any(|item| self.due(item, now)) {
+            return Ok(0);
+        }
+        let snapshots = snapshot_detections(dir, &manifest.items, self.keyring.as_deref());
+        let mut redacted = Vec::new();
+        for item in manifest.items.iter_mut().filter(|item| self.due(item, now)) {
+            let data = read_file(&dir.join(&item.file), self.keyring.as_deref())?;
+            let frames = decode_segment(&data).ok_or_else(|| EvidenceError::Corrupt(item.file.clone()))?;
+            let mut regions = 0;
+            let frames: Vec<(DateTime<Utc>, Vec<u8>)> = frames
+                .into_iter()
+                .map(|(timestamp, frame)| {
+                    let nearby = nearest(&snapshots, timestamp, self.config.max_detection_age_ms);
+                    match self.redact_frame(&frame, nearby) {
+                        Some((frame, blurred)) => {
+                            regions += blurred;
+                            (timestamp, frame)
+                        },
+                        None => {
+                            tracing::warn!("🕶️ Undecodable frame at {} in {} left as recorded", timestamp, item.file);
+                            (timestamp, frame)
+                        },
+                    }
+                })
+                .collect();
+            let data = encode_segment(frames.iter().map(|(timestamp, frame)| (*timestamp, frame.as_slice())));
+            let previous = std::mem::take(&mut item.sha256);
+            (item.sha256, item.bytes) = write_file(dir, &item.file, &data, self.keyring.as_deref())?;
+            item.redacted = true;
+            redacted.push(format!("{}: {} → {} ({} regions in {} frames)", item.file, previous, item.sha256, regions, frames.len()));
+        }
+        let count = redacted.len();
+        for detail in redacted {
+            manifest.log(actor, CustodyAction::Redacted, detail);
+        }
+        write_manifest(dir, &manifest)?;
+        tracing::info!("🕶️ Redacted {} files of evidence capture {}", count, manifest.capture_id);
+        Ok(count)
+    }
+
+    /// Blur the regions of one encoded frame, keeping its image format;
+    /// `None` if the frame cannot be decoded
+    fn redact_frame(&self, frame: &[u8], fallback: &[ObjectDetection]) -> Option<(Vec<u8>, usize)> {
+        let format = image::guess_format(frame).ok()?;
+        let mut image = image::load_from_memory_with_format(frame, format).ok()?.to_rgb8();
+        let detected;
+        let detections = match &self.detector {
+            Some(detector) => {
+                detected = detector.detect(&image);
+                &detected
+            },
+            None => fallback,
+        };
+        let mut blurred = 0;
+        for detection in detections {
+            // Whitelisted people stay visible
+            if detection.known_person.is_some() || !self.config.object_types.contains(&detection.object_type) {
+                continue;
+            }
+            let Some((left, top, width, height)) = region(&image, &detection.object_type, detection.bounding_box) else {
+                continue;
+            };
+            let crop = image::imageops::crop_imm(&image, left, top, width, height).to_image();
+            image::imageops::replace(&mut image, &image::imageops::blur(&crop, self.config.blur_sigma), left as i64, top as i64);
+            blurred += 1;
+        }
+        Some((encode(DynamicImage::ImageRgb8(image), format)?, blurred))
+    }
+}
+
+/// Pixel region to blur for a detection: the head of a person, the whole
+/// box of a face or number plate
+fn region(frame: &RgbImage, object_type: &str, (x, y, w, mut h): (f32, f32, f32, f32)) -> Option<(u32, u32, u32, u32)> {
+    if object_type == "person" {
+        h *= 0.2;
+    }
+    let (frame_w, frame_h) = (frame.width() as f32, frame.height() as f32);
+    let left = (x.max(0.0) * frame_w) as u32;
+    let top = (y.max(0.0) * frame_h) as u32;
+    let right = ((x + w).min(1.0) * frame_w) as u32;
+    let bottom = ((y + h).min(1.0) * frame_h) as u32;
+    (right > left && bottom > top).then(|| (left, top, right - left, bottom - top))
+}
+
+fn encode(image: DynamicImage, format: image::ImageFormat) -> Option<Vec<u8>> {
+    let mut out = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut out, format).ok()?;
+    Some(out.into_inner())
+}
+
+/// Object detections of each snapshot in the capture, by time
+fn snapshot_detections(dir: &Path, items: &[EvidenceItem], keyring: Option<&Keyring>) -> Vec<(DateTime<Utc>, Vec<ObjectDetection>)> {
+    #[derive(Deserialize)]
+    struct Snapshot {
+        assessment: ThreatAssessment,
+    }
+    items
+        .iter()
+        .filter(|item| item.kind == EvidenceKind::SensorSnapshot)
+        .filter_map(|item| serde_json::from_slice::<Snapshot>(&read_file(&dir.join(&item.file), keyring).ok()?).ok())
+        .map(|snapshot| {
+            let detections = snapshot.assessment.evidence.visual_data.map(|visual| visual.object_detections).unwrap_or_default();
+            (snapshot.assessment.timestamp, detections)
+        })
+        .collect()
+}
+
+fn nearest(snapshots: &[(DateTime<Utc>, Vec<ObjectDetection>)], at: DateTime<Utc>, max_age_ms: u64) -> &[ObjectDetection] {
+    snapshots
+        .iter()
+        .map(|(timestamp, detections)| ((*timestamp - at).num_milliseconds().unsigned_abs(), detections))
+        .filter(|(age, _)| *age <= max_age_ms)
+        .min_by_key(|(age, _)| *age)
+        .map_or(&[], |(_, detections)| detections.as_slice())
+}
+
+/// A video input with the no-record zones blacked out; a frame that cannot
+/// be decoded is stored empty rather than unmasked
+pub(crate) fn mask_input(input: &SensorInput, zones: &[DetectionZone]) -> SensorInput {
+    let masked = image::guess_format(&input.data).ok().and_then(|format| {
+        let mut image = image::load_from_memory_with_format(&input.data, format).ok()?.to_rgb8();
+        let (width, height) = image.dimensions();
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            let point = ((x as f32 + 0.5) / width as f32, (y as f32 + 0.5) / height as f32);
+            if zones.iter().any(|zone| zone.contains(point)) {
+                *pixel = image::Rgb([0, 0, 0]);
+            }
+        }
+        encode(DynamicImage::ImageRgb8(image), format)
+    });
+    SensorInput {
+        data: masked.unwrap_or_default(),
+        ..input.clone()
+    }
+}
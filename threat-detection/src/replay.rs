@@ -0,0 +1,250 @@
+//! Deterministic replay of a recorded session for post-incident review
+
+use crate::{risk_trend_at, SensorInput, ThreatAssessment, ThreatType, UltraSeekerEngine, HISTORY_LIMIT, SENSOR_STREAM, THREAT_STREAM};
+use chrono::{DateTime, Utc};
+use dark_phoenix_core::{EventStore, RingBuffer, RiskTrend, StoreError, ThreatLevel, ThreatStateMachine, ThreatTransition, TransitionRules};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+/// Confidence difference below which a replayed assessment still matches
+const CONFIDENCE_TOLERANCE: f32 = 0.001;
+
+/// Everything a recorded session needs to be replayed, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct ReplayLog {
+    pub inputs: Vec<SensorInput>,
+    pub assessments: Vec<ThreatAssessment>,
+}
+
+impl ReplayLog {
+    /// Read the recorded inputs and assessments from an event store
+    pub fn load(store: &EventStore) -> Result<Self, StoreError> {
+        let mut log = Self {
+            inputs: store.read(SENSOR_STREAM)?,
+            assessments: store.read(THREAT_STREAM)?,
+        };
+        log.inputs.sort_by_key(|input| input.timestamp);
+        log.assessments.sort_by_key(|assessment| assessment.timestamp);
+        Ok(log)
+    }
+
+    /// Only the part of the session between `from` and `to`
+    pub fn between(mut self, from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        self.inputs.retain(|input| input.timestamp >= from && input.timestamp <= to);
+        self.assessments.retain(|assessment| assessment.timestamp >= from && assessment.timestamp <= to);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assessments.is_empty()
+    }
+}
+
+/// What one analysis concluded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Decision {
+    pub threat_level: ThreatLevel,
+    pub confidence: f32,
+    pub threat_types: Vec<ThreatType>,
+    pub zone: Option<String>,
+    pub description: String,
+}
+
+impl From<&ThreatAssessment> for Decision {
+    fn from(assessment: &ThreatAssessment) -> Self {
+        Self {
+            threat_level: assessment.threat_level,
+            confidence: assessment.confidence,
+            threat_types: assessment.threat_types.clone(),
+            zone: assessment.zone.clone(),
+            description: assessment.description.clone(),
+        }
+    }
+}
+
+/// Where a replayed step parted ways with the original run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Divergence {
+    ThreatLevel { original: ThreatLevel, replayed: ThreatLevel },
+    ThreatTypes { original: Vec<ThreatType>, replayed: Vec<ThreatType> },
+    Confidence { original: f32, replayed: f32 },
+    Zone { original: Option<String>, replayed: Option<String> },
+    Escalation { original: ThreatLevel, replayed: ThreatLevel },
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Divergence::ThreatLevel { original, replayed } => write!(f, "assessed {:?}, originally {:?}", replayed, original),
+            Divergence::ThreatTypes { original, replayed } => write!(f, "threat types {:?}, originally {:?}", replayed, original),
+            Divergence::Confidence { original, replayed } => write!(f, "confidence {:.3}, originally {:.3}", replayed, original),
+            Divergence::Zone { original, replayed } => write!(f, "zone {:?}, originally {:?}", replayed, original),
+            Divergence::Escalation { original, replayed } => write!(f, "drone at {:?}, originally {:?}", replayed, original),
+        }
+    }
+}
+
+/// One analysis cycle of the replayed session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayStep {
+    pub timestamp: DateTime<Utc>,
+    pub assessment_id: Uuid, // The original assessment this step re-runs
+    pub inputs: Vec<String>, // Sensor inputs that arrived since the previous step
+    pub original: Decision,
+    pub replayed: Decision,
+    pub risk_trend: RiskTrend,
+    pub transition: Option<ThreatTransition>, // Escalation the replayed assessment caused
+    pub divergences: Vec<Divergence>,
+}
+
+impl ReplayStep {
+    pub fn is_faithful(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+/// Step-by-step decision trace of a replayed session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayReport {
+    pub steps: Vec<ReplayStep>,
+    pub trailing_inputs: usize, // Inputs recorded after the last assessment, never analysed
+}
+
+impl ReplayReport {
+    /// Whether every step reproduced the original decision
+    pub fn is_faithful(&self) -> bool {
+        self.steps.iter().all(ReplayStep::is_faithful)
+    }
+
+    pub fn divergent_steps(&self) -> impl Iterator<Item = &ReplayStep> {
+        self.steps.iter().filter(|step| !step.is_faithful())
+    }
+}
+
+/// Re-runs a recorded session through an engine and the escalation rules
+pub struct Replay {
+    engine: UltraSeekerEngine,
+    rules: TransitionRules,
+    confidence_tolerance: f32,
+}
+
+impl Replay {
+    /// Replay through `engine`, which should carry the original run's config,
+    /// zones and extractors; its event store and history are dropped so the
+    /// replay neither reads nor writes the log it is checking
+    pub fn new(mut engine: UltraSeekerEngine) -> Self {
+        engine.store = None;
+        engine.metrics = None;
+        engine.threat_history.clear();
+        engine.sensor_inputs.clear();
+        #[cfg(feature = "simulation")]
+        {
+            engine.scenario = None;
+        }
+        Self {
+            engine,
+            rules: TransitionRules::default(),
+            confidence_tolerance: CONFIDENCE_TOLERANCE,
+        }
+    }
+
+    /// Escalation rules the original drone ran with
+    pub fn with_transition_rules(mut self, rules: TransitionRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn with_confidence_tolerance(mut self, tolerance: f32) -> Self {
+        self.confidence_tolerance = tolerance.max(0.0);
+        self
+    }
+
+    /// Replay every recorded assessment in order
+    pub async fn run(mut self, log: &ReplayLog) -> Result<ReplayReport, Box<dyn std::error::Error>> {
+        tracing::info!("⏪ Replaying {} assessments from {} sensor inputs", log.assessments.len(), log.inputs.len());
+        let mut inputs = log.inputs.iter().peekable();
+        let mut original_history = RingBuffer::new(HISTORY_LIMIT);
+        let mut original_state = ThreatStateMachine::new(self.rules.clone());
+        let mut replayed_state = ThreatStateMachine::new(self.rules.clone());
+        let mut steps = Vec::with_capacity(log.assessments.len());
+
+        for original in &log.assessments {
+            let mut fed = Vec::new();
+            while let Some(input) = inputs.next_if(|input| input.timestamp <= original.timestamp) {
+                fed.push(input.sensor_type.clone());
+                self.engine.sensor_inputs.insert(input.sensor_type.clone(), input.clone());
+            }
+
+            self.engine.clock = Some(original.timestamp);
+            let replayed = self.engine.analyze_threats().await?;
+            let risk_trend = self.engine.risk_trend();
+            let transition = replayed_state.observe(replayed.threat_level, risk_trend, &replayed.description, &replayed.trace, original.timestamp);
+
+            original_history.push(original.clone());
+            let original_trend = risk_trend_at(&original_history, &self.engine.config, original.timestamp);
+            original_state.observe(original.threat_level, original_trend, &original.description, &original.trace, original.timestamp);
+
+            let step = ReplayStep {
+                timestamp: original.timestamp,
+                assessment_id: original.id,
+                inputs: fed,
+                divergences: self.compare(original, &replayed, original_state.level(), replayed_state.level()),
+                original: Decision::from(original),
+                replayed: Decision::from(&replayed),
+                risk_trend,
+                transition,
+            };
+            for divergence in &step.divergences {
+                tracing::warn!("🔀 Assessment {} at {}: {}", step.assessment_id, step.timestamp, divergence);
+            }
+            steps.push(step);
+        }
+        self.engine.clock = None;
+
+        let report = ReplayReport {
+            steps,
+            trailing_inputs: inputs.count(),
+        };
+        match report.divergent_steps().count() {
+            0 => tracing::info!("⏪ Replay reproduced all {} decisions", report.steps.len()),
+            divergent => tracing::warn!("⏪ Replay diverged on {} of {} steps", divergent, report.steps.len()),
+        }
+        Ok(report)
+    }
+
+    fn compare(&self, original: &ThreatAssessment, replayed: &ThreatAssessment, original_level: ThreatLevel, replayed_level: ThreatLevel) -> Vec<Divergence> {
+        let mut divergences = Vec::new();
+        if original.threat_level != replayed.threat_level {
+            divergences.push(Divergence::ThreatLevel {
+                original: original.threat_level,
+                replayed: replayed.threat_level,
+            });
+        }
+        if original.threat_types != replayed.threat_types {
+            divergences.push(Divergence::ThreatTypes {
+                original: original.threat_types.clone(),
+                replayed: replayed.threat_types.clone(),
+            });
+        }
+        if (original.confidence - replayed.confidence).abs() > self.confidence_tolerance {
+            divergences.push(Divergence::Confidence {
+                original: original.confidence,
+                replayed: replayed.confidence,
+            });
+        }
+        if original.zone != replayed.zone {
+            divergences.push(Divergence::Zone {
+                original: original.zone.clone(),
+                replayed: replayed.zone.clone(),
+            });
+        }
+        if original_level != replayed_level {
+            divergences.push(Divergence::Escalation {
+                original: original_level,
+                replayed: replayed_level,
+            });
+        }
+        divergences
+    }
+}
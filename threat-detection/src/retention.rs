@@ -0,0 +1,160 @@
+//! Retention of evidence captures
+
+use crate::evidence::{list_captures, write_manifest, CustodyAction, CustodyManifest, EvidenceError};
+use chrono::{DateTime, Utc};
+use dark_phoenix_core::ThreatLevel;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Who the custody log names for deletions and redactions
+const ACTOR: &str = "retention policy";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Days a capture is kept after it closes
+    pub keep_days: u64,
+    /// Longer or shorter retention for captures that reached a level
+    pub keep_days_by_level: HashMap<ThreatLevel, u64>,
+    /// Captures never purged or redacted, e.g. for an ongoing case
+    pub legal_hold: Vec<Uuid>,
+    /// How often the policy is applied (seconds)
+    pub sweep_interval_secs: u64,
+    /// Blurring of bystanders in footage kept (absent = footage kept whole)
+    #[cfg(feature = "redaction")]
+    pub redaction: Option<crate::RedactionConfig>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_days: 30,
+            keep_days_by_level: HashMap::new(),
+            legal_hold: Vec::new(),
+            sweep_interval_secs: 60 * 60,
+            #[cfg(feature = "redaction")]
+            redaction: Some(crate::RedactionConfig::default()),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.sweep_interval_secs == 0 {
+            problems.push("retention.sweep_interval_secs must be positive".to_string());
+        }
+        #[cfg(feature = "redaction")]
+        if let Some(redaction) = &self.redaction {
+            problems.extend(redaction.problems());
+            let keep_secs = self.keep_days_by_level.values().chain([&self.keep_days]).min().map_or(0, |days| days * 24 * 60 * 60);
+            if redaction.after_secs >= keep_secs {
+                problems.push("redaction.after_secs must be shorter than the shortest retention, or footage is purged unredacted".to_string());
+            }
+        }
+        problems
+    }
+
+    /// Days captures that reached `level` are kept
+    pub fn keep_days(&self, level: ThreatLevel) -> u64 {
+        self.keep_days_by_level.get(&level).copied().unwrap_or(self.keep_days)
+    }
+
+    /// When a closed capture's files are due for deletion
+    pub fn expires(&self, manifest: &CustodyManifest) -> Option<DateTime<Utc>> {
+        let days = self.keep_days(manifest.threat_level);
+        manifest.closed.map(|closed| closed + chrono::Duration::days(days as i64))
+    }
+}
+
+/// What one sweep did
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub purged: Vec<Uuid>,
+    /// Video files blurred
+    pub redacted: usize,
+}
+
+/// Applies a retention policy to an evidence directory
+pub struct RetentionEngine {
+    policy: RetentionPolicy,
+    dir: PathBuf,
+    #[cfg(feature = "redaction")]
+    redactor: Option<crate::Redactor>,
+}
+
+impl RetentionEngine {
+    /// `dir` is the recorder's `EvidenceConfig::dir`
+    pub fn new(policy: RetentionPolicy, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            #[cfg(feature = "redaction")]
+            redactor: policy.redaction.clone().map(crate::Redactor::new),
+            policy,
+            dir: dir.into(),
+        }
+    }
+
+    /// Redact footage the recorder sealed with `keyring`
+    #[cfg(feature = "redaction")]
+    pub fn with_keyring(mut self, keyring: std::sync::Arc<dark_phoenix_core::Keyring>) -> Self {
+        self.redactor = self.redactor.map(|redactor| redactor.with_keyring(keyring));
+        self
+    }
+
+    /// Find regions to blur with `detector` instead of the capture's snapshots
+    #[cfg(feature = "redaction")]
+    pub fn with_detector(mut self, detector: Box<dyn crate::RegionDetector>) -> Self {
+        self.redactor = self.redactor.map(|redactor| redactor.with_detector(detector));
+        self
+    }
+
+    /// Purge expired captures and redact footage that is due
+    pub fn sweep(&self, now: DateTime<Utc>) -> Result<RetentionReport, EvidenceError> {
+        let mut report = RetentionReport::default();
+        for mut manifest in list_captures(&self.dir)? {
+            // Still recording, already gone, or wanted as it is
+            if manifest.closed.is_none() || manifest.purged.is_some() || self.policy.legal_hold.contains(&manifest.capture_id) {
+                continue;
+            }
+            let dir = self.dir.join(manifest.capture_id.to_string());
+            if self.policy.expires(&manifest).is_some_and(|expires| now >= expires) {
+                for item in &manifest.items {
+                    match std::fs::remove_file(dir.join(&item.file)) {
+                        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                        _ => {},
+                    }
+                }
+                manifest.purged = Some(now);
+                let detail = format!("{} files, kept {} days", manifest.items.len(), self.policy.keep_days(manifest.threat_level));
+                manifest.log(ACTOR, CustodyAction::Purged, detail);
+                write_manifest(&dir, &manifest)?;
+                tracing::info!("🗑️ Purged evidence capture {}", manifest.capture_id);
+                report.purged.push(manifest.capture_id);
+                continue;
+            }
+            #[cfg(feature = "redaction")]
+            if let Some(redactor) = &self.redactor {
+                // One damaged capture must not hold up the rest
+                match redactor.redact_capture(&dir, ACTOR, now) {
+                    Ok(count) => report.redacted += count,
+                    Err(e) => tracing::warn!("⚠️ Could not redact evidence capture {}: {}", manifest.capture_id, e),
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Sweep every `sweep_interval_secs` for as long as the drone runs
+    pub async fn run(self) {
+        let mut interval = dark_phoenix_core::runtime::interval(std::time::Duration::from_secs(self.policy.sweep_interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.sweep(Utc::now()) {
+                tracing::warn!("⚠️ Evidence retention sweep failed: {}", e);
+            }
+        }
+    }
+}
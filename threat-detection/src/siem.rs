@@ -0,0 +1,40 @@
+//! Threat assessments as SIEM records (`siem` feature)
+
+use crate::ThreatAssessment;
+use chrono::{DateTime, Utc};
+use dark_phoenix_core::siem::{threat_severity, SiemField, SiemRecord};
+
+impl SiemRecord for ThreatAssessment {
+    const KIND: &'static str = "threat_assessment";
+
+    /// The leading threat type, or `NoThreat` for a quiet assessment
+    fn signature_id(&self) -> String {
+        self.threat_types.first().map_or_else(|| "NoThreat".to_string(), |threat_type| format!("{:?}", threat_type))
+    }
+
+    fn name(&self) -> String {
+        self.description.clone()
+    }
+
+    fn severity(&self) -> u8 {
+        threat_severity(self.threat_level)
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn default_fields() -> Vec<SiemField> {
+        vec![
+            SiemField::new("externalId", "id"),
+            SiemField::labelled("cs1", "threat_level", "threatLevel"),
+            SiemField::labelled("cs2", "threat_types", "threatTypes"),
+            SiemField::labelled("cs3", "zone", "zone"),
+            SiemField::labelled("cs4", "recommended_actions", "recommendedActions"),
+            SiemField::labelled("cfp1", "confidence", "confidence"),
+            SiemField::labelled("cfp2", "position.latitude", "latitude"),
+            SiemField::labelled("cfp3", "position.longitude", "longitude"),
+            SiemField::labelled("cfp4", "position.altitude", "altitude"),
+        ]
+    }
+}
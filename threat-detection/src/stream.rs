@@ -0,0 +1,167 @@
+use crate::{SensorHealthReport, ThreatAssessment, UltraSeekerEngine};
+use dark_phoenix_core::runtime::{self, Task};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, watch, Mutex, Notify};
+use tracing::{error, info};
+
+/// A running engine analysing on its own clock and streaming assessments
+///
+/// Subscribers that fall behind the broadcast buffer skip ahead with a
+/// `Lagged` error; use `latest()` when only the newest assessment matters.
+pub struct SeekerHandle {
+    engine: Arc<Mutex<UltraSeekerEngine>>,
+    assessments: broadcast::Sender<ThreatAssessment>,
+    latest: watch::Receiver<Option<ThreatAssessment>>,
+    health: watch::Receiver<SensorHealthReport>,
+    /// Runs an analysis ahead of the next tick
+    wake: Arc<Notify>,
+    immediate_sensors: Vec<String>,
+    task: Task<()>,
+    /// Ingest of each configured camera
+    #[cfg(any(feature = "rtsp", feature = "v4l2"))]
+    cameras: Vec<Task<()>>,
+    /// Reader of the configured radar
+    #[cfg(feature = "radar")]
+    radar: Option<Task<()>>,
+    /// Forwarding of each followed thermal camera
+    #[cfg(feature = "thermal")]
+    thermal: Vec<Task<()>>,
+}
+
+impl SeekerHandle {
+    pub(crate) fn start(engine: UltraSeekerEngine) -> Self {
+        let frequency_hz = engine.config.update_frequency_hz.max(1);
+        let assessments = engine.assessments.clone();
+        let immediate_sensors = engine.config.immediate_analysis_sensors.clone();
+        let health = engine.watch_sensor_health();
+        #[cfg(feature = "rtsp")]
+        let rtsp_cameras = engine.config.cameras.clone();
+        #[cfg(feature = "v4l2")]
+        let v4l2_cameras = engine.config.v4l2_cameras.clone();
+        #[cfg(feature = "radar")]
+        let radar_config = engine.config.radar.clone();
+        let (latest_tx, latest) = watch::channel(None);
+        let engine = Arc::new(Mutex::new(engine));
+        let wake = Arc::new(Notify::new());
+
+        let task = runtime::spawn({
+            let engine = Arc::clone(&engine);
+            let wake = Arc::clone(&wake);
+            async move {
+                // A tick missed during a long analysis is skipped, not made up
+                let mut ticker = runtime::interval(Duration::from_secs_f64(1.0 / frequency_hz as f64));
+                loop {
+                    tokio::select! {
+                        _ = ticker.tick() => {},
+                        _ = wake.notified() => {},
+                    }
+                    let result = engine.lock().await.analyze_threats().await.map_err(|e| e.to_string());
+                    match result {
+                        Ok(assessment) => {
+                            latest_tx.send_replace(Some(assessment));
+                        },
+                        Err(e) => error!("Threat analysis failed: {}", e),
+                    }
+                }
+            }
+        });
+        info!("👁️ Ultra Seeker analysis loop running at {} Hz", frequency_hz);
+        #[cfg(any(feature = "rtsp", feature = "v4l2"))]
+        let mut cameras = Vec::new();
+        #[cfg(feature = "rtsp")]
+        cameras.extend(rtsp_cameras.into_iter().map(|camera| runtime::spawn(crate::CameraIngest::new(camera).run(Arc::clone(&engine)))));
+        // Capture blocks, and ends by itself once the engine is dropped
+        #[cfg(feature = "v4l2")]
+        cameras.extend(v4l2_cameras.into_iter().map(|camera| {
+            let engine = Arc::downgrade(&engine);
+            runtime::spawn_blocking(move || crate::V4l2Capture::new(camera).run(engine))
+        }));
+        #[cfg(feature = "radar")]
+        let radar = radar_config.map(|radar| runtime::spawn(crate::RadarIngest::new(radar).run(Arc::clone(&engine))));
+
+        Self {
+            engine,
+            assessments,
+            latest,
+            health,
+            wake,
+            immediate_sensors,
+            task,
+            #[cfg(any(feature = "rtsp", feature = "v4l2"))]
+            cameras,
+            #[cfg(feature = "radar")]
+            radar,
+            #[cfg(feature = "thermal")]
+            thermal: Vec::new(),
+        }
+    }
+
+    /// Every assessment from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ThreatAssessment> {
+        self.assessments.subscribe()
+    }
+
+    /// The most recent assessment, updated in place
+    pub fn latest(&self) -> watch::Receiver<Option<ThreatAssessment>> {
+        self.latest.clone()
+    }
+
+    /// Sensor freshness and quality, updated on every analysis
+    pub fn sensor_health(&self) -> watch::Receiver<SensorHealthReport> {
+        self.health.clone()
+    }
+
+    /// Inputs from `immediate_analysis_sensors` (the microphone by default) are
+    /// analysed straight away so a gunshot does not wait for the next tick
+    pub async fn update_sensor_input(&self, sensor_type: String, data: Vec<u8>) {
+        let immediate = self.immediate_sensors.contains(&sensor_type);
+        self.engine.lock().await.update_sensor_input(sensor_type, data);
+        if immediate {
+            self.wake.notify_one();
+        }
+    }
+
+    /// Feed every frame from a thermal camera to the engine as
+    /// `thermal.sensor_type` input
+    #[cfg(feature = "thermal")]
+    pub async fn follow_thermal(&mut self, feed: dark_phoenix_core::ThermalFeed) {
+        let sensor_type = self.engine.lock().await.config.thermal.sensor_type.clone();
+        let engine = Arc::clone(&self.engine);
+        let mut frames = feed.subscribe();
+        self.thermal.push(runtime::spawn(async move {
+            while frames.changed().await.is_ok() {
+                let Some(frame) = frames.borrow_and_update().clone() else { continue };
+                engine.lock().await.update_sensor_input(sensor_type.clone(), frame.encode());
+            }
+        }));
+    }
+
+    /// Direct access to the engine between analysis ticks
+    pub fn engine(&self) -> Arc<Mutex<UltraSeekerEngine>> {
+        Arc::clone(&self.engine)
+    }
+
+    /// Stop the analysis loop; existing subscribers see the stream close
+    pub fn stop(self) {
+        drop(self);
+    }
+}
+
+impl Drop for SeekerHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+        #[cfg(any(feature = "rtsp", feature = "v4l2"))]
+        for camera in &self.cameras {
+            camera.abort();
+        }
+        #[cfg(feature = "radar")]
+        if let Some(radar) = &self.radar {
+            radar.abort();
+        }
+        #[cfg(feature = "thermal")]
+        for camera in &self.thermal {
+            camera.abort();
+        }
+    }
+}
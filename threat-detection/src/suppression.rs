@@ -0,0 +1,56 @@
+use crate::ThreatType;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// A temporary instruction to ignore one threat type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreatSuppression {
+    pub threat_type: ThreatType,
+    pub reason: String,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+}
+
+/// Time-limited suppressions that lapse on their own
+#[derive(Debug, Clone, Default)]
+pub struct SuppressionList {
+    entries: Vec<ThreatSuppression>,
+}
+
+impl SuppressionList {
+    /// Ignore a threat type for a while, replacing any existing suppression of it
+    pub fn suppress(&mut self, threat_type: ThreatType, duration: Duration, reason: &str) -> ThreatSuppression {
+        let since = Utc::now();
+        let suppression = ThreatSuppression {
+            threat_type,
+            reason: reason.to_string(),
+            since,
+            until: since + chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::MAX),
+        };
+        self.lift(threat_type);
+        self.entries.push(suppression.clone());
+        suppression
+    }
+
+    pub fn lift(&mut self, threat_type: ThreatType) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.threat_type != threat_type);
+        self.entries.len() != before
+    }
+
+    pub fn is_suppressed(&self, threat_type: ThreatType, now: DateTime<Utc>) -> bool {
+        self.entries.iter().any(|entry| entry.threat_type == threat_type && entry.until > now)
+    }
+
+    /// Drop lapsed suppressions, returning them for logging
+    pub fn expire(&mut self, now: DateTime<Utc>) -> Vec<ThreatSuppression> {
+        let (expired, active) = self.entries.drain(..).partition(|entry| entry.until <= now);
+        self.entries = active;
+        expired
+    }
+
+    pub fn active(&self, now: DateTime<Utc>) -> Vec<ThreatSuppression> {
+        self.entries.iter().filter(|entry| entry.until > now).cloned().collect()
+    }
+}
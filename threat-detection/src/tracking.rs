@@ -0,0 +1,319 @@
+use crate::extractors::{MotionExtractor, TrackPoint};
+use crate::{MovementEvidence, ObjectDetection};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackerConfig {
+    /// Sensor whose frames drive the tracker
+    pub sensor_type: String,
+    /// Minimum overlap for a detection to continue a track
+    pub iou_threshold: f32,
+    /// Consecutive missed frames before a track is dropped
+    pub max_misses: u32,
+    /// Matched frames before a track counts as confirmed
+    pub min_hits: u32,
+    /// Object classes worth tracking
+    pub tracked_types: Vec<String>,
+    /// Trajectory samples kept per track
+    pub history_len: usize,
+    /// Where the protectee sits in the frame, as fractions of width and height
+    pub protectee_anchor: (f32, f32),
+    /// Ground distance spanned by the frame width at subject range (metres)
+    pub frame_width_m: f32,
+}
+
+impl Default for TrackerConfig {
+    fn default() -> Self {
+        Self {
+            sensor_type: "camera".to_string(),
+            iou_threshold: 0.3,
+            max_misses: 10,
+            min_hits: 3,
+            tracked_types: ["person", "warm body", "car", "truck", "motorcycle", "bicycle"].map(String::from).to_vec(),
+            history_len: 60,
+            protectee_anchor: (0.5, 0.9),
+            frame_width_m: 12.0,
+        }
+    }
+}
+
+/// Constant-velocity Kalman filter for one image axis
+#[derive(Debug, Clone)]
+struct AxisFilter {
+    position: f32,
+    velocity: f32,
+    covariance: [[f32; 2]; 2],
+}
+
+impl AxisFilter {
+    const PROCESS_NOISE: f32 = 0.01;
+    const MEASUREMENT_NOISE: f32 = 0.001;
+
+    fn new(position: f32) -> Self {
+        Self {
+            position,
+            velocity: 0.0,
+            covariance: [[Self::MEASUREMENT_NOISE, 0.0], [0.0, 1.0]],
+        }
+    }
+
+    fn predict(&mut self, dt: f32) {
+        self.position += self.velocity * dt;
+        let [[p00, p01], [p10, p11]] = self.covariance;
+        let q = Self::PROCESS_NOISE;
+        self.covariance = [
+            [p00 + dt * (p10 + p01) + dt * dt * p11 + q * dt * dt * dt / 3.0, p01 + dt * p11 + q * dt * dt / 2.0],
+            [p10 + dt * p11 + q * dt * dt / 2.0, p11 + q * dt],
+        ];
+    }
+
+    fn correct(&mut self, measured: f32) {
+        let [[p00, p01], [p10, p11]] = self.covariance;
+        let innovation_covariance = p00 + Self::MEASUREMENT_NOISE;
+        let (gain_position, gain_velocity) = (p00 / innovation_covariance, p10 / innovation_covariance);
+        let innovation = measured - self.position;
+        self.position += gain_position * innovation;
+        self.velocity += gain_velocity * innovation;
+        self.covariance = [
+            [(1.0 - gain_position) * p00, (1.0 - gain_position) * p01],
+            [p10 - gain_velocity * p00, p11 - gain_velocity * p01],
+        ];
+    }
+}
+
+/// One object followed across frames
+#[derive(Debug, Clone)]
+pub struct Track {
+    pub id: u64,
+    pub object_type: String,
+    /// Last box as fractions of the frame (x, y, width, height)
+    pub bounding_box: (f32, f32, f32, f32),
+    pub confidence: f32,
+    pub hits: u32,
+    pub misses: u32,
+    x: AxisFilter,
+    y: AxisFilter,
+    /// Seconds since tracking started and filtered box centre
+    trajectory: VecDeque<(f32, f32, f32)>,
+}
+
+impl Track {
+    pub fn is_confirmed(&self, config: &TrackerConfig) -> bool {
+        self.hits >= config.min_hits
+    }
+
+    /// Filtered centre position in frame fractions
+    pub fn center(&self) -> (f32, f32) {
+        (self.x.position, self.y.position)
+    }
+
+    /// Filtered velocity in frame fractions per second
+    pub fn velocity(&self) -> (f32, f32) {
+        (self.x.velocity, self.y.velocity)
+    }
+
+    /// Trajectory in metres relative to the protectee
+    pub fn ground_track(&self, config: &TrackerConfig) -> Vec<TrackPoint> {
+        let (anchor_x, anchor_y) = config.protectee_anchor;
+        self.trajectory
+            .iter()
+            .map(|&(t, x, y)| TrackPoint {
+                t,
+                x: (x - anchor_x) * config.frame_width_m,
+                // Image y grows downwards; further up the frame is further away
+                y: (anchor_y - y) * config.frame_width_m,
+            })
+            .collect()
+    }
+
+    fn predicted_box(&self) -> (f32, f32, f32, f32) {
+        let (_, _, w, h) = self.bounding_box;
+        (self.x.position - w / 2.0, self.y.position - h / 2.0, w, h)
+    }
+}
+
+/// IoU association with Kalman-smoothed motion, assigning stable track IDs
+#[derive(Debug, Clone)]
+pub struct MultiObjectTracker {
+    config: TrackerConfig,
+    motion: MotionExtractor,
+    tracks: Vec<Track>,
+    next_id: u64,
+    started: Option<DateTime<Utc>>,
+    /// Time of the last frame processed and the IDs it was assigned
+    last_frame: Option<(DateTime<Utc>, Vec<Option<u64>>)>,
+}
+
+impl Default for MultiObjectTracker {
+    fn default() -> Self {
+        Self::new(TrackerConfig::default())
+    }
+}
+
+impl MultiObjectTracker {
+    pub fn new(config: TrackerConfig) -> Self {
+        Self {
+            config,
+            motion: MotionExtractor::default(),
+            tracks: Vec::new(),
+            next_id: 0,
+            started: None,
+            last_frame: None,
+        }
+    }
+
+    /// Judge trajectories with different speed and proximity limits
+    pub fn with_motion(mut self, motion: MotionExtractor) -> Self {
+        self.motion = motion;
+        self
+    }
+
+    pub fn config(&self) -> &TrackerConfig {
+        &self.config
+    }
+
+    /// Confirmed tracks only
+    pub fn tracks(&self) -> impl Iterator<Item = &Track> {
+        self.tracks.iter().filter(|track| track.is_confirmed(&self.config))
+    }
+
+    /// Advance every track to the frame at `timestamp` and associate its
+    /// detections, writing the assigned track IDs back onto them. A frame
+    /// seen before is only relabelled, so re-analysis between frames does
+    /// not count as fresh sightings.
+    pub fn update(&mut self, detections: &mut [ObjectDetection], timestamp: DateTime<Utc>) {
+        if let Some((last, assigned)) = &self.last_frame {
+            if timestamp <= *last {
+                if assigned.len() == detections.len() {
+                    for (detection, track_id) in detections.iter_mut().zip(assigned) {
+                        detection.track_id = *track_id;
+                    }
+                }
+                return;
+            }
+        }
+
+        let started = *self.started.get_or_insert(timestamp);
+        let now = (timestamp - started).num_milliseconds() as f32 / 1000.0;
+        let dt = self
+            .last_frame
+            .as_ref()
+            .map(|(last, _)| (timestamp - *last).num_milliseconds() as f32 / 1000.0)
+            .unwrap_or(0.0);
+
+        for track in &mut self.tracks {
+            track.x.predict(dt);
+            track.y.predict(dt);
+        }
+
+        // Greedy association, best overlaps first
+        let mut pairs = Vec::new();
+        for (detection_index, detection) in detections.iter().enumerate() {
+            if !self.config.tracked_types.contains(&detection.object_type) {
+                continue;
+            }
+            for (track_index, track) in self.tracks.iter().enumerate() {
+                if track.object_type != detection.object_type {
+                    continue;
+                }
+                let overlap = iou(track.predicted_box(), detection.bounding_box);
+                if overlap >= self.config.iou_threshold {
+                    pairs.push((overlap, detection_index, track_index));
+                }
+            }
+        }
+        pairs.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut detection_matched = vec![false; detections.len()];
+        let mut track_matched = vec![false; self.tracks.len()];
+        for (_, detection_index, track_index) in pairs {
+            if detection_matched[detection_index] || track_matched[track_index] {
+                continue;
+            }
+            detection_matched[detection_index] = true;
+            track_matched[track_index] = true;
+
+            let detection = &mut detections[detection_index];
+            let track = &mut self.tracks[track_index];
+            let (cx, cy) = center(detection.bounding_box);
+            track.x.correct(cx);
+            track.y.correct(cy);
+            track.bounding_box = detection.bounding_box;
+            track.confidence = detection.confidence;
+            track.hits += 1;
+            track.misses = 0;
+            track.trajectory.push_back((now, track.x.position, track.y.position));
+            if track.trajectory.len() > self.config.history_len {
+                track.trajectory.pop_front();
+            }
+            detection.track_id = Some(track.id);
+        }
+
+        for (track, matched) in self.tracks.iter_mut().zip(&track_matched) {
+            if !matched {
+                track.misses += 1;
+            }
+        }
+        let max_misses = self.config.max_misses;
+        self.tracks.retain(|track| track.misses <= max_misses);
+
+        for (detection, matched) in detections.iter_mut().zip(detection_matched) {
+            if matched || !self.config.tracked_types.contains(&detection.object_type) {
+                continue;
+            }
+            let (cx, cy) = center(detection.bounding_box);
+            self.next_id += 1;
+            detection.track_id = Some(self.next_id);
+            self.tracks.push(Track {
+                id: self.next_id,
+                object_type: detection.object_type.clone(),
+                bounding_box: detection.bounding_box,
+                confidence: detection.confidence,
+                hits: 1,
+                misses: 0,
+                x: AxisFilter::new(cx),
+                y: AxisFilter::new(cy),
+                trajectory: VecDeque::from([(now, cx, cy)]),
+            });
+        }
+
+        let assigned = detections.iter().map(|detection| detection.track_id).collect();
+        self.last_frame = Some((timestamp, assigned));
+    }
+
+    /// Whether a track (confirmed or not) is still alive
+    pub fn contains(&self, track_id: u64) -> bool {
+        self.tracks.iter().any(|track| track.id == track_id)
+    }
+
+    /// Movement evidence combined across confirmed trajectories, skipping `ignore`d tracks
+    pub fn movement_evidence(&self, ignore: impl Fn(&Track) -> bool) -> Option<MovementEvidence> {
+        self.tracks()
+            .filter(|track| !ignore(track))
+            .map(|track| self.motion.analyze(&track.ground_track(&self.config)))
+            .reduce(|worst, evidence| MovementEvidence {
+                velocity_anomaly: worst.velocity_anomaly.max(evidence.velocity_anomaly),
+                direction_changes: worst.direction_changes.max(evidence.direction_changes),
+                proximity_violations: worst.proximity_violations + evidence.proximity_violations,
+                pursuit_behavior: worst.pursuit_behavior || evidence.pursuit_behavior,
+                escape_attempts: worst.escape_attempts || evidence.escape_attempts,
+                targets: Vec::new(),
+            })
+    }
+}
+
+fn center(bounding_box: (f32, f32, f32, f32)) -> (f32, f32) {
+    let (x, y, w, h) = bounding_box;
+    (x + w / 2.0, y + h / 2.0)
+}
+
+/// Intersection over union of two (x, y, width, height) boxes
+pub fn iou(a: (f32, f32, f32, f32), b: (f32, f32, f32, f32)) -> f32 {
+    let overlap_w = ((a.0 + a.2).min(b.0 + b.2) - a.0.max(b.0)).max(0.0);
+    let overlap_h = ((a.1 + a.3).min(b.1 + b.3) - a.1.max(b.1)).max(0.0);
+    let intersection = overlap_w * overlap_h;
+    let union = a.2 * a.3 + b.2 * b.3 - intersection;
+    if union > 0.0 { intersection / union } else { 0.0 }
+}
@@ -0,0 +1,361 @@
+//! Onboard USB and CSI cameras through V4L2 (`v4l2` feature, Linux)
+
+use crate::extractors::jpeg_frame;
+use crate::{UltraSeekerEngine, ZoneMap};
+use linuxvideo::controls::Cid;
+use linuxvideo::format::{PixFormat, PixelFormat};
+use linuxvideo::{BufType, Device, Fract};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::PathBuf;
+use std::sync::Weak;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+/// Pixel formats frames can be handed on from, by FourCC
+const SUPPORTED_FORMATS: [&str; 4] = ["MJPG", "JPEG", "YUYV", "RGB3"];
+
+/// `V4L2_EXPOSURE_MANUAL`, a value of the auto exposure menu
+const EXPOSURE_MANUAL: i32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct V4l2CameraConfig {
+    /// Name the camera's zones and log lines go by
+    pub name: String,
+    pub device: PathBuf,
+    /// Pixel formats to ask for as FourCC codes, most preferred first
+    pub formats: Vec<String>,
+    /// Requested frame size; the driver picks the nearest it supports
+    pub width: u32,
+    pub height: u32,
+    /// Frame rate requested of the driver
+    pub fps: u32,
+    /// Frames handed to the engine per second
+    pub max_fps: f32,
+    /// Zones in this camera's view (absent = the engine's zones)
+    pub zones: Option<ZoneMap>,
+    pub night: Option<NightExposure>,
+    /// First wait before reopening a failed device, doubled on each failure
+    pub reopen_ms: u64,
+    pub max_reopen_ms: u64,
+}
+
+impl Default for V4l2CameraConfig {
+    fn default() -> Self {
+        Self {
+            name: "onboard".to_string(),
+            device: PathBuf::from("/dev/video0"),
+            formats: vec!["MJPG".to_string(), "YUYV".to_string()],
+            width: 1280,
+            height: 720,
+            fps: 15,
+            max_fps: 5.0,
+            zones: None,
+            night: None,
+            reopen_ms: 1000,
+            max_reopen_ms: 30_000,
+        }
+    }
+}
+
+/// Exposure used while the scene is dark
+///
+/// Brightness is the mean of the frames' luma (0.0-1.0). `leave_above` is
+/// measured with the night profile applied, so it needs to sit well above
+/// `enter_below`, or a longer exposure brightens the picture enough to
+/// switch straight back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NightExposure {
+    pub enter_below: f32,
+    pub leave_above: f32,
+    /// Fixed exposure time in 100 µs units (absent = auto exposure)
+    pub exposure: Option<i32>,
+    pub gain: Option<i32>,
+}
+
+impl Default for NightExposure {
+    fn default() -> Self {
+        Self {
+            enter_below: 0.12,
+            leave_above: 0.35,
+            exposure: None,
+            gain: None,
+        }
+    }
+}
+
+impl V4l2CameraConfig {
+    /// Problems with the configuration, for settings validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.name.trim().is_empty() {
+            problems.push("camera name must not be empty".to_string());
+        }
+        if self.formats.is_empty() {
+            problems.push(format!("camera '{}' formats must not be empty", self.name));
+        }
+        for format in self.formats.iter().filter(|format| !SUPPORTED_FORMATS.contains(&format.as_str())) {
+            problems.push(format!("camera '{}' format '{}' is not one of {}", self.name, format, SUPPORTED_FORMATS.join(", ")));
+        }
+        if self.width == 0 || self.height == 0 || self.fps == 0 {
+            problems.push(format!("camera '{}' width, height and fps must be positive", self.name));
+        }
+        if !self.max_fps.is_finite() || self.max_fps <= 0.0 {
+            problems.push(format!("camera '{}' max_fps must be positive", self.name));
+        }
+        if self.reopen_ms == 0 {
+            problems.push(format!("camera '{}' reopen_ms must be positive", self.name));
+        }
+        if let Some(night) = &self.night {
+            if !(0.0..1.0).contains(&night.enter_below) || night.leave_above <= night.enter_below || night.leave_above > 1.0 {
+                problems.push(format!("camera '{}' night.enter_below must be below night.leave_above, both within 0.0-1.0", self.name));
+            }
+        }
+        problems
+    }
+}
+
+/// One onboard camera capturing into an engine
+pub struct V4l2Capture {
+    config: V4l2CameraConfig,
+}
+
+impl V4l2Capture {
+    pub fn new(config: V4l2CameraConfig) -> Self {
+        Self { config }
+    }
+
+    /// Capture frames into `engine` until it is dropped, reopening the device
+    /// whenever it fails
+    ///
+    /// Blocks the calling thread, so run it with `spawn_blocking` or on a
+    /// thread of its own.
+    pub fn run(self, engine: Weak<Mutex<UltraSeekerEngine>>) {
+        let name = &self.config.name;
+        if let (Some(zones), Some(engine)) = (&self.config.zones, engine.upgrade()) {
+            engine.blocking_lock().set_camera_zones(name, zones.clone());
+        }
+        let max_backoff = Duration::from_millis(self.config.max_reopen_ms.max(self.config.reopen_ms));
+        let mut backoff = Duration::from_millis(self.config.reopen_ms);
+        loop {
+            let mut capturing = false;
+            match self.capture(&engine, &mut capturing) {
+                Ok(()) => return,
+                Err(e) => warn!("📹 Camera '{}' ({}): {}; reopening in {:?}", name, self.config.device.display(), e, backoff),
+            }
+            std::thread::sleep(backoff);
+            if engine.strong_count() == 0 {
+                return;
+            }
+            backoff = if capturing { Duration::from_millis(self.config.reopen_ms) } else { (backoff * 2).min(max_backoff) };
+        }
+    }
+
+    /// Open, negotiate and stream until the device fails (an error) or the
+    /// engine is gone
+    fn capture(&self, engine: &Weak<Mutex<UltraSeekerEngine>>, capturing: &mut bool) -> io::Result<()> {
+        let device = Device::open(&self.config.device)?;
+        let offered = device.formats(BufType::VIDEO_CAPTURE).map(|format| format.map(|f| f.pixel_format())).collect::<io::Result<Vec<_>>>()?;
+        let format = self
+            .config
+            .formats
+            .iter()
+            .filter_map(|fourcc| <[u8; 4]>::try_from(fourcc.as_bytes()).ok().map(PixelFormat::from_fourcc))
+            .find(|format| offered.contains(format))
+            .ok_or_else(|| {
+                let offered = offered.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+                io::Error::new(io::ErrorKind::Unsupported, format!("none of the configured formats offered (device has {})", offered))
+            })?;
+        let capture = device.video_capture(PixFormat::new(self.config.width, self.config.height, format))?;
+        let interval = match capture.set_frame_interval(Fract::new(1, self.config.fps)) {
+            Ok(interval) => Some(interval),
+            Err(e) => {
+                debug!("📹 Camera '{}' keeps its own frame rate: {}", self.config.name, e);
+                None
+            },
+        };
+        let layout = Layout::of(capture.format());
+        info!(
+            "📹 Camera '{}' capturing {} {}x{} at {} fps from {}",
+            self.config.name,
+            layout.format,
+            layout.width,
+            layout.height,
+            interval.map_or_else(|| "the driver's".to_string(), |interval| format!("{:.1}", 1.0 / interval.as_f32())),
+            self.config.device.display()
+        );
+        let mut exposure = match &self.config.night {
+            Some(night) => Some(Exposure::new(&self.config, night.clone())?),
+            None => None,
+        };
+
+        let mut stream = capture.into_stream()?;
+        let interval = Duration::from_secs_f32(1.0 / self.config.max_fps);
+        let mut last_sent: Option<Instant> = None;
+        loop {
+            let due = last_sent.is_none_or(|sent| sent.elapsed() >= interval);
+            let frame = stream.dequeue(|buffer| {
+                if !due || buffer.is_error() {
+                    return Ok(None);
+                }
+                Frame::convert(&layout, &buffer).map(Some)
+            })?;
+            let Some(frame) = frame else { continue };
+            if let Some(exposure) = &mut exposure {
+                exposure.observe(frame.brightness()?);
+            }
+            let Some(engine) = engine.upgrade() else { return Ok(()) };
+            engine.blocking_lock().update_camera_frame(&self.config.name, frame.jpeg);
+            last_sent = Some(Instant::now());
+            *capturing = true;
+        }
+    }
+}
+
+/// Negotiated format and size of the frames
+struct Layout {
+    format: PixelFormat,
+    width: usize,
+    height: usize,
+    /// Bytes per row, padding included
+    stride: usize,
+}
+
+impl Layout {
+    fn of(format: &PixFormat) -> Self {
+        Self {
+            format: format.pixel_format(),
+            width: format.width() as usize,
+            height: format.height() as usize,
+            stride: format.bytes_per_line() as usize,
+        }
+    }
+}
+
+/// A captured frame as JPEG, with its luma when it was captured raw
+struct Frame {
+    jpeg: Vec<u8>,
+    luma: Option<f32>,
+}
+
+impl Frame {
+    fn convert(layout: &Layout, data: &[u8]) -> io::Result<Self> {
+        let &Layout { format, width, height, stride } = layout;
+        let row_bytes = match format {
+            PixelFormat::MJPG | PixelFormat::JPEG => return Ok(Self { jpeg: data.to_vec(), luma: None }),
+            PixelFormat::YUYV => width * 2,
+            PixelFormat::RGB3 => width * 3,
+            other => return Err(io::Error::new(io::ErrorKind::Unsupported, format!("cannot convert {} frames", other))),
+        };
+        let stride = stride.max(row_bytes);
+        if height == 0 || data.len() < stride * (height - 1) + row_bytes {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "short frame"));
+        }
+        let rows = data.chunks(stride).take(height).map(|row| &row[..row_bytes]);
+        let rgb = match format {
+            PixelFormat::YUYV => yuyv_to_rgb(rows, width * height),
+            _ => rows.flatten().copied().collect(),
+        };
+        let luma = mean_luma(&rgb);
+        let jpeg = jpeg_frame(&rgb, width as u32, height as u32).map_err(io::Error::other)?;
+        Ok(Self { jpeg, luma: Some(luma) })
+    }
+
+    /// Mean brightness, decoding the JPEG when the frame came compressed
+    fn brightness(&self) -> io::Result<f32> {
+        match self.luma {
+            Some(luma) => Ok(luma),
+            None => {
+                let image = image::load_from_memory(&self.jpeg).map_err(io::Error::other)?;
+                Ok(mean_luma(image.to_rgb8().as_raw()))
+            },
+        }
+    }
+}
+
+/// BT.601 conversion of packed 4:2:2 YUYV rows
+fn yuyv_to_rgb<'a>(rows: impl Iterator<Item = &'a [u8]>, pixels: usize) -> Vec<u8> {
+    let mut rgb = Vec::with_capacity(pixels * 3);
+    for pair in rows.flat_map(|row| row.chunks_exact(4)) {
+        let (u, v) = (pair[1] as f32 - 128.0, pair[3] as f32 - 128.0);
+        for y in [pair[0] as f32, pair[2] as f32] {
+            rgb.push((y + 1.402 * v).clamp(0.0, 255.0) as u8);
+            rgb.push((y - 0.344 * u - 0.714 * v).clamp(0.0, 255.0) as u8);
+            rgb.push((y + 1.772 * u).clamp(0.0, 255.0) as u8);
+        }
+    }
+    rgb
+}
+
+/// Mean luma (0.0-1.0) of RGB8 pixels, sampling every 16th
+fn mean_luma(rgb: &[u8]) -> f32 {
+    let (sum, count) = rgb.chunks_exact(3).step_by(16).fold((0.0, 0usize), |(sum, count), pixel| {
+        (sum + 0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32, count + 1)
+    });
+    if count == 0 { 0.0 } else { sum / count as f32 / 255.0 }
+}
+
+/// Switches between the camera's own exposure controls and the night profile
+struct Exposure {
+    /// Second handle on the device; controls can be set while another streams
+    device: Device,
+    name: String,
+    night: NightExposure,
+    /// The camera's own values of the controls the night profile changes
+    day: Vec<(Cid, i32)>,
+    at_night: bool,
+}
+
+impl Exposure {
+    fn new(config: &V4l2CameraConfig, night: NightExposure) -> io::Result<Self> {
+        let device = Device::open(&config.device)?;
+        let day = [Cid::EXPOSURE_AUTO, Cid::EXPOSURE_ABSOLUTE, Cid::EXPOSURE_AUTO_PRIORITY, Cid::GAIN]
+            .into_iter()
+            .filter_map(|cid| device.read_control_raw(cid).ok().map(|value| (cid, value)))
+            .collect();
+        Ok(Self {
+            device,
+            name: config.name.clone(),
+            night,
+            day,
+            at_night: false,
+        })
+    }
+
+    fn observe(&mut self, brightness: f32) {
+        if !self.at_night && brightness < self.night.enter_below {
+            info!("🌙 Camera '{}' switching to night exposure (brightness {:.2})", self.name, brightness);
+            let mut controls = vec![(Cid::EXPOSURE_AUTO_PRIORITY, 1)];
+            if let Some(exposure) = self.night.exposure {
+                controls.extend([(Cid::EXPOSURE_AUTO, EXPOSURE_MANUAL), (Cid::EXPOSURE_ABSOLUTE, exposure)]);
+            }
+            controls.extend(self.night.gain.map(|gain| (Cid::GAIN, gain)));
+            self.apply(&controls);
+            self.at_night = true;
+        } else if self.at_night && brightness > self.night.leave_above {
+            info!("☀️ Camera '{}' back to its own exposure (brightness {:.2})", self.name, brightness);
+            self.apply(&self.day.clone());
+            self.at_night = false;
+        }
+    }
+
+    fn apply(&mut self, controls: &[(Cid, i32)]) {
+        for &(cid, value) in controls {
+            if let Err(e) = self.device.write_control_raw(cid, value) {
+                debug!("📹 Camera '{}' did not take control {:?}={}: {}", self.name, cid, value, e);
+            }
+        }
+    }
+}
+
+impl Drop for Exposure {
+    /// Leave the camera as it was found
+    fn drop(&mut self) {
+        if self.at_night {
+            self.apply(&self.day.clone());
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Binary wire schemas for threat assessments (`binary-wire` feature)
+
+use crate::{ThreatAssessment, ThreatType};
+use chrono::{DateTime, TimeZone, Utc};
+use dark_phoenix_core::wire::{from_e7, to_e7};
+use dark_phoenix_core::{ThreatLevel, WireFormat, WireKind, WireSchema};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+impl WireSchema for ThreatAssessment {
+    const KIND: WireKind = WireKind::ThreatAssessment;
+    const VERSION: u8 = 1;
+    const FORMATS: &'static [WireFormat] = &[WireFormat::Cbor];
+}
+
+/// Bit of each threat type in `ThreatFrame::threat_types`; fixed once assigned
+fn bit(threat_type: ThreatType) -> u16 {
+    1 << match threat_type {
+        ThreatType::PhysicalAggression => 0,
+        ThreatType::WeaponDetected => 1,
+        ThreatType::ErraticBehavior => 2,
+        ThreatType::HostileIntent => 3,
+        ThreatType::GroupThreat => 4,
+        ThreatType::EnvironmentalHazard => 5,
+        ThreatType::VehicleThreat => 6,
+        ThreatType::CyberThreat => 7,
+        ThreatType::UnknownAnomaly => 8,
+        ThreatType::Loitering => 9,
+    }
+}
+
+const ALL_TYPES: [ThreatType; 10] = [
+    ThreatType::PhysicalAggression,
+    ThreatType::WeaponDetected,
+    ThreatType::ErraticBehavior,
+    ThreatType::HostileIntent,
+    ThreatType::GroupThreat,
+    ThreatType::EnvironmentalHazard,
+    ThreatType::VehicleThreat,
+    ThreatType::CyberThreat,
+    ThreatType::UnknownAnomaly,
+    ThreatType::Loitering,
+];
+
+/// A threat assessment in a few dozen bytes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreatFrame {
+    pub id: Uuid,
+    /// Unix seconds
+    pub timestamp: i64,
+    pub threat_level: ThreatLevel,
+    /// Percent
+    pub confidence: u8,
+    /// One bit per `ThreatType`
+    pub threat_types: u16,
+    /// Where the threat is, when known
+    pub position_e7: Option<(i32, i32)>,
+}
+
+impl From<&ThreatAssessment> for ThreatFrame {
+    fn from(assessment: &ThreatAssessment) -> Self {
+        Self {
+            id: assessment.id,
+            timestamp: assessment.timestamp.timestamp(),
+            threat_level: assessment.threat_level,
+            confidence: (assessment.confidence.clamp(0.0, 1.0) * 100.0).round() as u8,
+            threat_types: assessment.threat_types.iter().fold(0, |bits, threat_type| bits | bit(*threat_type)),
+            position_e7: assessment.position.as_ref().map(|position| (to_e7(position.latitude), to_e7(position.longitude))),
+        }
+    }
+}
+
+impl ThreatFrame {
+    pub fn threat_types(&self) -> Vec<ThreatType> {
+        ALL_TYPES.into_iter().filter(|threat_type| self.threat_types & bit(*threat_type) != 0).collect()
+    }
+
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        Utc.timestamp_opt(self.timestamp, 0).single().unwrap_or_default()
+    }
+
+    /// Latitude and longitude in degrees
+    pub fn position(&self) -> Option<(f64, f64)> {
+        self.position_e7.map(|(latitude, longitude)| (from_e7(latitude), from_e7(longitude)))
+    }
+}
+
+impl WireSchema for ThreatFrame {
+    const KIND: WireKind = WireKind::ThreatFrame;
+    const VERSION: u8 = 1;
+    const FORMATS: &'static [WireFormat] = &[WireFormat::Postcard, WireFormat::Cbor];
+}
@@ -0,0 +1,247 @@
+use crate::ObjectDetection;
+use chrono::{DateTime, NaiveTime, Utc};
+use dark_phoenix_core::{ThreatLevel, TimeWindow};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// Named area of the camera view with its own threat rules
+///
+/// Objects are placed by the bottom centre of their box, where a person's
+/// feet or a vehicle's wheels meet the ground.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionZone {
+    pub name: String,
+    /// Polygon vertices as fractions of the frame (x, y)
+    pub polygon: Vec<(f32, f32)>,
+    /// Object classes this zone applies to (empty = any)
+    #[serde(default)]
+    pub object_types: Vec<String>,
+    /// Multiplier on the threat weight of objects inside (1.0 = neutral)
+    #[serde(default = "neutral_weight")]
+    pub threat_weight: f32,
+    /// Highest level presence in this zone can justify on its own
+    #[serde(default)]
+    pub max_level: Option<ThreatLevel>,
+    /// Levels raised by anything entering the zone
+    #[serde(default)]
+    pub entry_rules: Vec<ZoneEntryRule>,
+    /// How long a tracked object may stay before it counts as loitering
+    #[serde(default)]
+    pub loiter_rules: Vec<LoiterRule>,
+}
+
+fn neutral_weight() -> f32 {
+    1.0
+}
+
+/// Minimum threat level for any entry, optionally only at certain times
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZoneEntryRule {
+    pub level: ThreatLevel,
+    /// Local time window the rule applies to (absent = any time)
+    #[serde(default)]
+    pub time_window: Option<TimeWindow>,
+}
+
+/// Dwell time after which a track in the zone is loitering
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoiterRule {
+    pub after_secs: u64,
+    /// Level raised once the dwell time is exceeded
+    #[serde(default = "loiter_level")]
+    pub level: ThreatLevel,
+    /// Local time window the rule applies to (absent = any time), so the
+    /// same zone can tolerate five minutes by day and one minute at night
+    #[serde(default)]
+    pub time_window: Option<TimeWindow>,
+}
+
+fn loiter_level() -> ThreatLevel {
+    ThreatLevel::Yellow
+}
+
+impl DetectionZone {
+    pub fn applies_to(&self, object_type: &str) -> bool {
+        self.object_types.is_empty() || self.object_types.iter().any(|t| t == object_type)
+    }
+
+    /// Even-odd test against the polygon
+    pub fn contains(&self, (x, y): (f32, f32)) -> bool {
+        let mut inside = false;
+        let count = self.polygon.len();
+        for i in 0..count {
+            let (xi, yi) = self.polygon[i];
+            let (xj, yj) = self.polygon[(i + count - 1) % count];
+            if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    /// Strictest loitering rule exceeded by `dwell` at `local_time`
+    fn loiter_level(&self, dwell: Duration, local_time: NaiveTime) -> Option<ThreatLevel> {
+        self.loiter_rules
+            .iter()
+            .filter(|rule| rule.time_window.is_none_or(|window| window.contains(local_time)))
+            .filter(|rule| dwell >= Duration::from_secs(rule.after_secs))
+            .map(|rule| rule.level)
+            .max()
+    }
+
+    /// Strictest entry rule in force at `local_time`
+    fn entry_level(&self, local_time: NaiveTime) -> Option<ThreatLevel> {
+        self.entry_rules
+            .iter()
+            .filter(|rule| rule.time_window.is_none_or(|window| window.contains(local_time)))
+            .map(|rule| rule.level)
+            .max()
+    }
+}
+
+/// Level limits derived from where objects are in the frame
+#[derive(Debug, Clone, Default)]
+pub struct ZoneEvaluation {
+    /// Minimum level and the zone whose entry rule raised it
+    pub floor: Option<(ThreatLevel, String)>,
+    /// Maximum level when every object is in a capped zone
+    pub ceiling: Option<ThreatLevel>,
+    /// Zones with at least one object in them
+    pub occupied: Vec<String>,
+}
+
+/// Every detection zone for one camera
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ZoneMap {
+    pub zones: Vec<DetectionZone>,
+}
+
+impl ZoneMap {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// First zone containing an object of this type at this ground point
+    pub fn zone_at(&self, object_type: &str, point: (f32, f32)) -> Option<&DetectionZone> {
+        self.zones.iter().find(|zone| zone.applies_to(object_type) && zone.contains(point))
+    }
+
+    /// Tag each detection with its zone, apply zone weights, and work out
+    /// the level limits the zones impose. Whitelisted people are tagged but
+    /// never trigger entry rules.
+    pub fn evaluate(&self, detections: &mut [ObjectDetection], local_time: NaiveTime) -> ZoneEvaluation {
+        let mut evaluation = ZoneEvaluation::default();
+        if self.zones.is_empty() {
+            return evaluation;
+        }
+
+        let mut all_capped = true;
+        let mut ceiling = ThreatLevel::Green;
+        for detection in detections.iter_mut() {
+            // A zone the source already named (e.g. a scripted scenario) wins
+            let named = detection
+                .zone
+                .as_deref()
+                .and_then(|name| self.zones.iter().find(|zone| zone.name == name && zone.applies_to(&detection.object_type)));
+            let (x, y, w, h) = detection.bounding_box;
+            let Some(zone) = named.or_else(|| self.zone_at(&detection.object_type, (x + w / 2.0, y + h))) else {
+                // Scenery outside every zone does not lift a zone's cap
+                if detection.threat_relevance > 0.0 {
+                    all_capped = false;
+                }
+                continue;
+            };
+            detection.zone = Some(zone.name.clone());
+            detection.threat_relevance = (detection.threat_relevance * zone.threat_weight).clamp(0.0, 1.0);
+            if !evaluation.occupied.contains(&zone.name) {
+                evaluation.occupied.push(zone.name.clone());
+            }
+
+            match zone.max_level {
+                Some(max_level) => ceiling = ceiling.max(max_level),
+                None => all_capped = false,
+            }
+            if detection.known_person.is_some() {
+                continue;
+            }
+            if let Some(level) = zone.entry_level(local_time) {
+                if evaluation.floor.as_ref().is_none_or(|(floor, _)| level > *floor) {
+                    evaluation.floor = Some((level, zone.name.clone()));
+                }
+            }
+        }
+        if all_capped && !detections.is_empty() {
+            evaluation.ceiling = Some(ceiling);
+        }
+        evaluation
+    }
+}
+
+/// A track that has stayed in a zone past a loitering rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Loiterer {
+    pub track_id: u64,
+    pub zone: String,
+    pub dwell: Duration,
+    pub level: ThreatLevel,
+}
+
+/// How long each track has spent in each zone
+#[derive(Debug, Clone, Default)]
+pub struct DwellTracker {
+    /// When the track entered the zone and when it was last seen there
+    visits: HashMap<(u64, String), (DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl DwellTracker {
+    /// A track unseen for this long has left, rather than being briefly occluded
+    const ABSENCE_GRACE_SECS: i64 = 5;
+
+    /// Record this frame's zone-tagged, tracked detections and report loiterers.
+    /// Whitelisted people are never loitering.
+    pub fn update(
+        &mut self,
+        zones: &ZoneMap,
+        detections: &[ObjectDetection],
+        frame_time: DateTime<Utc>,
+        local_time: NaiveTime,
+    ) -> Vec<Loiterer> {
+        for detection in detections.iter().filter(|detection| detection.known_person.is_none()) {
+            let (Some(track_id), Some(zone)) = (detection.track_id, &detection.zone) else { continue };
+            self.visits
+                .entry((track_id, zone.clone()))
+                .and_modify(|(_, last_seen)| *last_seen = (*last_seen).max(frame_time))
+                .or_insert((frame_time, frame_time));
+        }
+        let grace = chrono::Duration::seconds(Self::ABSENCE_GRACE_SECS);
+        self.visits.retain(|_, (_, last_seen)| frame_time - *last_seen <= grace);
+
+        let mut loiterers: Vec<Loiterer> = self
+            .visits
+            .iter()
+            .filter_map(|((track_id, zone_name), (entered, last_seen))| {
+                let zone = zones.zones.iter().find(|zone| zone.name == *zone_name)?;
+                let dwell = (*last_seen - *entered).to_std().unwrap_or_default();
+                let level = zone.loiter_level(dwell, local_time)?;
+                Some(Loiterer {
+                    track_id: *track_id,
+                    zone: zone_name.clone(),
+                    dwell,
+                    level,
+                })
+            })
+            .collect();
+        loiterers.sort_by(|a, b| b.level.cmp(&a.level).then(b.dwell.cmp(&a.dwell)));
+        loiterers
+    }
+
+    /// Time a track has spent in a zone so far
+    pub fn dwell(&self, track_id: u64, zone: &str) -> Option<Duration> {
+        self.visits
+            .get(&(track_id, zone.to_string()))
+            .map(|(entered, last_seen)| (*last_seen - *entered).to_std().unwrap_or_default())
+    }
+}